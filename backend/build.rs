@@ -18,8 +18,10 @@ fn main() {
         .args(&["rev-parse", "--short", "HEAD"])
         .output()
         {
-            if let Ok(git_hash) = String::from_utf8(output.stdout) {
-                let git_hash = git_hash.trim();
+            let git_hash = String::from_utf8(output.stdout).unwrap_or_default();
+            let git_hash = git_hash.trim();
+            // Outside a checkout git exits non-zero with empty stdout
+            if output.status.success() && !git_hash.is_empty() {
                 println!("cargo:rustc-env=GIT_COMMIT={}", git_hash);
             } else {
                 println!("cargo:rustc-env=GIT_COMMIT=unknown");
@@ -0,0 +1,271 @@
+/*
+ * Reusable render buffers: escape-value scratch space and RGBA pixel buffers kept between renders, grouped by size class.
+ * I'm splitting each doubling into eight classes so a buffer is never more than a quarter bigger than asked for, and capping both each class and the bytes held overall so an idle pool stays small.
+ */
+
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+/// Buffers kept per size class when the pool isn't given a limit
+pub const DEFAULT_BUFFERS_PER_CLASS: usize = 4;
+
+/// Most bytes of free buffers a pool keeps across every class; buffers given back past it are freed
+pub const DEFAULT_MAX_RETAINED_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Size classes between one power of two and the next
+const CLASSES_PER_DOUBLING: usize = 8;
+
+/// The capacity a buffer of `len` elements is allocated with: `len` rounded up to the next eighth of its power of two
+fn class_capacity(len: usize) -> usize {
+    let step = (len.next_power_of_two() / CLASSES_PER_DOUBLING).max(1);
+    len.div_ceil(step) * step
+}
+
+/// Free buffers of one element type, keyed by the class capacity they were allocated with
+#[derive(Debug)]
+struct SizedPool<T> {
+    classes: Mutex<HashMap<usize, Vec<Vec<T>>>>,
+}
+
+impl<T: Copy + Default> SizedPool<T> {
+    fn new() -> Self {
+        Self { classes: Mutex::new(HashMap::new()) }
+    }
+
+    fn take(&self, len: usize) -> Option<Vec<T>> {
+        let mut classes = self.classes.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut buffer = classes.get_mut(&class_capacity(len))?.pop()?;
+        buffer.clear();
+        buffer.resize(len, T::default());
+        Some(buffer)
+    }
+
+    /// Keep `buffer` for reuse unless its class is full; false when it was dropped instead
+    fn give(&self, buffer: Vec<T>, per_class: usize) -> bool {
+        // Only buffers this pool allocated have a class capacity, and anything else would land in the wrong class
+        if class_capacity(buffer.capacity()) != buffer.capacity() {
+            return false;
+        }
+        let mut classes = self.classes.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let class = classes.entry(buffer.capacity()).or_default();
+        if class.len() >= per_class {
+            return false;
+        }
+        class.push(buffer);
+        true
+    }
+}
+
+/// Running totals of how often renders found a buffer waiting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct BufferPoolStats {
+    /// Buffers handed out from the pool
+    pub hits: u64,
+    /// Buffers that had to be allocated
+    pub misses: u64,
+    /// Buffers given back and kept
+    pub returned: u64,
+    /// Buffers given back to a full class, or of a size the pool doesn't keep, and freed
+    pub discarded: u64,
+    pub bytes_allocated: u64,
+    pub bytes_reused: u64,
+    /// Bytes held in free buffers right now
+    pub bytes_retained: u64,
+}
+
+/// Render buffers shared by every render of a FractalService
+#[derive(Debug)]
+pub struct BufferPool {
+    per_class: usize,
+    max_retained_bytes: u64,
+    pixels: SizedPool<u8>,
+    escapes: SizedPool<f64>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    returned: AtomicU64,
+    discarded: AtomicU64,
+    bytes_allocated: AtomicU64,
+    bytes_reused: AtomicU64,
+    bytes_retained: AtomicU64,
+}
+
+impl Default for BufferPool {
+    fn default() -> Self {
+        Self::new(DEFAULT_BUFFERS_PER_CLASS)
+    }
+}
+
+impl BufferPool {
+    /// A pool keeping up to `per_class` free buffers of each size; 0 keeps none, so every buffer is a fresh allocation
+    pub fn new(per_class: usize) -> Self {
+        Self {
+            per_class,
+            max_retained_bytes: DEFAULT_MAX_RETAINED_BYTES,
+            pixels: SizedPool::new(),
+            escapes: SizedPool::new(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            returned: AtomicU64::new(0),
+            discarded: AtomicU64::new(0),
+            bytes_allocated: AtomicU64::new(0),
+            bytes_reused: AtomicU64::new(0),
+            bytes_retained: AtomicU64::new(0),
+        }
+    }
+
+    /// Keep at most `bytes` of free buffers across every class
+    pub fn with_max_retained_bytes(mut self, bytes: u64) -> Self {
+        self.max_retained_bytes = bytes;
+        self
+    }
+
+    /// A zeroed RGBA buffer of `len` bytes
+    pub fn take_pixels(&self, len: usize) -> Vec<u8> {
+        self.take(&self.pixels, len)
+    }
+
+    /// A zeroed buffer of `len` escape values
+    pub fn take_escapes(&self, len: usize) -> Vec<f64> {
+        self.take(&self.escapes, len)
+    }
+
+    /// Hand a pixel buffer back once nothing reads it, such as a response's data after it has been encoded
+    pub fn give_pixels(&self, buffer: Vec<u8>) {
+        self.give(&self.pixels, buffer);
+    }
+
+    pub fn give_escapes(&self, buffer: Vec<f64>) {
+        self.give(&self.escapes, buffer);
+    }
+
+    pub fn stats(&self) -> BufferPoolStats {
+        BufferPoolStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            returned: self.returned.load(Ordering::Relaxed),
+            discarded: self.discarded.load(Ordering::Relaxed),
+            bytes_allocated: self.bytes_allocated.load(Ordering::Relaxed),
+            bytes_reused: self.bytes_reused.load(Ordering::Relaxed),
+            bytes_retained: self.bytes_retained.load(Ordering::Relaxed),
+        }
+    }
+
+    fn take<T: Copy + Default>(&self, pool: &SizedPool<T>, len: usize) -> Vec<T> {
+        let bytes = (len * std::mem::size_of::<T>()) as u64;
+        if let Some(buffer) = pool.take(len) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            self.bytes_reused.fetch_add(bytes, Ordering::Relaxed);
+            self.bytes_retained.fetch_sub(capacity_bytes(&buffer), Ordering::Relaxed);
+            return buffer;
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        self.bytes_allocated.fetch_add(bytes, Ordering::Relaxed);
+        // A pool that keeps nothing allocates exactly; otherwise the whole class, so the buffer fits any size in it later
+        let mut buffer = Vec::with_capacity(if self.per_class == 0 { len } else { class_capacity(len) });
+        buffer.resize(len, T::default());
+        buffer
+    }
+
+    fn give<T: Copy + Default>(&self, pool: &SizedPool<T>, buffer: Vec<T>) {
+        let bytes = capacity_bytes(&buffer);
+        // Reserve the bytes before pooling the buffer, so racing returns can't carry the pool past its limit together
+        let reserved = self.per_class > 0
+            && self
+                .bytes_retained
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |retained| {
+                    retained.checked_add(bytes).filter(|&total| total <= self.max_retained_bytes)
+                })
+                .is_ok();
+        let kept = reserved && pool.give(buffer, self.per_class);
+        if reserved && !kept {
+            self.bytes_retained.fetch_sub(bytes, Ordering::Relaxed);
+        }
+        let counter = if kept { &self.returned } else { &self.discarded };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn capacity_bytes<T>(buffer: &Vec<T>) -> u64 {
+    (buffer.capacity() * std::mem::size_of::<T>()) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_classes_waste_at_most_an_eighth() {
+        assert_eq!(class_capacity(0), 0);
+        assert_eq!(class_capacity(1000), 1024);
+        assert_eq!(class_capacity(600), 640);
+        assert_eq!(class_capacity(513), 640);
+        assert_eq!(class_capacity(4000), 4096);
+        assert_eq!(class_capacity(3000), 3072);
+        for len in [1, 7, 100, 600, 1025, 640 * 480 * 4, 1920 * 1080 * 4] {
+            let capacity = class_capacity(len);
+            assert!(capacity >= len && capacity - len <= len / 4 + 1);
+            assert_eq!(class_capacity(capacity), capacity);
+        }
+    }
+
+    #[test]
+    fn test_buffers_are_reused_within_a_size_class() {
+        let pool = BufferPool::new(1);
+        let first = pool.take_escapes(1000);
+        assert_eq!((first.len(), first.capacity()), (1000, 1024));
+        let second = pool.take_escapes(990);
+        pool.give_escapes(first);
+        // A second buffer for a full class is freed rather than kept
+        pool.give_escapes(second);
+
+        let mut pixels = pool.take_pixels(4000);
+        pixels[7] = 9;
+        pool.give_pixels(pixels);
+
+        let reused = pool.take_pixels(3900);
+        assert_eq!(reused.len(), 3900);
+        assert!(reused.iter().all(|&byte| byte == 0));
+        assert_eq!(pool.take_escapes(900).capacity(), 1024);
+        pool.give_pixels(vec![0; 1000]);
+
+        assert_eq!(
+            pool.stats(),
+            BufferPoolStats {
+                hits: 2,
+                misses: 3,
+                returned: 2,
+                discarded: 2,
+                bytes_allocated: 8000 + 7920 + 4000,
+                bytes_reused: 3900 + 7200,
+                bytes_retained: 0,
+            }
+        );
+
+        let unpooled = BufferPool::new(0);
+        let buffer = unpooled.take_pixels(100);
+        assert_eq!(buffer.capacity(), 100);
+        unpooled.give_pixels(buffer);
+        assert_eq!(unpooled.take_pixels(100).capacity(), 100);
+        assert_eq!((unpooled.stats().hits, unpooled.stats().misses, unpooled.stats().discarded), (0, 2, 1));
+    }
+
+    #[test]
+    fn test_retained_bytes_stay_under_the_limit() {
+        let pool = BufferPool::new(4).with_max_retained_bytes(6000);
+        let (first, second) = (pool.take_pixels(4096), pool.take_pixels(2048));
+        pool.give_pixels(first);
+        // Keeping this one too would hold 6144 bytes
+        pool.give_pixels(second);
+        assert_eq!((pool.stats().returned, pool.stats().discarded, pool.stats().bytes_retained), (1, 1, 4096));
+
+        drop(pool.take_pixels(4096));
+        assert_eq!(pool.stats().bytes_retained, 0);
+    }
+}
@@ -0,0 +1,96 @@
+/*
+ * Render cost estimates: how much iteration a request asks for, worked out before any of it runs.
+ * I'm counting the worst case of every pixel reaching max_iterations, since that's the only bound known without rendering, and expressing it in millions of iterations so budgets stay readable.
+ */
+
+use serde::Serialize;
+
+use crate::{fractal::FractalRequest, mandelbulb::MandelbulbRequest};
+
+/// Iterations in one unit of cost
+pub const ITERATIONS_PER_COST_UNIT: f64 = 1_000_000.0;
+
+/// Colourings that carry a derivative or a trap distance along the orbit do about twice the work per step
+const TRACKED_ORBIT_FACTOR: f64 = 2.0;
+
+/// The most a render can cost: pixels × max_iterations × kernel factor
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CostEstimate {
+    /// Pixels iterated, counting every supersample
+    pub pixels: u64,
+    pub max_iterations: u32,
+    /// Work per step relative to z² + c, including whatever the colouring tracks alongside the orbit; for a Mandelbulb, the steps each ray marches
+    pub kernel_factor: f64,
+    /// Millions of iterations, rounded up
+    pub cost: u64,
+}
+
+impl CostEstimate {
+    pub fn of(request: &FractalRequest) -> Self {
+        let pixels = u64::from(request.width) * u64::from(request.height) * u64::from(request.supersampling().pow(2));
+        let mut kernel_factor = request.fractal_type.kernel().cost_factor(&request.fractal_type.params());
+        if request.orbit_trap.is_some() || request.distance_estimated() {
+            kernel_factor *= TRACKED_ORBIT_FACTOR;
+        }
+        Self::from_work(pixels, request.max_iterations, kernel_factor)
+    }
+
+    /// Every frame of an animation, each the size and depth of `frame`
+    pub fn of_frames(frame: &FractalRequest, frames: u32) -> Self {
+        let single = Self::of(frame);
+        Self::from_work(single.pixels * u64::from(frames), single.max_iterations, single.kernel_factor)
+    }
+
+    /// A Mandelbulb march: every ray may take max_steps steps, each running max_iterations of the distance estimate
+    pub fn of_mandelbulb(request: &MandelbulbRequest) -> Self {
+        let pixels = u64::from(request.width) * u64::from(request.height);
+        Self::from_work(pixels, request.max_iterations, f64::from(request.max_steps))
+    }
+
+    fn from_work(pixels: u64, max_iterations: u32, kernel_factor: f64) -> Self {
+        let iterations = pixels as f64 * f64::from(max_iterations) * kernel_factor;
+        Self {
+            pixels,
+            max_iterations,
+            kernel_factor,
+            cost: (iterations / ITERATIONS_PER_COST_UNIT).ceil() as u64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fractal::{FractalType, OrbitTrap};
+
+    #[test]
+    fn test_cost_scales_with_pixels_iterations_and_kernel() {
+        let request = FractalRequest {
+            center_x: -0.5,
+            center_y: 0.0,
+            zoom: 1.0,
+            max_iterations: 200,
+            ..FractalRequest::new(FractalType::Mandelbrot, 1000, 500)
+        };
+        let plain = CostEstimate::of(&request);
+        assert_eq!((plain.pixels, plain.kernel_factor, plain.cost), (500_000, 1.0, 100));
+
+        let supersampled = CostEstimate::of(&FractalRequest { antialiasing: Some(2), ..request.clone() });
+        assert_eq!((supersampled.pixels, supersampled.cost), (2_000_000, 400));
+
+        let cubic = CostEstimate::of(&FractalRequest { fractal_type: FractalType::Multibrot { power: 3.0 }, ..request.clone() });
+        assert_eq!(cubic.cost, 200);
+        let fractional = CostEstimate::of(&FractalRequest { fractal_type: FractalType::Multibrot { power: 2.5 }, ..request.clone() });
+        assert_eq!(fractional.cost, 800);
+
+        let trapped = CostEstimate::of(&FractalRequest { orbit_trap: Some(OrbitTrap::Point { x: 0.0, y: 0.0 }), ..request.clone() });
+        assert_eq!((trapped.kernel_factor, trapped.cost), (2.0, 200));
+
+        let animation = CostEstimate::of_frames(&request, 30);
+        assert_eq!((animation.pixels, animation.cost), (15_000_000, 3_000));
+
+        let bulb = CostEstimate::of_mandelbulb(&MandelbulbRequest::default());
+        assert_eq!((bulb.pixels, bulb.max_iterations, bulb.kernel_factor), (480_000, 12, 128.0));
+        assert_eq!(bulb.cost, 738);
+    }
+}
@@ -0,0 +1,18 @@
+/*
+ * Errors the core engines can return; the server maps each onto its own AppError variant.
+ * I'm only defining what these engines actually fail with, so the server's HTTP categories stay in the server.
+ */
+
+pub type Result<T> = std::result::Result<T, CoreError>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CoreError {
+    #[error("Validation error: {0}")]
+    ValidationError(String),
+
+    #[error("Render error: {0}")]
+    RenderError(String),
+
+    #[error("Render cancelled")]
+    Cancelled,
+}
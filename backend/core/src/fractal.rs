@@ -0,0 +1,1223 @@
+/*
+ * Core fractal generation service showcasing Rust's computational performance.
+ * I'm implementing the Mandelbrot family, Julia sets, and their variants with deep zoom capabilities and parallel processing to really demonstrate speed.
+ */
+
+use num_complex::Complex;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fmt,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+use tracing::{field, instrument, warn, Span};
+
+use crate::{
+    buffers::{BufferPool, BufferPoolStats, DEFAULT_BUFFERS_PER_CLASS},
+    cancel::CancelToken,
+    error::{CoreError, Result},
+    kernels::{BurningShipKernel, FractalKernel, Interior, JuliaKernel, KernelParams, MandelbrotKernel, MultibrotKernel, TricornKernel},
+    palettes::Palette,
+    perturbation::F64_ZOOM_LIMIT,
+    renderers::{CpuRenderer, FractalRenderer, RendererKind, RendererRegistry},
+    strategies::{self, StrategyComparison},
+    threads::RenderPools,
+};
+
+/// Serializable so a render can be handed to a background worker as it was asked for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FractalRequest {
+    pub width: u32,
+    pub height: u32,
+    pub center_x: f64,
+    pub center_y: f64,
+    pub zoom: f64,
+    pub max_iterations: u32,
+    pub fractal_type: FractalType,
+    pub palette: Option<Palette>,
+    pub coloring_mode: ColoringMode,
+    /// Colours every pixel by how close its orbit comes to this shape, in place of the escape-count colouring modes
+    pub orbit_trap: Option<OrbitTrap>,
+    /// Samples per pixel along each axis, from 1 to MAX_ANTIALIASING; the view is drawn this many times larger and averaged down
+    pub antialiasing: Option<u8>,
+    /// Floating-point width to iterate in; renders fall back to f64 where the asked-for one isn't available or won't hold at the zoom
+    #[serde(default)]
+    pub precision: Precision,
+    /// Render threads to use, capped at the service's pool; the whole pool when unset
+    #[serde(default)]
+    pub threads: Option<u32>,
+    /// How points in the set are coloured; black unless asked otherwise
+    #[serde(default)]
+    pub interior_coloring: InteriorColoring,
+}
+
+/// Deepest zoom f32 kernels stay accurate at
+pub const F32_ZOOM_LIMIT: f64 = 1e3;
+
+/// Floating-point width a render iterates in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Precision {
+    /// Whatever the chosen renderer computes in, which is extended precision past F64_ZOOM_LIMIT
+    #[default]
+    Auto,
+    /// Single precision, cheaper per step but only accurate to F32_ZOOM_LIMIT
+    F32,
+    F64,
+    /// An arbitrary-precision reference orbit with f64 offsets from it, for zooms f64 can't place pixels at
+    Extended,
+}
+
+impl Precision {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Precision::Auto => "auto",
+            Precision::F32 => "f32",
+            Precision::F64 => "f64",
+            Precision::Extended => "extended",
+        }
+    }
+
+    /// Deepest zoom this precision tells neighbouring pixels apart at
+    pub fn max_zoom(&self) -> Option<f64> {
+        match self {
+            Precision::F32 => Some(F32_ZOOM_LIMIT),
+            Precision::F64 => Some(F64_ZOOM_LIMIT),
+            Precision::Auto | Precision::Extended => None,
+        }
+    }
+}
+
+/// Highest supersampling factor; 4x already draws sixteen samples a pixel
+pub const MAX_ANTIALIASING: u8 = 4;
+
+/// How escape counts become colours
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColoringMode {
+    /// Whole iteration counts, banded at every step
+    #[default]
+    EscapeTime,
+    /// Counts with a fractional part from how far past the escape radius the orbit landed, so the bands blend
+    Smooth,
+    /// Counts spread by how often they occur in the view, so each colour covers a similar share of the pixels
+    Histogram,
+    /// Brightness from each escaped point's estimated distance to the set's boundary, which traces filaments crisply at any zoom.
+    /// Burning ship and tricorn have no derivative to track, so they fall back to smooth colouring
+    DistanceEstimate,
+}
+
+impl ColoringMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColoringMode::EscapeTime => "escape_time",
+            ColoringMode::Smooth => "smooth",
+            ColoringMode::Histogram => "histogram",
+            ColoringMode::DistanceEstimate => "distance_estimate",
+        }
+    }
+}
+
+/// How points that never escape are coloured
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InteriorColoring {
+    #[default]
+    Black,
+    /// By the length of the cycle the orbit settles into, for points the Mandelbrot kernel's interior checks catch;
+    /// the rest stay black
+    Period,
+}
+
+impl InteriorColoring {
+    pub fn name(&self) -> &'static str {
+        match self {
+            InteriorColoring::Black => "black",
+            InteriorColoring::Period => "period",
+        }
+    }
+}
+
+/// Periods interior colouring cycles through before repeating a shade
+const INTERIOR_PERIOD_BANDS: u32 = 12;
+
+/// How many drawn points never escaped, and how many of those the interior checks caught before iterating them out
+/// Counts are of samples drawn, so an antialiased render counts each of a pixel's samples
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InteriorStats {
+    pub interior: u64,
+    /// In the main cardioid, skipped without iterating
+    pub cardioid: u64,
+    /// In the period-2 bulb, skipped without iterating
+    pub bulb: u64,
+    /// Stopped early once their orbit came back on itself
+    pub cycle: u64,
+    /// Iterations not run compared to taking every interior point to max_iterations
+    pub iterations_skipped: u64,
+}
+
+impl InteriorStats {
+    /// Points the interior checks stopped early
+    pub fn skipped(&self) -> u64 {
+        self.cardioid + self.bulb + self.cycle
+    }
+
+    pub(crate) fn count(&mut self, interior: Option<Interior>, max_iterations: u32) {
+        let Some(interior) = interior else {
+            return;
+        };
+        self.interior += 1;
+        match interior {
+            Interior::Exhausted => {}
+            Interior::Cardioid => self.cardioid += 1,
+            Interior::Bulb => self.bulb += 1,
+            Interior::Cycle { .. } => self.cycle += 1,
+        }
+        self.iterations_skipped += u64::from(interior.iterations_skipped(max_iterations));
+    }
+
+    pub(crate) fn merge(self, other: Self) -> Self {
+        Self {
+            interior: self.interior + other.interior,
+            cardioid: self.cardioid + other.cardioid,
+            bulb: self.bulb + other.bulb,
+            cycle: self.cycle + other.cycle,
+            iterations_skipped: self.iterations_skipped + other.iterations_skipped,
+        }
+    }
+}
+
+/// Shape an orbit is measured against for orbit-trap colouring, placed in the complex plane
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "shape", rename_all = "snake_case")]
+pub enum OrbitTrap {
+    Point { x: f64, y: f64 },
+    /// Infinite line through (x, y), `angle` radians from the real axis
+    Line { x: f64, y: f64, angle: f64 },
+    Circle { x: f64, y: f64, radius: f64 },
+}
+
+impl OrbitTrap {
+    pub fn name(&self) -> &'static str {
+        match self {
+            OrbitTrap::Point { .. } => "point",
+            OrbitTrap::Line { .. } => "line",
+            OrbitTrap::Circle { .. } => "circle",
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let (x, y, extra) = match *self {
+            OrbitTrap::Point { x, y } => (x, y, 0.0),
+            OrbitTrap::Line { x, y, angle } => (x, y, angle),
+            OrbitTrap::Circle { x, y, radius } => (x, y, radius),
+        };
+        if !(x.is_finite() && y.is_finite() && extra.is_finite()) {
+            return Err(CoreError::ValidationError("Orbit trap values must be finite".to_string()));
+        }
+        if matches!(self, OrbitTrap::Circle { radius, .. } if *radius <= 0.0) {
+            return Err(CoreError::ValidationError("Circle trap radius must be positive".to_string()));
+        }
+        Ok(())
+    }
+
+    /// How far z is from the trap's shape
+    fn distance(&self, z: Complex<f64>) -> f64 {
+        match *self {
+            OrbitTrap::Point { x, y } => (z - Complex::new(x, y)).norm(),
+            // Rotating the line onto the real axis leaves the distance as the imaginary part
+            OrbitTrap::Line { x, y, angle } => ((z - Complex::new(x, y)) * Complex::from_polar(1.0, -angle)).im.abs(),
+            OrbitTrap::Circle { x, y, radius } => ((z - Complex::new(x, y)).norm() - radius).abs(),
+        }
+    }
+}
+
+/// `point:x,y`, `line:x,y,angle`, or `circle:x,y,radius`, the form query strings carry a trap in
+impl FromStr for OrbitTrap {
+    type Err = CoreError;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        let invalid = || CoreError::ValidationError(format!(
+            "Invalid orbit trap `{}`; expected point:x,y, line:x,y,angle, or circle:x,y,radius",
+            spec
+        ));
+        let (shape, values) = spec.trim().split_once(':').ok_or_else(invalid)?;
+        let values: Vec<f64> = values
+            .split(',')
+            .map(|value| value.trim().parse::<f64>())
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|_| invalid())?;
+
+        let trap = match (shape.trim().to_ascii_lowercase().as_str(), values.as_slice()) {
+            ("point", &[x, y]) => OrbitTrap::Point { x, y },
+            ("line", &[x, y, angle]) => OrbitTrap::Line { x, y, angle },
+            ("circle", &[x, y, radius]) => OrbitTrap::Circle { x, y, radius },
+            _ => return Err(invalid()),
+        };
+        trap.validate()?;
+        Ok(trap)
+    }
+}
+
+impl fmt::Display for OrbitTrap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            OrbitTrap::Point { x, y } => write!(f, "point:{},{}", x, y),
+            OrbitTrap::Line { x, y, angle } => write!(f, "line:{},{},{}", x, y, angle),
+            OrbitTrap::Circle { x, y, radius } => write!(f, "circle:{},{},{}", x, y, radius),
+        }
+    }
+}
+
+/// Part of a larger view, positioned by its top-left pixel and drawn as a view of its own
+#[derive(Debug, Clone)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub request: FractalRequest,
+}
+
+impl FractalRequest {
+    /// A `width`×`height` view of `fractal_type` at its default centre, zoom 1, and 100 iterations, with every render option at its default;
+    /// callers set anything else with `..FractalRequest::new(...)`, so a new option only has to be added here
+    pub fn new(fractal_type: FractalType, width: u32, height: u32) -> Self {
+        let (center_x, center_y) = fractal_type.default_center();
+        Self {
+            width,
+            height,
+            center_x,
+            center_y,
+            zoom: 1.0,
+            max_iterations: 100,
+            fractal_type,
+            palette: None,
+            coloring_mode: ColoringMode::default(),
+            orbit_trap: None,
+            antialiasing: None,
+            precision: Precision::default(),
+            threads: None,
+            interior_coloring: InteriorColoring::default(),
+        }
+    }
+
+    /// Hex digest of everything that decides the pixels (type, viewport, iterations, palette, colouring, and size),
+    /// so two requests share a key exactly when they draw the same image; the thread hint changes only how fast
+    pub fn cache_key(&self) -> String {
+        let canonical = serde_json::to_vec(&FractalRequest { threads: None, ..self.clone() }).unwrap_or_default();
+        format!("{:x}", Sha256::digest(canonical))
+    }
+
+    /// Whether pixels are coloured by distance estimation, which needs both the mode and a type it works for
+    pub fn distance_estimated(&self) -> bool {
+        self.coloring_mode == ColoringMode::DistanceEstimate && self.fractal_type.is_holomorphic() && self.orbit_trap.is_none()
+    }
+
+    /// Samples per pixel along each axis, 1 when antialiasing is off
+    pub fn supersampling(&self) -> u32 {
+        u32::from(self.antialiasing.unwrap_or(1).clamp(1, MAX_ANTIALIASING))
+    }
+
+    /// The same view at `factor` times the resolution, which is what renderers actually draw for an antialiased request
+    fn supersampled(&self, factor: u32) -> Self {
+        FractalRequest {
+            width: self.width * factor,
+            height: self.height * factor,
+            antialiasing: None,
+            ..self.clone()
+        }
+    }
+
+    /// Split the view into a grid×grid set of regions that draw the same pixels as the whole, nearest the centre first
+    /// I'm returning the view whole when the grid doesn't divide it, it's past F64_ZOOM_LIMIT where region centres can't be placed to the pixel,
+    /// or it's histogram coloured, which needs every pixel's count before it can colour any
+    pub fn regions(&self, grid: u32) -> Vec<Region> {
+        if grid <= 1
+            || !self.width.is_multiple_of(grid)
+            || !self.height.is_multiple_of(grid)
+            || self.zoom > F64_ZOOM_LIMIT
+            || self.coloring_mode == ColoringMode::Histogram
+        {
+            return vec![Region { x: 0, y: 0, request: self.clone() }];
+        }
+
+        let (width, height) = (self.width / grid, self.height / grid);
+        let mut regions: Vec<Region> = (0..grid)
+            .flat_map(|row| (0..grid).map(move |column| (column * width, row * height)))
+            .map(|(x, y)| {
+                // Zooming in by the grid keeps the pixel spacing, so only the centre moves: to where the region's middle sits in the view
+                let scale = 4.0 / self.zoom;
+                let request = FractalRequest {
+                    width,
+                    height,
+                    center_x: self.center_x + (x as f64 + width as f64 / 2.0 - self.width as f64 / 2.0) * scale / self.width as f64,
+                    center_y: self.center_y + (y as f64 + height as f64 / 2.0 - self.height as f64 / 2.0) * scale / self.height as f64,
+                    zoom: self.zoom * grid as f64,
+                    ..self.clone()
+                };
+                Region { x, y, request }
+            })
+            .collect();
+
+        let middle = (self.width as f64 / 2.0, self.height as f64 / 2.0);
+        regions.sort_by(|a, b| {
+            let distance = |r: &Region| (r.x as f64 + width as f64 / 2.0 - middle.0).hypot(r.y as f64 + height as f64 / 2.0 - middle.1);
+            distance(a).total_cmp(&distance(b))
+        });
+        regions
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum FractalType {
+    Mandelbrot,
+    Julia { c_real: f64, c_imag: f64 },
+    /// Mandelbrot iteration on the absolute values of z's parts
+    BurningShip,
+    /// Mandelbrot iteration on the conjugate of z, also called the Mandelbar set
+    Tricorn,
+    /// z^power + c; non-integer powers use the principal branch
+    Multibrot { power: f64 },
+}
+
+impl FractalType {
+    /// The registered kernel that iterates this type; the one place a variant is tied to its iteration
+    pub fn kernel(&self) -> &'static dyn FractalKernel {
+        match self {
+            FractalType::Mandelbrot => &MandelbrotKernel,
+            FractalType::Julia { .. } => &JuliaKernel,
+            FractalType::BurningShip => &BurningShipKernel,
+            FractalType::Tricorn => &TricornKernel,
+            FractalType::Multibrot { .. } => &MultibrotKernel,
+        }
+    }
+
+    /// This type's parameter values, in the order its kernel's schema lists them
+    pub fn params(&self) -> KernelParams {
+        match *self {
+            FractalType::Julia { c_real, c_imag } => [c_real, c_imag],
+            FractalType::Multibrot { power } => [power, 0.0],
+            _ => [0.0; 2],
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.kernel().name()
+    }
+
+    /// Where the whole set sits in view at zoom 1
+    pub fn default_center(&self) -> (f64, f64) {
+        self.kernel().default_center()
+    }
+
+    /// Power z is raised to each step, which sets how quickly an escaped orbit runs away
+    pub fn degree(&self) -> f64 {
+        self.kernel().degree(&self.params())
+    }
+
+    /// Whether each step is complex-differentiable, which distance estimation needs; the folds and conjugates aren't
+    pub fn is_holomorphic(&self) -> bool {
+        self.kernel().is_holomorphic()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FractalResponse {
+    pub data: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub computation_time_ms: u128,
+    pub zoom_level: f64,
+    /// Name of the registered renderer that drew this image
+    pub renderer: &'static str,
+    /// Hardware the image was drawn on, which is the CPU whenever the selected renderer failed
+    pub compute_backend: RendererKind,
+    /// Whether this came from a cache of earlier renders rather than being drawn for this request
+    pub cache_hit: bool,
+    /// Precision the image was actually iterated in, which is never auto
+    pub precision: Precision,
+    /// Threads the render ran on; none for a cached image
+    pub threads: usize,
+    /// Points found inside the set and how many the interior checks skipped; all zero for a cached image
+    pub interior: InteriorStats,
+}
+
+/// Raw per-pixel escape values of a view, row-major, for exporting rather than colouring
+#[derive(Debug, Clone, PartialEq)]
+pub enum EscapeBuffer {
+    /// Whole iteration counts; points in the set hold max_iterations
+    Counts(Vec<u32>),
+    /// Counts with the fractional part smooth colouring blends by
+    Smooth(Vec<f64>),
+}
+
+impl EscapeBuffer {
+    pub fn len(&self) -> usize {
+        match self {
+            EscapeBuffer::Counts(counts) => counts.len(),
+            EscapeBuffer::Smooth(values) => values.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Clone)]
+pub struct FractalService {
+    renderers: RendererRegistry,
+    cancelled: Arc<AtomicU64>,
+    pools: Arc<RenderPools>,
+    buffers: Arc<BufferPool>,
+}
+
+impl FractalService {
+    pub fn new() -> Self {
+        Self {
+            renderers: RendererRegistry::new(),
+            cancelled: Arc::new(AtomicU64::new(0)),
+            pools: Arc::new(RenderPools::new(0)),
+            buffers: Arc::new(BufferPool::default()),
+        }
+    }
+
+    /// Render on a dedicated pool of `threads` threads, 0 for one per core, leaving the rest of the machine to whoever serves requests
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.pools = Arc::new(RenderPools::new(threads));
+        self
+    }
+
+    /// Keep up to `per_class` free render buffers of each size for later renders; 0 allocates every buffer fresh
+    pub fn with_buffer_pool(mut self, per_class: usize) -> Self {
+        self.buffers = Arc::new(BufferPool::new(per_class));
+        self
+    }
+
+    /// Pools renders run on; other CPU-heavy work can share them to stay inside the same budget
+    pub fn pools(&self) -> &RenderPools {
+        &self.pools
+    }
+
+    /// Registry the renderer for each request is chosen from; register additional backends here
+    pub fn renderers(&self) -> &RendererRegistry {
+        &self.renderers
+    }
+
+    pub fn render(&self, request: FractalRequest) -> FractalResponse {
+        self.draw(request, &CancelToken::new())
+    }
+
+    /// Render unless `cancel` fires first; a cancelled render stops at the next row and its pixels are thrown away
+    pub fn render_cancellable(&self, request: FractalRequest, cancel: &CancelToken) -> Result<FractalResponse> {
+        let response = (!cancel.is_cancelled()).then(|| self.draw(request, cancel));
+        match response {
+            Some(response) if !cancel.is_cancelled() => Ok(response),
+            response => {
+                if let Some(response) = response {
+                    self.recycle(response.data);
+                }
+                self.cancelled.fetch_add(1, Ordering::Relaxed);
+                Err(CoreError::Cancelled)
+            }
+        }
+    }
+
+    /// Render on the pool without holding up the caller's async runtime, once the request's share of the pool's threads is free
+    pub async fn render_async(&self, request: FractalRequest, cancel: CancelToken) -> Result<FractalResponse> {
+        let service = self.clone();
+        let threads = self.pools.budget(request.threads);
+        self.pools.run(threads, move || service.render_cancellable(request, &cancel)).await.0
+    }
+
+    /// Renders stopped by their cancel token since startup
+    pub fn cancelled_renders(&self) -> u64 {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// How often renders have reused a pooled buffer rather than allocating one, since startup
+    pub fn buffer_stats(&self) -> BufferPoolStats {
+        self.buffers.stats()
+    }
+
+    /// Hand a response's pixels back once they've been encoded or copied out, so the next render of its size reuses them
+    pub fn recycle(&self, data: Vec<u8>) {
+        self.buffers.give_pixels(data);
+    }
+
+    // Here I'm handing the request to the best renderer for it and timing the result
+    #[instrument(
+        name = "fractal.render",
+        level = "debug",
+        skip_all,
+        fields(fractal_type = request.fractal_type.name(), width = request.width, height = request.height, max_iterations = request.max_iterations, zoom = request.zoom, renderer = field::Empty, threads = field::Empty, computation_time_ms = field::Empty),
+    )]
+    fn draw(&self, request: FractalRequest, cancel: &CancelToken) -> FractalResponse {
+        // Renderers only ever see the supersampled view, so their pixel limits apply to what they really draw
+        let factor = request.supersampling();
+        let drawn = request.supersampled(factor);
+        let renderer = self.renderers.select(&drawn);
+        Span::current().record("renderer", renderer.name());
+
+        let start_time = Instant::now();
+        let span = Span::current();
+        let ((data, interior, drawn_by), threads) = self.pools.install(self.pools.budget(request.threads), || {
+            let ((data, interior), drawn_by): (_, &dyn FractalRenderer) = match renderer.render_counted(&drawn, cancel, &self.buffers) {
+                Ok(drawn) => (drawn, renderer.as_ref()),
+                Err(e) => {
+                    warn!("Renderer {} failed, drawing on the CPU instead: {}", renderer.name(), e);
+                    span.record("renderer", CpuRenderer.name());
+                    (CpuRenderer.draw_counted(&drawn, cancel, &self.buffers), &CpuRenderer)
+                }
+            };
+            (downsample(data, drawn.width, factor, &self.buffers), interior, drawn_by)
+        });
+        let computation_time_ms = start_time.elapsed().as_millis();
+        Span::current().record("threads", threads);
+        Span::current().record("computation_time_ms", computation_time_ms as u64);
+
+        FractalResponse {
+            data,
+            width: request.width,
+            height: request.height,
+            computation_time_ms,
+            zoom_level: request.zoom,
+            renderer: drawn_by.name(),
+            compute_backend: drawn_by.capabilities().kind,
+            cache_hit: false,
+            precision: drawn_by.capabilities().precision_for(&drawn),
+            threads,
+            interior,
+        }
+    }
+
+    /// Escape values for every pixel at the view's own resolution, iterated on the CPU in f64 whichever renderer would draw it
+    /// I'm leaving out orbit traps, distance estimation, and antialiasing, since an export is of the iteration itself rather than a colouring of it
+    #[instrument(name = "fractal.escape_buffer", level = "debug", skip_all, fields(fractal_type = request.fractal_type.name(), width = request.width, height = request.height))]
+    pub fn escape_buffer(&self, request: &FractalRequest) -> EscapeBuffer {
+        self.pools.install(self.pools.budget(request.threads), || {
+            let points = (0..request.height)
+                .into_par_iter()
+                .flat_map(|y| (0..request.width).into_par_iter().map(move |x| pixel_coordinate(request, x, y)));
+            let (kernel, params) = (request.fractal_type.kernel(), request.fractal_type.params());
+
+            if request.coloring_mode == ColoringMode::Smooth {
+                EscapeBuffer::Smooth(
+                    points
+                        .map(|point| {
+                            let (iterations, norm_sqr) = kernel.escape(point, &params, request.max_iterations);
+                            escape_value(request, iterations, norm_sqr)
+                        })
+                        .collect(),
+                )
+            } else {
+                EscapeBuffer::Counts(points.map(|point| kernel.escape(point, &params, request.max_iterations).0).collect())
+            }
+        })
+        .0
+    }
+
+    pub fn generate_mandelbrot(&self, mut request: FractalRequest) -> FractalResponse {
+        request.fractal_type = FractalType::Mandelbrot;
+        self.render(request)
+    }
+
+    pub fn generate_julia(&self, mut request: FractalRequest, c: Complex<f64>) -> FractalResponse {
+        request.fractal_type = FractalType::Julia { c_real: c.re, c_imag: c.im };
+        self.render(request)
+    }
+
+    pub fn generate_burning_ship(&self, mut request: FractalRequest) -> FractalResponse {
+        request.fractal_type = FractalType::BurningShip;
+        self.render(request)
+    }
+
+    pub fn generate_tricorn(&self, mut request: FractalRequest) -> FractalResponse {
+        request.fractal_type = FractalType::Tricorn;
+        self.render(request)
+    }
+
+    pub fn generate_multibrot(&self, mut request: FractalRequest, power: f64) -> FractalResponse {
+        request.fractal_type = FractalType::Multibrot { power };
+        self.render(request)
+    }
+
+    // Benchmark function to showcase computational speed
+    #[instrument(name = "fractal.benchmark_generation", level = "debug", skip(self))]
+    pub fn benchmark_generation(&self, iterations: u32) -> serde_json::Value {
+        let mut results = Vec::new();
+
+        // I'm testing different complexity levels to show performance scaling
+        let test_cases = vec![
+            (512, 512, 100),
+            (1024, 1024, 200),
+            (2048, 2048, 400),
+        ];
+
+        for (width, height, max_iter) in test_cases {
+            let request = FractalRequest {
+                center_x: -0.5,
+                center_y: 0.0,
+                zoom: 1.0,
+                max_iterations: max_iter,
+                ..FractalRequest::new(FractalType::Mandelbrot, width, height)
+            };
+
+            let response = self.generate_mandelbrot(request);
+            results.push(serde_json::json!({
+                "resolution": format!("{}x{}", width, height),
+                                           "max_iterations": max_iter,
+                                           "computation_time_ms": response.computation_time_ms,
+                                           "pixels_per_ms": (width * height) as f64 / response.computation_time_ms as f64
+            }));
+        }
+
+        serde_json::json!({
+            "benchmark_results": results,
+            "total_iterations": iterations,
+            "language": "Rust",
+            "parallel_processing": true
+        })
+    }
+
+    /// Time `renders` draws of `request` recycling their pixels against the same draws allocating every buffer,
+    /// each on a pool of its own so concurrent renders don't skew the counts
+    pub fn benchmark_buffer_pool(&self, request: &FractalRequest, renders: u32) -> serde_json::Value {
+        let run = |per_class: usize| {
+            let service = FractalService { buffers: Arc::new(BufferPool::new(per_class)), ..self.clone() };
+            let start_time = Instant::now();
+            for _ in 0..renders {
+                let response = service.render(request.clone());
+                service.recycle(response.data);
+            }
+            (start_time.elapsed().as_millis(), service.buffer_stats())
+        };
+        let (pooled_ms, pooled) = run(DEFAULT_BUFFERS_PER_CLASS);
+        let (unpooled_ms, unpooled) = run(0);
+
+        serde_json::json!({
+            "resolution": format!("{}x{}", request.width, request.height),
+            "renders": renders,
+            "pooled": { "computation_time_ms": pooled_ms, "buffers": pooled },
+            "unpooled": { "computation_time_ms": unpooled_ms, "buffers": unpooled },
+            "allocations_saved": unpooled.misses.saturating_sub(pooled.misses),
+            "bytes_saved": unpooled.bytes_allocated.saturating_sub(pooled.bytes_allocated),
+        })
+    }
+
+    /// Time `request` on every strategy this build has, single-threaded scalar first, on this service's pools
+    pub fn compare_strategies(&self, request: &FractalRequest, runs: u32) -> StrategyComparison {
+        strategies::compare_strategies(&self.pools, request, runs)
+    }
+}
+
+/// Average each factor×factor block of RGBA pixels into one, handing the supersampled buffer back to `buffers` afterwards
+fn downsample(data: Vec<u8>, width: u32, factor: u32, buffers: &BufferPool) -> Vec<u8> {
+    if factor <= 1 {
+        return data;
+    }
+
+    let (width, factor) = (width as usize, factor as usize);
+    let samples = (factor * factor) as u32;
+    let mut pixels = buffers.take_pixels(data.len() / (factor * factor));
+    pixels
+        .par_chunks_mut(width / factor * 4)
+        .zip(data.par_chunks(width * factor * 4))
+        .for_each(|(out, rows)| {
+            for (x, pixel_out) in out.chunks_mut(4).enumerate() {
+                let mut sum = [0u32; 4];
+                for row in rows.chunks(width * 4) {
+                    for pixel in row[x * factor * 4..(x + 1) * factor * 4].chunks(4) {
+                        for (total, &channel) in sum.iter_mut().zip(pixel) {
+                            *total += u32::from(channel);
+                        }
+                    }
+                }
+                for (channel, total) in pixel_out.iter_mut().zip(sum) {
+                    *channel = ((total + samples / 2) / samples) as u8;
+                }
+            }
+        });
+    buffers.give_pixels(data);
+    pixels
+}
+
+/// The point in the complex plane a pixel maps to
+pub(crate) fn pixel_coordinate(request: &FractalRequest, x: u32, y: u32) -> Complex<f64> {
+    Complex::new(request.center_x, request.center_y) + pixel_offset(request, x, y)
+}
+
+/// How far a pixel's point is from the view centre; exact in f64 at any zoom, unlike the point itself
+pub(crate) fn pixel_offset(request: &FractalRequest, x: u32, y: u32) -> Complex<f64> {
+    fractal_engine::view::pixel_offset(request.width, request.height, request.zoom, x, y)
+}
+
+/// Iterations before the orbit of a pixel's point escapes, capped at max_iterations, with |z|² at the step it stopped on;
+/// renders go through escape_interior instead, so only the tests compare against this
+#[cfg(test)]
+pub(crate) fn escape(fractal_type: &FractalType, point: Complex<f64>, max_iterations: u32) -> (u32, f64) {
+    fractal_type.kernel().escape(point, &fractal_type.params(), max_iterations)
+}
+
+/// How sharply trap colouring fades with distance from the trap
+const TRAP_FALLOFF: f64 = 4.0;
+
+/// |z|² an orbit has to pass before its distance estimate is trusted; the estimate only converges well outside the usual radius of 2
+const DE_ESCAPE_NORM_SQR: f64 = 1e6;
+
+/// How many pixels either side of the boundary distance estimation lights up
+const DE_GLOW_PIXELS: f64 = 4.0;
+
+/// What a pixel is coloured from on the scalar path: its closeness to the orbit trap when there is one,
+/// its closeness to the boundary when distance estimated, its escape value otherwise; with how it was found inside, if it was
+pub(crate) fn pixel_value(request: &FractalRequest, point: Complex<f64>) -> (f64, Option<Interior>) {
+    match &request.orbit_trap {
+        Some(trap) => (trap_closeness(&request.fractal_type, point, request.max_iterations, trap), None),
+        None if request.distance_estimated() => {
+            // Distances come out in the plane's units, so a pixel's width turns them into something the glow can be sized in
+            let pixel_width = 4.0 / request.zoom / request.width as f64;
+            match distance_estimate(&request.fractal_type, point, request.max_iterations) {
+                Some(distance) => ((1.0 - distance / pixel_width / DE_GLOW_PIXELS).clamp(0.0, 1.0), None),
+                None => (-1.0, Some(Interior::Exhausted)),
+            }
+        }
+        None => {
+            let (kernel, params) = (request.fractal_type.kernel(), request.fractal_type.params());
+            let (iterations, norm_sqr, interior) = kernel.escape_interior(point, &params, request.max_iterations);
+            (interior_value(request, escape_value(request, iterations, norm_sqr), interior), interior)
+        }
+    }
+}
+
+/// An interior point's value lifted just past max_iterations by its period's shade, when the request colours the interior;
+/// every other value comes back unchanged, so points left black keep exactly max_iterations
+pub(crate) fn interior_value(request: &FractalRequest, value: f64, interior: Option<Interior>) -> f64 {
+    match interior.and_then(|interior| interior.period()) {
+        Some(period) if request.interior_coloring == InteriorColoring::Period => {
+            let band = (period - 1) % INTERIOR_PERIOD_BANDS + 1;
+            request.max_iterations as f64 + f64::from(band) / f64::from(INTERIOR_PERIOD_BANDS + 1)
+        }
+        _ => value,
+    }
+}
+
+/// How near the orbit comes to the trap before escaping, from 1 for a direct hit falling towards 0 with distance
+/// I'm stepping through the kernel rather than its escape loop, since the trap needs every point of the orbit
+pub(crate) fn trap_closeness(fractal_type: &FractalType, point: Complex<f64>, max_iterations: u32, trap: &OrbitTrap) -> f64 {
+    let (kernel, params) = (fractal_type.kernel(), fractal_type.params());
+    let (mut z, c) = kernel.seed(point, &params);
+
+    let mut closest = f64::INFINITY;
+    for _ in 0..max_iterations {
+        if z.norm_sqr() > 4.0 {
+            break;
+        }
+        z = kernel.step(z, c, &params);
+        closest = closest.min(trap.distance(z));
+    }
+    (-TRAP_FALLOFF * closest).exp()
+}
+
+/// Lower bound on how far a point is from the set, from the derivative of its orbit tracked alongside it; None when it never escapes
+/// I'm using the Koebe estimate 0.5·|z|·ln|z|/|dz|, with dz taken against c for the Mandelbrot family and against the starting z for Julia sets
+pub(crate) fn distance_estimate(fractal_type: &FractalType, point: Complex<f64>, max_iterations: u32) -> Option<f64> {
+    let (kernel, params) = (fractal_type.kernel(), fractal_type.params());
+    let (mut z, c) = kernel.seed(point, &params);
+    let (mut dz, offset) = if kernel.seeds_orbit() { (Complex::new(1.0, 0.0), 0.0) } else { (Complex::new(0.0, 0.0), 1.0) };
+
+    for _ in 0..max_iterations {
+        let norm_sqr = z.norm_sqr();
+        if norm_sqr > DE_ESCAPE_NORM_SQR {
+            let norm = norm_sqr.sqrt();
+            return Some(0.5 * norm * norm.ln() / dz.norm());
+        }
+        // The chain rule through each step, plus 1 when differentiating against c
+        dz = kernel.derivative(z, &params) * dz + offset;
+        z = kernel.step(z, c, &params);
+    }
+    None
+}
+
+/// The value a pixel is coloured from: its escape count, plus a fractional part when the request is smooth coloured
+/// Points that never escape keep exactly max_iterations, so colouring can still tell them apart
+pub(crate) fn escape_value(request: &FractalRequest, iterations: u32, norm_sqr: f64) -> f64 {
+    let smooth = matches!(request.coloring_mode, ColoringMode::Smooth | ColoringMode::DistanceEstimate);
+    if !smooth || iterations >= request.max_iterations {
+        return iterations as f64;
+    }
+
+    // log|z| grows by about the degree each step once escaped, so where it landed past the radius of 2 places the pixel between counts
+    let degree = request.fractal_type.degree().max(1.0 + f64::EPSILON);
+    let overshoot = (0.5 * norm_sqr.log2()).ln() / degree.ln();
+    iterations as f64 + (1.0 - overshoot).clamp(0.0, 0.999)
+}
+
+/// Escape values for a whole view in a buffer from `buffers`, filled a row at a time by `fill_row`, row-major,
+/// with the interior counts each row reported added up
+/// Rows reached after `cancel` fires are left at zero, since FractalService discards a cancelled render's pixels
+pub(crate) fn escape_rows(
+    request: &FractalRequest,
+    cancel: &CancelToken,
+    buffers: &BufferPool,
+    fill_row: impl Fn(u32, &mut [f64]) -> InteriorStats + Send + Sync,
+) -> (Vec<f64>, InteriorStats) {
+    let width = request.width as usize;
+    let mut escapes = buffers.take_escapes(width * request.height as usize);
+    let interior = escapes
+        .par_chunks_mut(width.max(1))
+        .enumerate()
+        .map(|(y, row)| if cancel.is_cancelled() { InteriorStats::default() } else { fill_row(y as u32, row) })
+        .reduce(InteriorStats::default, InteriorStats::merge);
+    (escapes, interior)
+}
+
+/// RGBA pixels for a whole view from its escape values, or trap or boundary closeness when the request is coloured by one, row-major
+/// I'm colouring after every value is in because histogram colouring ranks each count against the rest of the view
+pub(crate) fn colorize(request: &FractalRequest, escapes: &[f64], buffers: &BufferPool) -> Vec<u8> {
+    let max_iterations = request.max_iterations;
+    let palette = request.palette.as_ref();
+    let trapped = request.orbit_trap.is_some();
+    let distance_estimated = request.distance_estimated();
+    let shares = (!trapped && !distance_estimated && request.coloring_mode == ColoringMode::Histogram)
+        .then(|| cumulative_shares(escapes, max_iterations));
+
+    let color = |escape: f64| {
+        if trapped {
+            // Points in the set have orbits too, so trap colouring leaves none of them black
+            return escape_to_color(0.0, escape, max_iterations, palette);
+        }
+        if distance_estimated {
+            // Negative closeness marks points that never escaped, which stay black
+            let inside = if escape < 0.0 { max_iterations as f64 } else { 0.0 };
+            return escape_to_color(inside, escape, max_iterations, palette);
+        }
+        if escape > max_iterations as f64 {
+            return interior_color(escape - max_iterations as f64, palette);
+        }
+        let t = match &shares {
+            Some(shares) if escape < max_iterations as f64 => shares[escape as usize],
+            _ => escape / max_iterations as f64,
+        };
+        escape_to_color(escape, t, max_iterations, palette)
+    };
+
+    let mut pixels = buffers.take_pixels(escapes.len() * 4);
+    pixels.par_chunks_mut(4).zip(escapes.par_iter()).for_each(|(pixel, &escape)| pixel.copy_from_slice(&color(escape)));
+    pixels
+}
+
+/// Fraction of the view's escaped pixels at or below each count
+fn cumulative_shares(escapes: &[f64], max_iterations: u32) -> Vec<f64> {
+    let mut counts = vec![0u64; max_iterations as usize];
+    for &escape in escapes {
+        if escape < max_iterations as f64 {
+            counts[escape as usize] += 1;
+        }
+    }
+
+    let total = counts.iter().sum::<u64>().max(1) as f64;
+    let mut running = 0;
+    counts
+        .into_iter()
+        .map(|count| {
+            running += count;
+            running as f64 / total
+        })
+        .collect()
+}
+
+// Uploaded palettes take over escape-point colouring; points in the set stay black either way
+fn escape_to_color(escape: f64, t: f64, max_iterations: u32, palette: Option<&Palette>) -> [u8; 4] {
+    match palette {
+        Some(palette) if escape < max_iterations as f64 => palette.sample(t),
+        _ => escape_to_dark_color(escape, t, max_iterations),
+    }
+}
+
+/// A coloured interior point, from its period's shade; a deep violet ramp when there's no palette, so it reads apart from the escaped gradient
+fn interior_color(shade: f64, palette: Option<&Palette>) -> [u8; 4] {
+    match palette {
+        Some(palette) => palette.sample(shade),
+        None => [(shade * 60.0) as u8, (shade * 20.0) as u8, (shade * 110.0) as u8, 255],
+    }
+}
+
+// I'm creating a dark, eerie color palette that fits the Mr. Robot theme
+fn escape_to_dark_color(escape: f64, t: f64, max_iterations: u32) -> [u8; 4] {
+    if escape >= max_iterations as f64 {
+        // Deep black for points in the set
+        [0, 0, 0, 255]
+    } else {
+        // Cool, dark gradient for escape points
+        let r = (t * 30.0) as u8;  // Very dark red
+        let g = (t * 50.0) as u8;  // Slightly more green for that eerie glow
+        let b = (t * 80.0) as u8;  // Cool blue tones
+        [r, g, b, 255]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variant_kernels() {
+        for (re, im) in [(-0.75, 0.1), (0.3, 0.5), (-1.2, -0.2), (0.26, 0.0)] {
+            let c = Complex::new(re, im);
+            assert_eq!(
+                escape(&FractalType::Multibrot { power: 2.0 }, c, 200).0,
+                escape(&FractalType::Mandelbrot, c, 200).0
+            );
+            // Conjugating c conjugates the whole tricorn orbit, so the set is symmetric about the real axis
+            assert_eq!(
+                escape(&FractalType::Tricorn, c, 200).0,
+                escape(&FractalType::Tricorn, c.conj(), 200).0
+            );
+        }
+
+        assert_eq!(escape(&FractalType::Multibrot { power: 3.5 }, Complex::new(0.0, 0.0), 50).0, 50);
+        assert!(escape(&FractalType::Multibrot { power: 3.5 }, Complex::new(1.5, 1.5), 50).0 < 5);
+
+        // Every type resolves to the engine's registered kernel of the same name, with its parameters filled in
+        let types = [
+            FractalType::Mandelbrot,
+            FractalType::Julia { c_real: -0.8, c_imag: 0.156 },
+            FractalType::BurningShip,
+            FractalType::Tricorn,
+            FractalType::Multibrot { power: 3.0 },
+        ];
+        assert_eq!(types.len(), crate::kernels::kernels().len());
+        for fractal_type in &types {
+            let registered = crate::kernels::kernel(fractal_type.name()).unwrap();
+            assert_eq!(registered.name(), fractal_type.kernel().name());
+            assert_eq!(registered.parameters().len(), fractal_type.params().iter().filter(|&&value| value != 0.0).count());
+        }
+    }
+
+    #[test]
+    fn test_regions_stitch_into_the_whole_view() {
+        let view = FractalRequest {
+            center_x: -0.745,
+            center_y: 0.113,
+            zoom: 40.0,
+            max_iterations: 150,
+            ..FractalRequest::new(FractalType::Mandelbrot, 96, 64)
+        };
+        let whole = CpuRenderer.draw(&view, &CancelToken::new(), &BufferPool::new(0));
+
+        let regions = view.regions(4);
+        assert_eq!(regions.len(), 16);
+        assert_eq!((regions[0].request.width, regions[0].request.height), (24, 16));
+        // The four middle regions come first
+        assert!(regions[..4].iter().all(|r| (24..72).contains(&r.x) && (16..48).contains(&r.y)));
+
+        let mut stitched = vec![0u8; whole.len()];
+        for region in &regions {
+            let pixels = CpuRenderer.draw(&region.request, &CancelToken::new(), &BufferPool::new(0));
+            for (row, line) in pixels.chunks(region.request.width as usize * 4).enumerate() {
+                let start = ((region.y as usize + row) * view.width as usize + region.x as usize) * 4;
+                stitched[start..start + line.len()].copy_from_slice(line);
+            }
+        }
+        // Region centres round differently to the view's, which can move a boundary pixel by an iteration
+        let differing = whole.chunks(4).zip(stitched.chunks(4)).filter(|(a, b)| a != b).count();
+        assert!(differing * 100 < whole.len() / 4, "{} of {} pixels differ", differing, whole.len() / 4);
+
+        assert_eq!(view.regions(5).len(), 1);
+        assert_eq!(FractalRequest { zoom: 1e15, ..view.clone() }.regions(4).len(), 1);
+        assert_eq!(FractalRequest { coloring_mode: ColoringMode::Histogram, ..view }.regions(4).len(), 1);
+    }
+
+    fn view(coloring_mode: ColoringMode) -> FractalRequest {
+        FractalRequest {
+            center_x: -0.5,
+            center_y: 0.0,
+            zoom: 1.2,
+            max_iterations: 80,
+            coloring_mode,
+            ..FractalRequest::new(FractalType::Mandelbrot, 40, 30)
+        }
+    }
+
+    #[test]
+    fn test_smooth_values_sit_between_whole_counts() {
+        let request = view(ColoringMode::Smooth);
+        let mut fractional = 0;
+        for (re, im) in [(0.3, 0.5), (-1.9, 0.1), (0.5, 0.0), (-0.75, 0.1), (0.0, 0.0), (1.0, 1.0)] {
+            let (iterations, norm_sqr) = escape(&request.fractal_type, Complex::new(re, im), request.max_iterations);
+            let value = escape_value(&request, iterations, norm_sqr);
+            assert!(value >= iterations as f64 && value < iterations as f64 + 1.0, "{} escaped at {} valued {}", re, iterations, value);
+            fractional += (value.fract() != 0.0) as u32;
+            assert_eq!(escape_value(&view(ColoringMode::EscapeTime), iterations, norm_sqr), iterations as f64);
+        }
+        assert!(fractional > 0);
+        // Points in the set keep their exact count however they're coloured
+        assert_eq!(escape_value(&request, 80, 0.5), 80.0);
+    }
+
+    #[test]
+    fn test_histogram_spreads_counts_across_the_palette() {
+        let palette = Palette::builtin("grayscale");
+        let escapes = [1.0, 1.0, 1.0, 2.0, 40.0, 80.0];
+
+        let histogram = colorize(&FractalRequest { palette: palette.clone(), ..view(ColoringMode::Histogram) }, &escapes, &BufferPool::new(0));
+        let pixels: Vec<_> = histogram.chunks(4).collect();
+        // Three of the five escaped pixels share the lowest count, so it sits three fifths of the way along
+        assert_eq!(pixels[0], [153, 153, 153, 255]);
+        assert_eq!(pixels[3], [204, 204, 204, 255]);
+        assert_eq!(pixels[4], [255, 255, 255, 255]);
+        assert_eq!(pixels[5], [0, 0, 0, 255]);
+
+        let escape_time = colorize(&FractalRequest { palette, ..view(ColoringMode::EscapeTime) }, &escapes, &BufferPool::new(0));
+        assert_eq!(&escape_time[..4], [3, 3, 3, 255]);
+    }
+
+    #[test]
+    fn test_orbit_traps() {
+        let traps: Vec<OrbitTrap> = ["point:0,0", "line: 0, 0.5, 1.5708", "CIRCLE:-1,0,0.25"]
+            .iter()
+            .map(|spec| spec.parse().unwrap())
+            .collect();
+        assert_eq!(traps[2], OrbitTrap::Circle { x: -1.0, y: 0.0, radius: 0.25 });
+        assert_eq!(traps[0].to_string().parse::<OrbitTrap>().unwrap(), traps[0]);
+        for bad in ["point:0", "square:0,0,1", "circle:0,0,0", "line:0,0,nan", "0,0"] {
+            assert!(bad.parse::<OrbitTrap>().is_err(), "{}", bad);
+        }
+
+        assert!((traps[1].distance(Complex::new(3.0, 0.0)) - 3.0).abs() < 1e-4);
+        assert!((traps[2].distance(Complex::new(-1.0, 1.0)) - 0.75).abs() < 1e-12);
+
+        // The origin's Mandelbrot orbit stays on the point trap, so it lands on the far end of the palette
+        let point = OrbitTrap::Point { x: 0.0, y: 0.0 };
+        assert_eq!(trap_closeness(&FractalType::Mandelbrot, Complex::new(0.0, 0.0), 50, &point), 1.0);
+        assert!(trap_closeness(&FractalType::Mandelbrot, Complex::new(1.5, 1.5), 50, &point) < 0.01);
+
+        let request = FractalRequest { orbit_trap: Some(point), palette: Palette::builtin("grayscale"), ..view(ColoringMode::EscapeTime) };
+        assert_eq!(colorize(&request, &[1.0, 0.0], &BufferPool::new(0)), [255, 255, 255, 255, 0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_antialiasing_averages_supersampled_pixels() {
+        let request = FractalRequest { width: 24, height: 16, antialiasing: Some(3), ..view(ColoringMode::EscapeTime) };
+        let response = FractalService::new().render(request.clone());
+        assert_eq!((response.width, response.height), (24, 16));
+        assert_eq!(response.data.len(), 24 * 16 * 4);
+
+        // Each output pixel is the rounded mean of its 3x3 block in the supersampled drawing
+        let full = CpuRenderer.draw(&request.supersampled(3), &CancelToken::new(), &BufferPool::new(0));
+        let block: Vec<u32> = (0..3)
+            .flat_map(|row| (0..3).map(move |column| ((row * 72 + 30 + column) * 4) as usize))
+            .map(|start| u32::from(full[start + 2]))
+            .collect();
+        let mean = ((block.iter().sum::<u32>() + 4) / 9) as u8;
+        assert_eq!(response.data[(10 * 4) + 2], mean);
+
+        assert_eq!(FractalRequest { antialiasing: Some(9), ..request.clone() }.supersampling(), MAX_ANTIALIASING as u32);
+        assert_eq!(downsample(vec![0, 0, 0, 255, 255, 255, 255, 255, 10, 20, 30, 255, 10, 20, 30, 255], 2, 2, &BufferPool::new(0)), [69, 74, 79, 255]);
+    }
+
+    #[test]
+    fn test_cancelled_render_is_discarded_and_counted() {
+        let service = FractalService::new();
+        let cancel = CancelToken::new();
+        let request = view(ColoringMode::EscapeTime);
+        assert_eq!(service.render_cancellable(request.clone(), &cancel).unwrap().data, service.render(request.clone()).data);
+        assert_eq!(service.cancelled_renders(), 0);
+
+        cancel.cancel();
+        assert!(matches!(service.render_cancellable(request.clone(), &cancel), Err(CoreError::Cancelled)));
+        let guarded = CancelToken::new();
+        drop(guarded.cancel_on_drop());
+        assert!(matches!(service.render_cancellable(request, &guarded), Err(CoreError::Cancelled)));
+        assert_eq!(service.clone().cancelled_renders(), 2);
+    }
+
+    #[test]
+    fn test_recycled_buffers_feed_the_next_render() {
+        let service = FractalService::new();
+        let request = FractalRequest { antialiasing: Some(2), ..view(ColoringMode::Histogram) };
+        let unpooled = FractalService::new().with_buffer_pool(0).render(request.clone());
+        let first = service.render(request.clone());
+        assert_eq!(first.data, unpooled.data);
+
+        // Escapes, supersampled pixels, and the downsampled output each take a buffer, and the first two go straight back
+        assert_eq!((service.buffer_stats().misses, service.buffer_stats().returned), (3, 2));
+        service.recycle(first.data);
+        assert_eq!(service.render(request).data, unpooled.data);
+        assert_eq!((service.buffer_stats().hits, service.buffer_stats().misses), (3, 3));
+    }
+
+    #[test]
+    fn test_cache_key_follows_the_pixels() {
+        let request = view(ColoringMode::EscapeTime);
+        assert_eq!(request.cache_key(), request.clone().cache_key());
+        assert_eq!(request.cache_key().len(), 64);
+
+        let changed = [
+            FractalRequest { center_x: request.center_x + 1e-12, ..request.clone() },
+            FractalRequest { max_iterations: request.max_iterations + 1, ..request.clone() },
+            FractalRequest { palette: Palette::builtin("grayscale"), ..request.clone() },
+            FractalRequest { width: request.width + 1, ..request.clone() },
+            FractalRequest { fractal_type: FractalType::Tricorn, ..request.clone() },
+        ];
+        for other in changed {
+            assert_ne!(other.cache_key(), request.cache_key());
+        }
+    }
+
+    #[test]
+    fn test_distance_estimation_lights_the_boundary() {
+        use crate::renderers::SimdRenderer;
+
+        // With c = 0 the Julia set is the unit disc and the orbit is z^(2^n), so the estimate is exactly half of |z0|·ln|z0|
+        let disc = FractalType::Julia { c_real: 0.0, c_imag: 0.0 };
+        let estimate = distance_estimate(&disc, Complex::new(2.0, 0.0), 100).unwrap();
+        assert!((estimate - 2f64.ln()).abs() < 1e-9, "{}", estimate);
+        assert_eq!(distance_estimate(&disc, Complex::new(0.5, 0.0), 100), None);
+
+        // Koebe bounds the true distance (2.75 from c = 3 to the set's tip at 0.25) within a factor of four
+        let estimate = distance_estimate(&FractalType::Mandelbrot, Complex::new(3.0, 0.0), 100).unwrap();
+        assert!(estimate > 2.75 / 4.0 && estimate < 2.75 * 4.0, "{}", estimate);
+        assert!(distance_estimate(&FractalType::Multibrot { power: 2.5 }, Complex::new(1.5, 1.5), 100).unwrap().is_finite());
+
+        let request = FractalRequest { palette: Palette::builtin("grayscale"), ..view(ColoringMode::DistanceEstimate) };
+        assert!(request.distance_estimated());
+        let near = pixel_value(&request, Complex::new(0.26, 0.0)).0;
+        let far = pixel_value(&request, Complex::new(2.0, 2.0)).0;
+        assert!(near > 0.9 && far == 0.0, "near {} far {}", near, far);
+        assert_eq!(pixel_value(&request, Complex::new(0.0, 0.0)).0, -1.0);
+        assert_eq!(colorize(&request, &[1.0, 0.0, -1.0], &BufferPool::new(0)), [255, 255, 255, 255, 0, 0, 0, 255, 0, 0, 0, 255]);
+
+        // Tricorn has no derivative to track, so it draws smooth coloured on any renderer that can
+        let tricorn = FractalRequest { fractal_type: FractalType::Tricorn, ..request.clone() };
+        assert!(!tricorn.distance_estimated());
+        assert!(SimdRenderer.capabilities().supports(&tricorn) && !SimdRenderer.capabilities().supports(&request));
+        assert!(CpuRenderer.capabilities().supports(&request));
+    }
+
+    #[test]
+    fn test_escape_buffer_holds_the_raw_counts() {
+        let service = FractalService::new();
+        let request = FractalRequest { width: 12, height: 8, ..view(ColoringMode::EscapeTime) };
+        let EscapeBuffer::Counts(counts) = service.escape_buffer(&request) else {
+            panic!("escape-time exports are whole counts");
+        };
+        assert_eq!(counts.len(), 12 * 8);
+        assert_eq!(counts[3 * 12 + 5], escape(&request.fractal_type, pixel_coordinate(&request, 5, 3), request.max_iterations).0);
+        assert!(counts.contains(&request.max_iterations) && counts.iter().any(|&count| count < 5));
+
+        let smooth = service.escape_buffer(&FractalRequest { coloring_mode: ColoringMode::Smooth, ..request.clone() });
+        let EscapeBuffer::Smooth(values) = smooth else {
+            panic!("smooth exports keep their fractional part");
+        };
+        assert!(values.iter().zip(&counts).all(|(&value, &count)| value.floor() == count as f64));
+        // Traps and supersampling colour the image, not the counts underneath it
+        let trapped = FractalRequest { orbit_trap: Some(OrbitTrap::Point { x: 0.0, y: 0.0 }), antialiasing: Some(2), ..request.clone() };
+        assert_eq!(service.escape_buffer(&trapped), EscapeBuffer::Counts(counts));
+    }
+}
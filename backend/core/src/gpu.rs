@@ -0,0 +1,324 @@
+/*
+ * wgpu compute-shader renderer for Mandelbrot and Julia sets, registered alongside the CPU renderers when the gpu feature is on.
+ * I'm only counting iterations on the device and colouring on the CPU, so uploaded palettes look identical whichever renderer drew the image.
+ * Counts alone can't be smooth coloured, so smooth requests go to the CPU renderers.
+ */
+
+use bytemuck::{Pod, Zeroable};
+use rayon::prelude::*;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc, Arc, OnceLock,
+};
+use tracing::{info, warn};
+use wgpu::util::DeviceExt;
+
+use crate::{
+    buffers::BufferPool,
+    cancel::CancelToken,
+    error::{CoreError, Result},
+    fractal::{colorize, FractalRequest, FractalResponse, FractalService, FractalType, Precision, F32_ZOOM_LIMIT},
+    renderers::{FractalRenderer, RendererCapabilities, RendererKind},
+};
+
+/// Largest image drawn in one dispatch; its counts buffer stays under wgpu's default storage binding limit
+const MAX_PIXELS: u64 = 4096 * 4096;
+
+const WORKGROUP_SIZE: u32 = 8;
+
+/// Same escape test and iteration as the scalar kernels in fractal.rs, in f32
+const SHADER: &str = r#"
+struct Params {
+    center: vec2<f32>,
+    step: vec2<f32>,
+    julia_c: vec2<f32>,
+    size: vec2<u32>,
+    max_iterations: u32,
+    julia: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read_write> counts: array<u32>;
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= params.size.x || id.y >= params.size.y) {
+        return;
+    }
+
+    let point = params.center + (vec2<f32>(id.xy) - vec2<f32>(params.size) / 2.0) * params.step;
+    var z = vec2<f32>(0.0, 0.0);
+    var c = point;
+    if (params.julia != 0u) {
+        z = point;
+        c = params.julia_c;
+    }
+
+    var i = 0u;
+    loop {
+        if (i >= params.max_iterations || dot(z, z) > 4.0) {
+            break;
+        }
+        z = vec2<f32>(z.x * z.x - z.y * z.y, 2.0 * z.x * z.y) + c;
+        i += 1u;
+    }
+    counts[id.y * params.size.x + id.x] = i;
+}
+"#;
+
+/// Uniform block matching `Params` in the shader
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct Params {
+    center: [f32; 2],
+    step: [f32; 2],
+    julia_c: [f32; 2],
+    size: [u32; 2],
+    max_iterations: u32,
+    julia: u32,
+}
+
+impl Params {
+    fn for_request(request: &FractalRequest) -> Self {
+        let scale = 4.0 / request.zoom;
+        let (julia, julia_c) = match request.fractal_type {
+            FractalType::Julia { c_real, c_imag } => (1, [c_real as f32, c_imag as f32]),
+            _ => (0, [0.0, 0.0]),
+        };
+        Self {
+            center: [request.center_x as f32, request.center_y as f32],
+            step: [(scale / request.width as f64) as f32, (scale / request.height as f64) as f32],
+            julia_c,
+            size: [request.width, request.height],
+            max_iterations: request.max_iterations,
+            julia,
+        }
+    }
+}
+
+struct GpuContext {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+}
+
+/// Set by wgpu when the device goes away, after which every request goes to the CPU renderers
+static DEVICE_LOST: AtomicBool = AtomicBool::new(false);
+
+/// The adapter is requested once per process, the first time anything asks whether the GPU renderer is available
+fn context() -> Option<&'static GpuContext> {
+    static CONTEXT: OnceLock<Option<GpuContext>> = OnceLock::new();
+    CONTEXT.get_or_init(|| match pollster::block_on(create_context()) {
+        Ok(context) => Some(context),
+        Err(e) => {
+            warn!("GPU renderer unavailable: {}", e);
+            None
+        }
+    })
+    .as_ref()
+}
+
+async fn create_context() -> Result<GpuContext> {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+    let adapter = instance
+        .request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        })
+        .await
+        .ok_or_else(|| CoreError::RenderError("no GPU adapter found".to_string()))?;
+
+    let (device, queue) = adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("fractal renderer"),
+                required_features: wgpu::Features::empty(),
+                required_limits: wgpu::Limits::downlevel_defaults().using_resolution(adapter.limits()),
+                memory_hints: wgpu::MemoryHints::Performance,
+            },
+            None,
+        )
+        .await
+        .map_err(|e| CoreError::RenderError(format!("failed to open GPU device: {}", e)))?;
+    device.set_device_lost_callback(|reason, message| {
+        warn!("GPU device lost ({:?}): {}", reason, message);
+        DEVICE_LOST.store(true, Ordering::Relaxed);
+    });
+
+    let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("escape iterations"),
+        source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+    });
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("escape iterations"),
+        layout: None,
+        module: &module,
+        entry_point: Some("main"),
+        compilation_options: Default::default(),
+        cache: None,
+    });
+
+    info!("GPU renderer using {} ({:?})", adapter.get_info().name, adapter.get_info().backend);
+    Ok(GpuContext { device, queue, pipeline })
+}
+
+impl GpuContext {
+    /// Escape iterations for every pixel, row-major
+    fn counts(&self, request: &FractalRequest) -> Result<Vec<u32>> {
+        let device = &self.device;
+        let size = request.width as u64 * request.height as u64 * std::mem::size_of::<u32>() as u64;
+        device.push_error_scope(wgpu::ErrorFilter::OutOfMemory);
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        let params = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("params"),
+            contents: bytemuck::bytes_of(&Params::for_request(request)),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let counts = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("counts"),
+            size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("escape iterations"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: params.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: counts.as_entire_binding() },
+            ],
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("fractal") });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: Some("fractal"), timestamp_writes: None });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(request.width.div_ceil(WORKGROUP_SIZE), request.height.div_ceil(WORKGROUP_SIZE), 1);
+        }
+        encoder.copy_buffer_to_buffer(&counts, 0, &readback, 0, size);
+        self.queue.submit(Some(encoder.finish()));
+
+        for _ in 0..2 {
+            if let Some(e) = pollster::block_on(device.pop_error_scope()) {
+                return Err(CoreError::RenderError(e.to_string()));
+            }
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        let slice = readback.slice(..);
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .map_err(|_| CoreError::RenderError("GPU readback was dropped".to_string()))?
+            .map_err(|e| CoreError::RenderError(format!("GPU readback failed: {}", e)))?;
+
+        let data = bytemuck::cast_slice::<u8, u32>(&slice.get_mapped_range()).to_vec();
+        readback.unmap();
+        Ok(data)
+    }
+}
+
+/// Draws shallow Mandelbrot and Julia views on the first GPU wgpu finds; deeper views exceed f32 and go to the CPU renderers
+pub struct GpuRenderer;
+
+impl GpuRenderer {
+    pub(crate) fn create() -> Arc<dyn FractalRenderer> {
+        Arc::new(GpuRenderer)
+    }
+}
+
+impl FractalRenderer for GpuRenderer {
+    fn name(&self) -> &'static str {
+        "gpu"
+    }
+
+    fn capabilities(&self) -> RendererCapabilities {
+        RendererCapabilities {
+            kind: RendererKind::Gpu,
+            fractal_types: vec!["mandelbrot", "julia"],
+            palettes: true,
+            smooth_coloring: false,
+            orbit_traps: false,
+            distance_estimation: false,
+            interior_coloring: false,
+            precisions: vec![Precision::F32],
+            max_pixels: Some(MAX_PIXELS),
+            max_zoom: Some(F32_ZOOM_LIMIT),
+            priority: 20,
+        }
+    }
+
+    fn is_available(&self) -> bool {
+        !DEVICE_LOST.load(Ordering::Relaxed) && context().is_some()
+    }
+
+    fn render(&self, request: &FractalRequest, cancel: &CancelToken) -> Result<Vec<u8>> {
+        self.render_pooled(request, cancel, &BufferPool::new(0))
+    }
+
+    /// A GPU dispatch can't be interrupted, so cancellation only spares the colouring afterwards
+    fn render_pooled(&self, request: &FractalRequest, cancel: &CancelToken, buffers: &BufferPool) -> Result<Vec<u8>> {
+        let context = context().ok_or_else(|| CoreError::RenderError("no GPU adapter found".to_string()))?;
+        let counts = context.counts(request)?;
+        if cancel.is_cancelled() {
+            return Ok(Vec::new());
+        }
+
+        let mut escapes = buffers.take_escapes(counts.len());
+        escapes.par_iter_mut().zip(counts.par_iter()).for_each(|(escape, &iterations)| *escape = iterations as f64);
+        let pixels = colorize(request, &escapes, buffers);
+        buffers.give_escapes(escapes);
+        Ok(pixels)
+    }
+}
+
+/// Render on the GPU when the request suits it and an adapter is present, and on the best CPU renderer otherwise
+pub fn gpu_accelerated_generation(request: FractalRequest) -> FractalResponse {
+    FractalService::new().render(request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderers::CpuRenderer;
+
+    #[test]
+    fn test_gpu_matches_cpu_or_falls_back() {
+        let request = FractalRequest {
+            center_x: -0.5,
+            center_y: 0.1,
+            zoom: 1.3,
+            max_iterations: 200,
+            ..FractalRequest::new(FractalType::Mandelbrot, 67, 45)
+        };
+
+        let response = gpu_accelerated_generation(request.clone());
+        if !GpuRenderer.is_available() {
+            assert_ne!(response.compute_backend, RendererKind::Gpu);
+            return;
+        }
+        assert_eq!(response.compute_backend, RendererKind::Gpu);
+
+        for fractal_type in [FractalType::Mandelbrot, FractalType::Julia { c_real: -0.8, c_imag: 0.156 }] {
+            let request = FractalRequest { fractal_type, ..request.clone() };
+            let cpu = CpuRenderer.draw(&request, &CancelToken::new(), &BufferPool::new(0));
+            let gpu = GpuRenderer.render(&request, &CancelToken::new()).unwrap();
+            assert_eq!(gpu.len(), cpu.len());
+
+            // f32 rounding can move a boundary pixel's escape by an iteration or two
+            let differing = cpu.chunks(4).zip(gpu.chunks(4)).filter(|(a, b)| a != b).count();
+            assert!(differing * 20 < cpu.len() / 4, "{} of {} pixels differ", differing, cpu.len() / 4);
+        }
+    }
+}
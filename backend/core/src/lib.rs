@@ -0,0 +1,40 @@
+/*
+ * Embeddable engines behind the Dark Performance Showcase: fractal rendering, 3D ray marching, palettes, and metrics collection.
+ * I'm keeping this crate free of axum, sqlx, and Redis so another Rust program can render or collect metrics without pulling in the server.
+ */
+
+#![doc = "Fractal rendering, palettes, and metrics collection without the HTTP layer"]
+
+pub mod buffers;
+pub mod cancel;
+pub mod cost;
+pub mod error;
+pub mod fractal;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod mandelbulb;
+pub mod metrics;
+pub mod palettes;
+pub mod perturbation;
+pub mod renderers;
+pub mod strategies;
+pub mod threads;
+
+// The kernels live in fractal-engine; this keeps dark_performance_core::kernels working
+pub use fractal_engine::kernels;
+
+pub use buffers::{BufferPool, BufferPoolStats};
+pub use cancel::{CancelOnDrop, CancelToken};
+pub use cost::CostEstimate;
+pub use error::{CoreError, Result};
+pub use fractal::{
+    ColoringMode, EscapeBuffer, FractalRequest, FractalResponse, FractalService, FractalType, InteriorColoring, InteriorStats, OrbitTrap, Region,
+    MAX_ANTIALIASING,
+};
+pub use kernels::{FractalKernel, KernelInfo};
+pub use mandelbulb::{MandelbulbRequest, MandelbulbResponse};
+pub use metrics::MetricsCollector;
+pub use palettes::Palette;
+pub use renderers::{FractalRenderer, RendererRegistry};
+pub use strategies::{Strategy, StrategyComparison};
+pub use threads::RenderPools;
@@ -0,0 +1,390 @@
+/*
+ * Mandelbulb renderer: the 3D power-n cousin of the Mandelbrot set, drawn by sphere-tracing each pixel's ray against a distance estimate.
+ * I'm marching every ray on its own across rayon, so the work per pixel follows the scene's depth rather than an iteration count, a very different load from the 2D renderers.
+ */
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    ops::{Add, Mul, Sub},
+    str::FromStr,
+    time::Instant,
+};
+use tracing::{field, instrument, Span};
+
+use crate::{
+    cancel::CancelToken,
+    error::{CoreError, Result},
+    palettes::Palette,
+};
+
+/// Radius of a sphere that holds the bulb at every power; rays that miss it are background without marching
+const BOUNDING_RADIUS: f64 = 1.5;
+
+/// Where an orbit counts as escaped
+const BAILOUT: f64 = 2.0;
+
+/// Smallest surface distance a ray can hit at, however close to the camera
+const MIN_HIT_DISTANCE: f64 = 1e-5;
+
+/// Colour of surfaces when no palette is given, in keeping with the 2D renders' dark blues
+const DARK_SURFACE: [f64; 3] = [70.0, 120.0, 190.0];
+
+/// A point or direction in the bulb's space
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Vec3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Vec3 {
+    pub const fn new(x: f64, y: f64, z: f64) -> Self {
+        Self { x, y, z }
+    }
+
+    fn dot(self, other: Self) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    fn cross(self, other: Self) -> Self {
+        Self::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    fn length(self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    fn normalized(self) -> Self {
+        self * (1.0 / self.length())
+    }
+
+    fn is_finite(self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.z.is_finite()
+    }
+}
+
+impl Add for Vec3 {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Vec3 {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Mul<f64> for Vec3 {
+    type Output = Self;
+
+    fn mul(self, scale: f64) -> Self {
+        Self::new(self.x * scale, self.y * scale, self.z * scale)
+    }
+}
+
+impl FromStr for Vec3 {
+    type Err = CoreError;
+
+    /// Parses `x,y,z`
+    fn from_str(spec: &str) -> Result<Self> {
+        let invalid = || CoreError::ValidationError(format!("Invalid vector `{}`; expected x,y,z", spec));
+        let values: Vec<f64> = spec
+            .split(',')
+            .map(|value| value.trim().parse::<f64>())
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|_| invalid())?;
+        match values.as_slice() {
+            &[x, y, z] if Self::new(x, y, z).is_finite() => Ok(Self::new(x, y, z)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MandelbulbRequest {
+    pub width: u32,
+    pub height: u32,
+    /// Power the bulb's spherical coordinates are raised to each step; 8 gives the classic bulb
+    pub power: f64,
+    /// Iterations of the distance estimate at each step of a ray
+    pub max_iterations: u32,
+    /// Most steps a ray takes before it counts as a miss
+    pub max_steps: u32,
+    pub camera: Vec3,
+    /// Point the camera looks at
+    pub target: Vec3,
+    /// Vertical field of view, in degrees
+    pub field_of_view: f64,
+    /// Direction the light travels in, towards the scene; its length is ignored
+    pub light: Vec3,
+    /// Share of a surface's colour lit even when facing away from the light, from 0 to 1
+    pub ambient: f64,
+    /// Colours surfaces by distance from the bulb's centre; without one they use the dark theme
+    pub palette: Option<Palette>,
+}
+
+impl Default for MandelbulbRequest {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 600,
+            power: 8.0,
+            max_iterations: 12,
+            max_steps: 128,
+            camera: Vec3::new(0.0, -3.4, 1.2),
+            target: Vec3::new(0.0, 0.0, 0.0),
+            field_of_view: 45.0,
+            light: Vec3::new(0.5, 0.7, -0.6),
+            ambient: 0.15,
+            palette: None,
+        }
+    }
+}
+
+impl MandelbulbRequest {
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |message: &str| Err(CoreError::ValidationError(message.to_string()));
+        if self.width == 0 || self.height == 0 {
+            return invalid("Mandelbulb width and height must be positive");
+        }
+        if !(self.power.is_finite() && self.power > 1.0) {
+            return invalid("Mandelbulb power must be greater than 1");
+        }
+        if !(self.field_of_view > 0.0 && self.field_of_view < 180.0) {
+            return invalid("Field of view must be between 0 and 180 degrees");
+        }
+        if !(self.camera.is_finite() && self.target.is_finite() && (self.target - self.camera).length() > 0.0) {
+            return invalid("Camera and target must be distinct points");
+        }
+        if !(self.light.is_finite() && self.light.length() > 0.0) {
+            return invalid("Light direction must be a non-zero vector");
+        }
+        if !(0.0..=1.0).contains(&self.ambient) {
+            return invalid("Ambient light must be between 0 and 1");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MandelbulbResponse {
+    /// RGBA, row-major
+    pub data: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub computation_time_ms: u128,
+    /// Rays that reached the bulb's surface
+    pub hits: u64,
+    /// Distance-estimate steps taken across every ray
+    pub march_steps: u64,
+}
+
+/// Camera basis and the angle one pixel spans, worked out once per render
+struct View {
+    origin: Vec3,
+    forward: Vec3,
+    right: Vec3,
+    up: Vec3,
+    half_height: f64,
+    pixel_angle: f64,
+}
+
+impl View {
+    fn new(request: &MandelbulbRequest) -> Self {
+        let forward = (request.target - request.camera).normalized();
+        // Up is the bulb's polar axis, z, unless the camera looks straight along it
+        let world_up = if forward.cross(Vec3::new(0.0, 0.0, 1.0)).length() < 1e-9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(0.0, 0.0, 1.0) };
+        let right = forward.cross(world_up).normalized();
+        let half_height = (request.field_of_view.to_radians() / 2.0).tan();
+        Self {
+            origin: request.camera,
+            forward,
+            right,
+            up: right.cross(forward),
+            half_height,
+            pixel_angle: 2.0 * half_height / request.height as f64,
+        }
+    }
+
+    fn ray(&self, request: &MandelbulbRequest, x: u32, y: u32) -> Vec3 {
+        let aspect = request.width as f64 / request.height as f64;
+        let u = (2.0 * (x as f64 + 0.5) / request.width as f64 - 1.0) * aspect * self.half_height;
+        let v = (1.0 - 2.0 * (y as f64 + 0.5) / request.height as f64) * self.half_height;
+        (self.forward + self.right * u + self.up * v).normalized()
+    }
+}
+
+/// Render the bulb; a cancelled render stops at the next row and its pixels are thrown away
+#[instrument(
+    name = "fractal.mandelbulb",
+    level = "debug",
+    skip_all,
+    fields(width = request.width, height = request.height, power = request.power, computation_time_ms = field::Empty),
+)]
+pub fn render(request: &MandelbulbRequest, cancel: &CancelToken) -> Result<MandelbulbResponse> {
+    request.validate()?;
+    let start_time = Instant::now();
+    let view = View::new(request);
+    let light = (request.light * -1.0).normalized();
+
+    let rows: Vec<(Vec<u8>, u64, u64)> = (0..request.height)
+        .into_par_iter()
+        .map(|y| {
+            if cancel.is_cancelled() {
+                return (Vec::new(), 0, 0);
+            }
+            let mut row = Vec::with_capacity(request.width as usize * 4);
+            let (mut hits, mut steps) = (0, 0);
+            for x in 0..request.width {
+                let (pixel, hit, taken) = shade(request, &view, light, view.ray(request, x, y));
+                row.extend_from_slice(&pixel);
+                hits += u64::from(hit);
+                steps += u64::from(taken);
+            }
+            (row, hits, steps)
+        })
+        .collect();
+    if cancel.is_cancelled() {
+        return Err(CoreError::Cancelled);
+    }
+
+    let (hits, march_steps) = rows.iter().fold((0, 0), |(hits, steps), row| (hits + row.1, steps + row.2));
+    let data = rows.into_iter().flat_map(|row| row.0).collect();
+    let computation_time_ms = start_time.elapsed().as_millis();
+    Span::current().record("computation_time_ms", computation_time_ms as u64);
+
+    Ok(MandelbulbResponse {
+        data,
+        width: request.width,
+        height: request.height,
+        computation_time_ms,
+        hits,
+        march_steps,
+    })
+}
+
+/// Lower bound on the distance from `point` to the bulb, from the running derivative of its orbit
+pub fn distance_estimate(point: Vec3, power: f64, max_iterations: u32) -> f64 {
+    let mut z = point;
+    let mut derivative = 1.0;
+    let mut radius = 0.0;
+
+    for _ in 0..max_iterations {
+        radius = z.length();
+        if radius > BAILOUT {
+            break;
+        }
+        let theta = if radius > 0.0 { (z.z / radius).acos() } else { 0.0 };
+        let phi = z.y.atan2(z.x);
+        derivative = radius.powf(power - 1.0) * power * derivative + 1.0;
+
+        let scaled = radius.powf(power);
+        let (theta, phi) = (theta * power, phi * power);
+        z = Vec3::new(theta.sin() * phi.cos(), theta.sin() * phi.sin(), theta.cos()) * scaled + point;
+    }
+
+    // Orbits that never leave the origin have no logarithm to take, and are inside anyway
+    (0.5 * radius.ln() * radius / derivative).max(0.0)
+}
+
+/// March one ray and light whatever it reaches, with whether it hit and the steps it took
+fn shade(request: &MandelbulbRequest, view: &View, light: Vec3, direction: Vec3) -> ([u8; 4], bool, u32) {
+    const BACKGROUND: [u8; 4] = [0, 0, 0, 255];
+
+    // Only the stretch of the ray inside the bounding sphere can reach the surface
+    let along = -view.origin.dot(direction);
+    let miss_sqr = view.origin.dot(view.origin) - along * along;
+    let chord_sqr = BOUNDING_RADIUS * BOUNDING_RADIUS - miss_sqr;
+    if chord_sqr < 0.0 {
+        return (BACKGROUND, false, 0);
+    }
+    let (mut t, exit) = ((along - chord_sqr.sqrt()).max(0.0), along + chord_sqr.sqrt());
+
+    for step in 0..request.max_steps {
+        let point = view.origin + direction * t;
+        let distance = distance_estimate(point, request.power, request.max_iterations);
+        // A surface closer than the pixel's footprint at this depth is as good as hit
+        let threshold = (view.pixel_angle * t * 0.5).max(MIN_HIT_DISTANCE);
+        if distance < threshold {
+            let occlusion = 1.0 - step as f64 / request.max_steps as f64;
+            return (surface_color(request, point, normal(request, point, threshold), light, direction, occlusion), true, step + 1);
+        }
+        t += distance;
+        if t > exit {
+            return (BACKGROUND, false, step + 1);
+        }
+    }
+    (BACKGROUND, false, request.max_steps)
+}
+
+/// Surface normal from the distance estimate's gradient, by central differences on the scale of the hit
+fn normal(request: &MandelbulbRequest, point: Vec3, h: f64) -> Vec3 {
+    let de = |offset: Vec3| distance_estimate(point + offset, request.power, request.max_iterations);
+    let gradient = Vec3::new(
+        de(Vec3::new(h, 0.0, 0.0)) - de(Vec3::new(-h, 0.0, 0.0)),
+        de(Vec3::new(0.0, h, 0.0)) - de(Vec3::new(0.0, -h, 0.0)),
+        de(Vec3::new(0.0, 0.0, h)) - de(Vec3::new(0.0, 0.0, -h)),
+    );
+    // Deep in a crease the estimate can be flat; facing the camera then looks better than a NaN
+    if gradient.length() > 0.0 { gradient.normalized() } else { Vec3::new(0.0, 0.0, -1.0) }
+}
+
+// Lambert diffuse and a Blinn highlight, darkened where rays needed many steps, which is where the surface folds in on itself
+fn surface_color(request: &MandelbulbRequest, point: Vec3, normal: Vec3, light: Vec3, direction: Vec3, occlusion: f64) -> [u8; 4] {
+    let diffuse = normal.dot(light).max(0.0);
+    let halfway = (light - direction).normalized();
+    let specular = normal.dot(halfway).max(0.0).powi(32) * 0.4;
+    let brightness = (request.ambient + (1.0 - request.ambient) * diffuse) * occlusion;
+
+    let base = match &request.palette {
+        Some(palette) => {
+            let [r, g, b, _] = palette.sample(point.length() / BOUNDING_RADIUS);
+            [r as f64, g as f64, b as f64]
+        }
+        None => DARK_SURFACE,
+    };
+    let channel = |value: f64| (value * brightness + 255.0 * specular * occlusion).clamp(0.0, 255.0) as u8;
+    [channel(base[0]), channel(base[1]), channel(base[2]), 255]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mandelbulb_is_marched_and_lit() {
+        assert_eq!(distance_estimate(Vec3::new(0.0, 0.0, 0.0), 8.0, 12), 0.0);
+        let far = distance_estimate(Vec3::new(0.0, 0.0, -3.0), 8.0, 12);
+        assert!(far > 1.0 && far < 3.0, "{}", far);
+
+        let request = MandelbulbRequest { width: 48, height: 36, ..MandelbulbRequest::default() };
+        let response = render(&request, &CancelToken::new()).unwrap();
+        assert_eq!(response.data.len(), 48 * 36 * 4);
+        assert!(response.hits > 0 && response.hits < 48 * 36);
+        assert!(response.march_steps > response.hits);
+
+        // The bulb fills the middle of the frame and misses the corners
+        let pixel = |x: usize, y: usize| &response.data[(y * 48 + x) * 4..(y * 48 + x) * 4 + 4];
+        assert_ne!(pixel(24, 18)[..3], [0, 0, 0]);
+        assert_eq!(pixel(0, 0), [0, 0, 0, 255]);
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        assert!(matches!(render(&request, &cancel), Err(CoreError::Cancelled)));
+
+        let stuck = MandelbulbRequest { target: request.camera, ..request };
+        assert!(matches!(render(&stuck, &CancelToken::new()), Err(CoreError::ValidationError(_))));
+        assert!("1, 2.5,-3".parse::<Vec3>().is_ok_and(|v| v == Vec3::new(1.0, 2.5, -3.0)));
+        assert!("1,2".parse::<Vec3>().is_err());
+    }
+}
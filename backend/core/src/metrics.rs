@@ -9,7 +9,7 @@ use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
 use tracing::{debug, warn, error};
 
-use crate::utils::error::{AppError, Result};
+use crate::error::Result;
 
 /// I'm implementing a thread-safe metrics collection system that minimizes performance impact
 #[derive(Debug, Clone)]
@@ -404,13 +404,18 @@ impl MetricsCollector {
     }
 
     pub fn with_config(config: MetricsConfig) -> Result<Self> {
+        Self::with_start_time(config, Instant::now())
+    }
+
+    /// A collector whose summary measures uptime from `start_time` rather than from its own creation
+    pub fn with_start_time(config: MetricsConfig, start_time: Instant) -> Result<Self> {
         let inner = Arc::new(MetricsCollectorInner {
             counters: RwLock::new(HashMap::new()),
             gauges: RwLock::new(HashMap::new()),
             histograms: RwLock::new(HashMap::new()),
             timers: RwLock::new(HashMap::new()),
             config,
-            start_time: Instant::now(),
+            start_time,
         });
 
         Ok(Self { inner })
@@ -817,7 +822,7 @@ mod tests {
         assert_eq!(test_timer["total_ms"].as_u64().unwrap(), 100);
     }
 
-    #[test]
+    #[std::prelude::v1::test]
     fn test_performance_timer() {
         let mut timer = PerformanceTimer::new("test_operation");
 
@@ -0,0 +1,503 @@
+/*
+ * Palette and preset models for user-supplied fractal colouring and saved parameter bundles.
+ * I'm parsing JSON and GIMP gradient (.ggr) uploads into one normalised gradient representation the renderer can sample directly.
+ */
+
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::error::{CoreError, Result};
+use crate::kernels;
+
+/// Upper bounds on uploaded palette content
+/// I'm keeping these tight since palettes are sampled once per pixel
+pub const MAX_PALETTE_STOPS: usize = 256;
+pub const MAX_PALETTE_NAME_LEN: usize = 100;
+pub const MAX_UPLOAD_BYTES: usize = 64 * 1024;
+
+/// Single colour stop on a gradient, positioned in [0, 1]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ColorStop {
+    pub position: f64,
+    pub color: [u8; 4],
+}
+
+/// Source format a palette was uploaded in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaletteFormat {
+    Json,
+    Ggr,
+    /// One of the named palettes shipped with the engine
+    Builtin,
+    /// Gradient stops given with a single render and never stored
+    Inline,
+}
+
+impl PaletteFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaletteFormat::Json => "json",
+            PaletteFormat::Ggr => "ggr",
+            PaletteFormat::Builtin => "builtin",
+            PaletteFormat::Inline => "inline",
+        }
+    }
+
+    /// Detect the format from the upload filename, falling back to sniffing the content
+    pub fn detect(filename: Option<&str>, content: &[u8]) -> Self {
+        let is_ggr_name = filename
+            .map(|name| name.to_lowercase().ends_with(".ggr"))
+            .unwrap_or(false);
+
+        if is_ggr_name || content.starts_with(b"GIMP Gradient") {
+            PaletteFormat::Ggr
+        } else {
+            PaletteFormat::Json
+        }
+    }
+}
+
+/// Normalised colour gradient stored in Postgres and used by the fractal renderer
+/// I'm keeping stops sorted by position so sampling is a simple linear scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Palette {
+    pub id: Uuid,
+    pub name: String,
+    pub source_format: PaletteFormat,
+    pub stops: Vec<ColorStop>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// JSON upload shape: either a list of stops or evenly spaced colours
+#[derive(Debug, Deserialize)]
+struct JsonPaletteUpload {
+    name: Option<String>,
+    #[serde(default)]
+    stops: Vec<ColorStop>,
+    #[serde(default)]
+    colors: Vec<[u8; 4]>,
+}
+
+impl Palette {
+    /// Parse and validate an uploaded palette file
+    /// I'm accepting an explicit name override so multipart forms can rename uploads
+    pub fn parse(format: PaletteFormat, content: &[u8], name_override: Option<String>) -> Result<Self> {
+        if content.len() > MAX_UPLOAD_BYTES {
+            return Err(CoreError::ValidationError(format!(
+                "Palette file exceeds {} bytes", MAX_UPLOAD_BYTES
+            )));
+        }
+
+        let (parsed_name, stops) = match format {
+            PaletteFormat::Ggr => parse_ggr_palette(content)?,
+            _ => parse_json_palette(content)?,
+        };
+
+        let name = name_override
+            .or(parsed_name)
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| "Untitled palette".to_string());
+
+        let palette = Self {
+            id: Uuid::new_v4(),
+            name,
+            source_format: format,
+            stops,
+            created_at: Utc::now(),
+        };
+
+        palette.validate()?;
+        Ok(palette)
+    }
+
+    /// Build a palette from already-persisted stops
+    pub fn from_stops(id: Uuid, name: String, source_format: PaletteFormat, stops: Vec<ColorStop>, created_at: DateTime<Utc>) -> Self {
+        Self { id, name, source_format, stops, created_at }
+    }
+
+    /// Every named palette, in catalogue order
+    pub fn builtins() -> Vec<Self> {
+        BUILTIN_PALETTES.iter().enumerate().map(|(index, (name, colors))| builtin_palette(index, name, colors)).collect()
+    }
+
+    /// Named palette by case-insensitive name
+    pub fn builtin(name: &str) -> Option<Self> {
+        BUILTIN_PALETTES
+            .iter()
+            .enumerate()
+            .find(|(_, (builtin, _))| builtin.eq_ignore_ascii_case(name.trim()))
+            .map(|(index, (name, colors))| builtin_palette(index, name, colors))
+    }
+
+    /// Named palette by its fixed id, so built-ins work anywhere a stored palette id does
+    pub fn builtin_by_id(id: Uuid) -> Option<Self> {
+        let index = (id.as_u128() as usize).checked_sub(1)?;
+        BUILTIN_PALETTES.get(index).map(|(name, colors)| builtin_palette(index, name, colors))
+    }
+
+    /// Palette from gradient stops given inline, as comma-separated `RRGGBB[AA]` colours each with an optional `:position`
+    /// I'm spacing the colours evenly when no positions are given, and requiring every stop to have one otherwise
+    pub fn from_gradient(spec: &str) -> Result<Self> {
+        let parsed: Vec<([u8; 4], Option<f64>)> = spec.split(',').map(parse_gradient_stop).collect::<Result<_>>()?;
+        let positioned = parsed.iter().filter(|(_, position)| position.is_some()).count();
+        if positioned != 0 && positioned != parsed.len() {
+            return Err(CoreError::ValidationError("Give every gradient stop a position, or none of them".to_string()));
+        }
+
+        let last = parsed.len().saturating_sub(1).max(1) as f64;
+        let stops = parsed
+            .into_iter()
+            .enumerate()
+            .map(|(i, (color, position))| ColorStop { position: position.unwrap_or(i as f64 / last), color })
+            .collect();
+
+        let palette = Self {
+            id: Uuid::new_v4(),
+            name: "Inline gradient".to_string(),
+            source_format: PaletteFormat::Inline,
+            stops,
+            created_at: Utc::now(),
+        };
+        palette.validate()?;
+        Ok(palette)
+    }
+
+    /// Id to record alongside a render so it can be repeated; inline gradients are never stored and have none
+    pub fn reference_id(&self) -> Option<Uuid> {
+        (self.source_format != PaletteFormat::Inline).then_some(self.id)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.name.chars().count() > MAX_PALETTE_NAME_LEN {
+            return Err(CoreError::ValidationError(format!(
+                "Palette name must be at most {} characters", MAX_PALETTE_NAME_LEN
+            )));
+        }
+
+        if self.stops.len() < 2 {
+            return Err(CoreError::ValidationError("Palette needs at least two colour stops".to_string()));
+        }
+
+        if self.stops.len() > MAX_PALETTE_STOPS {
+            return Err(CoreError::ValidationError(format!(
+                "Palette may contain at most {} colour stops", MAX_PALETTE_STOPS
+            )));
+        }
+
+        if self.stops.iter().any(|s| !s.position.is_finite() || !(0.0..=1.0).contains(&s.position)) {
+            return Err(CoreError::ValidationError("Stop positions must be within 0.0..=1.0".to_string()));
+        }
+
+        if self.stops.windows(2).any(|w| w[1].position < w[0].position) {
+            return Err(CoreError::ValidationError("Stop positions must be in ascending order".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Sample the gradient at `t` in [0, 1] with linear interpolation between stops
+    pub fn sample(&self, t: f64) -> [u8; 4] {
+        let t = t.clamp(0.0, 1.0);
+        let first = self.stops[0];
+        if t <= first.position {
+            return first.color;
+        }
+
+        for pair in self.stops.windows(2) {
+            let (left, right) = (pair[0], pair[1]);
+            if t <= right.position {
+                let span = right.position - left.position;
+                let local = if span > 0.0 { (t - left.position) / span } else { 0.0 };
+                let mut color = [0u8; 4];
+                for (i, channel) in color.iter_mut().enumerate() {
+                    let a = left.color[i] as f64;
+                    let b = right.color[i] as f64;
+                    *channel = (a + (b - a) * local).round() as u8;
+                }
+                return color;
+            }
+        }
+
+        self.stops[self.stops.len() - 1].color
+    }
+}
+
+/// Named palettes shipped with the engine, as evenly spaced RGB colours
+/// I'm deriving each one's id from its place here, so only ever append to this list
+const BUILTIN_PALETTES: &[(&str, &[[u8; 3]])] = &[
+    ("fire", &[[0, 0, 0], [128, 0, 0], [230, 81, 0], [255, 193, 7], [255, 255, 255]]),
+    ("ocean", &[[0, 7, 45], [0, 60, 130], [0, 150, 199], [144, 224, 239], [255, 255, 255]]),
+    ("grayscale", &[[0, 0, 0], [255, 255, 255]]),
+    ("viridis", &[[68, 1, 84], [59, 82, 139], [33, 145, 140], [94, 201, 98], [253, 231, 37]]),
+    ("sunset", &[[45, 27, 78], [142, 44, 110], [232, 80, 91], [249, 166, 90], [255, 243, 176]]),
+];
+
+fn builtin_palette(index: usize, name: &str, colors: &[[u8; 3]]) -> Palette {
+    let last = (colors.len() - 1) as f64;
+    Palette {
+        id: Uuid::from_u128(index as u128 + 1),
+        name: name.to_string(),
+        source_format: PaletteFormat::Builtin,
+        stops: colors
+            .iter()
+            .enumerate()
+            .map(|(i, [r, g, b])| ColorStop { position: i as f64 / last, color: [*r, *g, *b, 255] })
+            .collect(),
+        created_at: DateTime::UNIX_EPOCH,
+    }
+}
+
+/// One `RRGGBB[AA][:position]` stop of an inline gradient; a leading `#` is allowed
+fn parse_gradient_stop(stop: &str) -> Result<([u8; 4], Option<f64>)> {
+    let invalid = || CoreError::ValidationError(format!("Invalid gradient stop `{}`; expected RRGGBB[AA][:position]", stop));
+    let (hex, position) = match stop.trim().split_once(':') {
+        Some((hex, position)) => (hex, Some(position.trim().parse::<f64>().map_err(|_| invalid())?)),
+        None => (stop.trim(), None),
+    };
+
+    let hex = hex.trim().trim_start_matches('#');
+    if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+        return Err(invalid());
+    }
+    let mut color = [0, 0, 0, 255];
+    for (channel, digits) in color.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        let digits = std::str::from_utf8(digits).map_err(|_| invalid())?;
+        *channel = u8::from_str_radix(digits, 16).map_err(|_| invalid())?;
+    }
+    Ok((color, position))
+}
+
+fn parse_json_palette(content: &[u8]) -> Result<(Option<String>, Vec<ColorStop>)> {
+    let upload: JsonPaletteUpload = serde_json::from_slice(content)
+        .map_err(|e| CoreError::ValidationError(format!("Invalid palette JSON: {}", e)))?;
+
+    let stops = if !upload.stops.is_empty() {
+        upload.stops
+    } else if upload.colors.len() >= 2 {
+        let last = (upload.colors.len() - 1) as f64;
+        upload.colors
+            .iter()
+            .enumerate()
+            .map(|(i, color)| ColorStop { position: i as f64 / last, color: *color })
+            .collect()
+    } else {
+        return Err(CoreError::ValidationError(
+            "Palette JSON must contain `stops` or at least two `colors`".to_string()
+        ));
+    };
+
+    Ok((upload.name, stops))
+}
+
+/// Parse a GIMP gradient file
+/// I'm approximating every segment as linear RGB blending between its endpoint colours
+fn parse_ggr_palette(content: &[u8]) -> Result<(Option<String>, Vec<ColorStop>)> {
+    let text = std::str::from_utf8(content)
+        .map_err(|_| CoreError::ValidationError("GIMP gradient must be UTF-8 text".to_string()))?;
+    let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+
+    if lines.next() != Some("GIMP Gradient") {
+        return Err(CoreError::ValidationError("Missing `GIMP Gradient` header".to_string()));
+    }
+
+    let mut next = lines.next();
+    let mut name = None;
+    if let Some(line) = next {
+        if let Some(value) = line.strip_prefix("Name:") {
+            name = Some(value.trim().to_string());
+            next = lines.next();
+        }
+    }
+
+    let segment_count: usize = next
+        .and_then(|l| l.parse().ok())
+        .ok_or_else(|| CoreError::ValidationError("Missing GIMP gradient segment count".to_string()))?;
+
+    if segment_count == 0 || segment_count > MAX_PALETTE_STOPS {
+        return Err(CoreError::ValidationError(format!(
+            "GIMP gradient must have between 1 and {} segments", MAX_PALETTE_STOPS
+        )));
+    }
+
+    let to_byte = |v: f64| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let mut stops: Vec<ColorStop> = Vec::with_capacity(segment_count + 1);
+
+    for index in 0..segment_count {
+        let line = lines.next().ok_or_else(|| {
+            CoreError::ValidationError(format!("GIMP gradient is missing segment {}", index + 1))
+        })?;
+        let values: Vec<f64> = line
+            .split_whitespace()
+            .take(11)
+            .map(|v| v.parse::<f64>())
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|_| CoreError::ValidationError(format!("Invalid GIMP gradient segment {}", index + 1)))?;
+
+        if values.len() < 11 {
+            return Err(CoreError::ValidationError(format!("Invalid GIMP gradient segment {}", index + 1)));
+        }
+
+        let left = ColorStop {
+            position: values[0],
+            color: [to_byte(values[3]), to_byte(values[4]), to_byte(values[5]), to_byte(values[6])],
+        };
+        let right = ColorStop {
+            position: values[2],
+            color: [to_byte(values[7]), to_byte(values[8]), to_byte(values[9]), to_byte(values[10])],
+        };
+
+        if stops.last() != Some(&left) {
+            stops.push(left);
+        }
+        stops.push(right);
+    }
+
+    Ok((name, stops))
+}
+
+/// Optional fractal parameters carried by a preset bundle
+/// I'm leaving every field optional so presets can pin only what they care about
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PresetParameters {
+    pub fractal_type: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub center_x: Option<f64>,
+    pub center_y: Option<f64>,
+    pub zoom: Option<f64>,
+    pub max_iterations: Option<u32>,
+    pub c_real: Option<f64>,
+    pub c_imag: Option<f64>,
+    /// Exponent for multibrot presets
+    pub power: Option<f64>,
+}
+
+/// Saved preset bundle referencing an optional palette
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FractalPreset {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub parameters: PresetParameters,
+    pub palette_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Uploaded preset bundle; may reference an existing palette or embed a new one
+#[derive(Debug, Deserialize)]
+pub struct PresetBundle {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub parameters: PresetParameters,
+    pub palette_id: Option<Uuid>,
+    pub palette: Option<serde_json::Value>,
+}
+
+impl PresetBundle {
+    /// Parse and validate a bundle, with a non-blank `name_override` replacing its name before the checks run
+    pub fn parse(content: &[u8], name_override: Option<String>) -> Result<Self> {
+        if content.len() > MAX_UPLOAD_BYTES {
+            return Err(CoreError::ValidationError(format!(
+                "Preset bundle exceeds {} bytes", MAX_UPLOAD_BYTES
+            )));
+        }
+
+        let mut bundle: Self = serde_json::from_slice(content)
+            .map_err(|e| CoreError::ValidationError(format!("Invalid preset bundle: {}", e)))?;
+        if let Some(name) = name_override.filter(|n| !n.trim().is_empty()) {
+            bundle.name = name;
+        }
+
+        if bundle.name.trim().is_empty() || bundle.name.chars().count() > MAX_PALETTE_NAME_LEN {
+            return Err(CoreError::ValidationError(format!(
+                "Preset name must be 1-{} characters", MAX_PALETTE_NAME_LEN
+            )));
+        }
+
+        if let Some(ref kind) = bundle.parameters.fractal_type {
+            if kernels::kernel(kind).is_none() {
+                return Err(CoreError::ValidationError(format!("Unsupported fractal type: {}", kind)));
+            }
+        }
+
+        if bundle.palette_id.is_some() && bundle.palette.is_some() {
+            return Err(CoreError::ValidationError(
+                "Preset bundle may set `palette_id` or embed `palette`, not both".to_string()
+            ));
+        }
+
+        Ok(bundle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_GGR: &str = "GIMP Gradient\nName: Ember\n2\n\
+        0.000000 0.250000 0.500000 0.0 0.0 0.0 1.0 1.0 0.0 0.0 1.0 0 0\n\
+        0.500000 0.750000 1.000000 1.0 0.0 0.0 1.0 1.0 1.0 0.0 1.0 0 0\n";
+
+    #[test]
+    fn test_parse_ggr_palette() {
+        let palette = Palette::parse(PaletteFormat::Ggr, SAMPLE_GGR.as_bytes(), None).unwrap();
+        assert_eq!(palette.name, "Ember");
+        assert_eq!(palette.stops.len(), 3);
+        assert_eq!(palette.sample(0.0), [0, 0, 0, 255]);
+        assert_eq!(palette.sample(0.5), [255, 0, 0, 255]);
+        assert_eq!(palette.sample(1.0), [255, 255, 0, 255]);
+    }
+
+    #[test]
+    fn test_parse_json_colors_are_evenly_spaced() {
+        let json = br#"{"name": "mono", "colors": [[0,0,0,255],[255,255,255,255]]}"#;
+        let palette = Palette::parse(PaletteFormat::Json, json, Some("renamed".to_string())).unwrap();
+        assert_eq!(palette.name, "renamed");
+        assert_eq!(palette.sample(0.5), [128, 128, 128, 255]);
+    }
+
+    #[test]
+    fn test_rejects_invalid_palettes() {
+        assert!(Palette::parse(PaletteFormat::Json, br#"{"colors": [[0,0,0,255]]}"#, None).is_err());
+        let unordered = br#"{"stops": [{"position": 0.8, "color": [0,0,0,255]}, {"position": 0.2, "color": [1,1,1,255]}]}"#;
+        assert!(Palette::parse(PaletteFormat::Json, unordered, None).is_err());
+        assert!(Palette::parse(PaletteFormat::Ggr, b"not a gradient", None).is_err());
+
+        let bundle = br#"{"name": "Seahorse"}"#;
+        assert_eq!(PresetBundle::parse(bundle, Some("Valley".to_string())).unwrap().name, "Valley");
+        assert_eq!(PresetBundle::parse(bundle, Some("  ".to_string())).unwrap().name, "Seahorse");
+        assert!(PresetBundle::parse(bundle, Some("x".repeat(MAX_PALETTE_NAME_LEN + 1))).is_err());
+    }
+
+    #[test]
+    fn test_builtin_and_inline_palettes() {
+        let fire = Palette::builtin("Fire").unwrap();
+        assert_eq!(fire.source_format, PaletteFormat::Builtin);
+        assert_eq!(Palette::builtin_by_id(fire.id).unwrap().name, "fire");
+        assert!(Palette::builtin_by_id(Uuid::nil()).is_none());
+        assert!(Palette::builtin("plaid").is_none());
+        assert_eq!(Palette::builtins().len(), BUILTIN_PALETTES.len());
+
+        let even = Palette::from_gradient("000000,#ffffff80").unwrap();
+        assert_eq!(even.sample(0.5), [128, 128, 128, 192]);
+        assert_eq!(even.reference_id(), None);
+        assert_eq!(fire.reference_id(), Some(fire.id));
+
+        let placed = Palette::from_gradient("ff0000:0, 00ff00:0.25, 0000ff:1").unwrap();
+        assert_eq!(placed.stops[1].position, 0.25);
+        assert!(Palette::from_gradient("ff0000:0,00ff00").is_err());
+        assert!(Palette::from_gradient("ff0000").is_err());
+        assert!(Palette::from_gradient("ff00zz,000000").is_err());
+    }
+
+    #[test]
+    fn test_format_detection() {
+        assert_eq!(PaletteFormat::detect(Some("fire.GGR"), b"{}"), PaletteFormat::Ggr);
+        assert_eq!(PaletteFormat::detect(None, SAMPLE_GGR.as_bytes()), PaletteFormat::Ggr);
+        assert_eq!(PaletteFormat::detect(Some("fire.json"), b"{}"), PaletteFormat::Json);
+    }
+}
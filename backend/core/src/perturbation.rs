@@ -0,0 +1,343 @@
+/*
+ * Deep zoom renderer: one reference orbit in arbitrary precision, every pixel iterated as a small f64 offset from it.
+ * I'm rebasing a pixel onto the start of the reference whenever its offset outgrows its own value, so glitches never need a second reference orbit.
+ */
+
+use num_bigint::BigInt;
+use num_complex::Complex;
+use rayon::prelude::*;
+use std::sync::Arc;
+use tracing::debug;
+
+use crate::{
+    buffers::BufferPool,
+    cancel::CancelToken,
+    error::Result,
+    fractal::{colorize, escape_rows, escape_value, pixel_offset, FractalRequest, FractalType, InteriorStats, Precision},
+    kernels::Interior,
+    renderers::{FractalRenderer, RendererCapabilities, RendererKind},
+};
+
+/// Deepest zoom the plain f64 kernels stay accurate at; neighbouring pixels' coordinates start rounding together past this
+pub const F64_ZOOM_LIMIT: f64 = 1e13;
+
+/// Fractional bits kept beyond those needed to tell neighbouring pixels apart
+const GUARD_BITS: u64 = 64;
+
+/// Largest cubic series term allowed, as a fraction of the distance between neighbouring pixels at that iteration
+const SERIES_TOLERANCE: f64 = 1e-3;
+
+/// Signed fixed-point arithmetic on BigInt with `bits` fractional bits
+#[derive(Debug, Clone, Copy)]
+struct Fixed {
+    bits: u64,
+}
+
+impl Fixed {
+    /// Enough precision to resolve one pixel of the request, plus guard bits for the orbit's rounding
+    fn for_request(request: &FractalRequest) -> Self {
+        let pixels = request.width.max(request.height) as f64;
+        let needed = (request.zoom.max(1.0) * pixels).log2().ceil() as u64;
+        Self { bits: needed + GUARD_BITS }
+    }
+
+    /// The value in fixed point, exact for every f64 whose lowest set bit is within `bits` of the point
+    fn encode(self, value: f64) -> BigInt {
+        let raw = value.to_bits();
+        let exponent = ((raw >> 52) & 0x7ff) as i64;
+        let fraction = raw & ((1 << 52) - 1);
+        let (mantissa, exponent) = match exponent {
+            0 => (fraction, -1074),
+            _ => (fraction | (1 << 52), exponent - 1075),
+        };
+
+        let shift = exponent + self.bits as i64;
+        let magnitude = if shift >= 0 {
+            BigInt::from(mantissa) << shift as u64
+        } else {
+            BigInt::from(mantissa) >> (-shift) as u64
+        };
+        if value.is_sign_negative() { -magnitude } else { magnitude }
+    }
+
+    /// The fixed-point value back as an f64, truncated to its top 62 bits
+    fn decode(self, value: &BigInt) -> f64 {
+        let excess = value.bits().saturating_sub(62);
+        let top = i64::try_from(&(value >> excess)).unwrap_or_default();
+        top as f64 * 2f64.powi(excess as i32 - self.bits as i32)
+    }
+}
+
+/// Orbit of the view centre under the requested iteration, computed in fixed point and rounded to f64 for the pixels
+/// I'm stopping at the first point past the escape radius, which is still accurate and ends every pixel's walk along it
+fn reference_orbit(request: &FractalRequest, fixed: Fixed) -> Vec<Complex<f64>> {
+    let center = (fixed.encode(request.center_x), fixed.encode(request.center_y));
+    let ((mut zr, mut zi), (cr, ci)) = match request.fractal_type {
+        FractalType::Julia { c_real, c_imag } => (center, (fixed.encode(c_real), fixed.encode(c_imag))),
+        _ => ((BigInt::from(0), BigInt::from(0)), center),
+    };
+    let escape = BigInt::from(4) << (2 * fixed.bits);
+
+    let mut orbit = Vec::with_capacity(request.max_iterations as usize + 1);
+    loop {
+        orbit.push(Complex::new(fixed.decode(&zr), fixed.decode(&zi)));
+        let (zr2, zi2) = (&zr * &zr, &zi * &zi);
+        if &zr2 + &zi2 > escape || orbit.len() > request.max_iterations as usize {
+            return orbit;
+        }
+        let cross = (&zr * &zi) >> (fixed.bits - 1);
+        zr = ((zr2 - zi2) >> fixed.bits) + &cr;
+        zi = cross + &ci;
+    }
+}
+
+/// Cubic series for a pixel's offset after `skip` iterations, in terms of its starting offset
+#[derive(Debug, Clone, Copy)]
+struct Series {
+    skip: usize,
+    a: Complex<f64>,
+    b: Complex<f64>,
+    c: Complex<f64>,
+}
+
+impl Series {
+    /// Advance the coefficients along the reference for as long as the cubic term stays well under a pixel
+    /// I'm measuring against the pixel spacing scaled by A, the linear growth of offsets, so the error stays sub-pixel at every step
+    fn approximate(orbit: &[Complex<f64>], julia: bool, radius: f64, spacing: f64) -> Self {
+        let zero = Complex::new(0.0, 0.0);
+        let one = Complex::new(1.0, 0.0);
+        // Mandelbrot offsets start at zero and gain the pixel's c offset each step; Julia offsets start as the pixel offset
+        let (linear, step) = if julia { (one, zero) } else { (zero, one) };
+        let mut series = Self { skip: 0, a: linear, b: zero, c: zero };
+
+        for (n, z) in orbit.iter().enumerate().take(orbit.len().saturating_sub(1)) {
+            let two_z = z * 2.0;
+            let a = two_z * series.a + step;
+            let b = two_z * series.b + series.a * series.a;
+            let c = two_z * series.c + series.a * series.b * 2.0;
+
+            let error = c.norm() * radius.powi(3);
+            if !(a.is_finite() && b.is_finite() && c.is_finite()) || error > SERIES_TOLERANCE * a.norm() * spacing {
+                break;
+            }
+            series = Self { skip: n + 1, a, b, c };
+        }
+        series
+    }
+
+    fn offset(&self, delta: Complex<f64>) -> Complex<f64> {
+        ((self.c * delta + self.b) * delta + self.a) * delta
+    }
+}
+
+/// Escape iterations for one pixel and |z|² where it stopped, walking its offset along the reference orbit
+/// `c` is the pixel's own constant, iterated directly when the reference escapes too soon to rebase onto
+fn perturbed_iterations(orbit: &[Complex<f64>], series: &Series, delta: Complex<f64>, c: Complex<f64>, julia: bool, max_iterations: u32) -> (u32, f64) {
+    let dc = if julia { Complex::new(0.0, 0.0) } else { delta };
+    let mut dz = series.offset(delta);
+    let mut m = series.skip;
+
+    for i in series.skip as u32..max_iterations {
+        let z = orbit[m] + dz;
+        if z.norm_sqr() > 4.0 {
+            return (i, z.norm_sqr());
+        }
+        // A reference that escaped at its first point has nothing after it to rebase onto
+        if orbit.len() < 2 {
+            return direct_iterations(z, c, i, max_iterations);
+        }
+        // Rebase once the offset dominates the point or the reference runs out; starting the reference over keeps the offset small
+        if z.norm_sqr() < dz.norm_sqr() || m + 1 == orbit.len() {
+            dz = z - orbit[0];
+            m = 0;
+        }
+        dz = (orbit[m] * 2.0 + dz) * dz + dc;
+        m += 1;
+    }
+
+    (max_iterations, (orbit[m] + dz).norm_sqr())
+}
+
+/// Escape iterations for a point iterated in plain f64 from `z` at iteration `start`
+fn direct_iterations(mut z: Complex<f64>, c: Complex<f64>, start: u32, max_iterations: u32) -> (u32, f64) {
+    for i in start..max_iterations {
+        if z.norm_sqr() > 4.0 {
+            return (i, z.norm_sqr());
+        }
+        z = z * z + c;
+    }
+    (max_iterations, z.norm_sqr())
+}
+
+/// Draws Mandelbrot and Julia views too deep for f64; the registry picks it past F64_ZOOM_LIMIT, where the faster renderers drop out, or when extended precision is asked for
+pub struct PerturbationRenderer;
+
+impl PerturbationRenderer {
+    pub(crate) fn create() -> Arc<dyn FractalRenderer> {
+        Arc::new(PerturbationRenderer)
+    }
+}
+
+impl FractalRenderer for PerturbationRenderer {
+    fn name(&self) -> &'static str {
+        "perturbation"
+    }
+
+    fn capabilities(&self) -> RendererCapabilities {
+        RendererCapabilities {
+            kind: RendererKind::Cpu,
+            fractal_types: vec!["mandelbrot", "julia"],
+            palettes: true,
+            smooth_coloring: true,
+            orbit_traps: false,
+            distance_estimation: false,
+            interior_coloring: false,
+            precisions: vec![Precision::Extended],
+            max_pixels: None,
+            max_zoom: None,
+            priority: 5,
+        }
+    }
+
+    fn render(&self, request: &FractalRequest, cancel: &CancelToken) -> Result<Vec<u8>> {
+        self.render_pooled(request, cancel, &BufferPool::new(0))
+    }
+
+    fn render_pooled(&self, request: &FractalRequest, cancel: &CancelToken, buffers: &BufferPool) -> Result<Vec<u8>> {
+        self.render_counted(request, cancel, buffers).map(|(data, _)| data)
+    }
+
+    // Deep zooms have no interior checks, so every point in the set is counted as iterated out
+    fn render_counted(&self, request: &FractalRequest, cancel: &CancelToken, buffers: &BufferPool) -> Result<(Vec<u8>, InteriorStats)> {
+        let julia = matches!(request.fractal_type, FractalType::Julia { .. });
+        let fixed = Fixed::for_request(request);
+        let orbit = reference_orbit(request, fixed);
+
+        let corner = pixel_offset(request, 0, 0);
+        let spacing = 4.0 / request.zoom / request.width.max(request.height) as f64;
+        let series = Series::approximate(&orbit, julia, corner.norm(), spacing);
+        debug!(
+            precision_bits = fixed.bits,
+            reference_iterations = orbit.len(),
+            skipped_iterations = series.skip,
+            "Perturbation reference ready"
+        );
+
+        let (escapes, interior) = escape_rows(request, cancel, buffers, |y, row| {
+            row.par_iter_mut()
+                .enumerate()
+                .map(|(x, escape)| {
+                    let delta = pixel_offset(request, x as u32, y);
+                    let c = match request.fractal_type {
+                        FractalType::Julia { c_real, c_imag } => Complex::new(c_real, c_imag),
+                        _ => Complex::new(request.center_x, request.center_y) + delta,
+                    };
+                    let (iterations, norm_sqr) = perturbed_iterations(&orbit, &series, delta, c, julia, request.max_iterations);
+                    *escape = escape_value(request, iterations, norm_sqr);
+                    let mut stats = InteriorStats::default();
+                    stats.count((iterations >= request.max_iterations).then_some(Interior::Exhausted), request.max_iterations);
+                    stats
+                })
+                .reduce(InteriorStats::default, InteriorStats::merge)
+        });
+        let pixels = colorize(request, &escapes, buffers);
+        buffers.give_escapes(escapes);
+        Ok((pixels, interior))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderers::CpuRenderer;
+
+    fn request(fractal_type: FractalType, center: (f64, f64), zoom: f64) -> FractalRequest {
+        FractalRequest {
+            center_x: center.0,
+            center_y: center.1,
+            zoom,
+            max_iterations: 300,
+            ..FractalRequest::new(fractal_type, 48, 32)
+        }
+    }
+
+    #[test]
+    fn test_fixed_point_round_trip() {
+        let fixed = Fixed { bits: 120 };
+        for value in [0.0, -0.75, 0.1, 1.9999999999999998, -2.0, 2f64.powi(-100)] {
+            assert_eq!(fixed.decode(&fixed.encode(value)), value);
+        }
+    }
+
+    #[test]
+    fn test_matches_f64_where_f64_is_accurate() {
+        let views = [
+            request(FractalType::Mandelbrot, (-0.745, 0.113), 2e3),
+            request(FractalType::Julia { c_real: -0.8, c_imag: 0.156 }, (0.1, 0.2), 50.0),
+        ];
+        for view in views {
+            let cpu = CpuRenderer.draw(&view, &CancelToken::new(), &BufferPool::new(0));
+            let perturbed = PerturbationRenderer.render(&view, &CancelToken::new()).unwrap();
+            assert_eq!(perturbed.len(), cpu.len());
+
+            // Rounding differs between the two walks, so a boundary pixel may land one iteration apart
+            let differing = cpu.chunks(4).zip(perturbed.chunks(4)).filter(|(a, b)| a != b).count();
+            assert!(differing * 50 < cpu.len() / 4, "{} of {} pixels differ", differing, cpu.len() / 4);
+        }
+    }
+
+    /// Escape iterations of one point computed entirely in fixed point
+    fn exact_iterations(fixed: Fixed, cr: &BigInt, ci: &BigInt, max_iterations: u32) -> u32 {
+        let (mut zr, mut zi) = (BigInt::from(0), BigInt::from(0));
+        let escape = BigInt::from(4) << (2 * fixed.bits);
+        for i in 0..max_iterations {
+            let (zr2, zi2) = (&zr * &zr, &zi * &zi);
+            if &zr2 + &zi2 > escape {
+                return i;
+            }
+            let cross = (&zr * &zi) >> (fixed.bits - 1);
+            zr = ((zr2 - zi2) >> fixed.bits) + cr;
+            zi = cross + ci;
+        }
+        max_iterations
+    }
+
+    #[test]
+    fn test_deep_zoom_matches_exact_iteration() {
+        // A point on the boundary as seen at 2000 iterations, at a zoom where neighbouring pixels sit under one f64 step apart
+        let view = FractalRequest { max_iterations: 2000, ..request(FractalType::Mandelbrot, (-0.7436438870371, 0.11596226857809663), 1e15) };
+        let fixed = Fixed::for_request(&view);
+        let orbit = reference_orbit(&view, fixed);
+        let corner = pixel_offset(&view, 0, 0);
+        let series = Series::approximate(&orbit, false, corner.norm(), 4.0 / view.zoom / view.width as f64);
+        assert!(series.skip > 0);
+
+        let mut counts = std::collections::HashSet::new();
+        for (x, y) in [(0, 0), (47, 31), (24, 16), (5, 20), (40, 3), (13, 29), (31, 9)] {
+            let delta = pixel_offset(&view, x, y);
+            let cr = fixed.encode(view.center_x) + fixed.encode(delta.re);
+            let ci = fixed.encode(view.center_y) + fixed.encode(delta.im);
+            let exact = exact_iterations(fixed, &cr, &ci, view.max_iterations);
+            let c = Complex::new(view.center_x, view.center_y) + delta;
+            let (perturbed, _) = perturbed_iterations(&orbit, &series, delta, c, false, view.max_iterations);
+            assert!(exact.abs_diff(perturbed) <= 1, "pixel ({}, {}): exact {} perturbed {}", x, y, exact, perturbed);
+            counts.insert(exact);
+        }
+        assert!(counts.len() > 1);
+    }
+
+    #[test]
+    fn test_julia_centre_past_escape_radius() {
+        // The reference escapes at its first point, while pixels at the view's far corner are still inside the radius
+        let view = FractalRequest {
+            precision: Precision::Extended,
+            ..request(FractalType::Julia { c_real: -0.8, c_imag: 0.156 }, (2.0, 2.0), 0.5)
+        };
+        assert_eq!(reference_orbit(&view, Fixed::for_request(&view)).len(), 1);
+
+        let cpu = CpuRenderer.draw(&view, &CancelToken::new(), &BufferPool::new(0));
+        let perturbed = PerturbationRenderer.render(&view, &CancelToken::new()).unwrap();
+        let differing = cpu.chunks(4).zip(perturbed.chunks(4)).filter(|(a, b)| a != b).count();
+        assert!(differing * 50 < cpu.len() / 4, "{} of {} pixels differ", differing, cpu.len() / 4);
+    }
+}
@@ -0,0 +1,599 @@
+/*
+ * Fractal renderer registry: backends describe what they can draw and FractalService picks the best one per request.
+ * I'm keeping the escape-time maths and colouring in fractal_service, so a new backend only has to decide how to schedule the pixels.
+ */
+
+use num_traits::Float;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+
+use crate::{
+    buffers::BufferPool,
+    cancel::CancelToken,
+    error::Result,
+    fractal::{
+        colorize, escape_rows, escape_value, interior_value, pixel_coordinate, pixel_value, ColoringMode, FractalRequest, FractalType,
+        InteriorColoring, InteriorStats, Precision,
+    },
+    kernels::{self, mandelbrot_bulb, Interior, CYCLE_TOLERANCE},
+    perturbation::{PerturbationRenderer, F64_ZOOM_LIMIT},
+};
+
+#[cfg(feature = "gpu")]
+use crate::gpu::GpuRenderer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RendererKind {
+    Cpu,
+    Simd,
+    Gpu,
+    External,
+}
+
+impl RendererKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RendererKind::Cpu => "cpu",
+            RendererKind::Simd => "simd",
+            RendererKind::Gpu => "gpu",
+            RendererKind::External => "external",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RendererCapabilities {
+    pub kind: RendererKind,
+    /// FractalType names this renderer can draw
+    pub fractal_types: Vec<&'static str>,
+    pub palettes: bool,
+    /// Whether the renderer reports how far past the escape radius each orbit landed, which smooth colouring needs
+    pub smooth_coloring: bool,
+    /// Whether the renderer can colour by orbit trap, which needs the whole orbit rather than just where it escaped
+    pub orbit_traps: bool,
+    /// Whether the renderer can track each orbit's derivative for distance-estimation colouring
+    pub distance_estimation: bool,
+    /// Whether the renderer knows the period of interior points it skips, which interior colouring needs
+    pub interior_coloring: bool,
+    /// Precisions the renderer iterates in, the one auto requests get first
+    pub precisions: Vec<Precision>,
+    pub max_pixels: Option<u64>,
+    /// Deepest zoom this renderer stays accurate at
+    pub max_zoom: Option<f64>,
+    /// Higher wins when several available renderers support a request
+    pub priority: i32,
+}
+
+impl RendererCapabilities {
+    pub fn supports(&self, request: &FractalRequest) -> bool {
+        self.fractal_types.contains(&request.fractal_type.name())
+            && (self.palettes || request.palette.is_none())
+            && (self.smooth_coloring || request.coloring_mode != ColoringMode::Smooth)
+            && (self.orbit_traps || request.orbit_trap.is_none())
+            && (self.distance_estimation || !request.distance_estimated())
+            && (self.interior_coloring || request.interior_coloring == InteriorColoring::Black)
+            && (request.precision == Precision::Auto || self.precisions.contains(&request.precision))
+            && request.precision.max_zoom().is_none_or(|max| request.zoom <= max)
+            && self.max_pixels.is_none_or(|max| request.width as u64 * request.height as u64 <= max)
+            && self.max_zoom.is_none_or(|max| request.zoom <= max)
+    }
+
+    /// The precision a request is iterated in here: its own when this renderer has it, otherwise the renderer's first
+    pub fn precision_for(&self, request: &FractalRequest) -> Precision {
+        if self.precisions.contains(&request.precision) {
+            request.precision
+        } else {
+            self.precisions.first().copied().unwrap_or(Precision::F64)
+        }
+    }
+}
+
+pub trait FractalRenderer: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn capabilities(&self) -> RendererCapabilities;
+
+    /// Checked on every selection, so a backend can drop out at runtime (a lost device, an unreachable worker)
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    /// RGBA pixels, row-major; FractalService redraws the request on the CPU renderer when this fails.
+    /// Rows started after `cancel` fires may be left blank, since FractalService discards a cancelled render's pixels
+    fn render(&self, request: &FractalRequest, cancel: &CancelToken) -> Result<Vec<u8>>;
+
+    /// Render drawing scratch and pixel buffers from `buffers`, which FractalService shares across renders;
+    /// a renderer that allocates its own can leave this to `render`
+    fn render_pooled(&self, request: &FractalRequest, cancel: &CancelToken, _buffers: &BufferPool) -> Result<Vec<u8>> {
+        self.render(request, cancel)
+    }
+
+    /// `render_pooled`, also counting the points found inside the set; a renderer that doesn't count reports none
+    fn render_counted(&self, request: &FractalRequest, cancel: &CancelToken, buffers: &BufferPool) -> Result<(Vec<u8>, InteriorStats)> {
+        self.render_pooled(request, cancel, buffers).map(|data| (data, InteriorStats::default()))
+    }
+}
+
+/// Constructors for the renderers built into this binary; a backend registers itself by adding its constructor here
+const BUILTIN_RENDERERS: &[fn() -> Arc<dyn FractalRenderer>] = &[
+    CpuRenderer::create,
+    SimdRenderer::create,
+    PerturbationRenderer::create,
+    #[cfg(feature = "gpu")]
+    GpuRenderer::create,
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RendererInfo {
+    pub name: &'static str,
+    pub available: bool,
+    pub capabilities: RendererCapabilities,
+}
+
+#[derive(Clone)]
+pub struct RendererRegistry {
+    renderers: Arc<RwLock<Vec<Arc<dyn FractalRenderer>>>>,
+}
+
+impl std::fmt::Debug for RendererRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.list().iter().map(|info| info.name)).finish()
+    }
+}
+
+impl Default for RendererRegistry {
+    fn default() -> Self {
+        let registry = Self { renderers: Arc::new(RwLock::new(Vec::new())) };
+        for create in BUILTIN_RENDERERS {
+            registry.register(create());
+        }
+        registry
+    }
+}
+
+impl RendererRegistry {
+    /// A registry holding the built-in renderers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a renderer, replacing any already registered under the same name
+    pub fn register(&self, renderer: Arc<dyn FractalRenderer>) {
+        let mut renderers = self.renderers.write().unwrap_or_else(|e| e.into_inner());
+        renderers.retain(|existing| existing.name() != renderer.name());
+        renderers.push(renderer);
+    }
+
+    /// The highest-priority available renderer that supports the request; the CPU renderer takes anything left over
+    pub fn select(&self, request: &FractalRequest) -> Arc<dyn FractalRenderer> {
+        let renderers = self.renderers.read().unwrap_or_else(|e| e.into_inner());
+        renderers
+            .iter()
+            .filter(|renderer| renderer.is_available() && renderer.capabilities().supports(request))
+            .max_by_key(|renderer| renderer.capabilities().priority)
+            .cloned()
+            .unwrap_or_else(CpuRenderer::create)
+    }
+
+    pub fn list(&self) -> Vec<RendererInfo> {
+        let renderers = self.renderers.read().unwrap_or_else(|e| e.into_inner());
+        let mut list: Vec<RendererInfo> = renderers
+            .iter()
+            .map(|renderer| RendererInfo {
+                name: renderer.name(),
+                available: renderer.is_available(),
+                capabilities: renderer.capabilities(),
+            })
+            .collect();
+        list.sort_by_key(|info| std::cmp::Reverse(info.capabilities.priority));
+        list
+    }
+}
+
+/// One pixel at a time across the rayon pool; draws everything and is the fallback for every request
+pub struct CpuRenderer;
+
+impl CpuRenderer {
+    pub(crate) fn create() -> Arc<dyn FractalRenderer> {
+        Arc::new(CpuRenderer)
+    }
+
+    /// The CPU path every failed render falls back to, which can't fail itself
+    pub(crate) fn draw(&self, request: &FractalRequest, cancel: &CancelToken, buffers: &BufferPool) -> Vec<u8> {
+        self.draw_counted(request, cancel, buffers).0
+    }
+
+    pub(crate) fn draw_counted(&self, request: &FractalRequest, cancel: &CancelToken, buffers: &BufferPool) -> (Vec<u8>, InteriorStats) {
+        let (escapes, interior) = escape_rows(request, cancel, buffers, |y, row| {
+            row.par_iter_mut()
+                .enumerate()
+                .map(|(x, escape)| {
+                    let (value, interior) = pixel_value(request, pixel_coordinate(request, x as u32, y));
+                    *escape = value;
+                    let mut stats = InteriorStats::default();
+                    stats.count(interior, request.max_iterations);
+                    stats
+                })
+                .reduce(InteriorStats::default, InteriorStats::merge)
+        });
+        let pixels = colorize(request, &escapes, buffers);
+        buffers.give_escapes(escapes);
+        (pixels, interior)
+    }
+}
+
+impl FractalRenderer for CpuRenderer {
+    fn name(&self) -> &'static str {
+        "cpu"
+    }
+
+    fn capabilities(&self) -> RendererCapabilities {
+        RendererCapabilities {
+            kind: RendererKind::Cpu,
+            fractal_types: kernels::kernel_names(),
+            palettes: true,
+            smooth_coloring: true,
+            orbit_traps: true,
+            distance_estimation: true,
+            interior_coloring: true,
+            precisions: vec![Precision::F64],
+            max_pixels: None,
+            max_zoom: Some(F64_ZOOM_LIMIT),
+            priority: 0,
+        }
+    }
+
+    fn render(&self, request: &FractalRequest, cancel: &CancelToken) -> Result<Vec<u8>> {
+        self.render_pooled(request, cancel, &BufferPool::new(0))
+    }
+
+    fn render_pooled(&self, request: &FractalRequest, cancel: &CancelToken, buffers: &BufferPool) -> Result<Vec<u8>> {
+        Ok(self.draw(request, cancel, buffers))
+    }
+
+    fn render_counted(&self, request: &FractalRequest, cancel: &CancelToken, buffers: &BufferPool) -> Result<(Vec<u8>, InteriorStats)> {
+        Ok(self.draw_counted(request, cancel, buffers))
+    }
+}
+
+const LANES: usize = 4;
+
+/// Iterates LANES neighbouring pixels in lockstep with branch-free updates, so the inner loop vectorises
+pub struct SimdRenderer;
+
+impl SimdRenderer {
+    fn create() -> Arc<dyn FractalRenderer> {
+        Arc::new(SimdRenderer)
+    }
+}
+
+impl FractalRenderer for SimdRenderer {
+    fn name(&self) -> &'static str {
+        "simd"
+    }
+
+    fn capabilities(&self) -> RendererCapabilities {
+        RendererCapabilities {
+            kind: RendererKind::Simd,
+            fractal_types: vec!["mandelbrot", "julia", "burning_ship", "tricorn"],
+            palettes: true,
+            smooth_coloring: true,
+            orbit_traps: false,
+            distance_estimation: false,
+            interior_coloring: true,
+            precisions: vec![Precision::F64, Precision::F32],
+            max_pixels: None,
+            max_zoom: Some(F64_ZOOM_LIMIT),
+            priority: 10,
+        }
+    }
+
+    fn render(&self, request: &FractalRequest, cancel: &CancelToken) -> Result<Vec<u8>> {
+        self.render_pooled(request, cancel, &BufferPool::new(0))
+    }
+
+    fn render_pooled(&self, request: &FractalRequest, cancel: &CancelToken, buffers: &BufferPool) -> Result<Vec<u8>> {
+        self.render_counted(request, cancel, buffers).map(|(data, _)| data)
+    }
+
+    fn render_counted(&self, request: &FractalRequest, cancel: &CancelToken, buffers: &BufferPool) -> Result<(Vec<u8>, InteriorStats)> {
+        // The Mandelbrot kernel's interior checks run per lane too, so lanes land exactly where the scalar kernel does
+        let interior_checks = matches!(request.fractal_type, FractalType::Mandelbrot);
+        let (escapes, interior) = escape_rows(request, cancel, buffers, |y, row| {
+            let mut stats = InteriorStats::default();
+            for x0 in (0..request.width).step_by(LANES) {
+                let mut z = [(0.0, 0.0); LANES];
+                let mut c = [(0.0, 0.0); LANES];
+                for lane in 0..LANES {
+                    // Lanes past the row's end repeat its last pixel and are dropped below
+                    let point = pixel_coordinate(request, (x0 + lane as u32).min(request.width - 1), y);
+                    match request.fractal_type {
+                        FractalType::Julia { c_real, c_imag } => {
+                            z[lane] = (point.re, point.im);
+                            c[lane] = (c_real, c_imag);
+                        }
+                        _ => c[lane] = (point.re, point.im),
+                    }
+                }
+
+                let cross = CrossTerm::of(&request.fractal_type);
+                let (counts, norms, interiors) = if request.precision == Precision::F32 {
+                    let narrow = |(re, im): (f64, f64)| (re as f32, im as f32);
+                    escape_lanes(z.map(narrow), c.map(narrow), cross, request.max_iterations, interior_checks)
+                } else {
+                    escape_lanes(z, c, cross, request.max_iterations, interior_checks)
+                };
+                let visible = LANES.min((request.width - x0) as usize);
+                for (lane, escape) in row[x0 as usize..x0 as usize + visible].iter_mut().enumerate() {
+                    *escape = interior_value(request, escape_value(request, counts[lane], norms[lane]), interiors[lane]);
+                    stats.count(interiors[lane], request.max_iterations);
+                }
+            }
+            stats
+        });
+        let pixels = colorize(request, &escapes, buffers);
+        buffers.give_escapes(escapes);
+        Ok((pixels, interior))
+    }
+}
+
+/// How a quadratic variant changes the 2ab term of z squared; the real part is a² - b² for all of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CrossTerm {
+    Plain,
+    /// Burning Ship: |2ab| is exactly what squaring (|a|, |b|) gives
+    Absolute,
+    /// Tricorn: -2ab is exactly what squaring the conjugate gives
+    Negated,
+}
+
+impl CrossTerm {
+    fn of(fractal_type: &FractalType) -> Self {
+        match fractal_type {
+            FractalType::BurningShip => CrossTerm::Absolute,
+            FractalType::Tricorn => CrossTerm::Negated,
+            _ => CrossTerm::Plain,
+        }
+    }
+}
+
+/// Same arithmetic and escape test as the scalar kernel, so every f64 lane lands on the scalar iteration count and |z|²;
+/// f32 lanes run the same steps in single precision. With `interior_checks`, lanes skip the cardioid and bulb and stop on a cycle
+/// at the same steps the Mandelbrot kernel does
+fn escape_lanes<T: Float>(
+    z: [(T, T); LANES],
+    c: [(T, T); LANES],
+    cross: CrossTerm,
+    max_iterations: u32,
+    interior_checks: bool,
+) -> ([u32; LANES], [f64; LANES], [Option<Interior>; LANES]) {
+    let radius_sqr = T::from(4.0).unwrap_or_else(T::max_value);
+    let tolerance = T::from(CYCLE_TOLERANCE).unwrap_or_else(T::zero);
+    let (mut zr, mut zi) = (z.map(|p| p.0), z.map(|p| p.1));
+    let (cr, ci) = (c.map(|p| p.0), c.map(|p| p.1));
+    let mut active = [true; LANES];
+    let mut counts = [0u32; LANES];
+    let mut norms = [T::zero(); LANES];
+    let mut interiors = [None; LANES];
+
+    if interior_checks {
+        for lane in 0..LANES {
+            let point = num_complex::Complex::new(cr[lane].to_f64().unwrap_or(0.0), ci[lane].to_f64().unwrap_or(0.0));
+            interiors[lane] = mandelbrot_bulb(point);
+            active[lane] = interiors[lane].is_none();
+        }
+    }
+    let (mut saved_r, mut saved_i, mut saved_at, mut save_at) = (zr, zi, 0u32, 1u32);
+
+    for step in 0..max_iterations {
+        for lane in 0..LANES {
+            // Escape is sticky: with |c| > 2 an escaped orbit can dip back inside the radius
+            let norm_sqr = zr[lane] * zr[lane] + zi[lane] * zi[lane];
+            // Only the step a lane escapes on records its norm; later steps keep running on a lane that's already out
+            norms[lane] = if active[lane] { norm_sqr } else { norms[lane] };
+            active[lane] &= norm_sqr <= radius_sqr;
+            counts[lane] += active[lane] as u32;
+        }
+        if !active.iter().any(|&a| a) {
+            break;
+        }
+        for lane in 0..LANES {
+            let re = zr[lane] * zr[lane] - zi[lane] * zi[lane];
+            let im = zr[lane] * zi[lane] + zi[lane] * zr[lane];
+            let im = match cross {
+                CrossTerm::Plain => im,
+                CrossTerm::Absolute => im.abs(),
+                CrossTerm::Negated => -im,
+            };
+            zr[lane] = re + cr[lane];
+            zi[lane] = im + ci[lane];
+        }
+        if interior_checks {
+            for lane in 0..LANES {
+                let (dr, di) = (zr[lane] - saved_r[lane], zi[lane] - saved_i[lane]);
+                if active[lane] && dr * dr + di * di < tolerance {
+                    interiors[lane] = Some(Interior::Cycle { period: step + 1 - saved_at, at: step + 1 });
+                    active[lane] = false;
+                }
+            }
+            if step + 1 == save_at {
+                (saved_r, saved_i, saved_at, save_at) = (zr, zi, save_at, save_at.saturating_mul(2));
+            }
+        }
+    }
+
+    for lane in 0..LANES {
+        if interiors[lane].is_some() {
+            counts[lane] = max_iterations;
+        } else if counts[lane] >= max_iterations {
+            interiors[lane] = Some(Interior::Exhausted);
+        }
+    }
+    (counts, norms.map(|norm| norm.to_f64().unwrap_or(f64::INFINITY)), interiors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(fractal_type: FractalType, width: u32) -> FractalRequest {
+        FractalRequest {
+            center_x: -0.5,
+            center_y: 0.1,
+            zoom: 1.3,
+            max_iterations: 120,
+            ..FractalRequest::new(fractal_type, width, 9)
+        }
+    }
+
+    #[test]
+    fn test_simd_renderer_matches_cpu_renderer() {
+        for fractal_type in [
+            FractalType::Mandelbrot,
+            FractalType::Julia { c_real: -0.8, c_imag: 0.156 },
+            FractalType::BurningShip,
+            FractalType::Tricorn,
+        ] {
+            // 13 is not a multiple of the lane count, so the padded tail is exercised too
+            let request = request(fractal_type, 13);
+            let cpu = CpuRenderer.draw(&request, &CancelToken::new(), &BufferPool::new(0));
+            assert_eq!(cpu.len(), 13 * 9 * 4);
+            assert_eq!(SimdRenderer.render(&request, &CancelToken::new()).unwrap(), cpu);
+
+            for coloring_mode in [ColoringMode::Smooth, ColoringMode::Histogram] {
+                let request = FractalRequest { coloring_mode, ..request.clone() };
+                assert_eq!(SimdRenderer.render(&request, &CancelToken::new()).unwrap(), CpuRenderer.draw(&request, &CancelToken::new(), &BufferPool::new(0)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_requested_precision_picks_the_renderer_and_is_reported() {
+        let service = crate::FractalService::new();
+        let view = request(FractalType::Mandelbrot, 16);
+        let at = |precision, view: &FractalRequest| service.render(FractalRequest { precision, ..view.clone() });
+
+        let double = at(Precision::F64, &view);
+        assert_eq!((double.renderer, double.precision), ("simd", Precision::F64));
+        let single = at(Precision::F32, &view);
+        assert_eq!(single.precision, Precision::F32);
+        // A shallow view barely notices the narrower floats
+        let differing = single.data.chunks(4).zip(double.data.chunks(4)).filter(|(a, b)| a != b).count();
+        assert!(differing * 10 < 16 * 9, "{} pixels differ", differing);
+
+        let extended = at(Precision::Extended, &view);
+        assert_eq!((extended.renderer, extended.precision), ("perturbation", Precision::Extended));
+
+        // f32 can't place pixels this deep and nothing draws a tricorn in extended precision, so both settle for f64
+        let deep = FractalRequest { zoom: 1e6, ..view.clone() };
+        assert_eq!(at(Precision::F32, &deep).precision, Precision::F64);
+        let tricorn = FractalRequest { fractal_type: FractalType::Tricorn, ..view };
+        assert_eq!(at(Precision::Extended, &tricorn).precision, Precision::F64);
+    }
+
+    struct Limited;
+
+    impl FractalRenderer for Limited {
+        fn name(&self) -> &'static str {
+            "limited"
+        }
+
+        fn capabilities(&self) -> RendererCapabilities {
+            RendererCapabilities {
+                kind: RendererKind::External,
+                fractal_types: vec!["julia"],
+                palettes: false,
+                smooth_coloring: false,
+                orbit_traps: false,
+                distance_estimation: false,
+                interior_coloring: false,
+                precisions: vec![Precision::F64],
+                max_pixels: Some(64 * 64),
+                max_zoom: None,
+                priority: 100,
+            }
+        }
+
+        fn render(&self, _request: &FractalRequest, _cancel: &CancelToken) -> Result<Vec<u8>> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[test]
+    fn test_selects_highest_priority_supporting_renderer() {
+        let registry = RendererRegistry::new();
+        #[cfg(feature = "gpu")]
+        let fastest = if GpuRenderer.is_available() { "gpu" } else { "simd" };
+        #[cfg(not(feature = "gpu"))]
+        let fastest = "simd";
+        assert_eq!(registry.select(&request(FractalType::Mandelbrot, 16)).name(), fastest);
+        assert_eq!(registry.select(&request(FractalType::Multibrot { power: 3.5 }, 16)).name(), "cpu");
+
+        registry.register(Arc::new(Limited));
+        let julia = FractalType::Julia { c_real: 0.0, c_imag: 0.0 };
+        assert_eq!(registry.select(&request(julia.clone(), 16)).name(), "limited");
+        assert_eq!(registry.select(&request(julia.clone(), 1024)).name(), fastest);
+        let smooth = FractalRequest { coloring_mode: ColoringMode::Smooth, ..request(julia, 16) };
+        assert_eq!(registry.select(&smooth).name(), "simd");
+        let trapped = FractalRequest { orbit_trap: Some(crate::fractal::OrbitTrap::Point { x: 0.0, y: 0.0 }), ..request(FractalType::Mandelbrot, 16) };
+        assert_eq!(registry.select(&trapped).name(), "cpu");
+        assert_eq!(registry.select(&request(FractalType::Mandelbrot, 16)).name(), fastest);
+
+        // The GPU renderer is listed whenever the feature is on, even without an adapter to select it
+        let names: Vec<_> = registry.list().into_iter().map(|info| info.name).filter(|name| *name != "gpu").collect();
+        assert_eq!(names, vec!["limited", "simd", "perturbation", "cpu"]);
+    }
+
+    struct Failing;
+
+    impl FractalRenderer for Failing {
+        fn name(&self) -> &'static str {
+            "failing"
+        }
+
+        fn capabilities(&self) -> RendererCapabilities {
+            RendererCapabilities {
+                kind: RendererKind::Gpu,
+                fractal_types: vec!["mandelbrot"],
+                palettes: true,
+                smooth_coloring: true,
+                orbit_traps: false,
+                distance_estimation: false,
+                interior_coloring: false,
+                precisions: vec![Precision::F64],
+                max_pixels: None,
+                max_zoom: None,
+                priority: 100,
+            }
+        }
+
+        fn render(&self, _request: &FractalRequest, _cancel: &CancelToken) -> Result<Vec<u8>> {
+            Err(crate::CoreError::RenderError("device lost".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_failed_render_falls_back_to_cpu() {
+        let service = crate::FractalService::new();
+        service.renderers().register(Arc::new(Failing));
+
+        let request = request(FractalType::Mandelbrot, 16);
+        let response = service.render(request.clone());
+        assert_eq!(response.renderer, "cpu");
+        assert_eq!(response.compute_backend, RendererKind::Cpu);
+        assert_eq!(response.data, CpuRenderer.draw(&request, &CancelToken::new(), &BufferPool::new(0)));
+    }
+
+    #[test]
+    fn test_deep_zooms_switch_to_perturbation() {
+        let registry = RendererRegistry::new();
+        let deep = |fractal_type| FractalRequest { zoom: 1e15, ..request(fractal_type, 16) };
+        assert_eq!(registry.select(&deep(FractalType::Mandelbrot)).name(), "perturbation");
+        assert_eq!(registry.select(&deep(FractalType::Julia { c_real: -0.8, c_imag: 0.156 })).name(), "perturbation");
+        // Nothing draws a deep Burning Ship accurately, so it falls back to the CPU renderer as before
+        assert_eq!(registry.select(&deep(FractalType::BurningShip)).name(), "cpu");
+        assert_eq!(registry.select(&FractalRequest { zoom: F64_ZOOM_LIMIT, ..request(FractalType::Mandelbrot, 16) }).name(), "simd");
+    }
+}
@@ -0,0 +1,161 @@
+/*
+ * Strategy comparison: one view drawn each way this crate can compute it, timed side by side.
+ * I'm taking the fastest of several runs per strategy and drawing each into fresh buffers, so a noisy neighbour or a warm pool doesn't decide the ranking.
+ */
+
+use serde::Serialize;
+use std::{sync::Arc, time::Instant};
+
+use crate::{
+    cancel::CancelToken,
+    fractal::FractalRequest,
+    renderers::{CpuRenderer, FractalRenderer, SimdRenderer},
+    threads::RenderPools,
+};
+
+#[cfg(feature = "gpu")]
+use crate::gpu::GpuRenderer;
+
+/// A way of computing a view, from one core up to the GPU
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    /// The per-pixel CPU kernel on a single thread
+    Scalar,
+    /// The same kernel across the whole render pool
+    Rayon,
+    /// Lanes of neighbouring pixels iterated in lockstep, across the whole render pool
+    Simd,
+    /// A compute shader, when built with the gpu feature and an adapter is present
+    Gpu,
+}
+
+/// Every strategy, in the order comparisons list them; scalar comes first since speedups are measured against it
+pub const STRATEGIES: [Strategy; 4] = [Strategy::Scalar, Strategy::Rayon, Strategy::Simd, Strategy::Gpu];
+
+impl Strategy {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Strategy::Scalar => "scalar",
+            Strategy::Rayon => "rayon",
+            Strategy::Simd => "simd",
+            Strategy::Gpu => "gpu",
+        }
+    }
+
+    /// The renderer behind this strategy and the threads it gets, or None when it can't draw `request` here
+    fn renderer(&self, pools: &RenderPools, request: &FractalRequest) -> Option<(Arc<dyn FractalRenderer>, usize)> {
+        let (renderer, threads): (Arc<dyn FractalRenderer>, usize) = match self {
+            Strategy::Scalar => (Arc::new(CpuRenderer), 1),
+            Strategy::Rayon => (Arc::new(CpuRenderer), pools.size()),
+            Strategy::Simd => (Arc::new(SimdRenderer), pools.size()),
+            #[cfg(feature = "gpu")]
+            Strategy::Gpu => (GpuRenderer::create(), pools.size()),
+            #[cfg(not(feature = "gpu"))]
+            Strategy::Gpu => return None,
+        };
+        (renderer.is_available() && renderer.capabilities().supports(request)).then_some((renderer, threads))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StrategyTiming {
+    pub strategy: Strategy,
+    pub renderer: &'static str,
+    pub threads: usize,
+    /// Fastest of the runs
+    pub computation_time_ms: f64,
+    pub pixels_per_second: f64,
+    /// Scalar time over this strategy's, so scalar itself is 1
+    pub speedup: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StrategyComparison {
+    pub width: u32,
+    pub height: u32,
+    pub max_iterations: u32,
+    pub runs: u32,
+    pub timings: Vec<StrategyTiming>,
+    /// Strategies that couldn't draw the view here, such as the GPU without an adapter, or that failed when they tried
+    pub unavailable: Vec<Strategy>,
+}
+
+/// Draw `request` `runs` times with every strategy on `pools`, best called under the whole pool's reservation so nothing else skews the timings
+pub fn compare_strategies(pools: &RenderPools, request: &FractalRequest, runs: u32) -> StrategyComparison {
+    let runs = runs.max(1);
+    let pixels = f64::from(request.width) * f64::from(request.height);
+    let mut timings: Vec<StrategyTiming> = Vec::new();
+    let mut unavailable = Vec::new();
+
+    for strategy in STRATEGIES {
+        let Some((renderer, threads)) = strategy.renderer(pools, request) else {
+            unavailable.push(strategy);
+            continue;
+        };
+        let fastest = (0..runs)
+            .map(|_| {
+                let start_time = Instant::now();
+                let (result, threads) = pools.install(threads, || renderer.render(request, &CancelToken::new()));
+                result.map(|_| (start_time.elapsed().as_secs_f64() * 1000.0, threads))
+            })
+            .collect::<crate::error::Result<Vec<_>>>()
+            .ok()
+            .and_then(|times| times.into_iter().min_by(|a, b| a.0.total_cmp(&b.0)));
+        let Some((computation_time_ms, threads)) = fastest else {
+            unavailable.push(strategy);
+            continue;
+        };
+
+        let baseline_ms = timings.first().map_or(computation_time_ms, |scalar| scalar.computation_time_ms);
+        timings.push(StrategyTiming {
+            strategy,
+            renderer: renderer.name(),
+            threads,
+            computation_time_ms,
+            pixels_per_second: pixels / (computation_time_ms / 1000.0).max(f64::EPSILON),
+            speedup: baseline_ms / computation_time_ms.max(f64::EPSILON),
+        });
+    }
+
+    StrategyComparison {
+        width: request.width,
+        height: request.height,
+        max_iterations: request.max_iterations,
+        runs,
+        timings,
+        unavailable,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fractal::FractalType;
+
+    #[test]
+    fn test_strategies_are_timed_against_scalar() {
+        let request = FractalRequest {
+            center_x: -0.5,
+            center_y: 0.0,
+            zoom: 1.0,
+            max_iterations: 60,
+            ..FractalRequest::new(FractalType::Mandelbrot, 64, 48)
+        };
+        let pools = RenderPools::new(2);
+        let comparison = compare_strategies(&pools, &request, 2);
+
+        let names: Vec<&str> = comparison.timings.iter().map(|timing| timing.strategy.name()).collect();
+        assert_eq!(&names[..3], ["scalar", "rayon", "simd"]);
+        assert_eq!((comparison.timings[0].threads, comparison.timings[0].speedup), (1, 1.0));
+        assert_eq!((comparison.timings[1].renderer, comparison.timings[1].threads), ("cpu", 2));
+        assert!(comparison.timings.iter().all(|timing| timing.pixels_per_second > 0.0));
+        if cfg!(not(feature = "gpu")) {
+            assert_eq!(comparison.unavailable, [Strategy::Gpu]);
+        }
+
+        // Multibrot is beyond the SIMD kernel, so it sits out rather than drawing something else
+        let multibrot = FractalRequest { fractal_type: FractalType::Multibrot { power: 3.0 }, ..request };
+        assert!(compare_strategies(&pools, &multibrot, 1).unavailable.contains(&Strategy::Simd));
+    }
+}
@@ -0,0 +1,203 @@
+/*
+ * Thread pools for render work: FRACTAL_THREADS split between renders, each drawing on a rayon pool exactly as wide as the threads it reserved.
+ * I'm counting reservations on a semaphore holding a permit per thread, so however many renders arrive no more than FRACTAL_THREADS are ever busy at once.
+ * Async callers hand their work to a pool and await the result, which keeps the runtime's workers free while it draws.
+ */
+
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Mutex},
+};
+use tokio::sync::{oneshot, OwnedSemaphorePermit, Semaphore};
+use tracing::{warn, Span};
+
+/// The render threads, the permits they're reserved through, and the pools idle renders left behind
+#[derive(Debug)]
+pub struct RenderPools {
+    size: usize,
+    permits: Arc<Semaphore>,
+    /// Pools kept for the next render of their width, holding at most `size` threads between them
+    idle: Mutex<Vec<ThreadPool>>,
+}
+
+impl RenderPools {
+    /// `size` render threads; 0 means one per available core
+    pub fn new(size: usize) -> Self {
+        let size = match size {
+            0 => std::thread::available_parallelism().map_or(1, usize::from),
+            size => size,
+        };
+        Self { size, permits: Arc::new(Semaphore::new(size)), idle: Mutex::new(Vec::new()) }
+    }
+
+    /// Render threads in all, which is also the most any one render reserves
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Threads a render reserves for its hint: all of them without one, otherwise the hint capped at the total
+    pub fn budget(&self, requested: Option<u32>) -> usize {
+        match requested {
+            Some(threads) => (threads.max(1) as usize).min(self.size),
+            None => self.size,
+        }
+    }
+
+    /// Threads not reserved by a running or starting render
+    pub fn available(&self) -> usize {
+        self.permits.available_permits()
+    }
+
+    /// Wait for `threads` permits, then run `work` on a pool that wide without blocking the caller's runtime, returning its result and the threads it ran on
+    pub async fn run<R: Send + 'static>(&self, threads: usize, work: impl FnOnce() -> R + Send + 'static) -> (R, usize) {
+        let permit = self.reserve(threads).await;
+        let pool = self.checkout(self.reserved(threads));
+        let (sender, receiver) = oneshot::channel();
+        let span = Span::current();
+        let job = move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| span.in_scope(work)));
+            drop(permit);
+            let _ = sender.send(result);
+        };
+        match &pool {
+            Some(pool) => pool.spawn(job),
+            None => rayon::spawn(job),
+        }
+
+        let result = receiver.await;
+        let threads = pool.as_ref().map_or_else(rayon::current_num_threads, ThreadPool::current_num_threads);
+        self.checkin(pool);
+        match result {
+            Ok(Ok(result)) => (result, threads),
+            Ok(Err(payload)) => panic::resume_unwind(payload),
+            Err(_) => panic!("render pool dropped a job before running it"),
+        }
+    }
+
+    /// Run `work` on a pool `threads` wide from a thread that may block, such as a job worker
+    /// Work already on a pool runs within its caller's reservation, moving to a narrower pool only when it asks for fewer threads
+    pub fn install<R: Send>(&self, threads: usize, work: impl FnOnce() -> R + Send) -> (R, usize) {
+        let _permit = match rayon::current_thread_index() {
+            Some(_) if self.reserved(threads) >= rayon::current_num_threads() => return (work(), rayon::current_num_threads()),
+            Some(_) => None,
+            None => Some(futures::executor::block_on(self.reserve(threads))),
+        };
+        let Some(pool) = self.checkout(self.reserved(threads)) else {
+            return (work(), rayon::current_num_threads());
+        };
+        let result = (pool.install(work), pool.current_num_threads());
+        self.checkin(Some(pool));
+        result
+    }
+
+    async fn reserve(&self, threads: usize) -> OwnedSemaphorePermit {
+        Arc::clone(&self.permits)
+            .acquire_many_owned(self.reserved(threads) as u32)
+            .await
+            .expect("render permits are never closed")
+    }
+
+    fn reserved(&self, threads: usize) -> usize {
+        threads.clamp(1, self.size)
+    }
+
+    /// An idle pool `threads` wide, or a new one; None if the threads can't be started, leaving the work to rayon's global pool
+    fn checkout(&self, threads: usize) -> Option<ThreadPool> {
+        let mut idle = self.idle.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(index) = idle.iter().position(|pool| pool.current_num_threads() == threads) {
+            return Some(idle.swap_remove(index));
+        }
+        drop(idle);
+
+        match ThreadPoolBuilder::new().num_threads(threads).thread_name(|index| format!("fractal-{}", index)).build() {
+            Ok(pool) => Some(pool),
+            Err(e) => {
+                warn!("Failed to build a {}-thread render pool: {}", threads, e);
+                None
+            }
+        }
+    }
+
+    /// Keep a finished render's pool for the next one its width, dropping it instead when that would leave more than `size` threads idle
+    fn checkin(&self, pool: Option<ThreadPool>) {
+        let Some(pool) = pool else { return };
+        let mut idle = self.idle.lock().unwrap_or_else(|e| e.into_inner());
+        let kept: usize = idle.iter().map(ThreadPool::current_num_threads).sum();
+        if kept + pool.current_num_threads() <= self.size {
+            idle.push(pool);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budgets_are_reserved_from_one_pool() {
+        let pools = RenderPools::new(6);
+        assert_eq!(pools.size(), 6);
+        assert_eq!(pools.budget(None), 6);
+        assert_eq!(pools.budget(Some(64)), 6);
+        assert_eq!(pools.budget(Some(5)), 5);
+        assert_eq!(pools.budget(Some(0)), 1);
+
+        let (name, free) = pools.install(pools.budget(Some(3)), || {
+            (std::thread::current().name().map(str::to_string), pools.available())
+        }).0;
+        assert_eq!((name.as_deref().map(|name| name.starts_with("fractal-")), free), (Some(true), 3));
+        assert_eq!(pools.available(), 6);
+        assert!(RenderPools::new(0).size() >= 1);
+    }
+
+    /// Distinct threads a parallel loop inside `work`'s pool spreads over, slow enough per item that every idle thread joins in
+    fn threads_used() -> usize {
+        use rayon::prelude::*;
+        let names: std::collections::HashSet<_> = (0..64)
+            .into_par_iter()
+            .map(|_| {
+                std::thread::sleep(std::time::Duration::from_millis(2));
+                std::thread::current().id()
+            })
+            .collect();
+        names.len()
+    }
+
+    #[test]
+    fn test_renders_draw_on_only_the_threads_they_reserve() {
+        let pools = RenderPools::new(4);
+        assert_eq!(pools.install(1, threads_used), (1, 1));
+
+        let (used, threads) = pools.install(3, threads_used);
+        assert_eq!(threads, 3);
+        assert!((2..=3).contains(&used), "a 3-thread render spread over {} threads", used);
+        assert_eq!(pools.install(4, threads_used).1, 4);
+    }
+
+    #[tokio::test]
+    async fn test_run_waits_for_its_share_of_the_pool() {
+        let pools = Arc::new(RenderPools::new(2));
+        let held = Arc::clone(&pools.permits).acquire_many_owned(2).await.unwrap();
+
+        let waiting = tokio::spawn({
+            let pools = Arc::clone(&pools);
+            async move { pools.run(1, || std::thread::current().name().map(str::to_string)).await }
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+
+        drop(held);
+        let (name, threads) = waiting.await.unwrap();
+        assert!(name.is_some_and(|name| name.starts_with("fractal-")));
+        assert_eq!((threads, pools.available()), (1, 2));
+
+        let pools = Arc::new(RenderPools::new(4));
+        assert_eq!(pools.run(2, threads_used).await, (2, 2));
+
+        // A narrower render nested in a wider one's reservation gets a pool its own width rather than waiting on permits already held
+        let nested = Arc::clone(&pools);
+        let ((used, threads), outer) = pools.run(4, move || nested.install(1, threads_used)).await;
+        assert_eq!((used, threads, outer), (1, 1, 4));
+    }
+}
@@ -0,0 +1,440 @@
+/*
+ * Fractal kernels: each fractal type's per-pixel iteration together with what the API says about it, looked up through one registry.
+ * I'm keeping every escape loop behind the trait so renders, orbit traps, and distance estimates never match on the fractal type themselves.
+ */
+
+use num_complex::Complex;
+use serde::Serialize;
+
+/// Most parameters any kernel takes
+pub const MAX_KERNEL_PARAMETERS: usize = 2;
+
+/// A fractal type's parameter values, in the order its kernel's schema lists them and zero-padded past the end
+pub type KernelParams = [f64; MAX_KERNEL_PARAMETERS];
+
+/// Squared distance an orbit has to come back within of an earlier point to count as cycling; far below any escaping orbit's step
+pub const CYCLE_TOLERANCE: f64 = 1e-20;
+
+/// How a point that never escaped was found to be in the set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interior {
+    /// Iterated all max_iterations without escaping
+    Exhausted,
+    /// In the Mandelbrot set's main cardioid, known from the point alone
+    Cardioid,
+    /// In the period-2 bulb left of the cardioid, known from the point alone
+    Bulb,
+    /// The orbit came back to a point it visited `period` steps earlier, noticed on step `at`
+    Cycle { period: u32, at: u32 },
+}
+
+impl Interior {
+    /// Steps in the cycle the orbit settles into, when the shortcut that found the point knows it
+    pub fn period(&self) -> Option<u32> {
+        match *self {
+            Interior::Exhausted => None,
+            Interior::Cardioid => Some(1),
+            Interior::Bulb => Some(2),
+            Interior::Cycle { period, .. } => Some(period),
+        }
+    }
+
+    /// Iterations not run compared to iterating the point all the way to max_iterations
+    pub fn iterations_skipped(&self, max_iterations: u32) -> u32 {
+        match *self {
+            Interior::Exhausted => 0,
+            Interior::Cardioid | Interior::Bulb => max_iterations,
+            Interior::Cycle { at, .. } => max_iterations.saturating_sub(at),
+        }
+    }
+}
+
+/// Whether c is in the main cardioid or the period-2 bulb, where the Mandelbrot orbit never escapes
+pub fn mandelbrot_bulb(c: Complex<f64>) -> Option<Interior> {
+    let x = c.re - 0.25;
+    let q = x * x + c.im * c.im;
+    if q * (q + x) <= 0.25 * c.im * c.im {
+        Some(Interior::Cardioid)
+    } else if (c.re + 1.0) * (c.re + 1.0) + c.im * c.im <= 0.0625 {
+        Some(Interior::Bulb)
+    } else {
+        None
+    }
+}
+
+/// One parameter a fractal type takes in requests, beyond the view itself
+#[derive(Debug, Clone, Serialize)]
+pub struct KernelParameter {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub default: f64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+/// What /api/fractals/types reports for a kernel
+#[derive(Debug, Clone, Serialize)]
+pub struct KernelInfo {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub parameters: Vec<KernelParameter>,
+    pub default_center: (f64, f64),
+    /// Whether distance estimation works for this type
+    pub holomorphic: bool,
+}
+
+pub trait FractalKernel: Send + Sync {
+    /// The FractalType name requests use
+    fn name(&self) -> &'static str;
+
+    fn description(&self) -> &'static str;
+
+    fn parameters(&self) -> Vec<KernelParameter> {
+        Vec::new()
+    }
+
+    /// Where the whole set sits in view at zoom 1
+    fn default_center(&self) -> (f64, f64) {
+        (0.0, 0.0)
+    }
+
+    /// Power z is raised to each step, which sets how quickly an escaped orbit runs away
+    fn degree(&self, _params: &KernelParams) -> f64 {
+        2.0
+    }
+
+    /// Work one step takes relative to the Mandelbrot's z² + c, for estimating what a render will cost
+    fn cost_factor(&self, _params: &KernelParams) -> f64 {
+        1.0
+    }
+
+    /// Whether each step is complex-differentiable, which distance estimation needs
+    fn is_holomorphic(&self) -> bool {
+        true
+    }
+
+    /// Whether the pixel's point starts the orbit, as for Julia sets, rather than being added at every step
+    fn seeds_orbit(&self) -> bool {
+        false
+    }
+
+    /// The orbit's first z and the constant added each step, for a pixel's point
+    fn seed(&self, point: Complex<f64>, _params: &KernelParams) -> (Complex<f64>, Complex<f64>) {
+        (Complex::new(0.0, 0.0), point)
+    }
+
+    /// One iteration
+    fn step(&self, z: Complex<f64>, c: Complex<f64>, params: &KernelParams) -> Complex<f64>;
+
+    /// The step's derivative in z, which distance estimation carries along the orbit; only meaningful for holomorphic kernels
+    fn derivative(&self, z: Complex<f64>, _params: &KernelParams) -> Complex<f64> {
+        2.0 * z
+    }
+
+    /// Iterations before the orbit of a pixel's point escapes, capped at max_iterations, with |z|² at the step it stopped on
+    fn escape(&self, point: Complex<f64>, params: &KernelParams, max_iterations: u32) -> (u32, f64) {
+        let (mut z, c) = self.seed(point, params);
+        for i in 0..max_iterations {
+            let norm_sqr = z.norm_sqr();
+            if norm_sqr > 4.0 {
+                return (i, norm_sqr);
+            }
+            z = self.step(z, c, params);
+        }
+        (max_iterations, z.norm_sqr())
+    }
+
+    /// `escape`, also saying how a point that never escaped was found to be inside; kernels without shortcuts iterate it out
+    fn escape_interior(&self, point: Complex<f64>, params: &KernelParams, max_iterations: u32) -> (u32, f64, Option<Interior>) {
+        let (iterations, norm_sqr) = self.escape(point, params, max_iterations);
+        (iterations, norm_sqr, (iterations >= max_iterations).then_some(Interior::Exhausted))
+    }
+
+    fn info(&self) -> KernelInfo {
+        KernelInfo {
+            name: self.name(),
+            description: self.description(),
+            parameters: self.parameters(),
+            default_center: self.default_center(),
+            holomorphic: self.is_holomorphic(),
+        }
+    }
+}
+
+/// Kernels built into this crate; a new fractal type registers by adding its kernel here and a FractalType variant that names it
+const BUILTIN_KERNELS: &[&dyn FractalKernel] = &[&MandelbrotKernel, &JuliaKernel, &BurningShipKernel, &TricornKernel, &MultibrotKernel];
+
+/// Every registered kernel, in the order the API lists them
+pub fn kernels() -> &'static [&'static dyn FractalKernel] {
+    BUILTIN_KERNELS
+}
+
+pub fn kernel(name: &str) -> Option<&'static dyn FractalKernel> {
+    BUILTIN_KERNELS.iter().copied().find(|kernel| kernel.name() == name)
+}
+
+/// Names of every registered kernel
+pub fn kernel_names() -> Vec<&'static str> {
+    BUILTIN_KERNELS.iter().map(|kernel| kernel.name()).collect()
+}
+
+// Core Mandelbrot iteration calculation - this is where Rust's speed really shows
+pub struct MandelbrotKernel;
+
+impl FractalKernel for MandelbrotKernel {
+    fn name(&self) -> &'static str {
+        "mandelbrot"
+    }
+
+    fn description(&self) -> &'static str {
+        "z² + c from z = 0, with c the pixel's point"
+    }
+
+    fn default_center(&self) -> (f64, f64) {
+        (-0.5, 0.0)
+    }
+
+    fn step(&self, z: Complex<f64>, c: Complex<f64>, _params: &KernelParams) -> Complex<f64> {
+        z * z + c
+    }
+
+    fn escape(&self, c: Complex<f64>, params: &KernelParams, max_iterations: u32) -> (u32, f64) {
+        let (iterations, norm_sqr, _) = self.escape_interior(c, params, max_iterations);
+        (iterations, norm_sqr)
+    }
+
+    // Points in the cardioid or bulb skip iterating entirely, and an orbit that comes back to where it was stops there,
+    // checked against a point saved at doubling intervals so cycles of any length are caught
+    fn escape_interior(&self, c: Complex<f64>, _params: &KernelParams, max_iterations: u32) -> (u32, f64, Option<Interior>) {
+        if let Some(interior) = mandelbrot_bulb(c) {
+            return (max_iterations, 0.0, Some(interior));
+        }
+
+        let mut z: Complex<f64> = Complex::new(0.0, 0.0);
+        let (mut saved, mut saved_at, mut save_at) = (z, 0, 1);
+        for i in 0..max_iterations {
+            let norm_sqr = z.norm_sqr();
+            if norm_sqr > 4.0 {
+                return (i, norm_sqr, None);
+            }
+            z = z * z + c;
+            if (z - saved).norm_sqr() < CYCLE_TOLERANCE {
+                return (max_iterations, z.norm_sqr(), Some(Interior::Cycle { period: i + 1 - saved_at, at: i + 1 }));
+            }
+            if i + 1 == save_at {
+                (saved, saved_at, save_at) = (z, save_at, save_at.saturating_mul(2));
+            }
+        }
+
+        (max_iterations, z.norm_sqr(), Some(Interior::Exhausted))
+    }
+}
+
+// Julia set iteration calculation
+pub struct JuliaKernel;
+
+impl FractalKernel for JuliaKernel {
+    fn name(&self) -> &'static str {
+        "julia"
+    }
+
+    fn description(&self) -> &'static str {
+        "z² + c for a fixed c, starting from the pixel's point"
+    }
+
+    fn parameters(&self) -> Vec<KernelParameter> {
+        vec![
+            KernelParameter { name: "c_real", description: "Real part of the constant", default: -0.7, min: Some(-2.0), max: Some(2.0) },
+            KernelParameter { name: "c_imag", description: "Imaginary part of the constant", default: 0.27015, min: Some(-2.0), max: Some(2.0) },
+        ]
+    }
+
+    fn seeds_orbit(&self) -> bool {
+        true
+    }
+
+    fn seed(&self, point: Complex<f64>, params: &KernelParams) -> (Complex<f64>, Complex<f64>) {
+        (point, Complex::new(params[0], params[1]))
+    }
+
+    fn step(&self, z: Complex<f64>, c: Complex<f64>, _params: &KernelParams) -> Complex<f64> {
+        z * z + c
+    }
+}
+
+// Burning Ship iteration: folding z into the first quadrant before squaring gives the flame-like hull
+pub struct BurningShipKernel;
+
+impl FractalKernel for BurningShipKernel {
+    fn name(&self) -> &'static str {
+        "burning_ship"
+    }
+
+    fn description(&self) -> &'static str {
+        "Mandelbrot iteration on the absolute values of z's parts"
+    }
+
+    fn default_center(&self) -> (f64, f64) {
+        (-0.4, -0.5)
+    }
+
+    fn is_holomorphic(&self) -> bool {
+        false
+    }
+
+    fn step(&self, z: Complex<f64>, c: Complex<f64>, _params: &KernelParams) -> Complex<f64> {
+        let folded = Complex::new(z.re.abs(), z.im.abs());
+        folded * folded + c
+    }
+}
+
+// Tricorn iteration: squaring the conjugate mirrors each step, giving three-fold symmetry
+pub struct TricornKernel;
+
+impl FractalKernel for TricornKernel {
+    fn name(&self) -> &'static str {
+        "tricorn"
+    }
+
+    fn description(&self) -> &'static str {
+        "Mandelbrot iteration on the conjugate of z, also called the Mandelbar set"
+    }
+
+    fn default_center(&self) -> (f64, f64) {
+        (-0.4, 0.0)
+    }
+
+    fn is_holomorphic(&self) -> bool {
+        false
+    }
+
+    fn step(&self, z: Complex<f64>, c: Complex<f64>, _params: &KernelParams) -> Complex<f64> {
+        let conjugate = z.conj();
+        conjugate * conjugate + c
+    }
+}
+
+// Multibrot iteration: whole powers go through repeated multiplication, anything else through the polar form
+pub struct MultibrotKernel;
+
+impl FractalKernel for MultibrotKernel {
+    fn name(&self) -> &'static str {
+        "multibrot"
+    }
+
+    fn description(&self) -> &'static str {
+        "z^power + c; non-integer powers use the principal branch"
+    }
+
+    fn parameters(&self) -> Vec<KernelParameter> {
+        // Bounds are left to whoever serves requests, since the server configures its own
+        vec![KernelParameter { name: "power", description: "Exponent z is raised to each step", default: 3.0, min: None, max: None }]
+    }
+
+    fn degree(&self, params: &KernelParams) -> f64 {
+        params[0]
+    }
+
+    // Whole powers multiply power - 1 times; the polar form's log, exp, and trig cost about as much as eight squarings
+    fn cost_factor(&self, params: &KernelParams) -> f64 {
+        let power = params[0];
+        if power.fract() == 0.0 {
+            (power.abs() - 1.0).max(1.0)
+        } else {
+            8.0
+        }
+    }
+
+    fn step(&self, z: Complex<f64>, c: Complex<f64>, params: &KernelParams) -> Complex<f64> {
+        let power = params[0];
+        if power.fract() == 0.0 {
+            z.powi(power as i32) + c
+        } else {
+            z.powf(power) + c
+        }
+    }
+
+    fn derivative(&self, z: Complex<f64>, params: &KernelParams) -> Complex<f64> {
+        // The polar form takes a log, so the orbit's start at zero goes through powi like whole powers do
+        let power = params[0];
+        if power.fract() != 0.0 && z.norm_sqr() > 0.0 {
+            z.powf(power - 1.0) * power
+        } else {
+            z.powi(power as i32 - 1) * power
+        }
+    }
+
+    // Deciding between powi and powf once rather than every step
+    fn escape(&self, c: Complex<f64>, params: &KernelParams, max_iterations: u32) -> (u32, f64) {
+        let power = params[0];
+        let whole = (power.fract() == 0.0).then_some(power as i32);
+        let mut z: Complex<f64> = Complex::new(0.0, 0.0);
+
+        for i in 0..max_iterations {
+            let norm_sqr = z.norm_sqr();
+            if norm_sqr > 4.0 {
+                return (i, norm_sqr);
+            }
+            z = match whole {
+                Some(n) => z.powi(n),
+                None => z.powf(power),
+            } + c;
+        }
+
+        (max_iterations, z.norm_sqr())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_finds_kernels_by_name() {
+        for registered in kernels() {
+            assert_eq!(kernel(registered.name()).unwrap().name(), registered.name());
+            assert!(registered.parameters().len() <= MAX_KERNEL_PARAMETERS);
+        }
+        assert_eq!(kernel_names(), ["mandelbrot", "julia", "burning_ship", "tricorn", "multibrot"]);
+        assert!(kernel("newton").is_none());
+
+        // The multibrot's own escape loop agrees with stepping it through the trait's default
+        struct Stepped;
+        impl FractalKernel for Stepped {
+            fn name(&self) -> &'static str {
+                "stepped"
+            }
+            fn description(&self) -> &'static str {
+                ""
+            }
+            fn step(&self, z: Complex<f64>, c: Complex<f64>, params: &KernelParams) -> Complex<f64> {
+                MultibrotKernel.step(z, c, params)
+            }
+        }
+        for (re, im) in [(-0.75, 0.1), (0.3, 0.5), (0.0, 0.0), (1.5, 1.5)] {
+            let point = Complex::new(re, im);
+            assert_eq!(Stepped.escape(point, &[3.5, 0.0], 100), MultibrotKernel.escape(point, &[3.5, 0.0], 100));
+        }
+    }
+
+    #[test]
+    fn test_mandelbrot_interior_shortcuts_agree_with_iterating() {
+        let params = [0.0; MAX_KERNEL_PARAMETERS];
+        let interior = |re: f64, im: f64| MandelbrotKernel.escape_interior(Complex::new(re, im), &params, 1000).2;
+        assert_eq!(interior(0.0, 0.0), Some(Interior::Cardioid));
+        assert_eq!(interior(-1.0, 0.1), Some(Interior::Bulb));
+        // The period-3 bulb on top of the cardioid has no closed-form test, so cycle detection finds it
+        assert!(matches!(interior(-0.122, 0.745), Some(Interior::Cycle { period: 3, .. })));
+        assert_eq!(interior(0.5, 0.5), None);
+
+        // Every shortcut lands where stepping the same orbit out does, on a grid across the set
+        for y in -12..=12 {
+            for x in -20..=6 {
+                let c = Complex::new(x as f64 / 10.0, y as f64 / 10.0);
+                let stepped = MultibrotKernel.escape(c, &[2.0, 0.0], 300).0;
+                assert_eq!(MandelbrotKernel.escape(c, &params, 300).0, stepped, "{}", c);
+            }
+        }
+        assert_eq!(Interior::Cycle { period: 3, at: 40 }.iterations_skipped(1000), 960);
+    }
+}
@@ -0,0 +1,15 @@
+/*
+ * Fractal kernels on their own: each fractal type's escape loop, interior checks, and parameter schema, behind one trait and registry.
+ * I'm keeping this crate down to num-complex and serde so the CLI, WASM builds, and benchmarks can iterate points without rayon, tokio, or the server.
+ */
+
+#![doc = "Escape-time fractal kernels without the renderers or the web stack"]
+
+pub mod kernels;
+pub mod view;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use kernels::{
+    kernel, kernel_names, kernels, FractalKernel, Interior, KernelInfo, KernelParameter, KernelParams, MAX_KERNEL_PARAMETERS,
+};
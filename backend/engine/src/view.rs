@@ -0,0 +1,18 @@
+/*
+ * Where each pixel of a view lands on the complex plane, shared by the server's renderers and the browser previews.
+ * I'm keeping the mapping here, beside the kernels, so every caller that draws a view places its pixels the same way.
+ */
+
+use num_complex::Complex;
+
+/// Plane units a view spans across each axis at zoom 1
+pub const VIEW_SPAN: f64 = 4.0;
+
+/// How far pixel (`x`, `y`) of a `width`×`height` view at `zoom` is from the view centre; exact in f64 at any zoom, unlike the point itself
+pub fn pixel_offset(width: u32, height: u32, zoom: f64, x: u32, y: u32) -> Complex<f64> {
+    let scale = VIEW_SPAN / zoom;
+    Complex::new(
+        (x as f64 - width as f64 / 2.0) * scale / width as f64,
+        (y as f64 - height as f64 / 2.0) * scale / height as f64,
+    )
+}
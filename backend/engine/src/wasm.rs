@@ -0,0 +1,132 @@
+/*
+ * WebAssembly bindings: a view the browser can iterate itself, for quick previews next to the server's full renders.
+ * I'm handing back flat typed arrays rather than JS objects, so a frame crosses into JavaScript as one copy straight into ImageData.
+ */
+
+use num_complex::Complex;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    kernels::{kernel, FractalKernel, KernelParams, MAX_KERNEL_PARAMETERS},
+    view::pixel_offset,
+};
+
+/// A view of one fractal type to draw in the browser, mapped to the plane the same way the server maps its renders
+#[wasm_bindgen]
+pub struct PreviewView {
+    kernel: &'static dyn FractalKernel,
+    params: KernelParams,
+    width: u32,
+    height: u32,
+    center_x: f64,
+    center_y: f64,
+    zoom: f64,
+    max_iterations: u32,
+}
+
+#[wasm_bindgen]
+impl PreviewView {
+    /// A view of `fractal_type` (mandelbrot, julia, burning_ship, tricorn, or multibrot) with the server's defaults:
+    /// centred where the kernel's default view is, at zoom 1 and 100 iterations, with each parameter at its schema default
+    #[wasm_bindgen(constructor)]
+    pub fn new(fractal_type: &str, width: u32, height: u32) -> Result<PreviewView, JsError> {
+        let kernel = kernel(fractal_type).ok_or_else(|| JsError::new(&format!("Unknown fractal type '{}'", fractal_type)))?;
+        Ok(Self::with_kernel(kernel, width, height))
+    }
+
+    fn with_kernel(kernel: &'static dyn FractalKernel, width: u32, height: u32) -> Self {
+        let mut params = [0.0; MAX_KERNEL_PARAMETERS];
+        for (value, parameter) in params.iter_mut().zip(kernel.parameters()) {
+            *value = parameter.default;
+        }
+        let (center_x, center_y) = kernel.default_center();
+        Self {
+            kernel,
+            params,
+            width: width.max(1),
+            height: height.max(1),
+            center_x,
+            center_y,
+            zoom: 1.0,
+            max_iterations: 100,
+        }
+    }
+
+    /// Move the view; zoom 1 spans 4 units of the plane across each axis
+    #[wasm_bindgen(js_name = setView)]
+    pub fn set_view(&mut self, center_x: f64, center_y: f64, zoom: f64) {
+        self.center_x = center_x;
+        self.center_y = center_y;
+        self.zoom = zoom;
+    }
+
+    #[wasm_bindgen(js_name = setMaxIterations)]
+    pub fn set_max_iterations(&mut self, max_iterations: u32) {
+        self.max_iterations = max_iterations.max(1);
+    }
+
+    /// The kernel's parameters in its schema's order: c_real and c_imag for julia, power for multibrot
+    #[wasm_bindgen(js_name = setParams)]
+    pub fn set_params(&mut self, params: &[f64]) {
+        for (value, &param) in self.params.iter_mut().zip(params) {
+            *value = param;
+        }
+    }
+
+    /// Escape counts row-major as a Uint32Array, max_iterations for points that never escaped
+    pub fn counts(&self) -> Vec<u32> {
+        let center = Complex::new(self.center_x, self.center_y);
+        let mut counts = Vec::with_capacity(self.width as usize * self.height as usize);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let point = center + pixel_offset(self.width, self.height, self.zoom, x, y);
+                counts.push(self.kernel.escape(point, &self.params, self.max_iterations).0);
+            }
+        }
+        counts
+    }
+
+    /// Greyscale RGBA pixels, ready to wrap in a Uint8ClampedArray for ImageData; the set itself stays black
+    pub fn rgba(&self) -> Vec<u8> {
+        self.counts()
+            .into_iter()
+            .flat_map(|count| {
+                let shade = if count >= self.max_iterations { 0 } else { (255 * u64::from(count) / u64::from(self.max_iterations)) as u8 };
+                [shade, shade, shade, 255]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernels::{JuliaKernel, MandelbrotKernel};
+
+    #[test]
+    fn test_preview_pixels_follow_the_kernel() {
+        let mut view = PreviewView::with_kernel(&JuliaKernel, 8, 6);
+        view.set_view(0.1, -0.2, 2.0);
+        view.set_max_iterations(64);
+        view.set_params(&[-0.8, 0.156]);
+
+        let counts = view.counts();
+        assert_eq!(counts.len(), 48);
+        // The pixel at the centre of the view sits exactly on its centre point
+        assert_eq!(counts[3 * 8 + 4], JuliaKernel.escape(Complex::new(0.1, -0.2), &[-0.8, 0.156], 64).0);
+
+        let rgba = view.rgba();
+        assert_eq!(rgba.len(), 48 * 4);
+        for (pixel, &count) in rgba.chunks(4).zip(&counts) {
+            assert_eq!(pixel[3], 255);
+            assert_eq!(pixel[0] == 0, count >= 64 || count * 255 < 64);
+        }
+    }
+
+    #[test]
+    fn test_default_preview_matches_the_server_view() {
+        let view = PreviewView::with_kernel(&MandelbrotKernel, 8, 6);
+        assert_eq!((view.center_x, view.center_y), MandelbrotKernel.default_center());
+        assert_eq!(view.counts()[3 * 8 + 4], MandelbrotKernel.escape(Complex::new(-0.5, 0.0), &view.params, 100).0);
+    }
+}
@@ -0,0 +1,12 @@
+/*
+ * fractal-cli: offline fractal rendering to image files and the benchmark suite, with no Postgres or Redis to stand up.
+ * I'm keeping this binary to parsing and the exit code; the commands live in the library's cli::fractal module.
+ */
+
+use clap::Parser;
+use dark_performance_backend::cli::fractal::{self, FractalCli};
+
+fn main() {
+    let cli = FractalCli::parse();
+    std::process::exit(fractal::run(cli.command));
+}
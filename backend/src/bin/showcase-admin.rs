@@ -0,0 +1,15 @@
+/*
+ * showcase-admin: operator CLI for migrations, GitHub syncs, cache flushes, benchmarks, exports, and API keys.
+ * I'm keeping this binary to parsing and the exit code; the commands live in the library's cli::admin module.
+ */
+
+#![recursion_limit = "256"]
+
+use clap::Parser;
+use dark_performance_backend::cli::admin::{self, AdminCli};
+
+#[tokio::main]
+async fn main() {
+    let cli = AdminCli::parse();
+    std::process::exit(admin::run(cli.command).await);
+}
@@ -0,0 +1,91 @@
+/*
+ * Compile-time build metadata: version, commit, build time, toolchain, and cargo features.
+ * I'm reading every build.rs-provided variable here once so the rest of the crate shares one set of fallbacks.
+ */
+
+use serde::Serialize;
+
+use crate::utils::config::Environment;
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const BUILD_TIME: &str = env!("BUILD_TIME");
+pub const GIT_COMMIT: &str = env!("GIT_COMMIT");
+pub const RUST_VERSION: &str = match option_env!("BUILD_RUST_VERSION") {
+    Some(version) => version,
+    None => "unknown",
+};
+pub const PROFILE: &str = if cfg!(debug_assertions) { "debug" } else { "release" };
+pub const TARGET: &str = concat!(env!("TARGET_ARCH"), "-", env!("TARGET_OS"));
+
+/// Optional cargo features, paired with whether this binary was built with them
+const CARGO_FEATURES: &[(&str, bool)] = &[
+    ("postgres", cfg!(feature = "postgres")),
+    ("mysql", cfg!(feature = "mysql")),
+    ("sqlite", cfg!(feature = "sqlite")),
+    ("redis", cfg!(feature = "redis")),
+    ("memcached", cfg!(feature = "memcached")),
+    ("metrics", cfg!(feature = "metrics")),
+    ("tracing", cfg!(feature = "tracing")),
+    ("gpu-acceleration", cfg!(feature = "gpu-acceleration")),
+    ("machine-learning", cfg!(feature = "machine-learning")),
+    ("distributed-computing", cfg!(feature = "distributed-computing")),
+    ("advanced-auth", cfg!(feature = "advanced-auth")),
+    ("rate-limiting", cfg!(feature = "rate-limiting")),
+    ("jemalloc", cfg!(feature = "jemalloc")),
+    ("mimalloc", cfg!(feature = "mimalloc")),
+];
+
+/// Cargo features compiled into this binary
+pub fn enabled_features() -> Vec<&'static str> {
+    CARGO_FEATURES.iter().filter(|(_, enabled)| *enabled).map(|(name, _)| *name).collect()
+}
+
+/// Response body for GET /version
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    pub git_commit: &'static str,
+    pub build_time: &'static str,
+    pub rustc_version: &'static str,
+    pub profile: &'static str,
+    pub target: &'static str,
+    pub features: Vec<&'static str>,
+    pub gpu_acceleration: bool,
+    pub machine_learning: bool,
+    pub environment: Environment,
+}
+
+impl BuildInfo {
+    pub fn current(environment: Environment) -> Self {
+        Self {
+            version: VERSION,
+            git_commit: GIT_COMMIT,
+            build_time: BUILD_TIME,
+            rustc_version: RUST_VERSION,
+            profile: PROFILE,
+            target: TARGET,
+            features: enabled_features(),
+            gpu_acceleration: cfg!(feature = "gpu-acceleration"),
+            machine_learning: cfg!(feature = "machine-learning"),
+            environment,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_info_reports_default_features() {
+        let info = BuildInfo::current(Environment::Development);
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert!(!info.git_commit.is_empty());
+        assert_ne!(info.rustc_version, info.version);
+        assert!(info.features.contains(&"postgres"));
+        assert_eq!(info.gpu_acceleration, info.features.contains(&"gpu-acceleration"));
+
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(json["environment"], "Development");
+    }
+}
@@ -0,0 +1,165 @@
+/*
+ * Command-line entry points that run instead of the server, for checks deploy pipelines can script.
+ * I'm loading configuration through the exact path the server uses so a passing check means the server will accept it too.
+ */
+
+pub mod admin;
+pub mod bench;
+pub mod fractal;
+
+use clap::{Parser, Subcommand};
+use std::io::Read;
+
+use crate::utils::{
+    config::Config,
+    config_schema,
+    config_source::ConfigSource,
+    encryption::MasterKey,
+    error::{AppError, Result},
+    secrets::SecretResolver,
+};
+
+#[derive(Debug, Parser)]
+#[command(name = "dark-performance-backend", version, about = "Dark Performance Showcase backend")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Inspect configuration without starting the server
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+    /// Run the fractal and system benchmarks locally and report them as Markdown and JSON
+    Bench(bench::BenchArgs),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Load and validate configuration, exiting non-zero if it would be rejected at startup
+    Validate {
+        /// Also fetch vault:// and aws-sm:// references to prove they resolve
+        #[arg(long)]
+        resolve_secrets: bool,
+    },
+    /// Print the effective configuration as JSON with credentials masked
+    Print {
+        /// Fetch vault:// and aws-sm:// references instead of printing them as-is
+        #[arg(long)]
+        resolve_secrets: bool,
+    },
+    /// Print a JSON Schema describing every setting, its environment variable, and defaults
+    Schema,
+    /// Encrypt a value read from stdin into an enc:v1: string using the configured master key
+    Encrypt,
+    /// Print a new random master key for CONFIG_MASTER_KEY or CONFIG_MASTER_KEY_FILE
+    GenerateKey,
+}
+
+/// Run a subcommand and return the process exit code
+pub async fn run(command: Command) -> i32 {
+    let result = match command {
+        Command::Config { action: ConfigCommand::Validate { resolve_secrets } } => validate(resolve_secrets).await,
+        Command::Config { action: ConfigCommand::Print { resolve_secrets } } => print(resolve_secrets).await,
+        Command::Config { action: ConfigCommand::Schema } => schema(),
+        Command::Config { action: ConfigCommand::Encrypt } => encrypt().await,
+        Command::Config { action: ConfigCommand::GenerateKey } => MasterKey::generate().map(|key| println!("{}", key)),
+        Command::Bench(args) => tokio::task::spawn_blocking(move || bench::run(args))
+            .await
+            .unwrap_or_else(|e| Err(AppError::InternalServerError(format!("Benchmark run panicked: {}", e)))),
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            1
+        }
+    }
+}
+
+async fn load(resolve_secrets: bool) -> Result<Config> {
+    let mut config = Config::load()?;
+    if resolve_secrets {
+        SecretResolver::new().resolve_config(&mut config).await?;
+    }
+    Ok(config)
+}
+
+async fn validate(resolve_secrets: bool) -> Result<()> {
+    let config = load(resolve_secrets).await?;
+    let files = ConfigSource::layered()?.files().to_vec();
+
+    println!("Configuration is valid (environment: {:?})", config.environment);
+    if !files.is_empty() {
+        println!("Config files: {}", files.iter().map(|f| f.display().to_string()).collect::<Vec<_>>().join(", "));
+    }
+    Ok(())
+}
+
+async fn print(resolve_secrets: bool) -> Result<()> {
+    let config = load(resolve_secrets).await?;
+    println!("{}", serde_json::to_string_pretty(&config.masked_json()?)?);
+    Ok(())
+}
+
+fn schema() -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(&config_schema::config_schema()?)?);
+    Ok(())
+}
+
+/// Reads stdin rather than an argument so the plaintext never lands in shell history
+async fn encrypt() -> Result<()> {
+    let mut plaintext = String::new();
+    std::io::stdin()
+        .read_to_string(&mut plaintext)
+        .map_err(|e| AppError::BadRequestError(format!("Failed to read stdin: {}", e)))?;
+    let plaintext = plaintext.trim_end_matches(['\r', '\n']);
+    if plaintext.is_empty() {
+        return Err(AppError::ValidationError("Nothing to encrypt: pipe the value in on stdin".to_string()));
+    }
+
+    let resolver = SecretResolver::new();
+    println!("{}", resolver.master_key().await?.encrypt(plaintext)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_config_subcommands() {
+        let cli = Cli::try_parse_from(["backend", "config", "validate", "--resolve-secrets"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Config { action: ConfigCommand::Validate { resolve_secrets: true } })
+        ));
+
+        let cli = Cli::try_parse_from(["backend", "config", "print"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Config { action: ConfigCommand::Print { resolve_secrets: false } })
+        ));
+
+        let cli = Cli::try_parse_from(["backend", "config", "generate-key"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Config { action: ConfigCommand::GenerateKey })));
+
+        let cli = Cli::try_parse_from(["backend", "bench", "--suite", "fractal", "--baseline", "base.json"]).unwrap();
+        match cli.command {
+            Some(Command::Bench(args)) => {
+                assert_eq!(args.suite, bench::Suite::Fractal);
+                assert_eq!(args.samples, 3);
+                assert_eq!(args.baseline, Some(std::path::PathBuf::from("base.json")));
+            }
+            other => panic!("parsed as {:?}", other),
+        }
+        assert!(Cli::try_parse_from(["backend", "bench", "--samples", "0"]).is_err());
+
+        assert!(Cli::try_parse_from(["backend"]).unwrap().command.is_none());
+        assert!(Cli::try_parse_from(["backend", "config"]).is_err());
+    }
+}
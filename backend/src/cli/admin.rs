@@ -0,0 +1,243 @@
+/*
+ * Operator commands behind the showcase-admin binary, for managing a deployment without calling the admin API.
+ * I'm building the same AppState the server runs on, so each command goes through the services the matching endpoint uses.
+ */
+
+use chrono::{DateTime, Utc};
+use clap::{Parser, Subcommand, ValueEnum};
+use futures::StreamExt;
+use std::path::PathBuf;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::{
+    database::MigrationManager,
+    middleware::admin::key_principal,
+    models::{
+        exports::{ExportDataset, ExportFilters},
+        users, AuditAction, AuditLog,
+    },
+    routes::admin::migration_event,
+    services::{export_service::record_lines, sync_service::SyncTrigger},
+    utils::{
+        config::Config,
+        error::{AppError, Result},
+    },
+    AppState,
+};
+
+/// Actor recorded on audit events from this binary
+const CLI_ACTOR: &str = "cli";
+
+#[derive(Debug, Parser)]
+#[command(name = "showcase-admin", version, about = "Manage a Dark Performance Showcase deployment")]
+pub struct AdminCli {
+    #[command(subcommand)]
+    pub command: AdminCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AdminCommand {
+    /// Apply pending database migrations
+    Migrate {
+        /// List pending migrations without applying them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Fetch repositories from GitHub and store them in the database
+    SyncGithub {
+        /// Account to sync instead of GITHUB_USERNAME
+        #[arg(long)]
+        username: Option<String>,
+    },
+    /// Delete cached responses from Redis
+    FlushCache {
+        /// Redis glob matched against keys after the cache prefix
+        #[arg(long, default_value = "github:*")]
+        pattern: String,
+    },
+    /// Run the CPU and memory benchmark and print the results as JSON
+    RunBenchmark,
+    /// Write a dataset as JSON Lines to a file or stdout
+    ExportData {
+        #[arg(value_enum)]
+        dataset: DatasetArg,
+        /// Only rows at or after this RFC 3339 time
+        #[arg(long)]
+        since: Option<DateTime<Utc>>,
+        /// Only rows before this RFC 3339 time
+        #[arg(long)]
+        until: Option<DateTime<Utc>>,
+        #[arg(long)]
+        limit: Option<i64>,
+        /// Only performance metrics of this type
+        #[arg(long)]
+        metric_type: Option<String>,
+        /// File to write instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Generate a random API key and print the key id its usage is tracked under
+    GenerateApiKey,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DatasetArg {
+    FractalComputations,
+    PerformanceMetrics,
+}
+
+impl From<DatasetArg> for ExportDataset {
+    fn from(dataset: DatasetArg) -> Self {
+        match dataset {
+            DatasetArg::FractalComputations => ExportDataset::FractalComputations,
+            DatasetArg::PerformanceMetrics => ExportDataset::PerformanceMetrics,
+        }
+    }
+}
+
+/// Run a command and return the process exit code
+pub async fn run(command: AdminCommand) -> i32 {
+    match execute(command).await {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            1
+        }
+    }
+}
+
+async fn execute(command: AdminCommand) -> Result<()> {
+    match command {
+        AdminCommand::Migrate { dry_run } => migrate(&connect().await?, dry_run).await,
+        AdminCommand::SyncGithub { username } => sync_github(&connect().await?, username).await,
+        AdminCommand::FlushCache { pattern } => {
+            let app_state = connect().await?;
+            let flushed = app_state.cache_service.flush_pattern(&pattern).await?;
+            app_state.audit_service.record_event(AuditLog::admin_event(
+                CLI_ACTOR,
+                "cache",
+                pattern.clone(),
+                AuditAction::Delete,
+                None,
+                Some(serde_json::json!({ "flushed": flushed })),
+            )).await;
+            println!("Flushed {} cache entries matching {}", flushed, pattern);
+            Ok(())
+        }
+        AdminCommand::RunBenchmark => {
+            let results = connect().await?.performance_service.run_benchmark().await?;
+            println!("{}", serde_json::to_string_pretty(&results)?);
+            Ok(())
+        }
+        AdminCommand::ExportData { dataset, since, until, limit, metric_type, output } => {
+            let filters = ExportFilters { since, until, limit, metric_type };
+            export_data(&connect().await?, dataset.into(), filters, output).await
+        }
+        AdminCommand::GenerateApiKey => generate_api_key(),
+    }
+}
+
+/// The server's AppState, built from the same configuration sources
+async fn connect() -> Result<AppState> {
+    AppState::new(Config::load()?).await
+}
+
+async fn migrate(app_state: &AppState, dry_run: bool) -> Result<()> {
+    if dry_run {
+        let pending = MigrationManager::pending_migrations(&app_state.db_pool).await?;
+        if pending.is_empty() {
+            println!("No pending migrations");
+        }
+        for migration in pending {
+            println!("pending {} {}", migration.version, migration.description);
+        }
+        return Ok(());
+    }
+
+    let report = MigrationManager::run_pending(&app_state.db_pool).await?;
+    app_state.audit_service.record_event(migration_event(CLI_ACTOR, &report)).await;
+    for migration in &report.applied {
+        println!("applied {} {}", migration.version, migration.description);
+    }
+    println!(
+        "Applied {} migrations in {}ms; schema is at version {}",
+        report.applied.len(),
+        report.duration_ms,
+        report.latest_version
+    );
+    Ok(())
+}
+
+async fn sync_github(app_state: &AppState, username: Option<String>) -> Result<()> {
+    let username = username.unwrap_or_else(|| app_state.config.github_username.clone());
+    let report = app_state.sync_service.sync(&username, SyncTrigger::Admin).await?.report;
+    println!(
+        "Synced {} of {} repositories for {} in {}ms; invalidated {} cached repositories",
+        report.stored, report.fetched, username, report.duration_ms, report.caches_invalidated
+    );
+    Ok(())
+}
+
+/// Row counts go to stderr so stdout stays pure JSON Lines when piped
+async fn export_data(app_state: &AppState, dataset: ExportDataset, filters: ExportFilters, output: Option<PathBuf>) -> Result<()> {
+    let writer: Box<dyn AsyncWrite + Unpin + Send> = match &output {
+        Some(path) => Box::new(
+            tokio::fs::File::create(path)
+                .await
+                .map_err(|e| AppError::BadRequestError(format!("Failed to create {}: {}", path.display(), e)))?,
+        ),
+        None => Box::new(tokio::io::stdout()),
+    };
+    let mut writer = tokio::io::BufWriter::new(writer);
+    let write_error = |e: std::io::Error| AppError::InternalServerError(format!("Failed to write export: {}", e));
+
+    let mut rows = 0u64;
+    let mut lines = record_lines(app_state.db_pool.clone(), dataset, filters);
+    while let Some(line) = lines.next().await {
+        writer.write_all(&line?).await.map_err(write_error)?;
+        rows += 1;
+    }
+    writer.flush().await.map_err(write_error)?;
+
+    eprintln!("Exported {} {} rows", rows, dataset);
+    Ok(())
+}
+
+/// An unowned key for scripts; account keys come from /api/users and carry that user's render quota
+fn generate_api_key() -> Result<()> {
+    let key = users::generate_api_key();
+    println!("API key: {}", key);
+    println!("Key id:  {}", key_principal(&key));
+    println!("The key is not stored anywhere; copy it now.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_admin_subcommands() {
+        let cli = AdminCli::try_parse_from(["showcase-admin", "migrate", "--dry-run"]).unwrap();
+        assert!(matches!(cli.command, AdminCommand::Migrate { dry_run: true }));
+
+        let cli = AdminCli::try_parse_from(["showcase-admin", "flush-cache"]).unwrap();
+        assert!(matches!(cli.command, AdminCommand::FlushCache { pattern } if pattern == "github:*"));
+
+        let cli = AdminCli::try_parse_from([
+            "showcase-admin", "export-data", "performance-metrics", "--since", "2025-01-01T00:00:00Z", "-o", "out.jsonl",
+        ])
+        .unwrap();
+        match cli.command {
+            AdminCommand::ExportData { dataset, since, output, .. } => {
+                assert_eq!(ExportDataset::from(dataset), ExportDataset::PerformanceMetrics);
+                assert_eq!(since.unwrap().to_rfc3339(), "2025-01-01T00:00:00+00:00");
+                assert_eq!(output, Some(PathBuf::from("out.jsonl")));
+            }
+            other => panic!("parsed as {:?}", other),
+        }
+
+        assert!(AdminCli::try_parse_from(["showcase-admin"]).is_err());
+        assert!(AdminCli::try_parse_from(["showcase-admin", "export-data", "users"]).is_err());
+    }
+}
@@ -0,0 +1,400 @@
+/*
+ * Local benchmark runner behind `dark-performance-backend bench`: the fractal and system suites, reported as JSON and Markdown.
+ * I'm running the engines in-process with no config, database, or server, so a CI job can gate on the numbers with only the binary.
+ */
+
+use chrono::{DateTime, Utc};
+use clap::{Args, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::{
+    build_info,
+    services::{
+        fractal_service::{ColoringMode, FractalRequest, FractalService, FractalType, OrbitTrap},
+        performance_service,
+    },
+    utils::error::{AppError, Result},
+};
+
+/// Square resolution, max iterations, and label; the same grid GET /api/fractals/benchmark runs
+const FRACTAL_SCENARIOS: &[(u32, u32, &str)] = &[
+    (256, 100, "low"),
+    (512, 200, "medium"),
+    (1024, 400, "high"),
+    (2048, 800, "ultra"),
+];
+
+const JULIA_C: (f64, f64) = (-0.7, 0.27015);
+
+/// Trap for the orbit-trap benchmarks, which walk every orbit to the end instead of only testing for escape
+const BENCH_TRAP: OrbitTrap = OrbitTrap::Circle { x: 0.0, y: 0.0, radius: 0.5 };
+
+#[derive(Debug, Args)]
+pub struct BenchArgs {
+    #[arg(long, value_enum, default_value_t = Suite::All)]
+    pub suite: Suite,
+    /// Runs per benchmark; the median is reported and compared
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    pub samples: u32,
+    /// Write the JSON report to this file
+    #[arg(long)]
+    pub json: Option<PathBuf>,
+    /// Write the Markdown report to this file instead of stdout
+    #[arg(long)]
+    pub markdown: Option<PathBuf>,
+    /// JSON report from an earlier run; exit non-zero if anything regressed against it
+    #[arg(long)]
+    pub baseline: Option<PathBuf>,
+    /// Percent slowdown of a median over the baseline that counts as a regression
+    #[arg(long, default_value_t = 10.0)]
+    pub max_regression: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Suite {
+    All,
+    Fractal,
+    System,
+}
+
+impl Suite {
+    fn includes(&self, suite: Suite) -> bool {
+        *self == Suite::All || *self == suite
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchResult {
+    pub suite: Suite,
+    /// Stable identifier that baselines are matched on
+    pub name: String,
+    pub median_ms: f64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    /// Work per millisecond at the median, measured in `unit`
+    pub throughput: f64,
+    pub unit: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub renderer: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Comparison {
+    pub name: String,
+    pub baseline_ms: f64,
+    pub current_ms: f64,
+    /// Positive when slower than the baseline
+    pub change_percent: f64,
+    pub regressed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchReport {
+    pub version: String,
+    pub git_commit: String,
+    pub profile: String,
+    pub target: String,
+    pub timestamp: DateTime<Utc>,
+    pub samples: u32,
+    pub results: Vec<BenchResult>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub comparison: Vec<Comparison>,
+}
+
+impl BenchReport {
+    fn new(samples: u32, results: Vec<BenchResult>) -> Self {
+        Self {
+            version: build_info::VERSION.to_string(),
+            git_commit: build_info::GIT_COMMIT.to_string(),
+            profile: build_info::PROFILE.to_string(),
+            target: build_info::TARGET.to_string(),
+            timestamp: Utc::now(),
+            samples,
+            results,
+            comparison: Vec::new(),
+        }
+    }
+
+    pub fn regressions(&self) -> usize {
+        self.comparison.iter().filter(|c| c.regressed).count()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# Benchmark report\n");
+        let _ = writeln!(
+            out,
+            "Version {} ({}), {} build on {}, median of {} samples, {}\n",
+            self.version,
+            self.git_commit,
+            self.profile,
+            self.target,
+            self.samples,
+            self.timestamp.format("%Y-%m-%d %H:%M:%S UTC"),
+        );
+        let _ = writeln!(out, "| Suite | Benchmark | Median ms | Min ms | Max ms | Throughput | Renderer |");
+        let _ = writeln!(out, "|---|---|---:|---:|---:|---:|---|");
+        for result in &self.results {
+            let _ = writeln!(
+                out,
+                "| {:?} | {} | {:.2} | {:.2} | {:.2} | {:.1} {} | {} |",
+                result.suite,
+                result.name,
+                result.median_ms,
+                result.min_ms,
+                result.max_ms,
+                result.throughput,
+                result.unit,
+                result.renderer.as_deref().unwrap_or("-"),
+            );
+        }
+
+        if !self.comparison.is_empty() {
+            let _ = writeln!(out, "\n## Against baseline\n");
+            let _ = writeln!(out, "| Benchmark | Baseline ms | Current ms | Change | Status |");
+            let _ = writeln!(out, "|---|---:|---:|---:|---|");
+            for c in &self.comparison {
+                let _ = writeln!(
+                    out,
+                    "| {} | {:.2} | {:.2} | {:+.1}% | {} |",
+                    c.name,
+                    c.baseline_ms,
+                    c.current_ms,
+                    c.change_percent,
+                    if c.regressed { "regressed" } else { "ok" },
+                );
+            }
+        }
+        out
+    }
+}
+
+/// Run the selected suites and write the reports; fails when a baseline was given and something regressed
+pub fn run(args: BenchArgs) -> Result<()> {
+    // Read the baseline first so a bad path fails before minutes of benchmarking
+    let baseline = args.baseline.as_deref().map(read_report).transpose()?;
+
+    let mut report = BenchReport::new(args.samples, run_suites(args.suite, args.samples));
+    if let Some(baseline) = &baseline {
+        report.comparison = compare(baseline, &report.results, args.max_regression);
+    }
+
+    if let Some(path) = &args.json {
+        write_file(path, &serde_json::to_string_pretty(&report)?)?;
+    }
+    let markdown = report.to_markdown();
+    match &args.markdown {
+        Some(path) => write_file(path, &markdown)?,
+        None => print!("{}", markdown),
+    }
+
+    match report.regressions() {
+        0 => Ok(()),
+        count => Err(AppError::ValidationError(format!(
+            "{} benchmark(s) ran more than {}% slower than the baseline",
+            count, args.max_regression
+        ))),
+    }
+}
+
+fn run_suites(suite: Suite, samples: u32) -> Vec<BenchResult> {
+    let mut results = Vec::new();
+    if suite.includes(Suite::Fractal) {
+        results.extend(fractal_suite(samples));
+    }
+    if suite.includes(Suite::System) {
+        results.extend(system_suite(samples));
+    }
+    results
+}
+
+fn fractal_suite(samples: u32) -> Vec<BenchResult> {
+    let service = FractalService::new();
+    let mut results = Vec::new();
+
+    for &(size, max_iterations, label) in FRACTAL_SCENARIOS {
+        for fractal_type in [FractalType::Mandelbrot, FractalType::Julia { c_real: JULIA_C.0, c_imag: JULIA_C.1 }, FractalType::BurningShip] {
+            let request = FractalRequest { max_iterations, ..FractalRequest::new(fractal_type, size, size) };
+            eprintln!("Benchmarking {} {}x{} at {} iterations ({})", request.fractal_type.name(), size, size, max_iterations, label);
+
+            let renderer = service.renderers().select(&request).name();
+            let timings = measure(samples, || {
+                service.render(request.clone());
+            });
+            results.push(BenchResult::from_timings(
+                Suite::Fractal,
+                format!("{}_{}x{}_{}", request.fractal_type.name(), size, size, max_iterations),
+                &timings,
+                (size * size) as f64,
+                "pixels/ms",
+                Some(renderer.to_string()),
+            ));
+        }
+
+        // Per-pixel work heavier than an escape test: whole orbits measured against a trap, and a derivative carried through each step
+        for (scenario, coloring_mode, orbit_trap) in [
+            ("orbit_trap", ColoringMode::EscapeTime, Some(BENCH_TRAP)),
+            ("distance_estimate", ColoringMode::DistanceEstimate, None),
+        ] {
+            let request = FractalRequest {
+                center_x: -0.5,
+                center_y: 0.0,
+                zoom: 1.0,
+                max_iterations,
+                coloring_mode,
+                orbit_trap,
+                ..FractalRequest::new(FractalType::Mandelbrot, size, size)
+            };
+            eprintln!("Benchmarking mandelbrot {} {}x{} at {} iterations ({})", scenario.replace('_', " "), size, size, max_iterations, label);
+
+            let renderer = service.renderers().select(&request).name();
+            let timings = measure(samples, || {
+                service.render(request.clone());
+            });
+            results.push(BenchResult::from_timings(
+                Suite::Fractal,
+                format!("mandelbrot_{}_{}x{}_{}", scenario, size, size, max_iterations),
+                &timings,
+                (size * size) as f64,
+                "pixels/ms",
+                Some(renderer.to_string()),
+            ));
+        }
+    }
+    results
+}
+
+fn system_suite(samples: u32) -> Vec<BenchResult> {
+    eprintln!("Benchmarking prime counting and memory throughput");
+    let mut primes = 0;
+    let cpu = measure(samples, || primes = performance_service::cpu_benchmark().0);
+    let memory = measure(samples, || {
+        performance_service::memory_benchmark();
+    });
+
+    vec![
+        BenchResult::from_timings(Suite::System, "cpu_primes".to_string(), &cpu, primes as f64, "primes/ms", None),
+        BenchResult::from_timings(
+            Suite::System,
+            "memory_sum".to_string(),
+            &memory,
+            performance_service::memory_benchmark_megabytes(),
+            "MB/ms",
+            None,
+        ),
+    ]
+}
+
+/// Time `samples` runs of `work`, sorted fastest first
+fn measure(samples: u32, mut work: impl FnMut()) -> Vec<Duration> {
+    let mut timings: Vec<Duration> = (0..samples)
+        .map(|_| {
+            let start = Instant::now();
+            work();
+            start.elapsed()
+        })
+        .collect();
+    timings.sort();
+    timings
+}
+
+impl BenchResult {
+    fn from_timings(suite: Suite, name: String, sorted: &[Duration], work: f64, unit: &str, renderer: Option<String>) -> Self {
+        let ms = |d: &Duration| d.as_secs_f64() * 1000.0;
+        let mid = sorted.len() / 2;
+        let median_ms = if sorted.len() % 2 == 0 { (ms(&sorted[mid - 1]) + ms(&sorted[mid])) / 2.0 } else { ms(&sorted[mid]) };
+        Self {
+            suite,
+            name,
+            median_ms,
+            min_ms: ms(&sorted[0]),
+            max_ms: ms(&sorted[sorted.len() - 1]),
+            throughput: if median_ms > 0.0 { work / median_ms } else { 0.0 },
+            unit: unit.to_string(),
+            renderer,
+        }
+    }
+}
+
+/// Pair each result with the same-named baseline entry; benchmarks new since the baseline are left out
+pub fn compare(baseline: &BenchReport, results: &[BenchResult], max_regression: f64) -> Vec<Comparison> {
+    results
+        .iter()
+        .filter_map(|result| {
+            let before = baseline.results.iter().find(|b| b.name == result.name)?;
+            let change_percent = if before.median_ms > 0.0 {
+                (result.median_ms - before.median_ms) / before.median_ms * 100.0
+            } else {
+                0.0
+            };
+            Some(Comparison {
+                name: result.name.clone(),
+                baseline_ms: before.median_ms,
+                current_ms: result.median_ms,
+                change_percent,
+                regressed: change_percent > max_regression,
+            })
+        })
+        .collect()
+}
+
+fn read_report(path: &Path) -> Result<BenchReport> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| AppError::BadRequestError(format!("Failed to read baseline {}: {}", path.display(), e)))?;
+    serde_json::from_str(&contents)
+        .map_err(|e| AppError::ValidationError(format!("{} is not a benchmark report: {}", path.display(), e)))
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<()> {
+    std::fs::write(path, contents)
+        .map_err(|e| AppError::InternalServerError(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(name: &str, median_ms: f64) -> BenchResult {
+        BenchResult {
+            suite: Suite::System,
+            name: name.to_string(),
+            median_ms,
+            min_ms: median_ms,
+            max_ms: median_ms,
+            throughput: 1.0,
+            unit: "ops/ms".to_string(),
+            renderer: None,
+        }
+    }
+
+    #[test]
+    fn test_compare_flags_only_regressions_past_the_threshold() {
+        let baseline = BenchReport::new(3, vec![result("fast", 100.0), result("slow", 100.0), result("gone", 5.0)]);
+        let mut report = BenchReport::new(3, vec![result("fast", 108.0), result("slow", 125.0), result("new", 1.0)]);
+        report.comparison = compare(&baseline, &report.results, 10.0);
+
+        assert_eq!(report.comparison.len(), 2);
+        assert!(!report.comparison[0].regressed);
+        assert!(report.comparison[1].regressed);
+        assert_eq!(report.regressions(), 1);
+        assert!(report.to_markdown().contains("| slow | 100.00 | 125.00 | +25.0% | regressed |"));
+
+        // A report written by one run must load as the next run's baseline
+        let reloaded: BenchReport = serde_json::from_str(&serde_json::to_string(&report).unwrap()).unwrap();
+        assert_eq!(reloaded.results.len(), 3);
+        assert_eq!(reloaded.results[0].suite, Suite::System);
+    }
+
+    #[test]
+    fn test_median_of_sorted_timings() {
+        let timings = [Duration::from_millis(10), Duration::from_millis(20), Duration::from_millis(90)];
+        let result = BenchResult::from_timings(Suite::Fractal, "x".to_string(), &timings, 100.0, "pixels/ms", None);
+        assert_eq!(result.median_ms, 20.0);
+        assert_eq!(result.max_ms, 90.0);
+        assert_eq!(result.throughput, 5.0);
+    }
+}
@@ -0,0 +1,227 @@
+/*
+ * Offline fractal commands behind the fractal-cli binary: render a view straight to an image file, or run the benchmark suite.
+ * I'm drawing through the same FractalService the server does, with no config, database, or Redis, so a profiler sees exactly the server's render path.
+ */
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    cli::bench,
+    models::{fractals::OutputFormat, palettes::Palette},
+    services::{
+        fractal_service::{ColoringMode, FractalRequest, FractalService, FractalType, InteriorColoring, OrbitTrap, Precision},
+        image_service::encode_image,
+    },
+    utils::error::{AppError, Result},
+};
+
+#[derive(Debug, Parser)]
+#[command(name = "fractal-cli", version, about = "Render fractals to image files and benchmark the engine, without the server")]
+pub struct FractalCli {
+    #[command(subcommand)]
+    pub command: FractalCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum FractalCommand {
+    /// Render one view to a PNG, JPEG, or WebP file
+    Render(RenderArgs),
+    /// Run the fractal and system benchmarks and report them as Markdown and JSON
+    Bench(bench::BenchArgs),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum RenderType {
+    Mandelbrot,
+    Julia,
+    BurningShip,
+    Tricorn,
+    Multibrot,
+}
+
+#[derive(Debug, Args)]
+pub struct RenderArgs {
+    /// Image file to write; its extension picks the format unless --format is given
+    pub output: PathBuf,
+    #[arg(long = "type", value_enum, default_value_t = RenderType::Mandelbrot)]
+    pub fractal_type: RenderType,
+    #[arg(long, default_value_t = 800, value_parser = clap::value_parser!(u32).range(1..=16384))]
+    pub width: u32,
+    #[arg(long, default_value_t = 600, value_parser = clap::value_parser!(u32).range(1..=16384))]
+    pub height: u32,
+    /// The type's usual centre when unset
+    #[arg(long, allow_negative_numbers = true)]
+    pub center_x: Option<f64>,
+    #[arg(long, allow_negative_numbers = true)]
+    pub center_y: Option<f64>,
+    #[arg(long, default_value_t = 1.0)]
+    pub zoom: f64,
+    #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_iterations: u32,
+    /// Julia constant, real part
+    #[arg(long, default_value_t = -0.7, allow_negative_numbers = true)]
+    pub c_real: f64,
+    /// Julia constant, imaginary part
+    #[arg(long, default_value_t = 0.27015, allow_negative_numbers = true)]
+    pub c_imag: f64,
+    /// Multibrot exponent
+    #[arg(long, default_value_t = 3.0)]
+    pub power: f64,
+    /// Built-in palette name; the dark theme when unset
+    #[arg(long)]
+    pub palette: Option<String>,
+    /// escape_time, smooth, histogram, or distance_estimate
+    #[arg(long, default_value = "escape_time", value_parser = named::<ColoringMode>)]
+    pub coloring_mode: ColoringMode,
+    /// `point:x,y`, `line:x,y,angle`, or `circle:x,y,radius`
+    #[arg(long)]
+    pub orbit_trap: Option<OrbitTrap>,
+    /// Supersampling factor from 1 to 4
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=4))]
+    pub antialiasing: Option<u8>,
+    /// auto, f32, f64, or extended
+    #[arg(long, default_value = "auto", value_parser = named::<Precision>)]
+    pub precision: Precision,
+    /// black or period
+    #[arg(long, default_value = "black", value_parser = named::<InteriorColoring>)]
+    pub interior_coloring: InteriorColoring,
+    /// Render threads; every core when unset
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub threads: Option<u32>,
+    /// png, jpeg, or webp, overriding the output file's extension
+    #[arg(long, value_parser = named::<OutputFormat>)]
+    pub format: Option<OutputFormat>,
+}
+
+impl RenderArgs {
+    fn request(&self) -> Result<FractalRequest> {
+        let fractal_type = match self.fractal_type {
+            RenderType::Mandelbrot => FractalType::Mandelbrot,
+            RenderType::Julia => FractalType::Julia { c_real: self.c_real, c_imag: self.c_imag },
+            RenderType::BurningShip => FractalType::BurningShip,
+            RenderType::Tricorn => FractalType::Tricorn,
+            RenderType::Multibrot => FractalType::Multibrot { power: self.power },
+        };
+        let palette = self
+            .palette
+            .as_deref()
+            .map(|name| {
+                Palette::builtin(name).ok_or_else(|| {
+                    let names: Vec<String> = Palette::builtins().into_iter().map(|palette| palette.name).collect();
+                    AppError::ValidationError(format!("Unknown palette '{}'; built-ins are {}", name, names.join(", ")))
+                })
+            })
+            .transpose()?;
+        let (default_x, default_y) = fractal_type.default_center();
+
+        Ok(FractalRequest {
+            center_x: self.center_x.unwrap_or(default_x),
+            center_y: self.center_y.unwrap_or(default_y),
+            zoom: self.zoom,
+            max_iterations: self.max_iterations,
+            palette,
+            coloring_mode: self.coloring_mode,
+            orbit_trap: self.orbit_trap,
+            antialiasing: self.antialiasing,
+            precision: self.precision,
+            threads: self.threads,
+            interior_coloring: self.interior_coloring,
+            ..FractalRequest::new(fractal_type, self.width, self.height)
+        })
+    }
+}
+
+/// A snake_case enum value by the name the API takes it under
+fn named<T: DeserializeOwned>(value: &str) -> std::result::Result<T, String> {
+    serde_json::from_value(serde_json::Value::String(value.to_ascii_lowercase())).map_err(|e| e.to_string())
+}
+
+/// Image format for an output path from its extension
+fn format_for(path: &Path) -> Result<OutputFormat> {
+    match path.extension().and_then(|ext| ext.to_str()).map(str::to_ascii_lowercase).as_deref() {
+        Some("png") => Ok(OutputFormat::Png),
+        Some("jpg" | "jpeg") => Ok(OutputFormat::Jpeg),
+        Some("webp") => Ok(OutputFormat::Webp),
+        _ => Err(AppError::ValidationError(format!(
+            "Can't tell the image format of {}; name it .png, .jpg, or .webp, or pass --format",
+            path.display()
+        ))),
+    }
+}
+
+/// Run a subcommand and return the process exit code
+pub fn run(command: FractalCommand) -> i32 {
+    let result = match command {
+        FractalCommand::Render(args) => render(&args),
+        FractalCommand::Bench(args) => bench::run(args),
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            1
+        }
+    }
+}
+
+fn render(args: &RenderArgs) -> Result<()> {
+    let format = match args.format {
+        Some(OutputFormat::Raw) => return Err(AppError::ValidationError("--format must be png, jpeg, or webp".to_string())),
+        Some(format) => format,
+        None => format_for(&args.output)?,
+    };
+    let request = args.request()?;
+
+    let response = FractalService::new().render(request);
+    let encoded = encode_image(format, response.width, response.height, &response.data)?;
+    std::fs::write(&args.output, encoded)
+        .map_err(|e| AppError::InternalServerError(format!("Failed to write {}: {}", args.output.display(), e)))?;
+
+    eprintln!(
+        "Rendered {}x{} in {}ms on {} ({}, {} threads, {} interior points skipped) to {}",
+        response.width,
+        response.height,
+        response.computation_time_ms,
+        response.renderer,
+        response.precision.name(),
+        response.threads,
+        response.interior.skipped(),
+        args.output.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_render_into_a_request() {
+        let cli = FractalCli::try_parse_from([
+            "fractal-cli", "render", "out.webp", "--type", "julia", "--c-real", "-0.8", "--center-x", "-0.25",
+            "--coloring-mode", "smooth", "--interior-coloring", "period", "--palette", "grayscale",
+        ])
+        .unwrap();
+        let FractalCommand::Render(args) = cli.command else {
+            panic!("parsed as {:?}", cli.command);
+        };
+        assert_eq!(format_for(&args.output).unwrap(), OutputFormat::Webp);
+
+        let request = args.request().unwrap();
+        assert!(matches!(request.fractal_type, FractalType::Julia { c_real, c_imag } if c_real == -0.8 && c_imag == 0.27015));
+        assert_eq!((request.width, request.height, request.center_x), (800, 600, -0.25));
+        assert_eq!(request.coloring_mode, ColoringMode::Smooth);
+        assert_eq!(request.interior_coloring, InteriorColoring::Period);
+        assert!(request.palette.is_some());
+
+        assert!(format_for(Path::new("out.tiff")).is_err());
+        assert!(FractalCli::try_parse_from(["fractal-cli", "render", "out.png", "--precision", "f16"]).is_err());
+        assert!(matches!(
+            FractalCli::try_parse_from(["fractal-cli", "bench", "--suite", "system"]).unwrap().command,
+            FractalCommand::Bench(bench::BenchArgs { suite: bench::Suite::System, .. })
+        ));
+    }
+}
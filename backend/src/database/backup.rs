@@ -0,0 +1,392 @@
+/*
+ * Logical backup and restore of showcase data as a gzipped JSON archive.
+ * I'm letting Postgres serialize rows with to_jsonb and rebuild them with jsonb_populate_recordset so the archive tracks the schema without a hand-kept column list.
+ * Imports name only the columns an archive carries, so columns added since it was taken get their defaults instead of NULL.
+ */
+
+use axum::body::Bytes;
+use chrono::{DateTime, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{Read, Write};
+use tracing::info;
+
+use crate::{
+    database::{latest_migration_version, timing, DatabasePool},
+    models::tenants::DEFAULT_TENANT_SLUG,
+    utils::error::{AppError, Result},
+};
+
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+/// Default cap on the decompressed archive so a tiny gzip bomb can't exhaust memory
+pub const MAX_ARCHIVE_BYTES: u64 = 256 * 1024 * 1024;
+
+const IMPORT_BATCH_SIZE: usize = 500;
+
+/// Tables included in a backup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackupTable {
+    Repositories,
+    Palettes,
+    FractalPresets,
+    BenchmarkResults,
+}
+
+impl BackupTable {
+    /// Restore order; palettes come before the presets that reference them
+    pub const ALL: [BackupTable; 4] = [
+        BackupTable::Repositories,
+        BackupTable::Palettes,
+        BackupTable::FractalPresets,
+        BackupTable::BenchmarkResults,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BackupTable::Repositories => "repositories",
+            BackupTable::Palettes => "palettes",
+            BackupTable::FractalPresets => "fractal_presets",
+            BackupTable::BenchmarkResults => "benchmark_results",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|table| table.as_str() == name)
+    }
+
+    /// Whether rows carry a tenant_id, which archives taken before tenant scoping leave out
+    fn is_tenant_scoped(&self) -> bool {
+        matches!(self, BackupTable::Palettes | BackupTable::FractalPresets)
+    }
+
+    fn order_column(&self) -> &'static str {
+        match self {
+            BackupTable::Repositories => "github_id",
+            BackupTable::Palettes | BackupTable::FractalPresets => "created_at",
+            BackupTable::BenchmarkResults => "timestamp",
+        }
+    }
+
+    /// Every row as one JSON object, oldest first
+    pub fn export_sql(&self) -> String {
+        format!("SELECT to_jsonb(t) FROM {} t ORDER BY {}, id", self.as_str(), self.order_column())
+    }
+}
+
+pub fn schema_version() -> i64 {
+    latest_migration_version()
+}
+
+/// Parsed archive; rows stay as JSON objects until Postgres maps them back onto the table
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupArchive {
+    pub format_version: u32,
+    pub schema_version: i64,
+    pub exported_at: DateTime<Utc>,
+    #[serde(default)]
+    pub tables: BTreeMap<String, Vec<serde_json::Value>>,
+}
+
+/// How imported rows interact with what's already stored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// Keep existing rows and skip archive rows that conflict with them
+    #[default]
+    Merge,
+    /// Clear the backed-up tables first so the archive becomes the full contents
+    Replace,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportReport {
+    pub mode: &'static str,
+    pub schema_version: i64,
+    /// Rows inserted per table
+    pub imported: BTreeMap<String, u64>,
+    /// Archive rows skipped because they conflicted with existing rows
+    pub skipped: BTreeMap<String, u64>,
+    pub duration_ms: u64,
+}
+
+/// Incremental writer producing the gzipped archive one piece at a time
+/// I'm handing back whatever compressed bytes each write produced so the response can stream them straight out
+pub struct ArchiveWriter {
+    encoder: GzEncoder<Vec<u8>>,
+    rows_in_table: usize,
+    tables_written: usize,
+}
+
+impl ArchiveWriter {
+    pub fn new() -> Self {
+        Self {
+            encoder: GzEncoder::new(Vec::new(), Compression::default()),
+            rows_in_table: 0,
+            tables_written: 0,
+        }
+    }
+
+    /// Archive header up to the opening of the `tables` object
+    pub fn begin(&mut self, exported_at: DateTime<Utc>) -> Result<Bytes> {
+        let header = format!(
+            "{{\"format_version\":{},\"schema_version\":{},\"exported_at\":{},\"tables\":{{",
+            ARCHIVE_FORMAT_VERSION,
+            schema_version(),
+            serde_json::to_string(&exported_at)?
+        );
+        self.write(header.as_bytes())
+    }
+
+    pub fn begin_table(&mut self, table: BackupTable) -> Result<Bytes> {
+        let separator = if self.tables_written > 0 { "," } else { "" };
+        self.tables_written += 1;
+        self.rows_in_table = 0;
+        self.write(format!("{}\"{}\":[", separator, table.as_str()).as_bytes())
+    }
+
+    pub fn row(&mut self, row: &serde_json::Value) -> Result<Bytes> {
+        let mut encoded = if self.rows_in_table > 0 { vec![b','] } else { Vec::new() };
+        serde_json::to_writer(&mut encoded, row)?;
+        self.rows_in_table += 1;
+        self.write(&encoded)
+    }
+
+    pub fn end_table(&mut self) -> Result<Bytes> {
+        self.write(b"]")
+    }
+
+    /// Close the JSON document and flush the gzip trailer
+    pub fn finish(mut self) -> Result<Bytes> {
+        self.encoder.write_all(b"}}").map_err(compression_error)?;
+        Ok(Bytes::from(self.encoder.finish().map_err(compression_error)?))
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<Bytes> {
+        self.encoder.write_all(bytes).map_err(compression_error)?;
+        Ok(Bytes::from(std::mem::take(self.encoder.get_mut())))
+    }
+}
+
+fn compression_error(err: std::io::Error) -> AppError {
+    AppError::SerializationError(format!("Failed to compress backup archive: {}", err))
+}
+
+impl Default for ArchiveWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decode an uploaded archive, accepting plain JSON as well as gzip
+pub fn decode_archive(body: &[u8], max_bytes: u64) -> Result<BackupArchive> {
+    let is_gzip = body.starts_with(&[0x1f, 0x8b]);
+    let mut json = Vec::new();
+
+    if is_gzip {
+        GzDecoder::new(body)
+            .take(max_bytes + 1)
+            .read_to_end(&mut json)
+            .map_err(|e| AppError::bad_request(format!("Backup archive is not valid gzip: {}", e)))?;
+    } else {
+        json.extend_from_slice(body);
+    }
+
+    if json.len() as u64 > max_bytes {
+        return Err(AppError::bad_request(format!(
+            "Backup archive expands beyond {} bytes", max_bytes
+        )));
+    }
+
+    let archive: BackupArchive = serde_json::from_slice(&json)
+        .map_err(|e| AppError::bad_request(format!("Backup archive is not valid JSON: {}", e)))?;
+    validate_archive(&archive)?;
+    Ok(archive)
+}
+
+fn validate_archive(archive: &BackupArchive) -> Result<()> {
+    if archive.format_version != ARCHIVE_FORMAT_VERSION {
+        return Err(AppError::bad_request(format!(
+            "Unsupported backup format version {} (expected {})",
+            archive.format_version, ARCHIVE_FORMAT_VERSION
+        )));
+    }
+    // Older archives restore fine since missing columns take their defaults; newer ones may carry columns we can't store
+    if archive.schema_version > schema_version() {
+        return Err(AppError::bad_request(format!(
+            "Backup was taken at schema version {} but this deployment is at {}; migrate first",
+            archive.schema_version,
+            schema_version()
+        )));
+    }
+    if let Some(unknown) = archive.tables.keys().find(|name| BackupTable::parse(name).is_none()) {
+        return Err(AppError::bad_request(format!("Backup contains unknown table '{}'", unknown)));
+    }
+    Ok(())
+}
+
+/// Restore an archive inside one transaction so a failed import leaves the database untouched
+pub async fn import_archive(pool: &DatabasePool, archive: &BackupArchive, mode: ImportMode) -> Result<ImportReport> {
+    let start = std::time::Instant::now();
+    let mut report = ImportReport {
+        mode: match mode {
+            ImportMode::Merge => "merge",
+            ImportMode::Replace => "replace",
+        },
+        schema_version: archive.schema_version,
+        imported: BTreeMap::new(),
+        skipped: BTreeMap::new(),
+        duration_ms: 0,
+    };
+
+    let mut tx = pool.begin().await?;
+
+    if mode == ImportMode::Replace {
+        // Reverse restore order so referencing rows go before the rows they point at
+        for table in BackupTable::ALL.iter().rev() {
+            sqlx::query(&format!("DELETE FROM {}", table.as_str())).execute(&mut *tx).await?;
+        }
+    }
+
+    for table in BackupTable::ALL {
+        let Some(rows) = archive.tables.get(table.as_str()) else {
+            continue;
+        };
+
+        let rows: Vec<serde_json::Value> = rows.iter().map(|row| with_tenant(table, row)).collect();
+        let columns: Vec<String> = sqlx::query_scalar(
+            "SELECT column_name::TEXT FROM information_schema.columns
+             WHERE table_schema = current_schema() AND table_name = $1 ORDER BY ordinal_position"
+        )
+        .bind(table.as_str())
+        .fetch_all(&mut *tx)
+        .await?;
+        let archived = archived_columns(&columns, &rows);
+        if archived.is_empty() && !rows.is_empty() {
+            return Err(AppError::bad_request(format!("Backup rows for {} carry none of its columns", table.as_str())));
+        }
+        let sql = insert_sql(table, &archived);
+
+        let mut inserted = 0;
+        for batch in rows.chunks(IMPORT_BATCH_SIZE) {
+            let query = sqlx::query(&sql).bind(serde_json::Value::Array(batch.to_vec()));
+            let result = timing::timed(&format!("backup_import_{}", table.as_str()), query.execute(&mut *tx))
+                .await
+                .map_err(|e| AppError::DatabaseError(format!("Failed to import {}: {}", table.as_str(), e)))?;
+            inserted += result.rows_affected();
+        }
+
+        report.imported.insert(table.as_str().to_string(), inserted);
+        report.skipped.insert(table.as_str().to_string(), rows.len() as u64 - inserted);
+    }
+
+    tx.commit().await?;
+
+    report.duration_ms = start.elapsed().as_millis() as u64;
+    info!("Imported backup archive ({}): {:?}", report.mode, report.imported);
+    Ok(report)
+}
+
+/// The table's columns that at least one archived row carries, in table order
+fn archived_columns<'a>(columns: &'a [String], rows: &[serde_json::Value]) -> Vec<&'a str> {
+    let archived: BTreeSet<&str> = rows.iter().filter_map(serde_json::Value::as_object).flat_map(|fields| fields.keys().map(String::as_str)).collect();
+    columns.iter().map(String::as_str).filter(|column| archived.contains(column)).collect()
+}
+
+/// Insert only `columns`, leaving the rest to their defaults; table names come from BackupTable and columns from the catalog, never from the archive itself
+fn insert_sql(table: BackupTable, columns: &[&str]) -> String {
+    let columns = columns.iter().map(|column| format!("\"{}\"", column)).collect::<Vec<_>>().join(", ");
+    format!(
+        "INSERT INTO {table} ({columns}) SELECT {columns} FROM jsonb_populate_recordset(NULL::{table}, $1) ON CONFLICT DO NOTHING",
+        table = table.as_str()
+    )
+}
+
+/// The row as stored, with rows from before tenant scoping handed to the default tenant rather than a NULL tenant_id
+fn with_tenant(table: BackupTable, row: &serde_json::Value) -> serde_json::Value {
+    let mut row = row.clone();
+    if let Some(fields) = row.as_object_mut().filter(|_| table.is_tenant_scoped()) {
+        if fields.get("tenant_id").map_or(true, serde_json::Value::is_null) {
+            fields.insert("tenant_id".to_string(), DEFAULT_TENANT_SLUG.into());
+        }
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn write_archive(tables: &[(BackupTable, Vec<serde_json::Value>)]) -> Vec<u8> {
+        let mut writer = ArchiveWriter::new();
+        let mut out = writer.begin(Utc::now()).unwrap().to_vec();
+        for (table, rows) in tables {
+            out.extend_from_slice(&writer.begin_table(*table).unwrap());
+            for row in rows {
+                out.extend_from_slice(&writer.row(row).unwrap());
+            }
+            out.extend_from_slice(&writer.end_table().unwrap());
+        }
+        out.extend_from_slice(&writer.finish().unwrap());
+        out
+    }
+
+    #[test]
+    fn test_streamed_archive_round_trips() {
+        let bytes = write_archive(&[
+            (BackupTable::Palettes, vec![json!({ "id": "a", "name": "fire" }), json!({ "id": "b" })]),
+            (BackupTable::FractalPresets, vec![]),
+        ]);
+        assert!(bytes.starts_with(&[0x1f, 0x8b]));
+
+        let archive = decode_archive(&bytes, MAX_ARCHIVE_BYTES).unwrap();
+        assert_eq!(archive.schema_version, schema_version());
+        assert_eq!(archive.tables["palettes"].len(), 2);
+        assert_eq!(archive.tables["palettes"][0]["name"], "fire");
+        assert!(archive.tables["fractal_presets"].is_empty());
+    }
+
+    #[test]
+    fn test_decode_rejects_unknown_tables_and_newer_schemas() {
+        let unknown = json!({
+            "format_version": ARCHIVE_FORMAT_VERSION,
+            "schema_version": 1,
+            "exported_at": Utc::now(),
+            "tables": { "audit_logs": [] }
+        });
+        assert!(decode_archive(unknown.to_string().as_bytes(), MAX_ARCHIVE_BYTES).is_err());
+
+        let newer = json!({
+            "format_version": ARCHIVE_FORMAT_VERSION,
+            "schema_version": schema_version() + 1,
+            "exported_at": Utc::now(),
+            "tables": {}
+        });
+        assert!(decode_archive(newer.to_string().as_bytes(), MAX_ARCHIVE_BYTES).is_err());
+    }
+
+    #[test]
+    fn test_rows_from_before_tenant_scoping_go_to_the_default_tenant() {
+        let restored = with_tenant(BackupTable::Palettes, &json!({ "id": "a", "tenant_id": null }));
+        assert_eq!(restored["tenant_id"], DEFAULT_TENANT_SLUG);
+        let restored = with_tenant(BackupTable::FractalPresets, &json!({ "id": "b", "tenant_id": "octo" }));
+        assert_eq!(restored["tenant_id"], "octo");
+        assert!(with_tenant(BackupTable::Repositories, &json!({ "id": 1 })).get("tenant_id").is_none());
+    }
+
+    #[test]
+    fn test_imports_name_only_the_archived_columns() {
+        let columns: Vec<String> = ["id", "name", "stops", "tenant_id", "created_at"].map(String::from).into();
+        let rows = [json!({ "id": "a", "name": "fire" }), json!({ "id": "b", "created_at": "2025-01-01T00:00:00Z", "dropped": 1 })];
+
+        let archived = archived_columns(&columns, &rows);
+        assert_eq!(archived, ["id", "name", "created_at"]);
+        assert_eq!(
+            insert_sql(BackupTable::Palettes, &archived),
+            "INSERT INTO palettes (\"id\", \"name\", \"created_at\") SELECT \"id\", \"name\", \"created_at\" \
+             FROM jsonb_populate_recordset(NULL::palettes, $1) ON CONFLICT DO NOTHING"
+        );
+    }
+}
@@ -15,6 +15,8 @@ use crate::{
     utils::{
         error::{AppError, Result},
         config::{Config, DatabasePoolConfig},
+        metrics::MetricsCollector,
+        retry::{RetryBudget, RetryConfig},
     },
 };
 
@@ -147,7 +149,7 @@ impl DatabaseManager {
     pub async fn run_migrations(&self) -> Result<()> {
         info!("Running database migrations");
 
-        match sqlx::migrate!("src/database/migrations")
+        match super::MIGRATOR
         .run(&self.pool)
         .await
         {
@@ -232,6 +234,19 @@ pub async fn create_pool(database_url: &str) -> Result<DatabasePool> {
     Ok(pool)
 }
 
+/// Prepared statements cached per connection
+const STATEMENT_CACHE_CAPACITY: usize = 100;
+
+/// Statement cache size for the pool's connections
+/// I'm turning the cache off behind pgBouncer in transaction mode, where consecutive statements can land on
+/// different server connections: a named statement prepared on one is missing on the next. With no cache sqlx
+/// prepares every query as the unnamed statement within a single round trip, which pgBouncer passes through
+/// safely, at the cost of re-planning each query. Session-level state (LISTEN, advisory locks, SET) still
+/// doesn't survive, so callers also skip the notification listener in this mode.
+fn statement_cache_capacity(pgbouncer_mode: bool) -> usize {
+    if pgbouncer_mode { 0 } else { STATEMENT_CACHE_CAPACITY }
+}
+
 /// Create a database pool with custom configuration
 /// I'm providing flexibility for different deployment scenarios
 pub async fn create_pool_with_config(database_url: &str, config: &DatabasePoolConfig) -> Result<DatabasePool> {
@@ -243,7 +258,7 @@ pub async fn create_pool_with_config(database_url: &str, config: &DatabasePoolCo
     connect_options = connect_options
     .application_name("dark-performance-showcase")
     .ssl_mode(PgSslMode::Prefer)
-    .statement_cache_capacity(100)
+    .statement_cache_capacity(statement_cache_capacity(config.pgbouncer_mode))
     .log_statements(if cfg!(debug_assertions) {
         tracing::log::LevelFilter::Debug
     } else {
@@ -263,8 +278,8 @@ pub async fn create_pool_with_config(database_url: &str, config: &DatabasePoolCo
 
     test_database_connection(&pool).await?;
 
-    info!("Database connection pool created with custom config: max={}, min={}",
-          config.max_connections, config.min_connections);
+    info!("Database connection pool created with custom config: max={}, min={}, pgbouncer mode: {}",
+          config.max_connections, config.min_connections, config.pgbouncer_mode);
     Ok(pool)
 }
 
@@ -364,6 +379,85 @@ F: for<'c> FnOnce(&mut sqlx::Transaction<'c, sqlx::Postgres>) -> std::pin::Pin<B
     }
 }
 
+/// SQLSTATE codes Postgres uses for conflicts that succeed when simply retried
+const RETRYABLE_SQLSTATES: [&str; 2] = ["40001", "40P01"]; // serialization_failure, deadlock_detected
+
+const TRANSACTION_MAX_ATTEMPTS: u32 = 5;
+const TRANSACTION_BASE_BACKOFF: Duration = Duration::from_millis(20);
+
+/// Whether a failed statement or commit is worth running again from the top
+pub fn is_retryable_error(err: &sqlx::Error) -> bool {
+    err.as_database_error()
+        .and_then(|db_err| db_err.code())
+        .map_or(false, |code| RETRYABLE_SQLSTATES.contains(&code.as_ref()))
+}
+
+/// Run `f` in a transaction, replaying the whole body on serialization failures and deadlocks
+/// I'm having the body return sqlx::Error so the SQLSTATE survives long enough to decide whether to retry,
+/// and since the body may run several times it has to own (or cheaply clone) whatever it captures.
+/// Replays draw from the global retry budget like retry_with_backoff's, which can't see the SQLSTATE once it's an AppError
+pub async fn with_retrying_transaction<F, R>(pool: &DatabasePool, name: &str, mut f: F) -> Result<R>
+where
+    F: for<'t> FnMut(
+        &'t mut sqlx::Transaction<'static, sqlx::Postgres>,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = std::result::Result<R, sqlx::Error>> + Send + 't>>,
+{
+    let retry = transaction_retry();
+    let budget = RetryBudget::global();
+    budget.deposit();
+    let mut attempt = 1;
+
+    loop {
+        let span = tracing::debug_span!("db_transaction", transaction = name, attempt);
+        let outcome = tracing::Instrument::instrument(
+            async {
+                let mut tx = pool.begin().await?;
+                match f(&mut tx).await {
+                    Ok(result) => {
+                        tx.commit().await?;
+                        Ok(result)
+                    }
+                    Err(e) => {
+                        if let Err(rollback_err) = tx.rollback().await {
+                            error!("Failed to rollback transaction {}: {}", name, rollback_err);
+                        }
+                        Err(e)
+                    }
+                }
+            },
+            span,
+        )
+        .await;
+
+        match outcome {
+            Ok(result) => return Ok(result),
+            Err(e) if is_retryable_error(&e) && attempt < retry.max_attempts => {
+                if !budget.try_withdraw() {
+                    warn!("Retry budget exhausted; transaction {} giving up after attempt {}: {}", name, attempt, e);
+                    return Err(e.into());
+                }
+                let backoff = retry.delay(attempt);
+                warn!(
+                    "Transaction {} hit a retryable conflict on attempt {}, retrying in {}ms: {}",
+                    name, attempt, backoff.as_millis(), e
+                );
+                tokio::time::sleep(backoff).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Exponential backoff with full jitter so colliding writers don't retry in lockstep
+fn transaction_retry() -> RetryConfig {
+    RetryConfig {
+        max_attempts: TRANSACTION_MAX_ATTEMPTS,
+        initial_delay: TRANSACTION_BASE_BACKOFF,
+        ..RetryConfig::default()
+    }
+}
+
 /// Batch operation helper for improved performance
 /// I'm providing optimized batch processing for bulk operations
 pub async fn batch_execute<T>(
@@ -396,21 +490,61 @@ T: Send,
 /// I'm implementing performance monitoring for database operations
 pub struct ConnectionPoolMonitor {
     pool: DatabasePool,
+    metrics: MetricsCollector,
+    max_connections: u32,
     metrics_interval: Duration,
 }
 
+/// Point-in-time view of the pool, as published to the metrics collector
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoolSnapshot {
+    pub size: u32,
+    pub idle: u32,
+    pub active: u32,
+    pub max_connections: u32,
+    /// Fraction of the configured maximum currently checked out
+    pub utilization: f64,
+}
+
+impl PoolSnapshot {
+    pub fn new(size: u32, idle: u32, max_connections: u32) -> Self {
+        let active = size.saturating_sub(idle);
+        Self {
+            size,
+            idle,
+            active,
+            max_connections,
+            utilization: if max_connections == 0 { 0.0 } else { active as f64 / max_connections as f64 },
+        }
+    }
+
+    /// Every connection the pool may open is in use, so the next acquire has to wait
+    pub fn is_exhausted(&self) -> bool {
+        self.idle == 0 && self.size >= self.max_connections
+    }
+}
+
 impl ConnectionPoolMonitor {
-    pub fn new(pool: DatabasePool, metrics_interval: Duration) -> Self {
+    pub fn new(pool: DatabasePool, metrics: MetricsCollector, max_connections: u32, metrics_interval: Duration) -> Self {
         Self {
             pool,
+            metrics,
+            max_connections,
             metrics_interval,
         }
     }
 
+    /// Run the monitor in the background for the life of the process
+    pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+        info!("Publishing database pool metrics every {}s", self.metrics_interval.as_secs());
+        tokio::spawn(async move { self.start_monitoring().await })
+    }
+
     /// Start monitoring the connection pool
     /// I'm providing continuous monitoring of database performance
     pub async fn start_monitoring(&self) {
         let mut interval = tokio::time::interval(self.metrics_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
 
         loop {
             interval.tick().await;
@@ -421,25 +555,44 @@ impl ConnectionPoolMonitor {
         }
     }
 
-    /// Collect and log database metrics
-    /// I'm gathering comprehensive performance data
+    /// Collect pool statistics and publish them as gauges plus an acquire-latency histogram
+    /// I'm timing a real acquire each tick since sqlx doesn't expose how long callers queue for a connection
     async fn collect_metrics(&self) -> Result<()> {
-        let pool_size = self.pool.size();
-        let idle_connections = self.pool.num_idle();
-        let active_connections = pool_size - (idle_connections as u32);
+        let snapshot = PoolSnapshot::new(self.pool.size(), self.pool.num_idle() as u32, self.max_connections);
+
+        self.metrics.set_gauge("db_pool_size", snapshot.size as f64).await?;
+        self.metrics.set_gauge("db_pool_idle_connections", snapshot.idle as f64).await?;
+        self.metrics.set_gauge("db_pool_active_connections", snapshot.active as f64).await?;
+        self.metrics.set_gauge("db_pool_max_connections", snapshot.max_connections as f64).await?;
+        self.metrics.set_gauge("db_pool_utilization", snapshot.utilization).await?;
 
         // Log pool statistics
         debug!("Database pool stats - Total: {}, Active: {}, Idle: {}",
-               pool_size, active_connections, idle_connections);
+               snapshot.size, snapshot.active, snapshot.idle);
 
         // Check for potential issues
-        if active_connections > (pool_size * 3 / 4) {
+        if snapshot.is_exhausted() {
+            self.metrics.increment_counter("db_pool_exhausted_total").await?;
+            warn!("Database pool exhausted: {}/{} connections active",
+                  snapshot.active, snapshot.max_connections);
+        } else if snapshot.utilization > 0.75 {
             warn!("High database connection usage: {}/{} connections active",
-                  active_connections, pool_size);
+                  snapshot.active, snapshot.max_connections);
         }
 
-        if idle_connections == 0 {
-            warn!("No idle database connections available - consider increasing pool size");
+        let start = std::time::Instant::now();
+        let acquired = self.pool.acquire().await;
+        let wait_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+        match acquired {
+            Ok(connection) => {
+                drop(connection);
+                self.metrics.record_operation_time("db_pool_acquire", wait_ms).await?;
+            }
+            Err(e) => {
+                self.metrics.increment_counter("db_pool_acquire_errors_total").await?;
+                warn!("Database pool acquire failed after {:.1}ms: {}", wait_ms, e);
+            }
         }
 
         Ok(())
@@ -472,4 +625,34 @@ mod tests {
         let options = PgConnectOptions::from_str(url);
         assert!(options.is_ok());
     }
+
+    #[test]
+    fn test_transaction_retry_policy() {
+        assert!(!is_retryable_error(&sqlx::Error::RowNotFound));
+        assert!(!is_retryable_error(&sqlx::Error::PoolTimedOut));
+
+        let retry = transaction_retry();
+        assert_eq!(retry.backoff(1), TRANSACTION_BASE_BACKOFF);
+        assert_eq!(retry.backoff(3), TRANSACTION_BASE_BACKOFF * 4);
+        assert!((0..20).all(|_| retry.delay(3) <= TRANSACTION_BASE_BACKOFF * 4));
+    }
+
+    #[test]
+    fn test_pool_snapshot_utilization() {
+        let busy = PoolSnapshot::new(10, 0, 10);
+        assert_eq!(busy.active, 10);
+        assert!(busy.is_exhausted());
+        assert!((busy.utilization - 1.0).abs() < f64::EPSILON);
+
+        // Still allowed to open more connections, so not exhausted yet
+        let growing = PoolSnapshot::new(4, 0, 10);
+        assert!(!growing.is_exhausted());
+        assert!((growing.utilization - 0.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_pgbouncer_mode_disables_statement_cache() {
+        assert_eq!(statement_cache_capacity(true), 0);
+        assert_eq!(statement_cache_capacity(false), STATEMENT_CACHE_CAPACITY);
+    }
 }
@@ -0,0 +1,26 @@
+-- Request audit log storage for security review and debugging.
+-- I'm persisting one row per sampled HTTP request so the admin API can page through who called what, from where, and how it went.
+
+CREATE TABLE IF NOT EXISTS audit_logs (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    entity_type VARCHAR(100) NOT NULL DEFAULT 'http_request',
+    entity_id VARCHAR(255),
+    action VARCHAR(50) NOT NULL,
+    user_id VARCHAR(255), -- Principal that made the request (hashed API key or 'admin')
+    ip_address VARCHAR(64), -- Possibly truncated when PII redaction is enabled
+    user_agent TEXT,
+    method VARCHAR(16),
+    path TEXT,
+    status_code INTEGER,
+    latency_ms BIGINT,
+    changes JSONB,
+    metadata JSONB DEFAULT '{}',
+    timestamp TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_logs_timestamp ON audit_logs(timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_logs_user_id ON audit_logs(user_id, timestamp DESC) WHERE user_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_audit_logs_path ON audit_logs(path, timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_logs_status ON audit_logs(status_code) WHERE status_code >= 400;
+
+COMMENT ON TABLE audit_logs IS 'Sampled HTTP request audit trail written by the audit middleware';
@@ -0,0 +1,25 @@
+-- User-uploaded colour palettes and fractal preset bundles.
+-- I'm storing palettes as normalised JSONB stop lists so the renderer never has to re-parse the original upload.
+
+CREATE TABLE IF NOT EXISTS palettes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name VARCHAR(100) NOT NULL,
+    source_format VARCHAR(16) NOT NULL, -- 'json' or 'ggr'
+    stops JSONB NOT NULL, -- [{"position": 0.0, "color": [r, g, b, a]}, ...]
+    stop_count INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT chk_palettes_stop_count CHECK (stop_count BETWEEN 2 AND 256)
+);
+
+CREATE TABLE IF NOT EXISTS fractal_presets (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name VARCHAR(100) NOT NULL,
+    description TEXT,
+    parameters JSONB NOT NULL DEFAULT '{}',
+    palette_id UUID REFERENCES palettes(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_palettes_created_at ON palettes(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_fractal_presets_created_at ON fractal_presets(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_fractal_presets_palette ON fractal_presets(palette_id) WHERE palette_id IS NOT NULL;
@@ -0,0 +1,16 @@
+-- Metadata for rendered fractal images persisted to the image store.
+-- I'm keying rows by the content hash so identical renders share one stored file and one URL.
+
+CREATE TABLE IF NOT EXISTS rendered_images (
+    id VARCHAR(64) PRIMARY KEY, -- hex SHA-256 prefix of the encoded image bytes
+    content_type VARCHAR(64) NOT NULL,
+    byte_size BIGINT NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    storage_key TEXT NOT NULL,
+    parameters JSONB DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_served_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_rendered_images_created_at ON rendered_images(created_at DESC);
@@ -0,0 +1,17 @@
+-- Notify listeners whenever a cached repository row changes.
+-- I'm publishing only owner and name so the payload stays far below the 8000 byte NOTIFY limit.
+
+CREATE OR REPLACE FUNCTION notify_repo_updated() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify(
+        'repo_updated',
+        json_build_object('owner', NEW.owner_login, 'name', NEW.name, 'operation', lower(TG_OP))::text
+    );
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_repositories_notify ON repositories;
+CREATE TRIGGER trg_repositories_notify
+    AFTER INSERT OR UPDATE ON repositories
+    FOR EACH ROW EXECUTE FUNCTION notify_repo_updated();
@@ -0,0 +1,29 @@
+-- Full-text search over cached repositories.
+-- I'm maintaining the tsvector from a trigger so every upsert path keeps it current, weighting name over description and topics over README.
+
+ALTER TABLE repositories ADD COLUMN IF NOT EXISTS search_vector tsvector;
+
+CREATE OR REPLACE FUNCTION repositories_search_vector_update() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.search_vector :=
+        setweight(to_tsvector('english', coalesce(NEW.name, '')), 'A') ||
+        setweight(to_tsvector('english', coalesce(NEW.description, '')), 'B') ||
+        setweight(to_tsvector('english', coalesce(array_to_string(NEW.topics, ' '), '')), 'B') ||
+        setweight(to_tsvector('english', coalesce(NEW.readme_content, '')), 'C');
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_repositories_search_vector ON repositories;
+CREATE TRIGGER trg_repositories_search_vector
+    BEFORE INSERT OR UPDATE OF name, description, topics, readme_content ON repositories
+    FOR EACH ROW EXECUTE FUNCTION repositories_search_vector_update();
+
+-- Backfill rows cached before this migration
+UPDATE repositories SET search_vector =
+    setweight(to_tsvector('english', coalesce(name, '')), 'A') ||
+    setweight(to_tsvector('english', coalesce(description, '')), 'B') ||
+    setweight(to_tsvector('english', coalesce(array_to_string(topics, ' '), '')), 'B') ||
+    setweight(to_tsvector('english', coalesce(readme_content, '')), 'C');
+
+CREATE INDEX IF NOT EXISTS idx_repositories_search_vector ON repositories USING GIN (search_vector);
@@ -0,0 +1,72 @@
+-- Precomputed per-owner collection statistics and language distribution.
+-- I'm refreshing this view after each repository sync so the stats endpoints read one row instead of scanning every cached repository.
+
+CREATE MATERIALIZED VIEW IF NOT EXISTS repository_collection_stats AS
+WITH scored AS (
+    -- Mirrors Repository::calculate_activity_score, evaluated at refresh time
+    SELECT r.*,
+        LEAST(GREATEST((
+            CASE WHEN stargazers_count > 0 THEN ln(stargazers_count::float8) * 10 ELSE 0 END
+            + CASE
+                WHEN pushed_at > NOW() - INTERVAL '30 days' THEN 20
+                WHEN pushed_at > NOW() - INTERVAL '90 days' THEN 10
+                ELSE 0
+              END
+            + CASE WHEN stargazers_count > 0 THEN forks_count::float8 / stargazers_count * 15 ELSE 0 END
+            - CASE WHEN open_issues_count > 0 THEN ln(open_issues_count::float8) * 2 ELSE 0 END
+            + CASE WHEN description IS NOT NULL THEN 5 ELSE 0 END
+            + COALESCE(cardinality(topics), 0) * 2
+            + CASE WHEN license_name IS NOT NULL THEN 5 ELSE 0 END
+        ) * CASE WHEN is_archived THEN 0.5 ELSE 1 END, 0), 100) AS activity_score
+    FROM repositories r
+),
+topic_counts AS (
+    SELECT owner_login, COUNT(DISTINCT topic)::int AS topics_count
+    FROM repositories, unnest(topics) AS topic
+    GROUP BY owner_login
+),
+language_totals AS (
+    -- Archived repositories and forks are left out so the distribution reflects active original work
+    SELECT owner_login, language,
+        COUNT(*)::int AS repository_count,
+        SUM(size_kb)::bigint AS total_size_kb,
+        SUM(stargazers_count)::int AS total_stars
+    FROM repositories
+    WHERE language IS NOT NULL AND NOT is_archived AND NOT is_fork
+    GROUP BY owner_login, language
+),
+language_lists AS (
+    SELECT owner_login,
+        jsonb_agg(jsonb_build_object(
+            'name', language,
+            'repository_count', repository_count,
+            'total_size_kb', total_size_kb,
+            'total_stars', total_stars
+        ) ORDER BY repository_count DESC, language) AS languages
+    FROM language_totals
+    GROUP BY owner_login
+)
+SELECT
+    s.owner_login,
+    COUNT(*)::int AS repository_count,
+    SUM(s.stargazers_count)::int AS total_stars,
+    SUM(s.forks_count)::int AS total_forks,
+    SUM(s.size_kb)::bigint AS total_size_kb,
+    AVG(s.stargazers_count)::float8 AS average_stars,
+    (array_agg(s.full_name ORDER BY s.stargazers_count DESC))[1] AS most_starred_repo,
+    (array_agg(s.full_name ORDER BY s.created_at DESC))[1] AS newest_repo,
+    (array_agg(s.full_name ORDER BY s.activity_score DESC))[1] AS most_active_repo,
+    COUNT(DISTINCT s.language)::int AS language_count,
+    COALESCE(t.topics_count, 0) AS topics_count,
+    (COUNT(*) FILTER (WHERE s.is_archived))::int AS archived_count,
+    (COUNT(*) FILTER (WHERE s.is_fork))::int AS fork_count,
+    COALESCE(SUM(s.size_kb) FILTER (WHERE NOT s.is_archived AND NOT s.is_fork), 0)::bigint AS language_size_kb,
+    COALESCE(l.languages, '[]'::jsonb) AS languages,
+    NOW() AS refreshed_at
+FROM scored s
+LEFT JOIN topic_counts t USING (owner_login)
+LEFT JOIN language_lists l USING (owner_login)
+GROUP BY s.owner_login, t.topics_count, l.languages;
+
+-- REFRESH ... CONCURRENTLY needs a unique index
+CREATE UNIQUE INDEX IF NOT EXISTS idx_repository_collection_stats_owner ON repository_collection_stats(owner_login);
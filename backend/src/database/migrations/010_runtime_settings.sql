@@ -0,0 +1,21 @@
+-- Operational settings overridden at runtime through the admin API, and the history of every change
+
+CREATE TABLE IF NOT EXISTS runtime_settings (
+    key VARCHAR(64) PRIMARY KEY,
+    value JSONB NOT NULL,
+    updated_by TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS runtime_setting_changes (
+    id BIGSERIAL PRIMARY KEY,
+    key VARCHAR(64) NOT NULL,
+    -- NULL on either side means the setting fell back to its environment value
+    old_value JSONB,
+    new_value JSONB,
+    changed_by TEXT NOT NULL,
+    reason TEXT,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_runtime_setting_changes_key ON runtime_setting_changes (key, changed_at DESC);
@@ -0,0 +1,13 @@
+-- Feature flags gating new behaviour, with percentage rollouts and per-principal overrides.
+-- Flag edits are recorded in runtime_setting_changes under the key feature_flag:<name>.
+
+CREATE TABLE IF NOT EXISTS feature_flags (
+    name VARCHAR(48) PRIMARY KEY,
+    description TEXT,
+    enabled BOOLEAN NOT NULL DEFAULT false,
+    rollout_percentage SMALLINT NOT NULL DEFAULT 100 CHECK (rollout_percentage BETWEEN 0 AND 100),
+    allow_principals TEXT[] NOT NULL DEFAULT '{}',
+    deny_principals TEXT[] NOT NULL DEFAULT '{}',
+    updated_by TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
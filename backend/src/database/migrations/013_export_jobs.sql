@@ -0,0 +1,16 @@
+-- Background export jobs: what was asked for, where the worker got to, and how many rows the finished file holds
+
+CREATE TABLE IF NOT EXISTS export_jobs (
+    id UUID PRIMARY KEY,
+    dataset VARCHAR(32) NOT NULL CHECK (dataset IN ('fractal_computations', 'performance_metrics')),
+    filters JSONB NOT NULL DEFAULT '{}'::jsonb,
+    status VARCHAR(16) NOT NULL CHECK (status IN ('queued', 'running', 'succeeded', 'failed')),
+    row_count BIGINT,
+    -- Last failure; kept while a retry is queued
+    error TEXT,
+    requested_by TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_export_jobs_created_at ON export_jobs (created_at DESC);
@@ -0,0 +1,29 @@
+-- Anonymous visitor sessions and the renders each one made, backing GET /api/sessions/history
+-- I'm creating the session row on first render rather than first request so crawlers and health checks leave nothing behind
+
+CREATE TABLE IF NOT EXISTS sessions (
+    id UUID PRIMARY KEY,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_sessions_last_seen_at ON sessions (last_seen_at);
+
+CREATE TABLE IF NOT EXISTS session_history (
+    id UUID PRIMARY KEY,
+    session_id UUID NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+    fractal_type VARCHAR(32) NOT NULL,
+    -- Everything needed to request the same render again
+    parameters JSONB NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    zoom_level DOUBLE PRECISION NOT NULL,
+    computation_time_ms BIGINT NOT NULL,
+    renderer VARCHAR(32) NOT NULL,
+    -- Set when the render was persisted to the image store
+    image_id VARCHAR(64),
+    image_url TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_session_history_session ON session_history (session_id, created_at DESC);
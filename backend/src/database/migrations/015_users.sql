@@ -0,0 +1,35 @@
+-- User accounts, their personal API keys, and the daily counters behind per-user render quotas
+-- I'm storing only a SHA-256 of each key, so a leaked table can't be replayed against the API
+
+CREATE TABLE IF NOT EXISTS users (
+    id UUID PRIMARY KEY,
+    username VARCHAR(39) NOT NULL UNIQUE,
+    email VARCHAR(255),
+    -- GitHub's numeric id stays stable across renames, so accounts link on it rather than the login
+    github_id BIGINT UNIQUE,
+    github_login VARCHAR(39),
+    render_quota_daily INTEGER NOT NULL CHECK (render_quota_daily >= 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS api_keys (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(64) NOT NULL,
+    key_hash CHAR(64) NOT NULL UNIQUE,
+    -- The first characters of the key, enough for a user to recognise it in a list
+    prefix VARCHAR(16) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_keys_user ON api_keys (user_id, created_at DESC);
+
+CREATE TABLE IF NOT EXISTS render_usage (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    renders INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (user_id, day)
+);
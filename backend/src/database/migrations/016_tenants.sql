@@ -0,0 +1,18 @@
+-- Tenants hosting their own showcase on this deployment, resolved from a hostname or a /t/<slug> path prefix.
+-- The deployment's own GITHUB_USERNAME stays the implicit default tenant and never gets a row.
+
+CREATE TABLE IF NOT EXISTS tenants (
+    slug VARCHAR(32) PRIMARY KEY,
+    display_name VARCHAR(100) NOT NULL,
+    -- Repository rows are already keyed by owner, so this is what scopes a tenant's data
+    github_username VARCHAR(39) NOT NULL,
+    hostnames TEXT[] NOT NULL DEFAULT '{}',
+    -- Runtime-tunable settings layered over the deployment's values for this tenant only
+    settings JSONB NOT NULL DEFAULT '{}',
+    enabled BOOLEAN NOT NULL DEFAULT true,
+    updated_by TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_tenants_hostnames ON tenants USING GIN (hostnames);
@@ -0,0 +1,12 @@
+-- Operator overrides for the text of alert notifications, one tera template per rule and channel.
+-- A rule of '*' covers every rule without its own template; anything not stored here uses the built-in wording.
+
+CREATE TABLE IF NOT EXISTS notification_templates (
+    rule VARCHAR(64) NOT NULL,
+    -- email_subject, email_body, or webhook
+    channel VARCHAR(16) NOT NULL,
+    template TEXT NOT NULL,
+    updated_by TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (rule, channel)
+);
@@ -0,0 +1,21 @@
+-- Sanitized request/response pairs captured by the opt-in recording mode, replayable from the admin API.
+-- Credentials are redacted before a row is written; bodies over the size limit or not text are kept as a byte count only.
+
+CREATE TABLE IF NOT EXISTS request_recordings (
+    id UUID PRIMARY KEY,
+    method VARCHAR(16) NOT NULL,
+    path TEXT NOT NULL,
+    query TEXT,
+    tenant VARCHAR(64) NOT NULL,
+    request_headers JSONB NOT NULL DEFAULT '{}',
+    request_body TEXT,
+    request_body_bytes BIGINT,
+    status INTEGER NOT NULL,
+    response_headers JSONB NOT NULL DEFAULT '{}',
+    response_body TEXT,
+    response_body_bytes BIGINT,
+    duration_ms BIGINT NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_request_recordings_recorded_at ON request_recordings (recorded_at DESC);
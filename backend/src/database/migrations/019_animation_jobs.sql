@@ -0,0 +1,16 @@
+-- Zoom animations too large to render inside a request: the keyframes asked for, the output format, and where the worker got to
+
+CREATE TABLE IF NOT EXISTS animation_jobs (
+    id UUID PRIMARY KEY,
+    format VARCHAR(8) NOT NULL CHECK (format IN ('gif', 'mp4')),
+    parameters JSONB NOT NULL,
+    frame_count INTEGER NOT NULL,
+    status VARCHAR(16) NOT NULL CHECK (status IN ('queued', 'running', 'succeeded', 'failed')),
+    byte_size BIGINT,
+    -- Last failure; kept while a retry is queued
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_animation_jobs_created_at ON animation_jobs (created_at DESC);
@@ -0,0 +1,55 @@
+-- Curated exploration targets behind /api/fractals/presets: notable Julia constants and Mandelbrot-family deep-zoom coordinates.
+-- Rows seeded here are marked curated; operators can add, edit, or remove any of them through the admin-only write endpoints.
+
+CREATE TABLE IF NOT EXISTS catalog_presets (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name VARCHAR(80) NOT NULL UNIQUE,
+    kind VARCHAR(16) NOT NULL CHECK (kind IN ('julia_constant', 'deep_zoom')),
+    fractal_type VARCHAR(16) NOT NULL CHECK (fractal_type IN ('mandelbrot', 'julia', 'burning_ship', 'tricorn', 'multibrot')),
+    -- The Julia constant, set exactly when fractal_type is julia
+    c_real DOUBLE PRECISION,
+    c_imag DOUBLE PRECISION,
+    -- Exponent for multibrot targets
+    power DOUBLE PRECISION,
+    center_x DOUBLE PRECISION NOT NULL,
+    center_y DOUBLE PRECISION NOT NULL,
+    zoom DOUBLE PRECISION NOT NULL,
+    max_iterations INTEGER NOT NULL,
+    description TEXT,
+    tags TEXT[] NOT NULL DEFAULT '{}',
+    curated BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_catalog_presets_kind ON catalog_presets (kind, fractal_type);
+
+INSERT INTO catalog_presets (name, kind, fractal_type, c_real, c_imag, center_x, center_y, zoom, max_iterations, description, tags, curated) VALUES
+    ('Douady rabbit', 'julia_constant', 'julia', -0.123, 0.745, 0.0, 0.0, 1.0, 500,
+        'Three-lobed basins meeting at every junction, from the period-3 bulb', '{connected,period-3}', true),
+    ('Basilica', 'julia_constant', 'julia', -1.0, 0.0, 0.0, 0.0, 1.0, 500,
+        'Period-2 bulb centre: a chain of pinched discs', '{connected,period-2}', true),
+    ('San Marco', 'julia_constant', 'julia', -0.75, 0.0, 0.0, 0.0, 1.0, 1000,
+        'Where the main cardioid meets the period-2 bulb; converges slowly near the pinches', '{connected,parabolic}', true),
+    ('Dendrite', 'julia_constant', 'julia', 0.0, 1.0, 0.0, 0.0, 1.0, 500,
+        'c = i, a Misiurewicz point: a branching tree with no interior', '{dendrite,misiurewicz}', true),
+    ('Siegel disk', 'julia_constant', 'julia', -0.390541, -0.586788, 0.0, 0.0, 1.0, 1000,
+        'Orbits rotate around a disc at the golden-mean angle instead of settling', '{connected,siegel}', true),
+    ('Airplane', 'julia_constant', 'julia', -1.7549, 0.0, 0.0, 0.0, 1.0, 500,
+        'The real period-3 window: a flat fuselage with swept wings', '{connected,period-3}', true),
+    ('Spiral galaxies', 'julia_constant', 'julia', 0.285, 0.01, 0.0, 0.0, 1.0, 800,
+        'Just outside the cardioid, so the set breaks into spiralling dust', '{disconnected,spiral}', true),
+    ('Dragon spirals', 'julia_constant', 'julia', -0.8, 0.156, 0.0, 0.0, 1.0, 800,
+        'Tightly wound spirals either side of the period-2 bulb', '{connected,spiral}', true);
+
+INSERT INTO catalog_presets (name, kind, fractal_type, center_x, center_y, zoom, max_iterations, description, tags, curated) VALUES
+    ('Seahorse valley', 'deep_zoom', 'mandelbrot', -0.7436438870371587, 0.13182590420531198, 1e12, 6000,
+        'Deep in the valley between the cardioid and the period-2 bulb, where seahorse tails wind around minibrots', '{spiral,minibrot}', true),
+    ('Elephant valley', 'deep_zoom', 'mandelbrot', 0.2869318688950451, 0.014286693904085048, 1e5, 2000,
+        'Trunks curling off the cardioid''s right-hand cusp', '{spiral}', true),
+    ('Misiurewicz spiral', 'deep_zoom', 'mandelbrot', -0.77568377, 0.13646737, 1e6, 3000,
+        'A Misiurewicz point whose neighbourhood repeats itself at every zoom', '{misiurewicz,self-similar}', true),
+    ('Dendrite tip', 'deep_zoom', 'mandelbrot', 0.0, 1.0, 1e4, 2000,
+        'c = i, where the set looks like the Dendrite Julia set at every scale', '{misiurewicz,dendrite}', true),
+    ('Burning ship armada', 'deep_zoom', 'burning_ship', -1.762, -0.028, 30.0, 1000,
+        'The small ships trailing the main hull along the real axis', '{minibrot}', true);
@@ -0,0 +1,20 @@
+-- Saved fractal views behind /api/fractals/saved: a render's parameters kept under a short id that anyone with the link can open.
+-- I'm storing the request as the engine resolved it, palette inline, so a shared view keeps rendering the same after the palette it named changes.
+
+CREATE TABLE IF NOT EXISTS saved_fractals (
+    id VARCHAR(16) PRIMARY KEY, -- short base62 id that share links carry
+    title VARCHAR(80),
+    fractal_type VARCHAR(16) NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    request JSONB NOT NULL,
+    -- The stored render, set the first time the image is asked for
+    image_id VARCHAR(64) REFERENCES rendered_images(id) ON DELETE SET NULL,
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    view_count BIGINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_viewed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_saved_fractals_created_at ON saved_fractals (created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_saved_fractals_popular ON saved_fractals (view_count DESC, created_at DESC);
@@ -0,0 +1,24 @@
+-- Tenant ownership for the rows visitors create: palettes, presets, sessions, user accounts, and saved views.
+-- I'm defaulting existing rows to the implicit default tenant, which has no tenants row, so there's no foreign key to point at.
+
+ALTER TABLE palettes ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(32) NOT NULL DEFAULT 'default';
+ALTER TABLE fractal_presets ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(32) NOT NULL DEFAULT 'default';
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(32) NOT NULL DEFAULT 'default';
+ALTER TABLE users ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(32) NOT NULL DEFAULT 'default';
+ALTER TABLE saved_fractals ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(32) NOT NULL DEFAULT 'default';
+
+-- Usernames and GitHub links are unique within a tenant, so the same person can hold an account on each showcase
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_username_key;
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_github_id_key;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_tenant_username ON users (tenant_id, username);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_tenant_github_id ON users (tenant_id, github_id) WHERE github_id IS NOT NULL;
+
+DROP INDEX IF EXISTS idx_palettes_created_at;
+CREATE INDEX IF NOT EXISTS idx_palettes_tenant_created_at ON palettes (tenant_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_fractal_presets_tenant ON fractal_presets (tenant_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_sessions_tenant ON sessions (tenant_id);
+
+DROP INDEX IF EXISTS idx_saved_fractals_created_at;
+DROP INDEX IF EXISTS idx_saved_fractals_popular;
+CREATE INDEX IF NOT EXISTS idx_saved_fractals_tenant_created_at ON saved_fractals (tenant_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_saved_fractals_tenant_popular ON saved_fractals (tenant_id, view_count DESC, created_at DESC);
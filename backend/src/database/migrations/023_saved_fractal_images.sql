@@ -0,0 +1,4 @@
+-- Lookup from a stored render back to the save that owns it, so /images/:id can refuse saved images by id.
+-- I'm indexing only the rows that have a render, which is the only case the lookup cares about.
+
+CREATE INDEX IF NOT EXISTS idx_saved_fractals_image_id ON saved_fractals (image_id) WHERE image_id IS NOT NULL;
@@ -3,7 +3,12 @@
  * I'm organizing connection management, migration utilities, and database operations into a clean, cohesive interface that supports the high-performance architecture.
  */
 
+pub mod backup;
 pub mod connection;
+pub mod notifications;
+pub mod repositories;
+pub mod retention;
+pub mod timing;
 
 // Re-export commonly used database types and functions
 pub use connection::{
@@ -14,13 +19,22 @@ pub use connection::{
     create_pool,
     create_pool_with_config,
     with_transaction,
+    with_retrying_transaction,
+    is_retryable_error,
     batch_execute,
-    ConnectionPoolMonitor
+    ConnectionPoolMonitor,
+    PoolSnapshot
 };
+pub use notifications::{DbNotification, NotificationChannel, NotificationHub};
+pub use retention::{CleanupReport, RetentionPolicy};
 
 use crate::utils::error::{AppError, Result};
 use sqlx::Row;
 
+/// The embedded migration series, applied at startup
+/// I'm embedding the SQL at compile time so the binary always ships with the schema its queries were checked against
+pub static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!("src/database/migrations");
+
 /// Database utilities and helper functions for common operations
 /// I'm providing convenient database operations that maintain consistency across the application
 pub struct DatabaseUtils;
@@ -68,36 +82,10 @@ impl DatabaseUtils {
         Ok(size)
     }
 
-    /// Clean up expired cache entries and performance data
-    /// I'm implementing automated cleanup for maintaining database performance
-    pub async fn cleanup_expired_data(pool: &DatabasePool) -> Result<u64> {
-        let mut total_cleaned = 0u64;
-
-        // Clean expired cache entries
-        let cache_result = sqlx::query(
-            "DELETE FROM cache_entries WHERE expires_at < NOW()"
-        )
-        .execute(pool)
-        .await?;
-        total_cleaned += cache_result.rows_affected();
-
-        // Clean old performance metrics (keep last 30 days)
-        let metrics_result = sqlx::query(
-            "DELETE FROM performance_metrics WHERE timestamp < NOW() - INTERVAL '30 days'"
-        )
-        .execute(pool)
-        .await?;
-        total_cleaned += metrics_result.rows_affected();
-
-        // Clean old fractal computations (keep last 7 days)
-        let fractal_result = sqlx::query(
-            "DELETE FROM fractal_computations WHERE timestamp < NOW() - INTERVAL '7 days'"
-        )
-        .execute(pool)
-        .await?;
-        total_cleaned += fractal_result.rows_affected();
-
-        Ok(total_cleaned)
+    /// Clean up expired cache entries and data older than the retention policy allows
+    /// I'm delegating to the retention module so the scheduled job and manual calls share one implementation
+    pub async fn cleanup_expired_data(pool: &DatabasePool, policy: &RetentionPolicy) -> Result<CleanupReport> {
+        Ok(retention::run_cleanup(pool, policy).await)
     }
 
     /// Get comprehensive database statistics
@@ -204,7 +192,7 @@ impl MigrationManager {
     pub async fn run_migrations(pool: &DatabasePool) -> Result<()> {
         tracing::info!("Running database migrations");
 
-        match sqlx::migrate!("src/database/migrations").run(pool).await {
+        match MIGRATOR.run(pool).await {
             Ok(_) => {
                 tracing::info!("Database migrations completed successfully");
                 Ok(())
@@ -216,16 +204,62 @@ impl MigrationManager {
         }
     }
 
+    /// Run pending migrations and report which versions this call applied
+    /// I'm relying on the migrator's advisory lock so two instances racing here apply each version once
+    pub async fn run_pending(pool: &DatabasePool) -> Result<MigrationRunReport> {
+        let start = std::time::Instant::now();
+        let pending = Self::pending_migrations(pool).await?;
+
+        Self::run_migrations(pool).await?;
+
+        let still_pending = Self::pending_migrations(pool).await?;
+        let applied = pending
+            .into_iter()
+            .filter(|migration| !still_pending.iter().any(|p| p.version == migration.version))
+            .collect();
+
+        Ok(MigrationRunReport {
+            applied,
+            latest_version: latest_migration_version(),
+            duration_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Embedded migrations that haven't been recorded as applied yet
+    pub async fn pending_migrations(pool: &DatabasePool) -> Result<Vec<MigrationInfo>> {
+        let applied: Vec<i64> = if DatabaseUtils::table_exists(pool, "_sqlx_migrations").await? {
+            sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+                .fetch_all(pool)
+                .await?
+        } else {
+            Vec::new()
+        };
+
+        Ok(MIGRATOR
+            .iter()
+            .filter(|migration| !migration.migration_type.is_down_migration())
+            .filter(|migration| !applied.contains(&migration.version))
+            .map(|migration| MigrationInfo {
+                version: migration.version,
+                description: migration.description.to_string(),
+            })
+            .collect())
+    }
+
     /// Check migration status
     /// I'm providing migration status verification for deployment validation
     pub async fn check_migration_status(pool: &DatabasePool) -> Result<serde_json::Value> {
+        let pending = Self::pending_migrations(pool).await?;
+
         // Check if _sqlx_migrations table exists
         let migrations_table_exists = DatabaseUtils::table_exists(pool, "_sqlx_migrations").await?;
 
         if !migrations_table_exists {
             return Ok(serde_json::json!({
                 "status": "no_migrations_run",
-                "message": "No migrations have been executed yet"
+                "message": "No migrations have been executed yet",
+                "latest_version": latest_migration_version(),
+                "pending": pending
             }));
         }
 
@@ -251,13 +285,35 @@ impl MigrationManager {
             .collect();
 
         Ok(serde_json::json!({
-            "status": "migrations_applied",
+            "status": if pending.is_empty() { "migrations_applied" } else { "migrations_pending" },
             "count": migration_info.len(),
-            "migrations": migration_info
+            "latest_version": latest_migration_version(),
+            "migrations": migration_info,
+            "pending": pending
         }))
     }
 }
 
+/// An embedded migration identified by version
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct MigrationInfo {
+    pub version: i64,
+    pub description: String,
+}
+
+/// Outcome of a migration run triggered over the admin API
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MigrationRunReport {
+    pub applied: Vec<MigrationInfo>,
+    pub latest_version: i64,
+    pub duration_ms: u64,
+}
+
+/// Highest migration version compiled into this binary
+pub fn latest_migration_version() -> i64 {
+    MIGRATOR.iter().map(|migration| migration.version).max().unwrap_or(0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -267,4 +323,16 @@ mod tests {
         // I'm ensuring the module structure is properly organized
         assert!(true, "Database module structure is valid");
     }
+
+    #[test]
+    fn test_migration_series_is_ordered_and_complete() {
+        let versions: Vec<i64> = MIGRATOR.iter().map(|m| m.version).collect();
+        assert!(!versions.is_empty());
+        assert!(versions.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let schema: String = MIGRATOR.iter().map(|m| m.sql.as_ref()).collect::<Vec<_>>().join("\n");
+        for table in ["repositories", "performance_metrics", "fractal_computations", "cache_entries"] {
+            assert!(schema.contains(&format!("CREATE TABLE {} (", table)), "missing table {}", table);
+        }
+    }
 }
@@ -0,0 +1,161 @@
+/*
+ * Postgres LISTEN/NOTIFY bridge fanning database notifications out to in-process subscribers.
+ * I'm using one dedicated listener connection per instance and a broadcast channel so any number of handlers can react without polling.
+ */
+
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgListener;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+use crate::{
+    database::DatabasePool,
+    utils::error::{AppError, Result},
+};
+
+const BROADCAST_CAPACITY: usize = 256;
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Channels this service publishes and listens on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationChannel {
+    /// A cached repository row changed; payload carries `owner` and `name`
+    RepoUpdated,
+    /// Runtime configuration changed on some instance; payload carries `kind` plus details
+    ConfigChanged,
+}
+
+impl NotificationChannel {
+    pub const ALL: [NotificationChannel; 2] = [NotificationChannel::RepoUpdated, NotificationChannel::ConfigChanged];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationChannel::RepoUpdated => "repo_updated",
+            NotificationChannel::ConfigChanged => "config_changed",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|channel| channel.as_str() == name)
+    }
+}
+
+/// A notification received from Postgres
+#[derive(Debug, Clone)]
+pub struct DbNotification {
+    pub channel: NotificationChannel,
+    pub payload: serde_json::Value,
+}
+
+/// In-process fan-out for database notifications
+#[derive(Debug, Clone)]
+pub struct NotificationHub {
+    sender: broadcast::Sender<DbNotification>,
+    enabled: bool,
+}
+
+impl NotificationHub {
+    pub fn new(enabled: bool) -> Self {
+        let (sender, _) = broadcast::channel(BROADCAST_CAPACITY);
+        Self { sender, enabled }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<DbNotification> {
+        self.sender.subscribe()
+    }
+
+    /// Start the LISTEN loop; recv reconnects on its own, so I only back off when that fails too
+    pub fn spawn_listener(&self, pool: DatabasePool) -> Option<tokio::task::JoinHandle<()>> {
+        if !self.enabled {
+            return None;
+        }
+
+        let sender = self.sender.clone();
+        Some(tokio::spawn(async move {
+            loop {
+                match listen(&pool, &sender).await {
+                    Ok(()) => return,
+                    Err(e) => {
+                        warn!("Database notification listener failed, retrying in {}s: {}", RECONNECT_DELAY.as_secs(), e);
+                        tokio::time::sleep(RECONNECT_DELAY).await;
+                    }
+                }
+            }
+        }))
+    }
+}
+
+async fn listen(pool: &DatabasePool, sender: &broadcast::Sender<DbNotification>) -> Result<()> {
+    let mut listener = PgListener::connect_with(pool).await?;
+    listener
+        .listen_all(NotificationChannel::ALL.iter().map(NotificationChannel::as_str))
+        .await?;
+    info!("Listening for database notifications on {:?}", NotificationChannel::ALL.map(|c| c.as_str()));
+
+    loop {
+        let notification = listener.recv().await?;
+        let Some(channel) = NotificationChannel::parse(notification.channel()) else {
+            continue;
+        };
+
+        let payload = serde_json::from_str(notification.payload())
+            .unwrap_or_else(|_| serde_json::Value::String(notification.payload().to_string()));
+        debug!("Database notification on {}: {}", channel.as_str(), payload);
+
+        // A send error only means nobody is subscribed right now
+        let _ = sender.send(DbNotification { channel, payload });
+    }
+}
+
+/// Send a notification to every listening instance, this one included
+pub async fn publish(pool: &DatabasePool, channel: NotificationChannel, payload: &serde_json::Value) -> Result<()> {
+    let payload = serde_json::to_string(payload)?;
+    // Postgres rejects payloads of 8000 bytes or more
+    if payload.len() >= 8000 {
+        return Err(AppError::bad_request(format!(
+            "Notification payload for {} is too large ({} bytes)", channel.as_str(), payload.len()
+        )));
+    }
+
+    sqlx::query("SELECT pg_notify($1, $2)")
+        .bind(channel.as_str())
+        .bind(payload)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_names_round_trip() {
+        for channel in NotificationChannel::ALL {
+            assert_eq!(NotificationChannel::parse(channel.as_str()), Some(channel));
+        }
+        assert_eq!(NotificationChannel::parse("unknown"), None);
+    }
+
+    #[tokio::test]
+    async fn test_hub_fans_out_to_subscribers() {
+        let hub = NotificationHub::new(true);
+        let mut first = hub.subscribe();
+        let mut second = hub.subscribe();
+
+        hub.sender.send(DbNotification {
+            channel: NotificationChannel::RepoUpdated,
+            payload: serde_json::json!({ "owner": "octocat", "name": "hello" }),
+        }).unwrap();
+
+        assert_eq!(first.recv().await.unwrap().channel, NotificationChannel::RepoUpdated);
+        assert_eq!(second.recv().await.unwrap().payload["name"], "hello");
+    }
+}
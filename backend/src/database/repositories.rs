@@ -0,0 +1,426 @@
+/*
+ * Typed repository queries over the Postgres cache with filtering, sorting, and pagination pushed into SQL.
+ * I'm building the WHERE clause with QueryBuilder so every user-supplied value is bound rather than interpolated.
+ */
+
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, Postgres, QueryBuilder, Row};
+
+use crate::{
+    database::{timing, DatabasePool},
+    models::github::{CollectionStats, Repository, RepositoryFilter, RepositorySort},
+    utils::error::{AppError, Result},
+};
+
+const REPOSITORY_COLUMNS: &str = "id, github_id, owner_login, name, full_name, description, html_url, clone_url, ssh_url, \
+    language, size_kb, stargazers_count, watchers_count, forks_count, open_issues_count, \
+    created_at, updated_at, pushed_at, is_private, is_fork, is_archived, topics, \
+    license_name, readme_content, cache_updated_at, cache_expires_at";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+impl SortDirection {
+    pub fn parse(value: &str) -> Self {
+        if value.eq_ignore_ascii_case("asc") { SortDirection::Asc } else { SortDirection::Desc }
+    }
+
+    fn as_sql(self) -> &'static str {
+        match self {
+            SortDirection::Asc => "ASC",
+            SortDirection::Desc => "DESC",
+        }
+    }
+}
+
+/// One page of repositories for an owner
+#[derive(Debug, Clone)]
+pub struct RepositoryListQuery<'a> {
+    pub owner: &'a str,
+    pub filter: &'a RepositoryFilter,
+    pub sort: RepositorySort,
+    pub direction: SortDirection,
+    pub limit: i64,
+    pub offset: i64,
+    /// Include rows whose cache entry has expired, used when GitHub is unreachable
+    pub include_expired: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct RepositoryPage {
+    pub repositories: Vec<Repository>,
+    pub total_count: i64,
+    /// Oldest cache timestamp among the owner's matching rows
+    pub cache_updated_at: Option<DateTime<Utc>>,
+    /// Earliest expiry among the owner's matching rows
+    pub cache_expires_at: Option<DateTime<Utc>>,
+}
+
+/// Fetch a filtered, sorted page plus the total count for pagination
+pub async fn list_repositories(pool: &DatabasePool, query: &RepositoryListQuery<'_>) -> Result<RepositoryPage> {
+    let mut count_builder = QueryBuilder::<Postgres>::new(
+        "SELECT COUNT(*) AS total, MIN(cache_updated_at) AS cache_updated_at, MIN(cache_expires_at) AS cache_expires_at FROM repositories",
+    );
+    push_conditions(&mut count_builder, query);
+
+    let summary = timing::timed("repositories_count", count_builder.build().fetch_one(pool))
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to count repositories: {}", e)))?;
+
+    let total_count: i64 = summary.try_get("total")?;
+    if total_count == 0 {
+        return Ok(RepositoryPage {
+            repositories: Vec::new(),
+            total_count,
+            cache_updated_at: None,
+            cache_expires_at: None,
+        });
+    }
+
+    let mut builder = QueryBuilder::<Postgres>::new(format!("SELECT {} FROM repositories", REPOSITORY_COLUMNS));
+    push_conditions(&mut builder, query);
+    builder.push(format!(
+        " ORDER BY {} {} NULLS LAST, github_id ASC",
+        sort_column(&query.sort),
+        query.direction.as_sql()
+    ));
+    builder.push(" LIMIT ").push_bind(query.limit.max(1));
+    builder.push(" OFFSET ").push_bind(query.offset.max(0));
+
+    let repositories = timing::timed("repositories_list", builder.build_query_as::<Repository>().fetch_all(pool))
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to fetch repositories from database: {}", e)))?;
+
+    Ok(RepositoryPage {
+        repositories,
+        total_count,
+        cache_updated_at: summary.try_get("cache_updated_at")?,
+        cache_expires_at: summary.try_get("cache_expires_at")?,
+    })
+}
+
+/// Every cached repository for an owner, newest first
+pub async fn list_all_for_owner(pool: &DatabasePool, owner: &str, include_expired: bool) -> Result<Vec<Repository>> {
+    let filter = RepositoryFilter::default();
+    let query = RepositoryListQuery {
+        owner,
+        filter: &filter,
+        sort: RepositorySort::Updated,
+        direction: SortDirection::Desc,
+        limit: i64::MAX,
+        offset: 0,
+        include_expired,
+    };
+
+    Ok(list_repositories(pool, &query).await?.repositories)
+}
+
+/// Number of repositories for an owner whose cache entry hasn't expired
+pub async fn count_fresh(pool: &DatabasePool, owner: &str) -> Result<i64> {
+    let query = sqlx::query(
+        "SELECT COUNT(*) AS total FROM repositories WHERE owner_login = $1 AND cache_expires_at > CURRENT_TIMESTAMP",
+    )
+    .bind(owner);
+
+    let row = timing::timed("repositories_count_fresh", query.fetch_one(pool))
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to count cached repositories: {}", e)))?;
+
+    Ok(row.try_get("total")?)
+}
+
+/// Keep a fetched README on the repository's row, where the search trigger indexes it
+pub async fn store_readme(pool: &DatabasePool, owner: &str, name: &str, readme: &str) -> Result<()> {
+    let query = sqlx::query("UPDATE repositories SET readme_content = $3 WHERE owner_login = $1 AND name = $2 AND readme_content IS DISTINCT FROM $3")
+        .bind(owner)
+        .bind(name)
+        .bind(readme);
+
+    timing::timed("repositories_store_readme", query.execute(pool))
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to store README for {}/{}: {}", owner, name, e)))?;
+
+    Ok(())
+}
+
+pub async fn find_by_name(pool: &DatabasePool, owner: &str, name: &str) -> Result<Option<Repository>> {
+    let sql = format!("SELECT {} FROM repositories WHERE owner_login = $1 AND name = $2 LIMIT 1", REPOSITORY_COLUMNS);
+    let query = sqlx::query_as::<_, Repository>(&sql).bind(owner).bind(name);
+
+    timing::timed("repositories_find_by_name", query.fetch_optional(pool))
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to fetch repository {}/{}: {}", owner, name, e)))
+}
+
+/// One ranked full-text match
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RepositorySearchHit {
+    pub repository: Repository,
+    pub rank: f32,
+    /// Matching fragments of the description and README with terms wrapped in <mark>
+    pub snippet: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RepositorySearchQuery<'a> {
+    pub owner: Option<&'a str>,
+    /// Web-search syntax: quoted phrases, `or`, and `-excluded` terms
+    pub terms: &'a str,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// Ranked full-text search over name, description, topics, and README
+/// I'm using websearch_to_tsquery so arbitrary user input parses instead of raising a syntax error
+pub async fn search_repositories(
+    pool: &DatabasePool,
+    query: &RepositorySearchQuery<'_>,
+) -> Result<(Vec<RepositorySearchHit>, i64)> {
+    let total_sql = "SELECT COUNT(*) AS total FROM repositories \
+        WHERE search_vector @@ websearch_to_tsquery('english', $1) AND ($2::text IS NULL OR owner_login = $2)";
+    let total_query = sqlx::query(total_sql).bind(query.terms).bind(query.owner);
+    let total: i64 = timing::timed("repositories_search_count", total_query.fetch_one(pool))
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to count search results: {}", e)))?
+        .try_get("total")?;
+
+    if total == 0 {
+        return Ok((Vec::new(), 0));
+    }
+
+    let sql = format!(
+        "SELECT {columns}, \
+            ts_rank_cd(search_vector, q) AS rank, \
+            NULLIF(ts_headline('english', concat_ws(' ', description, readme_content), q, \
+                'StartSel=<mark>, StopSel=</mark>, MaxFragments=2, MaxWords=24, MinWords=8, FragmentDelimiter=\" ... \"'), '') AS snippet \
+        FROM repositories, websearch_to_tsquery('english', $1) AS q \
+        WHERE search_vector @@ q AND ($2::text IS NULL OR owner_login = $2) \
+        ORDER BY rank DESC, stargazers_count DESC, github_id ASC \
+        LIMIT $3 OFFSET $4",
+        columns = REPOSITORY_COLUMNS
+    );
+    let search = sqlx::query(&sql)
+        .bind(query.terms)
+        .bind(query.owner)
+        .bind(query.limit.max(1))
+        .bind(query.offset.max(0));
+
+    let rows = timing::timed("repositories_search", search.fetch_all(pool))
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to search repositories: {}", e)))?;
+
+    let hits = rows
+        .iter()
+        .map(|row| {
+            Ok(RepositorySearchHit {
+                repository: Repository::from_row(row)?,
+                rank: row.try_get("rank")?,
+                snippet: row.try_get("snippet")?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok((hits, total))
+}
+
+/// Per-language totals from the collection stats view
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct LanguageTotal {
+    pub name: String,
+    pub repository_count: i32,
+    pub total_size_kb: i64,
+    pub total_stars: i32,
+}
+
+/// One owner's row from the `repository_collection_stats` materialized view
+#[derive(Debug, Clone)]
+pub struct CollectionSummary {
+    pub repository_count: i32,
+    pub stats: CollectionStats,
+    /// Languages of non-archived, non-fork repositories, most used first
+    pub languages: Vec<LanguageTotal>,
+    /// Combined size of the repositories the language breakdown covers
+    pub language_size_kb: i64,
+    pub refreshed_at: DateTime<Utc>,
+}
+
+/// Precomputed stats for an owner, or None when nothing is cached for them yet
+pub async fn collection_summary(pool: &DatabasePool, owner: &str) -> Result<Option<CollectionSummary>> {
+    let query = sqlx::query("SELECT * FROM repository_collection_stats WHERE owner_login = $1").bind(owner);
+    let Some(row) = timing::timed("collection_stats_read", query.fetch_optional(pool))
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to read collection stats: {}", e)))?
+    else {
+        return Ok(None);
+    };
+
+    let languages: serde_json::Value = row.try_get("languages")?;
+    Ok(Some(CollectionSummary {
+        repository_count: row.try_get("repository_count")?,
+        stats: CollectionStats {
+            total_stars: row.try_get("total_stars")?,
+            total_forks: row.try_get("total_forks")?,
+            total_size_kb: row.try_get("total_size_kb")?,
+            average_stars: row.try_get("average_stars")?,
+            most_starred_repo: row.try_get::<Option<String>, _>("most_starred_repo")?.unwrap_or_default(),
+            newest_repo: row.try_get::<Option<String>, _>("newest_repo")?.unwrap_or_default(),
+            most_active_repo: row.try_get::<Option<String>, _>("most_active_repo")?.unwrap_or_default(),
+            language_count: row.try_get("language_count")?,
+            topics_count: row.try_get("topics_count")?,
+            archived_count: row.try_get("archived_count")?,
+            fork_count: row.try_get("fork_count")?,
+        },
+        languages: serde_json::from_value(languages)?,
+        language_size_kb: row.try_get("language_size_kb")?,
+        refreshed_at: row.try_get("refreshed_at")?,
+    }))
+}
+
+/// Recompute the collection stats view without blocking readers
+pub async fn refresh_collection_stats(pool: &DatabasePool) -> Result<()> {
+    let refresh = sqlx::query("REFRESH MATERIALIZED VIEW CONCURRENTLY repository_collection_stats").execute(pool);
+    timing::timed("collection_stats_refresh", refresh)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to refresh collection stats: {}", e)))?;
+    Ok(())
+}
+
+fn push_conditions<'a>(builder: &mut QueryBuilder<'a, Postgres>, query: &RepositoryListQuery<'a>) {
+    let filter = query.filter;
+
+    builder.push(" WHERE owner_login = ").push_bind(query.owner);
+    if !query.include_expired {
+        builder.push(" AND cache_expires_at > CURRENT_TIMESTAMP");
+    }
+
+    if let Some(language) = &filter.language {
+        builder.push(" AND language = ").push_bind(language.clone());
+    }
+    if let Some(min_stars) = filter.min_stars {
+        builder.push(" AND stargazers_count >= ").push_bind(min_stars);
+    }
+    if let Some(max_stars) = filter.max_stars {
+        builder.push(" AND stargazers_count <= ").push_bind(max_stars);
+    }
+    if let Some(min_size) = filter.min_size_kb {
+        builder.push(" AND size_kb >= ").push_bind(min_size);
+    }
+    if let Some(max_size) = filter.max_size_kb {
+        builder.push(" AND size_kb <= ").push_bind(max_size);
+    }
+    if let Some(is_fork) = filter.is_fork {
+        builder.push(" AND is_fork = ").push_bind(is_fork);
+    }
+    if let Some(is_archived) = filter.is_archived {
+        builder.push(" AND is_archived = ").push_bind(is_archived);
+    }
+    if let Some(has_topics) = filter.has_topics {
+        builder.push(if has_topics {
+            " AND COALESCE(cardinality(topics), 0) > 0"
+        } else {
+            " AND COALESCE(cardinality(topics), 0) = 0"
+        });
+    }
+    if let Some(topics) = filter.topics.as_ref().filter(|t| !t.is_empty()) {
+        builder.push(" AND topics @> ").push_bind(topics.clone());
+    }
+    if let Some(has_license) = filter.has_license {
+        builder.push(if has_license { " AND license_name IS NOT NULL" } else { " AND license_name IS NULL" });
+    }
+    if let Some(created_after) = filter.created_after {
+        builder.push(" AND created_at >= ").push_bind(created_after);
+    }
+    if let Some(created_before) = filter.created_before {
+        builder.push(" AND created_at <= ").push_bind(created_before);
+    }
+    if let Some(updated_after) = filter.updated_after {
+        builder.push(" AND updated_at >= ").push_bind(updated_after);
+    }
+    if let Some(updated_before) = filter.updated_before {
+        builder.push(" AND updated_at <= ").push_bind(updated_before);
+    }
+    if let Some(search) = filter.search_query.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        let pattern = format!("%{}%", escape_like(search));
+        builder
+            .push(" AND (name ILIKE ")
+            .push_bind(pattern.clone())
+            .push(" OR description ILIKE ")
+            .push_bind(pattern.clone())
+            .push(" OR array_to_string(topics, ' ') ILIKE ")
+            .push_bind(pattern)
+            .push(")");
+    }
+}
+
+fn sort_column(sort: &RepositorySort) -> &'static str {
+    match sort {
+        RepositorySort::Name => "name",
+        RepositorySort::Stars => "stargazers_count",
+        RepositorySort::Forks => "forks_count",
+        RepositorySort::Updated => "updated_at",
+        RepositorySort::Created => "created_at",
+        RepositorySort::Size => "size_kb",
+        RepositorySort::Issues => "open_issues_count",
+        // Activity scoring happens in Rust; last push is the closest column to sort on
+        RepositorySort::ActivityScore => "pushed_at",
+    }
+}
+
+/// Escape LIKE wildcards so user searches match literally
+fn escape_like(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conditions_bind_user_values() {
+        let filter = RepositoryFilter {
+            language: Some("Rust".to_string()),
+            min_stars: Some(10),
+            topics: Some(vec!["wasm".to_string()]),
+            search_query: Some("50%_off".to_string()),
+            ..Default::default()
+        };
+        let query = RepositoryListQuery {
+            owner: "octocat",
+            filter: &filter,
+            sort: RepositorySort::Stars,
+            direction: SortDirection::Desc,
+            limit: 20,
+            offset: 0,
+            include_expired: false,
+        };
+
+        let mut builder = QueryBuilder::<Postgres>::new("SELECT 1 FROM repositories");
+        push_conditions(&mut builder, &query);
+        let sql = builder.sql();
+
+        assert!(sql.contains("owner_login = $1"));
+        assert!(sql.contains("cache_expires_at > CURRENT_TIMESTAMP"));
+        assert!(sql.contains("language = $2"));
+        assert!(sql.contains("topics @> $4"));
+        assert!(!sql.contains("Rust"));
+        assert!(!sql.contains("50%"));
+    }
+
+    #[test]
+    fn test_escape_like_and_direction() {
+        assert_eq!(escape_like("50%_off\\"), "50\\%\\_off\\\\");
+        assert_eq!(SortDirection::parse("ASC"), SortDirection::Asc);
+        assert_eq!(SortDirection::parse("whatever"), SortDirection::Desc);
+        assert_eq!(sort_column(&RepositorySort::Issues), "open_issues_count");
+    }
+}
@@ -0,0 +1,171 @@
+/*
+ * Data retention policy and the cleanup pass the scheduler runs to enforce it.
+ * I'm deleting table by table so one slow or failing table doesn't stop the others from being trimmed.
+ */
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::Instant;
+use tracing::{debug, info, warn};
+
+use crate::{
+    database::{timing, DatabasePool},
+    utils::{config::Config, error::{AppError, Result}, metrics::MetricsCollector},
+};
+
+/// How many days of history to keep per table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    pub performance_metrics_days: u32,
+    pub fractal_computations_days: u32,
+    pub audit_log_days: u32,
+    pub webhook_delivery_days: u32,
+    pub job_run_days: u32,
+    /// Sessions idle this long are deleted along with their history
+    pub session_days: u32,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            performance_metrics_days: 30,
+            fractal_computations_days: 7,
+            audit_log_days: 90,
+            webhook_delivery_days: 30,
+            job_run_days: 30,
+            session_days: 30,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            performance_metrics_days: config.performance_metrics_retention_days,
+            fractal_computations_days: config.fractal_computations_retention_days,
+            audit_log_days: config.audit_log_retention_days,
+            webhook_delivery_days: config.webhook_delivery_retention_days,
+            job_run_days: config.job_run_retention_days,
+            session_days: config.session_ttl_days,
+        }
+    }
+
+    /// (table, timestamp column, days to keep) for every age-based rule
+    fn rules(&self) -> [(&'static str, &'static str, u32); 6] {
+        [
+            ("performance_metrics", "timestamp", self.performance_metrics_days),
+            ("fractal_computations", "timestamp", self.fractal_computations_days),
+            ("audit_logs", "timestamp", self.audit_log_days),
+            ("webhook_deliveries", "created_at", self.webhook_delivery_days),
+            ("job_runs", "started_at", self.job_run_days),
+            ("sessions", "last_seen_at", self.session_days),
+        ]
+    }
+}
+
+/// Rows removed by one cleanup pass
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CleanupReport {
+    pub deleted: BTreeMap<String, u64>,
+    pub failed: Vec<String>,
+    pub total_deleted: u64,
+    pub duration_ms: u64,
+}
+
+/// Remove expired cache rows and anything older than the policy allows
+pub async fn run_cleanup(pool: &DatabasePool, policy: &RetentionPolicy) -> CleanupReport {
+    let start = Instant::now();
+    let mut report = CleanupReport::default();
+
+    let expired_cache = sqlx::query("DELETE FROM cache_entries WHERE expires_at < NOW()").execute(pool);
+    let expired_cache = timing::timed("retention_cache_entries", expired_cache).await;
+    record_outcome(&mut report, "cache_entries", expired_cache.map(|r| r.rows_affected()).map_err(Into::into));
+
+    for (table, column, days) in policy.rules() {
+        let outcome = delete_older_than(pool, table, column, days).await;
+        record_outcome(&mut report, table, outcome);
+    }
+
+    report.duration_ms = start.elapsed().as_millis() as u64;
+    report
+}
+
+async fn delete_older_than(pool: &DatabasePool, table: &str, column: &str, days: u32) -> Result<u64> {
+    // Table and column names come from the fixed rule list above, never from user input
+    let sql = format!("DELETE FROM {} WHERE {} < NOW() - make_interval(days => $1)", table, column);
+    let query = sqlx::query(&sql).bind(days as i32);
+    let result = timing::timed(&format!("retention_{}", table), query.execute(pool)).await?;
+
+    Ok(result.rows_affected())
+}
+
+fn record_outcome(report: &mut CleanupReport, table: &str, outcome: Result<u64>) {
+    match outcome {
+        Ok(rows) => {
+            debug!("Retention cleanup removed {} rows from {}", rows, table);
+            report.total_deleted += rows;
+            report.deleted.insert(table.to_string(), rows);
+        }
+        Err(e) => {
+            warn!("Retention cleanup failed for {}: {}", table, e);
+            report.failed.push(table.to_string());
+        }
+    }
+}
+
+/// Publish per-table deletion counts so cleanup progress shows up on /metrics
+pub async fn record_cleanup_metrics(metrics: &MetricsCollector, report: &CleanupReport) -> Result<()> {
+    for (table, rows) in &report.deleted {
+        metrics.add_to_counter(&format!("retention_deleted_rows_{}", table), *rows).await?;
+    }
+    metrics.set_gauge("retention_last_run_deleted_rows", report.total_deleted as f64).await?;
+    metrics.set_gauge("retention_last_run_failed_tables", report.failed.len() as f64).await?;
+    metrics.record_operation_time("retention_cleanup", report.duration_ms as f64).await?;
+    Ok(())
+}
+
+/// One scheduled cleanup pass: trim, publish metrics, and summarise for the job history
+pub async fn run_scheduled_cleanup(pool: &DatabasePool, metrics: &MetricsCollector, policy: &RetentionPolicy) -> Result<String> {
+    let report = run_cleanup(pool, policy).await;
+    info!("Retention cleanup removed {} rows in {}ms", report.total_deleted, report.duration_ms);
+
+    if let Err(e) = record_cleanup_metrics(metrics, &report).await {
+        warn!("Failed to record retention metrics: {}", e);
+    }
+
+    if report.failed.is_empty() {
+        Ok(format!("Removed {} rows", report.total_deleted))
+    } else {
+        Err(AppError::DatabaseError(format!(
+            "Removed {} rows, failed for {}", report.total_deleted, report.failed.join(", ")
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rules_follow_policy() {
+        let policy = RetentionPolicy { audit_log_days: 14, ..Default::default() };
+        let rules = policy.rules();
+
+        assert!(rules.contains(&("audit_logs", "timestamp", 14)));
+        assert!(rules.contains(&("webhook_deliveries", "created_at", 30)));
+        assert!(rules.contains(&("job_runs", "started_at", 30)));
+        assert!(rules.contains(&("sessions", "last_seen_at", 30)));
+        assert_eq!(rules.len(), 6);
+    }
+
+    #[test]
+    fn test_report_tracks_failures_separately() {
+        let mut report = CleanupReport::default();
+        record_outcome(&mut report, "performance_metrics", Ok(12));
+        record_outcome(&mut report, "audit_logs", Err(crate::utils::error::AppError::DatabaseError("boom".into())));
+
+        assert_eq!(report.total_deleted, 12);
+        assert_eq!(report.deleted.get("performance_metrics"), Some(&12));
+        assert_eq!(report.failed, vec!["audit_logs".to_string()]);
+    }
+}
@@ -0,0 +1,154 @@
+/*
+ * Query timing wrapper recording per-query duration metrics and logging statements that exceed the slow-query threshold.
+ * I'm keeping the settings in a process-wide slot so query helpers that only receive a pool can still be instrumented.
+ * The Postgres circuit breaker rides in the same slot, so every timed query counts towards it and fails fast while it is open.
+ */
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+use crate::utils::{circuit_breaker::CircuitBreaker, metrics::MetricsCollector};
+
+pub const DEFAULT_SLOW_QUERY_THRESHOLD: Duration = Duration::from_millis(200);
+
+static INSTRUMENTATION: OnceLock<QueryInstrumentation> = OnceLock::new();
+static QUERY_COUNT: AtomicU64 = AtomicU64::new(0);
+static QUERY_MICROS: AtomicU64 = AtomicU64::new(0);
+static SLOW_QUERY_COUNT: AtomicU64 = AtomicU64::new(0);
+
+struct QueryInstrumentation {
+    metrics: MetricsCollector,
+    slow_threshold: Duration,
+    breaker: CircuitBreaker,
+}
+
+/// Errors a timed query fails with, as the Postgres circuit breaker sees them
+pub trait QueryError {
+    /// Whether the failure says the database is unreachable rather than that the statement was refused
+    fn is_outage(&self) -> bool;
+    /// The error a query gets without running while the circuit is open
+    fn circuit_open(message: String) -> Self;
+}
+
+impl QueryError for sqlx::Error {
+    fn is_outage(&self) -> bool {
+        matches!(
+            self,
+            sqlx::Error::Io(_) | sqlx::Error::Tls(_) | sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed | sqlx::Error::WorkerCrashed
+        )
+    }
+
+    fn circuit_open(message: String) -> Self {
+        sqlx::Error::Io(std::io::Error::new(std::io::ErrorKind::ConnectionRefused, message))
+    }
+}
+
+/// Aggregate timings since process start
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct QueryStats {
+    pub total_queries: u64,
+    pub slow_queries: u64,
+    pub average_query_time_ms: f64,
+}
+
+/// Route query timings into the metrics collector and outcomes through the Postgres breaker; later calls are ignored
+pub fn install(metrics: MetricsCollector, slow_threshold: Duration, breaker: CircuitBreaker) {
+    let _ = INSTRUMENTATION.set(QueryInstrumentation { metrics, slow_threshold, breaker });
+}
+
+/// Run a query future, recording its duration under `name` and warning when it's slow
+/// I'm timing failed queries too, since a statement that times out is exactly the one worth seeing
+pub async fn timed<T, E, F>(name: &str, query: F) -> std::result::Result<T, E>
+where
+    E: QueryError,
+    F: Future<Output = std::result::Result<T, E>>,
+{
+    let start = Instant::now();
+    let result = match INSTRUMENTATION.get() {
+        Some(inst) => inst.breaker.call_with(|| query, E::is_outage, E::circuit_open).await,
+        None => query.await,
+    };
+    let elapsed = start.elapsed();
+
+    QUERY_COUNT.fetch_add(1, Ordering::Relaxed);
+    QUERY_MICROS.fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+
+    let threshold = INSTRUMENTATION
+        .get()
+        .map_or(DEFAULT_SLOW_QUERY_THRESHOLD, |inst| inst.slow_threshold);
+    if is_slow(elapsed, threshold) {
+        SLOW_QUERY_COUNT.fetch_add(1, Ordering::Relaxed);
+        warn!("Slow query {} took {}ms (threshold {}ms, ok: {})",
+            name, elapsed.as_millis(), threshold.as_millis(), result.is_ok());
+    } else {
+        debug!("Query {} took {:.2}ms", name, elapsed.as_secs_f64() * 1000.0);
+    }
+
+    if let Some(inst) = INSTRUMENTATION.get() {
+        let duration_ms = elapsed.as_secs_f64() * 1000.0;
+        if let Err(e) = inst.metrics.record_operation_time(&format!("db_query_{}", name), duration_ms).await {
+            debug!("Failed to record query timing for {}: {}", name, e);
+        }
+        if is_slow(elapsed, threshold) {
+            let _ = inst.metrics.increment_counter("db_slow_queries_total").await;
+        }
+    }
+
+    result
+}
+
+pub fn query_stats() -> QueryStats {
+    let total_queries = QUERY_COUNT.load(Ordering::Relaxed);
+    let total_micros = QUERY_MICROS.load(Ordering::Relaxed);
+
+    QueryStats {
+        total_queries,
+        slow_queries: SLOW_QUERY_COUNT.load(Ordering::Relaxed),
+        average_query_time_ms: if total_queries == 0 {
+            0.0
+        } else {
+            total_micros as f64 / total_queries as f64 / 1000.0
+        },
+    }
+}
+
+fn is_slow(elapsed: Duration, threshold: Duration) -> bool {
+    !threshold.is_zero() && elapsed >= threshold
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl QueryError for String {
+        fn is_outage(&self) -> bool {
+            false
+        }
+
+        fn circuit_open(message: String) -> Self {
+            message
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timed_passes_results_through_and_counts() {
+        let before = query_stats().total_queries;
+
+        let ok: std::result::Result<u32, String> = timed("test_ok", async { Ok(7) }).await;
+        let err: std::result::Result<u32, String> = timed("test_err", async { Err("boom".to_string()) }).await;
+
+        assert_eq!(ok, Ok(7));
+        assert_eq!(err, Err("boom".to_string()));
+        assert!(query_stats().total_queries >= before + 2);
+    }
+
+    #[test]
+    fn test_zero_threshold_disables_slow_logging() {
+        assert!(!is_slow(Duration::from_secs(10), Duration::ZERO));
+        assert!(is_slow(Duration::from_millis(250), Duration::from_millis(200)));
+        assert!(!is_slow(Duration::from_millis(150), Duration::from_millis(200)));
+    }
+}
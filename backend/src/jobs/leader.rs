@@ -0,0 +1,249 @@
+/*
+ * Redis lease-based leader election so singleton background work runs on exactly one replica.
+ * I'm renewing the lease at a third of its lifetime and stepping down on any renewal error, since two leaders is worse than a short gap with none.
+ */
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    models::jobs::LeadershipStatus,
+    utils::{error::Result, redis_connection::SharedRedisConnection},
+};
+
+const LEADER_KEY: &str = "perf_showcase:leader:scheduler";
+
+/// Extends the lease only while this instance still holds it
+/// KEYS: leader key; ARGV: instance id, lease ms
+static RENEW_SCRIPT: Lazy<redis::Script> = Lazy::new(|| {
+    redis::Script::new(
+        r#"
+        if redis.call('GET', KEYS[1]) == ARGV[1] then
+            return redis.call('PEXPIRE', KEYS[1], ARGV[2])
+        end
+        return 0
+        "#,
+    )
+});
+
+/// Deletes the lease only if this instance holds it
+/// KEYS: leader key; ARGV: instance id
+static RELEASE_SCRIPT: Lazy<redis::Script> = Lazy::new(|| {
+    redis::Script::new(
+        r#"
+        if redis.call('GET', KEYS[1]) == ARGV[1] then
+            return redis.call('DEL', KEYS[1])
+        end
+        return 0
+        "#,
+    )
+});
+
+#[derive(Clone)]
+pub struct LeaderElection {
+    inner: Arc<LeaderInner>,
+}
+
+struct LeaderInner {
+    redis: SharedRedisConnection,
+    instance_id: String,
+    lease: Duration,
+    enabled: bool,
+    is_leader: AtomicBool,
+    state: Mutex<LeaderState>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct LeaderState {
+    leader_since: Option<DateTime<Utc>>,
+    last_renewed_at: Option<DateTime<Utc>>,
+    current_leader: Option<String>,
+}
+
+impl std::fmt::Debug for LeaderElection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LeaderElection")
+            .field("instance_id", &self.inner.instance_id)
+            .field("enabled", &self.inner.enabled)
+            .field("is_leader", &self.is_leader())
+            .finish()
+    }
+}
+
+impl LeaderElection {
+    /// With election disabled every instance acts as leader, which suits single-replica deployments
+    pub fn new(redis: SharedRedisConnection, enabled: bool, lease: Duration) -> Self {
+        Self {
+            inner: Arc::new(LeaderInner {
+                redis,
+                instance_id: instance_id(),
+                lease,
+                enabled,
+                is_leader: AtomicBool::new(false),
+                state: Mutex::new(LeaderState::default()),
+            }),
+        }
+    }
+
+    pub fn instance_id(&self) -> &str {
+        &self.inner.instance_id
+    }
+
+    pub fn is_leader(&self) -> bool {
+        !self.inner.enabled || self.inner.is_leader.load(Ordering::Acquire)
+    }
+
+    pub fn status(&self) -> LeadershipStatus {
+        let state = self.lock().clone();
+        let is_leader = self.is_leader();
+        LeadershipStatus {
+            enabled: self.inner.enabled,
+            instance_id: self.inner.instance_id.clone(),
+            is_leader,
+            leader: if is_leader { Some(self.inner.instance_id.clone()) } else { state.current_leader },
+            leader_since: state.leader_since,
+            last_renewed_at: state.last_renewed_at,
+            lease_seconds: self.inner.lease.as_secs(),
+        }
+    }
+
+    /// Start campaigning; the first attempt happens immediately
+    pub fn spawn(&self) -> Option<tokio::task::JoinHandle<()>> {
+        if !self.inner.enabled {
+            info!("Leader election disabled; this instance runs all singleton jobs");
+            return None;
+        }
+
+        let election = self.clone();
+        info!("Starting leader election as {} (lease: {}s)", election.inner.instance_id, election.inner.lease.as_secs());
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(election.inner.lease / 3);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = election.campaign().await {
+                    warn!("Leader election round failed: {}", e);
+                    election.step_down("lease could not be confirmed");
+                }
+            }
+        }))
+    }
+
+    /// Give up the lease so another instance can take over without waiting for it to expire
+    pub async fn resign(&self) -> Result<()> {
+        if !self.inner.enabled || !self.inner.is_leader.load(Ordering::Acquire) {
+            return Ok(());
+        }
+        self.step_down("resigned");
+
+        let mut conn = self.inner.redis.get().await?;
+        RELEASE_SCRIPT
+            .key(LEADER_KEY)
+            .arg(&self.inner.instance_id)
+            .invoke_async::<_, i32>(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn campaign(&self) -> Result<()> {
+        let mut conn = self.inner.redis.get().await?;
+        let lease_ms = self.inner.lease.as_millis() as u64;
+
+        if self.inner.is_leader.load(Ordering::Acquire) {
+            let renewed: i32 = RENEW_SCRIPT
+                .key(LEADER_KEY)
+                .arg(&self.inner.instance_id)
+                .arg(lease_ms)
+                .invoke_async(&mut conn)
+                .await?;
+            if renewed == 1 {
+                self.lock().last_renewed_at = Some(Utc::now());
+            } else {
+                self.step_down("lease was taken over");
+            }
+            return Ok(());
+        }
+
+        let acquired: Option<String> = redis::cmd("SET")
+            .arg(LEADER_KEY)
+            .arg(&self.inner.instance_id)
+            .arg("NX")
+            .arg("PX")
+            .arg(lease_ms)
+            .query_async(&mut conn)
+            .await?;
+        let holder: Option<String> = match acquired {
+            Some(_) => Some(self.inner.instance_id.clone()),
+            None => redis::cmd("GET").arg(LEADER_KEY).query_async(&mut conn).await?,
+        };
+
+        // A lease still held under our id, say after a renewal error, is ours to pick back up
+        if holder.as_deref() == Some(self.inner.instance_id.as_str()) {
+            self.take_over();
+        } else {
+            debug!("Leader is {:?}", holder);
+            self.lock().current_leader = holder;
+        }
+        Ok(())
+    }
+
+    fn take_over(&self) {
+        let now = Utc::now();
+        let mut state = self.lock();
+        state.leader_since = Some(now);
+        state.last_renewed_at = Some(now);
+        state.current_leader = Some(self.inner.instance_id.clone());
+        drop(state);
+
+        self.inner.is_leader.store(true, Ordering::Release);
+        info!("{} became leader", self.inner.instance_id);
+    }
+
+    fn step_down(&self, reason: &str) {
+        if self.inner.is_leader.swap(false, Ordering::AcqRel) {
+            warn!("{} is no longer leader: {}", self.inner.instance_id, reason);
+            let mut state = self.lock();
+            state.leader_since = None;
+            state.current_leader = None;
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LeaderState> {
+        self.inner.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Host name plus a random suffix, so restarts and replicas sharing a host never claim each other's lease
+fn instance_id() -> String {
+    let host = std::env::var("HOSTNAME")
+        .ok()
+        .filter(|host| !host.is_empty())
+        .unwrap_or_else(|| "instance".to_string());
+    format!("{}-{}", host, &Uuid::new_v4().simple().to_string()[..8])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leadership_follows_election_state() {
+        let redis = SharedRedisConnection::new(redis::Client::open("redis://127.0.0.1:6379").unwrap());
+        let election = LeaderElection::new(redis.clone(), false, Duration::from_secs(30));
+        assert!(election.is_leader());
+        assert!(election.status().is_leader);
+
+        let election = LeaderElection::new(redis, true, Duration::from_secs(30));
+        assert!(!election.is_leader());
+        election.take_over();
+        assert_eq!(election.status().leader.as_deref(), Some(election.instance_id()));
+        election.step_down("test");
+        assert!(!election.is_leader());
+        assert!(election.status().leader_since.is_none());
+    }
+}
@@ -0,0 +1,12 @@
+/*
+ * Background job module aggregator for scheduled maintenance work and queued tasks that run alongside the HTTP server.
+ * I'm keeping the scheduling machinery here and the job bodies with the services they belong to, so a job is just a registered closure.
+ */
+
+pub mod leader;
+pub mod queue;
+pub mod scheduler;
+
+pub use leader::LeaderElection;
+pub use queue::{QueueSettings, TaskHandler, TaskQueue};
+pub use scheduler::{JobResult, JobSchedule, JobTrigger, Scheduler};
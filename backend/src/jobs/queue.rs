@@ -0,0 +1,623 @@
+/*
+ * Redis-backed task queue with visibility timeouts, retries with exponential backoff, and a dead-letter set per queue.
+ * I'm keeping each queue's state in sorted sets scored by time, so ready work, delayed retries, and expired leases are all one range query.
+ */
+
+use chrono::{DateTime, TimeZone, Utc};
+use futures::future::BoxFuture;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn, Instrument};
+use uuid::Uuid;
+
+use crate::{
+    models::jobs::{DeadLetter, QueueStats, QueuedTask},
+    utils::{
+        config::Config,
+        correlation,
+        error::{AppError, Result},
+        metrics::MetricsCollector,
+        redis_connection::SharedRedisConnection,
+        retry::{RetryBudget, RetryConfig},
+        shutdown::Shutdown,
+    },
+};
+
+const KEY_PREFIX: &str = "perf_showcase:queue:";
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(2);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(300);
+const MAX_CLAIM_ERROR_DELAY: Duration = Duration::from_secs(30);
+const DEPTH_REPORT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Returns expired leases to the queue, then moves the oldest due task into the in-flight set
+/// KEYS: pending, inflight, tasks, attempts; ARGV: now, lease deadline (both epoch ms)
+static CLAIM_SCRIPT: Lazy<redis::Script> = Lazy::new(|| {
+    redis::Script::new(
+        r#"
+        local expired = redis.call('ZRANGEBYSCORE', KEYS[2], '-inf', ARGV[1], 'LIMIT', 0, 100)
+        for _, id in ipairs(expired) do
+            redis.call('ZREM', KEYS[2], id)
+            redis.call('ZADD', KEYS[1], ARGV[1], id)
+        end
+        local ids = redis.call('ZRANGEBYSCORE', KEYS[1], '-inf', ARGV[1], 'LIMIT', 0, 1)
+        if #ids == 0 then
+            return false
+        end
+        local id = ids[1]
+        redis.call('ZREM', KEYS[1], id)
+        local task = redis.call('HGET', KEYS[3], id)
+        if not task then
+            redis.call('HDEL', KEYS[4], id)
+            return false
+        end
+        redis.call('ZADD', KEYS[2], ARGV[2], id)
+        local attempts = redis.call('HINCRBY', KEYS[4], id, 1)
+        return {task, attempts}
+        "#,
+    )
+});
+
+/// Moves a task between sets only if it is still in the source, optionally rewriting the stored task
+/// KEYS: from, to, tasks; ARGV: id, score, task JSON or ''
+static MOVE_SCRIPT: Lazy<redis::Script> = Lazy::new(|| {
+    redis::Script::new(
+        r#"
+        if redis.call('ZREM', KEYS[1], ARGV[1]) == 0 then
+            return 0
+        end
+        redis.call('ZADD', KEYS[2], ARGV[2], ARGV[1])
+        if ARGV[3] ~= '' then
+            redis.call('HSET', KEYS[3], ARGV[1], ARGV[3])
+        end
+        return 1
+        "#,
+    )
+});
+
+/// Hands an in-flight task back to the queue without counting the attempt, for work cut off by shutdown
+/// KEYS: inflight, pending, attempts; ARGV: id, now (epoch ms)
+static RELEASE_SCRIPT: Lazy<redis::Script> = Lazy::new(|| {
+    redis::Script::new(
+        r#"
+        if redis.call('ZREM', KEYS[1], ARGV[1]) == 0 then
+            return 0
+        end
+        redis.call('ZADD', KEYS[2], ARGV[2], ARGV[1])
+        redis.call('HINCRBY', KEYS[3], ARGV[1], -1)
+        return 1
+        "#,
+    )
+});
+
+/// Processes one claimed task; an error sends it back for a retry, or to the dead-letter set on its last attempt
+pub type TaskHandler = Arc<dyn Fn(QueuedTask) -> BoxFuture<'static, Result<()>> + Send + Sync>;
+
+/// Worker and retry settings, snapshotted from Config
+#[derive(Debug, Clone)]
+pub struct QueueSettings {
+    pub workers: usize,
+    pub max_attempts: u32,
+    pub visibility_timeout: Duration,
+    pub poll_interval: Duration,
+}
+
+impl QueueSettings {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            workers: config.task_queue_workers as usize,
+            max_attempts: config.task_queue_max_attempts,
+            visibility_timeout: Duration::from_secs(config.task_queue_visibility_timeout_seconds),
+            poll_interval: Duration::from_millis(config.task_queue_poll_interval_ms),
+        }
+    }
+}
+
+/// Redis keys for one queue
+struct QueueKeys {
+    pending: String,
+    inflight: String,
+    dead: String,
+    tasks: String,
+    attempts: String,
+}
+
+impl QueueKeys {
+    fn new(queue: &str) -> Self {
+        let base = format!("{}{}", KEY_PREFIX, queue);
+        Self {
+            pending: format!("{}:pending", base),
+            inflight: format!("{}:inflight", base),
+            dead: format!("{}:dead", base),
+            tasks: format!("{}:tasks", base),
+            attempts: format!("{}:attempts", base),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct TaskQueue {
+    redis: SharedRedisConnection,
+    metrics: MetricsCollector,
+    settings: QueueSettings,
+    shutdown: Shutdown,
+    queues: Arc<RwLock<BTreeSet<String>>>,
+    reporting: Arc<AtomicBool>,
+}
+
+impl std::fmt::Debug for TaskQueue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskQueue")
+            .field("settings", &self.settings)
+            .field("queues", &self.queue_names())
+            .finish()
+    }
+}
+
+impl TaskQueue {
+    /// Workers stop claiming once `shutdown` begins, and release whatever is still running when it expires
+    pub fn new(redis: SharedRedisConnection, metrics: MetricsCollector, settings: QueueSettings, shutdown: Shutdown) -> Self {
+        Self {
+            redis,
+            metrics,
+            settings,
+            shutdown,
+            queues: Arc::new(RwLock::new(BTreeSet::new())),
+            reporting: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Add a task to `queue`; `max_attempts` falls back to TASK_QUEUE_MAX_ATTEMPTS
+    pub async fn enqueue<T: Serialize>(&self, queue: &str, kind: &str, payload: &T, max_attempts: Option<u32>) -> Result<Uuid> {
+        validate_queue_name(queue)?;
+        let task = QueuedTask {
+            id: Uuid::new_v4(),
+            queue: queue.to_string(),
+            kind: kind.to_string(),
+            payload: serde_json::to_value(payload)?,
+            attempts: 0,
+            max_attempts: max_attempts.unwrap_or(self.settings.max_attempts).max(1),
+            enqueued_at: Utc::now(),
+            correlation_id: correlation::current(),
+            last_error: None,
+        };
+
+        let keys = QueueKeys::new(queue);
+        let id = task.id.to_string();
+        let mut conn = self.redis.get().await?;
+        redis::pipe()
+            .atomic()
+            .hset(&keys.tasks, &id, serde_json::to_string(&task)?).ignore()
+            .zadd(&keys.pending, &id, epoch_ms(Utc::now())).ignore()
+            .query_async::<_, ()>(&mut conn)
+            .await?;
+
+        debug!("Enqueued {} task {} on {}", kind, task.id, queue);
+        Ok(task.id)
+    }
+
+    /// Start TASK_QUEUE_WORKERS workers pulling from `queue`
+    /// I'm running each task on its own spawned task so a panicking handler fails that task instead of killing the worker
+    pub fn spawn_workers<F, Fut>(&self, queue: &str, handler: F) -> Result<()>
+    where
+        F: Fn(QueuedTask) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        validate_queue_name(queue)?;
+        if !self.queues.write().unwrap_or_else(|poisoned| poisoned.into_inner()).insert(queue.to_string()) {
+            return Err(AppError::ConfigurationError(format!("Workers for queue {} are already running", queue)));
+        }
+
+        let handler: TaskHandler = Arc::new(move |task| Box::pin(handler(task)));
+        for _ in 0..self.settings.workers {
+            let worker = self.clone();
+            let queue = queue.to_string();
+            let handler = handler.clone();
+            tokio::spawn(async move { worker.work(queue, handler).await });
+        }
+        info!("Started {} workers for task queue {}", self.settings.workers, queue);
+
+        if !self.reporting.swap(true, Ordering::AcqRel) {
+            let queue = self.clone();
+            tokio::spawn(async move { queue.report_depths().await });
+        }
+        Ok(())
+    }
+
+    /// Queues with workers on this instance
+    pub fn queue_names(&self) -> Vec<String> {
+        self.queues.read().unwrap_or_else(|poisoned| poisoned.into_inner()).iter().cloned().collect()
+    }
+
+    pub async fn stats(&self, queue: &str) -> Result<QueueStats> {
+        let keys = QueueKeys::new(queue);
+        let now = epoch_ms(Utc::now());
+        let mut conn = self.redis.get().await?;
+        let (ready, delayed, in_flight, dead): (u64, u64, u64, u64) = redis::pipe()
+            .zcount(&keys.pending, "-inf", now)
+            .zcount(&keys.pending, format!("({}", now), "+inf")
+            .zcard(&keys.inflight)
+            .zcard(&keys.dead)
+            .query_async(&mut conn)
+            .await?;
+
+        Ok(QueueStats { queue: queue.to_string(), ready, delayed, in_flight, dead })
+    }
+
+    /// Dead-lettered tasks, most recent first
+    pub async fn dead_letters(&self, queue: &str, limit: isize) -> Result<Vec<DeadLetter>> {
+        self.require_queue(queue)?;
+        let keys = QueueKeys::new(queue);
+        let mut conn = self.redis.get().await?;
+        let entries: Vec<(String, f64)> = redis::cmd("ZREVRANGE")
+            .arg(&keys.dead)
+            .arg(0)
+            .arg(limit.max(1) - 1)
+            .arg("WITHSCORES")
+            .query_async(&mut conn)
+            .await?;
+
+        let mut letters = Vec::with_capacity(entries.len());
+        for (id, score) in entries {
+            let (raw, attempts): (Option<String>, Option<u32>) = redis::pipe()
+                .hget(&keys.tasks, &id)
+                .hget(&keys.attempts, &id)
+                .query_async(&mut conn)
+                .await?;
+            let Some(raw) = raw else { continue };
+
+            let mut task: QueuedTask = serde_json::from_str(&raw)?;
+            task.attempts = attempts.unwrap_or_default();
+            letters.push(DeadLetter { task, dead_at: from_epoch_ms(score as i64) });
+        }
+        Ok(letters)
+    }
+
+    /// Put a dead-lettered task back on its queue with a fresh set of attempts
+    pub async fn retry_dead_letter(&self, queue: &str, id: Uuid) -> Result<()> {
+        self.require_queue(queue)?;
+        let keys = QueueKeys::new(queue);
+        let id = id.to_string();
+        let mut conn = self.redis.get().await?;
+
+        let moved: i32 = MOVE_SCRIPT
+            .key(&keys.dead)
+            .key(&keys.pending)
+            .key(&keys.tasks)
+            .arg(&id)
+            .arg(epoch_ms(Utc::now()))
+            .arg("")
+            .invoke_async(&mut conn)
+            .await?;
+        if moved == 0 {
+            return Err(AppError::NotFoundError(format!("No dead-lettered task {} on queue {}", id, queue)));
+        }
+
+        redis::cmd("HDEL").arg(&keys.attempts).arg(&id).query_async::<_, ()>(&mut conn).await?;
+        info!("Requeued dead-lettered task {} on {}", id, queue);
+        Ok(())
+    }
+
+    /// Drop a dead-lettered task for good
+    pub async fn discard_dead_letter(&self, queue: &str, id: Uuid) -> Result<()> {
+        self.require_queue(queue)?;
+        let keys = QueueKeys::new(queue);
+        if !self.remove(&keys, &keys.dead, &id.to_string()).await? {
+            return Err(AppError::NotFoundError(format!("No dead-lettered task {} on queue {}", id, queue)));
+        }
+        Ok(())
+    }
+
+    async fn work(&self, queue: String, handler: TaskHandler) {
+        let mut error_delay = self.settings.poll_interval;
+        while !self.shutdown.is_draining() {
+            // Held across the claim too, so shutdown can't miss a task that is being handed to this worker
+            let in_flight = self.shutdown.track();
+            let idle_delay = match self.claim(&queue).await {
+                Ok(Some(task)) => {
+                    error_delay = self.settings.poll_interval;
+                    self.process(task, &handler).await;
+                    continue;
+                }
+                Ok(None) => self.settings.poll_interval,
+                Err(e) => {
+                    warn!("Failed to claim a task from {}: {}", queue, e);
+                    let delay = error_delay;
+                    error_delay = (error_delay * 2).min(MAX_CLAIM_ERROR_DELAY);
+                    delay
+                }
+            };
+            drop(in_flight);
+
+            tokio::select! {
+                _ = tokio::time::sleep(idle_delay) => {}
+                _ = self.shutdown.triggered() => {}
+            }
+        }
+        debug!("Worker for task queue {} stopped for shutdown", queue);
+    }
+
+    async fn claim(&self, queue: &str) -> Result<Option<QueuedTask>> {
+        let keys = QueueKeys::new(queue);
+        let now = Utc::now();
+        let deadline = now + chrono::Duration::from_std(self.settings.visibility_timeout).unwrap_or_default();
+        let mut conn = self.redis.get().await?;
+
+        let claimed: Option<(String, u32)> = CLAIM_SCRIPT
+            .key(&keys.pending)
+            .key(&keys.inflight)
+            .key(&keys.tasks)
+            .key(&keys.attempts)
+            .arg(epoch_ms(now))
+            .arg(epoch_ms(deadline))
+            .invoke_async(&mut conn)
+            .await?;
+
+        claimed
+            .map(|(raw, attempts)| {
+                let mut task: QueuedTask = serde_json::from_str(&raw)?;
+                task.attempts = attempts;
+                Ok(task)
+            })
+            .transpose()
+    }
+
+    async fn process(&self, task: QueuedTask, handler: &TaskHandler) {
+        let queue = task.queue.clone();
+        if task.attempts == 1 {
+            // First runs fund the retries, the same as first attempts through retry_with_backoff
+            RetryBudget::global().deposit();
+            let wait_ms = (Utc::now() - task.enqueued_at).num_milliseconds().max(0) as f64;
+            let _ = self.metrics.record_histogram(&format!("task_queue_{}_wait_ms", queue), wait_ms).await;
+        }
+
+        // A worker that died mid-task leaves its lease to expire, which hands the task out once more than allowed
+        if task.attempts > task.max_attempts {
+            self.dead_letter(task, "Lease expired on the final attempt".to_string()).await;
+            return;
+        }
+
+        let heartbeat = self.spawn_heartbeat(&task);
+        let span = tracing::info_span!("task", queue = %queue, kind = %task.kind, id = %task.id, attempt = task.attempts);
+        let correlation_id = task.correlation_id.clone().unwrap_or_else(|| task.id.to_string());
+        let start = Instant::now();
+        let mut run = tokio::spawn(correlation::scope(correlation_id, handler(task.clone())).instrument(span));
+        let outcome = tokio::select! {
+            joined = &mut run => match joined {
+                Ok(result) => result,
+                Err(e) => Err(AppError::InternalServerError(format!("Task handler panicked: {}", e))),
+            },
+            _ = self.shutdown.expired() => {
+                run.abort();
+                heartbeat.abort();
+                self.release(&task).await;
+                return;
+            }
+        };
+        heartbeat.abort();
+
+        let processing_ms = start.elapsed().as_secs_f64() * 1000.0;
+        let _ = self.metrics.record_histogram(&format!("task_queue_{}_processing_ms", queue), processing_ms).await;
+
+        match outcome {
+            Ok(()) => {
+                let _ = self.metrics.increment_counter(&format!("task_queue_{}_completed_total", queue)).await;
+                let keys = QueueKeys::new(&queue);
+                if let Err(e) = self.remove(&keys, &keys.inflight, &task.id.to_string()).await {
+                    warn!("Failed to acknowledge task {} on {}: {}", task.id, queue, e);
+                }
+            }
+            Err(e) if task.is_last_attempt() => self.dead_letter(task, e.to_string()).await,
+            // Left for an operator to retry from the dead letters once whatever is failing everywhere recovers
+            Err(e) if !RetryBudget::global().try_withdraw() => {
+                self.dead_letter(task, format!("Retry budget exhausted: {}", e)).await;
+            }
+            Err(e) => {
+                let _ = self.metrics.increment_counter(&format!("task_queue_{}_retried_total", queue)).await;
+                let delay = task_retry().delay(task.attempts);
+                warn!("Task {} ({}) on {} failed on attempt {}/{}, retrying in {:?}: {}",
+                    task.id, task.kind, queue, task.attempts, task.max_attempts, delay, e);
+
+                let retry_at = Utc::now() + chrono::Duration::from_std(delay).unwrap_or_default();
+                if let Err(e) = self.requeue(task, QueueKeys::new(&queue).pending, retry_at, e.to_string()).await {
+                    warn!("Failed to schedule retry on {}: {}", queue, e);
+                }
+            }
+        }
+    }
+
+    async fn dead_letter(&self, task: QueuedTask, error: String) {
+        let queue = task.queue.clone();
+        let _ = self.metrics.increment_counter(&format!("task_queue_{}_dead_lettered_total", queue)).await;
+        warn!("Task {} ({}) on {} dead-lettered after {} attempts: {}", task.id, task.kind, queue, task.attempts, error);
+
+        if let Err(e) = self.requeue(task, QueueKeys::new(&queue).dead, Utc::now(), error).await {
+            warn!("Failed to dead-letter task on {}: {}", queue, e);
+        }
+    }
+
+    /// Move an in-flight task to `target`, recording why it failed
+    async fn requeue(&self, mut task: QueuedTask, target: String, at: DateTime<Utc>, error: String) -> Result<()> {
+        let keys = QueueKeys::new(&task.queue);
+        task.last_error = Some(error);
+        let mut conn = self.redis.get().await?;
+
+        let moved: i32 = MOVE_SCRIPT
+            .key(&keys.inflight)
+            .key(&target)
+            .key(&keys.tasks)
+            .arg(task.id.to_string())
+            .arg(epoch_ms(at))
+            .arg(serde_json::to_string(&task)?)
+            .invoke_async(&mut conn)
+            .await?;
+        if moved == 0 {
+            debug!("Task {} lost its lease before it could be requeued", task.id);
+        }
+        Ok(())
+    }
+
+    /// Return a task cut off by shutdown to the front of its queue, leaving its attempt count as it was before the claim
+    async fn release(&self, task: &QueuedTask) {
+        let keys = QueueKeys::new(&task.queue);
+        let result = match self.redis.get().await {
+            Ok(mut conn) => RELEASE_SCRIPT
+                .key(&keys.inflight)
+                .key(&keys.pending)
+                .key(&keys.attempts)
+                .arg(task.id.to_string())
+                .arg(epoch_ms(Utc::now()))
+                .invoke_async::<_, i32>(&mut conn)
+                .await
+                .map_err(AppError::from),
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(_) => info!("Released task {} ({}) on {} for another worker after shutdown cut it off", task.id, task.kind, task.queue),
+            Err(e) => warn!("Failed to release task {} on {}, it will be retried once its lease expires: {}", task.id, task.queue, e),
+        }
+    }
+
+    /// Delete a task from `set` along with its stored body; false when it wasn't there
+    async fn remove(&self, keys: &QueueKeys, set: &str, id: &str) -> Result<bool> {
+        let mut conn = self.redis.get().await?;
+        let (removed,): (i32,) = redis::pipe()
+            .atomic()
+            .zrem(set, id)
+            .hdel(&keys.tasks, id).ignore()
+            .hdel(&keys.attempts, id).ignore()
+            .query_async(&mut conn)
+            .await?;
+        Ok(removed > 0)
+    }
+
+    /// Keep extending a claimed task's lease while its handler runs
+    fn spawn_heartbeat(&self, task: &QueuedTask) -> tokio::task::JoinHandle<()> {
+        let queue = self.clone();
+        let inflight = QueueKeys::new(&task.queue).inflight;
+        let id = task.id.to_string();
+        let interval = self.settings.visibility_timeout / 3;
+
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                let deadline = Utc::now() + chrono::Duration::from_std(queue.settings.visibility_timeout).unwrap_or_default();
+                let result = match queue.redis.get().await {
+                    Ok(mut conn) => redis::cmd("ZADD")
+                        .arg(&inflight)
+                        .arg("XX")
+                        .arg(epoch_ms(deadline))
+                        .arg(&id)
+                        .query_async::<_, ()>(&mut conn)
+                        .await
+                        .map_err(AppError::from),
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = result {
+                    warn!("Failed to extend lease on task {}: {}", id, e);
+                }
+            }
+        })
+    }
+
+    async fn report_depths(&self) {
+        loop {
+            for queue in self.queue_names() {
+                match self.stats(&queue).await {
+                    Ok(stats) => {
+                        let _ = self.metrics.set_gauge(&format!("task_queue_{}_ready", queue), stats.ready as f64).await;
+                        let _ = self.metrics.set_gauge(&format!("task_queue_{}_delayed", queue), stats.delayed as f64).await;
+                        let _ = self.metrics.set_gauge(&format!("task_queue_{}_in_flight", queue), stats.in_flight as f64).await;
+                        let _ = self.metrics.set_gauge(&format!("task_queue_{}_dead", queue), stats.dead as f64).await;
+                    }
+                    Err(e) => debug!("Failed to read depth of task queue {}: {}", queue, e),
+                }
+            }
+            tokio::time::sleep(DEPTH_REPORT_INTERVAL).await;
+        }
+    }
+
+    fn require_queue(&self, queue: &str) -> Result<()> {
+        if self.queues.read().unwrap_or_else(|poisoned| poisoned.into_inner()).contains(queue) {
+            Ok(())
+        } else {
+            Err(AppError::NotFoundError(format!("Unknown task queue {}", queue)))
+        }
+    }
+}
+
+/// Exponential backoff after failed attempts, capped at RETRY_MAX_DELAY, with full jitter so a batch failing together doesn't return together
+fn task_retry() -> RetryConfig {
+    RetryConfig {
+        initial_delay: RETRY_BASE_DELAY,
+        max_delay: RETRY_MAX_DELAY,
+        ..RetryConfig::default()
+    }
+}
+
+
+/// Queue names end up in Redis keys and metric names, so they stay to lowercase letters, digits, and underscores
+fn validate_queue_name(queue: &str) -> Result<()> {
+    if !queue.is_empty() && queue.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+        Ok(())
+    } else {
+        Err(AppError::ConfigurationError(format!("Invalid task queue name '{}'", queue)))
+    }
+}
+
+fn epoch_ms(time: DateTime<Utc>) -> i64 {
+    time.timestamp_millis()
+}
+
+fn from_epoch_ms(ms: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(ms).single().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_backoff_doubles_up_to_the_cap() {
+        let retry = task_retry();
+        assert_eq!(retry.backoff(1), Duration::from_secs(2));
+        assert_eq!(retry.backoff(2), Duration::from_secs(4));
+        assert_eq!(retry.backoff(5), Duration::from_secs(32));
+        assert_eq!(retry.backoff(9), RETRY_MAX_DELAY);
+        assert_eq!(retry.backoff(u32::MAX), RETRY_MAX_DELAY);
+        assert!((0..20).all(|_| retry.delay(5) <= Duration::from_secs(32)));
+    }
+
+    #[test]
+    fn test_queue_names_are_key_and_metric_safe() {
+        assert!(validate_queue_name("webhooks").is_ok());
+        assert!(validate_queue_name("fractal_renders2").is_ok());
+        assert!(validate_queue_name("").is_err());
+        assert!(validate_queue_name("web:hooks").is_err());
+        assert!(validate_queue_name("Exports").is_err());
+        assert_eq!(QueueKeys::new("exports").inflight, "perf_showcase:queue:exports:inflight");
+    }
+
+    #[test]
+    fn test_last_attempt_and_stored_attempts() {
+        let task: QueuedTask = serde_json::from_value(serde_json::json!({
+            "id": Uuid::nil(),
+            "queue": "exports",
+            "kind": "export.run",
+            "payload": {},
+            "max_attempts": 3,
+            "enqueued_at": "2025-01-01T00:00:00Z",
+            "correlation_id": null,
+            "last_error": null,
+        }))
+        .unwrap();
+        assert_eq!(task.attempts, 0);
+        assert!(!task.is_last_attempt());
+        assert!(QueuedTask { attempts: 3, ..task }.is_last_attempt());
+    }
+}
@@ -0,0 +1,496 @@
+/*
+ * Cron-style scheduler for periodic background work, with per-job overlap prevention, jitter, pausing, and persisted run history.
+ * I'm giving every job its own timer task so one slow schedule calculation or long run never delays the others.
+ */
+
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use rand::Rng;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn, Instrument};
+
+use crate::{
+    database::{timing, DatabasePool},
+    jobs::LeaderElection,
+    models::jobs::{JobRun, JobStatus},
+    utils::{
+        error::{AppError, Result},
+        shutdown::Shutdown,
+        Utils,
+    },
+};
+
+/// When a job runs: a fixed interval, or a cron expression evaluated in UTC
+#[derive(Debug, Clone)]
+pub enum JobSchedule {
+    Every(Duration),
+    Cron(Box<cron::Schedule>),
+}
+
+impl JobSchedule {
+    /// Parse `15m`-style intervals or cron expressions; five-field expressions get a leading seconds field
+    pub fn parse(expression: &str) -> Result<Self> {
+        let expression = expression.trim();
+        if !expression.contains(' ') {
+            let interval = Utils::parse_duration(expression)?;
+            if interval.is_zero() {
+                return Err(AppError::ConfigurationError("Job intervals must be longer than zero".to_string()));
+            }
+            return Ok(Self::Every(interval));
+        }
+
+        let fields = expression.split_whitespace().count();
+        let expression = if fields == 5 { format!("0 {}", expression) } else { expression.to_string() };
+        cron::Schedule::from_str(&expression)
+            .map(|schedule| Self::Cron(Box::new(schedule)))
+            .map_err(|e| AppError::ConfigurationError(format!("Invalid cron expression '{}': {}", expression, e)))
+    }
+
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Every(interval) => chrono::Duration::from_std(*interval).ok().map(|interval| after + interval),
+            Self::Cron(schedule) => schedule.after(&after).next(),
+        }
+    }
+}
+
+/// What a job hands back on success, recorded as the run's message
+pub type JobResult = Result<String>;
+
+type JobFn = Arc<dyn Fn() -> BoxFuture<'static, JobResult> + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobTrigger {
+    Schedule,
+    Manual,
+}
+
+impl JobTrigger {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Schedule => "schedule",
+            Self::Manual => "manual",
+        }
+    }
+}
+
+struct Job {
+    name: String,
+    description: String,
+    expression: String,
+    schedule: JobSchedule,
+    jitter: Duration,
+    run: JobFn,
+    paused: AtomicBool,
+    running: AtomicBool,
+    next_run: Mutex<Option<DateTime<Utc>>>,
+}
+
+/// Clears a job's running flag however its run ends
+struct RunningGuard(Arc<Job>);
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        self.0.running.store(false, Ordering::Release);
+    }
+}
+
+#[derive(Clone)]
+pub struct Scheduler {
+    inner: Arc<SchedulerInner>,
+}
+
+struct SchedulerInner {
+    db_pool: DatabasePool,
+    leader: LeaderElection,
+    shutdown: Shutdown,
+    enabled: bool,
+    overrides: BTreeMap<String, String>,
+    jobs: RwLock<BTreeMap<String, Arc<Job>>>,
+    started: AtomicBool,
+}
+
+impl std::fmt::Debug for Scheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Scheduler")
+            .field("enabled", &self.inner.enabled)
+            .field("jobs", &self.read_jobs().keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Scheduler {
+    /// Scheduled runs only fire on the instance holding `leader`'s lease, and stop once `shutdown` begins
+    pub fn new(
+        db_pool: DatabasePool,
+        leader: LeaderElection,
+        shutdown: Shutdown,
+        enabled: bool,
+        overrides: BTreeMap<String, String>,
+    ) -> Self {
+        Self {
+            inner: Arc::new(SchedulerInner {
+                db_pool,
+                leader,
+                shutdown,
+                enabled,
+                overrides,
+                jobs: RwLock::new(BTreeMap::new()),
+                started: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Add a job; a JOB_SCHEDULES entry for `name` replaces `default_schedule`
+    /// I'm adding up to `jitter` of random delay to each scheduled run so instances started together don't fire in lockstep
+    pub fn register<F, Fut>(&self, name: &str, description: &str, default_schedule: &str, jitter: Duration, run: F) -> Result<()>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = JobResult> + Send + 'static,
+    {
+        let expression = self.inner.overrides.get(name).map_or(default_schedule, String::as_str);
+        let schedule = JobSchedule::parse(expression)
+            .map_err(|e| AppError::ConfigurationError(format!("Schedule for job {}: {}", name, e)))?;
+
+        let job = Arc::new(Job {
+            name: name.to_string(),
+            description: description.to_string(),
+            expression: expression.to_string(),
+            schedule,
+            jitter,
+            run: Arc::new(move || Box::pin(run())),
+            paused: AtomicBool::new(false),
+            running: AtomicBool::new(false),
+            next_run: Mutex::new(None),
+        });
+
+        let mut jobs = self.inner.jobs.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if jobs.insert(name.to_string(), job.clone()).is_some() {
+            warn!("Job {} registered twice, keeping the later registration", name);
+        }
+        drop(jobs);
+
+        if self.inner.started.load(Ordering::Acquire) {
+            self.spawn_timer(job);
+        }
+        Ok(())
+    }
+
+    /// Load paused flags and start every job's timer; a no-op when SCHEDULER_ENABLED is off
+    pub async fn start(&self) -> Result<()> {
+        for name in self.inner.overrides.keys() {
+            if !self.read_jobs().contains_key(name) {
+                warn!("JOB_SCHEDULES names unknown job {}", name);
+            }
+        }
+
+        self.refresh_paused().await?;
+        if !self.inner.enabled {
+            info!("Scheduler disabled; jobs only run when triggered through the admin API");
+            return Ok(());
+        }
+        if self.inner.started.swap(true, Ordering::AcqRel) {
+            return Ok(());
+        }
+
+        let jobs: Vec<Arc<Job>> = self.read_jobs().values().cloned().collect();
+        info!("Scheduler started with {} jobs", jobs.len());
+        for job in jobs {
+            self.spawn_timer(job);
+        }
+        Ok(())
+    }
+
+    /// Every job with its state and most recent run
+    pub async fn list(&self) -> Result<Vec<JobStatus>> {
+        let mut last_runs: BTreeMap<String, JobRun> = self
+            .latest_runs()
+            .await?
+            .into_iter()
+            .map(|run| (run.job_name.clone(), run))
+            .collect();
+
+        Ok(self
+            .read_jobs()
+            .values()
+            .map(|job| JobStatus {
+                name: job.name.clone(),
+                description: job.description.clone(),
+                schedule: job.expression.clone(),
+                paused: job.paused.load(Ordering::Acquire),
+                running: job.running.load(Ordering::Acquire),
+                next_run_at: *job.next_run.lock().unwrap_or_else(|poisoned| poisoned.into_inner()),
+                last_run: last_runs.remove(&job.name),
+            })
+            .collect())
+    }
+
+    pub async fn get(&self, name: &str) -> Result<JobStatus> {
+        self.list()
+            .await?
+            .into_iter()
+            .find(|job| job.name == name)
+            .ok_or_else(|| AppError::NotFoundError(format!("Unknown job {}", name)))
+    }
+
+    /// Start a run now on this instance, leader or not; fails if the job is already running here
+    pub fn trigger(&self, name: &str) -> Result<()> {
+        let job = self.job(name)?;
+        if self.inner.shutdown.is_draining() {
+            return Err(AppError::ServiceUnavailableError("Shutting down; not starting new jobs".to_string()));
+        }
+        if job.running.load(Ordering::Acquire) {
+            return Err(AppError::BadRequestError(format!("Job {} is already running", name)));
+        }
+
+        let scheduler = self.clone();
+        tokio::spawn(async move { scheduler.execute(job, JobTrigger::Manual).await });
+        Ok(())
+    }
+
+    /// Pause or resume scheduled runs; manual triggers still work while paused
+    pub async fn set_paused(&self, name: &str, paused: bool, changed_by: &str) -> Result<()> {
+        let job = self.job(name)?;
+        sqlx::query(
+            r#"
+            INSERT INTO scheduled_jobs (name, paused, updated_by, updated_at)
+            VALUES ($1, $2, $3, NOW())
+            ON CONFLICT (name) DO UPDATE SET paused = EXCLUDED.paused, updated_by = EXCLUDED.updated_by, updated_at = NOW()
+            "#,
+        )
+        .bind(name)
+        .bind(paused)
+        .bind(changed_by)
+        .execute(&self.inner.db_pool)
+        .await?;
+
+        job.paused.store(paused, Ordering::Release);
+        info!("Job {} {} by {}", name, if paused { "paused" } else { "resumed" }, changed_by);
+        Ok(())
+    }
+
+    /// Re-read paused flags, after another instance changed one
+    pub async fn refresh_paused(&self) -> Result<()> {
+        let query = sqlx::query_as::<_, (String, bool)>("SELECT name, paused FROM scheduled_jobs");
+        let paused: BTreeMap<String, bool> = timing::timed("scheduled_jobs_load", query.fetch_all(&self.inner.db_pool))
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to load scheduled job state: {}", e)))?
+            .into_iter()
+            .collect();
+
+        for (name, job) in self.read_jobs().iter() {
+            job.paused.store(paused.get(name).copied().unwrap_or(false), Ordering::Release);
+        }
+        Ok(())
+    }
+
+    /// Recorded runs of one job, newest first
+    pub async fn history(&self, name: &str, limit: i64) -> Result<Vec<JobRun>> {
+        self.job(name)?;
+        let query = sqlx::query_as::<_, JobRun>(
+            r#"
+            SELECT id, job_name, trigger, status, started_at, finished_at, duration_ms, message
+            FROM job_runs
+            WHERE job_name = $1
+            ORDER BY started_at DESC, id DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(name)
+        .bind(limit);
+
+        timing::timed("job_run_history", query.fetch_all(&self.inner.db_pool))
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to load job history: {}", e)))
+    }
+
+    fn spawn_timer(&self, job: Arc<Job>) {
+        let scheduler = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let now = Utc::now();
+                let Some(next) = job.schedule.next_after(now) else {
+                    info!("Job {} has no further scheduled runs", job.name);
+                    return;
+                };
+                let jitter = random_jitter(job.jitter);
+                let due = next + chrono::Duration::from_std(jitter).unwrap_or_default();
+                *job.next_run.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(due);
+
+                tokio::select! {
+                    _ = tokio::time::sleep((due - now).to_std().unwrap_or_default()) => {}
+                    _ = scheduler.inner.shutdown.triggered() => return,
+                }
+                if job.paused.load(Ordering::Acquire) {
+                    debug!("Skipping scheduled run of paused job {}", job.name);
+                    continue;
+                }
+                if !scheduler.inner.leader.is_leader() {
+                    debug!("Skipping scheduled run of {}: another instance is leader", job.name);
+                    continue;
+                }
+
+                // Spawned so a long run can't hold up the timer; the next tick records a skip instead of queueing
+                let scheduler = scheduler.clone();
+                let job = job.clone();
+                tokio::spawn(async move { scheduler.execute(job, JobTrigger::Schedule).await });
+            }
+        });
+    }
+
+    async fn execute(&self, job: Arc<Job>, trigger: JobTrigger) {
+        if job.running.compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire).is_err() {
+            warn!("Skipping {} run of {}: previous run still in progress", trigger.as_str(), job.name);
+            self.record_skip(&job.name, trigger).await;
+            return;
+        }
+        let _guard = RunningGuard(job.clone());
+        let _in_flight = self.inner.shutdown.track();
+
+        let run_id = match self.record_start(&job.name, trigger).await {
+            Ok(id) => Some(id),
+            Err(e) => {
+                warn!("Failed to record start of job {}: {}", job.name, e);
+                None
+            }
+        };
+
+        let span = tracing::info_span!("job", name = %job.name, trigger = trigger.as_str(), run_id);
+        let start = Instant::now();
+        // Run on its own task so a panicking job is reported as a failure instead of taking the timer down
+        let mut run = tokio::spawn((job.run)().instrument(span));
+        let outcome = tokio::select! {
+            joined = &mut run => match joined {
+                Ok(result) => result,
+                Err(e) => Err(AppError::InternalServerError(format!("Job panicked: {}", e))),
+            },
+            // Jobs are idempotent sweeps, so the next scheduled run simply picks up where this one stopped
+            _ = self.inner.shutdown.expired() => {
+                run.abort();
+                Err(AppError::ServiceUnavailableError("Interrupted by shutdown".to_string()))
+            }
+        };
+        let duration_ms = start.elapsed().as_millis() as i64;
+
+        let (status, message) = match &outcome {
+            Ok(summary) => {
+                info!("Job {} succeeded in {}ms: {}", job.name, duration_ms, summary);
+                ("succeeded", summary.clone())
+            }
+            Err(e) => {
+                warn!("Job {} failed after {}ms: {}", job.name, duration_ms, e);
+                ("failed", e.to_string())
+            }
+        };
+
+        if let Some(id) = run_id {
+            if let Err(e) = self.record_finish(id, status, duration_ms, &message).await {
+                warn!("Failed to record outcome of job {}: {}", job.name, e);
+            }
+        }
+    }
+
+    async fn record_start(&self, name: &str, trigger: JobTrigger) -> Result<i64> {
+        Ok(sqlx::query_scalar("INSERT INTO job_runs (job_name, trigger, status) VALUES ($1, $2, 'running') RETURNING id")
+            .bind(name)
+            .bind(trigger.as_str())
+            .fetch_one(&self.inner.db_pool)
+            .await?)
+    }
+
+    async fn record_finish(&self, id: i64, status: &str, duration_ms: i64, message: &str) -> Result<()> {
+        sqlx::query("UPDATE job_runs SET status = $2, finished_at = NOW(), duration_ms = $3, message = $4 WHERE id = $1")
+            .bind(id)
+            .bind(status)
+            .bind(duration_ms)
+            .bind(message)
+            .execute(&self.inner.db_pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn record_skip(&self, name: &str, trigger: JobTrigger) {
+        let result = sqlx::query(
+            "INSERT INTO job_runs (job_name, trigger, status, finished_at, duration_ms, message) \
+             VALUES ($1, $2, 'skipped', NOW(), 0, 'Previous run still in progress')",
+        )
+        .bind(name)
+        .bind(trigger.as_str())
+        .execute(&self.inner.db_pool)
+        .await;
+
+        if let Err(e) = result {
+            warn!("Failed to record skipped run of job {}: {}", name, e);
+        }
+    }
+
+    async fn latest_runs(&self) -> Result<Vec<JobRun>> {
+        let query = sqlx::query_as::<_, JobRun>(
+            r#"
+            SELECT DISTINCT ON (job_name) id, job_name, trigger, status, started_at, finished_at, duration_ms, message
+            FROM job_runs
+            ORDER BY job_name, started_at DESC, id DESC
+            "#,
+        );
+
+        timing::timed("job_latest_runs", query.fetch_all(&self.inner.db_pool))
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to load latest job runs: {}", e)))
+    }
+
+    fn job(&self, name: &str) -> Result<Arc<Job>> {
+        self.read_jobs()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| AppError::NotFoundError(format!("Unknown job {}", name)))
+    }
+
+    fn read_jobs(&self) -> std::sync::RwLockReadGuard<'_, BTreeMap<String, Arc<Job>>> {
+        self.inner.jobs.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn random_jitter(max: Duration) -> Duration {
+    if max.is_zero() {
+        return Duration::ZERO;
+    }
+    Duration::from_millis(rand::thread_rng().gen_range(0..=max.as_millis() as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_schedules_parse_intervals_and_cron() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 10, 15, 0).unwrap();
+
+        let every = JobSchedule::parse("15m").unwrap();
+        assert_eq!(every.next_after(start), Some(start + chrono::Duration::minutes(15)));
+
+        // Five fields read as standard cron: 03:00 every day
+        let nightly = JobSchedule::parse("0 3 * * *").unwrap();
+        assert_eq!(nightly.next_after(start), Some(Utc.with_ymd_and_hms(2025, 1, 2, 3, 0, 0).unwrap()));
+
+        let with_seconds = JobSchedule::parse("30 */20 * * * *").unwrap();
+        assert_eq!(with_seconds.next_after(start), Some(Utc.with_ymd_and_hms(2025, 1, 1, 10, 20, 30).unwrap()));
+
+        assert!(JobSchedule::parse("0s").is_err());
+        assert!(JobSchedule::parse("61 * * * *").is_err());
+        assert!(JobSchedule::parse("soon").is_err());
+    }
+
+    #[test]
+    fn test_jitter_stays_in_bounds() {
+        assert_eq!(random_jitter(Duration::ZERO), Duration::ZERO);
+        for _ in 0..100 {
+            assert!(random_jitter(Duration::from_secs(5)) <= Duration::from_secs(5));
+        }
+    }
+}
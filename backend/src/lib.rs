@@ -3,7 +3,11 @@
  * Core Library Module
  */
 
+pub mod build_info;
+pub mod cli;
 pub mod database;
+pub mod jobs;
+pub mod middleware;
 pub mod models;
 pub mod routes;
 pub mod services;
@@ -15,8 +19,11 @@ pub use utils::{
     metrics::MetricsCollector,
 };
 
+/// The standalone kernel crate, for code built against this one that only needs to iterate points
+pub use fractal_engine;
+
 pub use database::{
-    connection::{DatabasePool, create_pool},
+    connection::{DatabasePool, create_pool, create_pool_with_config},
 };
 
 pub use models::{
@@ -30,6 +37,27 @@ pub use services::{
     fractal_service::FractalService,
     performance_service::PerformanceService,
     cache_service::CacheService,
+    audit_service::{AuditService, AuditSettings},
+    palette_service::PaletteService,
+    catalog_service::CatalogService,
+    gallery_service::GalleryService,
+    image_service::{DiskImageStore, ImageService},
+    webhook_service::WebhookService,
+    email_service::{EmailService, EmailSettings},
+    notification_template_service::NotificationTemplateService,
+    recording_service::{RecordingService, RecordingSettings},
+    usage_service::UsageService,
+    settings_service::SettingsService,
+    feature_flag_service::FeatureFlagService,
+    slo_service::{SloService, SloSettings},
+    export_service::ExportService,
+    animation_service::AnimationService,
+    fractal_job_service::FractalJobService,
+    fractal_cache_service::FractalCacheService,
+    sync_service::SyncService,
+    session_service::SessionService,
+    tenant_service::TenantService,
+    user_service::UserService,
 };
 
 #[derive(Clone)]
@@ -40,28 +68,145 @@ pub struct AppState {
     pub fractal_service: FractalService,
     pub performance_service: PerformanceService,
     pub cache_service: CacheService,
+    pub audit_service: AuditService,
+    pub palette_service: PaletteService,
+    pub catalog_service: CatalogService,
+    pub gallery_service: GalleryService,
+    pub image_service: ImageService,
+    pub webhook_service: WebhookService,
+    pub email_service: EmailService,
+    pub notification_templates: NotificationTemplateService,
+    pub recording_service: RecordingService,
+    pub sync_service: SyncService,
+    pub usage_service: UsageService,
+    pub session_service: SessionService,
+    pub user_service: UserService,
+    pub rate_limiter: utils::rate_limit::SharedRateLimiter,
+    pub circuit_breakers: utils::circuit_breaker::CircuitBreakers,
+    pub settings_service: SettingsService,
+    pub feature_flags: FeatureFlagService,
+    pub tenants: TenantService,
+    pub slo_service: SloService,
+    pub maintenance: middleware::MaintenanceMode,
+    pub health_monitor: routes::health::HealthMonitor,
+    pub warm_up: services::WarmUpGate,
+    pub notifications: database::NotificationHub,
     pub config: Config,
+    pub live_config: utils::live_config::LiveConfig,
+    pub log_control: utils::logging::LogControl,
+    pub started: utils::uptime::ProcessStart,
+    pub shutdown: utils::shutdown::Shutdown,
+    pub connections: middleware::ConnectionTracker,
+    pub leader: jobs::LeaderElection,
+    pub scheduler: jobs::Scheduler,
+    pub task_queue: jobs::TaskQueue,
+    pub export_service: ExportService,
+    pub animation_service: AnimationService,
+    pub fractal_jobs: FractalJobService,
+    pub fractal_cache: FractalCacheService,
     pub metrics: MetricsCollector,
 }
 
 impl AppState {
-    pub async fn new(config: Config) -> Result<Self> {
-        let db_pool = create_pool(&config.database_url).await?;
+    pub async fn new(mut config: Config) -> Result<Self> {
+        let started = utils::uptime::ProcessStart::now();
+        utils::secrets::SecretResolver::new().resolve_config(&mut config).await?;
+        utils::error::expose_error_context(config.is_development());
+
+        let db_pool = create_pool_with_config(&config.database_url, &config.database_pool_config()).await?;
 
         let redis_client = redis::Client::open(config.redis_url.clone())
             .map_err(|e| AppError::DatabaseError(format!("Redis connection failed: {}", e)))?;
+        let redis_connection = utils::redis_connection::SharedRedisConnection::new(redis_client.clone());
+
+        let metrics = MetricsCollector::with_start_time(utils::metrics::MetricsConfig::default(), started.instant())?;
+        let circuit_breakers = utils::circuit_breaker::CircuitBreakers::from_config(&config);
+        database::timing::install(
+            metrics.clone(),
+            std::time::Duration::from_millis(config.slow_query_threshold_ms),
+            circuit_breakers.postgres.clone(),
+        );
+        utils::retry::install_budget(utils::retry::RetryBudget::new(config.retry_budget_ratio, config.retry_budget_min_per_second));
 
-        let metrics = MetricsCollector::new()?;
-
-        let cache_service = CacheService::new(redis_client.clone());
+        let cache_service = CacheService::new(redis_client.clone()).with_circuit_breaker(circuit_breakers.redis.clone());
+        let rate_limiter = utils::rate_limit::from_config(&config, redis_client.clone());
         let github_service = GitHubService::new(
             config.github_token.clone(),
             cache_service.clone(),
-        );
+        )
+        .with_request_pacing(rate_limiter.clone(), config.github_rate_limit_requests)
+        .with_circuit_breaker(circuit_breakers.github.clone());
         let fractal_service = FractalService::new();
         let performance_service = PerformanceService::new(
             db_pool.clone(),
         );
+        let audit_service = AuditService::new(
+            db_pool.clone(),
+            AuditSettings::from_config(&config),
+        );
+        let palette_service = PaletteService::new(db_pool.clone());
+        let catalog_service = CatalogService::new(db_pool.clone());
+        let gallery_service = GalleryService::new(db_pool.clone());
+        let image_service = ImageService::new(
+            std::sync::Arc::new(DiskImageStore::new(&config.image_storage_path)),
+            db_pool.clone(),
+            config.image_storage_enabled,
+        );
+        let shutdown = utils::shutdown::Shutdown::new(std::time::Duration::from_secs(config.shutdown_grace_period_seconds));
+        let task_queue = jobs::TaskQueue::new(
+            redis_connection.clone(),
+            metrics.clone(),
+            jobs::QueueSettings::from_config(&config),
+            shutdown.clone(),
+        );
+        let webhook_service = WebhookService::new(
+            db_pool.clone(),
+            task_queue.clone(),
+            config.webhooks_enabled,
+            config.webhook_max_attempts,
+            config.webhook_allow_http,
+        );
+        let email_service = EmailService::new(EmailSettings::from_config(&config), task_queue.clone(), metrics.clone())?;
+        let notification_templates = NotificationTemplateService::new(db_pool.clone(), &config.frontend_url);
+        let recording_service = RecordingService::new(db_pool.clone(), RecordingSettings::from_config(&config));
+        let sync_service = SyncService::new(github_service.clone(), webhook_service.clone(), db_pool.clone());
+        let export_service = ExportService::new(db_pool.clone(), task_queue.clone(), &config.export_storage_path);
+        let animation_service = AnimationService::new(db_pool.clone(), task_queue.clone(), fractal_service.clone(), &config.animation_storage_path);
+        let fractal_jobs = FractalJobService::new(
+            redis_connection.clone(),
+            task_queue.clone(),
+            fractal_service.clone(),
+            config.fractal_job_concurrency,
+            std::time::Duration::from_secs(config.fractal_job_ttl_seconds),
+        );
+        let usage_service = UsageService::new(redis_connection.clone(), config.usage_tracking_enabled);
+        let session_service = SessionService::new(db_pool.clone(), config.session_history_limit);
+        let user_service = UserService::new(db_pool.clone(), config.user_daily_render_quota, config.expensive_render_pixels);
+        let maintenance = middleware::MaintenanceMode::new(config.maintenance_mode, config.maintenance_message.clone(), config.maintenance_retry_after);
+        let health_monitor = routes::health::HealthMonitor::from_config(&config);
+        let notifications = database::NotificationHub::new(
+            config.db_notifications_enabled && !config.database_pgbouncer_mode,
+        );
+
+        let live_config = utils::live_config::LiveConfig::new(config.clone());
+        let log_control = utils::logging::LogControl::new(&config.log_level);
+        let settings_service = SettingsService::new(db_pool.clone(), live_config.clone());
+        let feature_flags = FeatureFlagService::new(db_pool.clone());
+        let tenants = TenantService::new(db_pool.clone(), live_config.clone(), cache_service.clone(), &config.github_username);
+        let fractal_cache = FractalCacheService::new(&cache_service, fractal_service.clone(), live_config.clone());
+        let slo_service = SloService::new(db_pool.clone(), SloSettings::from_config(&config));
+        let leader = jobs::LeaderElection::new(
+            redis_connection.clone(),
+            config.leader_election_enabled,
+            std::time::Duration::from_secs(config.leader_lease_seconds),
+        );
+        let scheduler = jobs::Scheduler::new(
+            db_pool.clone(),
+            leader.clone(),
+            shutdown.clone(),
+            config.scheduler_enabled,
+            config.job_schedules.clone(),
+        );
 
         Ok(AppState {
             db_pool,
@@ -70,7 +215,42 @@ impl AppState {
             fractal_service,
             performance_service,
             cache_service,
+            audit_service,
+            palette_service,
+            catalog_service,
+            gallery_service,
+            image_service,
+            webhook_service,
+            email_service,
+            notification_templates,
+            recording_service,
+            sync_service,
+            usage_service,
+            session_service,
+            user_service,
+            rate_limiter,
+            circuit_breakers,
+            settings_service,
+            feature_flags,
+            tenants,
+            slo_service,
+            maintenance,
+            health_monitor,
+            warm_up: services::WarmUpGate::default(),
+            notifications,
             config,
+            live_config,
+            log_control,
+            started,
+            shutdown,
+            connections: middleware::ConnectionTracker::new(),
+            leader,
+            scheduler,
+            task_queue,
+            export_service,
+            animation_service,
+            fractal_jobs,
+            fractal_cache,
             metrics,
         })
     }
@@ -111,12 +291,13 @@ impl AppState {
             "system": {
                 "cpu_usage": system_info_json["cpu_usage_percent"].as_f64().unwrap_or_default(),
                 "memory_usage": system_info_json["memory_usage_percent"].as_f64().unwrap_or_default(),
-                "uptime_seconds": system_info_json["uptime_seconds"].as_u64().unwrap_or_default(),
+                "uptime_seconds": self.started.uptime_seconds(),
+                "host_uptime_seconds": system_info_json["uptime_seconds"].as_u64().unwrap_or_default(),
                 "active_processes": system_info_json["processes_count"].as_u64().unwrap_or_default()
             },
-            "version": env!("CARGO_PKG_VERSION"),
-            "build_time": env!("BUILD_TIME"),
-            "git_commit": env!("GIT_COMMIT")
+            "version": build_info::VERSION,
+            "build_time": build_info::BUILD_TIME,
+            "git_commit": build_info::GIT_COMMIT
         }))
 }
 
@@ -188,56 +369,12 @@ mod tests {
     }
 }
 
-#[cfg(feature = "bench")]
-pub mod benchmarks {
-    use criterion::{black_box, criterion_group, criterion_main, Criterion};
-    use super::*;
-
-    fn bench_fractal_generation(c: &mut Criterion) {
-        let rt = tokio::runtime::Runtime::new().unwrap();
-        let fractal_service = FractalService::new();
-
-        c.bench_function("mandelbrot_512x512", |b| {
-            b.iter(|| {
-                rt.block_on(async {
-                    let request = FractalRequest {
-                        width: 512,
-                        height: 512,
-                        center_x: -0.5,
-                        center_y: 0.0,
-                        zoom: 1.0,
-                        max_iterations: 100,
-                        fractal_type: FractalType::Mandelbrot,
-                    };
-                    black_box(fractal_service.generate_mandelbrot(request))
-                })
-            })
-        });
-    }
-
-    fn bench_performance_metrics(c: &mut Criterion) {
-        let rt = tokio::runtime::Runtime::new().unwrap();
-
-        c.bench_function("metrics_collection", |b| {
-            b.iter(|| {
-                rt.block_on(async {
-                    let metrics = MetricsCollector::new().unwrap();
-                    black_box(metrics.collect_system_metrics().await)
-                })
-            })
-        });
-    }
-
-    criterion_group!(benches, bench_fractal_generation, bench_performance_metrics);
-    criterion_main!(benches);
-}
-
 #[cfg(feature = "gpu-acceleration")]
 pub mod gpu {
-    //! GPU acceleration module for fractal generation using CUDA or OpenCL
-    //! I'm keeping this optional since not all deployment environments have GPU support
+    //! GPU acceleration module for fractal generation using wgpu compute shaders
+    //! I'm keeping this optional since not all deployment environments have GPU support; without an adapter every request falls back to the CPU
 
-    pub use crate::services::fractal_service::gpu_accelerated_generation;
+    pub use dark_performance_core::gpu::{gpu_accelerated_generation, GpuRenderer};
 }
 
 #[cfg(feature = "machine-learning")]
@@ -248,9 +385,7 @@ pub mod ml {
     pub use crate::services::performance_service::ml_performance_prediction;
 }
 
-pub const VERSION: &str = env!("CARGO_PKG_VERSION");
-pub const BUILD_TIME: &str = env!("BUILD_TIME");
-pub const GIT_COMMIT: &str = env!("GIT_COMMIT");
+pub use build_info::{BUILD_TIME, GIT_COMMIT, VERSION};
 
 pub mod async_utils {
     //! Async utilities and helpers for improved performance and error handling
@@ -258,33 +393,10 @@ pub mod async_utils {
 
     use std::future::Future;
     use std::time::Duration;
-    use tokio::time::{timeout, sleep};
+    use tokio::time::timeout;
     use crate::utils::error::{AppError, Result};
 
-    pub async fn retry_with_backoff<F, Fut, T>(
-        mut operation: F,
-        max_retries: usize,
-        initial_delay: Duration,
-    ) -> Result<T>
-    where
-        F: FnMut() -> Fut,
-        Fut: Future<Output = Result<T>>,
-    {
-        let mut delay = initial_delay;
-
-        for attempt in 0..max_retries {
-            match operation().await {
-                Ok(result) => return Ok(result),
-                Err(e) if attempt == max_retries - 1 => return Err(e),
-                Err(_) => {
-                    sleep(delay).await;
-                    delay = delay.mul_f32(1.5); // Exponential backoff
-                }
-            }
-        }
-
-        unreachable!()
-    }
+    pub use crate::utils::retry::{retry_with_backoff, RetryConfig};
 
     pub async fn with_timeout<F, T>(
         operation: F,
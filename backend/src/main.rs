@@ -6,6 +6,7 @@
 #![doc = "Dark Performance Showcase - High-performance Rust backend for computational demonstrations"]
 
 use axum::{
+    extract::State,
     routing::get,
     Router,
     http::{header, Method, HeaderName},
@@ -13,74 +14,256 @@ use axum::{
 
 use tower_http::{
     cors::{Any, CorsLayer},
-    compression::CompressionLayer,
-    trace::TraceLayer,
 };
 use tracing::{info, warn, error};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use axum_server::tls_rustls::RustlsConfig;
+use std::future::IntoFuture;
 use tokio::signal;
 
 use dark_performance_backend::{
+    build_info,
+    cli,
+    middleware,
+    models::logging::LogFilterOverride,
     routes,
     services::{
         github_service::GitHubService,
         fractal_service::FractalService,
         cache_service::CacheService,
         performance_service::PerformanceService,
+        audit_service::{AuditService, AuditSettings},
+        palette_service::PaletteService,
+        catalog_service::CatalogService,
+        gallery_service::GalleryService,
+        image_service::{DiskImageStore, ImageService},
+        webhook_service::WebhookService,
+        email_service::{self, EmailService, EmailSettings},
+        notification_template_service::NotificationTemplateService,
+        recording_service::{RecordingService, RecordingSettings},
+        usage_service::UsageService,
+        settings_service::SettingsService,
+        feature_flag_service::FeatureFlagService,
+        slo_service::{SloService, SloSettings},
+        export_service::{self, ExportService},
+        animation_service::{self, AnimationService},
+        fractal_job_service::{self, FractalJobService},
+        fractal_cache_service::FractalCacheService,
+        sync_service::{SyncService, SyncTrigger},
+        session_service::SessionService,
+        tenant_service::TenantService,
+        user_service::UserService,
+        webhook_service,
+        ServiceRegistry,
+        WarmUpGate,
     },
     utils::{
-        config::Config,
-        error::{AppError, Result},
-        metrics::MetricsCollector,
+        circuit_breaker::CircuitBreakers,
+        config::{Config, LogFormat},
+        config_source::ConfigSource,
+        error::{self, AppError, Result},
+        error_tracking,
+        live_config::LiveConfig,
+        logging,
+        rate_limit,
+        redis_connection::SharedRedisConnection,
+        retry,
+        secrets::{self, SecretRef, SecretResolver},
+        metrics::{MetricsCollector, MetricsConfig},
+        shutdown::Shutdown,
+        uptime::ProcessStart,
     },
-    database::connection::create_pool,
+    database::{self, connection::create_pool_with_config},
+    jobs::{LeaderElection, QueueSettings, Scheduler, TaskQueue},
     AppState,
 };
 
-async fn create_app_state() -> Result<AppState> {
+
+async fn create_app_state(started: ProcessStart) -> Result<AppState> {
         info!("Initializing application state");
 
-        let config = Config::from_env()?;
+        let mut config = Config::load()?;
+        SecretResolver::new().resolve_config(&mut config).await?;
+        error::expose_error_context(config.is_development());
         info!("Configuration loaded for environment: {:?}", config.environment);
 
-        let db_pool = create_pool(&config.database_url).await?;
+        let db_pool = create_pool_with_config(&config.database_url, &config.database_pool_config()).await?;
         info!("Database connection pool initialized with {} connections", db_pool.size());
 
         let redis_client = redis::Client::open(config.redis_url.clone())
             .map_err(|e| AppError::CacheError(format!("Failed to create Redis client: {}", e)))?;
         info!("Redis client initialized");
+        let redis_connection = SharedRedisConnection::new(redis_client.clone());
 
+        let circuit_breakers = CircuitBreakers::from_config(&config);
         let cache_service = CacheService::with_config(
             redis_client.clone(),
             "perf_showcase:".to_string(),
             config.cache_default_ttl,
-        );
+        )
+        .with_circuit_breaker(circuit_breakers.redis.clone());
 
         match cache_service.health_check().await {
             Ok(_) => info!("Cache service health check passed"),
             Err(e) => warn!("Cache service health check failed: {}", e),
         }
 
-        let github_service = GitHubService::new(config.github_token.clone(), cache_service.clone());
+        let rate_limiter = rate_limit::from_config(&config, redis_client.clone());
+        info!("Rate limiter initialized (backend: {:?})", config.rate_limit_backend);
+
+        let github_service = GitHubService::new(config.github_token.clone(), cache_service.clone())
+            .with_request_pacing(rate_limiter.clone(), config.github_rate_limit_requests)
+            .with_circuit_breaker(circuit_breakers.github.clone());
         info!("GitHub service initialized");
 
-        let fractal_service = FractalService::new();
-        info!("Fractal service initialized");
+        let fractal_service = FractalService::new()
+            .with_threads(config.fractal_threads)
+            .with_buffer_pool(config.fractal_buffer_pool);
+        info!("Fractal service initialized ({} render threads)", fractal_service.pools().size());
 
         let performance_service = PerformanceService::new(db_pool.clone());
         info!("Performance service initialized");
 
-        let metrics = MetricsCollector::new()?;
+        let audit_service = AuditService::new(db_pool.clone(), AuditSettings::from_config(&config));
+        info!("Audit service initialized (enabled: {})", config.audit_log_enabled);
+
+        let palette_service = PaletteService::new(db_pool.clone());
+        let catalog_service = CatalogService::new(db_pool.clone());
+        let gallery_service = GalleryService::new(db_pool.clone());
+        info!("Palette service initialized");
+
+        let image_service = ImageService::new(
+            std::sync::Arc::new(DiskImageStore::new(&config.image_storage_path)),
+            db_pool.clone(),
+            config.image_storage_enabled,
+        );
+        info!("Image service initialized (storage: {})", config.image_storage_path);
+
+        let metrics = MetricsCollector::with_start_time(MetricsConfig::default(), started.instant())?;
         info!("Metrics collector initialized");
+        database::timing::install(
+            metrics.clone(),
+            std::time::Duration::from_millis(config.slow_query_threshold_ms),
+            circuit_breakers.postgres.clone(),
+        );
+        retry::install_budget(retry::RetryBudget::new(config.retry_budget_ratio, config.retry_budget_min_per_second));
+
+        let shutdown = Shutdown::new(std::time::Duration::from_secs(config.shutdown_grace_period_seconds));
+        let task_queue = TaskQueue::new(
+            redis_connection.clone(),
+            metrics.clone(),
+            QueueSettings::from_config(&config),
+            shutdown.clone(),
+        );
+        let webhook_service = WebhookService::new(
+            db_pool.clone(),
+            task_queue.clone(),
+            config.webhooks_enabled,
+            config.webhook_max_attempts,
+            config.webhook_allow_http,
+        );
+        info!("Webhook service initialized (enabled: {})", config.webhooks_enabled);
+
+        let email_service = EmailService::new(EmailSettings::from_config(&config), task_queue.clone(), metrics.clone())?;
+        info!("Email service initialized (enabled: {})", email_service.is_enabled());
+        let notification_templates = NotificationTemplateService::new(db_pool.clone(), &config.frontend_url);
+
+        let recording_service = RecordingService::new(db_pool.clone(), RecordingSettings::from_config(&config));
+        info!("Recording service initialized (enabled: {})", config.request_recording_enabled);
+
+        let sync_service = SyncService::new(github_service.clone(), webhook_service.clone(), db_pool.clone());
+
+        let export_service = ExportService::new(db_pool.clone(), task_queue.clone(), &config.export_storage_path);
+        info!("Export service initialized (storage: {})", config.export_storage_path);
+        let animation_service = AnimationService::new(db_pool.clone(), task_queue.clone(), fractal_service.clone(), &config.animation_storage_path);
+        info!("Animation service initialized (storage: {})", config.animation_storage_path);
+        let fractal_jobs = FractalJobService::new(
+            redis_connection.clone(),
+            task_queue.clone(),
+            fractal_service.clone(),
+            config.fractal_job_concurrency,
+            std::time::Duration::from_secs(config.fractal_job_ttl_seconds),
+        );
+        info!("Fractal jobs initialized ({} at once, kept {}s)", config.fractal_job_concurrency, config.fractal_job_ttl_seconds);
+
+        let usage_service = UsageService::new(redis_connection.clone(), config.usage_tracking_enabled);
+        info!("Usage service initialized (enabled: {})", config.usage_tracking_enabled);
+
+        let session_service = SessionService::new(db_pool.clone(), config.session_history_limit);
+        let user_service = UserService::new(db_pool.clone(), config.user_daily_render_quota, config.expensive_render_pixels);
+
+        let maintenance = middleware::MaintenanceMode::new(config.maintenance_mode, config.maintenance_message.clone(), config.maintenance_retry_after);
+        if maintenance.is_enabled() {
+            warn!("Starting in maintenance mode");
+        }
+
+        let health_monitor = routes::health::HealthMonitor::from_config(&config);
+        let notifications = database::NotificationHub::new(
+            config.db_notifications_enabled && !config.database_pgbouncer_mode,
+        );
+
+        let live_config = LiveConfig::new(config.clone());
+        let log_control = logging::LogControl::new(&config.log_level);
+        let settings_service = SettingsService::new(db_pool.clone(), live_config.clone());
+        let feature_flags = FeatureFlagService::new(db_pool.clone());
+        let tenants = TenantService::new(db_pool.clone(), live_config.clone(), cache_service.clone(), &config.github_username);
+        let fractal_cache = FractalCacheService::new(&cache_service, fractal_service.clone(), live_config.clone());
+        let slo_service = SloService::new(db_pool.clone(), SloSettings::from_config(&config));
+        let leader = LeaderElection::new(
+            redis_connection.clone(),
+            config.leader_election_enabled,
+            std::time::Duration::from_secs(config.leader_lease_seconds),
+        );
+        let scheduler = Scheduler::new(
+            db_pool.clone(),
+            leader.clone(),
+            shutdown.clone(),
+            config.scheduler_enabled,
+            config.job_schedules.clone(),
+        );
 
         let app_state = AppState {
             config,
+            live_config,
+            log_control,
             db_pool,
             redis_client,
             github_service,
             fractal_service,
             cache_service,
             performance_service,
+            audit_service,
+            palette_service,
+            catalog_service,
+            gallery_service,
+            image_service,
+            webhook_service,
+            email_service,
+            notification_templates,
+            recording_service,
+            sync_service,
+            usage_service,
+            session_service,
+            user_service,
+            rate_limiter,
+            circuit_breakers,
+            settings_service,
+            feature_flags,
+            tenants,
+            slo_service,
+            leader,
+            scheduler,
+            task_queue,
+            export_service,
+            animation_service,
+            fractal_jobs,
+            fractal_cache,
+            started,
+            shutdown,
+            connections: middleware::ConnectionTracker::new(),
+            maintenance,
+            health_monitor,
+            warm_up: WarmUpGate::default(),
+            notifications,
             metrics,
         };
 
@@ -104,19 +287,41 @@ pub fn create_app_router(app_state: AppState) -> Router {
             HeaderName::from_static("x-correlation-id"),
             HeaderName::from_static("x-request-id"),
             HeaderName::from_static("x-request-start"),
+            HeaderName::from_static(middleware::session::SESSION_HEADER),
+        ])
+        .expose_headers([
+            HeaderName::from_static("x-request-id"),
+            HeaderName::from_static(middleware::session::SESSION_HEADER),
+            HeaderName::from_static("x-ratelimit-limit"),
+            HeaderName::from_static("x-ratelimit-remaining"),
+            HeaderName::from_static("x-ratelimit-reset"),
+            header::RETRY_AFTER,
+            HeaderName::from_static(routes::fractals::COMPUTATION_TIME_HEADER),
+            HeaderName::from_static(routes::fractals::RENDERER_HEADER),
+            HeaderName::from_static(routes::fractals::COMPUTE_BACKEND_HEADER),
+            HeaderName::from_static(routes::fractals::SKIPPED_PIXELS_HEADER),
+            HeaderName::from_static(routes::fractals::IMAGE_URL_HEADER),
         ])
         .allow_origin(Any);
     
-    routes::create_versioned_router()
+    let app = routes::create_policy_router(&app_state)
+        .layer(axum::middleware::from_fn_with_state(app_state.clone(), middleware::error_tracking_middleware))
+        .layer(axum::middleware::from_fn_with_state(app_state.clone(), middleware::log_sampling_middleware))
         .layer(cors)
-        .layer(CompressionLayer::new())
-        .layer(TraceLayer::new_for_http())
+        .layer(middleware::compression_layer(&app_state.config))
+        .layer(logging::http_trace_layer())
+        .layer(axum::middleware::from_fn(middleware::request_id_middleware))
         .route("/metrics", get(prometheus_metrics))
-        .with_state(app_state)
+        .with_state(app_state.clone());
+
+    // Tenant prefixes are stripped before routing, so this layer wraps the finished router
+    Router::new()
+        .fallback_service(app)
+        .layer(axum::middleware::from_fn_with_state(app_state, middleware::tenant_middleware))
 }
 
 
-async fn prometheus_metrics() -> Result<String> {
+async fn prometheus_metrics(State(app_state): State<AppState>) -> Result<String> {
     let metrics = format!(
         "# HELP app_requests_total Total number of requests\n\
          # TYPE app_requests_total counter\n\
@@ -132,9 +337,19 @@ async fn prometheus_metrics() -> Result<String> {
          \n\
          # HELP app_info Application information\n\
          # TYPE app_info gauge\n\
-         app_info{{version=\"{}\",rust_version=\"{}\"}} 1\n",
-        env!("CARGO_PKG_VERSION"),
-        option_env!("BUILD_RUST_VERSION").unwrap_or("unknown"),
+         app_info{{version=\"{}\",rust_version=\"{}\"}} 1\n\
+         \n\
+         # HELP process_start_time_seconds Start time of the process since unix epoch in seconds\n\
+         # TYPE process_start_time_seconds gauge\n\
+         process_start_time_seconds {}\n\
+         \n\
+         # HELP process_uptime_seconds Seconds since the process started\n\
+         # TYPE process_uptime_seconds gauge\n\
+         process_uptime_seconds {}\n",
+        build_info::VERSION,
+        build_info::RUST_VERSION,
+        app_state.started.started_at().timestamp(),
+        app_state.started.uptime_seconds(),
     );
 
     Ok(metrics)
@@ -145,52 +360,422 @@ async fn prometheus_metrics() -> Result<String> {
 ///
 #[tokio::main]
 pub async fn main() -> Result<()> {
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::new(
-            std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
-        ))
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    let started = ProcessStart::now();
+    let cli = <cli::Cli as clap::Parser>::parse();
+    if let Some(command) = cli.command {
+        std::process::exit(cli::run(command).await);
+    }
+
+    // The full config isn't loaded yet, so read just the log settings; a broken source fails properly in create_app_state
+    let log_source = ConfigSource::layered().unwrap_or_else(|_| ConfigSource::env_only());
+    let log_filter_handle = logging::init(
+        LogFormat::from_source(&log_source).unwrap_or(LogFormat::Plain),
+        &log_source.var("RUST_LOG").unwrap_or_else(|| "info".to_string()),
+    );
 
     info!("Starting Dark Performance Showcase backend");
 
-    let app_state = create_app_state().await?;
-
-    info!("Running database migrations");
-    match sqlx::migrate!("src/database/migrations").run(&app_state.db_pool).await {
-        Ok(_) => info!("Database migrations completed successfully"),
-        Err(e) => {
-            if e.to_string().contains("already exists") {
-                warn!("Database tables already exist, skipping migrations");
-            } else {
-                error!("Database migration failed: {}", e);
-                return Err(AppError::DatabaseError(format!("Migration failed: {}", e)));
+    let app_state = create_app_state(started).await?;
+    let _error_tracking = error_tracking::init(&app_state.config);
+
+    // Migrations take advisory locks and run DDL across statements, which a transaction-pooling pgBouncer splits between backends
+    if app_state.config.database_pgbouncer_mode {
+        warn!("pgBouncer mode: skipping startup migrations; apply them with `showcase-admin migrate` and DATABASE_URL pointed straight at Postgres");
+    } else {
+        info!("Running database migrations");
+        match database::MIGRATOR.run(&app_state.db_pool).await {
+            Ok(_) => info!("Database migrations completed successfully"),
+            Err(e) => {
+                if e.to_string().contains("already exists") {
+                    warn!("Database tables already exist, skipping migrations");
+                } else {
+                    error!("Database migration failed: {}", e);
+                    return Err(AppError::DatabaseError(format!("Migration failed: {}", e)));
+                }
             }
         }
     }
 
+    if let Err(e) = app_state.settings_service.refresh().await {
+        warn!("Failed to load runtime settings, using environment values: {}", e);
+    }
+    match app_state.feature_flags.refresh().await {
+        Ok(count) => info!("Loaded {} feature flags", count),
+        Err(e) => warn!("Failed to load feature flags, all flags are off: {}", e),
+    }
+    match app_state.tenants.refresh().await {
+        Ok(count) => info!("Loaded {} tenants", count),
+        Err(e) => warn!("Failed to load tenants, serving only the default showcase: {}", e),
+    }
+    match app_state.notification_templates.refresh().await {
+        Ok(count) => info!("Loaded {} notification templates", count),
+        Err(e) => warn!("Failed to load notification templates, alerts use the built-in wording: {}", e),
+    }
+
+    app_state.health_monitor.spawn(app_state.clone());
+    spawn_warm_up(&app_state);
+
+    app_state.log_control.attach(log_filter_handle)?;
+    spawn_live_config_sync(app_state.clone());
+    app_state.live_config.clone().spawn_watcher(
+        std::time::Duration::from_secs(app_state.config.config_reload_interval_seconds),
+    );
+
+    if app_state.notifications.spawn_listener(app_state.db_pool.clone()).is_some() {
+        spawn_notification_handlers(app_state.clone());
+    }
+
+    spawn_queue_workers(&app_state)?;
+    register_jobs(&app_state)?;
+    app_state.leader.spawn();
+    if let Err(e) = app_state.scheduler.start().await {
+        warn!("Failed to start job scheduler: {}", e);
+    }
+
+
+    if app_state.config.db_pool_metrics_interval_seconds > 0 {
+        database::ConnectionPoolMonitor::new(
+            app_state.db_pool.clone(),
+            app_state.metrics.clone(),
+            app_state.config.database_max_connections,
+            std::time::Duration::from_secs(app_state.config.db_pool_metrics_interval_seconds),
+        )
+        .spawn();
+    }
+
+    if app_state.config.github_token_rotation_seconds > 0 {
+        if let Some(reference) = SecretRef::configured("GITHUB_TOKEN")?.filter(SecretRef::can_rotate) {
+            let github_service = app_state.github_service.clone();
+            secrets::spawn_rotation(
+                SecretResolver::new(),
+                reference,
+                app_state.config.github_token.clone(),
+                std::time::Duration::from_secs(app_state.config.github_token_rotation_seconds),
+                move |token| {
+                    if let Err(e) = github_service.set_token(token) {
+                        warn!("Rejected rotated GitHub token: {}", e);
+                    }
+                },
+            );
+        }
+    }
+
     let app = create_app_router(app_state.clone());
 
     let addr = app_state.config.socket_addr()?;
     info!("Server starting on {}", addr);
 
-    let listener = tokio::net::TcpListener::bind(&addr).await
-        .map_err(|e| AppError::ConfigurationError(format!("Failed to bind to address {}: {}", addr, e)))?;
-
-    info!("Performance Showcase backend is running on {}", addr);
+    let scheme = if app_state.config.tls_enabled() { "https" } else { "http" };
+    info!("Performance Showcase backend is running on {}://{}", scheme, addr);
     info!("Frontend URL: {}", app_state.config.frontend_url);
-    info!("Metrics available at: http://{}/metrics", addr);
-    info!("Health check available at: http://{}/health", addr);
+    info!("Metrics available at: {}://{}/metrics", scheme, addr);
+    info!("Health check available at: {}://{}/health", scheme, addr);
+
+    app_state.connections.spawn_reporter(app_state.metrics.clone());
+    app_state.circuit_breakers.spawn_reporter(app_state.metrics.clone());
+
+    let shutdown = app_state.shutdown.clone();
+    let connections = app_state.connections.clone();
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        shutdown.begin();
+        info!("Waiting for {} open connections to close", connections.active());
+    });
+
+    match (&app_state.config.tls_cert_path, &app_state.config.tls_key_path) {
+        (Some(cert_path), Some(key_path)) => serve_tls(app, addr, cert_path, key_path, &app_state).await?,
+        _ => {
+            let listener = tokio::net::TcpListener::bind(&addr).await
+                .map_err(|e| AppError::ConfigurationError(format!("Failed to bind to address {}: {}", addr, e)))?;
+
+            let shutdown = app_state.shutdown.clone();
+            let make_service = app_state.connections.wrap(app.into_make_service_with_connect_info::<std::net::SocketAddr>());
+            let server = axum::serve(listener, make_service)
+                .with_graceful_shutdown(async move { shutdown.triggered().await })
+                .into_future();
+            tokio::select! {
+                result = server => result.map_err(|e| AppError::InternalServerError(format!("Server error: {}", e)))?,
+                _ = app_state.shutdown.expired() => warn!(
+                    "Cut off {} connections still open at the end of the shutdown grace period",
+                    app_state.connections.active()
+                ),
+            }
+        }
+    }
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await
-        .map_err(|e| AppError::InternalServerError(format!("Server error: {}", e)))?;
+    drain_and_close(&app_state).await;
+    Ok(())
+}
+
+///
+/// Lets in-flight jobs finish or checkpoint, hands off leadership, and closes the database pool
+///
+async fn drain_and_close(app_state: &AppState) {
+    if app_state.shutdown.drain().await {
+        info!("All in-flight jobs finished");
+    }
+
+    if let Err(e) = app_state.leader.resign().await {
+        warn!("Failed to release leadership on shutdown: {}", e);
+    }
+
+    app_state.db_pool.close().await;
+    info!("Server shut down gracefully");
+}
+
+///
+/// Warms caches and the fractal thread pool in the background; /health/ready reports 503 until this finishes
+///
+fn spawn_warm_up(app_state: &AppState) {
+    let registry = ServiceRegistry::from_app_state(app_state);
+    let gate = app_state.warm_up.clone();
+    let username = app_state.config.github_username.clone();
+    let timeout = std::time::Duration::from_secs(app_state.config.warm_up_timeout_seconds);
+
+    tokio::spawn(async move {
+        let start = std::time::Instant::now();
+        let timed_out = match tokio::time::timeout(timeout, registry.warm_up(&username)).await {
+            Ok(Ok(())) => false,
+            Ok(Err(e)) => {
+                warn!("Service warm-up failed: {}", e);
+                false
+            }
+            Err(_) => {
+                warn!("Service warm-up still running after {:?}; reporting ready anyway", timeout);
+                true
+            }
+        };
+        gate.complete(start.elapsed(), timed_out);
+        info!("Ready for traffic after {:?} of warm-up", start.elapsed());
+    });
+}
+
+///
+/// Starts the task queue workers for webhook deliveries, emails, export jobs, animations, and fractal jobs
+///
+fn spawn_queue_workers(app_state: &AppState) -> Result<()> {
+    let webhook_service = app_state.webhook_service.clone();
+    app_state.task_queue.spawn_workers(webhook_service::DELIVERY_QUEUE, move |task| {
+        let webhook_service = webhook_service.clone();
+        async move { webhook_service.process_delivery(task).await }
+    })?;
+
+    let email_service = app_state.email_service.clone();
+    app_state.task_queue.spawn_workers(email_service::EMAIL_QUEUE, move |task| {
+        let email_service = email_service.clone();
+        async move { email_service.process_delivery(task).await }
+    })?;
+
+    let export_service = app_state.export_service.clone();
+    app_state.task_queue.spawn_workers(export_service::EXPORT_QUEUE, move |task| {
+        let export_service = export_service.clone();
+        async move { export_service.run(task).await }
+    })?;
+
+    let animation_service = app_state.animation_service.clone();
+    app_state.task_queue.spawn_workers(animation_service::ANIMATION_QUEUE, move |task| {
+        let animation_service = animation_service.clone();
+        async move { animation_service.run(task).await }
+    })?;
+
+    let fractal_jobs = app_state.fractal_jobs.clone();
+    app_state.task_queue.spawn_workers(fractal_job_service::FRACTAL_JOB_QUEUE, move |task| {
+        let fractal_jobs = fractal_jobs.clone();
+        async move { fractal_jobs.run(task).await }
+    })?;
 
-    info!("Server shutting down gracefully");
     Ok(())
 }
 
+///
+/// Registers the periodic maintenance jobs; JOB_SCHEDULES can move any of them off its default schedule
+///
+fn register_jobs(app_state: &AppState) -> Result<()> {
+    let jitter = std::time::Duration::from_secs(30);
+
+    if app_state.config.retention_cleanup_enabled {
+        let pool = app_state.db_pool.clone();
+        let metrics = app_state.metrics.clone();
+        let policy = database::RetentionPolicy::from_config(&app_state.config);
+        app_state.scheduler.register(
+            "retention_cleanup",
+            "Deletes rows older than their retention window",
+            &format!("{}s", app_state.config.retention_cleanup_interval_seconds),
+            jitter,
+            move || {
+                let (pool, metrics, policy) = (pool.clone(), metrics.clone(), policy.clone());
+                async move { database::retention::run_scheduled_cleanup(&pool, &metrics, &policy).await }
+            },
+        )?;
+    }
+
+    if app_state.config.retention_cleanup_enabled && app_state.image_service.is_enabled() {
+        let image_service = app_state.image_service.clone();
+        let days = app_state.config.image_retention_days;
+        app_state.scheduler.register(
+            "image_cleanup",
+            "Deletes stored renders unused for IMAGE_RETENTION_DAYS that no saved fractal points at",
+            &format!("{}s", app_state.config.retention_cleanup_interval_seconds),
+            jitter,
+            move || {
+                let image_service = image_service.clone();
+                async move { Ok(format!("Removed {} images", image_service.prune(days).await?)) }
+            },
+        )?;
+    }
+
+    let sync_service = app_state.sync_service.clone();
+    let tenants = app_state.tenants.clone();
+    app_state.scheduler.register(
+        "github_sync",
+        "Refreshes the stored repository lists of the default and every enabled tenant's GitHub user",
+        &format!("{}s", app_state.config.github_cache_ttl),
+        jitter,
+        move || {
+            let (sync_service, owners) = (sync_service.clone(), tenants.github_owners());
+            async move {
+                let (mut stored, mut fetched) = (0, 0);
+                for owner in &owners {
+                    let report = sync_service.sync(owner, SyncTrigger::Scheduled).await?.report;
+                    stored += report.stored;
+                    fetched += report.fetched;
+                }
+                Ok(format!("Synced {} of {} repositories across {} accounts", stored, fetched, owners.len()))
+            }
+        },
+    )?;
+
+    Ok(())
+}
+
+///
+/// Pushes hot-reloaded settings into the pieces that don't read LiveConfig per request: the log filter, cache TTL, sync interval, and GitHub pacing budget
+///
+fn spawn_live_config_sync(app_state: AppState) {
+    let mut changes = app_state.live_config.subscribe();
+    // The file-provided log level may differ from the RUST_LOG the subscriber started with
+    changes.mark_changed();
+
+    tokio::spawn(async move {
+        while changes.changed().await.is_ok() {
+            let config = changes.borrow_and_update().clone();
+
+            if let Err(e) = app_state.log_control.set_base(&config.log_level) {
+                warn!("Ignoring log level '{}': {}", config.log_level, e);
+            }
+            app_state.cache_service.set_default_ttl(config.cache_default_ttl);
+            app_state.github_service.set_sync_interval(config.github_cache_ttl);
+            app_state.github_service.set_request_budget(config.github_rate_limit_requests);
+        }
+    });
+}
+
+///
+/// Reacts to database notifications: repository changes invalidate cached details, config changes sync runtime switches
+///
+fn spawn_notification_handlers(app_state: AppState) {
+    let mut receiver = app_state.notifications.subscribe();
+
+    tokio::spawn(async move {
+        loop {
+            let notification = match receiver.recv().await {
+                Ok(notification) => notification,
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Notification handler lagged, skipped {} notifications", skipped);
+                    continue;
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => return,
+            };
+
+            match notification.channel {
+                database::NotificationChannel::RepoUpdated => {
+                    let owner = notification.payload["owner"].as_str().unwrap_or_default();
+                    let name = notification.payload["name"].as_str().unwrap_or_default();
+                    if !owner.is_empty() && !name.is_empty() {
+                        // Each tenant showcasing this owner caches the details under its own prefix
+                        for tenant in app_state.tenants.for_owner(owner) {
+                            let github_service = app_state.github_service.with_cache(tenant.cache.clone());
+                            if let Err(e) = github_service.invalidate_repository_cache(owner, name).await {
+                                warn!("Failed to invalidate cache for {}/{} (tenant {}): {}", owner, name, tenant.slug, e);
+                            }
+                        }
+                    }
+                }
+                database::NotificationChannel::ConfigChanged => {
+                    if notification.payload["kind"] == "maintenance" {
+                        match serde_json::from_value::<middleware::maintenance::MaintenanceUpdate>(notification.payload["update"].clone()) {
+                            Ok(update) => { app_state.maintenance.apply(update); }
+                            Err(e) => warn!("Ignoring malformed maintenance notification: {}", e),
+                        }
+                    } else if notification.payload["kind"] == "settings" {
+                        if let Err(e) = app_state.settings_service.refresh().await {
+                            warn!("Failed to reload runtime settings: {}", e);
+                        }
+                    } else if notification.payload["kind"] == "log_filter" {
+                        apply_log_filter_notification(&app_state, &notification.payload);
+                    } else if notification.payload["kind"] == "feature_flags" {
+                        if let Err(e) = app_state.feature_flags.refresh().await {
+                            warn!("Failed to reload feature flags: {}", e);
+                        }
+                    } else if notification.payload["kind"] == "tenants" {
+                        if let Err(e) = app_state.tenants.refresh().await {
+                            warn!("Failed to reload tenants: {}", e);
+                        }
+                    } else if notification.payload["kind"] == "notification_templates" {
+                        if let Err(e) = app_state.notification_templates.refresh().await {
+                            warn!("Failed to reload notification templates: {}", e);
+                        }
+                    } else if notification.payload["kind"] == "jobs" {
+                        if let Err(e) = app_state.scheduler.refresh_paused().await {
+                            warn!("Failed to reload paused jobs: {}", e);
+                        }
+                    }
+                }
+            }
+        }
+    });
+}
+
+///
+/// Mirrors a log level override made on another instance
+///
+fn apply_log_filter_notification(app_state: &AppState, payload: &serde_json::Value) {
+    let target = payload["target"].as_str().unwrap_or_default();
+    let result = match payload.get("filter").filter(|filter| !filter.is_null()) {
+        Some(filter) => serde_json::from_value::<LogFilterOverride>(filter.clone())
+            .map_err(|e| AppError::SerializationError(e.to_string()))
+            .and_then(|filter| app_state.log_control.set_override(filter)),
+        None => app_state.log_control.clear_override(target),
+    };
+    if let Err(e) = result {
+        warn!("Ignoring log filter notification for {}: {}", target, e);
+    }
+}
+
+///
+/// Serves the router over TLS, advertising h2 and http/1.1 via ALPN
+///
+async fn serve_tls(app: Router, addr: std::net::SocketAddr, cert_path: &str, key_path: &str, app_state: &AppState) -> Result<()> {
+    let tls_config = RustlsConfig::from_pem_file(cert_path, key_path).await
+        .map_err(|e| AppError::ConfigurationError(format!("Failed to load TLS certificate/key: {}", e)))?;
+    info!("TLS enabled with certificate {} (ALPN: h2, http/1.1)", cert_path);
+
+    // axum-server drives shutdown through a handle rather than a future, so I'm bridging the signal here
+    let handle = axum_server::Handle::new();
+    let shutdown_handle = handle.clone();
+    let shutdown = app_state.shutdown.clone();
+    tokio::spawn(async move {
+        shutdown.triggered().await;
+        shutdown_handle.graceful_shutdown(Some(shutdown.grace_period()));
+    });
+
+    axum_server::bind_rustls(addr, tls_config)
+        .handle(handle)
+        .serve(app_state.connections.wrap(app.into_make_service_with_connect_info::<std::net::SocketAddr>()))
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Server error: {}", e)))
+}
+
 async fn shutdown_signal() {
     let ctrl_c = async {
         signal::ctrl_c()
@@ -0,0 +1,103 @@
+/*
+ * Admin authentication extractor guarding operational endpoints behind a shared bearer token.
+ * I'm keeping admin access opt-in: without ADMIN_API_TOKEN configured every admin route refuses requests.
+ */
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderMap},
+};
+
+use crate::{
+    utils::error::AppError,
+    AppState,
+};
+
+/// Marker extractor proving the caller presented the configured admin token
+/// I'm using an extractor instead of a layer so admin handlers opt in explicitly
+#[derive(Debug, Clone)]
+pub struct AdminAuth;
+
+#[async_trait]
+impl FromRequestParts<AppState> for AdminAuth {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let expected = state.config.admin_api_token.as_deref().ok_or_else(|| {
+            AppError::AuthorizationError("Admin API is disabled".to_string())
+        })?;
+
+        match bearer_token(&parts.headers) {
+            Some(token) if constant_time_eq(token.as_bytes(), expected.as_bytes()) => Ok(AdminAuth),
+            Some(_) => Err(AppError::AuthorizationError("Invalid admin token".to_string())),
+            None => Err(AppError::AuthenticationError("Missing admin token".to_string())),
+        }
+    }
+}
+
+/// Extract a bearer token from the Authorization header, falling back to X-API-Key
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or_else(|| headers.get("x-api-key").and_then(|value| value.to_str().ok()))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+/// Identify who made a request without ever storing the raw credential
+/// I'm hashing presented tokens so audit rows can be correlated but not replayed
+pub fn request_principal(headers: &HeaderMap, admin_token: Option<&str>) -> Option<String> {
+    let token = bearer_token(headers)?;
+
+    if admin_token.is_some_and(|admin| constant_time_eq(token.as_bytes(), admin.as_bytes())) {
+        return Some("admin".to_string());
+    }
+
+    Some(key_principal(token))
+}
+
+/// The `key:` principal a non-admin token is tracked under, as shown in audit and usage data
+pub fn key_principal(token: &str) -> String {
+    let digest = crate::utils::Utils::hash_string(token);
+    format!("key:{}", &digest[..16])
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_bearer_token_sources() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(bearer_token(&headers), None);
+
+        headers.insert("x-api-key", HeaderValue::from_static("abc"));
+        assert_eq!(bearer_token(&headers), Some("abc"));
+
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer xyz"));
+        assert_eq!(bearer_token(&headers), Some("xyz"));
+    }
+
+    #[test]
+    fn test_principal_never_contains_raw_token() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer super-secret"));
+
+        let principal = request_principal(&headers, None).unwrap();
+        assert!(principal.starts_with("key:"));
+        assert!(!principal.contains("super-secret"));
+
+        assert_eq!(request_principal(&headers, Some("super-secret")).as_deref(), Some("admin"));
+    }
+}
@@ -0,0 +1,104 @@
+/*
+ * Request audit middleware capturing method, path, principal, client address, status, and latency.
+ * I'm applying sampling and PII redaction here before anything reaches the audit service.
+ */
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, Request},
+    middleware::Next,
+    response::Response,
+};
+use std::net::IpAddr;
+use std::time::Instant;
+
+use crate::{
+    middleware::{admin::request_principal, client_ip::client_ip_from_parts},
+    models::{AuditAction, AuditLog, HTTP_REQUEST_AUDIT_ENTITY},
+    AppState,
+};
+
+/// Record an audit entry for every sampled request passing through the router
+/// I'm measuring latency around the inner service so the number matches what clients observe
+pub async fn audit_middleware(
+    State(app_state): State<AppState>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let settings = app_state.audit_service.settings().clone();
+    if !settings.enabled {
+        return next.run(request).await;
+    }
+
+    let start_time = Instant::now();
+    let method = request.method().as_str().to_string();
+    let path = request.uri().path().to_string();
+    let query = request.uri().query().map(str::to_string);
+    let user_agent = request
+        .headers()
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let principal = request_principal(request.headers(), app_state.config.admin_api_token.as_deref());
+    let (parts, body) = request.into_parts();
+    let client_ip = client_ip_from_parts(&parts, &app_state.config.trusted_proxies);
+
+    let response = next.run(Request::from_parts(parts, body)).await;
+
+    let status_code = response.status().as_u16();
+    if !settings.should_record(status_code) {
+        return response;
+    }
+
+    let (ip_address, metadata) = if settings.redact_pii {
+        (client_ip.map(redact_ip), serde_json::json!({ "redacted": true }))
+    } else {
+        (client_ip.map(|ip| ip.to_string()), serde_json::json!({ "query": query }))
+    };
+
+    app_state.audit_service.record(AuditLog {
+        id: uuid::Uuid::new_v4(),
+        entity_type: HTTP_REQUEST_AUDIT_ENTITY.to_string(),
+        entity_id: None,
+        action: AuditAction::from_http(&method, status_code),
+        user_id: principal,
+        ip_address,
+        user_agent: if settings.redact_pii { None } else { user_agent },
+        method: Some(method),
+        path: Some(path),
+        status_code: Some(status_code as i32),
+        latency_ms: Some(start_time.elapsed().as_millis() as i64),
+        timestamp: chrono::Utc::now(),
+        changes: None,
+        metadata: Some(metadata),
+    });
+
+    response
+}
+
+/// Truncate an address to its network prefix so individuals can't be singled out
+/// I'm keeping a /24 for IPv4 and a /48 for IPv6, which is still useful for abuse triage
+fn redact_ip(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, c, _] = v4.octets();
+            format!("{}.{}.{}.0", a, b, c)
+        }
+        IpAddr::V6(v6) => {
+            let segments = v6.segments();
+            format!("{:x}:{:x}:{:x}::", segments[0], segments[1], segments[2])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_ip() {
+        assert_eq!(redact_ip("203.0.113.77".parse().unwrap()), "203.0.113.0");
+        assert_eq!(redact_ip("2001:db8:abcd:12::1".parse().unwrap()), "2001:db8:abcd::");
+    }
+}
@@ -0,0 +1,139 @@
+/*
+ * Client address extractor that only believes forwarding headers set by proxies we actually trust.
+ * I'm walking X-Forwarded-For from the nearest hop outwards so a client can't spoof its address by prepending entries.
+ */
+
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts},
+    http::{request::Parts, HeaderMap},
+};
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+
+use crate::{
+    utils::{error::AppError, network::IpCidr},
+    AppState,
+};
+
+/// The resolved address of the client that originated the request
+/// I'm exposing this as an extractor so handlers and middleware share one resolution policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+#[async_trait]
+impl FromRequestParts<AppState> for ClientIp {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        client_ip_from_parts(parts, &state.config.trusted_proxies)
+            .map(ClientIp)
+            .ok_or_else(|| AppError::bad_request("Unable to determine client address"))
+    }
+}
+
+/// Resolve the client address from request parts using the socket peer and trusted proxy list
+pub fn client_ip_from_parts(parts: &Parts, trusted_proxies: &[IpCidr]) -> Option<IpAddr> {
+    let peer = parts
+        .extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+
+    resolve_client_ip(&parts.headers, peer, trusted_proxies)
+}
+
+/// Determine the originating client address
+/// I'm only consulting forwarding headers when the socket peer is a trusted proxy, then skipping
+/// every trusted hop in X-Forwarded-For so the first untrusted address is the one we report
+pub fn resolve_client_ip(headers: &HeaderMap, peer: Option<IpAddr>, trusted_proxies: &[IpCidr]) -> Option<IpAddr> {
+    let is_trusted = |ip: IpAddr| trusted_proxies.iter().any(|cidr| cidr.contains(ip));
+
+    let peer = match peer {
+        Some(peer) if is_trusted(peer) => peer,
+        other => return other,
+    };
+
+    let forwarded: Vec<IpAddr> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|hop| hop.trim().parse().ok())
+        .collect();
+
+    if !forwarded.is_empty() {
+        // Every hop is a trusted proxy, so the left-most entry is the best we have
+        return forwarded
+            .iter()
+            .rev()
+            .find(|ip| !is_trusted(**ip))
+            .or_else(|| forwarded.first())
+            .copied();
+    }
+
+    headers
+        .get("x-real-ip")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .or(Some(peer))
+}
+
+/// The network a client is budgeted as: its IPv4 address, or the /64 around its IPv6 address
+/// I'm grouping IPv6 by /64 because a single subscriber is usually handed a whole one and can rotate through it freely
+pub fn client_network(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => v4.to_string(),
+            None => {
+                let segments = v6.segments();
+                let network = Ipv6Addr::new(segments[0], segments[1], segments[2], segments[3], 0, 0, 0, 0);
+                format!("{}/64", network)
+            }
+        },
+        IpAddr::V4(v4) => v4.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trusted() -> Vec<IpCidr> {
+        vec!["10.0.0.0/8".parse().unwrap(), "127.0.0.1".parse().unwrap()]
+    }
+
+    fn headers(forwarded_for: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", forwarded_for.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_untrusted_peer_ignores_forwarding_headers() {
+        let peer = Some("203.0.113.9".parse().unwrap());
+        let resolved = resolve_client_ip(&headers("198.51.100.7"), peer, &trusted());
+        assert_eq!(resolved, peer);
+    }
+
+    #[test]
+    fn test_trusted_hops_are_skipped() {
+        let peer = Some("127.0.0.1".parse().unwrap());
+        let resolved = resolve_client_ip(&headers("6.6.6.6, 198.51.100.7, 10.1.1.1"), peer, &trusted());
+        assert_eq!(resolved, Some("198.51.100.7".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_trusted_peer_without_headers_uses_socket() {
+        let peer = Some("10.0.0.5".parse().unwrap());
+        assert_eq!(resolve_client_ip(&HeaderMap::new(), peer, &trusted()), peer);
+    }
+
+    #[test]
+    fn test_ipv6_clients_share_their_64() {
+        let network = |ip: &str| client_network(ip.parse().unwrap());
+        assert_eq!(network("2001:db8:1:2:aaaa::1"), "2001:db8:1:2::/64");
+        assert_eq!(network("2001:db8:1:2:ffff:1:2:3"), network("2001:db8:1:2::9"));
+        assert_ne!(network("2001:db8:1:3::1"), network("2001:db8:1:2::1"));
+        assert_eq!(network("::ffff:198.51.100.7"), "198.51.100.7");
+        assert_eq!(network("198.51.100.7"), "198.51.100.7");
+    }
+}
@@ -0,0 +1,138 @@
+/*
+ * Response compression negotiated from Accept-Encoding across zstd, brotli, gzip, and deflate, with the encodings and level taken from config.
+ * I'm leaving payloads that are already compressed, like PNG renders and backup archives, untouched since a second pass only costs CPU.
+ */
+
+use axum::http::{header, Response};
+use tower_http::compression::{
+    predicate::{Predicate, SizeAbove},
+    CompressionLayer, CompressionLevel,
+};
+
+use crate::utils::config::{CompressionQuality, Config};
+
+/// Content types whose bodies are already compressed
+const PRECOMPRESSED_TYPES: [&str; 6] = [
+    "application/gzip",
+    "application/zip",
+    "application/zstd",
+    "application/x-bzip2",
+    "application/x-xz",
+    "application/x-7z-compressed",
+];
+
+/// Compress large responses unless their content is already compressed or has to reach the client as it's produced
+#[derive(Debug, Clone, Copy)]
+pub struct CompressiblePayload {
+    min_size: SizeAbove,
+}
+
+impl CompressiblePayload {
+    pub fn new(min_size_bytes: u16) -> Self {
+        Self { min_size: SizeAbove::new(min_size_bytes) }
+    }
+}
+
+impl Predicate for CompressiblePayload {
+    fn should_compress<B>(&self, response: &Response<B>) -> bool
+    where
+        B: axum::body::HttpBody,
+    {
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        !is_incompressible(content_type) && self.min_size.should_compress(response)
+    }
+}
+
+/// Images, audio, video, and archives gain nothing from compression; event streams and gRPC would stall behind the encoder's buffer
+fn is_incompressible(content_type: &str) -> bool {
+    let essence = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    (essence.starts_with("image/") && essence != "image/svg+xml")
+        || essence.starts_with("audio/")
+        || essence.starts_with("video/")
+        || essence.starts_with("application/grpc")
+        || essence == "text/event-stream"
+        || PRECOMPRESSED_TYPES.contains(&essence.as_str())
+}
+
+/// The compression layer for the configured encodings and level; with compression off it passes every response through
+pub fn compression_layer(config: &Config) -> CompressionLayer<CompressiblePayload> {
+    let enabled = |encoding: &str| config.compression_enabled && config.compression_encodings.iter().any(|e| e == encoding);
+    let min_size = u16::try_from(config.compression_min_size_bytes).unwrap_or(u16::MAX);
+
+    CompressionLayer::new()
+        .zstd(enabled("zstd"))
+        .br(enabled("br"))
+        .gzip(enabled("gzip"))
+        .deflate(enabled("deflate"))
+        .quality(match config.compression_level {
+            CompressionQuality::Fastest => CompressionLevel::Fastest,
+            CompressionQuality::Default => CompressionLevel::Default,
+            CompressionQuality::Best => CompressionLevel::Best,
+            CompressionQuality::Precise(level) => CompressionLevel::Precise(level),
+        })
+        .compress_when(CompressiblePayload::new(min_size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::config::ConfigBuilder;
+    use axum::{body::Body, http::Request, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn app(config: &Config) -> Router {
+        Router::new()
+            .route("/buffer", get(|| async { "0123456789abcdef".repeat(512) }))
+            .route("/tiny", get(|| async { "ok" }))
+            .route("/render.png", get(|| async { ([(header::CONTENT_TYPE, "image/png")], vec![0u8; 8192]) }))
+            .layer(compression_layer(config))
+    }
+
+    fn config() -> Config {
+        ConfigBuilder::new()
+            .database_url("postgresql://localhost/test")
+            .github_token("token")
+            .build()
+            .unwrap()
+    }
+
+    async fn encoding(app: Router, path: &str, accept: &str) -> Option<String> {
+        let request = Request::get(path).header(header::ACCEPT_ENCODING, accept).body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        response
+            .headers()
+            .get(header::CONTENT_ENCODING)
+            .map(|value| value.to_str().unwrap().to_string())
+    }
+
+    #[tokio::test]
+    async fn test_best_encoding_is_negotiated() {
+        let config = config();
+        assert_eq!(encoding(app(&config), "/buffer", "gzip, br, zstd").await.as_deref(), Some("zstd"));
+        assert_eq!(encoding(app(&config), "/buffer", "gzip, br").await.as_deref(), Some("br"));
+        assert_eq!(encoding(app(&config), "/buffer", "zstd;q=0.5, gzip").await.as_deref(), Some("gzip"));
+        assert_eq!(encoding(app(&config), "/tiny", "zstd").await, None);
+        assert_eq!(encoding(app(&config), "/render.png", "zstd, br, gzip").await, None);
+
+        let gzip_only = Config { compression_encodings: vec!["gzip".to_string()], ..config.clone() };
+        assert_eq!(encoding(app(&gzip_only), "/buffer", "zstd, br, gzip").await.as_deref(), Some("gzip"));
+
+        let disabled = Config { compression_enabled: false, ..config };
+        assert_eq!(encoding(app(&disabled), "/buffer", "zstd, br, gzip").await, None);
+    }
+
+    #[test]
+    fn test_precompressed_content_is_recognised() {
+        assert!(is_incompressible("image/png"));
+        assert!(is_incompressible("application/gzip"));
+        assert!(is_incompressible("text/event-stream; charset=utf-8"));
+        assert!(!is_incompressible("image/svg+xml"));
+        assert!(!is_incompressible("application/x-ndjson"));
+        assert!(!is_incompressible("application/octet-stream"));
+        assert!(!is_incompressible(""));
+    }
+}
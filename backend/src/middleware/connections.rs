@@ -0,0 +1,176 @@
+/*
+ * Open HTTP connection counting for health output, the metrics gauge, and shutdown draining.
+ * I'm wrapping the make-service rather than adding a request layer, since a connection is accepted once but may carry many requests or none.
+ */
+
+use futures::future::BoxFuture;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::sync::watch;
+use tower::Service;
+
+use crate::utils::metrics::MetricsCollector;
+
+const ACTIVE_CONNECTIONS_GAUGE: &str = "http_active_connections";
+const ACCEPTED_CONNECTIONS_GAUGE: &str = "http_connections_accepted";
+
+#[derive(Debug, Clone)]
+pub struct ConnectionTracker {
+    inner: Arc<TrackerInner>,
+}
+
+#[derive(Debug)]
+struct TrackerInner {
+    active: watch::Sender<usize>,
+    accepted: AtomicU64,
+}
+
+impl Default for ConnectionTracker {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(TrackerInner {
+                active: watch::channel(0).0,
+                accepted: AtomicU64::new(0),
+            }),
+        }
+    }
+}
+
+impl ConnectionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Connections accepted and not yet closed
+    pub fn active(&self) -> usize {
+        *self.inner.active.borrow()
+    }
+
+    pub fn accepted_total(&self) -> u64 {
+        self.inner.accepted.load(Ordering::Relaxed)
+    }
+
+    /// Wrap a make-service so every connection it serves is counted until it closes
+    pub fn wrap<M>(&self, make_service: M) -> CountConnections<M> {
+        CountConnections {
+            inner: make_service,
+            tracker: self.clone(),
+        }
+    }
+
+    /// Keep the connection gauges in step with the counter; updates are pushed on change rather than polled
+    pub fn spawn_reporter(&self, metrics: MetricsCollector) -> tokio::task::JoinHandle<()> {
+        let tracker = self.clone();
+        tokio::spawn(async move {
+            let mut active = tracker.inner.active.subscribe();
+            loop {
+                let current = *active.borrow_and_update();
+                let _ = metrics.set_gauge(ACTIVE_CONNECTIONS_GAUGE, current as f64).await;
+                let _ = metrics.set_gauge(ACCEPTED_CONNECTIONS_GAUGE, tracker.accepted_total() as f64).await;
+                if active.changed().await.is_err() {
+                    break;
+                }
+            }
+        })
+    }
+
+    fn open(&self) -> ConnectionGuard {
+        self.inner.accepted.fetch_add(1, Ordering::Relaxed);
+        self.inner.active.send_modify(|count| *count += 1);
+        ConnectionGuard(self.inner.clone())
+    }
+}
+
+/// Counts one open connection until dropped
+#[derive(Debug)]
+struct ConnectionGuard(Arc<TrackerInner>);
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.0.active.send_modify(|count| *count -= 1);
+    }
+}
+
+/// Make-service wrapper returned by [`ConnectionTracker::wrap`]
+#[derive(Debug, Clone)]
+pub struct CountConnections<M> {
+    inner: M,
+    tracker: ConnectionTracker,
+}
+
+impl<M, T> Service<T> for CountConnections<M>
+where
+    M: Service<T>,
+    M::Future: Send + 'static,
+{
+    type Response = Counted<M::Response>;
+    type Error = M::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, target: T) -> Self::Future {
+        let guard = self.tracker.open();
+        let service = self.inner.call(target);
+        Box::pin(async move {
+            let service = service.await?;
+            Ok(Counted { inner: service, _guard: Arc::new(guard) })
+        })
+    }
+}
+
+/// A connection's service; the server clones it per request, so the guard drops with the connection's last clone
+#[derive(Debug, Clone)]
+pub struct Counted<S> {
+    inner: S,
+    _guard: Arc<ConnectionGuard>,
+}
+
+impl<S, R> Service<R> for Counted<S>
+where
+    S: Service<R>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        self.inner.call(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower::{service_fn, ServiceExt};
+
+    #[tokio::test]
+    async fn test_counts_connections_until_last_clone_drops() {
+        let tracker = ConnectionTracker::new();
+        let make_service = tracker.wrap(service_fn(|_: ()| async {
+            Ok::<_, std::convert::Infallible>(service_fn(|n: u32| async move { Ok::<_, std::convert::Infallible>(n * 2) }))
+        }));
+
+        let first = make_service.clone().oneshot(()).await.unwrap();
+        let second = make_service.oneshot(()).await.unwrap();
+        assert_eq!(tracker.active(), 2);
+        assert_eq!(tracker.accepted_total(), 2);
+
+        let per_request = first.clone();
+        assert_eq!(per_request.oneshot(21).await.unwrap(), 42);
+        assert_eq!(tracker.active(), 2);
+
+        drop(first);
+        assert_eq!(tracker.active(), 1);
+        drop(second);
+        assert_eq!(tracker.active(), 0);
+        assert_eq!(tracker.accepted_total(), 2);
+    }
+}
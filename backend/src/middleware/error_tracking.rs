@@ -0,0 +1,51 @@
+/*
+ * Error tracking middleware forwarding serious request failures to Sentry with the request's identity attached.
+ * I'm reading the route template rather than the raw path so events group by endpoint instead of by id.
+ */
+
+use axum::{
+    body::Body,
+    extract::{MatchedPath, State},
+    http::Request,
+    middleware::Next,
+    response::Response,
+};
+
+use crate::{
+    middleware::admin::request_principal,
+    utils::{
+        correlation,
+        error::ReportedError,
+        error_tracking::{self, RequestContext},
+    },
+    AppState,
+};
+
+/// Report responses carrying a High or Critical error
+pub async fn error_tracking_middleware(
+    State(app_state): State<AppState>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    if !error_tracking::is_enabled() {
+        return next.run(request).await;
+    }
+
+    let context = RequestContext {
+        request_id: correlation::current(),
+        method: request.method().to_string(),
+        route: request
+            .extensions()
+            .get::<MatchedPath>()
+            .map_or_else(|| request.uri().path().to_string(), |path| path.as_str().to_string()),
+        principal: request_principal(request.headers(), app_state.config.admin_api_token.as_deref()),
+    };
+
+    let response = next.run(request).await;
+    if let Some(error) = response.extensions().get::<ReportedError>() {
+        if error_tracking::should_report(&error.severity) {
+            error_tracking::capture(error, &context);
+        }
+    }
+    response
+}
@@ -0,0 +1,46 @@
+/*
+ * Feature flag extractor so handlers can branch on a flag evaluated for the caller.
+ * I'm resolving the principal the same way the audit and usage layers do, so a rollout bucket follows the caller's API key.
+ */
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::request::Parts,
+};
+use std::convert::Infallible;
+
+use crate::{
+    middleware::admin::request_principal,
+    services::feature_flag_service::FeatureFlagService,
+    AppState,
+};
+
+/// Feature flags evaluated for the calling principal
+#[derive(Debug, Clone)]
+pub struct Features {
+    service: FeatureFlagService,
+    principal: Option<String>,
+}
+
+impl Features {
+    pub fn enabled(&self, flag: &str) -> bool {
+        self.service.is_enabled(flag, self.principal.as_deref())
+    }
+
+    pub fn principal(&self) -> Option<&str> {
+        self.principal.as_deref()
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for Features {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        Ok(Features {
+            service: state.feature_flags.clone(),
+            principal: request_principal(&parts.headers, state.config.admin_api_token.as_deref()),
+        })
+    }
+}
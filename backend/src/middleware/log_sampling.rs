@@ -0,0 +1,34 @@
+/*
+ * Log sampling middleware thinning out request completion lines for paths listed in LOG_SAMPLE_PATHS.
+ * I'm marking the response rather than filtering in the subscriber so handler logs and failures on those paths still come through.
+ */
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::Request,
+    middleware::Next,
+    response::Response,
+};
+
+use crate::{
+    utils::logging::{self, SkipRequestLog},
+    AppState,
+};
+
+/// Drop the completion line for a sampled-out request on a noisy path
+pub async fn log_sampling_middleware(
+    State(app_state): State<AppState>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let rate = logging::sample_rate(&app_state.live_config.load().log_sample_paths, request.uri().path());
+
+    let mut response = next.run(request).await;
+    if let Some(rate) = rate {
+        if !response.status().is_server_error() && rand::random::<f64>() >= rate {
+            response.extensions_mut().insert(SkipRequestLog);
+        }
+    }
+    response
+}
@@ -0,0 +1,179 @@
+/*
+ * Maintenance mode switch and middleware answering non-health traffic with a structured 503 during deploy windows.
+ * I'm checking an atomic flag on the hot path so the switch costs nothing while maintenance is off.
+ */
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use tracing::info;
+
+use crate::{
+    utils::error::{ErrorCategory, ErrorDetails, ErrorResponse, ErrorSeverity},
+    AppState,
+};
+
+pub const DEFAULT_MAINTENANCE_MESSAGE: &str = "The service is undergoing scheduled maintenance.";
+
+/// Current maintenance window details
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceStatus {
+    pub enabled: bool,
+    pub message: String,
+    pub retry_after_seconds: u64,
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Body accepted by the admin toggle; omitted fields keep their current values
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceUpdate {
+    pub enabled: bool,
+    pub message: Option<String>,
+    pub retry_after_seconds: Option<u64>,
+}
+
+/// Shared, runtime-togglable maintenance switch
+#[derive(Debug, Clone)]
+pub struct MaintenanceMode {
+    enabled: Arc<AtomicBool>,
+    status: Arc<RwLock<MaintenanceStatus>>,
+}
+
+impl MaintenanceMode {
+    pub fn new(enabled: bool, message: Option<String>, retry_after_seconds: u64) -> Self {
+        let status = MaintenanceStatus {
+            enabled,
+            message: message.unwrap_or_else(|| DEFAULT_MAINTENANCE_MESSAGE.to_string()),
+            retry_after_seconds,
+            since: enabled.then(chrono::Utc::now),
+        };
+
+        Self {
+            enabled: Arc::new(AtomicBool::new(enabled)),
+            status: Arc::new(RwLock::new(status)),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Acquire)
+    }
+
+    pub fn status(&self) -> MaintenanceStatus {
+        self.status.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Apply an update, stamping the start time when maintenance begins
+    pub fn apply(&self, update: MaintenanceUpdate) -> MaintenanceStatus {
+        let mut status = self.status.write().unwrap_or_else(|e| e.into_inner());
+
+        if update.enabled && !status.enabled {
+            status.since = Some(chrono::Utc::now());
+        } else if !update.enabled {
+            status.since = None;
+        }
+        status.enabled = update.enabled;
+        if let Some(message) = update.message.filter(|m| !m.trim().is_empty()) {
+            status.message = message;
+        }
+        if let Some(retry_after) = update.retry_after_seconds {
+            status.retry_after_seconds = retry_after;
+        }
+
+        self.enabled.store(status.enabled, Ordering::Release);
+        info!("Maintenance mode {}", if status.enabled { "enabled" } else { "disabled" });
+        status.clone()
+    }
+}
+
+/// Short-circuit requests with 503 while maintenance mode is on
+/// I'm letting health probes, the version endpoint, and the maintenance toggle itself through so orchestration and recovery keep working
+pub async fn maintenance_middleware(
+    State(app_state): State<AppState>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    if !app_state.maintenance.is_enabled() || is_exempt(request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    maintenance_response(&app_state.maintenance.status())
+}
+
+/// Routes that keep answering during maintenance: the probes, version, and the toggle itself, under /v1 or not
+const EXEMPT_PATHS: [&str; 5] = ["/health", "/health/ready", "/health/live", "/version", "/api/admin/maintenance"];
+
+fn is_exempt(path: &str) -> bool {
+    let path = path.trim_end_matches('/');
+    EXEMPT_PATHS.contains(&path.strip_prefix("/v1").unwrap_or(path))
+}
+
+fn maintenance_response(status: &MaintenanceStatus) -> Response {
+    let body = ErrorResponse {
+        error: ErrorDetails {
+            code: "MAINTENANCE".to_string(),
+            message: status.message.clone(),
+            category: ErrorCategory::Service,
+            severity: ErrorSeverity::Medium,
+            retryable: true,
+            context: Some(serde_json::json!({
+                "maintenance": true,
+                "since": status.since,
+                "retry_after_seconds": status.retry_after_seconds,
+            })),
+        },
+        timestamp: chrono::Utc::now(),
+        request_id: None,
+        support_message: "Please retry after the maintenance window ends.".to_string(),
+    };
+
+    let retry_after = HeaderValue::from_str(&status.retry_after_seconds.to_string())
+        .unwrap_or_else(|_| HeaderValue::from_static("300"));
+
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        [(header::RETRY_AFTER, retry_after)],
+        Json(body),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_health_and_toggle_paths_are_exempt() {
+        assert!(is_exempt("/v1/health"));
+        assert!(is_exempt("/v1/health/ready"));
+        assert!(is_exempt("/api/admin/maintenance/"));
+        assert!(!is_exempt("/api/fractals/mandelbrot"));
+        assert!(!is_exempt("/api/healthcheck-ish"));
+        assert!(!is_exempt("/api/github/repo/octocat/health"));
+        assert!(!is_exempt("/v1/health/anything"));
+        assert!(!is_exempt("/api/fractals/version"));
+    }
+
+    #[test]
+    fn test_apply_tracks_window_start() {
+        let mode = MaintenanceMode::new(false, None, 120);
+        assert!(!mode.is_enabled());
+
+        let status = mode.apply(MaintenanceUpdate { enabled: true, message: Some("Upgrading".into()), retry_after_seconds: None });
+        assert!(mode.is_enabled());
+        assert_eq!(status.message, "Upgrading");
+        assert_eq!(status.retry_after_seconds, 120);
+        assert!(status.since.is_some());
+
+        let status = mode.apply(MaintenanceUpdate { enabled: false, message: None, retry_after_seconds: None });
+        assert!(!mode.is_enabled());
+        assert!(status.since.is_none());
+    }
+}
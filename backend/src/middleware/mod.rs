@@ -0,0 +1,40 @@
+/*
+ * Middleware module aggregator for cross-cutting HTTP concerns applied around the route handlers.
+ * I'm collecting request auditing, response compression, connection counting, error tracking, log sampling, request ids, usage tracking, maintenance mode, rate limiting, request recording, signed share links, admin authentication, user API keys, feature gating, visitor sessions, tenant resolution, and client address resolution here so routes stay focused on their own logic.
+ */
+
+pub mod admin;
+pub mod audit;
+pub mod client_ip;
+pub mod compression;
+pub mod connections;
+pub mod error_tracking;
+pub mod features;
+pub mod log_sampling;
+pub mod maintenance;
+pub mod rate_limit;
+pub mod recording;
+pub mod request_id;
+pub mod session;
+pub mod signed_urls;
+pub mod tenant;
+pub mod usage;
+pub mod users;
+
+pub use admin::{AdminAuth, request_principal};
+pub use audit::audit_middleware;
+pub use client_ip::ClientIp;
+pub use compression::compression_layer;
+pub use connections::ConnectionTracker;
+pub use error_tracking::error_tracking_middleware;
+pub use features::Features;
+pub use log_sampling::log_sampling_middleware;
+pub use maintenance::{maintenance_middleware, MaintenanceMode};
+pub use rate_limit::{rate_limit_middleware, RenderClient};
+pub use recording::recording_middleware;
+pub use request_id::request_id_middleware;
+pub use session::{session_middleware, Session};
+pub use signed_urls::{signed_url_middleware, UrlSigner};
+pub use tenant::{tenant_middleware, CurrentTenant};
+pub use usage::usage_middleware;
+pub use users::UserAuth;
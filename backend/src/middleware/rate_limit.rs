@@ -0,0 +1,171 @@
+/*
+ * Per-client request limits for the API, counted per minute through the shared rate limiter.
+ * I'm keying on the API key once it authenticates and the resolved client address otherwise, so clients behind one NAT with their own keys don't starve each other.
+ * Each tenant counts separately and can override the per-minute limits through its settings.
+ * Render handlers charge their cost budgets to the same client through the RenderClient extractor.
+ */
+
+use axum::{
+    async_trait,
+    body::Body,
+    extract::{ConnectInfo, FromRequestParts, State},
+    http::{request::Parts, HeaderMap, HeaderName, HeaderValue, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use crate::{
+    middleware::{client_ip::{client_network, resolve_client_ip}, tenant::CurrentTenant, users::authenticated_principal},
+    utils::{config::Config, error::AppError, rate_limit::RateLimitDecision},
+    AppState,
+};
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Who a render is counted against, and the tenant whose budgets it draws from
+#[derive(Debug, Clone)]
+pub struct RenderClient {
+    /// The principal the rate limiter counts the client under; None for operators and clients whose address can't be resolved
+    pub principal: Option<String>,
+    pub tenant: CurrentTenant,
+}
+
+impl RenderClient {
+    /// The client's key in one of its tenant's budgets, laid out like the rate limiter's own
+    pub fn budget_key(&self, bucket: &str) -> Option<String> {
+        self.principal.as_ref().map(|principal| self.tenant.scope_key(&format!("{}:{}", bucket, principal)))
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for RenderClient {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let Ok(tenant) = CurrentTenant::from_request_parts(parts, state).await;
+        Ok(Self { principal: client_key(parts, state).await, tenant })
+    }
+}
+
+/// Count the request against its client's budget, answering 429 with Retry-After once it is spent
+pub async fn rate_limit_middleware(
+    State(app_state): State<AppState>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let tenant = request.extensions().get::<CurrentTenant>().cloned();
+    let limits = match &tenant {
+        Some(tenant) => tenant.config(&app_state.live_config),
+        None => app_state.live_config.load(),
+    };
+    let Some((bucket, limit)) = bucket_for_path(request.uri().path(), &limits).filter(|_| limits.rate_limit_enabled) else {
+        return next.run(request).await;
+    };
+
+    let (mut parts, body) = request.into_parts();
+    let client = client_key(&mut parts, &app_state).await;
+    let request = Request::from_parts(parts, body);
+    let Some(client) = client else {
+        return next.run(request).await;
+    };
+
+    let key = format!("{}:{}", bucket, client);
+    let key = match &tenant {
+        Some(tenant) => tenant.scope_key(&key),
+        None => key,
+    };
+    let decision = app_state.rate_limiter.acquire(&key, limit, WINDOW).await;
+    let mut response = if decision.allowed {
+        next.run(request).await
+    } else {
+        let mut response = AppError::RateLimitError(format!(
+            "{} requests per minute allowed for {}",
+            limit, bucket
+        ))
+        .into_response();
+        response.headers_mut().insert(axum::http::header::RETRY_AFTER, HeaderValue::from(retry_after_secs(&decision)));
+        response
+    };
+
+    insert_limit_headers(response.headers_mut(), &decision);
+    response
+}
+
+/// The authenticated key's principal, or the client's address; None for operators, who shouldn't lock themselves out, and unresolvable clients
+async fn client_key(parts: &mut Parts, app_state: &AppState) -> Option<String> {
+    match authenticated_principal(parts, app_state).await {
+        Some(principal) if principal == "admin" => None,
+        Some(principal) => Some(principal),
+        None => {
+            let peer = parts.extensions.get::<ConnectInfo<SocketAddr>>().map(|ConnectInfo(addr)| addr.ip());
+            resolve_client_ip(&parts.headers, peer, &app_state.config.trusted_proxies).map(|ip| format!("ip:{}", client_network(ip)))
+        }
+    }
+}
+
+/// Which budget a path draws from; None for paths that are never limited
+fn bucket_for_path(path: &str, limits: &Config) -> Option<(&'static str, u32)> {
+    let path = path.strip_prefix("/v1").unwrap_or(path);
+
+    // Thumbnails and saved views are cheap or stored, and a gallery asks for a page of them at once; re-rendering a save is a full render
+    let gallery = (path.starts_with("/api/fractals/saved") && !path.ends_with("/render")) || path.starts_with("/shared/fractals/");
+    if path == "/api/fractals/thumbnail" || gallery {
+        Some(("api", limits.rate_limit_requests_per_minute))
+    } else if path.starts_with("/api/fractals/") {
+        Some(("fractals", limits.fractal_rate_limit_per_minute))
+    } else if path.starts_with("/api/") {
+        Some(("api", limits.rate_limit_requests_per_minute))
+    } else {
+        None
+    }
+}
+
+fn retry_after_secs(decision: &RateLimitDecision) -> u64 {
+    decision.reset_after.as_secs_f64().ceil().max(1.0) as u64
+}
+
+fn insert_limit_headers(headers: &mut HeaderMap, decision: &RateLimitDecision) {
+    headers.insert(HeaderName::from_static("x-ratelimit-limit"), HeaderValue::from(decision.limit));
+    headers.insert(HeaderName::from_static("x-ratelimit-remaining"), HeaderValue::from(decision.remaining));
+    headers.insert(HeaderName::from_static("x-ratelimit-reset"), HeaderValue::from(retry_after_secs(decision)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::config::ConfigBuilder;
+
+    #[test]
+    fn test_paths_map_to_budgets() {
+        let config = ConfigBuilder::new()
+            .database_url("postgresql://localhost/test")
+            .github_token("token")
+            .build()
+            .unwrap();
+
+        assert_eq!(bucket_for_path("/api/fractals/mandelbrot", &config), Some(("fractals", 10)));
+        assert_eq!(bucket_for_path("/v1/api/fractals/julia", &config), Some(("fractals", 10)));
+        assert_eq!(bucket_for_path("/api/fractals/thumbnail", &config), Some(("api", 100)));
+        assert_eq!(bucket_for_path("/api/fractals/saved/aB3dE5gH9k/image", &config), Some(("api", 100)));
+        assert_eq!(bucket_for_path("/api/fractals/saved/aB3dE5gH9k/render", &config), Some(("fractals", 10)));
+        assert_eq!(bucket_for_path("/shared/fractals/aB3dE5gH9k.png", &config), Some(("api", 100)));
+        assert_eq!(bucket_for_path("/api/github/repos", &config), Some(("api", 100)));
+        assert_eq!(bucket_for_path("/health", &config), None);
+        assert_eq!(bucket_for_path("/images/abc", &config), None);
+    }
+
+    #[test]
+    fn test_reset_rounds_up_to_whole_seconds() {
+        let decision = RateLimitDecision {
+            allowed: false,
+            limit: 10,
+            remaining: 0,
+            reset_after: Duration::from_millis(1200),
+        };
+        assert_eq!(retry_after_secs(&decision), 2);
+        assert_eq!(retry_after_secs(&RateLimitDecision { reset_after: Duration::ZERO, ..decision }), 1);
+    }
+}
@@ -0,0 +1,223 @@
+/*
+ * Request recording middleware capturing sanitized request/response pairs for replay from the admin API.
+ * I'm only buffering bodies whose size is known and under the limit, so streamed exports and event streams pass through untouched.
+ */
+
+use axum::{
+    body::{Body, HttpBody},
+    extract::State,
+    http::{header, HeaderMap, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::time::Instant;
+
+use crate::{
+    middleware::CurrentTenant,
+    models::recordings::{RequestRecording, REDACTED},
+    utils::error::AppError,
+    AppState,
+};
+
+/// Headers that carry credentials or identify a visitor
+const SENSITIVE_HEADERS: [&str; 6] = ["authorization", "proxy-authorization", "cookie", "set-cookie", "x-api-key", "x-session-id"];
+
+/// Query parameters and JSON keys containing any of these are redacted
+const SENSITIVE_KEYS: [&str; 6] = ["password", "token", "secret", "api_key", "apikey", "signature"];
+
+/// Keys redacted only on an exact match, such as the plaintext `key` /api/users returns when one is issued or rotated, which as a substring would hide `keys` lists and `key_id`s
+const SENSITIVE_EXACT_KEYS: [&str; 1] = ["key"];
+
+/// Record the exchange when its path is one REQUEST_RECORDING_PATHS covers
+pub async fn recording_middleware(
+    State(app_state): State<AppState>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let settings = app_state.recording_service.settings();
+    if !settings.records(request.uri().path()) {
+        return next.run(request).await;
+    }
+    let max_body_bytes = settings.max_body_bytes;
+
+    let start_time = Instant::now();
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let query = request.uri().query().map(sanitize_query);
+    let tenant = request
+        .extensions()
+        .get::<CurrentTenant>()
+        .map(|tenant| tenant.slug.clone())
+        .unwrap_or_else(|| app_state.tenants.default_tenant().slug.clone());
+    let request_headers = sanitize_headers(request.headers());
+
+    let (parts, body) = request.into_parts();
+    let (body, request_body, request_body_bytes) = match capture(body, &parts.headers, max_body_bytes).await {
+        Ok(captured) => captured,
+        Err(e) => return e.into_response(),
+    };
+
+    let response = next.run(Request::from_parts(parts, body)).await;
+    let duration_ms = start_time.elapsed().as_millis() as i64;
+
+    let (parts, body) = response.into_parts();
+    let (body, response_body, response_body_bytes) = match capture(body, &parts.headers, max_body_bytes).await {
+        Ok(captured) => captured,
+        Err(e) => return e.into_response(),
+    };
+
+    app_state.recording_service.record(RequestRecording {
+        id: uuid::Uuid::new_v4(),
+        method,
+        path,
+        query,
+        tenant,
+        request_headers,
+        request_body,
+        request_body_bytes,
+        status: parts.status.as_u16() as i32,
+        response_headers: sanitize_headers(&parts.headers),
+        response_body,
+        response_body_bytes,
+        duration_ms,
+        recorded_at: chrono::Utc::now(),
+    });
+
+    Response::from_parts(parts, body)
+}
+
+/// Buffer a body of known size up to the limit, returning it for sending on alongside its sanitized text and size
+pub(crate) async fn capture(body: Body, headers: &HeaderMap, max_bytes: usize) -> Result<(Body, Option<String>, Option<i64>), AppError> {
+    let size = body.size_hint().exact();
+    let Some(size) = size.filter(|size| *size as usize <= max_bytes && is_textual(headers)) else {
+        return Ok((body, None, size.map(|size| size as i64)));
+    };
+
+    let bytes = axum::body::to_bytes(body, max_bytes)
+        .await
+        .map_err(|e| AppError::bad_request(format!("Failed to read body: {}", e)))?;
+    let text = std::str::from_utf8(&bytes).ok().map(sanitize_body);
+    Ok((Body::from(bytes), text, Some(size as i64)))
+}
+
+/// Bodies without a content type, such as empty GETs, count as text
+fn is_textual(headers: &HeaderMap) -> bool {
+    let Some(content_type) = headers.get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok()) else {
+        return true;
+    };
+    let essence = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    essence.starts_with("text/")
+        || essence.ends_with("json")
+        || essence.ends_with("xml")
+        || essence == "application/x-www-form-urlencoded"
+}
+
+fn is_sensitive_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    SENSITIVE_EXACT_KEYS.contains(&key.as_str()) || SENSITIVE_KEYS.iter().any(|sensitive| key.contains(sensitive))
+}
+
+/// Headers as a JSON object, with credentials replaced
+pub fn sanitize_headers(headers: &HeaderMap) -> serde_json::Value {
+    let mut sanitized = serde_json::Map::new();
+    for (name, value) in headers {
+        let value = if SENSITIVE_HEADERS.contains(&name.as_str()) {
+            REDACTED.to_string()
+        } else {
+            String::from_utf8_lossy(value.as_bytes()).into_owned()
+        };
+        sanitized.insert(name.as_str().to_string(), serde_json::Value::String(value));
+    }
+    serde_json::Value::Object(sanitized)
+}
+
+fn sanitize_query(query: &str) -> String {
+    query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((key, _)) if is_sensitive_key(key) => format!("{}={}", key, REDACTED),
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// JSON bodies have credential fields replaced at any depth; other text is kept as sent
+pub fn sanitize_body(body: &str) -> String {
+    match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(mut value) if value.is_object() || value.is_array() => {
+            redact_json(&mut value);
+            value.to_string()
+        }
+        _ => body.to_string(),
+    }
+}
+
+fn redact_json(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_sensitive_key(key) && !value.is_null() {
+                    *value = serde_json::Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(value);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_credentials_are_redacted() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer dpk_secret"));
+        headers.insert("x-api-key", HeaderValue::from_static("dpk_secret"));
+        headers.insert(header::ACCEPT, HeaderValue::from_static("application/json"));
+        let sanitized = sanitize_headers(&headers);
+        assert_eq!(sanitized["authorization"], REDACTED);
+        assert_eq!(sanitized["x-api-key"], REDACTED);
+        assert_eq!(sanitized["accept"], "application/json");
+
+        assert_eq!(sanitize_query("width=800&token=abc&page=2"), "width=800&token=[redacted]&page=2");
+
+        let body = sanitize_body(r#"{"username":"octocat","password":"hunter2","keys":[{"api_key":"dpk_1"}],"refresh_token":null}"#);
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["username"], "octocat");
+        assert_eq!(body["password"], REDACTED);
+        assert_eq!(body["keys"][0]["api_key"], REDACTED);
+        assert!(body["refresh_token"].is_null());
+        assert_eq!(sanitize_body("plain text"), "plain text");
+
+        let issued = sanitize_body(r#"{"data":{"name":"ci","prefix":"dpk_pl","key":"dpk_plaintext"},"keys":[{"key_id":"k1"}]}"#);
+        let issued: serde_json::Value = serde_json::from_str(&issued).unwrap();
+        assert_eq!(issued["data"]["key"], REDACTED);
+        assert_eq!(issued["data"]["prefix"], "dpk_pl");
+        assert_eq!(issued["keys"][0]["key_id"], "k1");
+    }
+
+    #[tokio::test]
+    async fn test_only_small_text_bodies_are_captured() {
+        let mut json = HeaderMap::new();
+        json.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        let (body, text, size) = capture(Body::from(r#"{"zoom":2}"#), &json, 1024).await.unwrap();
+        assert_eq!(text.as_deref(), Some(r#"{"zoom":2}"#));
+        assert_eq!(size, Some(10));
+        assert_eq!(axum::body::to_bytes(body, 1024).await.unwrap(), r#"{"zoom":2}"#);
+
+        let (_, text, size) = capture(Body::from("x".repeat(2048)), &json, 1024).await.unwrap();
+        assert_eq!((text, size), (None, Some(2048)));
+
+        let mut png = HeaderMap::new();
+        png.insert(header::CONTENT_TYPE, HeaderValue::from_static("image/png"));
+        let (body, text, size) = capture(Body::from(vec![0u8; 16]), &png, 1024).await.unwrap();
+        assert_eq!((text, size), (None, Some(16)));
+        assert_eq!(axum::body::to_bytes(body, 1024).await.unwrap().len(), 16);
+    }
+}
@@ -0,0 +1,26 @@
+/*
+ * Request id middleware assigning every request a correlation id before anything else sees it.
+ * I'm writing the id back onto the request headers so the trace span, audit trail, and error tracking all read the same value.
+ */
+
+use axum::{
+    body::Body,
+    http::{HeaderName, HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::utils::correlation::{self, REQUEST_ID_HEADER};
+
+/// Accept or generate the request's id, scope it over the handler, and echo it on the response
+pub async fn request_id_middleware(mut request: Request<Body>, next: Next) -> Response {
+    let id = correlation::from_headers(request.headers());
+    // from_headers only returns printable ASCII, which is always a valid header value
+    let value = HeaderValue::from_str(&id).expect("correlation ids are valid header values");
+    let header = HeaderName::from_static(REQUEST_ID_HEADER);
+
+    request.headers_mut().insert(header.clone(), value.clone());
+    let mut response = correlation::scope(id, next.run(request)).await;
+    response.headers_mut().insert(header, value);
+    response
+}
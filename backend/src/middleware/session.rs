@@ -0,0 +1,125 @@
+/*
+ * Anonymous visitor sessions, carried in a cookie for browsers or an X-Session-Id header for API clients.
+ * I'm only issuing the id here; nothing is written to the database until the session renders something worth remembering.
+ */
+
+use axum::{
+    async_trait,
+    body::Body,
+    extract::{FromRequestParts, State},
+    http::{header, request::Parts, HeaderMap, HeaderName, HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
+use uuid::Uuid;
+
+use crate::{utils::error::AppError, AppState};
+
+pub const SESSION_COOKIE: &str = "dps_session";
+pub const SESSION_HEADER: &str = "x-session-id";
+
+/// The visitor session a request belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Session {
+    pub id: Uuid,
+    /// Issued for this request rather than presented by the client, so it has no history yet
+    pub is_new: bool,
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Session {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Session>()
+            .copied()
+            .ok_or_else(|| AppError::internal("Session middleware is not installed"))
+    }
+}
+
+/// Resolve or issue the request's session and echo its id; the cookie is only set when the id is new
+pub async fn session_middleware(State(app_state): State<AppState>, mut request: Request<Body>, next: Next) -> Response {
+    let presented = session_from_headers(request.headers());
+    let session = Session {
+        id: presented.unwrap_or_else(Uuid::new_v4),
+        is_new: presented.is_none(),
+    };
+    request.extensions_mut().insert(session);
+
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    // A hyphenated UUID is always a valid header value
+    let id = HeaderValue::from_str(&session.id.to_string()).expect("uuids are valid header values");
+    headers.insert(HeaderName::from_static(SESSION_HEADER), id);
+
+    if session.is_new {
+        let cookie = session_cookie(session.id, app_state.config.session_ttl_days, app_state.config.is_production());
+        if let Ok(cookie) = HeaderValue::from_str(&cookie) {
+            headers.append(header::SET_COOKIE, cookie);
+        }
+    }
+    response
+}
+
+/// The session id a client presented; the header wins so an API client isn't overridden by a stale browser cookie
+pub fn session_from_headers(headers: &HeaderMap) -> Option<Uuid> {
+    let from_header = headers
+        .get(SESSION_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| Uuid::parse_str(value.trim()).ok());
+
+    from_header.or_else(|| {
+        headers
+            .get_all(header::COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(';'))
+            .filter_map(|pair| pair.trim().split_once('='))
+            .find(|(name, _)| *name == SESSION_COOKIE)
+            .and_then(|(_, value)| Uuid::parse_str(value.trim()).ok())
+    })
+}
+
+fn session_cookie(id: Uuid, ttl_days: u32, secure: bool) -> String {
+    format!(
+        "{}={}; Path=/; Max-Age={}; HttpOnly; SameSite=Lax{}",
+        SESSION_COOKIE,
+        id,
+        u64::from(ttl_days) * 86_400,
+        if secure { "; Secure" } else { "" },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_wins_over_cookie_and_garbage_is_ignored() {
+        let from_cookie = Uuid::new_v4();
+        let from_header = Uuid::new_v4();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::COOKIE, format!("theme=dark; {}={}", SESSION_COOKIE, from_cookie).parse().unwrap());
+        assert_eq!(session_from_headers(&headers), Some(from_cookie));
+
+        headers.insert(SESSION_HEADER, from_header.to_string().parse().unwrap());
+        assert_eq!(session_from_headers(&headers), Some(from_header));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::COOKIE, format!("{}=not-a-uuid", SESSION_COOKIE).parse().unwrap());
+        assert_eq!(session_from_headers(&headers), None);
+    }
+
+    #[test]
+    fn test_cookie_is_secure_only_when_asked() {
+        let id = Uuid::nil();
+        assert_eq!(
+            session_cookie(id, 30, false),
+            format!("dps_session={}; Path=/; Max-Age=2592000; HttpOnly; SameSite=Lax", id)
+        );
+        assert!(session_cookie(id, 30, true).ends_with("; Secure"));
+    }
+}
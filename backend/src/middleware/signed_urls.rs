@@ -0,0 +1,174 @@
+/*
+ * Signed share links: HMAC-SHA256 over a path and its expiry, so a saved fractal's image can be fetched straight from a link without the JSON API.
+ * I'm signing the path along with the expiry so a signature lifted from one link can't be replayed against another image or kept alive past its deadline.
+ */
+
+use axum::{
+    body::Body,
+    extract::{Query, State},
+    http::Request,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+
+use crate::{
+    middleware::tenant::CurrentTenant,
+    utils::{config::Config, error::AppError},
+    AppState,
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Paths that answer only to a signed link
+pub const SHARED_PREFIX: &str = "/shared/";
+
+/// Signs and checks share links under IMAGE_SIGNING_KEY
+#[derive(Clone)]
+pub struct UrlSigner {
+    key: Vec<u8>,
+}
+
+impl std::fmt::Debug for UrlSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UrlSigner").finish_non_exhaustive()
+    }
+}
+
+/// The query a signed link carries
+#[derive(Debug, Deserialize)]
+pub struct SignedQuery {
+    pub expires: Option<i64>,
+    pub signature: Option<String>,
+}
+
+/// Cache-Control for a response behind a signed link, private and never outliving the link itself
+pub fn signed_cache_control(expires: Option<i64>, max_age: i64, now: DateTime<Utc>) -> String {
+    let remaining = expires.map_or(0, |expires| expires.saturating_sub(now.timestamp()));
+    format!("private, max-age={}", remaining.clamp(0, max_age))
+}
+
+impl UrlSigner {
+    pub fn new(key: &[u8]) -> Self {
+        Self { key: key.to_vec() }
+    }
+
+    /// The signer for IMAGE_SIGNING_KEY, or None when signed links are off
+    pub fn from_config(config: &Config) -> Option<Self> {
+        config.image_signing_key.as_deref().map(|key| Self::new(key.as_bytes()))
+    }
+
+    fn mac(&self, path: &str, expires: i64) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        mac.update(path.as_bytes());
+        mac.update(b"\n");
+        mac.update(expires.to_string().as_bytes());
+        mac
+    }
+
+    /// Hex signature of `path` valid until the unix time `expires`
+    pub fn sign(&self, path: &str, expires: i64) -> String {
+        self.mac(path, expires)
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    /// `path` with the expiry and signature appended, valid for `ttl_secs` from `now`
+    pub fn signed_url(&self, path: &str, ttl_secs: u64, now: DateTime<Utc>) -> (String, DateTime<Utc>) {
+        let ttl = chrono::Duration::seconds(i64::try_from(ttl_secs).unwrap_or(i64::MAX).min(i64::MAX / 1000));
+        let expires_at = now.checked_add_signed(ttl).unwrap_or(DateTime::<Utc>::MAX_UTC);
+        let expires = expires_at.timestamp();
+        (format!("{}?expires={}&signature={}", path, expires, self.sign(path, expires)), expires_at)
+    }
+
+    /// Whether `signature` was made for `path` and `expires` with this key and hasn't run out at `now`
+    pub fn verify(&self, path: &str, expires: i64, signature: &str, now: DateTime<Utc>) -> bool {
+        if expires < now.timestamp() {
+            return false;
+        }
+        match decode_hex(signature) {
+            Some(signature) => self.mac(path, expires).verify_slice(&signature).is_ok(),
+            None => false,
+        }
+    }
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) || !value.is_ascii() {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&value[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Let requests under /shared/ through only with a valid, unexpired signature for their path and tenant
+pub async fn signed_url_middleware(
+    State(app_state): State<AppState>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let path = request.uri().path();
+    if !path.starts_with(SHARED_PREFIX) {
+        return next.run(request).await;
+    }
+
+    let Some(signer) = UrlSigner::from_config(&app_state.config) else {
+        return AppError::not_found(path.to_string()).into_response();
+    };
+    let query = Query::<SignedQuery>::try_from_uri(request.uri()).map(|Query(query)| query);
+    let Ok(SignedQuery { expires: Some(expires), signature: Some(signature) }) = query else {
+        return AppError::AuthorizationError("Share link is missing its expiry or signature".to_string()).into_response();
+    };
+    if expires < Utc::now().timestamp() {
+        return AppError::AuthorizationError("Share link has expired".to_string()).into_response();
+    }
+    // Links are signed with the tenant prefix the router never sees, so one tenant's link can't be replayed against another
+    let prefix = request.extensions().get::<CurrentTenant>().map(|tenant| tenant.path_prefix()).unwrap_or_default();
+    if !signer.verify(&format!("{}{}", prefix, path), expires, &signature, Utc::now()) {
+        return AppError::AuthorizationError("Share link signature is invalid".to_string()).into_response();
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signed_links_verify_until_they_expire() {
+        let signer = UrlSigner::new(b"0123456789abcdef0123456789abcdef");
+        let now = Utc::now();
+        let path = "/shared/fractals/Ab3dE5gH9k.png";
+        let (url, expires_at) = signer.signed_url(path, 60, now);
+        let expires = expires_at.timestamp();
+        let signature = signer.sign(path, expires);
+
+        assert_eq!(url, format!("{}?expires={}&signature={}", path, expires, signature));
+        assert!(signer.verify(path, expires, &signature, now));
+        assert!(!signer.verify(path, expires, &signature, now + chrono::Duration::seconds(61)));
+        assert!(!signer.verify("/shared/fractals/Zz3dE5gH9k.png", expires, &signature, now));
+        assert!(!signer.verify(path, expires + 3600, &signature, now));
+        assert!(!signer.verify(path, expires, "not-hex", now));
+        assert!(!UrlSigner::new(b"another key entirely, 32+ bytes!").verify(path, expires, &signature, now));
+    }
+
+    #[test]
+    fn test_signed_cache_control_stops_at_the_link_expiry() {
+        let now = Utc::now();
+        let in_a_minute = now.timestamp() + 60;
+
+        assert_eq!(signed_cache_control(Some(in_a_minute), 86400, now), "private, max-age=60");
+        assert_eq!(signed_cache_control(Some(in_a_minute), 30, now), "private, max-age=30");
+        assert_eq!(signed_cache_control(Some(now.timestamp() - 5), 86400, now), "private, max-age=0");
+        assert_eq!(signed_cache_control(None, 86400, now), "private, max-age=0");
+    }
+}
@@ -0,0 +1,130 @@
+/*
+ * Tenant resolution: every request is assigned the showcase it belongs to, from a /t/<slug> path prefix or its Host header.
+ * I'm stripping the prefix before routing so each route serves tenants unchanged, which is why this layer wraps the router instead of sitting inside it.
+ */
+
+use axum::{
+    async_trait,
+    body::Body,
+    extract::{FromRequestParts, State},
+    http::{header, request::Parts, uri::PathAndQuery, Request, Uri},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::convert::Infallible;
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::{
+    models::tenants::{normalize_host, TENANT_PATH_PREFIX},
+    services::tenant_service::{TenantContext, TenantService},
+    utils::error::{AppError, Result},
+    AppState,
+};
+
+/// The tenant serving the request
+#[derive(Debug, Clone)]
+pub struct CurrentTenant(pub Arc<TenantContext>);
+
+impl Deref for CurrentTenant {
+    type Target = TenantContext;
+
+    fn deref(&self) -> &TenantContext {
+        &self.0
+    }
+}
+
+/// Falls back to the default tenant where the middleware isn't installed, as in tests and the admin CLI
+#[async_trait]
+impl FromRequestParts<AppState> for CurrentTenant {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> std::result::Result<Self, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<CurrentTenant>()
+            .cloned()
+            .unwrap_or_else(|| CurrentTenant(state.tenants.default_tenant())))
+    }
+}
+
+/// Resolve the request's tenant, answering 404 for unknown or disabled ones
+pub async fn tenant_middleware(State(app_state): State<AppState>, mut request: Request<Body>, next: Next) -> Response {
+    match resolve(&app_state.tenants, &mut request) {
+        Ok(tenant) => {
+            request.extensions_mut().insert(CurrentTenant(tenant));
+            next.run(request).await
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// A path prefix wins over the Host header; a host no tenant claims belongs to the default tenant
+fn resolve(tenants: &TenantService, request: &mut Request<Body>) -> Result<Arc<TenantContext>> {
+    if let Some((slug, rest)) = split_tenant_prefix(request.uri().path()) {
+        let tenant = tenants
+            .by_slug(slug)
+            .filter(|tenant| tenant.enabled)
+            .ok_or_else(|| AppError::NotFoundError(format!("Tenant {} not found", slug)))?;
+        *request.uri_mut() = with_path(request.uri(), &rest)?;
+        return Ok(tenant);
+    }
+
+    let host = request
+        .headers()
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .or_else(|| request.uri().host());
+    match host.and_then(normalize_host).and_then(|host| tenants.by_host(&host)) {
+        Some(tenant) if tenant.enabled => Ok(tenant),
+        Some(tenant) => Err(AppError::NotFoundError(format!("Tenant {} not found", tenant.slug))),
+        None => Ok(tenants.default_tenant()),
+    }
+}
+
+/// Split `/t/<slug>/rest` into the slug and `/rest`
+fn split_tenant_prefix(path: &str) -> Option<(&str, String)> {
+    let remainder = path.strip_prefix(TENANT_PATH_PREFIX)?;
+    Some(match remainder.split_once('/') {
+        Some((slug, rest)) => (slug, format!("/{}", rest)),
+        None => (remainder, "/".to_string()),
+    })
+}
+
+fn with_path(uri: &Uri, path: &str) -> Result<Uri> {
+    let path_and_query = match uri.query() {
+        Some(query) => format!("{}?{}", path, query),
+        None => path.to_string(),
+    };
+
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = Some(
+        PathAndQuery::try_from(path_and_query).map_err(|e| AppError::BadRequestError(format!("Invalid path: {}", e)))?,
+    );
+    Uri::from_parts(parts).map_err(|e| AppError::BadRequestError(format!("Invalid path: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_is_split_from_the_route() {
+        assert_eq!(split_tenant_prefix("/t/octo/api/github/repos"), Some(("octo", "/api/github/repos".to_string())));
+        assert_eq!(split_tenant_prefix("/t/octo"), Some(("octo", "/".to_string())));
+        assert_eq!(split_tenant_prefix("/t/octo/"), Some(("octo", "/".to_string())));
+        assert_eq!(split_tenant_prefix("/api/github/repos"), None);
+        assert_eq!(split_tenant_prefix("/tenants"), None);
+    }
+
+    #[test]
+    fn test_rewrite_keeps_the_query() {
+        let uri: Uri = "/t/octo/api/github/repos?page=2&language=Rust".parse().unwrap();
+        let rewritten = with_path(&uri, "/api/github/repos").unwrap();
+        assert_eq!(rewritten.path(), "/api/github/repos");
+        assert_eq!(rewritten.query(), Some("page=2&language=Rust"));
+
+        let absolute: Uri = "http://octo.example.com/t/octo/health".parse().unwrap();
+        assert_eq!(with_path(&absolute, "/health").unwrap().to_string(), "http://octo.example.com/health");
+    }
+}
@@ -0,0 +1,68 @@
+/*
+ * Usage tracking middleware attributing each authenticated request to the credential that made it.
+ * I'm skipping anonymous traffic and keys that don't authenticate, since there is no account to attribute them to or throttle.
+ * Keys past their tenant's hourly quota get a 429 until the hour turns over; the operator token is never held to one.
+ */
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, HeaderValue, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::time::Instant;
+use tracing::warn;
+
+use crate::{
+    middleware::{tenant::CurrentTenant, users::authenticated_principal},
+    utils::error::AppError,
+    AppState,
+};
+
+/// Turn away keys that have spent their hourly quota, then count the request against its API key once the response is ready
+pub async fn usage_middleware(
+    State(app_state): State<AppState>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    if !app_state.usage_service.is_enabled() {
+        return next.run(request).await;
+    }
+
+    let quota = match request.extensions().get::<CurrentTenant>() {
+        Some(tenant) => tenant.config(&app_state.live_config).usage_hourly_quota,
+        None => app_state.live_config.load().usage_hourly_quota,
+    };
+    let (mut parts, body) = request.into_parts();
+    let principal = authenticated_principal(&mut parts, &app_state).await;
+    let request = Request::from_parts(parts, body);
+    let start_time = Instant::now();
+
+    let response = match principal.as_deref() {
+        Some(key_id) if quota > 0 && key_id != "admin" => match app_state.usage_service.current_hour(key_id).await {
+            Ok((requests, resets_in)) if requests >= quota => {
+                let mut response = AppError::RateLimitError(format!("{} requests per hour allowed for this API key", quota)).into_response();
+                response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(resets_in));
+                response
+            }
+            Ok(_) => next.run(request).await,
+            Err(e) => {
+                // Redis being down shouldn't take the API with it, so the quota fails open
+                warn!("Failed to read usage for {}, not enforcing its quota: {}", key_id, e);
+                next.run(request).await
+            }
+        },
+        _ => next.run(request).await,
+    };
+
+    if let Some(key_id) = principal {
+        app_state.usage_service.record(
+            key_id,
+            response.status().as_u16(),
+            start_time.elapsed().as_millis() as u64,
+        );
+    }
+
+    response
+}
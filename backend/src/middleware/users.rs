@@ -0,0 +1,76 @@
+/*
+ * User authentication extractor resolving a personal API key to the account that owns it.
+ * I'm leaving anonymous access to the handlers: a route that takes Option<UserAuth> treats a missing or unknown key as no user at all.
+ * The lookup is kept in the request's extensions, so the rate limiter, usage tracking, and the extractor share one query.
+ */
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::request::Parts,
+};
+use tracing::warn;
+
+use crate::{
+    middleware::{admin::{bearer_token, request_principal}, tenant::CurrentTenant},
+    models::users::User,
+    utils::error::AppError,
+    AppState,
+};
+
+/// A request's key lookup, cached so later layers don't query again
+#[derive(Debug, Clone)]
+struct KeyLookup(Option<User>);
+
+/// The account whose API key authenticated the request, always one of the request tenant's accounts
+#[derive(Debug, Clone)]
+pub struct UserAuth(pub User);
+
+#[async_trait]
+impl FromRequestParts<AppState> for UserAuth {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        if bearer_token(&parts.headers).is_none() {
+            return Err(AppError::AuthenticationError("Missing API key".to_string()));
+        }
+
+        authenticated_user(parts, state)
+            .await?
+            .map(UserAuth)
+            .ok_or_else(|| AppError::AuthenticationError("Invalid or revoked API key".to_string()))
+    }
+}
+
+/// The tenant account the request's key belongs to; None without a key or when the key doesn't authenticate
+pub async fn authenticated_user(parts: &mut Parts, state: &AppState) -> Result<Option<User>, AppError> {
+    if let Some(KeyLookup(user)) = parts.extensions.get::<KeyLookup>() {
+        return Ok(user.clone());
+    }
+
+    let Ok(tenant) = CurrentTenant::from_request_parts(parts, state).await;
+    let user = match bearer_token(&parts.headers) {
+        Some(key) => state.user_service.authenticate(&tenant.slug, key).await?,
+        None => None,
+    };
+    parts.extensions.insert(KeyLookup(user.clone()));
+    Ok(user)
+}
+
+/// The principal a request is counted under once its credential checks out: "admin" for the operator token, the key's `key:` id for a user's key
+/// I'm returning None for anonymous requests and unknown keys alike, so a made-up key can't claim a fresh bucket of its own
+pub async fn authenticated_principal(parts: &mut Parts, state: &AppState) -> Option<String> {
+    let principal = request_principal(&parts.headers, state.config.admin_api_token.as_deref())?;
+    if principal == "admin" {
+        return Some(principal);
+    }
+
+    match authenticated_user(parts, state).await {
+        Ok(Some(_)) => Some(principal),
+        Ok(None) => None,
+        Err(e) => {
+            warn!("Failed to authenticate API key: {}", e);
+            None
+        }
+    }
+}
@@ -0,0 +1,191 @@
+/*
+ * Zoom animation models covering the keyframes a client asks for, the output container, and the background job that renders large ones.
+ * I'm giving every field but the two keyframes and the zoom factor a default so a short request body still describes a full animation.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::models::fractals::{FractalType, RenderOptions};
+
+/// Container an animation is encoded in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnimationFormat {
+    #[default]
+    Gif,
+    /// Only available when the server is built with the `ffmpeg` feature
+    Mp4,
+}
+
+impl AnimationFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnimationFormat::Gif => "gif",
+            AnimationFormat::Mp4 => "mp4",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            AnimationFormat::Gif => "image/gif",
+            AnimationFormat::Mp4 => "video/mp4",
+        }
+    }
+}
+
+/// Body for `/api/fractals/animate`: a zoom from one view to another over a fixed number of frames
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[validate(schema(function = "validate_end_zoom"))]
+pub struct AnimationRequest {
+    #[validate(range(min = 64, max = 1920, message = "Width must be between 64 and 1920 pixels"))]
+    #[serde(default = "default_width")]
+    pub width: u32,
+
+    #[validate(range(min = 64, max = 1080, message = "Height must be between 64 and 1080 pixels"))]
+    #[serde(default = "default_height")]
+    pub height: u32,
+
+    #[serde(default = "default_fractal_type")]
+    pub fractal_type: FractalType,
+
+    #[validate(range(min = -2.0, max = 2.0, message = "Start X must be between -2.0 and 2.0"))]
+    pub start_x: f64,
+
+    #[validate(range(min = -2.0, max = 2.0, message = "Start Y must be between -2.0 and 2.0"))]
+    pub start_y: f64,
+
+    #[validate(range(min = -2.0, max = 2.0, message = "End X must be between -2.0 and 2.0"))]
+    pub end_x: f64,
+
+    #[validate(range(min = -2.0, max = 2.0, message = "End Y must be between -2.0 and 2.0"))]
+    pub end_y: f64,
+
+    #[validate(range(min = 0.1, max = 1e15, message = "Start zoom must be between 0.1 and 1e15"))]
+    #[serde(default = "default_start_zoom")]
+    pub start_zoom: f64,
+
+    /// How much deeper the last frame is than the first; below 1 zooms out
+    pub zoom_factor: f64,
+
+    /// At least two; the most allowed is ANIMATION_MAX_FRAMES
+    #[validate(range(min = 2, message = "An animation needs at least 2 frames"))]
+    pub frames: u32,
+
+    #[validate(range(min = 1, max = 50, message = "FPS must be between 1 and 50"))]
+    #[serde(default = "default_fps")]
+    pub fps: u32,
+
+    #[validate(range(min = 50, max = 10000, message = "Max iterations must be between 50 and 10000"))]
+    #[serde(default = "default_max_iterations")]
+    pub max_iterations: u32,
+
+    /// Palette and colouring for every frame; antialiasing, threads, and output_format are refused, since `format` picks the container
+    #[serde(flatten)]
+    #[validate]
+    pub options: RenderOptions,
+
+    /// gif (the default) or mp4
+    #[serde(default)]
+    pub format: AnimationFormat,
+
+    /// Queue the animation as a job even when it is small enough to render inline
+    #[serde(default)]
+    pub background: bool,
+}
+
+impl AnimationRequest {
+    pub fn end_zoom(&self) -> f64 {
+        self.start_zoom * self.zoom_factor
+    }
+
+    /// Pixels drawn across every frame
+    pub fn total_pixels(&self) -> u64 {
+        u64::from(self.width) * u64::from(self.height) * u64::from(self.frames)
+    }
+}
+
+fn default_width() -> u32 {
+    640
+}
+
+fn default_height() -> u32 {
+    480
+}
+
+fn default_fractal_type() -> FractalType {
+    FractalType::Mandelbrot
+}
+
+fn default_start_zoom() -> f64 {
+    1.0
+}
+
+fn default_fps() -> u32 {
+    24
+}
+
+fn default_max_iterations() -> u32 {
+    200
+}
+
+/// The last frame has to land within the same zoom bounds as the first
+fn validate_end_zoom(request: &AnimationRequest) -> Result<(), ValidationError> {
+    let end_zoom = request.end_zoom();
+    if !request.zoom_factor.is_finite() || !(0.1..=1e15).contains(&end_zoom) {
+        let mut error = ValidationError::new("end_zoom");
+        error.message = Some("Start zoom times zoom factor must be between 0.1 and 1e15".into());
+        return Err(error);
+    }
+    Ok(())
+}
+
+/// An animation job as stored in animation_jobs
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AnimationJob {
+    pub id: Uuid,
+    pub format: String,
+    pub parameters: serde_json::Value,
+    pub frame_count: i32,
+    /// queued, running, succeeded, or failed
+    pub status: String,
+    pub byte_size: Option<i64>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(zoom_factor: f64) -> AnimationRequest {
+        serde_json::from_value(serde_json::json!({
+            "start_x": -0.5,
+            "start_y": 0.0,
+            "end_x": -0.743643,
+            "end_y": 0.131825,
+            "zoom_factor": zoom_factor,
+            "frames": 48,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn defaults_fill_in_a_short_request() {
+        let animation = request(1000.0);
+        assert_eq!((animation.width, animation.height, animation.fps), (640, 480, 24));
+        assert_eq!(animation.format, AnimationFormat::Gif);
+        assert_eq!(animation.total_pixels(), 640 * 480 * 48);
+        assert!(animation.validate().is_ok());
+    }
+
+    #[test]
+    fn end_zoom_must_stay_in_bounds() {
+        assert!(request(1e16).validate().is_err());
+        assert!(request(0.01).validate().is_err());
+        assert!(request(0.5).validate().is_ok());
+    }
+}
@@ -0,0 +1,161 @@
+/*
+ * Preset catalog models: curated Julia constants and deep-zoom coordinates the frontend offers as one-click exploration targets.
+ * I'm storing the fractal type flat, the way preset bundles describe it, so a catalog entry drops straight into a render request.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::models::fractals::FractalType;
+
+/// What a catalog entry points the frontend at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CatalogKind {
+    /// A Julia set worth seeing whole, chosen by its constant
+    JuliaConstant,
+    /// A spot deep inside a Mandelbrot-family set
+    DeepZoom,
+}
+
+impl CatalogKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CatalogKind::JuliaConstant => "julia_constant",
+            CatalogKind::DeepZoom => "deep_zoom",
+        }
+    }
+}
+
+/// A catalog entry as stored in catalog_presets
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct CatalogPreset {
+    pub id: Uuid,
+    pub name: String,
+    /// julia_constant or deep_zoom
+    pub kind: String,
+    pub fractal_type: String,
+    pub c_real: Option<f64>,
+    pub c_imag: Option<f64>,
+    pub power: Option<f64>,
+    pub center_x: f64,
+    pub center_y: f64,
+    pub zoom: f64,
+    pub max_iterations: i32,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    /// Shipped with the catalog rather than added by an operator
+    pub curated: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Body for creating or replacing a catalog entry
+#[derive(Debug, Clone, Deserialize, Validate)]
+#[validate(schema(function = "validate_kind"))]
+pub struct CatalogPresetInput {
+    #[validate(length(min = 1, max = 80, message = "Name must be 1 to 80 characters"))]
+    pub name: String,
+
+    pub kind: CatalogKind,
+
+    pub fractal_type: FractalType,
+
+    #[validate(range(min = -2.0, max = 2.0, message = "Center X must be between -2.0 and 2.0"))]
+    #[serde(default)]
+    pub center_x: f64,
+
+    #[validate(range(min = -2.0, max = 2.0, message = "Center Y must be between -2.0 and 2.0"))]
+    #[serde(default)]
+    pub center_y: f64,
+
+    #[validate(range(min = 0.1, max = 1e15, message = "Zoom must be between 0.1 and 1e15"))]
+    #[serde(default = "default_zoom")]
+    pub zoom: f64,
+
+    #[validate(range(min = 50, max = 10000, message = "Max iterations must be between 50 and 10000"))]
+    #[serde(default = "default_max_iterations")]
+    pub max_iterations: u32,
+
+    #[validate(length(max = 500, message = "Description must be at most 500 characters"))]
+    pub description: Option<String>,
+
+    #[validate(length(max = 8, message = "At most 8 tags"), custom = "validate_tags")]
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Filters for listing the catalog
+#[derive(Debug, Default, Deserialize)]
+pub struct CatalogQuery {
+    pub kind: Option<CatalogKind>,
+    /// mandelbrot, julia, burning_ship, tricorn, or multibrot
+    pub fractal_type: Option<String>,
+    pub tag: Option<String>,
+}
+
+fn default_zoom() -> f64 {
+    1.0
+}
+
+fn default_max_iterations() -> u32 {
+    500
+}
+
+/// Julia constants have to be Julia sets, and deep zooms have to be into something other than one
+fn validate_kind(input: &CatalogPresetInput) -> Result<(), ValidationError> {
+    let is_julia = input.fractal_type.is_julia();
+    if is_julia != (input.kind == CatalogKind::JuliaConstant) {
+        let mut error = ValidationError::new("kind");
+        error.message = Some("julia_constant entries must be Julia sets, and deep_zoom entries must not be".into());
+        return Err(error);
+    }
+    Ok(())
+}
+
+fn validate_tags(tags: &[String]) -> Result<(), ValidationError> {
+    let valid = |tag: &String| (1..=32).contains(&tag.len()) && tag.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if !tags.iter().all(valid) {
+        let mut error = ValidationError::new("tags");
+        error.message = Some("Tags are 1-32 lowercase letters, digits, or '-'".into());
+        return Err(error);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(body: serde_json::Value) -> CatalogPresetInput {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn kind_must_match_fractal_type() {
+        let rabbit = input(serde_json::json!({
+            "name": "Douady rabbit",
+            "kind": "julia_constant",
+            "fractal_type": { "Julia": { "c_real": -0.123, "c_imag": 0.745 } },
+        }));
+        assert!(rabbit.validate().is_ok());
+        assert_eq!((rabbit.zoom, rabbit.max_iterations), (1.0, 500));
+
+        let mislabelled = CatalogPresetInput { kind: CatalogKind::DeepZoom, ..rabbit.clone() };
+        assert!(mislabelled.validate().is_err());
+
+        let seahorse = CatalogPresetInput {
+            kind: CatalogKind::DeepZoom,
+            fractal_type: FractalType::Mandelbrot,
+            center_x: -0.7436438870371587,
+            center_y: 0.13182590420531198,
+            zoom: 1e12,
+            ..rabbit.clone()
+        };
+        assert!(seahorse.validate().is_ok());
+        assert!(CatalogPresetInput { zoom: 1e16, ..seahorse.clone() }.validate().is_err());
+        assert!(CatalogPresetInput { tags: vec!["Spiral".to_string()], ..seahorse }.validate().is_err());
+    }
+}
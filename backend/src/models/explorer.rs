@@ -0,0 +1,116 @@
+/*
+ * Interactive exploration models: the viewport updates a client sends over /ws/fractals and the events streamed back.
+ * I'm sending pixels as binary messages behind a fixed little-endian header, so a region never pays for base64 or JSON escaping.
+ */
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    models::fractals::FractalType,
+    services::{
+        fractal_service::{ColoringMode, OrbitTrap, Precision},
+        renderers::RendererKind,
+    },
+};
+
+/// Bytes before the RGBA pixels in each binary region message
+pub const REGION_HEADER_BYTES: usize = 24;
+
+/// Sent by the client whenever the view changes; fields left out keep their value from earlier updates
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct ViewportUpdate {
+    /// Echoed in every event and region for this view; the server numbers updates itself when absent
+    pub sequence: Option<u64>,
+    pub center_x: Option<f64>,
+    pub center_y: Option<f64>,
+    pub zoom: Option<f64>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub max_iterations: Option<u32>,
+    pub fractal_type: Option<FractalType>,
+    pub palette_id: Option<Uuid>,
+    /// Histogram-coloured views arrive as a single region, since each colour depends on the whole view
+    pub coloring_mode: Option<ColoringMode>,
+    pub orbit_trap: Option<OrbitTrap>,
+    pub antialiasing: Option<u8>,
+    pub precision: Option<Precision>,
+}
+
+impl ViewportUpdate {
+    /// Fold a newer update in, so one value describes the whole view however many updates were skipped
+    pub fn merge(&mut self, newer: ViewportUpdate) {
+        self.sequence = newer.sequence;
+        self.center_x = newer.center_x.or(self.center_x);
+        self.center_y = newer.center_y.or(self.center_y);
+        self.zoom = newer.zoom.or(self.zoom);
+        self.width = newer.width.or(self.width);
+        self.height = newer.height.or(self.height);
+        self.max_iterations = newer.max_iterations.or(self.max_iterations);
+        self.fractal_type = newer.fractal_type.or(self.fractal_type.take());
+        self.palette_id = newer.palette_id.or(self.palette_id);
+        self.coloring_mode = newer.coloring_mode.or(self.coloring_mode);
+        self.orbit_trap = newer.orbit_trap.or(self.orbit_trap);
+        self.antialiasing = newer.antialiasing.or(self.antialiasing);
+        self.precision = newer.precision.or(self.precision);
+    }
+}
+
+/// JSON text messages the server sends; pixels arrive separately as binary region messages
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExplorerEvent {
+    /// The view's regions follow, nearest the centre first
+    FrameStarted {
+        sequence: u64,
+        width: u32,
+        height: u32,
+        fractal_type: &'static str,
+        regions: usize,
+    },
+    FrameComplete {
+        sequence: u64,
+        /// Render time summed over the regions, excluding time spent waiting on the client
+        computation_time_ms: u128,
+        renderers: Vec<&'static str>,
+        compute_backend: RendererKind,
+    },
+    /// A newer viewport arrived before every region was sent, so the rest of this view was dropped
+    Superseded { sequence: u64, regions_sent: usize },
+    Error { sequence: Option<u64>, message: String },
+}
+
+/// One region as a binary message: sequence as u64, then x, y, width, and height as u32, then its RGBA rows
+pub fn encode_region(sequence: u64, x: u32, y: u32, width: u32, height: u32, pixels: &[u8]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(REGION_HEADER_BYTES + pixels.len());
+    message.extend_from_slice(&sequence.to_le_bytes());
+    for value in [x, y, width, height] {
+        message.extend_from_slice(&value.to_le_bytes());
+    }
+    message.extend_from_slice(pixels);
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_updates_merge_and_regions_encode() {
+        let mut view: ViewportUpdate = serde_json::from_str(r#"{"width":640,"height":480,"fractal_type":"Tricorn","zoom":2.0}"#).unwrap();
+        view.merge(serde_json::from_str(r#"{"sequence":7,"center_x":-0.4,"zoom":8.0}"#).unwrap());
+        assert_eq!(view.sequence, Some(7));
+        assert_eq!((view.width, view.height, view.zoom, view.center_x), (Some(640), Some(480), Some(8.0), Some(-0.4)));
+        assert_eq!(view.fractal_type, Some(FractalType::Tricorn));
+
+        let message = encode_region(7, 160, 120, 2, 1, &[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(message.len(), REGION_HEADER_BYTES + 8);
+        assert_eq!(u64::from_le_bytes(message[..8].try_into().unwrap()), 7);
+        assert_eq!(u32::from_le_bytes(message[8..12].try_into().unwrap()), 160);
+        assert_eq!(u32::from_le_bytes(message[12..16].try_into().unwrap()), 120);
+        assert_eq!(&message[REGION_HEADER_BYTES..], &[1, 2, 3, 4, 5, 6, 7, 8]);
+
+        let event = serde_json::to_value(ExplorerEvent::Superseded { sequence: 7, regions_sent: 3 }).unwrap();
+        assert_eq!(event, serde_json::json!({ "type": "superseded", "sequence": 7, "regions_sent": 3 }));
+    }
+}
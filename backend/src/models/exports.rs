@@ -0,0 +1,65 @@
+/*
+ * Export job models for the background export API, covering the dataset and filters requested and the job's progress.
+ * I'm storing the filters as they were submitted so a retried job exports exactly the rows first asked for.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportDataset {
+    FractalComputations,
+    PerformanceMetrics,
+}
+
+impl ExportDataset {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportDataset::FractalComputations => "fractal_computations",
+            ExportDataset::PerformanceMetrics => "performance_metrics",
+        }
+    }
+}
+
+impl fmt::Display for ExportDataset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Row filters shared by the streaming endpoints and export jobs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportFilters {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+    /// Only applies to performance metrics
+    pub metric_type: Option<String>,
+}
+
+/// Body for starting an export job
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateExportRequest {
+    pub dataset: ExportDataset,
+    #[serde(flatten)]
+    pub filters: ExportFilters,
+    pub actor: Option<String>,
+}
+
+/// An export job as stored in export_jobs
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ExportJob {
+    pub id: Uuid,
+    pub dataset: String,
+    pub filters: serde_json::Value,
+    /// queued, running, succeeded, or failed
+    pub status: String,
+    pub row_count: Option<i64>,
+    pub error: Option<String>,
+    pub requested_by: String,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,125 @@
+/*
+ * Background fractal job models: where a queued render has got to, and the pixels it left behind.
+ * I'm counting progress in finished regions, since that's the unit the worker renders in and the only one it can report honestly.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FractalJobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl FractalJobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FractalJobStatus::Queued => "queued",
+            FractalJobStatus::Running => "running",
+            FractalJobStatus::Succeeded => "succeeded",
+            FractalJobStatus::Failed => "failed",
+            FractalJobStatus::Cancelled => "cancelled",
+        }
+    }
+
+    /// Whether the job will never change again
+    pub fn is_finished(&self) -> bool {
+        matches!(self, FractalJobStatus::Succeeded | FractalJobStatus::Failed | FractalJobStatus::Cancelled)
+    }
+}
+
+/// A fractal job as kept in Redis until FRACTAL_JOB_TTL_SECONDS after its last update
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FractalJob {
+    pub id: Uuid,
+    pub status: FractalJobStatus,
+    pub fractal_type: String,
+    pub width: u32,
+    pub height: u32,
+    pub regions_done: usize,
+    /// Zero until a worker has split the view
+    pub regions_total: usize,
+    pub computation_time_ms: Option<u128>,
+    /// Renderers that drew at least one region
+    #[serde(default)]
+    pub renderers: Vec<String>,
+    /// Last failure; kept while a retry is queued
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl FractalJob {
+    pub fn new(id: Uuid, fractal_type: &str, width: u32, height: u32) -> Self {
+        Self {
+            id,
+            status: FractalJobStatus::Queued,
+            fractal_type: fractal_type.to_string(),
+            width,
+            height,
+            regions_done: 0,
+            regions_total: 0,
+            computation_time_ms: None,
+            renderers: Vec::new(),
+            error: None,
+            created_at: Utc::now(),
+            started_at: None,
+            completed_at: None,
+        }
+    }
+
+    /// Share of the view rendered so far, from 0 to 1
+    pub fn progress(&self) -> f64 {
+        match self.status {
+            FractalJobStatus::Succeeded => 1.0,
+            _ if self.regions_total == 0 => 0.0,
+            _ => self.regions_done as f64 / self.regions_total as f64,
+        }
+    }
+}
+
+/// Status response for `/api/fractals/jobs/:id`
+#[derive(Debug, Clone, Serialize)]
+pub struct FractalJobView {
+    #[serde(flatten)]
+    pub job: FractalJob,
+    pub progress: f64,
+    /// Where the pixels can be fetched once the job has succeeded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_url: Option<String>,
+}
+
+impl From<FractalJob> for FractalJobView {
+    fn from(job: FractalJob) -> Self {
+        let result_url = (job.status == FractalJobStatus::Succeeded).then(|| format!("/api/fractals/jobs/{}/result", job.id));
+        Self { progress: job.progress(), result_url, job }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_follows_finished_regions() {
+        let mut job = FractalJob::new(Uuid::new_v4(), "mandelbrot", 800, 600);
+        assert_eq!(job.progress(), 0.0);
+
+        job.status = FractalJobStatus::Running;
+        job.regions_total = 16;
+        job.regions_done = 4;
+        assert_eq!(job.progress(), 0.25);
+
+        job.status = FractalJobStatus::Succeeded;
+        let view = FractalJobView::from(job);
+        assert_eq!(view.progress, 1.0);
+        assert!(view.result_url.unwrap().ends_with("/result"));
+    }
+}
@@ -3,14 +3,21 @@
  * I'm implementing comprehensive fractal parameter management, result handling, and benchmark structures that integrate seamlessly with the high-performance Rust computation engine.
  */
 
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize};
 use sqlx::FromRow;
 use chrono::{DateTime, Utc};
+use std::{fmt, str::FromStr};
 use validator::{Validate, ValidationError};
 
+use crate::{
+    services::fractal_service::{ColoringMode, InteriorColoring, OrbitTrap, Precision},
+    utils::error::{AppError, Result as AppResult},
+};
+
 /// Core fractal generation request with comprehensive parameter validation
 /// I'm ensuring all fractal parameters are within safe computational bounds
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[validate(schema(function = "validate_supersampled_size"))]
 pub struct FractalRequest {
     #[validate(range(min = 64, max = 4096, message = "Width must be between 64 and 4096 pixels"))]
     pub width: u32,
@@ -30,7 +37,174 @@ pub struct FractalRequest {
     #[validate(range(min = 50, max = 10000, message = "Max iterations must be between 50 and 10000"))]
     pub max_iterations: u32,
 
+    /// Scale max_iterations with zoom by the server's formula instead of using the value above
+    #[serde(default)]
+    pub auto_iterations: bool,
+
     pub fractal_type: FractalType,
+
+    /// Palette and colouring; output_format is refused, since a JSON request's result is encoded where it's fetched
+    #[serde(flatten)]
+    #[validate]
+    pub options: RenderOptions,
+}
+
+/// The supersampled drawing has to fit the same bounds as a plain one
+fn validate_supersampled_size(request: &FractalRequest) -> Result<(), ValidationError> {
+    let factor = u32::from(request.options.antialiasing.unwrap_or(1));
+    if request.width * factor > 4096 || request.height * factor > 4096 {
+        let mut error = ValidationError::new("supersampled_size");
+        error.message = Some("Width and height times antialiasing must stay within 4096 pixels".into());
+        return Err(error);
+    }
+    Ok(())
+}
+
+/// Palette, colouring, and output options shared by every render request, flattened into each query string and body
+/// I'm leaving every option unset by default so each endpoint applies its own defaults and can refuse the ones it can't draw
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Validate)]
+pub struct RenderOptions {
+    /// Uploaded palette to colour the render with; the built-in dark theme is used when absent
+    #[serde(default)]
+    pub palette_id: Option<uuid::Uuid>,
+
+    /// Built-in palette name, instead of palette_id
+    #[serde(default)]
+    pub palette: Option<String>,
+
+    /// Inline gradient stops as `RRGGBB[AA][:position]`, comma-separated, instead of palette_id
+    #[serde(default)]
+    pub gradient: Option<String>,
+
+    /// escape_time (the default), smooth, histogram, or distance_estimate
+    #[serde(default)]
+    pub coloring_mode: Option<ColoringMode>,
+
+    /// Orbit trap to colour by instead, as `point:x,y`, `line:x,y,angle`, or `circle:x,y,radius`,
+    /// or in a JSON body as e.g. `{"shape": "circle", "x": 0.0, "y": 0.0, "radius": 0.5}`
+    #[serde(default, deserialize_with = "orbit_trap_from_spec")]
+    pub orbit_trap: Option<OrbitTrap>,
+
+    /// Supersampling factor from 1 to 4; the view is drawn this many times wider and taller and averaged down
+    #[validate(range(min = 1, max = 4, message = "Antialiasing must be between 1x and 4x"))]
+    #[serde(default, deserialize_with = "number_from_text")]
+    pub antialiasing: Option<u8>,
+
+    /// auto (the default), f32, f64, or extended; the response reports the precision actually used
+    #[serde(default)]
+    pub precision: Option<Precision>,
+
+    /// Render threads to use, capped at FRACTAL_THREADS; all of them when unset
+    #[validate(range(min = 1, message = "Threads must be at least 1"))]
+    #[serde(default, deserialize_with = "number_from_text")]
+    pub threads: Option<u32>,
+
+    /// black (the default), or period to shade Mandelbrot points inside the set by their orbit's cycle length
+    #[serde(default)]
+    pub interior_coloring: Option<InteriorColoring>,
+
+    /// raw (JSON with the RGBA buffer, the default), png, jpeg, or webp
+    #[serde(default)]
+    pub output_format: Option<OutputFormat>,
+}
+
+impl RenderOptions {
+    /// Refuse any of `unsupported` the caller set, rather than quietly drawing without it
+    pub fn refuse(&self, endpoint: &str, unsupported: &[&str]) -> AppResult<()> {
+        let given = [
+            ("palette_id", self.palette_id.is_some()),
+            ("palette", self.palette.is_some()),
+            ("gradient", self.gradient.is_some()),
+            ("coloring_mode", self.coloring_mode.is_some()),
+            ("orbit_trap", self.orbit_trap.is_some()),
+            ("antialiasing", self.antialiasing.is_some()),
+            ("precision", self.precision.is_some()),
+            ("threads", self.threads.is_some()),
+            ("interior_coloring", self.interior_coloring.is_some()),
+            ("output_format", self.output_format.is_some()),
+        ];
+        match given.iter().find(|(name, set)| *set && unsupported.contains(name)) {
+            Some((name, _)) => Err(AppError::ValidationError(format!("{} does not support `{}`", endpoint, name))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Image size, view, and iteration count shared by the escape-time render queries, flattened into each beside RenderOptions
+/// I'm leaving every field unset by default so presets and each endpoint's own defaults fill in what the query leaves out
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ViewportQuery {
+    #[serde(default, deserialize_with = "number_from_text")]
+    pub width: Option<u32>,
+    #[serde(default, deserialize_with = "number_from_text")]
+    pub height: Option<u32>,
+    #[serde(default, deserialize_with = "number_from_text")]
+    pub center_x: Option<f64>,
+    #[serde(default, deserialize_with = "number_from_text")]
+    pub center_y: Option<f64>,
+    #[serde(default, deserialize_with = "number_from_text")]
+    pub zoom: Option<f64>,
+    #[serde(default, deserialize_with = "number_from_text")]
+    pub max_iterations: Option<u32>,
+    /// Let the server scale max_iterations with zoom instead, overriding any value given here or by the preset
+    #[serde(default, deserialize_with = "number_from_text")]
+    pub auto_iterations: Option<bool>,
+}
+
+/// A number from a JSON body, or from the text a flattened query string hands every value over as
+pub(crate) fn number_from_text<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + FromStr,
+    T::Err: fmt::Display,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Number<T> {
+        Value(T),
+        Text(String),
+    }
+
+    match Option::<Number<T>>::deserialize(deserializer)? {
+        Some(Number::Value(value)) => Ok(Some(value)),
+        Some(Number::Text(text)) => text.trim().parse().map(Some).map_err(de::Error::custom),
+        None => Ok(None),
+    }
+}
+
+/// An orbit trap written as a query-string spec or as a JSON object
+fn orbit_trap_from_spec<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<OrbitTrap>, D::Error> {
+    struct TrapVisitor;
+
+    impl<'de> de::Visitor<'de> for TrapVisitor {
+        type Value = Option<OrbitTrap>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("an orbit trap like `circle:x,y,radius` or {\"shape\": \"circle\", ...}")
+        }
+
+        fn visit_str<E: de::Error>(self, spec: &str) -> Result<Self::Value, E> {
+            spec.parse().map(Some).map_err(E::custom)
+        }
+
+        fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+            OrbitTrap::deserialize(de::value::MapAccessDeserializer::new(map)).map(Some)
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+            deserializer.deserialize_any(self)
+        }
+    }
+
+    deserializer.deserialize_any(TrapVisitor)
 }
 
 /// Fractal computation response with comprehensive performance metrics
@@ -47,12 +221,15 @@ pub struct FractalResponse {
     pub metadata: FractalMetadata,
 }
 
-/// Fractal type enumeration supporting Mandelbrot and Julia sets
+/// Fractal type enumeration supporting the Mandelbrot family and Julia sets
 /// I'm implementing type-safe fractal variants with specific parameters
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum FractalType {
     Mandelbrot,
     Julia { c_real: f64, c_imag: f64 },
+    BurningShip,
+    Tricorn,
+    Multibrot { power: f64 },
 }
 
 impl FractalType {
@@ -60,6 +237,9 @@ impl FractalType {
         match self {
             FractalType::Mandelbrot => "mandelbrot",
             FractalType::Julia { .. } => "julia",
+            FractalType::BurningShip => "burning_ship",
+            FractalType::Tricorn => "tricorn",
+            FractalType::Multibrot { .. } => "multibrot",
         }
     }
 
@@ -73,6 +253,69 @@ impl FractalType {
             _ => None,
         }
     }
+
+    pub fn multibrot_power(&self) -> Option<f64> {
+        match *self {
+            FractalType::Multibrot { power } => Some(power),
+            _ => None,
+        }
+    }
+}
+
+/// How a render endpoint returns its pixels: JSON with the raw RGBA buffer, or an encoded image body
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Raw,
+    Png,
+    Jpeg,
+    /// Lossless, since that's the only WebP the encoder writes
+    Webp,
+}
+
+impl OutputFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            OutputFormat::Raw => "application/json",
+            OutputFormat::Png => "image/png",
+            OutputFormat::Jpeg => "image/jpeg",
+            OutputFormat::Webp => "image/webp",
+        }
+    }
+}
+
+/// File formats the raw escape counts can be exported in, instead of a coloured image
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EscapeExport {
+    /// NumPy array file, loadable with numpy.load
+    Npy,
+    /// One line per row, values comma-separated
+    Csv,
+    /// Bare little-endian values, row-major; shape and dtype travel in headers
+    Bin,
+    /// Runs of equal values, zstd-compressed; the decoding recipe travels in headers
+    Rle,
+}
+
+impl EscapeExport {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            EscapeExport::Npy | EscapeExport::Bin => "application/octet-stream",
+            EscapeExport::Csv => "text/csv",
+            EscapeExport::Rle => "application/zstd",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            EscapeExport::Npy => "npy",
+            EscapeExport::Csv => "csv",
+            EscapeExport::Bin => "bin",
+            EscapeExport::Rle => "rle.zst",
+        }
+    }
 }
 
 /// Fractal computation parameters for result tracking
@@ -85,7 +328,12 @@ pub struct FractalParameters {
     pub zoom_level: f64,
     pub max_iterations: u32,
     pub julia_constant: Option<(f64, f64)>,
+    pub multibrot_power: Option<f64>,
     pub color_palette: String,
+    pub coloring_mode: ColoringMode,
+    pub orbit_trap: Option<OrbitTrap>,
+    pub antialiasing: u8,
+    pub precision: Precision,
     pub escape_radius: f64,
 }
 
@@ -98,7 +346,14 @@ impl FractalParameters {
             zoom_level: request.zoom,
             max_iterations: request.max_iterations,
             julia_constant: request.fractal_type.julia_constant(),
-            color_palette: "dark_theme".to_string(),
+            multibrot_power: request.fractal_type.multibrot_power(),
+            color_palette: request.options.palette_id
+                .map(|id| id.to_string())
+                .unwrap_or_else(|| "dark_theme".to_string()),
+            coloring_mode: request.options.coloring_mode.unwrap_or_default(),
+            orbit_trap: request.options.orbit_trap,
+            antialiasing: request.options.antialiasing.unwrap_or(1),
+            precision: request.options.precision.unwrap_or_default(),
             escape_radius: 4.0,
         }
     }
@@ -383,7 +638,9 @@ impl Default for BenchmarkRequest {
                         center_y: 0.0,
                         zoom: 1.0,
                         max_iterations: 100,
+                        auto_iterations: false,
                         fractal_type: FractalType::Mandelbrot,
+                        options: RenderOptions::default(),
                     },
                     expected_performance: None,
                 },
@@ -397,7 +654,9 @@ impl Default for BenchmarkRequest {
                         center_y: 0.0,
                         zoom: 1.0,
                         max_iterations: 200,
+                        auto_iterations: false,
                         fractal_type: FractalType::Julia { c_real: -0.7, c_imag: 0.27015 },
+                        options: RenderOptions::default(),
                     },
                     expected_performance: None,
                 },
@@ -420,6 +679,11 @@ mod tests {
 
         assert_eq!(mandelbrot.name(), "mandelbrot");
         assert_eq!(julia.name(), "julia");
+        assert_eq!(FractalType::BurningShip.name(), "burning_ship");
+        assert_eq!(FractalType::BurningShip.julia_constant(), None);
+        assert_eq!(FractalType::Tricorn.name(), "tricorn");
+        assert_eq!(FractalType::Multibrot { power: 3.0 }.multibrot_power(), Some(3.0));
+        assert_eq!(julia.multibrot_power(), None);
         assert!(!mandelbrot.is_julia());
         assert!(julia.is_julia());
         assert_eq!(julia.julia_constant(), Some((-0.7, 0.27015)));
@@ -443,7 +707,9 @@ mod tests {
             center_y: 0.0,
             zoom: 1.0,
             max_iterations: 100,
+            auto_iterations: false,
             fractal_type: FractalType::Mandelbrot,
+            options: RenderOptions::default(),
         };
 
         assert!(valid_request.validate().is_ok());
@@ -455,9 +721,68 @@ mod tests {
             center_y: 0.0,
             zoom: 1.0,
             max_iterations: 100,
+            auto_iterations: false,
             fractal_type: FractalType::Mandelbrot,
+            options: RenderOptions::default(),
         };
 
         assert!(invalid_request.validate().is_err());
+
+        let with_antialiasing = |antialiasing| RenderOptions { antialiasing: Some(antialiasing), ..RenderOptions::default() };
+        let supersampled = FractalRequest { width: 1024, options: with_antialiasing(4), ..valid_request.clone() };
+        assert!(supersampled.validate().is_ok());
+        assert!(FractalRequest { width: 1025, ..supersampled.clone() }.validate().is_err());
+        assert!(FractalRequest { options: with_antialiasing(5), ..supersampled }.validate().is_err());
+    }
+
+    #[test]
+    fn test_render_options_read_from_query_strings_and_json() {
+        #[derive(Deserialize)]
+        struct Query {
+            width: Option<u32>,
+            #[serde(flatten)]
+            options: RenderOptions,
+        }
+
+        let uri: axum::http::Uri = "/?width=640&antialiasing=2&threads=4&orbit_trap=circle:0,0,0.5&precision=f64&palette=fire"
+            .parse()
+            .unwrap();
+        let axum::extract::Query(query) = axum::extract::Query::<Query>::try_from_uri(&uri).unwrap();
+        assert_eq!(query.width, Some(640));
+        assert_eq!(query.options.antialiasing, Some(2));
+        assert_eq!(query.options.threads, Some(4));
+        assert_eq!(query.options.orbit_trap, Some(OrbitTrap::Circle { x: 0.0, y: 0.0, radius: 0.5 }));
+        assert_eq!(query.options.precision, Some(Precision::F64));
+        assert_eq!(query.options.palette.as_deref(), Some("fire"));
+
+        #[derive(Deserialize)]
+        struct EscapeTimeQuery {
+            #[serde(flatten)]
+            viewport: ViewportQuery,
+            #[serde(flatten)]
+            options: RenderOptions,
+        }
+
+        let uri: axum::http::Uri = "/?width=640&center_x=-0.75&zoom=2.5&auto_iterations=true&threads=4".parse().unwrap();
+        let axum::extract::Query(query) = axum::extract::Query::<EscapeTimeQuery>::try_from_uri(&uri).unwrap();
+        assert_eq!(query.viewport.width, Some(640));
+        assert_eq!(query.viewport.center_x, Some(-0.75));
+        assert_eq!(query.viewport.zoom, Some(2.5));
+        assert_eq!(query.viewport.auto_iterations, Some(true));
+        assert_eq!(query.viewport.max_iterations, None);
+        assert_eq!(query.options.threads, Some(4));
+
+        let request: FractalRequest = serde_json::from_value(serde_json::json!({
+            "width": 640, "height": 480, "center_x": 0.0, "center_y": 0.0, "zoom": 1.0, "max_iterations": 100,
+            "fractal_type": "Mandelbrot",
+            "antialiasing": 2,
+            "orbit_trap": { "shape": "point", "x": 0.5, "y": 0.0 }
+        }))
+        .unwrap();
+        assert_eq!(request.options.antialiasing, Some(2));
+        assert_eq!(request.options.orbit_trap, Some(OrbitTrap::Point { x: 0.5, y: 0.0 }));
+
+        assert!(request.options.refuse("test", &["threads"]).is_ok());
+        assert!(request.options.refuse("test", &["antialiasing"]).is_err());
     }
 }
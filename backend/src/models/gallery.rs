@@ -0,0 +1,155 @@
+/*
+ * Fractal gallery models: views saved under short ids for sharing, and how the gallery lists them.
+ * I'm taking the same JSON request a batch line or job uses, so anything that can be rendered can be saved.
+ */
+
+use chrono::{DateTime, Utc};
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::fractals::{FractalRequest, OutputFormat};
+
+/// Characters in a generated share id; 62^10 ids keep collisions out of reach while links stay short
+pub const SAVED_FRACTAL_ID_LEN: usize = 10;
+
+/// Most saves one gallery page lists
+pub const MAX_GALLERY_PAGE: u32 = 100;
+
+/// A saved view as stored in saved_fractals
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct SavedFractal {
+    pub id: String,
+    pub title: Option<String>,
+    pub fractal_type: String,
+    pub width: i32,
+    pub height: i32,
+    /// The engine's request, palette resolved inline
+    pub request: serde_json::Value,
+    /// Stored render, once the image has been asked for; never sent out, since /images/:id would serve it without a signature
+    #[serde(skip_serializing)]
+    pub image_id: Option<String>,
+    /// The account that saved it, kept server-side for the owner check
+    #[serde(skip_serializing)]
+    pub user_id: Option<Uuid>,
+    pub view_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub last_viewed_at: Option<DateTime<Utc>>,
+}
+
+/// A save with the links to share it by
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedFractalView {
+    #[serde(flatten)]
+    pub saved: SavedFractal,
+    /// Opens the save, counting a view
+    pub share_url: String,
+    /// The rendered PNG for the account that saved it, drawn on first request and stored after that; others use signed_image_url
+    pub image_url: String,
+    /// Renders the view again through the full render path, with its metrics and output formats
+    pub render_url: String,
+    /// The PNG behind a signed link that needs no API call, when IMAGE_SIGNING_KEY is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signed_image_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signed_image_expires_at: Option<DateTime<Utc>>,
+}
+
+impl From<SavedFractal> for SavedFractalView {
+    fn from(saved: SavedFractal) -> Self {
+        let share_url = format!("/api/fractals/saved/{}", saved.id);
+        Self {
+            image_url: format!("{}/image", share_url),
+            render_url: format!("{}/render", share_url),
+            signed_image_url: None,
+            signed_image_expires_at: None,
+            share_url,
+            saved,
+        }
+    }
+}
+
+/// Path a signed link to the save's PNG points at, under the tenant's path prefix, before its query is added
+pub fn shared_image_path(tenant_prefix: &str, id: &str) -> String {
+    format!("{}/shared/fractals/{}.png", tenant_prefix, id)
+}
+
+/// Body for saving a view
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct SaveFractalInput {
+    #[validate(length(min = 1, max = 80, message = "Title must be 1 to 80 characters"))]
+    pub title: Option<String>,
+
+    #[validate]
+    pub request: FractalRequest,
+}
+
+/// Order the gallery lists saves in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GallerySort {
+    /// Newest first
+    #[default]
+    Recent,
+    /// Most viewed first, newest breaking ties
+    Popular,
+}
+
+/// Output for re-rendering a save
+#[derive(Debug, Default, Deserialize)]
+pub struct RenderSavedQuery {
+    /// raw (JSON with the RGBA buffer, the default), png, jpeg, or webp
+    pub output_format: Option<OutputFormat>,
+}
+
+/// Filters for listing the gallery
+#[derive(Debug, Default, Deserialize)]
+pub struct GalleryQuery {
+    #[serde(default)]
+    pub sort: GallerySort,
+    /// mandelbrot, julia, burning_ship, tricorn, or multibrot
+    pub fractal_type: Option<String>,
+    /// Saves per page, at most 100 (default: 24)
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// A fresh share id of SAVED_FRACTAL_ID_LEN letters and digits
+pub fn new_saved_fractal_id() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(SAVED_FRACTAL_ID_LEN)
+        .map(char::from)
+        .collect()
+}
+
+/// Whether `id` could be a share id, checked before it reaches the database
+pub fn is_valid_saved_fractal_id(id: &str) -> bool {
+    id.len() == SAVED_FRACTAL_ID_LEN && id.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn share_ids_are_short_and_checkable() {
+        let id = new_saved_fractal_id();
+        assert!(is_valid_saved_fractal_id(&id));
+        assert_ne!(id, new_saved_fractal_id());
+        assert!(!is_valid_saved_fractal_id("short"));
+        assert!(!is_valid_saved_fractal_id("../etc/pwd"));
+
+        let input: SaveFractalInput = serde_json::from_value(serde_json::json!({
+            "title": "Seahorse tail",
+            "request": {
+                "width": 800, "height": 600, "center_x": -0.745, "center_y": 0.113,
+                "zoom": 200.0, "max_iterations": 800, "fractal_type": "Mandelbrot",
+            },
+        }))
+        .unwrap();
+        assert!(input.validate().is_ok());
+        assert!(SaveFractalInput { title: Some(String::new()), ..input }.validate().is_err());
+    }
+}
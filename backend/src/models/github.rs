@@ -112,6 +112,8 @@ pub struct RepositoryFilter {
     pub is_fork: Option<bool>,
     pub is_archived: Option<bool>,
     pub has_topics: Option<bool>,
+    /// Repositories must carry every one of these topics
+    pub topics: Option<Vec<String>>,
     pub has_license: Option<bool>,
     pub created_after: Option<DateTime<Utc>>,
     pub created_before: Option<DateTime<Utc>>,
@@ -134,6 +136,22 @@ pub enum RepositorySort {
     ActivityScore,
 }
 
+impl RepositorySort {
+    /// Parse a query-string sort key, falling back to most recently updated
+    pub fn from_param(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "name" => RepositorySort::Name,
+            "stars" => RepositorySort::Stars,
+            "forks" => RepositorySort::Forks,
+            "created" => RepositorySort::Created,
+            "size" => RepositorySort::Size,
+            "issues" => RepositorySort::Issues,
+            "activity" => RepositorySort::ActivityScore,
+            _ => RepositorySort::Updated,
+        }
+    }
+}
+
 /// Repository collection response with pagination and metadata
 /// I'm providing comprehensive response structure for API endpoints
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -324,6 +342,7 @@ impl RepositoryFilter {
             is_fork: None,
             is_archived: None,
             has_topics: None,
+            topics: None,
             has_license: None,
             created_after: None,
             created_before: None,
@@ -394,6 +413,13 @@ impl RepositoryFilter {
             }
         }
 
+        if let Some(ref required) = self.topics {
+            let repo_topics = repo.topics.as_deref().unwrap_or(&[]);
+            if !required.iter().all(|topic| repo_topics.contains(topic)) {
+                return false;
+            }
+        }
+
         if let Some(has_license) = self.has_license {
             if repo.license_name.is_some() != has_license {
                 return false;
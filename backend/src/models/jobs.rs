@@ -0,0 +1,110 @@
+/*
+ * Background job models for the admin jobs API: scheduled jobs with their run history, and tasks in the Redis queue.
+ * I'm keeping run rows flat so the history endpoint can page through them without joins.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A registered job as the scheduler currently sees it
+#[derive(Debug, Clone, Serialize)]
+pub struct JobStatus {
+    pub name: String,
+    pub description: String,
+    /// Cron expression or interval, after any JOB_SCHEDULES override
+    pub schedule: String,
+    pub paused: bool,
+    pub running: bool,
+    /// None while the scheduler is disabled
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run: Option<JobRun>,
+}
+
+/// One execution of a job, or a scheduled execution skipped because the previous one was still running
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct JobRun {
+    pub id: i64,
+    pub job_name: String,
+    pub trigger: String,
+    pub status: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub duration_ms: Option<i64>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct JobRunQuery {
+    pub limit: Option<i64>,
+}
+
+impl JobRunQuery {
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(50).clamp(1, 500)
+    }
+}
+
+/// Optional body for pausing or resuming a job
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct JobControl {
+    pub actor: Option<String>,
+}
+
+/// A unit of work in the task queue, stored as JSON in Redis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedTask {
+    pub id: Uuid,
+    pub queue: String,
+    /// What the payload describes, such as `webhook.deliver`
+    pub kind: String,
+    pub payload: serde_json::Value,
+    /// Claims so far, counting the current one; kept in Redis apart from the stored task
+    #[serde(default)]
+    pub attempts: u32,
+    pub max_attempts: u32,
+    pub enqueued_at: DateTime<Utc>,
+    pub correlation_id: Option<String>,
+    pub last_error: Option<String>,
+}
+
+impl QueuedTask {
+    /// Whether a failure now sends the task to the dead-letter set instead of back to the queue
+    pub fn is_last_attempt(&self) -> bool {
+        self.attempts >= self.max_attempts
+    }
+}
+
+/// Task counts for one queue
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct QueueStats {
+    pub queue: String,
+    /// Due now and waiting for a worker
+    pub ready: u64,
+    /// Waiting out a retry backoff
+    pub delayed: u64,
+    pub in_flight: u64,
+    pub dead: u64,
+}
+
+/// A task that used up its attempts
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadLetter {
+    #[serde(flatten)]
+    pub task: QueuedTask,
+    pub dead_at: DateTime<Utc>,
+}
+
+/// This instance's view of scheduler leadership, reported on /health
+#[derive(Debug, Clone, Serialize)]
+pub struct LeadershipStatus {
+    /// When false every instance runs scheduled jobs
+    pub enabled: bool,
+    pub instance_id: String,
+    pub is_leader: bool,
+    /// Current lease holder as last seen, if any
+    pub leader: Option<String>,
+    pub leader_since: Option<DateTime<Utc>>,
+    pub last_renewed_at: Option<DateTime<Utc>>,
+    pub lease_seconds: u64,
+}
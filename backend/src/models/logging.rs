@@ -0,0 +1,36 @@
+/*
+ * Log filter models for the admin logging API, covering per-module level overrides layered over the configured level.
+ * I'm storing absolute expiry times so an override broadcast to other instances lapses at the same moment everywhere.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A temporary level for one tracing target, such as `dark_performance_backend::services::fractal_service`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LogFilterOverride {
+    pub target: String,
+    pub level: String,
+    /// None keeps the override until it is removed or the process restarts
+    pub expires_at: Option<DateTime<Utc>>,
+    pub set_by: String,
+}
+
+/// The filter as currently applied
+#[derive(Debug, Clone, Serialize)]
+pub struct LogFilterStatus {
+    /// Directives from the log_level setting
+    pub base: String,
+    pub overrides: Vec<LogFilterOverride>,
+    /// The full directive string handed to the subscriber
+    pub effective: String,
+}
+
+/// Body for overriding one target's level
+#[derive(Debug, Clone, Deserialize)]
+pub struct LogFilterUpdate {
+    pub level: String,
+    /// How long the override lasts, such as `10m` or `1h`
+    pub duration: Option<String>,
+    pub actor: Option<String>,
+}
@@ -3,9 +3,27 @@
  * I'm providing a clean interface to GitHub repository data, fractal computation parameters, and performance metrics with comprehensive serialization and validation support.
  */
 
+pub mod animations;
+pub mod catalog;
+pub mod gallery;
 pub mod github;
+pub mod exports;
+pub mod explorer;
+pub mod jobs;
 pub mod fractals;
+pub mod fractal_jobs;
 pub mod performance;
+pub mod recordings;
+pub mod feature_flags;
+pub mod logging;
+pub mod notification_templates;
+pub mod settings;
+pub mod sessions;
+pub mod tenants;
+pub mod users;
+pub mod webhooks;
+
+pub use dark_performance_core::palettes;
 
 // Re-export commonly used models for convenient access throughout the application
 pub use github::{
@@ -43,6 +61,21 @@ pub use performance::{
     ResourceUsage
 };
 
+pub use palettes::{
+    Palette,
+    ColorStop,
+    PaletteFormat,
+    FractalPreset,
+    PresetParameters
+};
+
+pub use webhooks::{
+    WebhookEvent,
+    WebhookSubscription,
+    WebhookDelivery,
+    DeliveryStatus
+};
+
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
@@ -195,7 +228,7 @@ impl ListQuery {
 
 /// Audit log structure for tracking changes and operations
 /// I'm implementing comprehensive audit logging for security and debugging
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuditLog {
     pub id: uuid::Uuid,
     pub entity_type: String,
@@ -204,12 +237,49 @@ pub struct AuditLog {
     pub user_id: Option<String>,
     pub ip_address: Option<String>,
     pub user_agent: Option<String>,
+    pub method: Option<String>,
+    pub path: Option<String>,
+    pub status_code: Option<i32>,
+    pub latency_ms: Option<i64>,
     pub timestamp: DateTime<Utc>,
     pub changes: Option<serde_json::Value>,
     pub metadata: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Entity type of the rows the audit middleware writes for sampled requests; every other type is an admin event
+pub const HTTP_REQUEST_AUDIT_ENTITY: &str = "http_request";
+
+impl AuditLog {
+    /// An administrative action, with the affected entity's state before and after it in `changes`
+    /// I'm tagging it with the request id so it can be matched to the request row that triggered it
+    pub fn admin_event(
+        actor: &str,
+        entity_type: &str,
+        entity_id: impl Into<String>,
+        action: AuditAction,
+        before: Option<serde_json::Value>,
+        after: Option<serde_json::Value>,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4(),
+            entity_type: entity_type.to_string(),
+            entity_id: Some(entity_id.into()),
+            action,
+            user_id: Some(actor.to_string()),
+            ip_address: None,
+            user_agent: None,
+            method: None,
+            path: None,
+            status_code: None,
+            latency_ms: None,
+            timestamp: Utc::now(),
+            changes: Some(serde_json::json!({ "before": before, "after": after })),
+            metadata: Some(serde_json::json!({ "request_id": crate::utils::correlation::current() })),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AuditAction {
     Create,
     Read,
@@ -221,6 +291,87 @@ pub enum AuditAction {
     Error,
 }
 
+impl AuditAction {
+    /// Map an HTTP method and response status onto an audit action
+    /// I'm treating any server error as an Error regardless of the verb that caused it
+    pub fn from_http(method: &str, status_code: u16) -> Self {
+        if status_code >= 500 {
+            return Self::Error;
+        }
+
+        match method {
+            "GET" | "HEAD" | "OPTIONS" => Self::Read,
+            "POST" => Self::Execute,
+            "PUT" | "PATCH" => Self::Update,
+            "DELETE" => Self::Delete,
+            _ => Self::Execute,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Read => "read",
+            Self::Update => "update",
+            Self::Delete => "delete",
+            Self::Execute => "execute",
+            Self::Login => "login",
+            Self::Logout => "logout",
+            Self::Error => "error",
+        }
+    }
+}
+
+impl std::str::FromStr for AuditAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "create" => Ok(Self::Create),
+            "read" => Ok(Self::Read),
+            "update" => Ok(Self::Update),
+            "delete" => Ok(Self::Delete),
+            "execute" => Ok(Self::Execute),
+            "login" => Ok(Self::Login),
+            "logout" => Ok(Self::Logout),
+            "error" => Ok(Self::Error),
+            other => Err(format!("Unknown audit action: {}", other)),
+        }
+    }
+}
+
+/// Query parameters for the admin audit log listing
+/// I'm keeping filters optional so the default call simply pages through the newest entries
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditLogQuery {
+    pub page: Option<i32>,
+    pub per_page: Option<i32>,
+    pub user_id: Option<String>,
+    pub path: Option<String>,
+    pub method: Option<String>,
+    pub status_code: Option<i32>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// e.g. setting, cache, migration, api_key
+    pub entity_type: Option<String>,
+    pub entity_id: Option<String>,
+    pub action: Option<String>,
+}
+
+impl AuditLogQuery {
+    pub fn page(&self) -> i32 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    pub fn per_page(&self) -> i32 {
+        self.per_page.unwrap_or(50).clamp(1, 500)
+    }
+
+    pub fn offset(&self) -> i32 {
+        (self.page() - 1) * self.per_page()
+    }
+}
+
 /// Cache metadata for intelligent caching strategies
 /// I'm providing comprehensive cache metadata for optimization
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -288,6 +439,23 @@ mod tests {
         assert!(pagination.has_previous_page);
     }
 
+    #[test]
+    fn test_admin_event_records_actor_and_snapshots() {
+        let event = AuditLog::admin_event(
+            "admin:alice",
+            "setting",
+            "cache_default_ttl",
+            AuditAction::Update,
+            Some(serde_json::json!(3600)),
+            Some(serde_json::json!(600)),
+        );
+
+        assert_ne!(event.entity_type, HTTP_REQUEST_AUDIT_ENTITY);
+        assert_eq!(event.user_id.as_deref(), Some("admin:alice"));
+        assert_eq!(event.entity_id.as_deref(), Some("cache_default_ttl"));
+        assert_eq!(event.changes, Some(serde_json::json!({ "before": 3600, "after": 600 })));
+    }
+
     #[test]
     fn test_list_query_defaults() {
         let query = ListQuery {
@@ -313,4 +481,13 @@ mod tests {
         metadata.expires_at = Utc::now() - chrono::Duration::seconds(1);
         assert!(metadata.is_expired());
     }
+
+    #[test]
+    fn test_audit_action_mapping() {
+        assert_eq!(AuditAction::from_http("GET", 200), AuditAction::Read);
+        assert_eq!(AuditAction::from_http("DELETE", 204), AuditAction::Delete);
+        assert_eq!(AuditAction::from_http("POST", 503), AuditAction::Error);
+        assert_eq!("update".parse::<AuditAction>(), Ok(AuditAction::Update));
+        assert!("bogus".parse::<AuditAction>().is_err());
+    }
 }
@@ -0,0 +1,179 @@
+/*
+ * Notification template models: which alert text an operator may customise, and the variables a template can use.
+ * I'm keeping the built-in wording here beside the variables it reads, so a change to one is visibly a change to the other.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Rule name whose templates apply to every rule without its own
+pub const WILDCARD_RULE: &str = "*";
+pub const MAX_RULE_NAME_LEN: usize = 64;
+pub const MAX_TEMPLATE_LEN: usize = 16 * 1024;
+
+/// Where a rendered template ends up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TemplateChannel {
+    EmailSubject,
+    EmailBody,
+    /// The `message` field of alert.fired webhook payloads, ready for chat relays such as Slack
+    Webhook,
+}
+
+impl TemplateChannel {
+    pub const ALL: [TemplateChannel; 3] = [Self::EmailSubject, Self::EmailBody, Self::Webhook];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::EmailSubject => "email_subject",
+            Self::EmailBody => "email_body",
+            Self::Webhook => "webhook",
+        }
+    }
+
+    /// The wording used when no stored template applies
+    pub fn builtin_template(&self) -> &'static str {
+        match self {
+            Self::EmailSubject => "[alert] {{ rule }}{% if metric %}: {{ metric }} is {{ value }}{% endif %}",
+            Self::EmailBody => concat!(
+                "Alert fired: {{ rule }}\n\n",
+                "{% if metric %}{{ metric }} is {{ value }}{% if threshold %} (threshold {{ threshold }}){% endif %}\n\n{% endif %}",
+                "{% for line in details %}- {{ line }}\n{% endfor %}",
+                "\nDashboard: {{ dashboard_url }}\n",
+            ),
+            Self::Webhook => concat!(
+                "{{ rule }}{% if metric %}: {{ metric }} is {{ value }}{% if threshold %} (threshold {{ threshold }}){% endif %}{% endif %}",
+                "{% if details %} ({{ details | join(sep=\"; \") }}){% endif %}",
+            ),
+        }
+    }
+}
+
+impl fmt::Display for TemplateChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TemplateChannel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|channel| channel.as_str() == s)
+            .ok_or_else(|| format!("Unknown template channel '{}'; expected email_subject, email_body, or webhook", s))
+    }
+}
+
+/// A stored template override
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct NotificationTemplate {
+    pub rule: String,
+    pub channel: String,
+    pub template: String,
+    pub updated_by: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Body for creating or replacing a template
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotificationTemplateUpdate {
+    pub template: String,
+    /// Who is making the change, recorded alongside the authenticated principal
+    pub actor: Option<String>,
+}
+
+/// Body for rendering a draft template against sample values before saving it
+#[derive(Debug, Clone, Deserialize)]
+pub struct TemplatePreviewRequest {
+    pub channel: TemplateChannel,
+    pub template: String,
+    /// Values to render with in place of the sample alert
+    pub context: Option<AlertContext>,
+}
+
+/// The variables every alert template can use
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertContext {
+    /// The rule that fired, such as service_unhealthy
+    pub rule: String,
+    pub metric: Option<String>,
+    pub value: Option<serde_json::Value>,
+    pub threshold: Option<serde_json::Value>,
+    /// One line per failing check or breached limit
+    #[serde(default)]
+    pub details: Vec<String>,
+    pub dashboard_url: String,
+    pub fired_at: DateTime<Utc>,
+}
+
+impl AlertContext {
+    pub fn new(rule: &str, dashboard_url: &str) -> Self {
+        Self {
+            rule: rule.to_string(),
+            metric: None,
+            value: None,
+            threshold: None,
+            details: Vec::new(),
+            dashboard_url: dashboard_url.to_string(),
+            fired_at: Utc::now(),
+        }
+    }
+
+    pub fn with_metric<V: Into<serde_json::Value>>(mut self, metric: &str, value: V, threshold: Option<V>) -> Self {
+        self.metric = Some(metric.to_string());
+        self.value = Some(value.into());
+        self.threshold = threshold.map(Into::into);
+        self
+    }
+
+    pub fn with_details(mut self, details: Vec<String>) -> Self {
+        self.details = details;
+        self
+    }
+
+    /// Values templates are checked against before they're saved
+    pub fn sample(dashboard_url: &str) -> Self {
+        Self::new("cpu_usage_high", dashboard_url)
+            .with_metric("cpu_usage_percent", 93.5, Some(90.0))
+            .with_details(vec!["system: CPU usage 93.5%".to_string()])
+    }
+}
+
+/// One alert rendered for every channel
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RenderedAlert {
+    pub subject: String,
+    pub body: String,
+    pub message: String,
+}
+
+/// Rule names key stored templates and appear in URLs
+pub fn is_valid_rule_name(rule: &str) -> bool {
+    rule == WILDCARD_RULE
+        || (!rule.is_empty()
+            && rule.len() <= MAX_RULE_NAME_LEN
+            && rule.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channels_and_rule_names() {
+        for channel in TemplateChannel::ALL {
+            assert_eq!(channel.as_str().parse::<TemplateChannel>(), Ok(channel));
+        }
+        assert!("slack".parse::<TemplateChannel>().is_err());
+
+        assert!(is_valid_rule_name("service_unhealthy"));
+        assert!(is_valid_rule_name(WILDCARD_RULE));
+        assert!(!is_valid_rule_name("Service-Unhealthy"));
+        assert!(!is_valid_rule_name(""));
+    }
+}
@@ -0,0 +1,97 @@
+/*
+ * Request recording models: a sanitized request/response pair, its listing summary, and the result of replaying it.
+ * I'm keeping bodies as text with their byte counts alongside, so a body too large or too binary to keep still shows how big it was.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Value that replaces credentials in recorded headers, query strings, and JSON bodies
+pub const REDACTED: &str = "[redacted]";
+
+/// One recorded exchange, as stored in request_recordings or a recording file
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct RequestRecording {
+    pub id: Uuid,
+    pub method: String,
+    pub path: String,
+    pub query: Option<String>,
+    /// Slug of the tenant that served the request
+    pub tenant: String,
+    pub request_headers: serde_json::Value,
+    /// None when the body was binary or over REQUEST_RECORDING_MAX_BODY_SIZE
+    pub request_body: Option<String>,
+    pub request_body_bytes: Option<i64>,
+    pub status: i32,
+    pub response_headers: serde_json::Value,
+    pub response_body: Option<String>,
+    pub response_body_bytes: Option<i64>,
+    pub duration_ms: i64,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl RequestRecording {
+    pub fn summary(&self) -> RecordingSummary {
+        RecordingSummary {
+            id: self.id,
+            method: self.method.clone(),
+            path: self.path.clone(),
+            query: self.query.clone(),
+            tenant: self.tenant.clone(),
+            status: self.status,
+            duration_ms: self.duration_ms,
+            recorded_at: self.recorded_at,
+        }
+    }
+}
+
+/// A recording without its headers and bodies, for listings
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct RecordingSummary {
+    pub id: Uuid,
+    pub method: String,
+    pub path: String,
+    pub query: Option<String>,
+    pub tenant: String,
+    pub status: i32,
+    pub duration_ms: i64,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RecordingQuery {
+    pub limit: Option<usize>,
+    /// Only recordings whose path starts with this
+    pub path: Option<String>,
+}
+
+impl RecordingQuery {
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(50).clamp(1, 500)
+    }
+}
+
+/// Optional body for replaying a recording
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ReplayOptions {
+    /// Headers sent in place of the recorded ones, such as the Authorization header redaction removed
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Sent in place of the recorded request body
+    pub body: Option<serde_json::Value>,
+}
+
+/// What the current code answered to a recorded request, next to what was recorded
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayResult {
+    pub recording: RecordingSummary,
+    pub status: u16,
+    pub headers: serde_json::Value,
+    pub body: Option<String>,
+    pub duration_ms: i64,
+    pub status_matches: bool,
+    /// None when the recorded response body wasn't kept
+    pub body_matches: Option<bool>,
+}
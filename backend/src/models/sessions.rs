@@ -0,0 +1,40 @@
+/*
+ * Session history models: the renders an anonymous visitor made, newest first.
+ * I'm storing each entry's parameters in the shape the render endpoints accept so the frontend can replay one without translating it.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// One render recorded against a session
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct SessionHistoryEntry {
+    pub id: Uuid,
+    pub fractal_type: String,
+    /// Query parameters that reproduce this render, including width, height, and zoom
+    pub parameters: serde_json::Value,
+    pub width: i32,
+    pub height: i32,
+    pub zoom_level: f64,
+    pub computation_time_ms: i64,
+    pub renderer: String,
+    pub image_id: Option<String>,
+    pub image_url: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Response body for GET /api/sessions/history
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionHistory {
+    pub session_id: Uuid,
+    pub entries: Vec<SessionHistoryEntry>,
+    /// Most entries kept per session; older renders are dropped as new ones arrive
+    pub retained: u32,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SessionHistoryQuery {
+    pub limit: Option<i64>,
+    pub fractal_type: Option<String>,
+}
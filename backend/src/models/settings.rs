@@ -0,0 +1,61 @@
+/*
+ * Runtime setting models for the admin settings API and its change history.
+ * I'm reporting the environment value next to any override so operators can see what a reset would fall back to.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// One tunable setting as currently in effect
+#[derive(Debug, Clone, Serialize)]
+pub struct RuntimeSetting {
+    pub key: String,
+    pub value: serde_json::Value,
+    /// Value from environment variables and config files, used whenever no override is stored
+    pub default_value: serde_json::Value,
+    pub overridden: bool,
+    pub updated_by: Option<String>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// A recorded change to a runtime setting
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct SettingChange {
+    pub id: i64,
+    pub key: String,
+    /// None means the setting was at its environment value
+    pub old_value: Option<serde_json::Value>,
+    /// None means the override was removed
+    pub new_value: Option<serde_json::Value>,
+    pub changed_by: String,
+    pub reason: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// Body for overriding a setting
+#[derive(Debug, Clone, Deserialize)]
+pub struct SettingUpdate {
+    pub value: serde_json::Value,
+    /// Who is making the change, recorded alongside the authenticated principal
+    pub actor: Option<String>,
+    pub reason: Option<String>,
+}
+
+/// Optional body for removing an override
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SettingReset {
+    pub actor: Option<String>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SettingHistoryQuery {
+    pub key: Option<String>,
+    pub limit: Option<i64>,
+}
+
+impl SettingHistoryQuery {
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(50).clamp(1, 500)
+    }
+}
@@ -0,0 +1,172 @@
+/*
+ * Tenant models: the showcases hosted alongside the deployment's own, and the rules for naming and addressing them.
+ * I'm normalising hostnames on the way in so resolution is a plain map lookup on the request's Host header.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{
+    models::users::validate_username,
+    utils::error::{AppError, Result},
+};
+
+/// Slug of the implicit tenant built from GITHUB_USERNAME; it can't be stored or deleted
+pub const DEFAULT_TENANT_SLUG: &str = "default";
+/// Requests under /t/<slug>/ are served as that tenant with the prefix stripped
+pub const TENANT_PATH_PREFIX: &str = "/t/";
+pub const MAX_TENANT_SLUG_LEN: usize = 32;
+pub const MAX_DISPLAY_NAME_LEN: usize = 100;
+pub const MAX_HOSTNAMES_PER_TENANT: usize = 16;
+
+/// A stored tenant
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Tenant {
+    pub slug: String,
+    pub display_name: String,
+    pub github_username: String,
+    pub hostnames: Vec<String>,
+    /// Runtime-tunable settings overridden for this tenant, keyed like /api/admin/settings
+    pub settings: serde_json::Value,
+    pub enabled: bool,
+    pub updated_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Tenant {
+    /// The setting overrides as a map; anything but a JSON object counts as none
+    pub fn setting_overrides(&self) -> BTreeMap<String, serde_json::Value> {
+        match &self.settings {
+            serde_json::Value::Object(map) => map.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+            _ => BTreeMap::new(),
+        }
+    }
+}
+
+/// Body for creating or replacing a tenant
+#[derive(Debug, Clone, Deserialize)]
+pub struct TenantUpdate {
+    pub display_name: Option<String>,
+    pub github_username: String,
+    #[serde(default)]
+    pub hostnames: Vec<String>,
+    #[serde(default)]
+    pub settings: BTreeMap<String, serde_json::Value>,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    /// Who is making the change, recorded alongside the authenticated principal
+    pub actor: Option<String>,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+/// The tenant serving the current request, as shown to its visitors
+#[derive(Debug, Clone, Serialize)]
+pub struct TenantInfo {
+    pub slug: String,
+    pub display_name: String,
+    pub github_username: String,
+}
+
+/// Slugs appear in URLs and cache keys, so they stay short, lowercase, and free of separators
+pub fn is_valid_tenant_slug(slug: &str) -> bool {
+    !slug.is_empty()
+        && slug.len() <= MAX_TENANT_SLUG_LEN
+        && !slug.starts_with('-')
+        && slug.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+/// Lowercase a Host header value and drop any port; None for values that can't be a hostname
+pub fn normalize_host(host: &str) -> Option<String> {
+    let host = host.trim().trim_end_matches('.');
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) && !name.contains(':') => name,
+        _ => host,
+    };
+
+    let valid = !host.is_empty()
+        && host.len() <= 253
+        && host.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.');
+    valid.then(|| host.to_ascii_lowercase())
+}
+
+impl TenantUpdate {
+    /// Check the update and return its hostnames normalised and deduplicated
+    pub fn validate(&self, slug: &str) -> Result<Vec<String>> {
+        if !is_valid_tenant_slug(slug) {
+            return Err(AppError::ValidationError(format!(
+                "Tenant slugs are 1-{} lowercase letters, digits, or '-'", MAX_TENANT_SLUG_LEN
+            )));
+        }
+        if slug == DEFAULT_TENANT_SLUG {
+            return Err(AppError::ValidationError(
+                "The default tenant comes from GITHUB_USERNAME and can't be edited".to_string(),
+            ));
+        }
+        validate_username(&self.github_username)?;
+        if self.display_name.as_deref().is_some_and(|name| name.trim().is_empty() || name.len() > MAX_DISPLAY_NAME_LEN) {
+            return Err(AppError::ValidationError(format!(
+                "Display name must be between 1 and {} characters", MAX_DISPLAY_NAME_LEN
+            )));
+        }
+        if self.hostnames.len() > MAX_HOSTNAMES_PER_TENANT {
+            return Err(AppError::ValidationError(format!(
+                "A tenant can have at most {} hostnames", MAX_HOSTNAMES_PER_TENANT
+            )));
+        }
+
+        let mut hostnames = Vec::with_capacity(self.hostnames.len());
+        for raw in &self.hostnames {
+            let host = normalize_host(raw)
+                .ok_or_else(|| AppError::ValidationError(format!("'{}' is not a valid hostname", raw)))?;
+            if !hostnames.contains(&host) {
+                hostnames.push(host);
+            }
+        }
+        Ok(hostnames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(hostnames: &[&str]) -> TenantUpdate {
+        TenantUpdate {
+            display_name: Some("Octo Showcase".to_string()),
+            github_username: "octocat".to_string(),
+            hostnames: hostnames.iter().map(|h| h.to_string()).collect(),
+            settings: BTreeMap::new(),
+            enabled: true,
+            actor: None,
+        }
+    }
+
+    #[test]
+    fn test_hosts_are_normalised() {
+        assert_eq!(normalize_host("Octo.Example.com:8443").as_deref(), Some("octo.example.com"));
+        assert_eq!(normalize_host("octo.example.com.").as_deref(), Some("octo.example.com"));
+        assert_eq!(normalize_host("localhost").as_deref(), Some("localhost"));
+        assert_eq!(normalize_host("bad host"), None);
+        assert_eq!(normalize_host(""), None);
+
+        let hosts = update(&["A.example.com", "a.example.com:80", "b.example.com"]).validate("octo").unwrap();
+        assert_eq!(hosts, vec!["a.example.com", "b.example.com"]);
+    }
+
+    #[test]
+    fn test_slugs_and_reserved_names() {
+        assert!(is_valid_tenant_slug("octo-labs"));
+        assert!(!is_valid_tenant_slug("Octo"));
+        assert!(!is_valid_tenant_slug("-octo"));
+        assert!(!is_valid_tenant_slug("octo/labs"));
+
+        assert!(update(&[]).validate(DEFAULT_TENANT_SLUG).is_err());
+        assert!(update(&["not a host"]).validate("octo").is_err());
+        assert!(TenantUpdate { github_username: "-bad".to_string(), ..update(&[]) }.validate("octo").is_err());
+    }
+}
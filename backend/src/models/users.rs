@@ -0,0 +1,166 @@
+/*
+ * User account models: registered users, their personal API keys, and the daily quota on expensive renders.
+ * I'm returning a key's secret only in the response that created it; every later view shows the prefix and principal.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::utils::{
+    error::{AppError, Result},
+    Utils,
+};
+
+pub const API_KEY_PREFIX: &str = "dps_";
+pub const API_KEY_RANDOM_CHARS: usize = 40;
+/// Characters of a key kept in the clear so users can tell their keys apart
+pub const API_KEY_VISIBLE_CHARS: usize = 8;
+pub const MAX_USERNAME_LEN: usize = 39;
+pub const MAX_KEY_NAME_LEN: usize = 64;
+pub const MAX_ACTIVE_KEYS_PER_USER: i64 = 10;
+
+/// A registered account
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct User {
+    pub id: Uuid,
+    pub username: String,
+    pub email: Option<String>,
+    pub github_login: Option<String>,
+    /// Expensive renders allowed per UTC day
+    pub render_quota_daily: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A personal API key as listed back to its owner
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    /// The `key:` id this key appears under in usage and audit data
+    pub principal: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// Returned once when a key is created or rotated so the caller can store it
+#[derive(Debug, Clone, Serialize)]
+pub struct IssuedApiKey {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    pub key: String,
+}
+
+/// Response body for registration and GitHub sign-in
+#[derive(Debug, Clone, Serialize)]
+pub struct RegisteredUser {
+    pub user: User,
+    pub api_key: IssuedApiKey,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegisterUserRequest {
+    pub username: String,
+    pub email: Option<String>,
+}
+
+/// Body for POST /api/users/github; the token is used once to look the account up and never stored
+#[derive(Debug, Clone, Deserialize)]
+pub struct GitHubSignInRequest {
+    pub access_token: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateQuotaRequest {
+    pub render_quota_daily: i32,
+}
+
+/// Where a user stands against today's render quota
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderQuota {
+    pub limit: i32,
+    pub used: i32,
+    pub remaining: i32,
+    /// Renders of at least this many pixels count against the quota
+    pub expensive_render_pixels: u64,
+    pub resets_at: DateTime<Utc>,
+}
+
+/// Response body for GET /api/users/me
+#[derive(Debug, Clone, Serialize)]
+pub struct UserProfile {
+    #[serde(flatten)]
+    pub user: User,
+    pub quota: RenderQuota,
+}
+
+/// A fresh personal API key
+pub fn generate_api_key() -> String {
+    format!("{}{}", API_KEY_PREFIX, Utils::generate_random_string(API_KEY_RANDOM_CHARS))
+}
+
+/// The visible start of a key, stored alongside its hash
+pub fn api_key_prefix(key: &str) -> String {
+    key.chars().take(API_KEY_VISIBLE_CHARS).collect()
+}
+
+/// Usernames follow GitHub's rules so a linked account can keep its login
+pub fn validate_username(username: &str) -> Result<()> {
+    if username.is_empty() || username.len() > MAX_USERNAME_LEN {
+        return Err(AppError::ValidationError(format!(
+            "Username must be between 1 and {} characters",
+            MAX_USERNAME_LEN
+        )));
+    }
+    if username.starts_with('-') || username.ends_with('-') {
+        return Err(AppError::ValidationError("Username cannot start or end with a hyphen".to_string()));
+    }
+    if !username.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(AppError::ValidationError(
+            "Username may only contain letters, digits, and hyphens".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+pub fn validate_key_name(name: &str) -> Result<()> {
+    if name.trim().is_empty() || name.len() > MAX_KEY_NAME_LEN {
+        return Err(AppError::ValidationError(format!(
+            "Key name must be between 1 and {} characters",
+            MAX_KEY_NAME_LEN
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_keys_carry_prefix_and_visible_start() {
+        let key = generate_api_key();
+        assert!(key.starts_with(API_KEY_PREFIX));
+        assert_eq!(key.len(), API_KEY_PREFIX.len() + API_KEY_RANDOM_CHARS);
+        assert_eq!(api_key_prefix(&key), key[..API_KEY_VISIBLE_CHARS]);
+        assert_ne!(key, generate_api_key());
+    }
+
+    #[test]
+    fn test_username_rules() {
+        assert!(validate_username("octocat").is_ok());
+        assert!(validate_username("dark-performance").is_ok());
+        assert!(validate_username("").is_err());
+        assert!(validate_username("-leading").is_err());
+        assert!(validate_username("under_score").is_err());
+        assert!(validate_username(&"a".repeat(MAX_USERNAME_LEN + 1)).is_err());
+    }
+}
@@ -0,0 +1,198 @@
+/*
+ * Webhook models describing subscriptions, the events they listen for, and the delivery log.
+ * I'm keeping event names as dotted strings on the wire so receivers can route on them without knowing our enums.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::utils::error::{AppError, Result};
+
+pub const MAX_WEBHOOK_URL_LEN: usize = 2048;
+
+/// Application events that can be delivered to webhook subscribers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WebhookEvent {
+    #[serde(rename = "sync.completed")]
+    SyncCompleted,
+    #[serde(rename = "alert.fired")]
+    AlertFired,
+    #[serde(rename = "job.finished")]
+    JobFinished,
+}
+
+impl WebhookEvent {
+    pub const ALL: [WebhookEvent; 3] = [
+        WebhookEvent::SyncCompleted,
+        WebhookEvent::AlertFired,
+        WebhookEvent::JobFinished,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::SyncCompleted => "sync.completed",
+            WebhookEvent::AlertFired => "alert.fired",
+            WebhookEvent::JobFinished => "job.finished",
+        }
+    }
+}
+
+impl FromStr for WebhookEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|event| event.as_str() == s)
+            .ok_or_else(|| format!("Unknown webhook event: {}", s))
+    }
+}
+
+/// A registered webhook target
+/// I'm never serializing the signing secret back out after creation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookSubscription {
+    pub id: Uuid,
+    pub url: String,
+    pub event_types: Vec<WebhookEvent>,
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub active: bool,
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl WebhookSubscription {
+    pub fn subscribes_to(&self, event: WebhookEvent) -> bool {
+        self.active && self.event_types.contains(&event)
+    }
+}
+
+/// Body accepted when creating a subscription; a secret is generated when omitted
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    pub event_types: Vec<WebhookEvent>,
+    pub secret: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Partial update of a subscription
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpdateWebhookRequest {
+    pub url: Option<String>,
+    pub event_types: Option<Vec<WebhookEvent>>,
+    pub active: Option<bool>,
+    pub description: Option<String>,
+}
+
+/// Returned once on creation so the caller can store the signing secret
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatedWebhook {
+    #[serde(flatten)]
+    pub subscription: WebhookSubscription,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Delivered => "delivered",
+            DeliveryStatus::Failed => "failed",
+        }
+    }
+}
+
+impl FromStr for DeliveryStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(DeliveryStatus::Pending),
+            "delivered" => Ok(DeliveryStatus::Delivered),
+            "failed" => Ok(DeliveryStatus::Failed),
+            other => Err(format!("Unknown delivery status: {}", other)),
+        }
+    }
+}
+
+/// One attempt-tracked delivery of an event to a subscription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub subscription_id: Uuid,
+    pub event_type: WebhookEvent,
+    pub payload: serde_json::Value,
+    pub status: DeliveryStatus,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+/// Check a target URL before it is stored
+/// I'm only allowing http(s) and insisting on https unless plain http is explicitly permitted
+pub fn validate_webhook_url(url: &str, allow_http: bool) -> Result<()> {
+    if url.len() > MAX_WEBHOOK_URL_LEN {
+        return Err(AppError::ValidationError("Webhook URL is too long".to_string()));
+    }
+
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|e| AppError::ValidationError(format!("Invalid webhook URL: {}", e)))?;
+
+    match parsed.scheme() {
+        "https" => {}
+        "http" if allow_http => {}
+        "http" => return Err(AppError::ValidationError("Webhook URLs must use https".to_string())),
+        other => return Err(AppError::ValidationError(format!("Unsupported webhook URL scheme: {}", other))),
+    }
+
+    if parsed.host_str().is_none() {
+        return Err(AppError::ValidationError("Webhook URL must include a host".to_string()));
+    }
+
+    Ok(())
+}
+
+pub fn validate_event_types(event_types: &[WebhookEvent]) -> Result<()> {
+    if event_types.is_empty() {
+        return Err(AppError::ValidationError("At least one event type is required".to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_names_roundtrip() {
+        for event in WebhookEvent::ALL {
+            assert_eq!(event.as_str().parse::<WebhookEvent>().unwrap(), event);
+            assert_eq!(serde_json::to_value(event).unwrap(), event.as_str());
+        }
+        assert!("repo.deleted".parse::<WebhookEvent>().is_err());
+    }
+
+    #[test]
+    fn test_webhook_url_validation() {
+        assert!(validate_webhook_url("https://hooks.example.com/x", false).is_ok());
+        assert!(validate_webhook_url("http://hooks.example.com/x", false).is_err());
+        assert!(validate_webhook_url("http://localhost:9000/x", true).is_ok());
+        assert!(validate_webhook_url("ftp://example.com", true).is_err());
+        assert!(validate_webhook_url("not a url", true).is_err());
+    }
+}
@@ -0,0 +1,560 @@
+/*
+ * Administrative endpoints for operational insight, guarded by the admin bearer token.
+ * I'm exposing the persisted audit trail here so operators can investigate traffic without database access.
+ */
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Json as JsonResponse, Response},
+    Json,
+};
+use futures::StreamExt;
+use serde::Deserialize;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    database::{
+        backup::{self, ArchiveWriter, BackupTable, ImportMode, ImportReport},
+        notifications::{self, NotificationChannel},
+        MigrationManager, MigrationRunReport,
+    },
+    middleware::{
+        maintenance::{MaintenanceStatus, MaintenanceUpdate},
+        AdminAuth,
+    },
+    models::{
+        jobs::{DeadLetter, JobControl, JobRun, JobRunQuery, JobStatus, QueueStats},
+        logging::{LogFilterOverride, LogFilterStatus, LogFilterUpdate},
+        settings::{RuntimeSetting, SettingChange, SettingHistoryQuery, SettingReset, SettingUpdate},
+        ApiResponse, AuditAction, AuditLog, AuditLogQuery, Pagination,
+    },
+    services::audit_service::AuditScope,
+    utils::{circuit_breaker::CircuitSnapshot, config_schema, error::{AppError, Result}, Utils},
+    AppState,
+};
+
+/// List persisted audit log entries, newest first
+/// I'm supporting principal, path prefix, method, status, and time-window filters
+pub async fn list_audit_logs(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Query(params): Query<AuditLogQuery>,
+) -> Result<JsonResponse<ApiResponse<Vec<AuditLog>>>> {
+    let start_time = std::time::Instant::now();
+    info!("Listing audit logs with params: {:?}", params);
+
+    let (entries, total) = app_state.audit_service.list(&params, AuditScope::All).await?;
+    let pagination = Pagination::new(params.page(), params.per_page(), total as i32);
+
+    Ok(Json(
+        ApiResponse::new(entries)
+            .with_pagination(pagination)
+            .with_duration(start_time.elapsed().as_millis()),
+    ))
+}
+
+/// Administrative events only: setting, maintenance, and log level changes, cache flushes, migration runs, and key changes
+/// I'm taking the same filters as the request log; `user_id` matches the actor
+pub async fn list_audit_events(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Query(params): Query<AuditLogQuery>,
+) -> Result<JsonResponse<ApiResponse<Vec<AuditLog>>>> {
+    let start_time = std::time::Instant::now();
+
+    let (entries, total) = app_state.audit_service.list(&params, AuditScope::AdminEvents).await?;
+    let pagination = Pagination::new(params.page(), params.per_page(), total as i32);
+
+    Ok(Json(
+        ApiResponse::new(entries)
+            .with_pagination(pagination)
+            .with_duration(start_time.elapsed().as_millis()),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CacheFlushQuery {
+    /// Redis glob matched after the cache prefix; omitted flushes every cached response
+    pub pattern: Option<String>,
+}
+
+/// Delete cached responses so the next reads go back to Postgres and GitHub
+pub async fn flush_cache(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Query(params): Query<CacheFlushQuery>,
+) -> Result<JsonResponse<serde_json::Value>> {
+    let pattern = params.pattern.unwrap_or_else(|| "*".to_string());
+    let flushed = app_state.cache_service.flush_pattern(&pattern).await?;
+    info!("Flushed {} cache entries matching {}", flushed, pattern);
+
+    app_state.audit_service.record_event(AuditLog::admin_event(
+        "admin",
+        "cache",
+        pattern.clone(),
+        AuditAction::Delete,
+        None,
+        Some(serde_json::json!({ "flushed": flushed })),
+    )).await;
+
+    Ok(Json(serde_json::json!({ "pattern": pattern, "flushed": flushed })))
+}
+
+/// Current maintenance mode state
+pub async fn get_maintenance(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+) -> JsonResponse<MaintenanceStatus> {
+    Json(app_state.maintenance.status())
+}
+
+/// Toggle maintenance mode at runtime
+/// I'm keeping this route reachable during maintenance so operators can always switch it back off
+pub async fn set_maintenance(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Json(update): Json<MaintenanceUpdate>,
+) -> JsonResponse<MaintenanceStatus> {
+    info!("Maintenance mode update requested: {:?}", update);
+    let before = app_state.maintenance.status();
+    let status = app_state.maintenance.apply(update.clone());
+
+    app_state.audit_service.record_event(AuditLog::admin_event(
+        "admin",
+        "maintenance",
+        "maintenance",
+        AuditAction::Update,
+        serde_json::to_value(&before).ok(),
+        serde_json::to_value(&status).ok(),
+    )).await;
+
+    // Other instances pick the change up through the config_changed channel
+    let payload = serde_json::json!({ "kind": "maintenance", "update": update });
+    if let Err(e) = notifications::publish(&app_state.db_pool, NotificationChannel::ConfigChanged, &payload).await {
+        warn!("Failed to broadcast maintenance change: {}", e);
+    }
+
+    Json(status)
+}
+
+/// Applied and pending migrations for this binary's embedded series
+pub async fn get_migration_status(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+) -> Result<JsonResponse<serde_json::Value>> {
+    Ok(Json(MigrationManager::check_migration_status(&app_state.db_pool).await?))
+}
+
+/// Apply pending migrations so deploy tooling can roll the schema forward over HTTP
+pub async fn run_migrations(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+) -> Result<JsonResponse<ApiResponse<MigrationRunReport>>> {
+    info!("Migration run requested over the admin API");
+    let report = MigrationManager::run_pending(&app_state.db_pool).await?;
+    info!("Applied {} migrations, schema now at version {}", report.applied.len(), report.latest_version);
+    app_state.audit_service.record_event(migration_event("admin", &report)).await;
+
+    let duration_ms = report.duration_ms as u128;
+    Ok(Json(ApiResponse::new(report).with_duration(duration_ms)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BackupImportQuery {
+    #[serde(default)]
+    pub mode: ImportMode,
+}
+
+/// Stream a gzipped JSON archive of repositories, palettes, presets, and benchmark history
+/// I'm compressing row by row so the export never holds a whole table in memory
+pub async fn export_backup(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+) -> Response {
+    let exported_at = chrono::Utc::now();
+    info!("Streaming database backup archive");
+    let pool = app_state.db_pool.clone();
+
+    let chunks = async_stream::stream! {
+        let mut writer = ArchiveWriter::new();
+        yield writer.begin(exported_at);
+
+        for table in BackupTable::ALL {
+            yield writer.begin_table(table);
+
+            let sql = table.export_sql();
+            let mut rows = sqlx::query_scalar::<_, serde_json::Value>(&sql).fetch(&pool);
+            while let Some(row) = rows.next().await {
+                match row {
+                    Ok(row) => yield writer.row(&row),
+                    Err(e) => {
+                        // Ending the stream early leaves a truncated gzip the client will refuse to unpack
+                        warn!("Backup export failed while reading {}: {}", table.as_str(), e);
+                        yield Err(e.into());
+                        return;
+                    }
+                }
+            }
+
+            yield writer.end_table();
+        }
+
+        yield writer.finish();
+    };
+
+    let body = Body::from_stream(chunks.map(|chunk| chunk.map_err(std::io::Error::other)));
+    let disposition = format!(
+        "attachment; filename=\"showcase-backup-{}.json.gz\"",
+        exported_at.format("%Y%m%dT%H%M%SZ")
+    );
+
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, HeaderValue::from_static("application/gzip")),
+            (header::CACHE_CONTROL, HeaderValue::from_static("no-store")),
+            (
+                header::CONTENT_DISPOSITION,
+                HeaderValue::from_str(&disposition).unwrap_or_else(|_| HeaderValue::from_static("attachment")),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+/// Restore a backup archive produced by `export_backup`, gzipped or plain JSON
+/// I'm merging by default; `?mode=replace` clears the backed-up tables first
+/// The route lifts axum's default body limit, so the upload is capped here by BACKUP_MAX_ARCHIVE_SIZE instead
+pub async fn import_backup(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Query(params): Query<BackupImportQuery>,
+    body: Body,
+) -> Result<JsonResponse<ApiResponse<ImportReport>>> {
+    let start_time = std::time::Instant::now();
+    let max_bytes = app_state.config.backup_max_archive_bytes;
+    let body = axum::body::to_bytes(body, usize::try_from(max_bytes).unwrap_or(usize::MAX))
+        .await
+        .map_err(|_| AppError::bad_request(format!("Backup archive is larger than {} bytes", max_bytes)))?;
+    info!("Importing database backup ({} bytes, {:?})", body.len(), params.mode);
+
+    let archive = backup::decode_archive(&body, max_bytes)?;
+    let report = backup::import_archive(&app_state.db_pool, &archive, params.mode).await?;
+
+    Ok(Json(ApiResponse::new(report).with_duration(start_time.elapsed().as_millis())))
+}
+
+/// JSON Schema for the configuration, for deployment tooling to validate against and render docs from
+pub async fn get_config_schema(_admin: AdminAuth) -> Result<JsonResponse<serde_json::Value>> {
+    Ok(Json(config_schema::config_schema()?))
+}
+
+/// Every runtime-tunable setting with its effective value and environment default
+pub async fn list_settings(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+) -> Result<JsonResponse<ApiResponse<Vec<RuntimeSetting>>>> {
+    Ok(Json(ApiResponse::new(app_state.settings_service.list().await?)))
+}
+
+/// Override one setting; it takes effect immediately on every instance
+pub async fn update_setting(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path(key): Path<String>,
+    Json(update): Json<SettingUpdate>,
+) -> Result<JsonResponse<ApiResponse<RuntimeSetting>>> {
+    let changed_by = setting_actor(update.actor.as_deref());
+    let before = app_state.settings_service.get(&key).await.ok();
+    let setting = app_state
+        .settings_service
+        .set(&key, update.value, &changed_by, update.reason.as_deref())
+        .await?;
+    record_setting_event(&app_state, &changed_by, &key, before, &setting).await;
+
+    broadcast_settings_change(&app_state, &key).await;
+    Ok(Json(ApiResponse::new(setting)))
+}
+
+/// Remove an override so the environment value applies again
+pub async fn reset_setting(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path(key): Path<String>,
+    body: Option<Json<SettingReset>>,
+) -> Result<JsonResponse<ApiResponse<RuntimeSetting>>> {
+    let reset = body.map(|Json(reset)| reset).unwrap_or_default();
+    let changed_by = setting_actor(reset.actor.as_deref());
+    let before = app_state.settings_service.get(&key).await.ok();
+    let setting = app_state
+        .settings_service
+        .reset(&key, &changed_by, reset.reason.as_deref())
+        .await?;
+    record_setting_event(&app_state, &changed_by, &key, before, &setting).await;
+
+    broadcast_settings_change(&app_state, &key).await;
+    Ok(Json(ApiResponse::new(setting)))
+}
+
+/// Who changed which setting and when, newest first
+pub async fn list_setting_history(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Query(params): Query<SettingHistoryQuery>,
+) -> Result<JsonResponse<ApiResponse<Vec<SettingChange>>>> {
+    Ok(Json(ApiResponse::new(app_state.settings_service.history(&params).await?)))
+}
+
+/// The log filter in effect on this instance, with any temporary overrides
+pub async fn get_log_filters(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+) -> JsonResponse<LogFilterStatus> {
+    Json(app_state.log_control.status())
+}
+
+/// Set one target's log level, optionally for a limited time
+/// I'm applying the override locally before broadcasting so a bad directive is rejected here rather than on every instance
+pub async fn set_log_filter(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path(target): Path<String>,
+    Json(update): Json<LogFilterUpdate>,
+) -> Result<JsonResponse<LogFilterStatus>> {
+    let expires_at = match update.duration.as_deref() {
+        Some(duration) => {
+            let duration = chrono::Duration::from_std(Utils::parse_duration(duration)?)
+                .map_err(|e| AppError::ValidationError(format!("Invalid duration: {}", e)))?;
+            Some(chrono::Utc::now() + duration)
+        }
+        None => None,
+    };
+    let filter = LogFilterOverride {
+        target: target.clone(),
+        level: update.level.to_lowercase(),
+        expires_at,
+        set_by: setting_actor(update.actor.as_deref()),
+    };
+
+    let before = current_log_override(&app_state, &target);
+    let status = app_state.log_control.set_override(filter.clone())?;
+    info!("Log level for {} set to {} by {} (expires: {:?})", target, filter.level, filter.set_by, expires_at);
+
+    app_state.audit_service.record_event(AuditLog::admin_event(
+        &filter.set_by,
+        "log_filter",
+        target.clone(),
+        AuditAction::Update,
+        before,
+        serde_json::to_value(&filter).ok(),
+    )).await;
+
+    broadcast_log_filter_change(&app_state, &target, Some(&filter)).await;
+    Ok(Json(status))
+}
+
+/// Remove a target's override so the log_level setting applies to it again
+pub async fn clear_log_filter(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path(target): Path<String>,
+) -> Result<JsonResponse<LogFilterStatus>> {
+    let before = current_log_override(&app_state, &target);
+    let status = app_state.log_control.clear_override(&target)?;
+    info!("Log level override for {} removed", target);
+
+    app_state.audit_service.record_event(AuditLog::admin_event(
+        "admin",
+        "log_filter",
+        target.clone(),
+        AuditAction::Delete,
+        before,
+        None,
+    )).await;
+
+    broadcast_log_filter_change(&app_state, &target, None).await;
+    Ok(Json(status))
+}
+
+fn current_log_override(app_state: &AppState, target: &str) -> Option<serde_json::Value> {
+    app_state
+        .log_control
+        .status()
+        .overrides
+        .into_iter()
+        .find(|filter| filter.target == target)
+        .and_then(|filter| serde_json::to_value(filter).ok())
+}
+
+async fn record_setting_event(
+    app_state: &AppState,
+    changed_by: &str,
+    key: &str,
+    before: Option<RuntimeSetting>,
+    after: &RuntimeSetting,
+) {
+    app_state.audit_service.record_event(AuditLog::admin_event(
+        changed_by,
+        "setting",
+        key,
+        AuditAction::Update,
+        before.and_then(|setting| serde_json::to_value(setting).ok()),
+        serde_json::to_value(after).ok(),
+    )).await;
+}
+
+/// Audit row for a migration run, shared with the showcase-admin migrate command
+pub(crate) fn migration_event(actor: &str, report: &MigrationRunReport) -> AuditLog {
+    AuditLog::admin_event(
+        actor,
+        "migration",
+        report.latest_version.to_string(),
+        AuditAction::Execute,
+        None,
+        serde_json::to_value(report).ok(),
+    )
+}
+
+async fn broadcast_log_filter_change(app_state: &AppState, target: &str, filter: Option<&LogFilterOverride>) {
+    let payload = serde_json::json!({ "kind": "log_filter", "target": target, "filter": filter });
+    if let Err(e) = notifications::publish(&app_state.db_pool, NotificationChannel::ConfigChanged, &payload).await {
+        warn!("Failed to broadcast log filter change: {}", e);
+    }
+}
+
+/// Every scheduled job with its next run and most recent outcome
+pub async fn list_jobs(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+) -> Result<JsonResponse<ApiResponse<Vec<JobStatus>>>> {
+    Ok(Json(ApiResponse::new(app_state.scheduler.list().await?)))
+}
+
+/// Run a job now on this instance; the run is recorded in its history like a scheduled one
+pub async fn run_job(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<(StatusCode, JsonResponse<ApiResponse<JobStatus>>)> {
+    app_state.scheduler.trigger(&name)?;
+    info!("Job {} triggered over the admin API", name);
+    Ok((StatusCode::ACCEPTED, Json(ApiResponse::new(app_state.scheduler.get(&name).await?))))
+}
+
+pub async fn pause_job(
+    admin: AdminAuth,
+    state: State<AppState>,
+    name: Path<String>,
+    body: Option<Json<JobControl>>,
+) -> Result<JsonResponse<ApiResponse<JobStatus>>> {
+    set_job_paused(admin, state, name, body, true).await
+}
+
+pub async fn resume_job(
+    admin: AdminAuth,
+    state: State<AppState>,
+    name: Path<String>,
+    body: Option<Json<JobControl>>,
+) -> Result<JsonResponse<ApiResponse<JobStatus>>> {
+    set_job_paused(admin, state, name, body, false).await
+}
+
+async fn set_job_paused(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path(name): Path<String>,
+    body: Option<Json<JobControl>>,
+    paused: bool,
+) -> Result<JsonResponse<ApiResponse<JobStatus>>> {
+    let control = body.map(|Json(control)| control).unwrap_or_default();
+    app_state
+        .scheduler
+        .set_paused(&name, paused, &setting_actor(control.actor.as_deref()))
+        .await?;
+
+    // Every instance runs the scheduler, so each one reloads the paused flags
+    let payload = serde_json::json!({ "kind": "jobs", "name": name, "paused": paused });
+    if let Err(e) = notifications::publish(&app_state.db_pool, NotificationChannel::ConfigChanged, &payload).await {
+        warn!("Failed to broadcast job state change: {}", e);
+    }
+    Ok(Json(ApiResponse::new(app_state.scheduler.get(&name).await?)))
+}
+
+/// A job's recorded runs, newest first
+pub async fn list_job_runs(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path(name): Path<String>,
+    Query(params): Query<JobRunQuery>,
+) -> Result<JsonResponse<ApiResponse<Vec<JobRun>>>> {
+    Ok(Json(ApiResponse::new(app_state.scheduler.history(&name, params.limit()).await?)))
+}
+
+/// Depth of every task queue with workers on this instance
+pub async fn list_queues(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+) -> Result<JsonResponse<ApiResponse<Vec<QueueStats>>>> {
+    let mut stats = Vec::new();
+    for queue in app_state.task_queue.queue_names() {
+        stats.push(app_state.task_queue.stats(&queue).await?);
+    }
+    Ok(Json(ApiResponse::new(stats)))
+}
+
+/// Where each dependency's circuit breaker stands on this instance
+pub async fn list_circuit_breakers(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+) -> Result<JsonResponse<ApiResponse<Vec<CircuitSnapshot>>>> {
+    Ok(Json(ApiResponse::new(app_state.circuit_breakers.snapshots())))
+}
+
+/// Tasks that used up their attempts, most recent first
+pub async fn list_dead_letters(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path(queue): Path<String>,
+    Query(params): Query<JobRunQuery>,
+) -> Result<JsonResponse<ApiResponse<Vec<DeadLetter>>>> {
+    Ok(Json(ApiResponse::new(app_state.task_queue.dead_letters(&queue, params.limit() as isize).await?)))
+}
+
+pub async fn retry_dead_letter(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path((queue, id)): Path<(String, Uuid)>,
+) -> Result<StatusCode> {
+    app_state.task_queue.retry_dead_letter(&queue, id).await?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+pub async fn discard_dead_letter(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path((queue, id)): Path<(String, Uuid)>,
+) -> Result<StatusCode> {
+    app_state.task_queue.discard_dead_letter(&queue, id).await?;
+    info!("Discarded dead-lettered task {} on {}", id, queue);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The admin token is shared, so I'm recording the caller's self-reported name next to it
+pub(crate) fn setting_actor(actor: Option<&str>) -> String {
+    match actor.map(str::trim).filter(|a| !a.is_empty()) {
+        Some(actor) => format!("admin:{}", actor),
+        None => "admin".to_string(),
+    }
+}
+
+async fn broadcast_settings_change(app_state: &AppState, key: &str) {
+    // Other instances reload their overrides from the table when they see this
+    let payload = serde_json::json!({ "kind": "settings", "key": key });
+    if let Err(e) = notifications::publish(&app_state.db_pool, NotificationChannel::ConfigChanged, &payload).await {
+        warn!("Failed to broadcast settings change: {}", e);
+    }
+}
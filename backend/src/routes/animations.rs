@@ -0,0 +1,131 @@
+/*
+ * Zoom animation endpoints rendering a fly-in between two views as an animated GIF or MP4.
+ * I'm rendering small animations inside the request and queueing anything larger as a job the client polls, so a long render never holds a connection open.
+ */
+
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures::StreamExt;
+use std::time::Instant;
+use tracing::info;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    middleware::{rate_limit::RenderClient, tenant::CurrentTenant, users::UserAuth},
+    models::{
+        animations::{AnimationJob, AnimationRequest},
+        ApiResponse,
+    },
+    routes::fractals::{charge_cost_estimate, engine_fractal_type, COMPUTATION_TIME_HEADER},
+    services::{
+        animation_service::{check_format, AnimationSpec},
+        cost::CostEstimate,
+    },
+    utils::error::{AppError, Result},
+    AppState,
+};
+
+/// Render a zoom animation, inline when it fits ANIMATION_INLINE_MAX_PIXELS and as a queued job otherwise
+pub async fn create_animation(
+    State(app_state): State<AppState>,
+    user: Option<UserAuth>,
+    client: RenderClient,
+    tenant: CurrentTenant,
+    Json(request): Json<AnimationRequest>,
+) -> Result<Response> {
+    request.validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+    check_format(request.format)?;
+    request.options.refuse("Animations", &["antialiasing", "threads", "output_format"])?;
+    if let Some(trap) = &request.options.orbit_trap {
+        trap.validate()?;
+    }
+
+    let limits = tenant.config(&app_state.live_config);
+    if request.frames > limits.animation_max_frames {
+        return Err(AppError::ValidationError(format!("Animations may have at most {} frames", limits.animation_max_frames)));
+    }
+    if request.width > limits.fractal_max_width || request.height > limits.fractal_max_height {
+        return Err(AppError::ValidationError(format!(
+            "Frames may be at most {}x{}", limits.fractal_max_width, limits.fractal_max_height
+        )));
+    }
+    if request.max_iterations > limits.fractal_max_iterations || request.start_zoom.max(request.end_zoom()) > limits.fractal_max_zoom {
+        return Err(AppError::ValidationError(format!(
+            "Animations may use at most {} iterations and zoom {}", limits.fractal_max_iterations, limits.fractal_max_zoom
+        )));
+    }
+
+    let fractal_type = engine_fractal_type(&limits, &request.fractal_type)?;
+    let palette = app_state
+        .palette_service
+        .resolve_palette(&tenant.slug, request.options.palette_id, request.options.palette.as_deref(), request.options.gradient.as_deref())
+        .await?;
+    let background = request.background || request.total_pixels() > limits.animation_inline_max_pixels;
+    let format = request.format;
+    let spec = AnimationSpec { request, fractal_type, palette };
+
+    if let Some(UserAuth(user)) = &user {
+        app_state.user_service.charge_render(user, spec.request.total_pixels()).await?;
+    }
+    // Queued animations are let past the per-request cap like queued renders, but every frame still comes out of the client's budget
+    if let Some(frame) = spec.frame_requests().first() {
+        charge_cost_estimate(&app_state, &client, CostEstimate::of_frames(frame, spec.request.frames), background, None).await?;
+    }
+
+    if background {
+        let job = app_state.animation_service.submit(spec).await?;
+        return Ok((StatusCode::ACCEPTED, Json(ApiResponse::new(job))).into_response());
+    }
+
+    let frames = spec.request.frames;
+    let started = Instant::now();
+    let encoded = app_state.animation_service.render(spec).await?;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    info!("Rendered {}-frame {} animation inline in {}ms ({} bytes)", frames, format.as_str(), elapsed_ms, encoded.len());
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, HeaderValue::from_static(format.content_type())),
+            (HeaderName::from_static(COMPUTATION_TIME_HEADER), HeaderValue::from(elapsed_ms)),
+        ],
+        encoded,
+    )
+        .into_response())
+}
+
+/// Poll an animation job
+pub async fn get_animation_job(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<AnimationJob>>> {
+    Ok(Json(ApiResponse::new(app_state.animation_service.get(id).await?)))
+}
+
+/// Download a finished animation job's file
+pub async fn download_animation(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Response> {
+    let (format, chunks) = app_state.animation_service.download(id).await?;
+    let body = Body::from_stream(chunks.map(|chunk| chunk.map_err(std::io::Error::other)));
+    let disposition = HeaderValue::from_str(&format!("inline; filename=\"animation-{}.{}\"", id, format.as_str()))
+        .unwrap_or_else(|_| HeaderValue::from_static("inline"));
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, HeaderValue::from_static(format.content_type())),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response())
+}
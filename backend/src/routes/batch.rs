@@ -0,0 +1,214 @@
+/*
+ * Batch endpoint executing several API sub-requests in one round trip.
+ * I'm dispatching sub-requests in-process through the router and its policy middleware, so every sub-request is rate limited, metered, and audited exactly as it would be over HTTP.
+ */
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method, Request},
+    response::Json as JsonResponse,
+    Json,
+};
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use tower::ServiceExt;
+use tracing::{debug, info};
+
+use crate::{
+    middleware::{session::SESSION_HEADER, CurrentTenant},
+    utils::error::{AppError, Result},
+    AppState,
+};
+
+pub const MAX_BATCH_SUB_REQUESTS: usize = 20;
+pub const BATCH_CONCURRENCY: usize = 4;
+const MAX_SUB_RESPONSE_BYTES: usize = 10 * 1024 * 1024;
+
+/// Headers copied from the outer request so sub-requests carry the caller's credentials, address, and session
+const FORWARDED_HEADERS: [&str; 6] = ["authorization", "x-api-key", "x-forwarded-for", "x-real-ip", "cookie", SESSION_HEADER];
+
+/// The only headers a sub-request may set itself; identity always comes from the outer request
+const SUB_REQUEST_HEADERS: [&str; 2] = ["content-type", "accept"];
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchSubRequest {
+    /// Optional caller-chosen id echoed back on the matching response
+    pub id: Option<String>,
+    pub method: String,
+    pub path: String,
+    pub body: Option<serde_json::Value>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchSubResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub status: u16,
+    pub body: serde_json::Value,
+}
+
+/// Execute an array of sub-requests with bounded concurrency, answering in input order
+/// I'm returning per-item statuses with an overall 200 so one failing call doesn't hide the others
+pub async fn execute_batch(
+    State(app_state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    tenant: CurrentTenant,
+    headers: HeaderMap,
+    Json(requests): Json<Vec<BatchSubRequest>>,
+) -> Result<JsonResponse<Vec<BatchSubResponse>>> {
+    if requests.is_empty() {
+        return Err(AppError::bad_request("Batch must contain at least one request"));
+    }
+    if requests.len() > MAX_BATCH_SUB_REQUESTS {
+        return Err(AppError::ValidationError(format!(
+            "Batches are limited to {} requests", MAX_BATCH_SUB_REQUESTS
+        )));
+    }
+
+    info!("Executing batch of {} sub-requests", requests.len());
+
+    let router = super::create_policy_router(&app_state).with_state(app_state);
+    let forwarded: Vec<(HeaderName, HeaderValue)> = FORWARDED_HEADERS
+        .iter()
+        .filter_map(|name| headers.get(*name).map(|value| (HeaderName::from_static(name), value.clone())))
+        .collect();
+
+    let responses = stream::iter(requests)
+        .map(|sub| {
+            let router = router.clone();
+            let forwarded = forwarded.clone();
+            let tenant = tenant.clone();
+            async move {
+                let id = sub.id.clone();
+                match dispatch(router, sub, &forwarded, connect_info, tenant).await {
+                    Ok(response) => response,
+                    Err(e) => BatchSubResponse {
+                        id,
+                        status: e.status_code().as_u16(),
+                        body: serde_json::json!({ "error": e.user_message(), "code": e.error_code() }),
+                    },
+                }
+            }
+        })
+        .buffered(BATCH_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+
+    Ok(Json(responses))
+}
+
+async fn dispatch(
+    router: axum::Router,
+    sub: BatchSubRequest,
+    forwarded: &[(HeaderName, HeaderValue)],
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    tenant: CurrentTenant,
+) -> Result<BatchSubResponse> {
+    let method: Method = sub.method.to_uppercase().parse()
+        .map_err(|_| AppError::bad_request(format!("Invalid method: {}", sub.method)))?;
+    validate_sub_path(&sub.path)?;
+
+    debug!("Batch dispatching {} {}", method, sub.path);
+
+    let mut builder = Request::builder().method(method).uri(&sub.path);
+    for (name, value) in forwarded {
+        builder = builder.header(name, value);
+    }
+    for (name, value) in &sub.headers {
+        if is_sub_request_header(name) {
+            builder = builder.header(name.as_str(), value.as_str());
+        } else {
+            debug!("Batch dropping sub-request header {}", name);
+        }
+    }
+
+    let body = match &sub.body {
+        Some(value) => {
+            builder = builder.header(header::CONTENT_TYPE, "application/json");
+            Body::from(serde_json::to_vec(value)?)
+        }
+        None => Body::empty(),
+    };
+
+    let mut request = builder
+        .body(body)
+        .map_err(|e| AppError::bad_request(format!("Invalid sub-request: {}", e)))?;
+    if let Some(info) = connect_info {
+        request.extensions_mut().insert(info);
+    }
+    // Sub-paths skip tenant resolution, so they run as the tenant the batch itself was sent to
+    request.extensions_mut().insert(tenant);
+
+    let response = router
+        .oneshot(request)
+        .await
+        .map_err(|e| AppError::internal(format!("Sub-request failed: {}", e)))?;
+
+    let status = response.status().as_u16();
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+
+    let bytes = axum::body::to_bytes(response.into_body(), MAX_SUB_RESPONSE_BYTES)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to read sub-response: {}", e)))?;
+
+    let body = if bytes.is_empty() {
+        serde_json::Value::Null
+    } else if is_json {
+        serde_json::from_slice(&bytes)?
+    } else {
+        serde_json::Value::String(String::from_utf8_lossy(&bytes).into_owned())
+    };
+
+    Ok(BatchSubResponse { id: sub.id, status, body })
+}
+
+fn is_sub_request_header(name: &str) -> bool {
+    SUB_REQUEST_HEADERS.iter().any(|allowed| name.eq_ignore_ascii_case(allowed))
+}
+
+/// Sub-request paths must be absolute API paths and may not recurse into the batch endpoint
+fn validate_sub_path(path: &str) -> Result<()> {
+    if !path.starts_with('/') || path.starts_with("//") {
+        return Err(AppError::bad_request(format!("Sub-request path must be absolute: {}", path)));
+    }
+
+    let route = path.split('?').next().unwrap_or(path).trim_end_matches('/');
+    if route.ends_with("/batch") && !route.contains("/fractals/") {
+        return Err(AppError::bad_request("Batch requests cannot be nested"));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sub_path_validation() {
+        assert!(validate_sub_path("/api/github/repos?page=2").is_ok());
+        assert!(validate_sub_path("/api/fractals/batch").is_ok());
+        assert!(validate_sub_path("/api/batch").is_err());
+        assert!(validate_sub_path("/v1/api/batch/").is_err());
+        assert!(validate_sub_path("https://example.com/api").is_err());
+        assert!(validate_sub_path("//example.com/api").is_err());
+    }
+
+    #[test]
+    fn test_sub_requests_cannot_set_their_own_identity() {
+        assert!(is_sub_request_header("Content-Type"));
+        assert!(is_sub_request_header("accept"));
+        for name in ["Authorization", "x-api-key", "X-Forwarded-For", "x-real-ip", "cookie", SESSION_HEADER] {
+            assert!(!is_sub_request_header(name), "{} must come from the outer request", name);
+        }
+    }
+}
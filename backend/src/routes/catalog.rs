@@ -0,0 +1,69 @@
+/*
+ * Preset catalog endpoints: anyone can browse the exploration targets, and edits need the admin token.
+ * I'm keeping writes admin-only because the catalog is curated; personal bookmarks belong in uploaded presets instead.
+ */
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    middleware::AdminAuth,
+    models::{
+        catalog::{CatalogPreset, CatalogPresetInput, CatalogQuery},
+        ApiResponse,
+    },
+    utils::error::{AppError, Result},
+    AppState,
+};
+
+/// Catalog entries, optionally narrowed by kind, fractal type, or tag
+pub async fn list_catalog_presets(
+    State(app_state): State<AppState>,
+    Query(query): Query<CatalogQuery>,
+) -> Result<Json<ApiResponse<Vec<CatalogPreset>>>> {
+    Ok(Json(ApiResponse::new(app_state.catalog_service.list(&query).await?)))
+}
+
+pub async fn get_catalog_preset(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<CatalogPreset>>> {
+    Ok(Json(ApiResponse::new(app_state.catalog_service.get(id).await?)))
+}
+
+pub async fn create_catalog_preset(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Json(input): Json<CatalogPresetInput>,
+) -> Result<(StatusCode, Json<ApiResponse<CatalogPreset>>)> {
+    input.validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+    let preset = app_state.catalog_service.create(&input).await?;
+    Ok((StatusCode::CREATED, Json(ApiResponse::new(preset))))
+}
+
+/// Replace a catalog entry
+pub async fn update_catalog_preset(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(input): Json<CatalogPresetInput>,
+) -> Result<Json<ApiResponse<CatalogPreset>>> {
+    input.validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+    Ok(Json(ApiResponse::new(app_state.catalog_service.update(id, &input).await?)))
+}
+
+pub async fn delete_catalog_preset(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode> {
+    app_state.catalog_service.delete(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,243 @@
+/*
+ * Interactive fractal exploration over one WebSocket: the client moves the viewport and the server streams each view back region by region.
+ * I'm rendering only the newest viewport and dropping a view's remaining regions once a newer one arrives, so a fast pan never queues stale frames.
+ * A superseded view's region stops mid-render too, through a cancel token, rather than finishing pixels nobody will see.
+ */
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+};
+use futures::{SinkExt, StreamExt};
+use tokio::sync::{mpsc, watch};
+use tracing::{debug, info, warn, Span};
+
+use crate::{
+    middleware::{rate_limit::RenderClient, tenant::CurrentTenant, users::UserAuth},
+    models::explorer::{encode_region, ExplorerEvent, ViewportUpdate},
+    services::{
+        cancel::CancelToken,
+        fractal_service::{FractalRequest, FractalType},
+        renderers::RendererKind,
+    },
+    utils::error::{AppError, Result},
+    AppState,
+};
+
+use super::fractals::{antialiasing_limits, charge_render_cost, charge_render_quota, engine_fractal_type};
+
+/// Views are split into a grid of this many regions a side, and sized to a multiple of it
+const REGION_GRID: u32 = 4;
+
+/// Messages queued for a client before rendering waits for it to read them
+const OUTBOUND_BUFFER: usize = 8;
+
+/// Largest viewport update accepted; real ones are a few hundred bytes
+const MAX_MESSAGE_BYTES: usize = 16 * 1024;
+
+/// Latest merged viewport and its sequence number, as handed from the reader to the render loop
+type LatestView = Option<(u64, ViewportUpdate)>;
+
+/// Upgrade to the exploration socket; API keys are checked once here and every frame is charged to the key's quota and cost budget
+pub async fn fractal_socket(
+    ws: WebSocketUpgrade,
+    State(app_state): State<AppState>,
+    user: Option<UserAuth>,
+    tenant: CurrentTenant,
+    client: RenderClient,
+) -> Response {
+    let session = ExplorerSession { app_state, user, tenant, client };
+    ws.max_message_size(MAX_MESSAGE_BYTES).on_upgrade(move |socket| session.run(socket))
+}
+
+/// One client's exploration: who it renders for and under which tenant's limits
+pub struct ExplorerSession {
+    app_state: AppState,
+    user: Option<UserAuth>,
+    tenant: CurrentTenant,
+    client: RenderClient,
+}
+
+impl ExplorerSession {
+    /// Read viewport updates until the client leaves, while the render loop draws the newest and a writer drains the outbound queue
+    async fn run(self, socket: WebSocket) {
+        let (mut sink, mut stream) = socket.split();
+        let (outbound, mut queued) = mpsc::channel::<Message>(OUTBOUND_BUFFER);
+        let writer = tokio::spawn(async move {
+            while let Some(message) = queued.recv().await {
+                if sink.send(message).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let (views, latest) = watch::channel::<LatestView>(None);
+        let renderer = tokio::spawn(self.render_views(latest, outbound.clone()));
+        info!("Fractal explorer connected");
+
+        let mut view = ViewportUpdate::default();
+        let mut received = 0u64;
+        while let Some(Ok(message)) = stream.next().await {
+            match message {
+                Message::Text(text) => match serde_json::from_str::<ViewportUpdate>(&text) {
+                    Ok(update) => {
+                        received += 1;
+                        let sequence = update.sequence.unwrap_or(received);
+                        view.merge(update);
+                        views.send_replace(Some((sequence, view.clone())));
+                    }
+                    Err(e) => {
+                        // Dropped rather than queued when the client is this far behind
+                        let error = ExplorerEvent::Error { sequence: None, message: format!("Invalid viewport update: {}", e) };
+                        let _ = outbound.try_send(event_message(&error));
+                    }
+                },
+                Message::Close(_) => break,
+                // axum answers pings itself, and binary messages carry nothing for the server
+                _ => {}
+            }
+        }
+
+        renderer.abort();
+        writer.abort();
+        info!("Fractal explorer disconnected after {} viewport updates", received);
+    }
+
+    /// Draw each newest view in turn; views replaced while one was drawing are never started
+    async fn render_views(self, mut latest: watch::Receiver<LatestView>, outbound: mpsc::Sender<Message>) {
+        while latest.changed().await.is_ok() {
+            let Some((sequence, view)) = latest.borrow_and_update().clone() else {
+                continue;
+            };
+            if let Err(e) = self.render_view(sequence, &view, &latest, &outbound).await {
+                let error = ExplorerEvent::Error { sequence: Some(sequence), message: e.to_string() };
+                if outbound.send(event_message(&error)).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    async fn render_view(
+        &self,
+        sequence: u64,
+        view: &ViewportUpdate,
+        latest: &watch::Receiver<LatestView>,
+        outbound: &mpsc::Sender<Message>,
+    ) -> Result<()> {
+        let request = self.request_for(view).await?;
+        charge_render_quota(&self.app_state, self.user.as_ref(), &request).await?;
+        charge_render_cost(&self.app_state, &self.client, &request, false).await?;
+
+        let type_name = request.fractal_type.name();
+        let regions = request.regions(REGION_GRID);
+        let started = ExplorerEvent::FrameStarted {
+            sequence,
+            width: request.width,
+            height: request.height,
+            fractal_type: type_name,
+            regions: regions.len(),
+        };
+        if outbound.send(event_message(&started)).await.is_err() {
+            return Ok(());
+        }
+
+        // A clone shares the seen version, so waiting on it spots newer views without consuming them
+        let mut newer = latest.clone();
+        // Leaving this view for any reason, including the socket closing, stops its region render
+        let cancel = CancelToken::new();
+        let _cancel_on_drop = cancel.cancel_on_drop();
+        let mut computation_time_ms = 0;
+        let mut renderers = Vec::new();
+        let mut compute_backend = RendererKind::Cpu;
+        for (sent, region) in regions.into_iter().enumerate() {
+            if newer.has_changed().unwrap_or(true) {
+                return superseded(outbound, sequence, sent).await;
+            }
+
+            // Rendering is CPU bound, so keep it off the async workers that are reading the socket
+            let fractal_service = self.app_state.fractal_service.clone();
+            let region_request = region.request.clone();
+            let region_cancel = cancel.clone();
+            let span = Span::current();
+            let render = tokio::task::spawn_blocking(move || {
+                span.in_scope(|| fractal_service.render_cancellable(region_request, &region_cancel))
+            });
+            let response = tokio::select! {
+                rendered = render => rendered
+                    .map_err(|e| AppError::FractalComputationError(format!("Render task failed: {}", e)))??,
+                _ = newer.changed() => return superseded(outbound, sequence, sent).await,
+            };
+
+            computation_time_ms += response.computation_time_ms;
+            compute_backend = response.compute_backend;
+            if !renderers.contains(&response.renderer) {
+                renderers.push(response.renderer);
+            }
+
+            let message = Message::Binary(encode_region(sequence, region.x, region.y, response.width, response.height, &response.data));
+            // Waiting here is the backpressure: a client that stops reading stops the rendering too
+            tokio::select! {
+                delivered = outbound.send(message) => if delivered.is_err() {
+                    return Ok(());
+                },
+                _ = newer.changed() => return superseded(outbound, sequence, sent).await,
+            }
+        }
+
+        let pixels_per_second = (request.width * request.height) as f64 / (computation_time_ms.max(1) as f64 / 1000.0);
+        if let Err(e) = self.app_state.metrics.record_fractal_generation(type_name, computation_time_ms as f64, pixels_per_second).await {
+            warn!("Failed to record explorer frame metrics: {}", e);
+        }
+
+        let complete = ExplorerEvent::FrameComplete { sequence, computation_time_ms, renderers, compute_backend };
+        let _ = outbound.send(event_message(&complete)).await;
+        Ok(())
+    }
+
+    /// The render for a merged view, clamped to the tenant's current limits and sized to a whole number of regions
+    async fn request_for(&self, view: &ViewportUpdate) -> Result<FractalRequest> {
+        let limits = self.tenant.config(&self.app_state.live_config);
+        let fractal_type = match &view.fractal_type {
+            Some(fractal_type) => engine_fractal_type(&limits, fractal_type)?,
+            None => FractalType::Mandelbrot,
+        };
+        if let Some(trap) = &view.orbit_trap {
+            trap.validate()?;
+        }
+        let palette = match view.palette_id {
+            Some(id) => Some(self.app_state.palette_service.get_palette(&self.tenant.slug, id).await?),
+            None => None,
+        };
+
+        let (default_x, default_y) = fractal_type.default_center();
+        let (antialiasing, max_width, max_height) = antialiasing_limits(view.antialiasing, &limits);
+        let width = view.width.unwrap_or(800).clamp(64, max_width);
+        let height = view.height.unwrap_or(600).clamp(64, max_height);
+        Ok(FractalRequest {
+            center_x: view.center_x.unwrap_or(default_x).clamp(-2.0, 2.0),
+            center_y: view.center_y.unwrap_or(default_y).clamp(-2.0, 2.0),
+            zoom: view.zoom.unwrap_or(1.0).clamp(0.1, limits.fractal_max_zoom),
+            max_iterations: view.max_iterations.unwrap_or(100).clamp(50, limits.fractal_max_iterations),
+            palette,
+            coloring_mode: view.coloring_mode.unwrap_or_default(),
+            orbit_trap: view.orbit_trap,
+            antialiasing: Some(antialiasing),
+            precision: view.precision.unwrap_or_default(),
+            ..FractalRequest::new(fractal_type, width - width % REGION_GRID, height - height % REGION_GRID)
+        })
+    }
+}
+
+async fn superseded(outbound: &mpsc::Sender<Message>, sequence: u64, regions_sent: usize) -> Result<()> {
+    debug!("View {} superseded after {} regions", sequence, regions_sent);
+    let _ = outbound.send(event_message(&ExplorerEvent::Superseded { sequence, regions_sent })).await;
+    Ok(())
+}
+
+fn event_message(event: &ExplorerEvent) -> Message {
+    Message::Text(serde_json::to_string(event).unwrap_or_default())
+}
@@ -0,0 +1,106 @@
+/*
+ * Data export endpoints streaming stored analytics as JSON Lines, or writing them to a file from a background job.
+ * I'm pulling rows from a database cursor and writing them out chunk by chunk so exports never buffer the full table.
+ */
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Json as JsonResponse, Response},
+    Json,
+};
+use futures::StreamExt;
+use serde::Deserialize;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    middleware::AdminAuth,
+    models::{
+        exports::{CreateExportRequest, ExportDataset, ExportFilters, ExportJob},
+        ApiResponse,
+    },
+    routes::admin::setting_actor,
+    services::export_service::record_lines,
+    utils::{
+        error::Result,
+        jsonl::{ndjson_response, NDJSON_CONTENT_TYPE},
+    },
+    AppState,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct ExportJobQuery {
+    pub limit: Option<i64>,
+}
+
+/// Stream every fractal computation in the requested window as JSON Lines
+pub async fn export_fractal_computations(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Query(params): Query<ExportFilters>,
+) -> Response {
+    info!("Streaming fractal computation export: {:?}", params);
+    ndjson_response(record_lines(app_state.db_pool.clone(), ExportDataset::FractalComputations, params))
+}
+
+/// Stream raw performance metrics as JSON Lines, optionally filtered by metric type
+pub async fn export_performance_metrics(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Query(params): Query<ExportFilters>,
+) -> Response {
+    info!("Streaming performance metric export: {:?}", params);
+    ndjson_response(record_lines(app_state.db_pool.clone(), ExportDataset::PerformanceMetrics, params))
+}
+
+/// Queue an export to a file, for datasets too large to stream through one request
+pub async fn create_export_job(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Json(request): Json<CreateExportRequest>,
+) -> Result<(StatusCode, JsonResponse<ApiResponse<ExportJob>>)> {
+    let requested_by = setting_actor(request.actor.as_deref());
+    let job = app_state.export_service.submit(request, &requested_by).await?;
+    Ok((StatusCode::ACCEPTED, Json(ApiResponse::new(job))))
+}
+
+pub async fn list_export_jobs(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Query(params): Query<ExportJobQuery>,
+) -> Result<JsonResponse<ApiResponse<Vec<ExportJob>>>> {
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+    Ok(Json(ApiResponse::new(app_state.export_service.list(limit).await?)))
+}
+
+pub async fn get_export_job(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<JsonResponse<ApiResponse<ExportJob>>> {
+    Ok(Json(ApiResponse::new(app_state.export_service.get(id).await?)))
+}
+
+/// Download a finished export's JSON Lines file
+pub async fn download_export(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Response> {
+    let chunks = app_state.export_service.download(id).await?;
+    let body = Body::from_stream(chunks.map(|chunk| chunk.map_err(std::io::Error::other)));
+    let disposition = HeaderValue::from_str(&format!("attachment; filename=\"export-{}.jsonl\"", id))
+        .unwrap_or_else(|_| HeaderValue::from_static("attachment"));
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, HeaderValue::from_static(NDJSON_CONTENT_TYPE)),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response())
+}
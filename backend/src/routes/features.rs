@@ -0,0 +1,77 @@
+/*
+ * Feature flag endpoints: the caller's evaluated flags publicly, and flag management behind the admin token.
+ * I'm letting the frontend ask which flags it sees so client and server gate the same behaviour from one source.
+ */
+
+use axum::{
+    extract::{Path, State},
+    response::Json as JsonResponse,
+    Json,
+};
+use std::collections::HashMap;
+use tracing::warn;
+
+use crate::{
+    database::notifications::{self, NotificationChannel},
+    middleware::{AdminAuth, Features},
+    models::{
+        feature_flags::{FeatureFlag, FeatureFlagUpdate},
+        settings::SettingReset,
+        ApiResponse,
+    },
+    utils::error::Result,
+    AppState,
+};
+
+/// Every flag evaluated for the caller
+pub async fn get_features(
+    State(app_state): State<AppState>,
+    features: Features,
+) -> JsonResponse<ApiResponse<HashMap<String, bool>>> {
+    Json(ApiResponse::new(app_state.feature_flags.evaluate_all(features.principal())))
+}
+
+/// All stored flags with their rollout rules
+pub async fn list_feature_flags(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+) -> JsonResponse<ApiResponse<Vec<FeatureFlag>>> {
+    Json(ApiResponse::new(app_state.feature_flags.list()))
+}
+
+/// Create or replace a flag
+pub async fn upsert_feature_flag(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path(name): Path<String>,
+    Json(update): Json<FeatureFlagUpdate>,
+) -> Result<JsonResponse<ApiResponse<FeatureFlag>>> {
+    let changed_by = super::admin::setting_actor(update.actor.as_deref());
+    let flag = app_state.feature_flags.upsert(&name, &update, &changed_by).await?;
+
+    broadcast_flag_change(&app_state, &name).await;
+    Ok(Json(ApiResponse::new(flag)))
+}
+
+/// Delete a flag, turning it off for everyone
+pub async fn delete_feature_flag(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path(name): Path<String>,
+    body: Option<Json<SettingReset>>,
+) -> Result<JsonResponse<ApiResponse<serde_json::Value>>> {
+    let reset = body.map(|Json(reset)| reset).unwrap_or_default();
+    let changed_by = super::admin::setting_actor(reset.actor.as_deref());
+    app_state.feature_flags.delete(&name, &changed_by, reset.reason.as_deref()).await?;
+
+    broadcast_flag_change(&app_state, &name).await;
+    Ok(Json(ApiResponse::new(serde_json::json!({ "deleted": name }))))
+}
+
+async fn broadcast_flag_change(app_state: &AppState, name: &str) {
+    // Other instances reload their flag snapshot when they see this
+    let payload = serde_json::json!({ "kind": "feature_flags", "name": name });
+    if let Err(e) = notifications::publish(&app_state.db_pool, NotificationChannel::ConfigChanged, &payload).await {
+        warn!("Failed to broadcast feature flag change: {}", e);
+    }
+}
@@ -0,0 +1,91 @@
+/*
+ * Fractal job endpoints queueing a render to run in the background, then reporting its progress and handing back its pixels.
+ * I'm taking the same JSON request body as batch lines so a client can move a render that's too slow to wait for onto a job unchanged.
+ */
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    middleware::{rate_limit::RenderClient, users::UserAuth},
+    models::{
+        fractal_jobs::FractalJobView,
+        fractals::{FractalRequest, OutputFormat},
+        ApiResponse,
+    },
+    routes::fractals::{charge_render_cost, charge_render_quota, engine_request, COMPUTATION_TIME_HEADER},
+    services::image_service::encode_image,
+    utils::error::{AppError, Result},
+    AppState,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct FractalJobResultQuery {
+    /// raw (JSON with the RGBA buffer, the default), png, jpeg, or webp
+    pub output_format: Option<OutputFormat>,
+}
+
+/// Queue a render and return its job straight away; renders over the synchronous cost cap are accepted here
+pub async fn create_fractal_job(
+    State(app_state): State<AppState>,
+    user: Option<UserAuth>,
+    client: RenderClient,
+    Json(item): Json<FractalRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<FractalJobView>>)> {
+    let request = engine_request(&app_state, &client.tenant, item).await?;
+    charge_render_quota(&app_state, user.as_ref(), &request).await?;
+    charge_render_cost(&app_state, &client, &request, true).await?;
+
+    let job = app_state.fractal_jobs.submit(request).await?;
+    Ok((StatusCode::ACCEPTED, Json(ApiResponse::new(job.into()))))
+}
+
+/// A job's status and progress, with where to fetch the result once it has succeeded
+pub async fn get_fractal_job(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<FractalJobView>>> {
+    Ok(Json(ApiResponse::new(app_state.fractal_jobs.get(id).await?.into())))
+}
+
+/// Cancel a queued or running job, returning it as it stands
+pub async fn cancel_fractal_job(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<FractalJobView>>> {
+    Ok(Json(ApiResponse::new(app_state.fractal_jobs.cancel(id).await?.into())))
+}
+
+/// A finished job's pixels, as JSON or an encoded image
+pub async fn get_fractal_job_result(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<FractalJobResultQuery>,
+) -> Result<Response> {
+    let result = app_state.fractal_jobs.result(id).await?;
+    let output_format = params.output_format.unwrap_or_default();
+    if output_format == OutputFormat::Raw {
+        return Ok(Json(result).into_response());
+    }
+
+    let computation_time_ms = result.computation_time_ms as u64;
+    let encoded = tokio::task::spawn_blocking(move || encode_image(output_format, result.width, result.height, &result.data))
+        .await
+        .map_err(|e| AppError::internal(format!("Image encoding task failed: {}", e)))??;
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, HeaderValue::from_static(output_format.content_type())),
+            (HeaderName::from_static(COMPUTATION_TIME_HEADER), HeaderValue::from(computation_time_ms)),
+        ],
+        encoded,
+    )
+        .into_response())
+}
@@ -3,44 +3,199 @@
  */
 
 use axum::{
+    body::Body,
     extract::{Query, State},
-    http::StatusCode,
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
     Json,
-    response::Response,
+    response::{IntoResponse, Response},
 };
 use serde::{Deserialize, Serialize};
+use futures::StreamExt;
 use std::collections::HashMap;
+use std::time::Duration;
 use tracing::{info, warn, error};
 use uuid::Uuid;
 
 use crate::{
-    services::fractal_service::{FractalService, FractalRequest, FractalResponse, FractalType},
-    utils::error::{AppError, Result},
+    middleware::{rate_limit::RenderClient, session::Session, tenant::CurrentTenant, users::UserAuth},
+    models::{
+        fractals::{self as fractal_models, RenderOptions, ViewportQuery},
+        palettes::{Palette, PresetParameters},
+        webhooks::WebhookEvent,
+    },
+    services::{
+        cancel::CancelToken,
+        cost::CostEstimate,
+        fractal_service::{FractalService, FractalRequest, FractalResponse, FractalType, InteriorStats, Precision, MAX_ANTIALIASING},
+        escape_export::{accepts_zstd, dtype_name, export_stream, RLE_DECODING},
+        image_service::{encode_image, StoredImage},
+        kernels::{self, KernelInfo},
+        mandelbulb::{self, MandelbulbRequest, Vec3},
+        renderers::RendererKind,
+        strategies::StrategyComparison,
+    },
+    utils::{
+        config::Config,
+        error::{AppError, Result},
+        jsonl::{encode_line, ndjson_response, JsonLinesDecoder},
+    },
     AppState,
 };
 
 #[derive(Debug, Deserialize)]
 pub struct MandelbrotQuery {
-    pub width: Option<u32>,
-    pub height: Option<u32>,
-    pub center_x: Option<f64>,
-    pub center_y: Option<f64>,
-    pub zoom: Option<f64>,
-    pub max_iterations: Option<u32>,
+    #[serde(flatten)]
+    pub viewport: ViewportQuery,
+    pub preset_id: Option<Uuid>,
+    /// Palette, colouring, antialiasing (which shrinks the width and height limits by its factor), and output format
+    #[serde(flatten)]
+    pub options: RenderOptions,
+    /// npy, csv, or bin: download the raw escape counts instead of an image
+    pub export: Option<fractal_models::EscapeExport>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct JuliaQuery {
+    #[serde(flatten)]
+    pub viewport: ViewportQuery,
+    #[serde(default, deserialize_with = "fractal_models::number_from_text")]
+    pub c_real: Option<f64>,
+    #[serde(default, deserialize_with = "fractal_models::number_from_text")]
+    pub c_imag: Option<f64>,
+    pub preset_id: Option<Uuid>,
+    /// Palette, colouring, antialiasing (which shrinks the width and height limits by its factor), and output format
+    #[serde(flatten)]
+    pub options: RenderOptions,
+    /// npy, csv, or bin: download the raw escape counts instead of an image
+    pub export: Option<fractal_models::EscapeExport>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BurningShipQuery {
+    #[serde(flatten)]
+    pub viewport: ViewportQuery,
+    pub preset_id: Option<Uuid>,
+    /// Palette, colouring, antialiasing (which shrinks the width and height limits by its factor), and output format
+    #[serde(flatten)]
+    pub options: RenderOptions,
+    /// npy, csv, or bin: download the raw escape counts instead of an image
+    pub export: Option<fractal_models::EscapeExport>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TricornQuery {
+    #[serde(flatten)]
+    pub viewport: ViewportQuery,
+    pub preset_id: Option<Uuid>,
+    /// Palette, colouring, antialiasing (which shrinks the width and height limits by its factor), and output format
+    #[serde(flatten)]
+    pub options: RenderOptions,
+    /// npy, csv, or bin: download the raw escape counts instead of an image
+    pub export: Option<fractal_models::EscapeExport>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MultibrotQuery {
+    #[serde(flatten)]
+    pub viewport: ViewportQuery,
+    #[serde(default, deserialize_with = "fractal_models::number_from_text")]
+    pub power: Option<f64>,
+    pub preset_id: Option<Uuid>,
+    /// Palette, colouring, antialiasing (which shrinks the width and height limits by its factor), and output format
+    #[serde(flatten)]
+    pub options: RenderOptions,
+    /// npy, csv, or bin: download the raw escape counts instead of an image
+    pub export: Option<fractal_models::EscapeExport>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MandelbulbQuery {
+    #[serde(default, deserialize_with = "fractal_models::number_from_text")]
     pub width: Option<u32>,
+    #[serde(default, deserialize_with = "fractal_models::number_from_text")]
     pub height: Option<u32>,
+    #[serde(default, deserialize_with = "fractal_models::number_from_text")]
+    pub power: Option<f64>,
+    /// Distance-estimate iterations at each step of a ray
+    #[serde(default, deserialize_with = "fractal_models::number_from_text")]
+    pub max_iterations: Option<u32>,
+    /// Most steps a ray marches before giving up
+    #[serde(default, deserialize_with = "fractal_models::number_from_text")]
+    pub max_steps: Option<u32>,
+    /// Camera position as `x,y,z`
+    pub camera: Option<String>,
+    /// Point the camera looks at, as `x,y,z`
+    pub target: Option<String>,
+    /// Vertical field of view in degrees
+    #[serde(default, deserialize_with = "fractal_models::number_from_text")]
+    pub fov: Option<f64>,
+    /// Direction the light travels in, as `x,y,z`
+    pub light: Option<String>,
+    /// Light on surfaces facing away from it, from 0 to 1
+    #[serde(default, deserialize_with = "fractal_models::number_from_text")]
+    pub ambient: Option<f64>,
+    /// Palette, threads, and output format; the escape-time colouring options don't apply to a ray-marched surface
+    #[serde(flatten)]
+    pub options: RenderOptions,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ThumbnailQuery {
+    /// mandelbrot (the default), julia, burning_ship, tricorn, or multibrot; a preset's type when unset
+    pub fractal_type: Option<String>,
+    /// Longest edge in pixels, at most 256; the other edge follows the preset's aspect ratio, or matches it without one
+    #[serde(default, deserialize_with = "fractal_models::number_from_text")]
+    pub size: Option<u32>,
+    #[serde(default, deserialize_with = "fractal_models::number_from_text")]
     pub center_x: Option<f64>,
+    #[serde(default, deserialize_with = "fractal_models::number_from_text")]
     pub center_y: Option<f64>,
+    #[serde(default, deserialize_with = "fractal_models::number_from_text")]
     pub zoom: Option<f64>,
+    /// Capped at 256 whatever the query or preset asks for
+    #[serde(default, deserialize_with = "fractal_models::number_from_text")]
     pub max_iterations: Option<u32>,
+    #[serde(default, deserialize_with = "fractal_models::number_from_text")]
     pub c_real: Option<f64>,
+    #[serde(default, deserialize_with = "fractal_models::number_from_text")]
     pub c_imag: Option<f64>,
+    #[serde(default, deserialize_with = "fractal_models::number_from_text")]
+    pub power: Option<f64>,
+    pub preset_id: Option<Uuid>,
+    /// Palette, colouring, and output format, png by default; antialiasing is refused to keep previews cheap
+    #[serde(flatten)]
+    pub options: RenderOptions,
 }
 
+/// Multibrot power used when neither the query nor a preset sets one
+const DEFAULT_MULTIBROT_POWER: f64 = 3.0;
+
+/// Thumbnail edges: the size used when none is asked for, and the range any asked-for size is clamped to
+const THUMBNAIL_DEFAULT_SIZE: u32 = 128;
+const THUMBNAIL_MIN_SIZE: u32 = 16;
+const THUMBNAIL_MAX_SIZE: u32 = 256;
+
+/// Iterations a thumbnail stops at, since detail past this is lost at preview size anyway
+const THUMBNAIL_MAX_ITERATIONS: u32 = 256;
+
+/// Mandelbulb powers a request may ask for; past 16 the bulb is all spikes and the estimate too coarse to march
+const MANDELBULB_POWERS: (f64, f64) = (2.0, 16.0);
+
+/// Renderer name an encoded Mandelbulb image reports, since it bypasses the 2D renderer registry
+const MANDELBULB_RENDERER: &str = "mandelbulb";
+
+/// Window each client's render cost budget refills over
+const RENDER_COST_WINDOW: Duration = Duration::from_secs(60);
+
+/// Where renders too big for a synchronous request can go instead
+const FRACTAL_JOBS_PATH: &str = "/api/fractals/jobs";
+
+/// Back-to-back renders the benchmark times with and without the buffer pool
+const BUFFER_POOL_BENCHMARK_RENDERS: u32 = 8;
+
+/// Runs per strategy in the benchmark's comparison, of which the fastest counts
+const STRATEGY_BENCHMARK_RUNS: u32 = 3;
+
 #[derive(Debug, Serialize)]
 pub struct FractalApiResponse {
     pub data: Vec<u8>,
@@ -48,8 +203,84 @@ pub struct FractalApiResponse {
     pub height: u32,
     pub computation_time_ms: u128,
     pub zoom_level: f64,
+    pub renderer: &'static str,
+    /// cpu, simd, or gpu; a failed GPU render reports the CPU that redrew it
+    pub compute_backend: RendererKind,
+    /// True when an identical earlier render was served from the fractal cache
+    pub cache_hit: bool,
+    /// f32, f64, or extended: what the render actually iterated in, whatever the request asked for
+    pub precision: Precision,
+    /// Threads the render ran on, 0 when it came from the cache
+    pub threads: usize,
+    /// Iterations the render ran at, the server's choice when auto_iterations was asked for
+    pub max_iterations: u32,
+    /// Points inside the set, and how many the Mandelbrot interior checks skipped rather than iterating; zero when cached
+    pub interior: InteriorStats,
     pub parameters: serde_json::Value,
     pub performance_metrics: PerformanceMetrics,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<StoredImage>,
+}
+
+/// Render timing and renderer of an encoded image response, which has no JSON body to carry them
+pub const COMPUTATION_TIME_HEADER: &str = "x-computation-time-ms";
+pub const RENDERER_HEADER: &str = "x-renderer";
+pub const COMPUTE_BACKEND_HEADER: &str = "x-compute-backend";
+pub const CACHE_HIT_HEADER: &str = "x-cache-hit";
+pub const PRECISION_HEADER: &str = "x-precision";
+pub const THREADS_HEADER: &str = "x-threads";
+/// Iterations an encoded render ran at, the server's choice when auto_iterations was asked for
+pub const MAX_ITERATIONS_HEADER: &str = "x-max-iterations";
+/// Interior points the Mandelbrot checks skipped rather than iterating to max_iterations
+pub const SKIPPED_PIXELS_HEADER: &str = "x-skipped-pixels";
+/// Where the persisted copy of an encoded response lives, when image storage is on
+pub const IMAGE_URL_HEADER: &str = "x-image-url";
+/// Element type and row-major (height, width) shape of an escape-count export
+pub const EXPORT_DTYPE_HEADER: &str = "x-export-dtype";
+pub const EXPORT_SHAPE_HEADER: &str = "x-export-shape";
+/// How to turn an rle export back into the matrix
+pub const EXPORT_DECODE_HEADER: &str = "x-export-decode";
+/// Content type of an rle export a client takes zstd for, which then arrives as bare runs
+pub const RLE_CONTENT_TYPE: &str = "application/x-fractal-rle";
+
+/// Upper bound on records in a single streamed batch
+pub const MAX_BATCH_ITEMS: usize = 1000;
+
+/// One line of the streamed batch response, matching an input line by number
+#[derive(Debug, Serialize)]
+pub struct BatchItemResult {
+    pub line: usize,
+    pub status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fractal_type: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub computation_time_ms: Option<u128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_hit: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub precision: Option<Precision>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_iterations: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<StoredImage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl BatchItemResult {
+    fn failed(line: usize, error: &AppError) -> Self {
+        Self {
+            line,
+            status: "error",
+            fractal_type: None,
+            computation_time_ms: None,
+            cache_hit: None,
+            precision: None,
+            max_iterations: None,
+            image: None,
+            error: Some(error.to_string()),
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -60,166 +291,583 @@ pub struct PerformanceMetrics {
     pub cpu_utilization: f64,
 }
 
+#[derive(Debug, Serialize)]
+pub struct MandelbulbApiResponse {
+    pub data: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub computation_time_ms: u128,
+    /// Rays that reached the surface, out of width × height
+    pub hits: u64,
+    /// Distance-estimate steps across every ray, the work a 3D render actually does
+    pub march_steps: u64,
+    pub threads: usize,
+    pub parameters: serde_json::Value,
+    pub performance_metrics: PerformanceMetrics,
+}
+
+/// A registered fractal type as /api/fractals/types lists it
+#[derive(Debug, Serialize)]
+pub struct FractalTypeInfo {
+    #[serde(flatten)]
+    pub kernel: KernelInfo,
+    /// Renderers whose capabilities include this type
+    pub renderers: Vec<&'static str>,
+}
+
+/// Fractal types the engine has kernels for, with each one's parameters and the renderers that can draw it
+pub async fn list_fractal_types(State(app_state): State<AppState>, tenant: CurrentTenant) -> Json<serde_json::Value> {
+    let limits = tenant.config(&app_state.live_config);
+    let renderers = app_state.fractal_service.renderers().list();
+
+    let types: Vec<FractalTypeInfo> = kernels::kernels()
+        .iter()
+        .map(|kernel| {
+            let mut info = kernel.info();
+            // The engine leaves the multibrot power open; this server bounds it by the caller's limits
+            for parameter in info.parameters.iter_mut().filter(|parameter| parameter.name == "power") {
+                parameter.min = Some(limits.fractal_min_multibrot_power);
+                parameter.max = Some(limits.fractal_max_multibrot_power);
+                parameter.default = DEFAULT_MULTIBROT_POWER.clamp(limits.fractal_min_multibrot_power, limits.fractal_max_multibrot_power);
+            }
+            FractalTypeInfo {
+                renderers: renderers
+                    .iter()
+                    .filter(|renderer| renderer.capabilities.fractal_types.contains(&info.name))
+                    .map(|renderer| renderer.name)
+                    .collect(),
+                kernel: info,
+            }
+        })
+        .collect();
+
+    Json(serde_json::json!({
+        "types": types,
+    }))
+}
+
+/// Renderers registered with the fractal service, most preferred first
+pub async fn list_renderers(State(app_state): State<AppState>) -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "renderers": app_state.fractal_service.renderers().list(),
+    }))
+}
+
 /// Generate Mandelbrot fractal with real-time performance tracking
 /// I'm implementing comprehensive parameter validation and performance optimization
 pub async fn generate_mandelbrot(
     State(app_state): State<AppState>,
+                                 session: Option<Session>,
+                                 user: Option<UserAuth>,
+                                 client: RenderClient,
+                                 tenant: CurrentTenant,
+                                 headers: HeaderMap,
                                  Query(params): Query<MandelbrotQuery>,
-) -> Result<Json<FractalApiResponse>> {
+) -> Result<Response> {
     info!("Generating Mandelbrot fractal with params: {:?}", params);
 
-    // I'm setting sensible defaults and validating parameters for safety
-    let width = params.width.unwrap_or(800).clamp(64, 4096);
-    let height = params.height.unwrap_or(600).clamp(64, 4096);
-    let center_x = params.center_x.unwrap_or(-0.5).clamp(-2.0, 2.0);
-    let center_y = params.center_y.unwrap_or(0.0).clamp(-2.0, 2.0);
-    let zoom = params.zoom.unwrap_or(1.0).clamp(0.1, 1e15);
-    let max_iterations = params.max_iterations.unwrap_or(100).clamp(50, 10000);
+    let view = GenerateView {
+        viewport: &params.viewport,
+        preset_id: params.preset_id,
+        options: &params.options,
+        export: params.export,
+        headers: &headers,
+    };
+    generate_view(&app_state, session, user, client, tenant, view, |_, _| Ok(FractalType::Mandelbrot)).await
+}
+
+/// Generate Julia set fractal with customizable complex parameter
+/// I'm providing flexible parameter control while maintaining performance
+pub async fn generate_julia(
+    State(app_state): State<AppState>,
+                            session: Option<Session>,
+                            user: Option<UserAuth>,
+                            client: RenderClient,
+                            tenant: CurrentTenant,
+                            headers: HeaderMap,
+                            Query(params): Query<JuliaQuery>,
+) -> Result<Response> {
+    info!("Generating Julia fractal with params: {:?}", params);
+
+    let view = GenerateView {
+        viewport: &params.viewport,
+        preset_id: params.preset_id,
+        options: &params.options,
+        export: params.export,
+        headers: &headers,
+    };
+    generate_view(&app_state, session, user, client, tenant, view, |preset, _| {
+        Ok(FractalType::Julia {
+            c_real: params.c_real.or(preset.c_real).unwrap_or(-0.7).clamp(-2.0, 2.0),
+            c_imag: params.c_imag.or(preset.c_imag).unwrap_or(0.27015).clamp(-2.0, 2.0),
+        })
+    })
+    .await
+}
+
+/// Generate the Burning Ship fractal, the Mandelbrot iteration with z folded into the first quadrant
+pub async fn generate_burning_ship(
+    State(app_state): State<AppState>,
+    session: Option<Session>,
+    user: Option<UserAuth>,
+    client: RenderClient,
+    tenant: CurrentTenant,
+    headers: HeaderMap,
+    Query(params): Query<BurningShipQuery>,
+) -> Result<Response> {
+    info!("Generating Burning Ship fractal with params: {:?}", params);
+
+    let view = GenerateView {
+        viewport: &params.viewport,
+        preset_id: params.preset_id,
+        options: &params.options,
+        export: params.export,
+        headers: &headers,
+    };
+    generate_view(&app_state, session, user, client, tenant, view, |_, _| Ok(FractalType::BurningShip)).await
+}
+
+/// Generate the Tricorn, the Mandelbrot iteration on the conjugate of z
+pub async fn generate_tricorn(
+    State(app_state): State<AppState>,
+    session: Option<Session>,
+    user: Option<UserAuth>,
+    client: RenderClient,
+    tenant: CurrentTenant,
+    headers: HeaderMap,
+    Query(params): Query<TricornQuery>,
+) -> Result<Response> {
+    info!("Generating Tricorn fractal with params: {:?}", params);
+
+    let view = GenerateView {
+        viewport: &params.viewport,
+        preset_id: params.preset_id,
+        options: &params.options,
+        export: params.export,
+        headers: &headers,
+    };
+    generate_view(&app_state, session, user, client, tenant, view, |_, _| Ok(FractalType::Tricorn)).await
+}
+
+/// Generate a Multibrot set, z^power + c, for a power within the configured limits
+/// I'm rejecting an out-of-range power rather than clamping it, since a different power is a different fractal
+pub async fn generate_multibrot(
+    State(app_state): State<AppState>,
+    session: Option<Session>,
+    user: Option<UserAuth>,
+    client: RenderClient,
+    tenant: CurrentTenant,
+    headers: HeaderMap,
+    Query(params): Query<MultibrotQuery>,
+) -> Result<Response> {
+    info!("Generating Multibrot fractal with params: {:?}", params);
+
+    let view = GenerateView {
+        viewport: &params.viewport,
+        preset_id: params.preset_id,
+        options: &params.options,
+        export: params.export,
+        headers: &headers,
+    };
+    generate_view(&app_state, session, user, client, tenant, view, |preset, limits| {
+        let power = params.power.or(preset.power).unwrap_or_else(|| {
+            DEFAULT_MULTIBROT_POWER.clamp(limits.fractal_min_multibrot_power, limits.fractal_max_multibrot_power)
+        });
+        check_multibrot_power(limits, power)?;
+        Ok(FractalType::Multibrot { power })
+    })
+    .await
+}
+
+/// What the escape-time generate endpoints read from their query besides the fractal's own parameters
+struct GenerateView<'a> {
+    viewport: &'a ViewportQuery,
+    preset_id: Option<Uuid>,
+    options: &'a RenderOptions,
+    export: Option<fractal_models::EscapeExport>,
+    headers: &'a HeaderMap,
+}
+
+/// The body every escape-time generate endpoint shares: fill the view in from its preset and defaults, clamp it to the
+/// tenant's limits, and export or render it; `fractal_type` builds the fractal from the query and preset once both are known
+async fn generate_view(
+    app_state: &AppState,
+    session: Option<Session>,
+    user: Option<UserAuth>,
+    client: RenderClient,
+    tenant: CurrentTenant,
+    view: GenerateView<'_>,
+    fractal_type: impl FnOnce(&PresetParameters, &Config) -> Result<FractalType>,
+) -> Result<Response> {
+    // Preset values fill in anything the query string leaves unset
+    let (preset, palette) = resolve_preset_and_palette(app_state, &tenant.slug, view.preset_id, view.options).await?;
+
+    // I'm setting sensible defaults and clamping to the limits currently configured for this tenant
+    let limits = tenant.config(&app_state.live_config);
+    let fractal_type = fractal_type(&preset, &limits)?;
+    let (default_x, default_y) = fractal_type.default_center();
+    let viewport = view.viewport;
+    let options = view.options;
+    let (antialiasing, max_width, max_height) = antialiasing_limits(options.antialiasing, &limits);
+    let zoom = viewport.zoom.or(preset.zoom).unwrap_or(1.0).clamp(0.1, limits.fractal_max_zoom);
+    let width = viewport.width.or(preset.width).unwrap_or(800).clamp(64, max_width);
+    let height = viewport.height.or(preset.height).unwrap_or(600).clamp(64, max_height);
+    let auto_iterations = viewport.auto_iterations.unwrap_or(false);
 
     let request = FractalRequest {
-        width,
-        height,
-        center_x,
-        center_y,
+        center_x: viewport.center_x.or(preset.center_x).unwrap_or(default_x).clamp(-2.0, 2.0),
+        center_y: viewport.center_y.or(preset.center_y).unwrap_or(default_y).clamp(-2.0, 2.0),
         zoom,
-        max_iterations,
-        fractal_type: FractalType::Mandelbrot,
+        max_iterations: choose_max_iterations(&limits, auto_iterations, viewport.max_iterations.or(preset.max_iterations), zoom),
+        palette,
+        coloring_mode: options.coloring_mode.unwrap_or_default(),
+        orbit_trap: options.orbit_trap,
+        antialiasing: Some(antialiasing),
+        precision: options.precision.unwrap_or_default(),
+        threads: options.threads,
+        interior_coloring: options.interior_coloring.unwrap_or_default(),
+        ..FractalRequest::new(fractal_type, width, height)
     };
+    charge_render_quota(app_state, user.as_ref(), &request).await?;
+    if let Some(format) = view.export {
+        return export_escapes(app_state, &client, view.headers, request, format).await;
+    }
 
-    // Record system state before computation
-    let start_memory = get_memory_usage();
-    let start_cpu = get_cpu_usage().await;
+    let mut parameters = render_parameters(&request);
+    parameters["auto_iterations"] = serde_json::json!(auto_iterations);
+    render_and_record(app_state, &client, session, request, parameters, options.output_format.unwrap_or_default()).await
+}
 
-    // Generate the fractal using our high-performance service
-    let response = app_state.fractal_service.generate_mandelbrot(request.clone());
+/// The parameters recorded with a render, enough to tell it apart from any other and draw it again
+fn render_parameters(request: &FractalRequest) -> serde_json::Value {
+    let mut parameters = serde_json::json!({
+        "center_x": request.center_x,
+        "center_y": request.center_y,
+        "zoom": request.zoom,
+        "width": request.width,
+        "height": request.height,
+        "max_iterations": request.max_iterations,
+        "fractal_type": request.fractal_type.name(),
+        "palette_id": request.palette.as_ref().and_then(Palette::reference_id),
+        "coloring_mode": request.coloring_mode.name(),
+        "orbit_trap": request.orbit_trap.map(|trap| trap.to_string()),
+        "antialiasing": request.supersampling(),
+        "precision": request.precision.name(),
+        "interior_coloring": request.interior_coloring.name()
+    });
+    match request.fractal_type {
+        FractalType::Julia { c_real, c_imag } => {
+            parameters["c_real"] = serde_json::json!(c_real);
+            parameters["c_imag"] = serde_json::json!(c_imag);
+        }
+        FractalType::Multibrot { power } => parameters["power"] = serde_json::json!(power),
+        FractalType::Mandelbrot | FractalType::BurningShip | FractalType::Tricorn => {}
+    }
+    parameters
+}
 
-    // Calculate performance metrics
-    let end_memory = get_memory_usage();
-    let end_cpu = get_cpu_usage().await;
+/// Ray-march a Mandelbulb, the 3D power-n analogue of the Mandelbrot set, from a configurable camera and light
+/// I'm reporting march steps next to the timing, since per-ray marching scales with scene depth rather than iterations
+pub async fn generate_mandelbulb(
+    State(app_state): State<AppState>,
+    user: Option<UserAuth>,
+    client: RenderClient,
+    tenant: CurrentTenant,
+    Query(params): Query<MandelbulbQuery>,
+) -> Result<Response> {
+    info!("Generating Mandelbulb with params: {:?}", params);
+    params.options.refuse("Mandelbulb", &["coloring_mode", "orbit_trap", "antialiasing", "precision", "interior_coloring"])?;
 
-    let pixels_per_second = (width * height) as f64 / (response.computation_time_ms as f64 / 1000.0);
-    let memory_delta = end_memory - start_memory;
-    let cpu_delta = end_cpu - start_cpu;
+    let options = &params.options;
+    let palette = app_state.palette_service.resolve_palette(&tenant.slug, options.palette_id, options.palette.as_deref(), options.gradient.as_deref()).await?;
+    let limits = tenant.config(&app_state.live_config);
+    let defaults = MandelbulbRequest::default();
+    let vector = |spec: Option<&str>, default: Vec3| spec.map(str::parse::<Vec3>).transpose().map(|v| v.unwrap_or(default));
 
-    // Store computation in database for analytics
-    if let Err(e) = store_fractal_computation(&app_state, &request, &response, memory_delta, cpu_delta).await {
-        warn!("Failed to store fractal computation: {}", e);
+    let request = MandelbulbRequest {
+        width: params.width.unwrap_or(defaults.width).clamp(64, limits.fractal_max_width),
+        height: params.height.unwrap_or(defaults.height).clamp(64, limits.fractal_max_height),
+        power: params.power.unwrap_or(defaults.power).clamp(MANDELBULB_POWERS.0, MANDELBULB_POWERS.1),
+        max_iterations: params.max_iterations.unwrap_or(defaults.max_iterations).clamp(4, 64),
+        max_steps: params.max_steps.unwrap_or(defaults.max_steps).clamp(16, 512),
+        camera: vector(params.camera.as_deref(), defaults.camera)?,
+        target: vector(params.target.as_deref(), defaults.target)?,
+        field_of_view: params.fov.unwrap_or(defaults.field_of_view),
+        light: vector(params.light.as_deref(), defaults.light)?,
+        ambient: params.ambient.unwrap_or(defaults.ambient),
+        palette,
+    };
+    request.validate()?;
+    if let Some(UserAuth(user)) = user.as_ref() {
+        app_state.user_service.charge_render(user, u64::from(request.width) * u64::from(request.height)).await?;
     }
+    charge_cost_estimate(&app_state, &client, CostEstimate::of_mandelbulb(&request), false, None).await?;
 
-    // Update real-time performance metrics
-    app_state.metrics.record_fractal_generation(
-        "mandelbrot",
-        response.computation_time_ms as f64,
-        pixels_per_second,
-    ).await;
+    let parameters = serde_json::json!({
+        "fractal_type": "mandelbulb",
+        "power": request.power,
+        "max_iterations": request.max_iterations,
+        "max_steps": request.max_steps,
+        "camera": request.camera,
+        "target": request.target,
+        "fov": request.field_of_view,
+        "light": request.light,
+        "ambient": request.ambient,
+        "palette_id": request.palette.as_ref().and_then(Palette::reference_id)
+    });
 
-    let api_response = FractalApiResponse {
-        data: response.data,
+    // Marching is CPU bound like any render; a client that goes away cancels the rows it left behind
+    let render_request = request.clone();
+    let cancel = CancelToken::new();
+    let _cancel_on_drop = cancel.cancel_on_drop();
+    let pools = app_state.fractal_service.pools();
+    let (response, threads) = pools.run(pools.budget(params.options.threads), move || mandelbulb::render(&render_request, &cancel)).await;
+    let response = response?;
+
+    let total_pixels = response.width * response.height;
+    let pixels_per_second = total_pixels as f64 / (response.computation_time_ms.max(1) as f64 / 1000.0);
+    if let Err(e) = app_state.metrics.record_fractal_generation(MANDELBULB_RENDERER, response.computation_time_ms as f64, pixels_per_second).await {
+        warn!("Failed to record Mandelbulb generation metrics: {}", e);
+    }
+    info!("Mandelbulb completed in {}ms, {} of {} rays hit", response.computation_time_ms, response.hits, total_pixels);
+
+    let output_format = params.options.output_format.unwrap_or_default();
+    if output_format != fractal_models::OutputFormat::Raw {
+        let (width, height, computation_time_ms) = (response.width, response.height, response.computation_time_ms);
+        let data = response.data;
+        let encoded = tokio::task::spawn_blocking(move || encode_image(output_format, width, height, &data))
+            .await
+            .map_err(|e| AppError::internal(format!("Image encoding task failed: {}", e)))??;
+        let headers = [
+            (header::CONTENT_TYPE, HeaderValue::from_static(output_format.content_type())),
+            (HeaderName::from_static(COMPUTATION_TIME_HEADER), HeaderValue::from(computation_time_ms as u64)),
+            (HeaderName::from_static(RENDERER_HEADER), HeaderValue::from_static(MANDELBULB_RENDERER)),
+            (HeaderName::from_static(THREADS_HEADER), HeaderValue::from(threads)),
+        ];
+        return Ok((headers, encoded).into_response());
+    }
+
+    Ok(Json(MandelbulbApiResponse {
+        computation_time_ms: response.computation_time_ms,
         width: response.width,
         height: response.height,
-        computation_time_ms: response.computation_time_ms,
-        zoom_level: response.zoom_level,
-        parameters: serde_json::json!({
-            "center_x": center_x,
-            "center_y": center_y,
-            "max_iterations": max_iterations,
-            "fractal_type": "mandelbrot"
-        }),
+        hits: response.hits,
+        march_steps: response.march_steps,
+        threads,
+        data: response.data,
+        parameters,
         performance_metrics: PerformanceMetrics {
             pixels_per_second,
-            parallel_efficiency: calculate_parallel_efficiency(response.computation_time_ms, width * height),
-            memory_usage_mb: memory_delta,
-            cpu_utilization: cpu_delta,
+            parallel_efficiency: calculate_parallel_efficiency(response.computation_time_ms, total_pixels),
+            memory_usage_mb: 0.0,
+            cpu_utilization: 0.0,
         },
-    };
-
-    info!("Mandelbrot generation completed in {}ms", response.computation_time_ms);
-    Ok(Json(api_response))
+    })
+    .into_response())
 }
 
-/// Generate Julia set fractal with customizable complex parameter
-/// I'm providing flexible parameter control while maintaining performance
-pub async fn generate_julia(
+/// Draw a small preview of a view or preset as an image, for galleries and preset listings to embed
+/// I'm serving encoded thumbnails from Redis and skipping the recording a full render gets, so a page of previews stays cheap
+pub async fn generate_thumbnail(
     State(app_state): State<AppState>,
-                            Query(params): Query<JuliaQuery>,
-) -> Result<Json<FractalApiResponse>> {
-    info!("Generating Julia fractal with params: {:?}", params);
+    client: RenderClient,
+    tenant: CurrentTenant,
+    Query(params): Query<ThumbnailQuery>,
+) -> Result<Response> {
+    params.options.refuse("Thumbnails", &["antialiasing"])?;
+    let output_format = params.options.output_format.unwrap_or(fractal_models::OutputFormat::Png);
+    if output_format == fractal_models::OutputFormat::Raw {
+        return Err(AppError::ValidationError("Thumbnails are images; output_format must be png, jpeg, or webp".to_string()));
+    }
+
+    let (preset, palette) = resolve_preset_and_palette(&app_state, &tenant.slug, params.preset_id, &params.options).await?;
 
-    let width = params.width.unwrap_or(800).clamp(64, 4096);
-    let height = params.height.unwrap_or(600).clamp(64, 4096);
-    let center_x = params.center_x.unwrap_or(0.0).clamp(-2.0, 2.0);
-    let center_y = params.center_y.unwrap_or(0.0).clamp(-2.0, 2.0);
-    let zoom = params.zoom.unwrap_or(1.0).clamp(0.1, 1e15);
-    let max_iterations = params.max_iterations.unwrap_or(100).clamp(50, 10000);
-    let c_real = params.c_real.unwrap_or(-0.7).clamp(-2.0, 2.0);
-    let c_imag = params.c_imag.unwrap_or(0.27015).clamp(-2.0, 2.0);
+    let limits = tenant.config(&app_state.live_config);
+    let fractal_type = thumbnail_fractal_type(&limits, &params, &preset)?;
+    let (default_x, default_y) = fractal_type.default_center();
+    let size = params.size.unwrap_or(THUMBNAIL_DEFAULT_SIZE).clamp(THUMBNAIL_MIN_SIZE, THUMBNAIL_MAX_SIZE);
+    let (width, height) = thumbnail_dimensions(size, preset.width, preset.height);
 
     let request = FractalRequest {
-        width,
-        height,
-        center_x,
-        center_y,
-        zoom,
-        max_iterations,
-        fractal_type: FractalType::Julia { c_real, c_imag },
+        center_x: params.center_x.or(preset.center_x).unwrap_or(default_x).clamp(-2.0, 2.0),
+        center_y: params.center_y.or(preset.center_y).unwrap_or(default_y).clamp(-2.0, 2.0),
+        zoom: params.zoom.or(preset.zoom).unwrap_or(1.0).clamp(0.1, limits.fractal_max_zoom),
+        max_iterations: params.max_iterations.or(preset.max_iterations).unwrap_or(100).clamp(50, THUMBNAIL_MAX_ITERATIONS.min(limits.fractal_max_iterations)),
+        palette,
+        coloring_mode: params.options.coloring_mode.unwrap_or_default(),
+        orbit_trap: params.options.orbit_trap,
+        antialiasing: None,
+        precision: params.options.precision.unwrap_or_default(),
+        threads: params.options.threads,
+        interior_coloring: params.options.interior_coloring.unwrap_or_default(),
+        ..FractalRequest::new(fractal_type, width.min(limits.fractal_max_width), height.min(limits.fractal_max_height))
     };
 
-    let start_memory = get_memory_usage();
-    let start_cpu = get_cpu_usage().await;
+    // A cached thumbnail costs the client nothing, which is what lets a listing embed dozens of them
+    let cached = app_state.fractal_cache.get_thumbnail(&request, output_format).await;
+    let cache_hit = cached.is_some();
+    let encoded = match cached {
+        Some(encoded) => encoded,
+        None => {
+            charge_render_cost(&app_state, &client, &request, false).await?;
+            let fractal_service = app_state.fractal_service.clone();
+            let render_request = request.clone();
+            let pools = app_state.fractal_service.pools();
+            let encoded = pools
+                .run(pools.budget(request.threads), move || {
+                    let response = fractal_service.render(render_request);
+                    let encoded = encode_image(output_format, response.width, response.height, &response.data);
+                    fractal_service.recycle(response.data);
+                    encoded
+                })
+                .await
+                .0?;
+            app_state.fractal_cache.put_thumbnail(&request, output_format, &encoded).await;
+            encoded
+        }
+    };
 
-    let c = num_complex::Complex::new(c_real, c_imag);
-    let response = app_state.fractal_service.generate_julia(request.clone(), c);
+    // Browsers and CDNs may keep a thumbnail as long as Redis does
+    let cache_control = match app_state.fractal_cache.thumbnail_ttl() {
+        Some(ttl) => HeaderValue::from_str(&format!("public, max-age={}", ttl)).unwrap_or(HeaderValue::from_static("no-cache")),
+        None => HeaderValue::from_static("no-cache"),
+    };
+    let mut response = encoded.into_response();
+    for (name, value) in [
+        (header::CONTENT_TYPE, HeaderValue::from_static(output_format.content_type())),
+        (header::CACHE_CONTROL, cache_control),
+        (HeaderName::from_static(CACHE_HIT_HEADER), HeaderValue::from_static(if cache_hit { "true" } else { "false" })),
+    ] {
+        response.headers_mut().insert(name, value);
+    }
+    Ok(response)
+}
 
-    let end_memory = get_memory_usage();
-    let end_cpu = get_cpu_usage().await;
+/// Render a JSON Lines stream of fractal requests, answering with one JSON line per input
+/// I'm decoding the body as it arrives and rendering sequentially so memory stays bounded however long the batch is
+pub async fn generate_batch(
+    State(app_state): State<AppState>,
+    user: Option<UserAuth>,
+    client: RenderClient,
+    body: Body,
+) -> Response {
+    let results = async_stream::stream! {
+        let mut decoder = JsonLinesDecoder::new();
+        let mut chunks = body.into_data_stream();
+        let mut line_no = 0usize;
 
-    let pixels_per_second = (width * height) as f64 / (response.computation_time_ms as f64 / 1000.0);
-    let memory_delta = end_memory - start_memory;
-    let cpu_delta = end_cpu - start_cpu;
+        'body: loop {
+            let (lines, finished) = match chunks.next().await {
+                Some(Ok(chunk)) => match decoder.push(&chunk) {
+                    Ok(lines) => (lines, false),
+                    Err(e) => {
+                        yield encode_line(&BatchItemResult::failed(line_no + 1, &e));
+                        break 'body;
+                    }
+                },
+                Some(Err(e)) => {
+                    let e = AppError::bad_request(format!("Failed to read request body: {}", e));
+                    yield encode_line(&BatchItemResult::failed(line_no + 1, &e));
+                    break 'body;
+                }
+                None => (decoder.finish().into_iter().collect(), true),
+            };
 
-    if let Err(e) = store_fractal_computation(&app_state, &request, &response, memory_delta, cpu_delta).await {
-        warn!("Failed to store fractal computation: {}", e);
-    }
+            for line in lines {
+                line_no += 1;
+                if line_no > MAX_BATCH_ITEMS {
+                    let e = AppError::ValidationError(format!("Batches are limited to {} requests", MAX_BATCH_ITEMS));
+                    yield encode_line(&BatchItemResult::failed(line_no, &e));
+                    break 'body;
+                }
 
-    app_state.metrics.record_fractal_generation(
-        "julia",
-        response.computation_time_ms as f64,
-        pixels_per_second,
-    ).await;
+                let result = match render_batch_item(&app_state, user.as_ref(), &client, line_no, &line).await {
+                    Ok(result) => result,
+                    Err(e) => BatchItemResult::failed(line_no, &e),
+                };
+                yield encode_line(&result);
+            }
 
-    let api_response = FractalApiResponse {
-        data: response.data,
-        width: response.width,
-        height: response.height,
-        computation_time_ms: response.computation_time_ms,
-        zoom_level: response.zoom_level,
-        parameters: serde_json::json!({
-            "center_x": center_x,
-            "center_y": center_y,
-            "max_iterations": max_iterations,
-            "c_real": c_real,
-            "c_imag": c_imag,
-            "fractal_type": "julia"
-        }),
-        performance_metrics: PerformanceMetrics {
-            pixels_per_second,
-            parallel_efficiency: calculate_parallel_efficiency(response.computation_time_ms, width * height),
-            memory_usage_mb: memory_delta,
-            cpu_utilization: cpu_delta,
-        },
+            if finished {
+                break;
+            }
+        }
+
+        info!("Streamed fractal batch of {} requests", line_no);
+        app_state.webhook_service.emit(WebhookEvent::JobFinished, serde_json::json!({
+            "job": "fractal_batch",
+            "items": line_no,
+        }));
     };
 
-    info!("Julia generation completed in {}ms", response.computation_time_ms);
-    Ok(Json(api_response))
+    ndjson_response(results)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BenchmarkQuery {
+    /// Comma-separated precisions (f32, f64, extended, auto) to also time each scenario's Mandelbrot at
+    pub precisions: Option<String>,
 }
 
 /// Comprehensive benchmark suite comparing different fractal parameters and resolutions
 /// I'm providing detailed performance analysis across multiple computational scenarios
 pub async fn benchmark_generation(
     State(app_state): State<AppState>,
+    Query(query): Query<BenchmarkQuery>,
 ) -> Result<Json<serde_json::Value>> {
     info!("Starting comprehensive fractal benchmark suite");
 
+    let precisions = query
+        .precisions
+        .as_deref()
+        .map(parse_precisions)
+        .transpose()?
+        .unwrap_or_default();
+
+    // The suite holds the whole render pool, so its timings aren't shared with live renders and the runtime stays free meanwhile
+    let fractal_service = app_state.fractal_service.clone();
+    let pools = app_state.fractal_service.pools();
+    let ((benchmark_results, buffer_pool, pool_request, strategy_comparison), _) =
+        pools.run(pools.size(), move || run_benchmark_suite(&fractal_service, &precisions)).await;
+
+    // System information for context
+    let system_info = app_state.performance_service.get_system_info().await?;
+
+    if let Err(e) = store_strategy_benchmarks(&app_state, &pool_request, &strategy_comparison, &system_info).await {
+        warn!("Failed to store strategy benchmarks: {}", e);
+    }
+
+    let benchmark_summary = serde_json::json!({
+        "benchmark_results": benchmark_results,
+        "buffer_pool": buffer_pool,
+        "strategy_comparison": strategy_comparison,
+        "system_context": {
+            "cpu_model": system_info["hardware"]["cpu"]["model"].as_str().unwrap_or_default(),
+            "cpu_cores": system_info["hardware"]["cpu"]["cores"].as_u64().unwrap_or_default(),
+            "memory_total_gb": system_info["hardware"]["memory"]["total_gb"].as_f64().unwrap_or_default(),
+            "rust_version": crate::build_info::RUST_VERSION,
+                                              "parallel_processing": true,
+                                              "simd_optimized": cfg!(target_feature = "avx2")
+        },
+        "performance_analysis": {
+            "language": "Rust",
+            "framework": "Rayon parallel processing",
+            "optimization_level": "Maximum (-O3, LTO)",
+                                              "memory_allocator": if cfg!(feature = "jemalloc") { "jemalloc" } else { "system" }
+        },
+        "benchmark_timestamp": chrono::Utc::now(),
+                                              "total_benchmarks": benchmark_results.len()
+    });
+
+    info!("Benchmark suite completed with {} scenarios", benchmark_results.len());
+    Ok(Json(benchmark_summary))
+}
+
+/// The benchmark's renders, run on a render pool thread: each scenario, the buffer pool comparison, and the strategy comparison
+fn run_benchmark_suite(
+    fractal_service: &FractalService,
+    precisions: &[Precision],
+) -> (Vec<serde_json::Value>, serde_json::Value, FractalRequest, StrategyComparison) {
     let mut benchmark_results = Vec::new();
 
     // I'm testing various resolution and complexity combinations
@@ -235,32 +883,61 @@ pub async fn benchmark_generation(
 
         // Mandelbrot benchmark
         let mandelbrot_request = FractalRequest {
-            width,
-            height,
             center_x: -0.5,
             center_y: 0.0,
             zoom: 1.0,
             max_iterations: max_iter,
-            fractal_type: FractalType::Mandelbrot,
+            ..FractalRequest::new(FractalType::Mandelbrot, width, height)
         };
 
-        let mandelbrot_response = app_state.fractal_service.generate_mandelbrot(mandelbrot_request);
+        let mandelbrot_response = fractal_service.generate_mandelbrot(mandelbrot_request.clone());
         let mandelbrot_pixels_per_ms = (width * height) as f64 / mandelbrot_response.computation_time_ms as f64;
+        fractal_service.recycle(mandelbrot_response.data);
 
         // Julia benchmark
         let julia_request = FractalRequest {
-            width,
-            height,
             center_x: 0.0,
             center_y: 0.0,
             zoom: 1.0,
             max_iterations: max_iter,
-            fractal_type: FractalType::Julia { c_real: -0.7, c_imag: 0.27015 },
+            ..FractalRequest::new(FractalType::Julia { c_real: -0.7, c_imag: 0.27015 }, width, height)
         };
 
         let c = num_complex::Complex::new(-0.7, 0.27015);
-        let julia_response = app_state.fractal_service.generate_julia(julia_request, c);
+        let julia_response = fractal_service.generate_julia(julia_request, c);
         let julia_pixels_per_ms = (width * height) as f64 / julia_response.computation_time_ms as f64;
+        fractal_service.recycle(julia_response.data);
+
+        // Burning Ship benchmark
+        let (ship_x, ship_y) = FractalType::BurningShip.default_center();
+        let burning_ship_request = FractalRequest {
+            center_x: ship_x,
+            center_y: ship_y,
+            zoom: 1.0,
+            max_iterations: max_iter,
+            ..FractalRequest::new(FractalType::BurningShip, width, height)
+        };
+
+        let burning_ship_response = fractal_service.generate_burning_ship(burning_ship_request);
+        let burning_ship_pixels_per_ms = (width * height) as f64 / burning_ship_response.computation_time_ms as f64;
+        fractal_service.recycle(burning_ship_response.data);
+
+        // The same Mandelbrot view again at each asked-for precision, reporting what each one actually ran at
+        let precision_comparison: Vec<serde_json::Value> = precisions
+            .iter()
+            .map(|&precision| {
+                let response = fractal_service.render(FractalRequest { precision, ..mandelbrot_request.clone() });
+                let pixels_per_ms = (width * height) as f64 / response.computation_time_ms.max(1) as f64;
+                fractal_service.recycle(response.data);
+                serde_json::json!({
+                    "requested": precision,
+                    "effective": response.precision,
+                    "renderer": response.renderer,
+                    "computation_time_ms": response.computation_time_ms,
+                    "pixels_per_ms": pixels_per_ms,
+                })
+            })
+            .collect();
 
         benchmark_results.push(serde_json::json!({
             "complexity": complexity,
@@ -276,38 +953,575 @@ pub async fn benchmark_generation(
                                                      "computation_time_ms": julia_response.computation_time_ms,
                                                      "pixels_per_ms": julia_pixels_per_ms,
                                                      "performance_rating": calculate_performance_rating(julia_pixels_per_ms)
-                                                 }
+                                                 },
+                                                 "burning_ship": {
+                                                     "computation_time_ms": burning_ship_response.computation_time_ms,
+                                                     "pixels_per_ms": burning_ship_pixels_per_ms,
+                                                     "performance_rating": calculate_performance_rating(burning_ship_pixels_per_ms)
+                                                 },
+                                                 "precision_comparison": precision_comparison
         }));
     }
 
-    // System information for context
-    let system_info = app_state.performance_service.get_system_info().await?;
+    // The same medium Mandelbrot drawn repeatedly, recycling its buffers against allocating them every time
+    let pool_request = FractalRequest {
+        center_x: -0.5,
+        center_y: 0.0,
+        zoom: 1.0,
+        max_iterations: 200,
+        ..FractalRequest::new(FractalType::Mandelbrot, 512, 512)
+    };
+    let buffer_pool = fractal_service.benchmark_buffer_pool(&pool_request, BUFFER_POOL_BENCHMARK_RENDERS);
 
-    let benchmark_summary = serde_json::json!({
-        "benchmark_results": benchmark_results,
-        "system_context": {
-            "cpu_model": system_info["hardware"]["cpu"]["model"].as_str().unwrap_or_default(),
-            "cpu_cores": system_info["hardware"]["cpu"]["cores"].as_u64().unwrap_or_default(),
-            "memory_total_gb": system_info["hardware"]["memory"]["total_gb"].as_f64().unwrap_or_default(),
-            "rust_version": env!("CARGO_PKG_VERSION"),
-                                              "parallel_processing": true,
-                                              "simd_optimized": cfg!(target_feature = "avx2")
+    // The same view again on each strategy, from one scalar thread up to the GPU
+    let strategy_comparison = fractal_service.compare_strategies(&pool_request, STRATEGY_BENCHMARK_RUNS);
+
+    (benchmark_results, buffer_pool, pool_request, strategy_comparison)
+}
+
+// Helper functions for performance tracking and analysis
+
+/// Render a request and do the bookkeeping every fractal endpoint shares
+/// I'm measuring, storing, publishing metrics, and persisting here so each endpoint only has to settle its parameters
+pub(crate) async fn render_and_record(
+    app_state: &AppState,
+    client: &RenderClient,
+    session: Option<Session>,
+    request: FractalRequest,
+    parameters: serde_json::Value,
+    output_format: fractal_models::OutputFormat,
+) -> Result<Response> {
+    let type_name = request.fractal_type.name();
+    let total_pixels = request.width * request.height;
+
+    // Record system state before computation
+    let start_memory = get_memory_usage();
+    let start_cpu = get_cpu_usage().await;
+
+    // Serve an identical earlier render from the cache, or generate it using our high-performance service
+    // A client that goes away drops this future, and the guard stops the render it left behind
+    let cancel = CancelToken::new();
+    let _cancel_on_drop = cancel.cancel_on_drop();
+    let response = match app_state.fractal_cache.get(&request).await {
+        Some(cached) => cached,
+        None => {
+            // Like a thumbnail, a cached render costs the client nothing
+            charge_render_cost(app_state, client, &request, false).await?;
+            let rendered = app_state.fractal_service.render_async(request.clone(), cancel).await?;
+            app_state.fractal_cache.put(&request, &rendered).await;
+            rendered
+        }
+    };
+
+    // Calculate performance metrics
+    let end_memory = get_memory_usage();
+    let end_cpu = get_cpu_usage().await;
+
+    let pixels_per_second = total_pixels as f64 / (response.computation_time_ms as f64 / 1000.0);
+    let memory_delta = end_memory - start_memory;
+    let cpu_delta = end_cpu - start_cpu;
+
+    // Store computation in database for analytics, and update real-time performance metrics; a cache hit computed nothing
+    if !response.cache_hit {
+        if let Err(e) = store_fractal_computation(app_state, &request, &response, memory_delta, cpu_delta).await {
+            warn!("Failed to store fractal computation: {}", e);
+        }
+
+        if let Err(e) = app_state.metrics.record_fractal_generation(type_name, response.computation_time_ms as f64, pixels_per_second).await {
+            warn!("Failed to record {} generation metrics: {}", type_name, e);
+        }
+    }
+
+    let image = persist_render(app_state, &response, &parameters).await;
+    record_session_history(app_state, &client.tenant.slug, session, &request, &response, &parameters, image.as_ref()).await;
+
+    info!("{} generation completed in {}ms (cache hit: {})", type_name, response.computation_time_ms, response.cache_hit);
+    if output_format != fractal_models::OutputFormat::Raw {
+        return encoded_response(&app_state.fractal_service, response, request.max_iterations, output_format, image).await;
+    }
+
+    Ok(Json(FractalApiResponse {
+        data: response.data,
+        width: response.width,
+        height: response.height,
+        computation_time_ms: response.computation_time_ms,
+        zoom_level: response.zoom_level,
+        renderer: response.renderer,
+        compute_backend: response.compute_backend,
+        cache_hit: response.cache_hit,
+        precision: response.precision,
+        threads: response.threads,
+        max_iterations: request.max_iterations,
+        interior: response.interior,
+        parameters,
+        performance_metrics: PerformanceMetrics {
+            pixels_per_second,
+            parallel_efficiency: calculate_parallel_efficiency(response.computation_time_ms, total_pixels),
+            memory_usage_mb: memory_delta,
+            cpu_utilization: cpu_delta,
         },
-        "performance_analysis": {
-            "language": "Rust",
-            "framework": "Rayon parallel processing",
-            "optimization_level": "Maximum (-O3, LTO)",
-                                              "memory_allocator": if cfg!(feature = "jemalloc") { "jemalloc" } else { "system" }
+        image,
+    })
+    .into_response())
+}
+
+/// The render as an image body in the requested format, its timing and renderer moved into headers
+/// I'm recycling the raw pixels once they're encoded, since nothing reads them after that
+async fn encoded_response(
+    fractal_service: &FractalService,
+    response: FractalResponse,
+    max_iterations: u32,
+    output_format: fractal_models::OutputFormat,
+    image: Option<StoredImage>,
+) -> Result<Response> {
+    let (width, height) = (response.width, response.height);
+    let data = response.data;
+    let (encoded, data) = tokio::task::spawn_blocking(move || (encode_image(output_format, width, height, &data), data))
+        .await
+        .map_err(|e| AppError::internal(format!("Image encoding task failed: {}", e)))?;
+    fractal_service.recycle(data);
+    let encoded = encoded?;
+
+    let mut headers = vec![
+        (header::CONTENT_TYPE, HeaderValue::from_static(output_format.content_type())),
+        (HeaderName::from_static(COMPUTATION_TIME_HEADER), HeaderValue::from(response.computation_time_ms as u64)),
+        (HeaderName::from_static(RENDERER_HEADER), HeaderValue::from_static(response.renderer)),
+        (HeaderName::from_static(COMPUTE_BACKEND_HEADER), HeaderValue::from_static(response.compute_backend.name())),
+        (HeaderName::from_static(CACHE_HIT_HEADER), HeaderValue::from_static(if response.cache_hit { "true" } else { "false" })),
+        (HeaderName::from_static(PRECISION_HEADER), HeaderValue::from_static(response.precision.name())),
+        (HeaderName::from_static(THREADS_HEADER), HeaderValue::from(response.threads)),
+        (HeaderName::from_static(MAX_ITERATIONS_HEADER), HeaderValue::from(max_iterations)),
+        (HeaderName::from_static(SKIPPED_PIXELS_HEADER), HeaderValue::from(response.interior.skipped())),
+    ];
+    if let Some(url) = image.and_then(|image| HeaderValue::from_str(&image.url).ok()) {
+        headers.push((HeaderName::from_static(IMAGE_URL_HEADER), url));
+    }
+
+    let mut http_response = encoded.into_response();
+    for (name, value) in headers {
+        http_response.headers_mut().insert(name, value);
+    }
+    Ok(http_response)
+}
+
+/// Stream the view's raw escape counts as a download, for analysis outside the API
+/// I'm sending an rle export's zstd as Content-Encoding when the client accepts it, so ordinary HTTP clients hand over the bare runs
+async fn export_escapes(
+    app_state: &AppState,
+    client: &RenderClient,
+    request_headers: &HeaderMap,
+    request: FractalRequest,
+    format: fractal_models::EscapeExport,
+) -> Result<Response> {
+    charge_render_cost(app_state, client, &request, false).await?;
+    let (width, height) = (request.width, request.height);
+    let filename = format!("{}-{}x{}.{}", request.fractal_type.name(), width, height, format.extension());
+    let fractal_service = app_state.fractal_service.clone();
+    let span = tracing::Span::current();
+    let buffer = tokio::task::spawn_blocking(move || span.in_scope(|| fractal_service.escape_buffer(&request)))
+        .await
+        .map_err(|e| AppError::FractalComputationError(format!("Export task failed: {}", e)))?;
+
+    let disposition = HeaderValue::from_str(&format!("attachment; filename=\"{}\"", filename))
+        .unwrap_or_else(|_| HeaderValue::from_static("attachment"));
+    let shape = HeaderValue::from_str(&format!("{},{}", height, width))
+        .map_err(|e| AppError::internal(format!("Invalid export shape header: {}", e)))?;
+    let rle = format == fractal_models::EscapeExport::Rle;
+    let zstd_encoded = rle
+        && request_headers
+            .get(header::ACCEPT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(accepts_zstd);
+    let content_type = if zstd_encoded { RLE_CONTENT_TYPE } else { format.content_type() };
+
+    let mut headers = vec![
+        (header::CONTENT_TYPE, HeaderValue::from_static(content_type)),
+        (header::CONTENT_DISPOSITION, disposition),
+        (HeaderName::from_static(EXPORT_DTYPE_HEADER), HeaderValue::from_static(dtype_name(&buffer))),
+        (HeaderName::from_static(EXPORT_SHAPE_HEADER), shape),
+    ];
+    if rle {
+        headers.push((HeaderName::from_static(EXPORT_DECODE_HEADER), HeaderValue::from_static(RLE_DECODING)));
+        headers.push((header::VARY, HeaderValue::from_static("accept-encoding")));
+    }
+    if zstd_encoded {
+        headers.push((header::CONTENT_ENCODING, HeaderValue::from_static("zstd")));
+    }
+
+    let body = Body::from_stream(export_stream(buffer, width, format).map(|chunk| chunk.map_err(std::io::Error::other)));
+    let mut response = (StatusCode::OK, body).into_response();
+    for (name, value) in headers {
+        response.headers_mut().insert(name, value);
+    }
+    Ok(response)
+}
+
+/// Load the requested preset (if any) and the palette to render with
+/// I'm letting a palette chosen in the request win over the palette a preset points at
+async fn resolve_preset_and_palette(
+    app_state: &AppState,
+    tenant_id: &str,
+    preset_id: Option<Uuid>,
+    options: &RenderOptions,
+) -> Result<(PresetParameters, Option<Palette>)> {
+    let preset = match preset_id {
+        Some(id) => Some(app_state.palette_service.get_preset(tenant_id, id).await?),
+        None => None,
+    };
+
+    let palette = match app_state.palette_service.resolve_palette(tenant_id, options.palette_id, options.palette.as_deref(), options.gradient.as_deref()).await? {
+        Some(palette) => Some(palette),
+        None => match preset.as_ref().and_then(|p| p.palette_id) {
+            Some(id) => Some(app_state.palette_service.get_palette(tenant_id, id).await?),
+            None => None,
         },
-        "benchmark_timestamp": chrono::Utc::now(),
-                                              "total_benchmarks": benchmark_results.len()
-    });
+    };
 
-    info!("Benchmark suite completed with {} scenarios", benchmark_results.len());
-    Ok(Json(benchmark_summary))
+    Ok((preset.map(|p| p.parameters).unwrap_or_default(), palette))
 }
 
-// Helper functions for performance tracking and analysis
+/// Validate, render, and record a single batch line
+async fn render_batch_item(
+    app_state: &AppState,
+    user: Option<&UserAuth>,
+    client: &RenderClient,
+    line_no: usize,
+    line: &[u8],
+) -> Result<BatchItemResult> {
+    let item: fractal_models::FractalRequest = serde_json::from_slice(line)
+        .map_err(|e| AppError::bad_request(format!("Invalid fractal request: {}", e)))?;
+    let request = engine_request(app_state, &client.tenant, item).await?;
+    let type_name = request.fractal_type.name();
+    charge_render_quota(app_state, user, &request).await?;
+
+    // Rendering is CPU bound, so it runs on the render pool while the stream is being polled.
+    // A client that disconnects drops this future, and the guard stops the render it left behind
+    let cancel = CancelToken::new();
+    let _cancel_on_drop = cancel.cancel_on_drop();
+    let response = match app_state.fractal_cache.get(&request).await {
+        Some(cached) => cached,
+        None => {
+            charge_render_cost(app_state, client, &request, false).await?;
+            let rendered = app_state.fractal_service.render_async(request.clone(), cancel).await?;
+            app_state.fractal_cache.put(&request, &rendered).await;
+
+            if let Err(e) = store_fractal_computation(app_state, &request, &rendered, 0.0, 0.0).await {
+                warn!("Failed to store fractal computation: {}", e);
+            }
+
+            let pixels_per_second = (request.width * request.height) as f64 / (rendered.computation_time_ms.max(1) as f64 / 1000.0);
+            if let Err(e) = app_state.metrics.record_fractal_generation(type_name, rendered.computation_time_ms as f64, pixels_per_second).await {
+                warn!("Failed to record {} batch generation metrics: {}", type_name, e);
+            }
+            rendered
+        }
+    };
+
+    let parameters = render_parameters(&request);
+    let image = persist_render(app_state, &response, &parameters).await;
+
+    Ok(BatchItemResult {
+        line: line_no,
+        status: "ok",
+        fractal_type: Some(type_name),
+        computation_time_ms: Some(response.computation_time_ms),
+        cache_hit: Some(response.cache_hit),
+        precision: Some(response.precision),
+        max_iterations: Some(request.max_iterations),
+        image,
+        error: None,
+    })
+}
+
+/// Validate a JSON fractal request and resolve it into the engine's form, palette included from the tenant's own
+pub(crate) async fn engine_request(app_state: &AppState, tenant: &CurrentTenant, item: fractal_models::FractalRequest) -> Result<FractalRequest> {
+    use validator::Validate;
+
+    item.validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+    let options = &item.options;
+    options.refuse("JSON fractal requests", &["output_format"])?;
+
+    let palette = app_state
+        .palette_service
+        .resolve_palette(&tenant.slug, options.palette_id, options.palette.as_deref(), options.gradient.as_deref())
+        .await?;
+    if let Some(trap) = &options.orbit_trap {
+        trap.validate()?;
+    }
+
+    // The same clamps the query endpoints apply, against this tenant's limits
+    let limits = tenant.config(&app_state.live_config);
+    let (antialiasing, max_width, max_height) = antialiasing_limits(options.antialiasing, &limits);
+    let zoom = item.zoom.clamp(0.1, limits.fractal_max_zoom);
+    let fractal_type = engine_fractal_type(&limits, &item.fractal_type)?;
+
+    Ok(FractalRequest {
+        center_x: item.center_x.clamp(-2.0, 2.0),
+        center_y: item.center_y.clamp(-2.0, 2.0),
+        zoom,
+        max_iterations: choose_max_iterations(&limits, item.auto_iterations, Some(item.max_iterations), zoom),
+        palette,
+        coloring_mode: options.coloring_mode.unwrap_or_default(),
+        orbit_trap: options.orbit_trap,
+        antialiasing: Some(antialiasing),
+        precision: options.precision.unwrap_or_default(),
+        threads: options.threads,
+        interior_coloring: options.interior_coloring.unwrap_or_default(),
+        ..FractalRequest::new(fractal_type, item.width.clamp(64, max_width), item.height.clamp(64, max_height))
+    })
+}
+
+/// The engine's form of a requested fractal type, refusing multibrot powers the limits don't allow
+pub(crate) fn engine_fractal_type(limits: &Config, fractal_type: &fractal_models::FractalType) -> Result<FractalType> {
+    Ok(match *fractal_type {
+        fractal_models::FractalType::Mandelbrot => FractalType::Mandelbrot,
+        fractal_models::FractalType::Julia { c_real, c_imag } => FractalType::Julia { c_real, c_imag },
+        fractal_models::FractalType::BurningShip => FractalType::BurningShip,
+        fractal_models::FractalType::Tricorn => FractalType::Tricorn,
+        fractal_models::FractalType::Multibrot { power } => {
+            check_multibrot_power(limits, power)?;
+            FractalType::Multibrot { power }
+        }
+    })
+}
+
+/// The supersampling factor a request gets, with the largest output width and height it leaves room for
+/// I'm shrinking the size limits by the factor so the view actually drawn never passes MAX_FRACTAL_WIDTH or MAX_FRACTAL_HEIGHT,
+/// and capping the factor where the limits couldn't fit even the smallest view
+pub(crate) fn antialiasing_limits(requested: Option<u8>, limits: &Config) -> (u8, u32, u32) {
+    let room = (limits.fractal_max_width.min(limits.fractal_max_height) / 64).clamp(1, u32::from(MAX_ANTIALIASING));
+    let factor = requested.unwrap_or(1).clamp(1, room as u8);
+    (factor, limits.fractal_max_width / u32::from(factor), limits.fractal_max_height / u32::from(factor))
+}
+
+/// The iteration count a render runs at: scaled to the zoom when the client asks for auto_iterations,
+/// otherwise the one it or its preset gave, defaulting to 100, held within 50 and MAX_FRACTAL_ITERATIONS either way
+pub(crate) fn choose_max_iterations(limits: &Config, auto: bool, requested: Option<u32>, zoom: f64) -> u32 {
+    if auto {
+        limits.auto_iterations(zoom)
+    } else {
+        requested.unwrap_or(100).clamp(50, limits.fractal_max_iterations)
+    }
+}
+
+/// Precisions from a comma-separated list such as `f32,f64,extended`
+fn parse_precisions(list: &str) -> Result<Vec<Precision>> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            serde_json::from_value(serde_json::Value::String(name.to_ascii_lowercase()))
+                .map_err(|_| AppError::ValidationError(format!("Unknown precision '{}'; expected auto, f32, f64, or extended", name)))
+        })
+        .collect()
+}
+
+/// The fractal a thumbnail draws, named by the query or else its preset, with the Julia constant or multibrot power it needs
+fn thumbnail_fractal_type(limits: &Config, params: &ThumbnailQuery, preset: &PresetParameters) -> Result<FractalType> {
+    let name = params.fractal_type.as_deref().or(preset.fractal_type.as_deref()).unwrap_or("mandelbrot");
+    Ok(match name {
+        "mandelbrot" => FractalType::Mandelbrot,
+        "julia" => FractalType::Julia {
+            c_real: params.c_real.or(preset.c_real).unwrap_or(-0.7).clamp(-2.0, 2.0),
+            c_imag: params.c_imag.or(preset.c_imag).unwrap_or(0.27015).clamp(-2.0, 2.0),
+        },
+        "burning_ship" => FractalType::BurningShip,
+        "tricorn" => FractalType::Tricorn,
+        "multibrot" => {
+            let power = params.power.or(preset.power).unwrap_or_else(|| {
+                DEFAULT_MULTIBROT_POWER.clamp(limits.fractal_min_multibrot_power, limits.fractal_max_multibrot_power)
+            });
+            check_multibrot_power(limits, power)?;
+            FractalType::Multibrot { power }
+        }
+        other => return Err(AppError::ValidationError(format!("Unsupported fractal type: {}", other))),
+    })
+}
+
+/// Width and height of a thumbnail whose longest edge is `size`, keeping the preset's aspect ratio when it has one
+fn thumbnail_dimensions(size: u32, preset_width: Option<u32>, preset_height: Option<u32>) -> (u32, u32) {
+    match (preset_width, preset_height) {
+        (Some(width), Some(height)) if width > 0 && height > 0 => {
+            if width >= height {
+                (size, (size * height / width).max(THUMBNAIL_MIN_SIZE))
+            } else {
+                ((size * width / height).max(THUMBNAIL_MIN_SIZE), size)
+            }
+        }
+        _ => (size, size),
+    }
+}
+
+/// Refuse a multibrot power outside MIN_MULTIBROT_POWER..=MAX_MULTIBROT_POWER
+fn check_multibrot_power(limits: &Config, power: f64) -> Result<()> {
+    if (limits.fractal_min_multibrot_power..=limits.fractal_max_multibrot_power).contains(&power) {
+        Ok(())
+    } else {
+        Err(AppError::ValidationError(format!(
+            "Multibrot power must be between {} and {}",
+            limits.fractal_min_multibrot_power, limits.fractal_max_multibrot_power
+        )))
+    }
+}
+
+/// Persist the rendered pixels so the result can be shared without re-rendering
+/// I'm treating storage failures as non-fatal since the caller still gets the raw pixels
+pub(crate) async fn persist_render(
+    app_state: &AppState,
+    response: &FractalResponse,
+    parameters: &serde_json::Value,
+) -> Option<StoredImage> {
+    if !app_state.image_service.is_enabled() {
+        return None;
+    }
+
+    match app_state.image_service
+        .persist_rgba(response.width, response.height, response.data.clone(), parameters.clone())
+        .await
+    {
+        Ok(image) => Some(image),
+        Err(e) => {
+            warn!("Failed to persist rendered image: {}", e);
+            None
+        }
+    }
+}
+
+/// Refuse a render estimated to cost more than one request may, or than its client has left this minute, otherwise spending its cost
+/// I'm letting queued renders past the per-request cap, which only exists to keep synchronous requests short; they still spend the client's budget
+pub(crate) async fn charge_render_cost(app_state: &AppState, client: &RenderClient, request: &FractalRequest, queued: bool) -> Result<CostEstimate> {
+    charge_cost_estimate(app_state, client, CostEstimate::of(request), queued, Some(FRACTAL_JOBS_PATH)).await
+}
+
+/// Spend an already worked out estimate, for renders that aren't a single FractalRequest; `jobs_url` is where an over-cap render could be queued instead
+pub(crate) async fn charge_cost_estimate(
+    app_state: &AppState,
+    client: &RenderClient,
+    estimate: CostEstimate,
+    queued: bool,
+    jobs_url: Option<&str>,
+) -> Result<CostEstimate> {
+    let limits = client.tenant.config(&app_state.live_config);
+    let (max_request_cost, budget) = (limits.fractal_max_request_cost, limits.fractal_client_cost_per_minute);
+    let refusal = |message: String, remaining: Option<u32>, retry_after: Option<u64>| AppError::RenderBudgetError {
+        message,
+        estimate: serde_json::json!({
+            "estimate": estimate,
+            "max_request_cost": max_request_cost,
+            "client_cost_per_minute": budget,
+            "remaining": remaining,
+            "jobs_url": jobs_url,
+        }),
+        retry_after,
+    };
+
+    if !queued && max_request_cost > 0 && estimate.cost > max_request_cost {
+        let queue_hint = jobs_url.map(|url| format!(", or submit it to {}", url)).unwrap_or_default();
+        return Err(refusal(
+            format!(
+                "Estimated cost {} is over the {} allowed per render; lower the size, antialiasing, or iterations{}",
+                estimate.cost, max_request_cost, queue_hint
+            ),
+            None,
+            None,
+        ));
+    }
+    let Some(key) = client.budget_key("render_cost").filter(|_| budget > 0) else {
+        return Ok(estimate);
+    };
+    if estimate.cost > u64::from(budget) {
+        return Err(refusal(
+            format!("Estimated cost {} is over the {} a client may spend per minute", estimate.cost, budget),
+            None,
+            None,
+        ));
+    }
+
+    let decision = app_state.rate_limiter.charge(&key, estimate.cost as u32, budget, RENDER_COST_WINDOW).await;
+    if !decision.allowed {
+        return Err(refusal(
+            format!("Estimated cost {} is more than the {} left of this minute's render budget", estimate.cost, decision.remaining),
+            Some(decision.remaining),
+            Some(decision.reset_after.as_secs_f64().ceil().max(1.0) as u64),
+        ));
+    }
+    Ok(estimate)
+}
+
+/// Count an expensive render against the signed-in caller's daily quota; anonymous renders are only rate limited
+pub(crate) async fn charge_render_quota(app_state: &AppState, user: Option<&UserAuth>, request: &FractalRequest) -> Result<()> {
+    match user {
+        Some(UserAuth(user)) => {
+            // Supersampled renders draw factor² samples per pixel, and are charged for all of them
+            let pixels = u64::from(request.width) * u64::from(request.height) * u64::from(request.supersampling().pow(2));
+            app_state.user_service.charge_render(user, pixels).await
+        }
+        None => Ok(()),
+    }
+}
+
+/// Remember the render in the visitor's session history
+/// I'm only logging failures since losing a history entry shouldn't fail the render itself
+async fn record_session_history(
+    app_state: &AppState,
+    tenant_id: &str,
+    session: Option<Session>,
+    request: &FractalRequest,
+    response: &FractalResponse,
+    parameters: &serde_json::Value,
+    image: Option<&StoredImage>,
+) {
+    let Some(session) = session else { return };
+    if let Err(e) = app_state.session_service.record(tenant_id, session.id, request, response, parameters, image).await {
+        warn!("Failed to record history for session {}: {}", session.id, e);
+    }
+}
+
+/// Persist one benchmark_results row per strategy, with scalar as every row's baseline
+async fn store_strategy_benchmarks(
+    app_state: &AppState,
+    request: &FractalRequest,
+    comparison: &StrategyComparison,
+    system_info: &serde_json::Value,
+) -> Result<()> {
+    let baseline_ms = comparison.timings.first().map(|scalar| scalar.computation_time_ms.round() as i32);
+
+    for timing in &comparison.timings {
+        sqlx::query(
+            r#"
+            INSERT INTO benchmark_results (
+                benchmark_type, benchmark_name, parameters, results, duration_ms, iterations,
+                baseline_duration_ms, performance_ratio, rust_version, cpu_model, cpu_cores, memory_total_bytes)
+            VALUES ('fractal_generation', $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            "#,
+        )
+        .bind(format!("strategy:{}", timing.strategy.name()))
+        .bind(serde_json::json!({
+            "fractal_type": request.fractal_type.name(),
+            "width": request.width,
+            "height": request.height,
+            "center_x": request.center_x,
+            "center_y": request.center_y,
+            "zoom": request.zoom,
+            "max_iterations": request.max_iterations,
+        }))
+        .bind(serde_json::to_value(timing).unwrap_or_default())
+        .bind(timing.computation_time_ms.round() as i32)
+        .bind(comparison.runs as i32)
+        .bind(baseline_ms)
+        .bind(1.0 / timing.speedup)
+        .bind(crate::build_info::RUST_VERSION)
+        .bind(system_info["cpu_model"].as_str())
+        .bind(system_info["cpu_cores"].as_i64().map(|cores| cores as i32))
+        .bind(system_info["memory_total_gb"].as_f64().map(|gb| (gb * 1024.0 * 1024.0 * 1024.0) as i64))
+        .execute(&app_state.db_pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+    }
+
+    Ok(())
+}
 
 async fn store_fractal_computation(
     app_state: &AppState,
@@ -316,10 +1530,7 @@ async fn store_fractal_computation(
     memory_delta: f64,
     cpu_delta: f64,
 ) -> Result<()> {
-    let fractal_type_str = match request.fractal_type {
-        FractalType::Mandelbrot => "mandelbrot",
-        FractalType::Julia { .. } => "julia",
-    };
+    let fractal_type_str = request.fractal_type.name();
 
     sqlx::query(
         r#"
@@ -342,8 +1553,14 @@ async fn store_fractal_computation(
     .bind(memory_delta)
     .bind(serde_json::json!({
         "fractal_type": fractal_type_str,
+        "palette_id": request.palette.as_ref().and_then(Palette::reference_id),
+        "coloring_mode": request.coloring_mode.name(),
+        "orbit_trap": request.orbit_trap,
+        "antialiasing": request.supersampling(),
+        "interior_coloring": request.interior_coloring.name(),
         "parameters": match request.fractal_type {
             FractalType::Julia { c_real, c_imag } => serde_json::json!({"c_real": c_real, "c_imag": c_imag}),
+            FractalType::Multibrot { power } => serde_json::json!({"power": power}),
             _ => serde_json::json!({})
         }
     }))
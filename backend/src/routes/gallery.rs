@@ -0,0 +1,213 @@
+/*
+ * Fractal gallery endpoints: save a view under a short id, browse recent or popular saves, and open, re-render, or fetch the image of one by id.
+ * I'm leaving saves open to anyone with the link, since sharing is the point; renders still spend the caller's cost budget like any other.
+ */
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use uuid::Uuid;
+
+use crate::{
+    middleware::{
+        rate_limit::RenderClient,
+        session::Session,
+        signed_urls::{signed_cache_control, SignedQuery, UrlSigner},
+        tenant::CurrentTenant,
+        users::UserAuth,
+    },
+    models::{
+        gallery::{
+            is_valid_saved_fractal_id, shared_image_path, GalleryQuery, RenderSavedQuery, SaveFractalInput, SavedFractal,
+            SavedFractalView,
+        },
+        ApiResponse,
+    },
+    routes::fractals::{charge_render_cost, charge_render_quota, engine_request, persist_render, render_and_record},
+    services::{cancel::CancelToken, gallery_service::saved_request, image_service::{encode_png, PNG_CONTENT_TYPE}},
+    utils::error::{AppError, Result},
+    AppState,
+};
+
+/// How long browsers may keep a save's image; a save never changes, so neither does its render
+const SAVED_IMAGE_MAX_AGE: i64 = 86400;
+
+/// Save a render's parameters under a new share id
+pub async fn save_fractal(
+    State(app_state): State<AppState>,
+    tenant: CurrentTenant,
+    user: Option<UserAuth>,
+    Json(input): Json<SaveFractalInput>,
+) -> Result<(StatusCode, Json<ApiResponse<SavedFractalView>>)> {
+    use validator::Validate;
+
+    input.validate()
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+    let request = engine_request(&app_state, &tenant, input.request).await?;
+    let saved = app_state
+        .gallery_service
+        .save(&tenant.slug, input.title.as_deref(), &request, user.map(|UserAuth(user)| user.id))
+        .await?;
+    Ok((StatusCode::CREATED, Json(ApiResponse::new(saved_view(&app_state, &tenant, saved)))))
+}
+
+/// Recent or popular saves, optionally of one fractal type
+pub async fn list_saved_fractals(
+    State(app_state): State<AppState>,
+    tenant: CurrentTenant,
+    Query(query): Query<GalleryQuery>,
+) -> Result<Json<ApiResponse<Vec<SavedFractalView>>>> {
+    let saved = app_state.gallery_service.list(&tenant.slug, &query).await?;
+    Ok(Json(ApiResponse::new(saved.into_iter().map(|saved| saved_view(&app_state, &tenant, saved)).collect())))
+}
+
+/// Open a save by its share id, counting the view
+pub async fn get_saved_fractal(
+    State(app_state): State<AppState>,
+    tenant: CurrentTenant,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<SavedFractalView>>> {
+    check_id(&id)?;
+    let saved = app_state.gallery_service.open(&tenant.slug, &id).await?;
+    Ok(Json(ApiResponse::new(saved_view(&app_state, &tenant, saved))))
+}
+
+/// Render a save again through the full render path, in any output format
+pub async fn render_saved_fractal(
+    State(app_state): State<AppState>,
+    session: Option<Session>,
+    user: Option<UserAuth>,
+    client: RenderClient,
+    Path(id): Path<String>,
+    Query(query): Query<RenderSavedQuery>,
+) -> Result<Response> {
+    check_id(&id)?;
+    let saved = app_state.gallery_service.open(&client.tenant.slug, &id).await?;
+    let request = saved_request(&saved)?;
+    charge_render_quota(&app_state, user.as_ref(), &request).await?;
+
+    let parameters = saved_parameters(&saved);
+    render_and_record(&app_state, &client, session, request, parameters, query.output_format.unwrap_or_default()).await
+}
+
+/// The save's image as PNG for the account that saved it, drawn and stored the first time it's asked for and read back from storage after that
+/// I'm answering anyone else with 404, so the signed link's expiry is the only way the image reaches someone without the key
+pub async fn get_saved_fractal_image(
+    State(app_state): State<AppState>,
+    UserAuth(user): UserAuth,
+    client: RenderClient,
+    Path(id): Path<String>,
+) -> Result<Response> {
+    let cache_control = format!("private, max-age={}", SAVED_IMAGE_MAX_AGE);
+    saved_image_response(&app_state, &client, &id, Some(user.id), &cache_control).await
+}
+
+/// The same image behind a signed share link; the signed URL middleware has already checked the signature and expiry
+/// I'm keeping it out of shared caches and capping its lifetime at the link's, so an expired link stops serving it
+pub async fn get_shared_fractal_image(
+    State(app_state): State<AppState>,
+    client: RenderClient,
+    Path(id): Path<String>,
+    Query(query): Query<SignedQuery>,
+) -> Result<Response> {
+    let cache_control = signed_cache_control(query.expires, SAVED_IMAGE_MAX_AGE, chrono::Utc::now());
+    saved_image_response(&app_state, &client, &id, None, &cache_control).await
+}
+
+/// The PNG of save `id`, limited to saves made by `owner` when one is given
+async fn saved_image_response(
+    app_state: &AppState,
+    client: &RenderClient,
+    id: &str,
+    owner: Option<Uuid>,
+    cache_control: &str,
+) -> Result<Response> {
+    let id = id.strip_suffix(".png").unwrap_or(id);
+    check_id(id)?;
+    let saved = app_state.gallery_service.get(&client.tenant.slug, id).await?;
+    if owner.is_some_and(|owner| saved.user_id != Some(owner)) {
+        return Err(AppError::not_found(format!("saved fractal {}", id)));
+    }
+
+    let stored = match &saved.image_id {
+        Some(image_id) => app_state.image_service.load(image_id).await?,
+        None => None,
+    };
+    let png = match stored {
+        Some(png) => png,
+        None => render_saved_image(app_state, client, &saved).await?,
+    };
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, HeaderValue::from_static(PNG_CONTENT_TYPE)),
+            (header::CACHE_CONTROL, HeaderValue::from_str(cache_control).map_err(|e| AppError::internal(e.to_string()))?),
+        ],
+        png,
+    )
+        .into_response())
+}
+
+/// Draw a save and store the PNG against it when image storage is on, encoding it here when it's off
+async fn render_saved_image(app_state: &AppState, client: &RenderClient, saved: &SavedFractal) -> Result<Vec<u8>> {
+    let request = saved_request(saved)?;
+
+    let response = match app_state.fractal_cache.get(&request).await {
+        Some(cached) => cached,
+        None => {
+            charge_render_cost(app_state, client, &request, false).await?;
+            let rendered = app_state.fractal_service.render_async(request.clone(), CancelToken::new()).await?;
+            app_state.fractal_cache.put(&request, &rendered).await;
+            rendered
+        }
+    };
+
+    if let Some(image) = persist_render(app_state, &response, &saved_parameters(saved)).await {
+        app_state.gallery_service.set_image(&client.tenant.slug, &saved.id, &image.id).await?;
+        if let Some(png) = app_state.image_service.load(&image.id).await? {
+            app_state.fractal_service.recycle(response.data);
+            return Ok(png);
+        }
+    }
+
+    let (width, height, data) = (response.width, response.height, response.data);
+    let (png, data) = tokio::task::spawn_blocking(move || (encode_png(width, height, &data), data))
+        .await
+        .map_err(|e| AppError::internal(format!("Image encoding task failed: {}", e)))?;
+    app_state.fractal_service.recycle(data);
+    png
+}
+
+/// A save with its share links, signing one for the image when IMAGE_SIGNING_KEY is set
+fn saved_view(app_state: &AppState, tenant: &CurrentTenant, saved: SavedFractal) -> SavedFractalView {
+    let mut view = SavedFractalView::from(saved);
+    if let Some(signer) = UrlSigner::from_config(&app_state.config) {
+        let path = shared_image_path(&tenant.path_prefix(), &view.saved.id);
+        let (url, expires_at) = signer.signed_url(&path, app_state.config.image_share_url_ttl, chrono::Utc::now());
+        view.signed_image_url = Some(url);
+        view.signed_image_expires_at = Some(expires_at);
+    }
+    view
+}
+
+/// Parameters recorded with a save's renders, pointing back at the save
+fn saved_parameters(saved: &SavedFractal) -> serde_json::Value {
+    serde_json::json!({
+        "saved_fractal_id": saved.id,
+        "fractal_type": saved.fractal_type,
+        "request": saved.request,
+    })
+}
+
+/// Refuse anything that can't be a share id before it reaches the database
+fn check_id(id: &str) -> Result<()> {
+    if is_valid_saved_fractal_id(id) {
+        Ok(())
+    } else {
+        Err(AppError::not_found(format!("saved fractal {}", id)))
+    }
+}
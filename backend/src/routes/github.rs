@@ -9,7 +9,6 @@ use axum::{
     response::Json as JsonResponse,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use tracing::{info, warn, error, debug};
 use uuid::Uuid;
 
@@ -18,6 +17,9 @@ use crate::{
         Repository, RepositoryDetailed, RepositoryCollection, RepositoryFilter,
         RepositorySort, CollectionStats, RateLimitInfo, calculate_collection_stats
     },
+    database::repositories::{self, RepositoryListQuery, RepositorySearchHit, RepositorySearchQuery, SortDirection},
+    middleware::CurrentTenant,
+    services::sync_service::SyncTrigger,
     utils::error::{AppError, Result},
     AppState,
 };
@@ -34,6 +36,8 @@ pub struct RepositoryQuery {
     pub is_fork: Option<bool>,
     pub is_archived: Option<bool>,
     pub search: Option<String>,
+    /// Comma-separated topics a repository must all carry
+    pub topics: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -66,6 +70,7 @@ pub struct CacheInfo {
 /// I'm providing a full-featured repository listing endpoint with performance optimization
 pub async fn get_repositories(
     State(app_state): State<AppState>,
+    tenant: CurrentTenant,
     Query(params): Query<RepositoryQuery>,
 ) -> Result<JsonResponse<RepositoryResponse>> {
     info!("Fetching repositories with params: {:?}", params);
@@ -75,40 +80,80 @@ pub async fn get_repositories(
     let per_page = params.per_page.unwrap_or(20).clamp(1, 100);
     let offset = (page - 1) * per_page;
 
-    // Get GitHub username from config
-    let username = &app_state.config.github_username;
+    let username = &tenant.github_username;
 
-    // Try to get fresh repositories from GitHub API
-    let repositories = match app_state.github_service.get_user_repositories(username).await {
-        Ok(repos) => {
-            // Store in database for caching
-            if let Err(e) = app_state.github_service.store_repositories_in_db(&app_state.db_pool, &repos).await {
-                warn!("Failed to store repositories in database: {}", e);
-            }
-            repos
-        }
+    let filter = create_filter_from_params(&params);
+    let sort = RepositorySort::from_param(params.sort.as_deref().unwrap_or("updated"));
+    let direction = SortDirection::parse(params.direction.as_deref().unwrap_or("desc"));
+
+    // I'm serving from the Postgres cache while it's fresh and only going to GitHub once it has expired
+    let mut cache_is_fresh = match repositories::count_fresh(&app_state.db_pool, username).await {
+        Ok(count) => count > 0,
         Err(e) => {
-            warn!("GitHub API failed, falling back to database cache: {}", e);
-            // Fallback to database cache
-            get_repositories_from_db(&app_state, username).await?
+            warn!("Failed to check repository cache freshness: {}", e);
+            false
         }
     };
 
-    // Apply filtering
-    let filter = create_filter_from_params(&params);
-    let filtered_repos = filter.apply(repositories);
+    let mut include_expired = false;
+    let mut uncached_repositories = None;
+    if !cache_is_fresh {
+        match app_state.sync_service.sync_if_stale(username, SyncTrigger::Read).await {
+            Ok(Some(outcome)) if !outcome.fully_stored() => {
+                warn!("Only {} of {} repositories were stored; serving this page from the API response", outcome.report.stored, outcome.report.fetched);
+                uncached_repositories = Some(outcome.repositories);
+            }
+            Ok(Some(_)) => {}
+            // Another request synced while this one waited
+            Ok(None) => cache_is_fresh = true,
+            Err(e) => {
+                warn!("GitHub API failed, falling back to database cache: {}", e);
+                include_expired = true;
+            }
+        }
+    }
 
-    // Apply sorting
-    let sorted_repos = apply_sorting(filtered_repos, &params);
+    let (paginated_repos, total_count, cache_info) = match uncached_repositories {
+        // The database write failed, so I'm paging through the fresh API response in memory
+        Some(repos) => {
+            let sorted_repos = apply_sorting(filter.apply(repos), &params);
+            let total_count = sorted_repos.len() as i64;
+            let page_repos = sorted_repos
+                .into_iter()
+                .skip(offset as usize)
+                .take(per_page as usize)
+                .collect::<Vec<_>>();
+            let cache_info = CacheInfo { cached: false, cache_age_seconds: 0, expires_in_seconds: 0 };
+            (page_repos, total_count, cache_info)
+        }
+        None => {
+            let list_query = RepositoryListQuery {
+                owner: username,
+                filter: &filter,
+                sort,
+                direction,
+                limit: per_page as i64,
+                offset: offset as i64,
+                include_expired,
+            };
+            let page_result = repositories::list_repositories(&app_state.db_pool, &list_query).await?;
+
+            let now = chrono::Utc::now();
+            let cache_info = CacheInfo {
+                cached: cache_is_fresh || include_expired,
+                cache_age_seconds: page_result.cache_updated_at
+                    .map(|updated| (now - updated).num_seconds().max(0))
+                    .unwrap_or(0),
+                expires_in_seconds: page_result.cache_expires_at
+                    .map(|expires| (expires - now).num_seconds().max(0))
+                    .unwrap_or(0),
+            };
+            (page_result.repositories, page_result.total_count, cache_info)
+        }
+    };
 
-    // Apply pagination
-    let total_count = sorted_repos.len() as i32;
+    let total_count = total_count as i32;
     let total_pages = (total_count + per_page - 1) / per_page;
-    let paginated_repos = sorted_repos
-        .into_iter()
-        .skip(offset as usize)
-        .take(per_page as usize)
-        .collect::<Vec<_>>();
 
     // Calculate statistics for the filtered set
     let statistics = calculate_collection_stats(&paginated_repos);
@@ -145,11 +190,7 @@ pub async fn get_repositories(
         },
         statistics,
         rate_limit,
-        cache_info: CacheInfo {
-            cached: false, // This could be enhanced to track actual cache usage
-            cache_age_seconds: 0,
-            expires_in_seconds: 3600,
-        },
+        cache_info,
     };
 
     info!(
@@ -162,19 +203,95 @@ pub async fn get_repositories(
     Ok(Json(response))
 }
 
+/// Longest search string accepted, to keep tsquery parsing cheap
+const MAX_SEARCH_QUERY_LENGTH: usize = 256;
+
+#[derive(Debug, Deserialize)]
+pub struct RepositorySearchParams {
+    pub q: String,
+    /// Defaults to the tenant's GitHub user; other tenants' owners are not searchable
+    pub owner: Option<String>,
+    pub page: Option<i32>,
+    pub per_page: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RepositorySearchResponse {
+    pub query: String,
+    pub results: Vec<RepositorySearchHit>,
+    pub pagination: PaginationInfo,
+}
+
+/// Full-text search over cached repositories, best matches first with highlighted snippets
+/// I'm searching only what's already cached so a search never spends GitHub API quota
+pub async fn search_repositories(
+    State(app_state): State<AppState>,
+    tenant: CurrentTenant,
+    Query(params): Query<RepositorySearchParams>,
+) -> Result<JsonResponse<RepositorySearchResponse>> {
+    let terms = params.q.trim();
+    if terms.is_empty() {
+        return Err(AppError::ValidationError("Search query 'q' must not be empty".to_string()));
+    }
+    if terms.len() > MAX_SEARCH_QUERY_LENGTH {
+        return Err(AppError::ValidationError(format!(
+            "Search query must be at most {} characters", MAX_SEARCH_QUERY_LENGTH
+        )));
+    }
+
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = params.per_page.unwrap_or(20).clamp(1, 100);
+    let owner = params.owner.as_deref().unwrap_or(&tenant.github_username);
+    tenant.ensure_owner(owner)?;
+    info!("Searching repositories of {} for {:?}", owner, terms);
+
+    let (results, total_count) = repositories::search_repositories(&app_state.db_pool, &RepositorySearchQuery {
+        owner: Some(owner),
+        terms,
+        limit: per_page as i64,
+        offset: ((page - 1) * per_page) as i64,
+    }).await?;
+
+    let total_count = total_count as i32;
+    let total_pages = (total_count + per_page - 1) / per_page;
+
+    Ok(Json(RepositorySearchResponse {
+        query: terms.to_string(),
+        results,
+        pagination: PaginationInfo {
+            current_page: page,
+            per_page,
+            total_pages,
+            total_count,
+            has_next_page: page < total_pages,
+            has_previous_page: page > 1,
+        },
+    }))
+}
+
 /// Get detailed information for a specific repository including README and analytics
 /// I'm providing comprehensive repository analysis with performance metrics and content
 pub async fn get_repository_details(
     State(app_state): State<AppState>,
+    tenant: CurrentTenant,
     Path((owner, name)): Path<(String, String)>,
 ) -> Result<JsonResponse<RepositoryDetailed>> {
     info!("Fetching detailed repository information for {}/{}", owner, name);
+    tenant.ensure_owner(&owner)?;
 
-    // Get detailed repository information
+    // Get detailed repository information, cached under the tenant's prefix
     let repository_details = app_state.github_service
+        .with_cache(tenant.cache.clone())
         .get_repository_details(&owner, &name)
         .await?;
 
+    // Sync only lists repositories, so the README reaches search once its details are viewed
+    if !repository_details.readme_content.is_empty() {
+        if let Err(e) = repositories::store_readme(&app_state.db_pool, &owner, &name, &repository_details.readme_content).await {
+            warn!("Failed to store README for {}/{}: {}", owner, name, e);
+        }
+    }
+
     // Update access metrics in database
     if let Err(e) = record_repository_access(&app_state, &owner, &name).await {
         warn!("Failed to record repository access: {}", e);
@@ -188,16 +305,19 @@ pub async fn get_repository_details(
 /// I'm providing detailed analytics that highlight the repository's characteristics
 pub async fn get_repository_stats(
     State(app_state): State<AppState>,
+    tenant: CurrentTenant,
     Path((owner, name)): Path<(String, String)>,
 ) -> Result<JsonResponse<serde_json::Value>> {
     info!("Fetching repository statistics for {}/{}", owner, name);
+    tenant.ensure_owner(&owner)?;
 
     // Get repository from database or API
-    let repo = match get_single_repository(&app_state, &owner, &name).await {
-        Ok(repo) => repo,
-        Err(_) => {
+    let repo = match repositories::find_by_name(&app_state.db_pool, &owner, &name).await {
+        Ok(Some(repo)) => repo,
+        _ => {
             // Try fetching from GitHub API
             let detailed = app_state.github_service
+                .with_cache(tenant.cache.clone())
                 .get_repository_details(&owner, &name)
                 .await?;
             detailed.basic
@@ -258,74 +378,96 @@ pub async fn get_repository_stats(
 /// I'm providing insights into technology usage patterns across the portfolio
 pub async fn get_language_distribution(
     State(app_state): State<AppState>,
+    tenant: CurrentTenant,
 ) -> Result<JsonResponse<serde_json::Value>> {
     info!("Calculating language distribution across repositories");
 
-    let username = &app_state.config.github_username;
+    let username = &tenant.github_username;
+    sync_repository_cache_if_stale(&app_state, username).await;
 
-    // Get all repositories
-    let repositories = match app_state.github_service.get_user_repositories(username).await {
-        Ok(repos) => repos,
-        Err(_) => get_repositories_from_db(&app_state, username).await?,
+    // I'm reading the precomputed view; percentages and averages are cheap enough to derive per request
+    let summary = repositories::collection_summary(&app_state.db_pool, username).await?;
+    let (languages, total_repositories, total_size, refreshed_at) = match summary {
+        Some(summary) => (summary.languages, summary.repository_count, summary.language_size_kb, Some(summary.refreshed_at)),
+        None => (Vec::new(), 0, 0, None),
     };
 
-    // Calculate language statistics
-    let mut language_stats: HashMap<String, LanguageStat> = HashMap::new();
-    let mut total_size: i64 = 0;
-
-    for repo in &repositories {
-        if repo.is_archived || repo.is_fork {
-            continue; // Skip archived and forked repositories for cleaner stats
-        }
-
-        total_size += repo.size_kb as i64;
-
-        if let Some(ref language) = repo.language {
-            let stat = language_stats.entry(language.clone()).or_insert(LanguageStat {
-                name: language.clone(),
-                repository_count: 0,
-                total_size_kb: 0,
-                total_stars: 0,
-                average_stars: 0.0,
-                percentage: 0.0,
-            });
-
-            stat.repository_count += 1;
-            stat.total_size_kb += repo.size_kb as i64;
-            stat.total_stars += repo.stargazers_count;
-        }
-    }
-
-    // Calculate percentages and averages
-    for stat in language_stats.values_mut() {
-        stat.percentage = if total_size > 0 {
-            (stat.total_size_kb as f64 / total_size as f64) * 100.0
-        } else { 0.0 };
-        stat.average_stars = if stat.repository_count > 0 {
-            stat.total_stars as f64 / stat.repository_count as f64
-        } else { 0.0 };
-    }
-
-    // Sort by usage (repository count)
-    let mut sorted_languages: Vec<_> = language_stats.into_values().collect();
-    sorted_languages.sort_by(|a, b| b.repository_count.cmp(&a.repository_count));
+    let sorted_languages: Vec<LanguageStat> = languages
+        .into_iter()
+        .map(|language| LanguageStat {
+            percentage: if total_size > 0 {
+                (language.total_size_kb as f64 / total_size as f64) * 100.0
+            } else { 0.0 },
+            average_stars: if language.repository_count > 0 {
+                language.total_stars as f64 / language.repository_count as f64
+            } else { 0.0 },
+            name: language.name,
+            repository_count: language.repository_count,
+            total_size_kb: language.total_size_kb,
+            total_stars: language.total_stars,
+        })
+        .collect();
 
     let response = serde_json::json!({
         "languages": sorted_languages,
         "summary": {
             "total_languages": sorted_languages.len(),
-            "total_repositories_analyzed": repositories.len(),
+            "total_repositories_analyzed": total_repositories,
             "total_size_kb": total_size,
             "most_used_language": sorted_languages.first().map(|l| &l.name),
             "language_diversity_score": calculate_diversity_score(&sorted_languages)
         },
-        "analysis_timestamp": chrono::Utc::now()
+        "analysis_timestamp": refreshed_at.unwrap_or_else(chrono::Utc::now)
     });
 
     info!("Language distribution calculated for {} languages", sorted_languages.len());
     Ok(Json(response))
 }
 
+#[derive(Debug, Serialize)]
+pub struct CollectionStatsResponse {
+    pub owner: String,
+    pub repository_count: i32,
+    pub statistics: CollectionStats,
+    /// When the underlying view was last recomputed
+    pub refreshed_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Aggregate statistics across every cached repository for the tenant's GitHub user
+pub async fn get_collection_stats(
+    State(app_state): State<AppState>,
+    tenant: CurrentTenant,
+) -> Result<JsonResponse<CollectionStatsResponse>> {
+    let username = &tenant.github_username;
+    sync_repository_cache_if_stale(&app_state, username).await;
+
+    let summary = repositories::collection_summary(&app_state.db_pool, username).await?;
+    let response = match summary {
+        Some(summary) => CollectionStatsResponse {
+            owner: username.clone(),
+            repository_count: summary.repository_count,
+            statistics: summary.stats,
+            refreshed_at: Some(summary.refreshed_at),
+        },
+        None => CollectionStatsResponse {
+            owner: username.clone(),
+            repository_count: 0,
+            statistics: calculate_collection_stats(&[]),
+            refreshed_at: None,
+        },
+    };
+
+    Ok(Json(response))
+}
+
+/// Pull repositories from GitHub when the cache has expired; storing them refreshes the stats view
+/// I'm only logging failures here since callers can still serve the last computed stats
+async fn sync_repository_cache_if_stale(app_state: &AppState, username: &str) {
+    if let Err(e) = app_state.sync_service.sync_if_stale(username, SyncTrigger::Read).await {
+        warn!("GitHub API failed, serving stats from the last sync: {}", e);
+    }
+}
+
 #[derive(Debug, Serialize)]
 struct LanguageStat {
     name: String,
@@ -338,49 +480,6 @@ struct LanguageStat {
 
 // Helper functions for repository processing and analysis
 
-async fn get_repositories_from_db(app_state: &AppState, username: &str) -> Result<Vec<Repository>> {
-    let repositories = sqlx::query_as::<_, Repository>(
-        r#"
-        SELECT
-            id, github_id, owner_login, name, full_name, description, html_url, clone_url, ssh_url,
-            language, size_kb, stargazers_count, watchers_count, forks_count, open_issues_count,
-            created_at, updated_at, pushed_at, is_private, is_fork, is_archived, topics,
-            license_name, readme_content, cache_updated_at, cache_expires_at
-        FROM repositories
-        WHERE owner_login = $1 AND cache_expires_at > CURRENT_TIMESTAMP
-        ORDER BY updated_at DESC
-        "#
-    )
-    .bind(username)
-    .fetch_all(&app_state.db_pool)
-    .await
-    .map_err(|e| AppError::DatabaseError(format!("Failed to fetch repositories from database: {}", e)))?;
-
-    Ok(repositories)
-}
-
-async fn get_single_repository(app_state: &AppState, owner: &str, name: &str) -> Result<Repository> {
-    let repo = sqlx::query_as::<_, Repository>(
-        r#"
-        SELECT
-            id, github_id, owner_login, name, full_name, description, html_url, clone_url, ssh_url,
-            language, size_kb, stargazers_count, watchers_count, forks_count, open_issues_count,
-            created_at, updated_at, pushed_at, is_private, is_fork, is_archived, topics,
-            license_name, readme_content, cache_updated_at, cache_expires_at
-        FROM repositories
-        WHERE owner_login = $1 AND name = $2
-        LIMIT 1
-        "#
-    )
-    .bind(owner)
-    .bind(name)
-    .fetch_one(&app_state.db_pool)
-    .await
-    .map_err(|e| AppError::DatabaseError(format!("Repository not found: {}", e)))?;
-
-    Ok(repo)
-}
-
 async fn record_repository_access(app_state: &AppState, owner: &str, name: &str) -> Result<()> {
     sqlx::query(
         r#"
@@ -405,6 +504,12 @@ fn create_filter_from_params(params: &RepositoryQuery) -> RepositoryFilter {
         is_fork: params.is_fork,
         is_archived: params.is_archived,
         search_query: params.search.clone(),
+        topics: params.topics.as_deref().map(|raw| {
+            raw.split(',')
+                .map(|topic| topic.trim().to_lowercase())
+                .filter(|topic| !topic.is_empty())
+                .collect()
+        }),
         ..Default::default()
     }
 }
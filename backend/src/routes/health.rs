@@ -4,33 +4,265 @@
 
 use axum::{
     response::IntoResponse,
-    extract::State,
+    extract::{Query, State},
     http::StatusCode,
     Json,
     response::Json as JsonResponse,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::{Duration, Instant};
 use tracing::{info, warn, error};
 use sqlx::Row;
 
 use crate::{
-    utils::error::{AppError, Result},
+    build_info::{self, BuildInfo},
+    models::{jobs::LeadershipStatus, webhooks::WebhookEvent},
+    utils::{config::Config, error::{AppError, Result}},
     AppState,
 };
 
+/// Whether an unhealthy alert has already been sent for the current outage
+static UNHEALTHY_ALERT_SENT: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// How long a shallow health result is reused before the checks run again
+pub const SHALLOW_HEALTH_CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// Most recent shallow result, keyed by the checks it covered
+static SHALLOW_HEALTH_CACHE: Mutex<Option<CachedHealth>> = Mutex::new(None);
+
+struct CachedHealth {
+    checks: Vec<HealthCheckKind>,
+    computed_at: Instant,
+    response: HealthCheckResponse,
+}
+
+/// Query parameters selecting which health checks run
+#[derive(Debug, Default, Deserialize)]
+pub struct HealthQuery {
+    /// Comma-separated subset of checks, e.g. `db,redis`
+    pub checks: Option<String>,
+    /// `shallow` (default, cached briefly) or `deep` (always runs fresh)
+    pub depth: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthDepth {
+    Shallow,
+    Deep,
+}
+
+/// Individual checks the health endpoint can run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HealthCheckKind {
+    Database,
+    Redis,
+    GithubApi,
+    FractalEngine,
+    System,
+}
+
+impl HealthCheckKind {
+    pub const ALL: [HealthCheckKind; 5] = [
+        HealthCheckKind::Database,
+        HealthCheckKind::Redis,
+        HealthCheckKind::GithubApi,
+        HealthCheckKind::FractalEngine,
+        HealthCheckKind::System,
+    ];
+
+    /// The cheap local dependencies load balancers care about
+    pub const SHALLOW: [HealthCheckKind; 2] = [HealthCheckKind::Database, HealthCheckKind::Redis];
+
+    /// The name HEALTH_CHECK_WEIGHTS knows the check by
+    pub fn name(&self) -> &'static str {
+        match self {
+            HealthCheckKind::Database => "database",
+            HealthCheckKind::Redis => "redis",
+            HealthCheckKind::GithubApi => "github_api",
+            HealthCheckKind::FractalEngine => "fractal_engine",
+            HealthCheckKind::System => "system",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "db" | "database" => Some(HealthCheckKind::Database),
+            "redis" | "cache" => Some(HealthCheckKind::Redis),
+            "github" | "github_api" => Some(HealthCheckKind::GithubApi),
+            "fractal" | "fractals" | "fractal_engine" => Some(HealthCheckKind::FractalEngine),
+            "system" => Some(HealthCheckKind::System),
+            _ => None,
+        }
+    }
+}
+
+impl HealthQuery {
+    /// Whether the caller left both the depth and the check list unspecified
+    pub fn is_default(&self) -> bool {
+        let blank = |value: &Option<String>| value.as_deref().map_or(true, |v| v.trim().is_empty());
+        blank(&self.checks) && blank(&self.depth)
+    }
+
+    /// Resolve the requested depth and the sorted, de-duplicated set of checks to run
+    pub fn resolve(&self) -> Result<(HealthDepth, Vec<HealthCheckKind>)> {
+        let depth = match self.depth.as_deref().map(|d| d.trim().to_lowercase()) {
+            None => HealthDepth::Shallow,
+            Some(d) if d.is_empty() || d == "shallow" => HealthDepth::Shallow,
+            Some(d) if d == "deep" || d == "full" => HealthDepth::Deep,
+            Some(other) => {
+                return Err(AppError::bad_request(format!(
+                    "Unknown health depth '{}', expected 'shallow' or 'deep'", other
+                )))
+            }
+        };
+
+        let checks = match self.checks.as_deref().filter(|c| !c.trim().is_empty()) {
+            Some(raw) => parse_check_list(raw)?,
+            None if depth == HealthDepth::Deep => HealthCheckKind::ALL.to_vec(),
+            None => HealthCheckKind::SHALLOW.to_vec(),
+        };
+
+        Ok((depth, checks))
+    }
+}
+
+fn parse_check_list(raw: &str) -> Result<Vec<HealthCheckKind>> {
+    let mut checks = Vec::new();
+    for name in raw.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        let kind = HealthCheckKind::parse(name).ok_or_else(|| {
+            AppError::bad_request(format!(
+                "Unknown health check '{}', expected any of db, redis, github, fractal, system", name
+            ))
+        })?;
+        checks.push(kind);
+    }
+
+    if checks.is_empty() {
+        return Err(AppError::bad_request("At least one health check must be selected"));
+    }
+
+    checks.sort();
+    checks.dedup();
+    Ok(checks)
+}
+
 /// Comprehensive health check response for monitoring systems
 /// I'm providing detailed health information for production monitoring and alerting
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct HealthCheckResponse {
     pub status: ServiceStatus,
     pub timestamp: chrono::DateTime<chrono::Utc>,
     pub uptime_seconds: u64,
+    pub depth: HealthDepth,
     pub version: VersionInfo,
     pub services: ServiceHealthStatus,
-    pub system: SystemHealth,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<SystemHealth>,
     pub performance: PerformanceMetrics,
     pub checks: Vec<HealthCheck>,
+    pub thresholds: HealthThresholds,
+    /// Always current, even on a cached snapshot
+    pub leadership: LeadershipStatus,
+    /// Present when the response was served from the background monitor's cache
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub freshness: Option<HealthFreshness>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthFreshness {
+    pub checked_at: chrono::DateTime<chrono::Utc>,
+    pub age_seconds: u64,
+    pub stale: bool,
+    pub refresh_interval_seconds: u64,
+    pub stale_after_seconds: u64,
+}
+
+/// Runs every health check on a background interval and keeps the latest result
+/// I'm serving the cached snapshot so probes answer instantly no matter how slow a dependency is
+#[derive(Debug, Clone)]
+pub struct HealthMonitor {
+    latest: Arc<RwLock<Option<HealthSnapshot>>>,
+    interval: Duration,
+    stale_after: Duration,
+}
+
+#[derive(Debug, Clone)]
+struct HealthSnapshot {
+    response: HealthCheckResponse,
+    checked_at: Instant,
+}
+
+impl HealthMonitor {
+    pub fn new(interval: Duration, stale_after: Duration) -> Self {
+        Self {
+            latest: Arc::new(RwLock::new(None)),
+            interval,
+            stale_after,
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(
+            Duration::from_secs(config.health_check_interval_seconds),
+            Duration::from_secs(config.health_stale_after_seconds),
+        )
+    }
+
+    /// Start the refresh loop; the first run happens immediately
+    pub fn spawn(&self, app_state: AppState) -> tokio::task::JoinHandle<()> {
+        let monitor = self.clone();
+        info!("Starting background health monitor (interval: {}s, stale after: {}s)",
+            monitor.interval.as_secs(), monitor.stale_after.as_secs());
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(monitor.interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                monitor.refresh(&app_state).await;
+            }
+        })
+    }
+
+    /// Run every check now and replace the cached snapshot
+    pub async fn refresh(&self, app_state: &AppState) {
+        let response = run_health_checks(app_state, HealthDepth::Deep, &HealthCheckKind::ALL).await;
+        let mut latest = self.latest.write().unwrap_or_else(|e| e.into_inner());
+        *latest = Some(HealthSnapshot { response, checked_at: Instant::now() });
+    }
+
+    /// The latest snapshot annotated with its age, or None before the first run completes
+    pub fn latest(&self) -> Option<HealthCheckResponse> {
+        let latest = self.latest.read().unwrap_or_else(|e| e.into_inner());
+        latest.as_ref().map(|snapshot| {
+            with_freshness(snapshot.response.clone(), snapshot.checked_at.elapsed(), self.interval, self.stale_after)
+        })
+    }
+}
+
+/// Annotate a cached response with its age, downgrading a healthy status once it goes stale
+fn with_freshness(
+    mut response: HealthCheckResponse,
+    age: Duration,
+    interval: Duration,
+    stale_after: Duration,
+) -> HealthCheckResponse {
+    let stale = age > stale_after;
+    if stale && matches!(response.status, ServiceStatus::Healthy) {
+        response.status = ServiceStatus::Degraded;
+    }
+
+    response.freshness = Some(HealthFreshness {
+        checked_at: response.timestamp,
+        age_seconds: age.as_secs(),
+        stale,
+        refresh_interval_seconds: interval.as_secs(),
+        stale_after_seconds: stale_after.as_secs(),
+    });
+    response
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -40,7 +272,75 @@ pub enum ServiceStatus {
     Unhealthy,
 }
 
-#[derive(Debug, Serialize)]
+impl ServiceStatus {
+    fn severity(&self) -> u8 {
+        match self {
+            ServiceStatus::Healthy => 0,
+            ServiceStatus::Degraded => 1,
+            ServiceStatus::Unhealthy => 2,
+        }
+    }
+}
+
+/// The cutoffs and weights a response's checks were judged against
+/// I'm reading them from the live config on every run, so a tuned cutoff applies from the next check onwards
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthThresholds {
+    pub cpu: UsageCutoffs,
+    pub memory: UsageCutoffs,
+    pub disk: UsageCutoffs,
+    pub github_min_remaining_percent: f64,
+    /// Every check's weight, including the 1.0 unlisted checks get
+    pub weights: BTreeMap<&'static str, f64>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct UsageCutoffs {
+    pub degraded_percent: f64,
+    pub unhealthy_percent: f64,
+}
+
+impl UsageCutoffs {
+    fn status(&self, usage_percent: f64) -> ServiceStatus {
+        if usage_percent > self.unhealthy_percent {
+            ServiceStatus::Unhealthy
+        } else if usage_percent > self.degraded_percent {
+            ServiceStatus::Degraded
+        } else {
+            ServiceStatus::Healthy
+        }
+    }
+}
+
+impl HealthThresholds {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            cpu: UsageCutoffs {
+                degraded_percent: config.health_cpu_degraded_percent,
+                unhealthy_percent: config.health_cpu_unhealthy_percent,
+            },
+            memory: UsageCutoffs {
+                degraded_percent: config.health_memory_degraded_percent,
+                unhealthy_percent: config.health_memory_unhealthy_percent,
+            },
+            disk: UsageCutoffs {
+                degraded_percent: config.health_disk_degraded_percent,
+                unhealthy_percent: config.health_disk_unhealthy_percent,
+            },
+            github_min_remaining_percent: config.health_github_min_remaining_percent,
+            weights: HealthCheckKind::ALL
+                .iter()
+                .map(|kind| (kind.name(), config.health_check_weights.get(kind.name()).copied().unwrap_or(1.0)))
+                .collect(),
+        }
+    }
+
+    pub fn weight(&self, kind: HealthCheckKind) -> f64 {
+        self.weights.get(kind.name()).copied().unwrap_or(1.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct VersionInfo {
     pub version: String,
     pub build_time: String,
@@ -48,15 +348,20 @@ pub struct VersionInfo {
     pub rust_version: String,
 }
 
-#[derive(Debug, Serialize)]
+/// Per-service results; services that weren't selected are omitted
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct ServiceHealthStatus {
-    pub database: ComponentStatus,
-    pub redis: ComponentStatus,
-    pub github_api: ComponentStatus,
-    pub fractal_engine: ComponentStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database: Option<ComponentStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redis: Option<ComponentStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub github_api: Option<ComponentStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fractal_engine: Option<ComponentStatus>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ComponentStatus {
     pub status: ServiceStatus,
     pub response_time_ms: Option<u64>,
@@ -65,7 +370,7 @@ pub struct ComponentStatus {
     pub metadata: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SystemHealth {
     pub cpu_usage_percent: f64,
     pub memory_usage_percent: f64,
@@ -74,7 +379,7 @@ pub struct SystemHealth {
     pub load_average: Vec<f64>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct PerformanceMetrics {
     pub requests_per_second: f64,
     pub average_response_time_ms: f64,
@@ -90,76 +395,173 @@ pub struct HealthCheck {
     pub message: String,
 }
 
-/// Simple health check endpoint for load balancers
-/// I'm providing a lightweight endpoint for basic availability checks
+/// Health check endpoint for load balancers and dashboards
+/// I'm serving the background monitor's snapshot by default; `?depth=deep` or `?checks=` run checks inline instead
 pub async fn health_check(
     State(app_state): State<AppState>,
+    Query(query): Query<HealthQuery>,
 ) -> Result<JsonResponse<HealthCheckResponse>> {
-    let start_time = Instant::now();
-    info!("Performing comprehensive health check");
+    let (depth, kinds) = query.resolve()?;
 
-    // I'm collecting health information from all critical services
-    let mut checks = Vec::new();
-    let mut overall_status = ServiceStatus::Healthy;
+    if query.is_default() {
+        if let Some(mut snapshot) = app_state.health_monitor.latest() {
+            snapshot.leadership = app_state.leader.status();
+            snapshot.uptime_seconds = app_state.started.uptime_seconds();
+            return Ok(Json(snapshot));
+        }
+    }
 
-    // Database health check
-    let (database_status, database_check) = check_database_health(&app_state).await;
-    checks.push(database_check);
+    if depth == HealthDepth::Shallow {
+        if let Some(mut cached) = cached_shallow_health(&kinds) {
+            cached.leadership = app_state.leader.status();
+            cached.uptime_seconds = app_state.started.uptime_seconds();
+            return Ok(Json(cached));
+        }
+    }
+
+    let health_response = run_health_checks(&app_state, depth, &kinds).await;
 
-    // Redis health check
-    let (redis_status, redis_check) = check_redis_health(&app_state).await;
-    checks.push(redis_check);
+    if depth == HealthDepth::Shallow {
+        let mut cache = SHALLOW_HEALTH_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+        *cache = Some(CachedHealth {
+            checks: kinds,
+            computed_at: Instant::now(),
+            response: health_response.clone(),
+        });
+    }
 
-    // GitHub API health check
-    let (github_status, github_check) = check_github_api_health(&app_state).await;
-    checks.push(github_check);
+    Ok(Json(health_response))
+}
+
+fn cached_shallow_health(kinds: &[HealthCheckKind]) -> Option<HealthCheckResponse> {
+    let cache = SHALLOW_HEALTH_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    cache
+        .as_ref()
+        .filter(|entry| entry.checks == kinds && entry.computed_at.elapsed() < SHALLOW_HEALTH_CACHE_TTL)
+        .map(|entry| entry.response.clone())
+}
+
+/// Run the selected checks and assemble the response
+pub async fn run_health_checks(
+    app_state: &AppState,
+    depth: HealthDepth,
+    kinds: &[HealthCheckKind],
+) -> HealthCheckResponse {
+    let start_time = Instant::now();
+    info!("Performing {:?} health check: {:?}", depth, kinds);
+    let thresholds = HealthThresholds::from_config(&app_state.live_config.load());
 
-    // Fractal engine health check
-    let (fractal_status, fractal_check) = check_fractal_engine_health(&app_state).await;
-    checks.push(fractal_check);
+    let mut checks = Vec::new();
+    let mut services = ServiceHealthStatus::default();
+    let mut system = None;
+
+    for kind in kinds {
+        match kind {
+            HealthCheckKind::Database => {
+                let (status, check) = check_database_health(app_state).await;
+                services.database = Some(status);
+                checks.push(check);
+            }
+            HealthCheckKind::Redis => {
+                let (status, check) = check_redis_health(app_state).await;
+                services.redis = Some(status);
+                checks.push(check);
+            }
+            HealthCheckKind::GithubApi => {
+                let (status, check) = check_github_api_health(app_state, &thresholds).await;
+                services.github_api = Some(status);
+                checks.push(check);
+            }
+            HealthCheckKind::FractalEngine => {
+                let (status, check) = check_fractal_engine_health(app_state).await;
+                services.fractal_engine = Some(status);
+                checks.push(check);
+            }
+            HealthCheckKind::System => {
+                let (system_health, check) = check_system_health(app_state, &thresholds).await;
+                system = Some(system_health);
+                checks.push(check);
+            }
+        }
+    }
 
-    // System resources check
-    let (system_health_struct, system_check_item) = check_system_health(&app_state).await;
-    checks.push(system_check_item.clone());
+    // Every check pushes exactly one entry, so checks line up with the kinds that produced them
+    let weighted: Vec<(f64, &ServiceStatus)> = kinds
+        .iter()
+        .zip(&checks)
+        .map(|(kind, check)| (thresholds.weight(*kind), &check.status))
+        .collect();
+    let overall_status = determine_overall_status(&weighted);
 
-    // Determine overall service status
-    overall_status = determine_overall_status(&[
-        &database_status.status,
-        &redis_status.status,
-        &github_status.status,
-        &fractal_status.status,
-        &system_check_item.status,
-    ]);
+    notify_on_status_change(app_state, &overall_status, &checks);
 
-    // Collect performance metrics
-    let performance_metrics = collect_performance_metrics(&app_state).await;
+    let performance_metrics = collect_performance_metrics(app_state).await;
 
     let health_response = HealthCheckResponse {
         status: overall_status,
         timestamp: chrono::Utc::now(),
-        uptime_seconds: get_uptime_seconds(),
+        uptime_seconds: app_state.started.uptime_seconds(),
+        depth,
         version: VersionInfo {
-            version: env!("CARGO_PKG_VERSION").to_string(),
-            build_time: env!("BUILD_TIME").to_string(),
-            git_commit: env!("GIT_COMMIT").to_string(),
-            rust_version: option_env!("BUILD_RUST_VERSION").unwrap_or("unknown").to_string(),
+            version: build_info::VERSION.to_string(),
+            build_time: build_info::BUILD_TIME.to_string(),
+            git_commit: build_info::GIT_COMMIT.to_string(),
+            rust_version: build_info::RUST_VERSION.to_string(),
         },
-        services: ServiceHealthStatus {
-            database: database_status,
-            redis: redis_status,
-            github_api: github_status,
-            fractal_engine: fractal_status,
-        },
-        system: system_health_struct,
+        services,
+        system,
         performance: performance_metrics,
         checks,
+        thresholds,
+        leadership: app_state.leader.status(),
+        freshness: None,
     };
 
-    let total_check_time = start_time.elapsed();
     info!("Health check completed in {}ms with status: {:?}",
-        total_check_time.as_millis(), health_response.status);
+        start_time.elapsed().as_millis(), health_response.status);
 
-    Ok(Json(health_response))
+    health_response
+}
+
+/// Fire an alert.fired webhook and alert email when the service first becomes unhealthy
+/// I'm only alerting on the transition so frequent health polling doesn't flood subscribers
+fn notify_on_status_change(app_state: &AppState, status: &ServiceStatus, checks: &[HealthCheck]) {
+    use std::sync::atomic::Ordering;
+
+    if !matches!(status, ServiceStatus::Unhealthy) {
+        UNHEALTHY_ALERT_SENT.store(false, Ordering::SeqCst);
+        return;
+    }
+
+    if UNHEALTHY_ALERT_SENT.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let failing_checks: Vec<&HealthCheck> = checks
+        .iter()
+        .filter(|check| matches!(check.status, ServiceStatus::Unhealthy))
+        .collect();
+    let failing: Vec<_> = failing_checks
+        .iter()
+        .map(|check| serde_json::json!({ "name": check.name, "message": check.message }))
+        .collect();
+
+    let alert = app_state
+        .notification_templates
+        .alert("service_unhealthy")
+        .with_metric("unhealthy_checks", failing_checks.len() as u64, None)
+        .with_details(failing_checks.iter().map(|check| format!("{}: {}", check.name, check.message)).collect());
+    let rendered = app_state.notification_templates.render(&alert);
+
+    app_state.email_service.send_alert(&rendered);
+    app_state.webhook_service.emit(WebhookEvent::AlertFired, serde_json::json!({
+        "alert": alert.rule,
+        "message": rendered.message,
+        "metric": alert.metric,
+        "value": alert.value,
+        "dashboard_url": alert.dashboard_url,
+        "failing_checks": failing,
+    }));
 }
 
 /// Readiness probe endpoint for Kubernetes deployments
@@ -169,12 +571,20 @@ pub async fn readiness_check(
 ) -> Result<JsonResponse<serde_json::Value>> {
     info!("Performing readiness check");
 
+    // A draining instance should drop out of the load balancer before its listener closes
+    if app_state.shutdown.is_draining() {
+        warn!("Service is shutting down - not ready");
+        return Err(AppError::ServiceUnavailableError("Service is shutting down".to_string()));
+    }
+
     // I'm checking only critical services needed for request handling
     let database_ready = check_database_readiness(&app_state).await;
     let redis_ready = check_redis_readiness(&app_state).await;
     let config_ready = check_configuration_readiness(&app_state).await;
 
-    let is_ready = database_ready && redis_ready && config_ready;
+    let warmed_up = app_state.warm_up.is_complete();
+
+    let is_ready = database_ready && redis_ready && config_ready && warmed_up;
 
     let readiness_response = serde_json::json!({
         "ready": is_ready,
@@ -182,27 +592,38 @@ pub async fn readiness_check(
         "checks": {
             "database": database_ready,
             "redis": redis_ready,
-            "configuration": config_ready
-        }
+            "configuration": config_ready,
+            "warm_up": warmed_up
+        },
+        "warm_up": app_state.warm_up.status()
     });
 
     if is_ready {
         info!("Service is ready to accept traffic");
         Ok(Json(readiness_response))
+    } else if database_ready && redis_ready && config_ready {
+        info!("Service is still warming up - not ready");
+        Err(AppError::ServiceUnavailableError("Service is warming up".to_string()))
     } else {
         warn!("Service is not ready - some dependencies are unavailable");
         Err(AppError::ServiceUnavailableError("Service not ready".to_string()))
     }
 }
 
+/// Build and version details for the running binary
+pub async fn version_info(State(app_state): State<AppState>) -> JsonResponse<BuildInfo> {
+    Json(BuildInfo::current(app_state.config.environment.clone()))
+}
+
 /// Liveness probe endpoint for Kubernetes deployments
 /// I'm providing a liveness check to detect if the service needs to be restarted
-pub async fn liveness_check() -> Result<JsonResponse<serde_json::Value>> {
+pub async fn liveness_check(State(app_state): State<AppState>) -> Result<JsonResponse<serde_json::Value>> {
     // I'm implementing a simple liveness check that verifies basic service operation
     let liveness_response = serde_json::json!({
         "alive": true,
         "timestamp": chrono::Utc::now(),
-        "uptime_seconds": get_uptime_seconds()
+        "started_at": app_state.started.started_at(),
+        "uptime_seconds": app_state.started.uptime_seconds()
     });
 
     Ok(Json(liveness_response))
@@ -360,7 +781,7 @@ async fn check_redis_health(app_state: &AppState) -> (ComponentStatus, HealthChe
     }
 }
 
-async fn check_github_api_health(app_state: &AppState) -> (ComponentStatus, HealthCheck) {
+async fn check_github_api_health(app_state: &AppState, thresholds: &HealthThresholds) -> (ComponentStatus, HealthCheck) {
     let start_time = Instant::now();
     let check_name = "github_api".to_string();
 
@@ -370,7 +791,7 @@ async fn check_github_api_health(app_state: &AppState) -> (ComponentStatus, Heal
             let duration = start_time.elapsed();
             let remaining_percentage = (rate_limit.remaining as f64 / rate_limit.limit as f64) * 100.0;
 
-            let status = if remaining_percentage < 10.0 {
+            let status = if remaining_percentage < thresholds.github_min_remaining_percent {
                 ServiceStatus::Degraded
             } else {
                 ServiceStatus::Healthy
@@ -428,13 +849,10 @@ async fn check_fractal_engine_health(app_state: &AppState) -> (ComponentStatus,
 
     // I'm performing a quick fractal computation test to verify the engine
     let test_request = crate::services::fractal_service::FractalRequest {
-        width: 32,
-        height: 32,
         center_x: -0.5,
         center_y: 0.0,
-        zoom: 1.0,
         max_iterations: 50,
-        fractal_type: crate::services::fractal_service::FractalType::Mandelbrot,
+        ..crate::services::fractal_service::FractalRequest::new(crate::services::fractal_service::FractalType::Mandelbrot, 32, 32)
     };
 
     let computation_result = tokio::task::spawn_blocking(move || {
@@ -454,7 +872,9 @@ async fn check_fractal_engine_health(app_state: &AppState) -> (ComponentStatus,
                 metadata: Some(serde_json::json!({
                     "test_computation_time_ms": result.computation_time_ms,
                     "pixels_computed": result.width * result.height,
-                    "engine_version": "rayon-parallel"
+                    "engine_version": "rayon-parallel",
+                    "cancelled_renders": app_state.fractal_service.cancelled_renders(),
+                    "buffer_pool": app_state.fractal_service.buffer_stats()
                 })),
             };
 
@@ -490,7 +910,7 @@ async fn check_fractal_engine_health(app_state: &AppState) -> (ComponentStatus,
     }
 }
 
-async fn check_system_health(_app_state: &AppState) -> (SystemHealth, HealthCheck) {
+async fn check_system_health(app_state: &AppState, thresholds: &HealthThresholds) -> (SystemHealth, HealthCheck) {
     let start_time = Instant::now();
 
     // I'm collecting system resource information
@@ -512,20 +932,21 @@ async fn check_system_health(_app_state: &AppState) -> (SystemHealth, HealthChec
     let load_average = sys.load_average();
     let load_avg_vec = vec![load_average.one, load_average.five, load_average.fifteen];
 
-    // Determine system health status
-    let system_status = if cpu_usage > 90.0 || memory_usage > 95.0 || disk_usage > 95.0 {
-        ServiceStatus::Unhealthy
-    } else if cpu_usage > 70.0 || memory_usage > 80.0 || disk_usage > 80.0 {
-        ServiceStatus::Degraded
-    } else {
-        ServiceStatus::Healthy
-    };
+    // The system is as healthy as its most strained resource
+    let system_status = [
+        thresholds.cpu.status(cpu_usage),
+        thresholds.memory.status(memory_usage),
+        thresholds.disk.status(disk_usage),
+    ]
+    .into_iter()
+    .max_by_key(ServiceStatus::severity)
+    .unwrap_or(ServiceStatus::Healthy);
 
     let system_health = SystemHealth {
         cpu_usage_percent: cpu_usage,
         memory_usage_percent: memory_usage,
         disk_usage_percent: disk_usage,
-        active_connections: 0, // This would need to be implemented based on your connection tracking
+        active_connections: app_state.connections.active() as u32,
         load_average: load_avg_vec,
     };
 
@@ -585,21 +1006,161 @@ async fn collect_performance_metrics(_app_state: &AppState) -> PerformanceMetric
     }
 }
 
-fn determine_overall_status(statuses: &[&ServiceStatus]) -> ServiceStatus {
-    // I'm implementing a conservative approach to overall status determination
-    if statuses.iter().any(|&status| matches!(status, ServiceStatus::Unhealthy)) {
+/// Unhealthy once the weights of failing checks add up to 1.0; any other impaired check with weight degrades the service
+/// I'm leaving weight-0 checks out entirely so an operator can keep a check visible without letting it page anyone
+fn determine_overall_status(checks: &[(f64, &ServiceStatus)]) -> ServiceStatus {
+    let unhealthy_weight: f64 = checks
+        .iter()
+        .filter(|(_, status)| matches!(status, ServiceStatus::Unhealthy))
+        .map(|(weight, _)| weight)
+        .sum();
+
+    if unhealthy_weight >= 1.0 - 1e-9 {
         ServiceStatus::Unhealthy
-    } else if statuses.iter().any(|&status| matches!(status, ServiceStatus::Degraded)) {
+    } else if checks.iter().any(|(weight, status)| *weight > 0.0 && !matches!(status, ServiceStatus::Healthy)) {
         ServiceStatus::Degraded
     } else {
         ServiceStatus::Healthy
     }
 }
 
-fn get_uptime_seconds() -> u64 {
-    // I'm implementing a simple uptime calculation
-    // In a production system, you'd want to track this more accurately
-    static START_TIME: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
-    let start = START_TIME.get_or_init(|| std::time::Instant::now());
-    start.elapsed().as_secs()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(checks: Option<&str>, depth: Option<&str>) -> HealthQuery {
+        HealthQuery { checks: checks.map(String::from), depth: depth.map(String::from) }
+    }
+
+    #[test]
+    fn test_default_is_shallow_db_and_redis() {
+        let (depth, checks) = query(None, None).resolve().unwrap();
+        assert_eq!(depth, HealthDepth::Shallow);
+        assert_eq!(checks, HealthCheckKind::SHALLOW.to_vec());
+
+        let (depth, checks) = query(None, Some("deep")).resolve().unwrap();
+        assert_eq!(depth, HealthDepth::Deep);
+        assert_eq!(checks, HealthCheckKind::ALL.to_vec());
+    }
+
+    #[test]
+    fn test_check_list_parsing() {
+        let (_, checks) = query(Some("redis, db,redis"), None).resolve().unwrap();
+        assert_eq!(checks, vec![HealthCheckKind::Database, HealthCheckKind::Redis]);
+
+        let (_, checks) = query(Some("fractal,system"), Some("deep")).resolve().unwrap();
+        assert_eq!(checks, vec![HealthCheckKind::FractalEngine, HealthCheckKind::System]);
+
+        assert!(query(Some("db,kafka"), None).resolve().is_err());
+        assert!(query(Some(" , "), None).resolve().is_err());
+        assert!(query(None, Some("extreme")).resolve().is_err());
+    }
+
+    fn test_config() -> Config {
+        crate::utils::config::ConfigBuilder::new()
+            .database_url("postgresql://localhost/test")
+            .github_token("token")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_weighted_overall_status() {
+        use ServiceStatus::*;
+        let status = |checks: &[(f64, &ServiceStatus)]| determine_overall_status(checks).severity();
+
+        // Default weights keep the old rule: any failure fails the service, any degradation degrades it
+        assert_eq!(status(&[(1.0, &Healthy), (1.0, &Unhealthy)]), Unhealthy.severity());
+        assert_eq!(status(&[(1.0, &Healthy), (1.0, &Degraded)]), Degraded.severity());
+        assert_eq!(status(&[(1.0, &Healthy), (1.0, &Healthy)]), Healthy.severity());
+
+        // Half-weight failures only fail the service together
+        assert_eq!(status(&[(0.5, &Unhealthy), (1.0, &Healthy)]), Degraded.severity());
+        assert_eq!(status(&[(0.5, &Unhealthy), (0.5, &Unhealthy)]), Unhealthy.severity());
+        assert_eq!(status(&[(0.0, &Unhealthy), (1.0, &Healthy)]), Healthy.severity());
+    }
+
+    #[test]
+    fn test_thresholds_come_from_config() {
+        let mut config = test_config();
+        config.health_cpu_degraded_percent = 85.0;
+        config.health_check_weights.insert("system".to_string(), 0.5);
+        let thresholds = HealthThresholds::from_config(&config);
+
+        assert_eq!(thresholds.cpu.status(80.0).severity(), ServiceStatus::Healthy.severity());
+        assert_eq!(thresholds.cpu.status(86.0).severity(), ServiceStatus::Degraded.severity());
+        assert_eq!(thresholds.cpu.status(91.0).severity(), ServiceStatus::Unhealthy.severity());
+        assert_eq!(thresholds.weight(HealthCheckKind::System), 0.5);
+        assert_eq!(thresholds.weight(HealthCheckKind::Database), 1.0);
+        assert_eq!(thresholds.weights.len(), HealthCheckKind::ALL.len());
+
+        config.health_check_weights.insert("kafka".to_string(), 1.0);
+        assert!(config.validate().is_err());
+        config.health_check_weights.remove("kafka");
+        config.health_memory_unhealthy_percent = 50.0;
+        assert!(config.validate().is_err());
+    }
+
+    fn sample_response(status: ServiceStatus) -> HealthCheckResponse {
+        HealthCheckResponse {
+            status,
+            timestamp: chrono::Utc::now(),
+            uptime_seconds: 0,
+            depth: HealthDepth::Deep,
+            version: VersionInfo {
+                version: "test".to_string(),
+                build_time: "now".to_string(),
+                git_commit: "abc".to_string(),
+                rust_version: "unknown".to_string(),
+            },
+            services: ServiceHealthStatus::default(),
+            system: None,
+            performance: PerformanceMetrics {
+                requests_per_second: 0.0,
+                average_response_time_ms: 0.0,
+                error_rate_percent: 0.0,
+                fractal_computations_last_hour: 0,
+                github_api_calls_last_hour: 0,
+            },
+            checks: Vec::new(),
+            thresholds: HealthThresholds::from_config(&test_config()),
+            leadership: LeadershipStatus {
+                enabled: false,
+                instance_id: "test".to_string(),
+                is_leader: true,
+                leader: Some("test".to_string()),
+                leader_since: None,
+                last_renewed_at: None,
+                lease_seconds: 30,
+            },
+            freshness: None,
+        }
+    }
+
+    #[test]
+    fn test_stale_snapshot_is_degraded() {
+        let interval = Duration::from_secs(15);
+        let stale_after = Duration::from_secs(60);
+
+        let fresh = with_freshness(sample_response(ServiceStatus::Healthy), Duration::from_secs(10), interval, stale_after);
+        assert!(matches!(fresh.status, ServiceStatus::Healthy));
+        assert!(!fresh.freshness.as_ref().unwrap().stale);
+
+        let stale = with_freshness(sample_response(ServiceStatus::Healthy), Duration::from_secs(90), interval, stale_after);
+        assert!(matches!(stale.status, ServiceStatus::Degraded));
+        let freshness = stale.freshness.unwrap();
+        assert!(freshness.stale);
+        assert_eq!(freshness.age_seconds, 90);
+
+        let unhealthy = with_freshness(sample_response(ServiceStatus::Unhealthy), Duration::from_secs(90), interval, stale_after);
+        assert!(matches!(unhealthy.status, ServiceStatus::Unhealthy));
+    }
+
+    #[test]
+    fn test_default_query_detection() {
+        assert!(query(None, None).is_default());
+        assert!(query(Some(" "), Some("")).is_default());
+        assert!(!query(None, Some("shallow")).is_default());
+        assert!(!query(Some("db"), None).is_default());
+    }
 }
@@ -0,0 +1,64 @@
+/*
+ * Persisted image endpoint serving previously rendered fractals straight from storage.
+ * I'm marking responses immutable because image ids are content hashes, so the bytes behind a URL never change.
+ * Renders stored for a saved fractal aren't served here; they go out only through the owner route and signed links.
+ */
+
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use tracing::debug;
+
+use crate::{
+    services::image_service::PNG_CONTENT_TYPE,
+    utils::error::{AppError, Result},
+    AppState,
+};
+
+const IMMUTABLE_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+/// Serve a stored render by id, accepting an optional `.png` suffix
+/// I'm answering conditional requests with 304 so browsers revalidating a shared link skip the body
+pub async fn get_image(
+    State(app_state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let id = id.strip_suffix(".png").unwrap_or(&id).to_string();
+    let etag = format!("\"{}\"", id);
+
+    // Looks the same as a missing image, so probing ids doesn't reveal which ones belong to saves
+    if app_state.gallery_service.holds_image(&id).await? {
+        return Err(AppError::not_found(format!("image {}", id)));
+    }
+
+    let bytes = app_state.image_service
+        .load(&id)
+        .await?
+        .ok_or_else(|| AppError::not_found(format!("image {}", id)))?;
+
+    let cache_headers = [
+        (header::CACHE_CONTROL, HeaderValue::from_static(IMMUTABLE_CACHE_CONTROL)),
+        (header::ETAG, HeaderValue::from_str(&etag).map_err(|e| AppError::internal(e.to_string()))?),
+    ];
+
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.split(',').any(|tag| tag.trim() == etag || tag.trim() == "*"))
+        .unwrap_or(false);
+
+    if not_modified {
+        debug!("Image {} not modified", id);
+        return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
+    }
+
+    Ok((
+        StatusCode::OK,
+        cache_headers,
+        [(header::CONTENT_TYPE, HeaderValue::from_static(PNG_CONTENT_TYPE))],
+        bytes,
+    ).into_response())
+}
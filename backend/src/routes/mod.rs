@@ -5,29 +5,64 @@
 
 pub mod github;
 pub mod fractals;
+pub mod animations;
+pub mod fractal_jobs;
+pub mod catalog;
+pub mod gallery;
 pub mod performance;
 pub mod health;
 pub mod docs;
+pub mod admin;
+pub mod palettes;
+pub mod images;
+pub mod exports;
+pub mod webhooks;
+pub mod batch;
+pub mod usage;
+pub mod features;
+pub mod sessions;
+pub mod tenants;
+pub mod notification_templates;
+pub mod recordings;
+pub mod users;
+pub mod explorer;
 
 // Re-export all route handlers for convenient access from main.rs
 pub use github::*;
 pub use fractals::*;
+pub use animations::*;
+pub use fractal_jobs::*;
+pub use catalog::*;
+pub use gallery::*;
 pub use performance::*;
 pub use health::*;
 pub use docs::*;
+pub use admin::*;
+pub use palettes::*;
+pub use images::*;
+pub use exports::*;
+pub use webhooks::*;
+pub use batch::*;
+pub use usage::*;
+pub use features::*;
+pub use sessions::*;
+pub use tenants::*;
+pub use notification_templates::*;
+pub use recordings::*;
+pub use users::*;
+pub use explorer::*;
 
 use crate::utils::config::Config;
 
 use axum::{
     Router,
+    extract::DefaultBodyLimit,
     response::IntoResponse,
-    routing::{get, post, Route},
+    routing::{delete, get, post, put, Route},
     http::{Method, HeaderValue, HeaderName, header},
 };
 use tower_http::{
     cors::{CorsLayer, Any},
-    compression::CompressionLayer,
-    trace::TraceLayer,
     timeout::TimeoutLayer,
     limit::RequestBodyLimitLayer,
 };
@@ -36,7 +71,8 @@ use tracing::info;
 
 use crate::{
     AppState,
-    utils::error::AppError,
+    middleware::{request_id_middleware, ClientIp},
+    utils::{error::AppError, logging},
 };
 
 /// Create the complete application router with all endpoints and middleware
@@ -48,23 +84,111 @@ pub fn create_router() -> Router<AppState> {
         .route("/health", get(health::health_check))
         .route("/health/ready", get(health::readiness_check))
         .route("/health/live", get(health::liveness_check))
+        .route("/version", get(health::version_info))
         
         .route("/docs", get(docs::get_api_docs_html))
         .route("/docs.json", get(docs::get_api_docs_json))
 
         .route("/api/github/repos", get(github::get_repositories))
+        .route("/api/github/search", get(github::search_repositories))
         .route("/api/github/repo/:owner/:name", get(github::get_repository_details))
         .route("/api/github/repo/:owner/:name/stats", get(github::get_repository_stats))
         .route("/api/github/language-distribution", get(github::get_language_distribution))
+        .route("/api/github/stats", get(github::get_collection_stats))
 
         .route("/api/fractals/mandelbrot", post(fractals::generate_mandelbrot))
         .route("/api/fractals/julia", post(fractals::generate_julia))
+        .route("/api/fractals/burning-ship", post(fractals::generate_burning_ship))
+        .route("/api/fractals/tricorn", post(fractals::generate_tricorn))
+        .route("/api/fractals/multibrot", post(fractals::generate_multibrot))
+        .route("/api/fractals/mandelbulb", post(fractals::generate_mandelbulb))
         .route("/api/fractals/benchmark", post(fractals::benchmark_generation))
+        .route("/api/fractals/batch", post(fractals::generate_batch))
+        .route("/api/fractals/renderers", get(fractals::list_renderers))
+        .route("/api/fractals/types", get(fractals::list_fractal_types))
+        .route("/api/fractals/thumbnail", get(fractals::generate_thumbnail))
+        .route("/api/fractals/animate", post(animations::create_animation))
+        .route("/api/fractals/animate/:id", get(animations::get_animation_job))
+        .route("/api/fractals/animate/:id/download", get(animations::download_animation))
+        .route("/api/fractals/jobs", post(fractal_jobs::create_fractal_job))
+        .route("/api/fractals/jobs/:id", get(fractal_jobs::get_fractal_job).delete(fractal_jobs::cancel_fractal_job))
+        .route("/api/fractals/jobs/:id/result", get(fractal_jobs::get_fractal_job_result))
+        .route("/api/fractals/presets", get(catalog::list_catalog_presets).post(catalog::create_catalog_preset))
+        .route("/api/fractals/presets/:id", get(catalog::get_catalog_preset).put(catalog::update_catalog_preset).delete(catalog::delete_catalog_preset))
+        .route("/api/fractals/saved", get(gallery::list_saved_fractals).post(gallery::save_fractal))
+        .route("/api/fractals/saved/:id", get(gallery::get_saved_fractal))
+        .route("/api/fractals/saved/:id/image", get(gallery::get_saved_fractal_image))
+        .route("/api/fractals/saved/:id/render", get(gallery::render_saved_fractal))
+        .route("/api/sessions/history", get(sessions::get_session_history).delete(sessions::clear_session_history))
+        .route("/api/users", post(users::register_user))
+        .route("/api/users/github", post(users::github_sign_in))
+        .route("/api/users/me", get(users::get_current_user))
+        .route("/api/users/me/keys", get(users::list_api_keys).post(users::create_api_key))
+        .route("/api/users/me/keys/:id", delete(users::revoke_api_key))
+        .route("/api/users/me/keys/:id/rotate", post(users::rotate_api_key))
+        .route("/api/palettes", get(palettes::list_palettes).post(palettes::upload_palette))
+        .route("/api/palettes/builtin", get(palettes::list_builtin_palettes))
+        .route("/api/palettes/:id", get(palettes::get_palette))
+        .route("/api/presets", post(palettes::upload_preset))
+        .route("/api/presets/:id", get(palettes::get_preset))
 
         .route("/api/performance/metrics", get(performance::get_current_metrics))
         .route("/api/performance/system", get(performance::get_system_info))
         .route("/api/performance/benchmark", post(performance::run_benchmark))
         .route("/api/performance/history", get(performance::get_metrics_history))
+        .route("/api/performance/slo", get(performance::get_slo_report))
+
+        .route("/api/batch", post(batch::execute_batch))
+        .route("/api/keys/:id/usage", get(usage::get_key_usage))
+        .route("/api/features", get(features::get_features))
+        .route("/api/tenant", get(tenants::get_current_tenant))
+
+        .route("/api/admin/audit-logs", get(admin::list_audit_logs))
+        .route("/api/admin/audit-events", get(admin::list_audit_events))
+        .route("/api/admin/cache", delete(admin::flush_cache))
+        .route("/api/admin/usage", get(usage::list_key_usage))
+        .route("/api/admin/maintenance", get(admin::get_maintenance).put(admin::set_maintenance))
+        .route("/api/admin/backup", get(admin::export_backup).post(admin::import_backup).layer(DefaultBodyLimit::disable()))
+        .route("/api/admin/migrations", get(admin::get_migration_status))
+        .route("/api/admin/migrations/run", post(admin::run_migrations))
+        .route("/api/admin/config/schema", get(admin::get_config_schema))
+        .route("/api/admin/logging", get(admin::get_log_filters))
+        .route("/api/admin/logging/:target", put(admin::set_log_filter).delete(admin::clear_log_filter))
+        .route("/api/admin/jobs", get(admin::list_jobs))
+        .route("/api/admin/jobs/:name/run", post(admin::run_job))
+        .route("/api/admin/jobs/:name/pause", post(admin::pause_job))
+        .route("/api/admin/jobs/:name/resume", post(admin::resume_job))
+        .route("/api/admin/jobs/:name/runs", get(admin::list_job_runs))
+        .route("/api/admin/queues", get(admin::list_queues))
+        .route("/api/admin/circuit-breakers", get(admin::list_circuit_breakers))
+        .route("/api/admin/queues/:queue/dead-letters", get(admin::list_dead_letters))
+        .route("/api/admin/queues/:queue/dead-letters/:id", delete(admin::discard_dead_letter))
+        .route("/api/admin/queues/:queue/dead-letters/:id/retry", post(admin::retry_dead_letter))
+        .route("/api/admin/settings", get(admin::list_settings))
+        .route("/api/admin/settings/history", get(admin::list_setting_history))
+        .route("/api/admin/settings/:key", put(admin::update_setting).delete(admin::reset_setting))
+        .route("/api/admin/feature-flags", get(features::list_feature_flags))
+        .route("/api/admin/feature-flags/:name", put(features::upsert_feature_flag).delete(features::delete_feature_flag))
+        .route("/api/admin/tenants", get(tenants::list_tenants))
+        .route("/api/admin/tenants/:slug", put(tenants::upsert_tenant).delete(tenants::delete_tenant))
+        .route("/api/admin/notification-templates", get(notification_templates::list_notification_templates))
+        .route("/api/admin/notification-templates/preview", post(notification_templates::preview_notification_template))
+        .route(
+            "/api/admin/notification-templates/:rule/:channel",
+            put(notification_templates::upsert_notification_template).delete(notification_templates::delete_notification_template),
+        )
+        .route("/api/admin/recordings", get(recordings::list_recordings).delete(recordings::clear_recordings))
+        .route("/api/admin/recordings/:id", get(recordings::get_recording))
+        .route("/api/admin/recordings/:id/replay", post(recordings::replay_recording))
+        .route("/api/exports/fractal-computations", get(exports::export_fractal_computations))
+        .route("/api/exports/performance-metrics", get(exports::export_performance_metrics))
+        .route("/api/exports/jobs", get(exports::list_export_jobs).post(exports::create_export_job))
+        .route("/api/exports/jobs/:id", get(exports::get_export_job))
+        .route("/api/exports/jobs/:id/download", get(exports::download_export))
+        .route("/api/admin/webhooks", get(webhooks::list_webhooks).post(webhooks::create_webhook))
+        .route("/api/admin/webhooks/:id", get(webhooks::get_webhook).put(webhooks::update_webhook).delete(webhooks::delete_webhook))
+        .route("/api/admin/webhooks/:id/deliveries", get(webhooks::list_webhook_deliveries))
+        .route("/api/admin/users/:id/quota", put(users::set_user_quota))
 }
 
 
@@ -72,11 +196,12 @@ pub fn create_middleware_stack(config: &Config) -> impl tower::Layer<Route> + Cl
     use tower::ServiceBuilder;
 
     ServiceBuilder::new()
+        .layer(axum::middleware::from_fn::<_, (axum::extract::Request,)>(request_id_middleware))
         .layer(create_cors_layer(config))
-        .layer(CompressionLayer::new())
+        .layer(crate::middleware::compression_layer(config))
         .layer(TimeoutLayer::new(Duration::from_secs(30)))
         .layer(RequestBodyLimitLayer::new(10 * 1024 * 1024))
-        .layer(TraceLayer::new_for_http())
+        .layer(logging::http_trace_layer())
 }
 
 /// I'm implementing flexible CORS that supports development while maintaining security in production
@@ -94,6 +219,20 @@ fn create_cors_layer(config: &Config) -> CorsLayer {
             HeaderName::from_static("x-correlation-id"),
             HeaderName::from_static("x-request-id"),
             HeaderName::from_static("x-request-start"),
+            HeaderName::from_static(crate::middleware::session::SESSION_HEADER),
+        ])
+        .expose_headers([
+            HeaderName::from_static("x-request-id"),
+            HeaderName::from_static(crate::middleware::session::SESSION_HEADER),
+            HeaderName::from_static("x-ratelimit-limit"),
+            HeaderName::from_static("x-ratelimit-remaining"),
+            HeaderName::from_static("x-ratelimit-reset"),
+            header::RETRY_AFTER,
+            HeaderName::from_static(fractals::COMPUTATION_TIME_HEADER),
+            HeaderName::from_static(fractals::RENDERER_HEADER),
+            HeaderName::from_static(fractals::COMPUTE_BACKEND_HEADER),
+            HeaderName::from_static(fractals::SKIPPED_PIXELS_HEADER),
+            HeaderName::from_static(fractals::IMAGE_URL_HEADER),
         ]);
 
     if config.is_development() {
@@ -116,17 +255,10 @@ fn create_cors_layer(config: &Config) -> CorsLayer {
 /// I'm providing a foundation for rate limiting that can be expanded based on requirements
 #[allow(dead_code)]
 async fn rate_limiting_middleware(
+    ClientIp(client_ip): ClientIp,
     request: axum::http::Request<axum::body::Body>,
     next: axum::middleware::Next,
 ) -> Result<axum::response::Response, AppError> {
-    // Get client IP address
-    let client_ip = request
-    .headers()
-    .get("x-forwarded-for")
-    .or_else(|| request.headers().get("x-real-ip"))
-    .and_then(|hv| hv.to_str().ok())
-    .unwrap_or("unknown");
-
     // Check rate limit based on endpoint
     let path = request.uri().path();
     let rate_limit = get_rate_limit_for_path(path);
@@ -148,6 +280,12 @@ struct RateLimit {
 
 fn get_rate_limit_for_path(path: &str) -> RateLimit {
     match path {
+        // Thumbnails are small, capped, and mostly cached, and saved views are stored, so galleries can load a page of them at once
+        "/api/fractals/thumbnail" | "/api/fractals/saved" | "/api/fractals/saved/:id" | "/shared/fractals/:id" => RateLimit {
+            requests_per_minute: 100,
+            burst_size: 20,
+        },
+
         // Fractal endpoints are computationally expensive
         p if p.starts_with("/api/fractals/") => RateLimit {
             requests_per_minute: 10,
@@ -167,7 +305,7 @@ fn get_rate_limit_for_path(path: &str) -> RateLimit {
         },
 
         // Health checks should be very permissive
-        "/health" | "/health/ready" | "/health/live" => RateLimit {
+        "/health" | "/health/ready" | "/health/live" | "/version" => RateLimit {
             requests_per_minute: 200,
             burst_size: 50,
         },
@@ -211,29 +349,142 @@ pub fn create_versioned_router() -> Router<AppState> {
 
         .nest("/api", create_api_routes())
 
+        // Persisted renders live at a stable, unversioned path so shared links never change
+        .route("/images/:id", get(images::get_image))
+
+        // Saved fractal images behind signed, expiring links, checked by the signed URL middleware before they get here
+        .route("/shared/fractals/:id", get(gallery::get_shared_fractal_image))
+
+        // Interactive exploration streams over one long-lived connection rather than a POST per frame
+        .route("/ws/fractals", get(explorer::fractal_socket))
+
         .fallback(handle_404)
 }
 
+/// The versioned router behind the per-request policy middleware: recording, sessions, signed links, rate limits, maintenance, usage, and auditing
+/// I'm sharing this between the server and batch sub-requests so a sub-request is limited, metered, and audited like a request of its own
+pub fn create_policy_router(app_state: &AppState) -> Router<AppState> {
+    create_versioned_router()
+        .layer(axum::middleware::from_fn_with_state(app_state.clone(), crate::middleware::recording_middleware))
+        .layer(axum::middleware::from_fn_with_state(app_state.clone(), crate::middleware::session_middleware))
+        .layer(axum::middleware::from_fn_with_state(app_state.clone(), crate::middleware::signed_url_middleware))
+        .layer(axum::middleware::from_fn_with_state(app_state.clone(), crate::middleware::rate_limit_middleware))
+        .layer(axum::middleware::from_fn_with_state(app_state.clone(), crate::middleware::maintenance_middleware))
+        .layer(axum::middleware::from_fn_with_state(app_state.clone(), crate::middleware::usage_middleware))
+        .layer(axum::middleware::from_fn_with_state(app_state.clone(), crate::middleware::audit_middleware))
+}
+
 /// Create just the API routes without health endpoints
 /// I'm separating API routes for cleaner organization
 fn create_api_routes() -> Router<AppState> {
     Router::new()
     // GitHub API integration endpoints
     .route("/github/repos", get(github::get_repositories))
+    .route("/github/search", get(github::search_repositories))
     .route("/github/repo/:owner/:name", get(github::get_repository_details))
     .route("/github/repo/:owner/:name/stats", get(github::get_repository_stats))
     .route("/github/language-distribution", get(github::get_language_distribution))
+    .route("/github/stats", get(github::get_collection_stats))
 
     // Fractal generation endpoints
     .route("/fractals/mandelbrot", post(fractals::generate_mandelbrot))
     .route("/fractals/julia", post(fractals::generate_julia))
+    .route("/fractals/burning-ship", post(fractals::generate_burning_ship))
+    .route("/fractals/tricorn", post(fractals::generate_tricorn))
+    .route("/fractals/multibrot", post(fractals::generate_multibrot))
+    .route("/fractals/mandelbulb", post(fractals::generate_mandelbulb))
     .route("/fractals/benchmark", post(fractals::benchmark_generation))
+    .route("/fractals/batch", post(fractals::generate_batch))
+    .route("/fractals/renderers", get(fractals::list_renderers))
+    .route("/fractals/types", get(fractals::list_fractal_types))
+    .route("/fractals/thumbnail", get(fractals::generate_thumbnail))
+    .route("/fractals/animate", post(animations::create_animation))
+    .route("/fractals/animate/:id", get(animations::get_animation_job))
+    .route("/fractals/animate/:id/download", get(animations::download_animation))
+    .route("/fractals/jobs", post(fractal_jobs::create_fractal_job))
+    .route("/fractals/jobs/:id", get(fractal_jobs::get_fractal_job).delete(fractal_jobs::cancel_fractal_job))
+    .route("/fractals/jobs/:id/result", get(fractal_jobs::get_fractal_job_result))
+    .route("/fractals/presets", get(catalog::list_catalog_presets).post(catalog::create_catalog_preset))
+    .route("/fractals/presets/:id", get(catalog::get_catalog_preset).put(catalog::update_catalog_preset).delete(catalog::delete_catalog_preset))
+    .route("/fractals/saved", get(gallery::list_saved_fractals).post(gallery::save_fractal))
+    .route("/fractals/saved/:id", get(gallery::get_saved_fractal))
+    .route("/fractals/saved/:id/image", get(gallery::get_saved_fractal_image))
+    .route("/fractals/saved/:id/render", get(gallery::render_saved_fractal))
+    .route("/sessions/history", get(sessions::get_session_history).delete(sessions::clear_session_history))
+
+    // User accounts and personal API keys
+    .route("/users", post(users::register_user))
+    .route("/users/github", post(users::github_sign_in))
+    .route("/users/me", get(users::get_current_user))
+    .route("/users/me/keys", get(users::list_api_keys).post(users::create_api_key))
+    .route("/users/me/keys/:id", delete(users::revoke_api_key))
+    .route("/users/me/keys/:id/rotate", post(users::rotate_api_key))
+
+    // Palette and preset uploads
+    .route("/palettes", get(palettes::list_palettes).post(palettes::upload_palette))
+    .route("/palettes/builtin", get(palettes::list_builtin_palettes))
+    .route("/palettes/:id", get(palettes::get_palette))
+    .route("/presets", post(palettes::upload_preset))
+    .route("/presets/:id", get(palettes::get_preset))
 
     // Performance monitoring endpoints
     .route("/performance/metrics", get(performance::get_current_metrics))
     .route("/performance/system", get(performance::get_system_info))
     .route("/performance/benchmark", post(performance::run_benchmark))
     .route("/performance/history", get(performance::get_metrics_history))
+    .route("/performance/slo", get(performance::get_slo_report))
+
+    // Administrative endpoints (require ADMIN_API_TOKEN)
+    .route("/batch", post(batch::execute_batch))
+    .route("/keys/:id/usage", get(usage::get_key_usage))
+    .route("/features", get(features::get_features))
+    .route("/tenant", get(tenants::get_current_tenant))
+    .route("/admin/audit-logs", get(admin::list_audit_logs))
+    .route("/admin/audit-events", get(admin::list_audit_events))
+    .route("/admin/cache", delete(admin::flush_cache))
+    .route("/admin/usage", get(usage::list_key_usage))
+    .route("/admin/maintenance", get(admin::get_maintenance).put(admin::set_maintenance))
+    .route("/admin/backup", get(admin::export_backup).post(admin::import_backup).layer(DefaultBodyLimit::disable()))
+    .route("/admin/migrations", get(admin::get_migration_status))
+    .route("/admin/migrations/run", post(admin::run_migrations))
+    .route("/admin/config/schema", get(admin::get_config_schema))
+    .route("/admin/logging", get(admin::get_log_filters))
+    .route("/admin/logging/:target", put(admin::set_log_filter).delete(admin::clear_log_filter))
+    .route("/admin/jobs", get(admin::list_jobs))
+    .route("/admin/jobs/:name/run", post(admin::run_job))
+    .route("/admin/jobs/:name/pause", post(admin::pause_job))
+    .route("/admin/jobs/:name/resume", post(admin::resume_job))
+    .route("/admin/jobs/:name/runs", get(admin::list_job_runs))
+    .route("/admin/queues", get(admin::list_queues))
+    .route("/admin/circuit-breakers", get(admin::list_circuit_breakers))
+    .route("/admin/queues/:queue/dead-letters", get(admin::list_dead_letters))
+    .route("/admin/queues/:queue/dead-letters/:id", delete(admin::discard_dead_letter))
+    .route("/admin/queues/:queue/dead-letters/:id/retry", post(admin::retry_dead_letter))
+    .route("/admin/settings", get(admin::list_settings))
+    .route("/admin/settings/history", get(admin::list_setting_history))
+    .route("/admin/settings/:key", put(admin::update_setting).delete(admin::reset_setting))
+    .route("/admin/feature-flags", get(features::list_feature_flags))
+    .route("/admin/feature-flags/:name", put(features::upsert_feature_flag).delete(features::delete_feature_flag))
+    .route("/admin/tenants", get(tenants::list_tenants))
+    .route("/admin/tenants/:slug", put(tenants::upsert_tenant).delete(tenants::delete_tenant))
+    .route("/admin/notification-templates", get(notification_templates::list_notification_templates))
+    .route("/admin/notification-templates/preview", post(notification_templates::preview_notification_template))
+    .route(
+        "/admin/notification-templates/:rule/:channel",
+        put(notification_templates::upsert_notification_template).delete(notification_templates::delete_notification_template),
+    )
+    .route("/admin/recordings", get(recordings::list_recordings).delete(recordings::clear_recordings))
+    .route("/admin/recordings/:id", get(recordings::get_recording))
+    .route("/admin/recordings/:id/replay", post(recordings::replay_recording))
+    .route("/exports/fractal-computations", get(exports::export_fractal_computations))
+    .route("/exports/performance-metrics", get(exports::export_performance_metrics))
+    .route("/exports/jobs", get(exports::list_export_jobs).post(exports::create_export_job))
+    .route("/exports/jobs/:id", get(exports::get_export_job))
+    .route("/exports/jobs/:id/download", get(exports::download_export))
+    .route("/admin/webhooks", get(webhooks::list_webhooks).post(webhooks::create_webhook))
+    .route("/admin/webhooks/:id", get(webhooks::get_webhook).put(webhooks::update_webhook).delete(webhooks::delete_webhook))
+    .route("/admin/webhooks/:id/deliveries", get(webhooks::list_webhook_deliveries))
+    .route("/admin/users/:id/quota", put(users::set_user_quota))
 }
 
 /// Route information for API documentation
@@ -263,11 +514,32 @@ pub fn get_route_documentation() -> Vec<RouteInfo> {
         RouteInfo {
             path: "/health".to_string(),
             method: "GET".to_string(),
-            description: "Comprehensive health check with system status".to_string(),
-            parameters: vec![],
+            description: "Health check; serves the background monitor snapshot (with staleness) by default".to_string(),
+            parameters: vec![
+                RouteParameter {
+                    name: "depth".to_string(),
+                    param_type: "query".to_string(),
+                    required: false,
+                    description: "shallow (database and Redis, briefly cached) or deep to run every check inline".to_string(),
+                },
+                RouteParameter {
+                    name: "checks".to_string(),
+                    param_type: "query".to_string(),
+                    required: false,
+                    description: "Comma-separated checks: db, redis, github, fractal, system".to_string(),
+                },
+            ],
             response_type: "HealthCheckResponse".to_string(),
             rate_limit: get_rate_limit_for_path("/health"),
         },
+        RouteInfo {
+            path: "/version".to_string(),
+            method: "GET".to_string(),
+            description: "Version, git commit, build time, rustc version, cargo features, and configured environment".to_string(),
+            parameters: vec![],
+            response_type: "BuildInfo".to_string(),
+            rate_limit: get_rate_limit_for_path("/version"),
+        },
         RouteInfo {
             path: "/api/github/repos".to_string(),
             method: "GET".to_string(),
@@ -295,6 +567,27 @@ pub fn get_route_documentation() -> Vec<RouteInfo> {
             response_type: "RepositoryResponse".to_string(),
             rate_limit: get_rate_limit_for_path("/api/github/repos"),
         },
+        RouteInfo {
+            path: "/api/github/search".to_string(),
+            method: "GET".to_string(),
+            description: "Ranked full-text search over cached repositories with highlighted snippets".to_string(),
+            parameters: vec![
+                RouteParameter {
+                    name: "q".to_string(),
+                    param_type: "query".to_string(),
+                    required: true,
+                    description: "Search terms; supports quoted phrases, or, and -excluded words".to_string(),
+                },
+                RouteParameter {
+                    name: "owner".to_string(),
+                    param_type: "query".to_string(),
+                    required: false,
+                    description: "Repository owner (default: the configured GitHub user)".to_string(),
+                },
+            ],
+            response_type: "RepositorySearchResponse".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/github/search"),
+        },
         RouteInfo {
             path: "/api/fractals/mandelbrot".to_string(),
             method: "POST".to_string(),
@@ -316,12 +609,446 @@ pub fn get_route_documentation() -> Vec<RouteInfo> {
                     name: "zoom".to_string(),
                     param_type: "query".to_string(),
                     required: false,
-                    description: "Zoom level (default: 1.0)".to_string(),
+                    description: "Zoom level (default: 1.0); deeper than 1e13 renders by perturbation".to_string(),
+                },
+                RouteParameter {
+                    name: "auto_iterations".to_string(),
+                    param_type: "query".to_string(),
+                    required: false,
+                    description: "true to have max_iterations scaled with zoom as FRACTAL_AUTO_ITERATIONS_BASE + FRACTAL_AUTO_ITERATIONS_PER_DECADE × log10(zoom)^FRACTAL_AUTO_ITERATIONS_EXPONENT, overriding any given; the response's max_iterations, or X-Max-Iterations on an encoded image, reports the count chosen".to_string(),
+                },
+                RouteParameter {
+                    name: "output_format".to_string(),
+                    param_type: "query".to_string(),
+                    required: false,
+                    description: "raw (JSON with RGBA data, default), png, jpeg, or webp for an image body with timing in X-Computation-Time-Ms, X-Renderer, and X-Compute-Backend".to_string(),
+                },
+                RouteParameter {
+                    name: "threads".to_string(),
+                    param_type: "query".to_string(),
+                    required: false,
+                    description: "Render threads to draw on, capped at FRACTAL_THREADS (default: all of them); a render waits until that many are free, then runs on exactly that many, which the response's threads, or X-Threads, reports".to_string(),
+                },
+                RouteParameter {
+                    name: "interior_coloring".to_string(),
+                    param_type: "query".to_string(),
+                    required: false,
+                    description: "black (default), or period to shade points inside the set by the cycle their orbit settles into; the response's interior counts the points the cardioid, bulb, and cycle checks skipped, or X-Skipped-Pixels for an image".to_string(),
+                },
+                RouteParameter {
+                    name: "export".to_string(),
+                    param_type: "query".to_string(),
+                    required: false,
+                    description: "npy, csv, bin, or rle to download the raw escape counts (uint32, or float64 with smooth colouring) as an attachment, shaped by X-Export-Shape; rle is zstd-compressed runs, sent as Content-Encoding when Accept-Encoding allows zstd, with decoding steps in X-Export-Decode".to_string(),
                 },
             ],
             response_type: "FractalApiResponse".to_string(),
             rate_limit: get_rate_limit_for_path("/api/fractals/mandelbrot"),
         },
+        RouteInfo {
+            path: "/api/fractals/burning-ship".to_string(),
+            method: "POST".to_string(),
+            description: "Generate the Burning Ship fractal with the same parameters and metrics as Mandelbrot".to_string(),
+            parameters: vec![
+                RouteParameter {
+                    name: "center_x".to_string(),
+                    param_type: "query".to_string(),
+                    required: false,
+                    description: "Real part of the view centre (default: -0.4)".to_string(),
+                },
+                RouteParameter {
+                    name: "center_y".to_string(),
+                    param_type: "query".to_string(),
+                    required: false,
+                    description: "Imaginary part of the view centre (default: -0.5)".to_string(),
+                },
+            ],
+            response_type: "FractalApiResponse".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/fractals/burning-ship"),
+        },
+        RouteInfo {
+            path: "/api/fractals/tricorn".to_string(),
+            method: "POST".to_string(),
+            description: "Generate the Tricorn (Mandelbar) fractal with the same parameters and metrics as Mandelbrot".to_string(),
+            parameters: vec![],
+            response_type: "FractalApiResponse".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/fractals/tricorn"),
+        },
+        RouteInfo {
+            path: "/api/fractals/multibrot".to_string(),
+            method: "POST".to_string(),
+            description: "Generate a Multibrot set, z^power + c, with the same parameters and metrics as Mandelbrot".to_string(),
+            parameters: vec![
+                RouteParameter {
+                    name: "power".to_string(),
+                    param_type: "query".to_string(),
+                    required: false,
+                    description: "Exponent, fractional allowed (default: 3, range: MIN_MULTIBROT_POWER to MAX_MULTIBROT_POWER)".to_string(),
+                },
+            ],
+            response_type: "FractalApiResponse".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/fractals/multibrot"),
+        },
+        RouteInfo {
+            path: "/api/fractals/mandelbulb".to_string(),
+            method: "POST".to_string(),
+            description: "Ray-march a Mandelbulb, the 3D power-n Mandelbrot, returning RGBA data or an encoded image (output_format) with hit and march-step counts".to_string(),
+            parameters: vec![
+                RouteParameter {
+                    name: "power".to_string(),
+                    param_type: "query".to_string(),
+                    required: false,
+                    description: "Bulb power (default: 8, range: 2 to 16)".to_string(),
+                },
+                RouteParameter {
+                    name: "camera".to_string(),
+                    param_type: "query".to_string(),
+                    required: false,
+                    description: "Camera position as x,y,z, with z up (default: 0,-3.4,1.2); target, also x,y,z, is the point it looks at (default: the origin) and fov its vertical angle in degrees (default: 45)".to_string(),
+                },
+                RouteParameter {
+                    name: "light".to_string(),
+                    param_type: "query".to_string(),
+                    required: false,
+                    description: "Direction the light travels in as x,y,z (default: 0.5,0.7,-0.6), with ambient from 0 to 1 (default: 0.15) lighting the far side".to_string(),
+                },
+                RouteParameter {
+                    name: "max_steps".to_string(),
+                    param_type: "query".to_string(),
+                    required: false,
+                    description: "Most steps a ray marches before it counts as a miss (default: 128, range: 16 to 512); max_iterations (default: 12, range: 4 to 64) sets the distance estimate's accuracy".to_string(),
+                },
+            ],
+            response_type: "MandelbulbApiResponse".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/fractals/mandelbulb"),
+        },
+        RouteInfo {
+            path: "/api/fractals/thumbnail".to_string(),
+            method: "GET".to_string(),
+            description: "Draw a small preview of a view or preset (preset_id) as an image, for galleries and listings to embed; iterations are capped at 256, and the encoded image is served from Redis for FRACTAL_THUMBNAIL_CACHE_TTL with a matching Cache-Control. Draws from the general API rate limit, and only uncached thumbnails spend render cost".to_string(),
+            parameters: vec![
+                RouteParameter {
+                    name: "size".to_string(),
+                    param_type: "query".to_string(),
+                    required: false,
+                    description: "Longest edge in pixels (default: 128, range: 16 to 256); the preset's aspect ratio sets the other edge, or it's square".to_string(),
+                },
+                RouteParameter {
+                    name: "fractal_type".to_string(),
+                    param_type: "query".to_string(),
+                    required: false,
+                    description: "mandelbrot, julia, burning_ship, tricorn, or multibrot (default: the preset's type, or mandelbrot); c_real, c_imag, and power apply as on the full endpoints".to_string(),
+                },
+                RouteParameter {
+                    name: "output_format".to_string(),
+                    param_type: "query".to_string(),
+                    required: false,
+                    description: "png (default), jpeg, or webp".to_string(),
+                },
+            ],
+            response_type: "image/png | image/jpeg | image/webp".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/fractals/thumbnail"),
+        },
+        RouteInfo {
+            path: "/api/fractals/animate".to_string(),
+            method: "POST".to_string(),
+            description: "Render a zoom from (start_x, start_y) at start_zoom to (end_x, end_y) at start_zoom × zoom_factor as an animated GIF, or MP4 with the ffmpeg feature; animations over ANIMATION_INLINE_MAX_PIXELS (width × height × frames), or with background set, return 202 with a job to poll at /api/fractals/animate/:id and download from /api/fractals/animate/:id/download".to_string(),
+            parameters: vec![],
+            response_type: "image/gif | video/mp4 | AnimationJob".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/fractals/animate"),
+        },
+        RouteInfo {
+            path: "/api/fractals/jobs".to_string(),
+            method: "POST".to_string(),
+            description: "Queue a render with the same JSON body as a batch line and get a job back at once; poll /api/fractals/jobs/:id for status and progress, then fetch /api/fractals/jobs/:id/result (output_format: raw, png, jpeg, or webp) while it is kept, FRACTAL_JOB_TTL_SECONDS. Renders over FRACTAL_MAX_REQUEST_COST, which synchronous endpoints refuse, are accepted here and still spend the client's per-minute cost budget".to_string(),
+            parameters: vec![],
+            response_type: "FractalJobView".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/fractals/jobs"),
+        },
+        RouteInfo {
+            path: "/api/fractals/jobs/:id".to_string(),
+            method: "DELETE".to_string(),
+            description: "Cancel a queued or running fractal job; a running render stops within a region, and the job is kept as cancelled until it expires".to_string(),
+            parameters: vec![],
+            response_type: "FractalJobView".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/fractals/jobs/:id"),
+        },
+        RouteInfo {
+            path: "/api/fractals/presets".to_string(),
+            method: "GET".to_string(),
+            description: "Curated exploration targets: notable Julia constants (kind julia_constant) and deep-zoom coordinates (kind deep_zoom), filterable by kind, fractal_type, and tag; POST, PUT /:id, and DELETE /:id edit the catalog with the admin token".to_string(),
+            parameters: vec![],
+            response_type: "Vec<CatalogPreset>".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/fractals/presets"),
+        },
+        RouteInfo {
+            path: "/api/fractals/saved".to_string(),
+            method: "POST".to_string(),
+            description: "Save a view (a title and a request, with the same JSON as a batch line) under a short share id; GET lists saves, newest first or with sort=popular by views, filterable by fractal_type and paged by limit (default: 24, max: 100) and offset".to_string(),
+            parameters: vec![],
+            response_type: "SavedFractalView".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/fractals/saved"),
+        },
+        RouteInfo {
+            path: "/api/fractals/saved/:id".to_string(),
+            method: "GET".to_string(),
+            description: "Open a save by its share id, counting a view; /:id/image returns its PNG to the account that saved it, rendered and stored on first request, and /:id/render draws it again like the fractal endpoints (output_format: raw, png, jpeg, or webp)".to_string(),
+            parameters: vec![],
+            response_type: "SavedFractalView".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/fractals/saved/:id"),
+        },
+        RouteInfo {
+            path: "/shared/fractals/:id".to_string(),
+            method: "GET".to_string(),
+            description: "A save's PNG (/shared/fractals/{id}.png) through the signed link in its signed_image_url, for sharing without the JSON API; the link stops working at signed_image_expires_at, and none are handed out unless IMAGE_SIGNING_KEY is set".to_string(),
+            parameters: vec![
+                RouteParameter {
+                    name: "expires".to_string(),
+                    param_type: "query".to_string(),
+                    required: true,
+                    description: "Unix time the link expires at".to_string(),
+                },
+                RouteParameter {
+                    name: "signature".to_string(),
+                    param_type: "query".to_string(),
+                    required: true,
+                    description: "Hex HMAC-SHA256 of the path and expiry".to_string(),
+                },
+            ],
+            response_type: "image/png".to_string(),
+            rate_limit: get_rate_limit_for_path("/shared/fractals/:id"),
+        },
+        RouteInfo {
+            path: "/api/fractals/types".to_string(),
+            method: "GET".to_string(),
+            description: "Fractal types the engine has kernels for, each with its parameter schema (name, default, bounds), default centre, whether distance estimation applies, and the renderers that can draw it".to_string(),
+            parameters: vec![],
+            response_type: "Vec<FractalTypeInfo>".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/fractals/types"),
+        },
+        RouteInfo {
+            path: "/ws/fractals".to_string(),
+            method: "GET".to_string(),
+            description: "WebSocket for interactive exploration: send viewport updates as JSON, receive each view as binary regions (24-byte little-endian header of sequence, x, y, width, height, then RGBA) between frame_started and frame_complete events; a newer update supersedes the view being sent".to_string(),
+            parameters: vec![],
+            response_type: "ExplorerEvent".to_string(),
+            rate_limit: get_rate_limit_for_path("/ws/fractals"),
+        },
+        RouteInfo {
+            path: "/api/sessions/history".to_string(),
+            method: "GET".to_string(),
+            description: "Fractals rendered by the current session (dps_session cookie or X-Session-Id header), newest first".to_string(),
+            parameters: vec![
+                RouteParameter {
+                    name: "limit".to_string(),
+                    param_type: "query".to_string(),
+                    required: false,
+                    description: "Entries to return (default and max: SESSION_HISTORY_LIMIT)".to_string(),
+                },
+                RouteParameter {
+                    name: "fractal_type".to_string(),
+                    param_type: "query".to_string(),
+                    required: false,
+                    description: "Only entries of this type, such as mandelbrot or julia".to_string(),
+                },
+            ],
+            response_type: "SessionHistory".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/sessions/history"),
+        },
+        RouteInfo {
+            path: "/api/users".to_string(),
+            method: "POST".to_string(),
+            description: "Register an account; the response includes its first API key, shown only once".to_string(),
+            parameters: vec![
+                RouteParameter {
+                    name: "username".to_string(),
+                    param_type: "body".to_string(),
+                    required: true,
+                    description: "Letters, digits, and hyphens, up to 39 characters".to_string(),
+                },
+                RouteParameter {
+                    name: "email".to_string(),
+                    param_type: "body".to_string(),
+                    required: false,
+                    description: "Contact address".to_string(),
+                },
+            ],
+            response_type: "RegisteredUser".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/users"),
+        },
+        RouteInfo {
+            path: "/api/users/github".to_string(),
+            method: "POST".to_string(),
+            description: "Sign in with a GitHub OAuth access token, creating the linked account on first use; issues a new API key".to_string(),
+            parameters: vec![
+                RouteParameter {
+                    name: "access_token".to_string(),
+                    param_type: "body".to_string(),
+                    required: true,
+                    description: "GitHub OAuth access token; used once and not stored".to_string(),
+                },
+            ],
+            response_type: "RegisteredUser".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/users/github"),
+        },
+        RouteInfo {
+            path: "/api/users/me".to_string(),
+            method: "GET".to_string(),
+            description: "The account behind the presented API key and its remaining render quota for today".to_string(),
+            parameters: vec![],
+            response_type: "UserProfile".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/users/me"),
+        },
+        RouteInfo {
+            path: "/api/users/me/keys".to_string(),
+            method: "GET".to_string(),
+            description: "List the caller's API keys, including revoked ones; POST creates a key and DELETE /:id revokes one".to_string(),
+            parameters: vec![],
+            response_type: "Vec<ApiKey>".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/users/me/keys"),
+        },
+        RouteInfo {
+            path: "/api/users/me/keys/:id/rotate".to_string(),
+            method: "POST".to_string(),
+            description: "Revoke a key and issue a replacement under the same name".to_string(),
+            parameters: vec![
+                RouteParameter {
+                    name: "id".to_string(),
+                    param_type: "path".to_string(),
+                    required: true,
+                    description: "Id of an active key".to_string(),
+                },
+            ],
+            response_type: "IssuedApiKey".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/users/me/keys/:id/rotate"),
+        },
+        RouteInfo {
+            path: "/api/admin/audit-events".to_string(),
+            method: "GET".to_string(),
+            description: "Administrative actions with actor and before/after snapshots, newest first (admin token required)".to_string(),
+            parameters: vec![
+                RouteParameter {
+                    name: "entity_type".to_string(),
+                    param_type: "query".to_string(),
+                    required: false,
+                    description: "setting, maintenance, log_filter, cache, migration, api_key, user_quota, or tenant".to_string(),
+                },
+                RouteParameter {
+                    name: "user_id".to_string(),
+                    param_type: "query".to_string(),
+                    required: false,
+                    description: "Actor, such as admin, admin:<name>, user:<username>, or cli".to_string(),
+                },
+            ],
+            response_type: "ApiResponse<Vec<AuditLog>>".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/admin/audit-events"),
+        },
+        RouteInfo {
+            path: "/api/admin/cache".to_string(),
+            method: "DELETE".to_string(),
+            description: "Flush cached responses, optionally only keys matching a pattern (admin token required)".to_string(),
+            parameters: vec![
+                RouteParameter {
+                    name: "pattern".to_string(),
+                    param_type: "query".to_string(),
+                    required: false,
+                    description: "Redis glob after the cache prefix, such as github:*".to_string(),
+                },
+            ],
+            response_type: "object".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/admin/cache"),
+        },
+        RouteInfo {
+            path: "/api/admin/circuit-breakers".to_string(),
+            method: "GET".to_string(),
+            description: "State of the GitHub, Redis, and Postgres circuit breakers (admin token required)".to_string(),
+            parameters: vec![],
+            response_type: "ApiResponse<Vec<CircuitSnapshot>>".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/admin/circuit-breakers"),
+        },
+        RouteInfo {
+            path: "/api/tenant".to_string(),
+            method: "GET".to_string(),
+            description: "The tenant serving the request, resolved from a /t/<slug> path prefix or the Host header".to_string(),
+            parameters: vec![],
+            response_type: "ApiResponse<TenantInfo>".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/tenant"),
+        },
+        RouteInfo {
+            path: "/api/admin/tenants/:slug".to_string(),
+            method: "PUT".to_string(),
+            description: "Create or replace a tenant with its GitHub user, hostnames, and setting overrides (admin token required)".to_string(),
+            parameters: vec![
+                RouteParameter {
+                    name: "slug".to_string(),
+                    param_type: "path".to_string(),
+                    required: true,
+                    description: "Lowercase letters, digits, or '-'; also the /t/<slug> path prefix".to_string(),
+                },
+                RouteParameter {
+                    name: "settings".to_string(),
+                    param_type: "body".to_string(),
+                    required: false,
+                    description: "Runtime-tunable settings for this tenant only, such as rate_limit_requests_per_minute".to_string(),
+                },
+            ],
+            response_type: "ApiResponse<Tenant>".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/admin/tenants"),
+        },
+        RouteInfo {
+            path: "/api/admin/notification-templates/:rule/:channel".to_string(),
+            method: "PUT".to_string(),
+            description: "Replace the tera template an alert rule is sent with on one channel (admin token required)".to_string(),
+            parameters: vec![
+                RouteParameter {
+                    name: "rule".to_string(),
+                    param_type: "path".to_string(),
+                    required: true,
+                    description: "Alert rule such as service_unhealthy, or '*' for every rule without its own template".to_string(),
+                },
+                RouteParameter {
+                    name: "channel".to_string(),
+                    param_type: "path".to_string(),
+                    required: true,
+                    description: "email_subject, email_body, or webhook".to_string(),
+                },
+                RouteParameter {
+                    name: "template".to_string(),
+                    param_type: "body".to_string(),
+                    required: true,
+                    description: "Template using rule, metric, value, threshold, details, dashboard_url, and fired_at".to_string(),
+                },
+            ],
+            response_type: "ApiResponse<NotificationTemplate>".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/admin/notification-templates"),
+        },
+        RouteInfo {
+            path: "/api/admin/recordings/:id/replay".to_string(),
+            method: "POST".to_string(),
+            description: "Replay a recorded request against the running code and compare its status and body (admin token required)".to_string(),
+            parameters: vec![
+                RouteParameter {
+                    name: "id".to_string(),
+                    param_type: "path".to_string(),
+                    required: true,
+                    description: "Recording id from GET /api/admin/recordings".to_string(),
+                },
+                RouteParameter {
+                    name: "headers".to_string(),
+                    param_type: "body".to_string(),
+                    required: false,
+                    description: "Headers sent in place of recorded ones, such as the Authorization header redaction removed".to_string(),
+                },
+                RouteParameter {
+                    name: "body".to_string(),
+                    param_type: "body".to_string(),
+                    required: false,
+                    description: "JSON request body sent in place of the recorded one".to_string(),
+                },
+            ],
+            response_type: "ApiResponse<ReplayResult>".to_string(),
+            rate_limit: get_rate_limit_for_path("/api/admin/recordings"),
+        },
         RouteInfo {
             path: "/api/performance/metrics".to_string(),
             method: "GET".to_string(),
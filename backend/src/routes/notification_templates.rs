@@ -0,0 +1,122 @@
+/*
+ * Notification template endpoints behind the admin token: list, preview, edit, and delete the text alerts are sent with.
+ * I'm recording every edit as an audit event and announcing it so other instances recompile their templates.
+ */
+
+use axum::{
+    extract::{Path, State},
+    response::Json as JsonResponse,
+    Json,
+};
+use std::collections::BTreeMap;
+use tracing::warn;
+
+use crate::{
+    database::notifications::{self, NotificationChannel},
+    middleware::AdminAuth,
+    models::{
+        notification_templates::{NotificationTemplate, NotificationTemplateUpdate, TemplateChannel, TemplatePreviewRequest},
+        settings::SettingReset,
+        ApiResponse, AuditAction, AuditLog,
+    },
+    utils::error::{AppError, Result},
+    AppState,
+};
+
+/// Variables every alert template can use
+const TEMPLATE_VARIABLES: [&str; 7] = ["rule", "metric", "value", "threshold", "details", "dashboard_url", "fired_at"];
+
+/// Stored templates, alongside the built-in wording they replace and the variables they may use
+pub async fn list_notification_templates(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+) -> JsonResponse<ApiResponse<serde_json::Value>> {
+    let builtin: BTreeMap<&str, &str> = TemplateChannel::ALL
+        .into_iter()
+        .map(|channel| (channel.as_str(), channel.builtin_template()))
+        .collect();
+
+    Json(ApiResponse::new(serde_json::json!({
+        "templates": app_state.notification_templates.list(),
+        "builtin": builtin,
+        "variables": TEMPLATE_VARIABLES,
+    })))
+}
+
+/// Render a draft template without saving it
+pub async fn preview_notification_template(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Json(request): Json<TemplatePreviewRequest>,
+) -> Result<JsonResponse<ApiResponse<serde_json::Value>>> {
+    let rendered = app_state.notification_templates.preview(&request.template, request.context)?;
+    Ok(Json(ApiResponse::new(serde_json::json!({
+        "channel": request.channel,
+        "rendered": rendered,
+    }))))
+}
+
+/// Create or replace the template one rule, or '*' for every rule, uses on a channel
+pub async fn upsert_notification_template(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path((rule, channel)): Path<(String, String)>,
+    Json(update): Json<NotificationTemplateUpdate>,
+) -> Result<JsonResponse<ApiResponse<NotificationTemplate>>> {
+    let channel = parse_channel(&channel)?;
+    let changed_by = super::admin::setting_actor(update.actor.as_deref());
+    let (before, template) = app_state
+        .notification_templates
+        .upsert(&rule, channel, &update.template, &changed_by)
+        .await?;
+
+    let action = if before.is_some() { AuditAction::Update } else { AuditAction::Create };
+    app_state.audit_service.record_event(AuditLog::admin_event(
+        &changed_by,
+        "notification_template",
+        format!("{}.{}", rule, channel),
+        action,
+        before.and_then(|template| serde_json::to_value(template).ok()),
+        serde_json::to_value(&template).ok(),
+    )).await;
+
+    broadcast_template_change(&app_state, &rule).await;
+    Ok(Json(ApiResponse::new(template)))
+}
+
+/// Delete a template, returning the rule to the wildcard or built-in wording
+pub async fn delete_notification_template(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path((rule, channel)): Path<(String, String)>,
+    body: Option<Json<SettingReset>>,
+) -> Result<JsonResponse<ApiResponse<serde_json::Value>>> {
+    let channel = parse_channel(&channel)?;
+    let reset = body.map(|Json(reset)| reset).unwrap_or_default();
+    let changed_by = super::admin::setting_actor(reset.actor.as_deref());
+    let template = app_state.notification_templates.delete(&rule, channel).await?;
+
+    app_state.audit_service.record_event(AuditLog::admin_event(
+        &changed_by,
+        "notification_template",
+        format!("{}.{}", rule, channel),
+        AuditAction::Delete,
+        serde_json::to_value(&template).ok(),
+        None,
+    )).await;
+
+    broadcast_template_change(&app_state, &rule).await;
+    Ok(Json(ApiResponse::new(serde_json::json!({ "deleted": { "rule": rule, "channel": channel } }))))
+}
+
+fn parse_channel(channel: &str) -> Result<TemplateChannel> {
+    channel.parse().map_err(AppError::ValidationError)
+}
+
+async fn broadcast_template_change(app_state: &AppState, rule: &str) {
+    // Other instances recompile their templates when they see this
+    let payload = serde_json::json!({ "kind": "notification_templates", "rule": rule });
+    if let Err(e) = notifications::publish(&app_state.db_pool, NotificationChannel::ConfigChanged, &payload).await {
+        warn!("Failed to broadcast notification template change: {}", e);
+    }
+}
@@ -0,0 +1,183 @@
+/*
+ * Palette and preset upload endpoints accepting multipart/form-data file uploads, plus JSON palette registration and the built-in catalogue.
+ * I'm validating uploads fully before they touch the database so stored palettes are always renderable.
+ */
+
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, Multipart, Path, Query, Request, State},
+    http::{header, StatusCode},
+    response::Json as JsonResponse,
+    Json,
+};
+use serde::Deserialize;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    middleware::CurrentTenant,
+    models::palettes::{FractalPreset, Palette, PaletteFormat, PresetBundle, MAX_UPLOAD_BYTES},
+    utils::error::{AppError, Result},
+    AppState,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct PaletteListQuery {
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+/// Fields collected from a multipart upload
+struct UploadedFile {
+    filename: Option<String>,
+    content: Vec<u8>,
+    name: Option<String>,
+    format: Option<String>,
+}
+
+/// Register a palette from a JSON body, or upload one as JSON or GIMP gradient (.ggr)
+/// I'm accepting `file` plus optional `name` and `format` form fields for uploads
+pub async fn upload_palette(
+    State(app_state): State<AppState>,
+    tenant: CurrentTenant,
+    request: Request,
+) -> Result<(StatusCode, JsonResponse<Palette>)> {
+    let upload = if is_json(&request) {
+        let content = Bytes::from_request(request, &app_state)
+            .await
+            .map_err(|e| AppError::bad_request(format!("Failed to read palette: {}", e)))?;
+        UploadedFile { filename: None, content: content.to_vec(), name: None, format: Some("json".to_string()) }
+    } else {
+        let multipart = Multipart::from_request(request, &app_state)
+            .await
+            .map_err(|e| AppError::bad_request(format!("Expected a JSON or multipart palette: {}", e)))?;
+        read_upload(multipart).await?
+    };
+
+    let format = match upload.format.as_deref() {
+        Some("json") => PaletteFormat::Json,
+        Some("ggr") => PaletteFormat::Ggr,
+        Some(other) => return Err(AppError::bad_request(format!("Unsupported palette format: {}", other))),
+        None => PaletteFormat::detect(upload.filename.as_deref(), &upload.content),
+    };
+
+    info!("Uploading {} palette ({} bytes)", format.as_str(), upload.content.len());
+    let palette = Palette::parse(format, &upload.content, upload.name)?;
+    let palette = app_state.palette_service.create_palette(&tenant.slug, palette).await?;
+
+    Ok((StatusCode::CREATED, Json(palette)))
+}
+
+/// List the tenant's stored palettes, newest first
+pub async fn list_palettes(
+    State(app_state): State<AppState>,
+    tenant: CurrentTenant,
+    Query(params): Query<PaletteListQuery>,
+) -> Result<JsonResponse<Vec<Palette>>> {
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = params.per_page.unwrap_or(20).clamp(1, 100);
+
+    let palettes = app_state.palette_service
+        .list_palettes(&tenant.slug, per_page, (page - 1) * per_page)
+        .await?;
+
+    Ok(Json(palettes))
+}
+
+/// List the named palettes every render can use through `palette=<name>`
+pub async fn list_builtin_palettes() -> JsonResponse<Vec<Palette>> {
+    Json(Palette::builtins())
+}
+
+/// Fetch a single palette by id, stored or built-in
+pub async fn get_palette(
+    State(app_state): State<AppState>,
+    tenant: CurrentTenant,
+    Path(id): Path<Uuid>,
+) -> Result<JsonResponse<Palette>> {
+    Ok(Json(app_state.palette_service.get_palette(&tenant.slug, id).await?))
+}
+
+/// Upload a preset bundle (JSON) that may embed or reference a palette
+pub async fn upload_preset(
+    State(app_state): State<AppState>,
+    tenant: CurrentTenant,
+    multipart: Multipart,
+) -> Result<(StatusCode, JsonResponse<FractalPreset>)> {
+    let upload = read_upload(multipart).await?;
+    let bundle = PresetBundle::parse(&upload.content, upload.name)?;
+
+    info!("Uploading preset bundle '{}'", bundle.name);
+    let preset = app_state.palette_service.create_preset(&tenant.slug, bundle).await?;
+
+    Ok((StatusCode::CREATED, Json(preset)))
+}
+
+/// Fetch a single preset by id
+pub async fn get_preset(
+    State(app_state): State<AppState>,
+    tenant: CurrentTenant,
+    Path(id): Path<Uuid>,
+) -> Result<JsonResponse<FractalPreset>> {
+    Ok(Json(app_state.palette_service.get_preset(&tenant.slug, id).await?))
+}
+
+fn is_json(request: &Request) -> bool {
+    request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"))
+}
+
+async fn read_upload(mut multipart: Multipart) -> Result<UploadedFile> {
+    let mut upload = UploadedFile {
+        filename: None,
+        content: Vec::new(),
+        name: None,
+        format: None,
+    };
+    let mut saw_file = false;
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::bad_request(format!("Invalid multipart body: {}", e)))?
+    {
+        let field_name = field.name().unwrap_or_default().to_string();
+        match field_name.as_str() {
+            "file" => {
+                upload.filename = field.file_name().map(str::to_string);
+                let bytes = field
+                    .bytes()
+                    .await
+                    .map_err(|e| AppError::bad_request(format!("Failed to read upload: {}", e)))?;
+                if bytes.len() > MAX_UPLOAD_BYTES {
+                    return Err(AppError::ValidationError(format!(
+                        "Uploaded file exceeds {} bytes", MAX_UPLOAD_BYTES
+                    )));
+                }
+                upload.content = bytes.to_vec();
+                saw_file = true;
+            }
+            "name" | "format" => {
+                let value = field
+                    .text()
+                    .await
+                    .map_err(|e| AppError::bad_request(format!("Invalid `{}` field: {}", field_name, e)))?;
+                if field_name == "name" {
+                    upload.name = Some(value);
+                } else {
+                    upload.format = Some(value.trim().to_lowercase());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !saw_file {
+        return Err(AppError::bad_request("Multipart body must include a `file` field"));
+    }
+
+    Ok(upload)
+}
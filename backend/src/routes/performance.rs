@@ -15,6 +15,7 @@ use tracing::{info, warn, error};
 use sysinfo::{System, SystemExt, CpuExt, DiskExt, NetworkExt};
 
 use crate::{
+    services::slo_service::SloReport,
     utils::error::{AppError, Result},
     AppState,
 };
@@ -55,7 +56,11 @@ pub struct ApplicationPerformance {
     pub fractal_computations: u64,
     pub github_api_calls: u64,
     pub cache_hit_rate: f64,
+    pub uptime_seconds: u64,
+    pub active_connections: u32,
     pub database_connections: u32,
+    pub database_query_time_ms: f64,
+    pub slow_queries: u64,
     pub memory_usage_mb: f64,
 }
 
@@ -133,19 +138,24 @@ pub async fn get_current_metrics(
     };
 
     // Application performance metrics (simplified for now)
+    let query_stats = crate::database::timing::query_stats();
     let app_perf = ApplicationPerformance {
         requests_handled: 0, // Would be tracked from middleware
         average_response_time_ms: 0.0, // Would be calculated from request timings
         fractal_computations: 0, // Would be tracked from fractal service
         github_api_calls: 0, // Would be tracked from GitHub service
         cache_hit_rate: 0.0, // Would be retrieved from cache service
+        uptime_seconds: app_state.started.uptime_seconds(),
+        active_connections: app_state.connections.active() as u32,
         database_connections: app_state.db_pool.size(),
+        database_query_time_ms: query_stats.average_query_time_ms,
+        slow_queries: query_stats.slow_queries,
         memory_usage_mb: 0.0, // Would be calculated from process memory usage
     };
 
     let runtime_info = RuntimeInfo {
-        rust_version: option_env!("BUILD_RUST_VERSION").unwrap_or("unknown").to_string(),
-        build_type: if cfg!(debug_assertions) { "debug".to_string() } else { "release".to_string() },
+        rust_version: crate::build_info::RUST_VERSION.to_string(),
+        build_type: crate::build_info::PROFILE.to_string(),
         optimization_level: if cfg!(debug_assertions) { "none".to_string() } else { "3".to_string() },
         features_enabled: get_enabled_features(),
     };
@@ -165,7 +175,7 @@ pub async fn get_current_metrics(
 /// Get detailed system information for display
 /// I'm providing comprehensive system information for the showcase
 pub async fn get_system_info(
-    State(_app_state): State<AppState>,
+    State(app_state): State<AppState>,
 ) -> Result<JsonResponse<serde_json::Value>> {
     info!("Fetching detailed system information");
     let mut system = System::new_all();
@@ -173,7 +183,9 @@ pub async fn get_system_info(
 
     let system_info = serde_json::json!({
         "timestamp": chrono::Utc::now(),
-        "os_name": system.name().unwrap_or_default()
+        "os_name": system.name().unwrap_or_default(),
+        "active_connections": app_state.connections.active(),
+        "connections_accepted": app_state.connections.accepted_total()
     });
     Ok(Json(system_info))
 }
@@ -336,6 +348,13 @@ pub async fn get_metrics_history(
     Ok(Json(history))
 }
 
+/// Compliance, burn rates, and remaining error budget for each configured SLO
+pub async fn get_slo_report(
+    State(app_state): State<AppState>,
+) -> Result<JsonResponse<SloReport>> {
+    Ok(Json(app_state.slo_service.report().await?))
+}
+
 // Helper functions for performance calculations and utilities
 
 fn is_prime(n: u32) -> bool {
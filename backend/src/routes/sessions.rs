@@ -0,0 +1,54 @@
+/*
+ * Session history endpoints letting a returning visitor list or forget the fractals they explored.
+ * I'm answering a brand-new session straight away with an empty history instead of querying for rows that can't exist yet.
+ */
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use tracing::info;
+
+use crate::{
+    middleware::{session::Session, tenant::CurrentTenant},
+    models::sessions::{SessionHistory, SessionHistoryQuery},
+    utils::error::Result,
+    AppState,
+};
+
+/// The current session's renders, newest first, optionally filtered by fractal type
+pub async fn get_session_history(
+    State(app_state): State<AppState>,
+    session: Session,
+    tenant: CurrentTenant,
+    Query(query): Query<SessionHistoryQuery>,
+) -> Result<Json<SessionHistory>> {
+    let retained = app_state.session_service.history_limit();
+    let entries = if session.is_new {
+        Vec::new()
+    } else {
+        let limit = query.limit.unwrap_or(retained as i64).clamp(1, retained as i64);
+        app_state.session_service.history(&tenant.slug, session.id, limit, query.fractal_type.as_deref()).await?
+    };
+
+    Ok(Json(SessionHistory {
+        session_id: session.id,
+        entries,
+        retained,
+    }))
+}
+
+/// Forget the current session's history
+pub async fn clear_session_history(
+    State(app_state): State<AppState>,
+    session: Session,
+    tenant: CurrentTenant,
+) -> Result<Json<serde_json::Value>> {
+    let deleted = app_state.session_service.clear(&tenant.slug, session.id).await?;
+    info!("Cleared {} history entries for session {}", deleted, session.id);
+
+    Ok(Json(serde_json::json!({
+        "session_id": session.id,
+        "deleted": deleted,
+    })))
+}
@@ -0,0 +1,92 @@
+/*
+ * Tenant endpoints: which showcase the caller is looking at publicly, and tenant management behind the admin token.
+ * I'm recording every edit as an audit event and announcing it so other instances reload their tenant directory.
+ */
+
+use axum::{
+    extract::{Path, State},
+    response::Json as JsonResponse,
+    Json,
+};
+use tracing::warn;
+
+use crate::{
+    database::notifications::{self, NotificationChannel},
+    middleware::{AdminAuth, CurrentTenant},
+    models::{
+        settings::SettingReset,
+        tenants::{Tenant, TenantInfo, TenantUpdate},
+        ApiResponse, AuditAction, AuditLog,
+    },
+    utils::error::Result,
+    AppState,
+};
+
+/// The tenant serving this request, so a frontend on a tenant's hostname can title itself
+pub async fn get_current_tenant(tenant: CurrentTenant) -> JsonResponse<ApiResponse<TenantInfo>> {
+    Json(ApiResponse::new(tenant.info()))
+}
+
+/// Every stored tenant; the default one comes from GITHUB_USERNAME and isn't listed
+pub async fn list_tenants(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+) -> JsonResponse<ApiResponse<Vec<Tenant>>> {
+    Json(ApiResponse::new(app_state.tenants.list()))
+}
+
+/// Create or replace a tenant
+pub async fn upsert_tenant(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path(slug): Path<String>,
+    Json(update): Json<TenantUpdate>,
+) -> Result<JsonResponse<ApiResponse<Tenant>>> {
+    let changed_by = super::admin::setting_actor(update.actor.as_deref());
+    let (before, tenant) = app_state.tenants.upsert(&slug, &update, &changed_by).await?;
+
+    let action = if before.is_some() { AuditAction::Update } else { AuditAction::Create };
+    app_state.audit_service.record_event(AuditLog::admin_event(
+        &changed_by,
+        "tenant",
+        slug.as_str(),
+        action,
+        before.and_then(|tenant| serde_json::to_value(tenant).ok()),
+        serde_json::to_value(&tenant).ok(),
+    )).await;
+
+    broadcast_tenant_change(&app_state, &slug).await;
+    Ok(Json(ApiResponse::new(tenant)))
+}
+
+/// Delete a tenant; its hostnames and path prefix fall back to the default showcase and 404 respectively
+pub async fn delete_tenant(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path(slug): Path<String>,
+    body: Option<Json<SettingReset>>,
+) -> Result<JsonResponse<ApiResponse<serde_json::Value>>> {
+    let reset = body.map(|Json(reset)| reset).unwrap_or_default();
+    let changed_by = super::admin::setting_actor(reset.actor.as_deref());
+    let tenant = app_state.tenants.delete(&slug).await?;
+
+    app_state.audit_service.record_event(AuditLog::admin_event(
+        &changed_by,
+        "tenant",
+        slug.as_str(),
+        AuditAction::Delete,
+        serde_json::to_value(&tenant).ok(),
+        None,
+    )).await;
+
+    broadcast_tenant_change(&app_state, &slug).await;
+    Ok(Json(ApiResponse::new(serde_json::json!({ "deleted": slug }))))
+}
+
+async fn broadcast_tenant_change(app_state: &AppState, slug: &str) {
+    // Other instances reload their tenant directory when they see this
+    let payload = serde_json::json!({ "kind": "tenants", "slug": slug });
+    if let Err(e) = notifications::publish(&app_state.db_pool, NotificationChannel::ConfigChanged, &payload).await {
+        warn!("Failed to broadcast tenant change: {}", e);
+    }
+}
@@ -0,0 +1,61 @@
+/*
+ * API key usage endpoints letting clients inspect their own consumption and admins find the heaviest callers.
+ * I'm authorising per-key lookups against the presented credential so one client can't read another's usage.
+ */
+
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::Json as JsonResponse,
+    Json,
+};
+use serde::Deserialize;
+
+use crate::{
+    middleware::{request_principal, AdminAuth},
+    services::usage_service::UsageSummary,
+    utils::error::{AppError, Result},
+    AppState,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct UsageQuery {
+    pub hours: Option<i64>,
+    pub limit: Option<usize>,
+}
+
+/// Usage for a single key; callable by that key's holder or an admin
+/// I'm accepting either the bare key id or the full `key:` principal form in the path
+pub async fn get_key_usage(
+    State(app_state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<UsageQuery>,
+    headers: HeaderMap,
+) -> Result<JsonResponse<UsageSummary>> {
+    let key_id = if id == "admin" || id.starts_with("key:") { id } else { format!("key:{}", id) };
+
+    match request_principal(&headers, app_state.config.admin_api_token.as_deref()) {
+        Some(principal) if principal == "admin" || principal == key_id => {}
+        Some(_) => return Err(AppError::AuthorizationError("Usage is only visible to the key holder".to_string())),
+        None => return Err(AppError::AuthenticationError("Missing API key".to_string())),
+    }
+
+    let summary = app_state.usage_service
+        .usage_for_key(&key_id, params.hours.unwrap_or(24))
+        .await?;
+
+    Ok(Json(summary))
+}
+
+/// Aggregate admin view of the busiest keys over the window
+pub async fn list_key_usage(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Query(params): Query<UsageQuery>,
+) -> Result<JsonResponse<Vec<UsageSummary>>> {
+    let summaries = app_state.usage_service
+        .top_keys(params.hours.unwrap_or(24), params.limit.unwrap_or(20).clamp(1, 100))
+        .await?;
+
+    Ok(Json(summaries))
+}
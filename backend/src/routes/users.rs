@@ -0,0 +1,157 @@
+/*
+ * User account endpoints: registration, GitHub sign-in, personal API key management, and the admin quota override.
+ * I'm returning a key's secret only from the call that created it, exactly like webhook signing secrets.
+ */
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    middleware::{AdminAuth, CurrentTenant, UserAuth},
+    models::{
+        users::{
+            ApiKey, CreateApiKeyRequest, GitHubSignInRequest, IssuedApiKey, RegisterUserRequest, RegisteredUser,
+            UpdateQuotaRequest, User, UserProfile,
+        },
+        AuditAction, AuditLog,
+    },
+    services::EmailTemplate,
+    utils::error::{AppError, Result},
+    AppState,
+};
+
+/// Create an account; the response carries its first API key
+pub async fn register_user(
+    State(app_state): State<AppState>,
+    tenant: CurrentTenant,
+    Json(request): Json<RegisterUserRequest>,
+) -> Result<(StatusCode, Json<RegisteredUser>)> {
+    let email = request.email.as_deref().map(str::trim).filter(|email| !email.is_empty());
+    if email.is_some_and(|email| !email.contains('@')) {
+        return Err(AppError::ValidationError("Invalid email address".to_string()));
+    }
+
+    let registered = app_state.user_service.register(&tenant.slug, request.username.trim(), email).await?;
+    notify_key_issued(&app_state, &registered.user, &registered.api_key.api_key, false);
+    Ok((StatusCode::CREATED, Json(registered)))
+}
+
+/// Exchange a GitHub OAuth access token for a linked account and a fresh API key
+pub async fn github_sign_in(
+    State(app_state): State<AppState>,
+    tenant: CurrentTenant,
+    Json(request): Json<GitHubSignInRequest>,
+) -> Result<(StatusCode, Json<RegisteredUser>)> {
+    let github_user = app_state.github_service.authenticated_user(request.access_token.trim()).await?;
+    let signed_in = app_state.user_service.sign_in_with_github(&tenant.slug, &github_user).await?;
+    notify_key_issued(&app_state, &signed_in.user, &signed_in.api_key.api_key, false);
+    Ok((StatusCode::CREATED, Json(signed_in)))
+}
+
+/// The calling account and where it stands against today's render quota
+pub async fn get_current_user(
+    State(app_state): State<AppState>,
+    UserAuth(user): UserAuth,
+) -> Result<Json<UserProfile>> {
+    let quota = app_state.user_service.quota(&user).await?;
+    Ok(Json(UserProfile { user, quota }))
+}
+
+pub async fn list_api_keys(
+    State(app_state): State<AppState>,
+    UserAuth(user): UserAuth,
+) -> Result<Json<Vec<ApiKey>>> {
+    Ok(Json(app_state.user_service.list_keys(user.id).await?))
+}
+
+pub async fn create_api_key(
+    State(app_state): State<AppState>,
+    UserAuth(user): UserAuth,
+    request: Option<Json<CreateApiKeyRequest>>,
+) -> Result<(StatusCode, Json<IssuedApiKey>)> {
+    let Json(request) = request.unwrap_or_default();
+    let key = app_state.user_service.create_key(user.id, request.name.as_deref().map(str::trim)).await?;
+    info!("User {} created API key {}", user.username, key.api_key.principal);
+    notify_key_issued(&app_state, &user, &key.api_key, false);
+    Ok((StatusCode::CREATED, Json(key)))
+}
+
+/// Revoke a key and return its replacement; the old key stops working immediately
+pub async fn rotate_api_key(
+    State(app_state): State<AppState>,
+    UserAuth(user): UserAuth,
+    Path(id): Path<Uuid>,
+) -> Result<(StatusCode, Json<IssuedApiKey>)> {
+    let key = app_state.user_service.rotate_key(user.id, id).await?;
+    notify_key_issued(&app_state, &user, &key.api_key, true);
+    app_state.audit_service.record_event(AuditLog::admin_event(
+        &user_actor(&user),
+        "api_key",
+        id.to_string(),
+        AuditAction::Update,
+        Some(serde_json::json!({ "id": id })),
+        serde_json::to_value(&key.api_key).ok(),
+    )).await;
+    Ok((StatusCode::CREATED, Json(key)))
+}
+
+pub async fn revoke_api_key(
+    State(app_state): State<AppState>,
+    UserAuth(user): UserAuth,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiKey>> {
+    let key = app_state.user_service.revoke_key(user.id, id).await?;
+    info!("User {} revoked API key {}", user.username, key.principal);
+    app_state.audit_service.record_event(AuditLog::admin_event(
+        &user_actor(&user),
+        "api_key",
+        id.to_string(),
+        AuditAction::Delete,
+        serde_json::to_value(ApiKey { revoked_at: None, ..key.clone() }).ok(),
+        serde_json::to_value(&key).ok(),
+    )).await;
+    Ok(Json(key))
+}
+
+/// Override the daily render quota of one of the tenant's users
+pub async fn set_user_quota(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    tenant: CurrentTenant,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateQuotaRequest>,
+) -> Result<Json<User>> {
+    let (before, user) = app_state.user_service.set_quota(&tenant.slug, id, request.render_quota_daily).await?;
+    info!("Set render quota for {} to {}", user.username, user.render_quota_daily);
+    app_state.audit_service.record_event(AuditLog::admin_event(
+        "admin",
+        "user_quota",
+        id.to_string(),
+        AuditAction::Update,
+        Some(serde_json::json!({ "render_quota_daily": before })),
+        Some(serde_json::json!({ "render_quota_daily": user.render_quota_daily })),
+    )).await;
+    Ok(Json(user))
+}
+
+/// Email the account holder about a new key, if they gave an address
+fn notify_key_issued(app_state: &AppState, user: &User, key: &ApiKey, rotated: bool) {
+    if let Some(email) = &user.email {
+        app_state.email_service.send(email, EmailTemplate::ApiKeyCreated {
+            username: user.username.clone(),
+            key_name: key.name.clone(),
+            prefix: key.prefix.clone(),
+            rotated,
+        });
+    }
+}
+
+/// Actor recorded for changes account holders make to their own keys
+fn user_actor(user: &User) -> String {
+    format!("user:{}", user.username)
+}
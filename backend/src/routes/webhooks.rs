@@ -0,0 +1,84 @@
+/*
+ * Webhook subscription management and delivery log endpoints, guarded by the admin bearer token.
+ * I'm returning the signing secret only from the create call so it never shows up in list responses or logs.
+ */
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json as JsonResponse,
+    Json,
+};
+use serde::Deserialize;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    middleware::AdminAuth,
+    models::webhooks::{CreateWebhookRequest, CreatedWebhook, UpdateWebhookRequest, WebhookDelivery, WebhookSubscription},
+    utils::error::Result,
+    AppState,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct DeliveryLogQuery {
+    pub limit: Option<i64>,
+}
+
+/// Register a webhook target for one or more event types
+pub async fn create_webhook(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Json(request): Json<CreateWebhookRequest>,
+) -> Result<(StatusCode, JsonResponse<CreatedWebhook>)> {
+    info!("Creating webhook subscription for {}", request.url);
+    let created = app_state.webhook_service.create_subscription(request).await?;
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+pub async fn list_webhooks(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+) -> Result<JsonResponse<Vec<WebhookSubscription>>> {
+    Ok(Json(app_state.webhook_service.list_subscriptions().await?))
+}
+
+pub async fn get_webhook(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<JsonResponse<WebhookSubscription>> {
+    Ok(Json(app_state.webhook_service.get_subscription(id).await?))
+}
+
+pub async fn update_webhook(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(update): Json<UpdateWebhookRequest>,
+) -> Result<JsonResponse<WebhookSubscription>> {
+    Ok(Json(app_state.webhook_service.update_subscription(id, update).await?))
+}
+
+pub async fn delete_webhook(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode> {
+    app_state.webhook_service.delete_subscription(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Recent delivery attempts for a subscription, newest first
+pub async fn list_webhook_deliveries(
+    _admin: AdminAuth,
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<DeliveryLogQuery>,
+) -> Result<JsonResponse<Vec<WebhookDelivery>>> {
+    // Resolve the subscription first so unknown ids 404 instead of returning an empty log
+    app_state.webhook_service.get_subscription(id).await?;
+
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+    Ok(Json(app_state.webhook_service.list_deliveries(id, limit).await?))
+}
@@ -0,0 +1,488 @@
+/*
+ * Zoom animation service rendering a run of frames between two views and encoding them as an animated GIF, or MP4 through ffmpeg.
+ * I'm rendering a batch of frames at a time in parallel and handing each batch to the encoder, so memory stays bounded however long the animation runs.
+ */
+
+use axum::body::Bytes;
+use chrono::Utc;
+use futures::stream::BoxStream;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+use tokio::io::AsyncReadExt;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    database::DatabasePool,
+    jobs::TaskQueue,
+    models::{
+        animations::{AnimationFormat, AnimationJob, AnimationRequest},
+        jobs::QueuedTask,
+        palettes::Palette,
+    },
+    services::fractal_service::{FractalRequest, FractalService, FractalType},
+    utils::error::{AppError, Result},
+};
+
+/// Task queue that animation jobs run on
+pub const ANIMATION_QUEUE: &str = "animations";
+
+const DOWNLOAD_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Middle of the gif crate's 1-30 range, trading palette quality against quantization time
+const GIF_ENCODER_SPEED: i32 = 10;
+
+/// Everything needed to render an animation, as stored with its job
+/// I'm resolving the fractal type and palette before storing so a retried job renders the frames first asked for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnimationSpec {
+    pub request: AnimationRequest,
+    pub fractal_type: FractalType,
+    pub palette: Option<Palette>,
+}
+
+impl AnimationSpec {
+    /// The render for each frame, zooming geometrically from the start view to the end view
+    /// I'm panning in step with the zoom rather than linearly, otherwise a deep zoom overshoots
+    /// the end point early and spends the last frames swinging back onto it
+    pub fn frame_requests(&self) -> Vec<FractalRequest> {
+        let request = &self.request;
+        let last = f64::from(request.frames.max(2) - 1);
+        let factor = request.zoom_factor;
+
+        (0..request.frames)
+            .map(|frame| {
+                let t = f64::from(frame) / last;
+                let pan = if (factor - 1.0).abs() < 1e-9 {
+                    t
+                } else {
+                    (1.0 - factor.powf(-t)) / (1.0 - factor.recip())
+                };
+
+                FractalRequest {
+                    center_x: request.start_x + (request.end_x - request.start_x) * pan,
+                    center_y: request.start_y + (request.end_y - request.start_y) * pan,
+                    zoom: request.start_zoom * factor.powf(t),
+                    max_iterations: request.max_iterations,
+                    palette: self.palette.clone(),
+                    coloring_mode: request.options.coloring_mode.unwrap_or_default(),
+                    orbit_trap: request.options.orbit_trap,
+                    precision: request.options.precision.unwrap_or_default(),
+                    interior_coloring: request.options.interior_coloring.unwrap_or_default(),
+                    ..FractalRequest::new(self.fractal_type.clone(), request.width, request.height)
+                }
+            })
+            .collect()
+    }
+}
+
+/// Refuse formats this build can't encode
+pub fn check_format(format: AnimationFormat) -> Result<()> {
+    match format {
+        AnimationFormat::Gif => Ok(()),
+        AnimationFormat::Mp4 if cfg!(feature = "ffmpeg") => Ok(()),
+        AnimationFormat::Mp4 => Err(AppError::ValidationError(
+            "MP4 output needs the server built with the ffmpeg feature".to_string()
+        )),
+    }
+}
+
+/// Render every frame of an animation and encode them into `out`
+/// I'm rendering as many frames at once as rayon has threads; each render is itself parallel, so this mostly keeps cores busy between frames
+pub fn render_animation<W: Write + Send + 'static>(fractal_service: &FractalService, spec: &AnimationSpec, out: W) -> Result<()> {
+    check_format(spec.request.format)?;
+    let request = &spec.request;
+    let mut encoder = FrameEncoder::new(request.format, request.width, request.height, request.fps, out)?;
+
+    let frames = spec.frame_requests();
+    for batch in frames.chunks(rayon::current_num_threads().max(1)) {
+        let rendered: Vec<Vec<u8>> = batch
+            .par_iter()
+            .map(|frame| fractal_service.render(frame.clone()).data)
+            .collect();
+        for rgba in rendered {
+            encoder.push(rgba)?;
+        }
+    }
+
+    encoder.finish()
+}
+
+enum FrameEncoder<W: Write + Send + 'static> {
+    Gif {
+        encoder: image::codecs::gif::GifEncoder<W>,
+        width: u32,
+        height: u32,
+        delay: image::Delay,
+    },
+    #[cfg(feature = "ffmpeg")]
+    Mp4(ffmpeg::Mp4Encoder<W>),
+}
+
+impl<W: Write + Send + 'static> FrameEncoder<W> {
+    fn new(format: AnimationFormat, width: u32, height: u32, fps: u32, out: W) -> Result<Self> {
+        match format {
+            AnimationFormat::Gif => {
+                let mut encoder = image::codecs::gif::GifEncoder::new_with_speed(out, GIF_ENCODER_SPEED);
+                encoder
+                    .set_repeat(image::codecs::gif::Repeat::Infinite)
+                    .map_err(|e| AppError::InternalServerError(format!("GIF encoding error: {}", e)))?;
+                Ok(FrameEncoder::Gif {
+                    encoder,
+                    width,
+                    height,
+                    delay: image::Delay::from_numer_denom_ms(1000, fps),
+                })
+            }
+            #[cfg(feature = "ffmpeg")]
+            AnimationFormat::Mp4 => Ok(FrameEncoder::Mp4(ffmpeg::Mp4Encoder::spawn(width, height, fps, out)?)),
+            #[cfg(not(feature = "ffmpeg"))]
+            AnimationFormat::Mp4 => {
+                let _ = (width, height, fps, out);
+                Err(AppError::internal("MP4 output needs the ffmpeg feature"))
+            }
+        }
+    }
+
+    fn push(&mut self, rgba: Vec<u8>) -> Result<()> {
+        match self {
+            FrameEncoder::Gif { encoder, width, height, delay } => {
+                let buffer = image::RgbaImage::from_raw(*width, *height, rgba)
+                    .ok_or_else(|| AppError::internal("Frame buffer does not match the animation size"))?;
+                encoder
+                    .encode_frame(image::Frame::from_parts(buffer, 0, 0, *delay))
+                    .map_err(|e| AppError::InternalServerError(format!("GIF encoding error: {}", e)))
+            }
+            #[cfg(feature = "ffmpeg")]
+            FrameEncoder::Mp4(encoder) => encoder.push(&rgba),
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            // The GIF trailer is written when the encoder drops
+            FrameEncoder::Gif { .. } => Ok(()),
+            #[cfg(feature = "ffmpeg")]
+            FrameEncoder::Mp4(encoder) => encoder.finish(),
+        }
+    }
+}
+
+#[cfg(feature = "ffmpeg")]
+mod ffmpeg {
+    use std::{
+        io::Write,
+        process::{Child, ChildStdin, Command, Output, Stdio},
+        thread::JoinHandle,
+    };
+
+    use crate::utils::error::{AppError, Result};
+
+    /// Pipes raw RGBA frames into an `ffmpeg` on PATH and copies the fragmented MP4 it writes to `out`
+    /// I'm collecting ffmpeg's output on its own thread, since it blocks writing stdout once the pipe fills while we block feeding it stdin
+    pub struct Mp4Encoder<W: Write + Send + 'static> {
+        stdin: ChildStdin,
+        output: JoinHandle<std::io::Result<Output>>,
+        out: W,
+    }
+
+    impl<W: Write + Send + 'static> Mp4Encoder<W> {
+        pub fn spawn(width: u32, height: u32, fps: u32, out: W) -> Result<Self> {
+            let mut child: Child = Command::new("ffmpeg")
+                .args(["-loglevel", "error", "-f", "rawvideo", "-pix_fmt", "rgba"])
+                .args(["-s", &format!("{}x{}", width, height), "-r", &fps.to_string(), "-i", "pipe:0"])
+                // yuv420p needs even dimensions
+                .args(["-vf", "scale=trunc(iw/2)*2:trunc(ih/2)*2", "-c:v", "libx264", "-pix_fmt", "yuv420p"])
+                .args(["-movflags", "frag_keyframe+empty_moov", "-f", "mp4", "pipe:1"])
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .map_err(|e| AppError::InternalServerError(format!("Failed to start ffmpeg: {}", e)))?;
+
+            let stdin = child.stdin.take().ok_or_else(|| AppError::internal("ffmpeg has no stdin"))?;
+            let output = std::thread::spawn(move || child.wait_with_output());
+            Ok(Self { stdin, output, out })
+        }
+
+        pub fn push(&mut self, rgba: &[u8]) -> Result<()> {
+            self.stdin
+                .write_all(rgba)
+                .map_err(|e| AppError::InternalServerError(format!("Failed to send frame to ffmpeg: {}", e)))
+        }
+
+        pub fn finish(self) -> Result<()> {
+            let Self { stdin, output, mut out } = self;
+            drop(stdin);
+
+            let output = output
+                .join()
+                .map_err(|_| AppError::internal("ffmpeg output thread panicked"))?
+                .map_err(|e| AppError::InternalServerError(format!("ffmpeg failed: {}", e)))?;
+            if !output.status.success() {
+                return Err(AppError::InternalServerError(format!(
+                    "ffmpeg exited with {}: {}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )));
+            }
+
+            out.write_all(&output.stdout)
+                .and_then(|_| out.flush())
+                .map_err(|e| AppError::InternalServerError(format!("Failed to write MP4: {}", e)))
+        }
+    }
+}
+
+/// Zoom animations, rendered inline when small and as background jobs otherwise
+#[derive(Clone)]
+pub struct AnimationService {
+    db_pool: DatabasePool,
+    queue: TaskQueue,
+    fractal_service: FractalService,
+    storage_dir: PathBuf,
+}
+
+impl AnimationService {
+    pub fn new(db_pool: DatabasePool, queue: TaskQueue, fractal_service: FractalService, storage_dir: impl Into<PathBuf>) -> Self {
+        Self { db_pool, queue, fractal_service, storage_dir: storage_dir.into() }
+    }
+
+    /// Render an animation within the request, returning the encoded file
+    pub async fn render(&self, spec: AnimationSpec) -> Result<Vec<u8>> {
+        let fractal_service = self.fractal_service.clone();
+        let span = tracing::Span::current();
+        tokio::task::spawn_blocking(move || {
+            span.in_scope(|| {
+                let mut encoded = SharedBuffer::default();
+                render_animation(&fractal_service, &spec, encoded.clone())?;
+                Ok(encoded.take())
+            })
+        })
+        .await
+        .map_err(|e| AppError::FractalComputationError(format!("Animation task failed: {}", e)))?
+    }
+
+    /// Record an animation job and queue it
+    pub async fn submit(&self, spec: AnimationSpec) -> Result<AnimationJob> {
+        let id = Uuid::new_v4();
+        let job = sqlx::query_as::<_, AnimationJob>(
+            r#"
+            INSERT INTO animation_jobs (id, format, parameters, frame_count, status)
+            VALUES ($1, $2, $3, $4, 'queued')
+            RETURNING id, format, parameters, frame_count, status, byte_size, error, created_at, completed_at
+            "#
+        )
+        .bind(id)
+        .bind(spec.request.format.as_str())
+        .bind(serde_json::to_value(&spec)?)
+        .bind(spec.request.frames as i32)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        if let Err(e) = self.queue.enqueue(ANIMATION_QUEUE, "animation.render", &serde_json::json!({ "animation_id": id }), None).await {
+            self.set_status(id, "failed", None, Some(format!("Failed to queue animation: {}", e))).await?;
+            return Err(e);
+        }
+
+        info!("Queued {}-frame {} animation {}", spec.request.frames, spec.request.format.as_str(), id);
+        Ok(job)
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<AnimationJob> {
+        sqlx::query_as::<_, AnimationJob>(
+            "SELECT id, format, parameters, frame_count, status, byte_size, error, created_at, completed_at
+             FROM animation_jobs WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::not_found(format!("animation {}", id)))
+    }
+
+    /// Render one queued animation to disk; errors hand the retry back to the queue
+    pub async fn run(&self, task: QueuedTask) -> Result<()> {
+        let id: Uuid = serde_json::from_value(task.payload["animation_id"].clone())?;
+        let job = self.get(id).await?;
+        // A lease that expired after the file was written hands the task out again
+        if job.status == "succeeded" {
+            return Ok(());
+        }
+
+        self.set_status(id, "running", None, None).await?;
+        let started = Utc::now();
+        match self.write_file(&job).await {
+            Ok(bytes) => {
+                info!("Animation {} finished in {}ms ({} bytes)", id, (Utc::now() - started).num_milliseconds(), bytes);
+                self.set_status(id, "succeeded", Some(bytes), None).await
+            }
+            Err(e) => {
+                let status = if task.is_last_attempt() { "failed" } else { "queued" };
+                if let Err(update) = self.set_status(id, status, None, Some(e.to_string())).await {
+                    warn!("Failed to record failure of animation {}: {}", id, update);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Stream a finished animation's file, with its content type
+    pub async fn download(&self, id: Uuid) -> Result<(AnimationFormat, BoxStream<'static, Result<Bytes>>)> {
+        let job = self.get(id).await?;
+        if job.status != "succeeded" {
+            return Err(AppError::BadRequestError(format!("Animation {} is {}, not ready for download", id, job.status)));
+        }
+        let format: AnimationFormat = serde_json::from_value(serde_json::Value::String(job.format.clone()))?;
+
+        let mut file = tokio::fs::File::open(self.file_path(id, format))
+            .await
+            .map_err(|e| AppError::NotFoundError(format!("Animation file for {} is missing: {}", id, e)))?;
+
+        Ok((format, Box::pin(async_stream::stream! {
+            let mut buffer = vec![0u8; DOWNLOAD_CHUNK_BYTES];
+            loop {
+                match file.read(&mut buffer).await {
+                    Ok(0) => break,
+                    Ok(read) => yield Ok(Bytes::copy_from_slice(&buffer[..read])),
+                    Err(e) => {
+                        yield Err(AppError::InternalServerError(format!("Failed to read animation file: {}", e)));
+                        break;
+                    }
+                }
+            }
+        })))
+    }
+
+    /// Encode the animation next to its final path and rename it into place, returning its size
+    async fn write_file(&self, job: &AnimationJob) -> Result<i64> {
+        let spec: AnimationSpec = serde_json::from_value(job.parameters.clone())?;
+
+        tokio::fs::create_dir_all(&self.storage_dir)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Failed to create animation directory: {}", e)))?;
+        let path = self.file_path(job.id, spec.request.format);
+        let tmp_path = path.with_extension(format!("{}.part", spec.request.format.as_str()));
+
+        let fractal_service = self.fractal_service.clone();
+        let span = tracing::Span::current();
+        let render_path = tmp_path.clone();
+        tokio::task::spawn_blocking(move || {
+            span.in_scope(|| {
+                let file = std::fs::File::create(&render_path)
+                    .map_err(|e| AppError::InternalServerError(format!("Failed to create animation file: {}", e)))?;
+                render_animation(&fractal_service, &spec, std::io::BufWriter::new(file))
+            })
+        })
+        .await
+        .map_err(|e| AppError::FractalComputationError(format!("Animation task failed: {}", e)))??;
+
+        let bytes = file_size(&tmp_path).await?;
+        tokio::fs::rename(&tmp_path, &path)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Failed to finalise animation file: {}", e)))?;
+        Ok(bytes)
+    }
+
+    async fn set_status(&self, id: Uuid, status: &str, byte_size: Option<i64>, error: Option<String>) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE animation_jobs
+            SET status = $2, byte_size = COALESCE($3, byte_size),
+                error = CASE WHEN $2 = 'succeeded' THEN NULL ELSE COALESCE($4, error) END,
+                completed_at = CASE WHEN $2 IN ('succeeded', 'failed') THEN NOW() ELSE NULL END
+            WHERE id = $1
+            "#
+        )
+        .bind(id)
+        .bind(status)
+        .bind(byte_size)
+        .bind(error)
+        .execute(&self.db_pool)
+        .await?;
+        Ok(())
+    }
+
+    fn file_path(&self, id: Uuid, format: AnimationFormat) -> PathBuf {
+        self.storage_dir.join(format!("{}.{}", id, format.as_str()))
+    }
+}
+
+async fn file_size(path: &Path) -> Result<i64> {
+    let metadata = tokio::fs::metadata(path)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Failed to read animation file: {}", e)))?;
+    Ok(metadata.len() as i64)
+}
+
+/// In-memory sink the encoders can own while the caller keeps a handle to the bytes
+#[derive(Clone, Default)]
+struct SharedBuffer(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn take(&mut self) -> Vec<u8> {
+        std::mem::take(&mut *self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner()))
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(zoom_factor: f64, frames: u32) -> AnimationSpec {
+        let request: AnimationRequest = serde_json::from_value(serde_json::json!({
+            "width": 64,
+            "height": 64,
+            "start_x": -0.5,
+            "start_y": 0.0,
+            "end_x": -0.75,
+            "end_y": 0.1,
+            "zoom_factor": zoom_factor,
+            "frames": frames,
+            "max_iterations": 50,
+        }))
+        .unwrap();
+        AnimationSpec { request, fractal_type: FractalType::Mandelbrot, palette: None }
+    }
+
+    #[test]
+    fn frames_run_from_the_start_view_to_the_end_view() {
+        let frames = spec(1000.0, 10).frame_requests();
+        let (first, last) = (&frames[0], &frames[9]);
+        assert_eq!(frames.len(), 10);
+        assert_eq!((first.center_x, first.center_y, first.zoom), (-0.5, 0.0, 1.0));
+        assert!((last.center_x + 0.75).abs() < 1e-12 && (last.center_y - 0.1).abs() < 1e-12);
+        assert!((last.zoom - 1000.0).abs() < 1e-6);
+        // Zoom steps are geometric, so each frame is the same factor deeper than the last
+        let step = frames[1].zoom / frames[0].zoom;
+        assert!((frames[5].zoom / frames[4].zoom - step).abs() < 1e-9);
+    }
+
+    #[test]
+    fn gif_has_one_image_per_frame() {
+        let mut encoded = SharedBuffer::default();
+        render_animation(&FractalService::new(), &spec(4.0, 3), encoded.clone()).unwrap();
+        let encoded = encoded.take();
+        assert_eq!(&encoded[..6], b"GIF89a");
+
+        use image::AnimationDecoder;
+        let decoder = image::codecs::gif::GifDecoder::new(std::io::Cursor::new(encoded)).unwrap();
+        let frames = decoder.into_frames().collect_frames().unwrap();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].buffer().dimensions(), (64, 64));
+    }
+}
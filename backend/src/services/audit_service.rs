@@ -0,0 +1,228 @@
+/*
+ * Audit logging service persisting sampled request records and administrative events, and serving both back to administrators.
+ * I'm keeping the request write path fire-and-forget so auditing never adds latency to the request it describes.
+ */
+
+use sqlx::{postgres::PgArguments, Postgres, Row};
+use tracing::{debug, warn};
+
+use crate::{
+    database::DatabasePool,
+    models::{AuditLog, AuditLogQuery, HTTP_REQUEST_AUDIT_ENTITY},
+    utils::{
+        config::Config,
+        correlation,
+        error::{AppError, Result},
+    },
+};
+
+/// Settings controlling which requests are audited and how much is stored
+/// I'm snapshotting these from Config so the middleware doesn't need the whole config
+#[derive(Debug, Clone)]
+pub struct AuditSettings {
+    pub enabled: bool,
+    pub sample_rate: f64,
+    pub redact_pii: bool,
+}
+
+impl AuditSettings {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            enabled: config.audit_log_enabled,
+            sample_rate: config.audit_log_sample_rate,
+            redact_pii: config.audit_log_redact_pii,
+        }
+    }
+
+    /// Decide whether a request with the given status should be recorded
+    /// I'm always keeping failures so sampling never hides the interesting requests
+    pub fn should_record(&self, status_code: u16) -> bool {
+        if !self.enabled {
+            return false;
+        }
+
+        if status_code >= 400 || self.sample_rate >= 1.0 {
+            return true;
+        }
+
+        self.sample_rate > 0.0 && rand::random::<f64>() < self.sample_rate
+    }
+}
+
+/// Which rows a listing covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditScope {
+    All,
+    /// Only administrative events, leaving out per-request rows
+    AdminEvents,
+}
+
+const AUDIT_FILTERS: &str = "
+    WHERE ($1::text IS NULL OR user_id = $1)
+      AND ($2::text IS NULL OR path LIKE $2 || '%')
+      AND ($3::text IS NULL OR method = $3)
+      AND ($4::int IS NULL OR status_code = $4)
+      AND ($5::timestamptz IS NULL OR timestamp >= $5)
+      AND ($6::timestamptz IS NULL OR timestamp < $6)
+      AND ($7::text IS NULL OR entity_type = $7)
+      AND ($8::text IS NULL OR entity_id = $8)
+      AND ($9::text IS NULL OR action = $9)
+      AND (NOT $10 OR entity_type <> $11)";
+
+#[derive(Debug, Clone)]
+pub struct AuditService {
+    db_pool: DatabasePool,
+    settings: AuditSettings,
+}
+
+impl AuditService {
+    pub fn new(db_pool: DatabasePool, settings: AuditSettings) -> Self {
+        Self { db_pool, settings }
+    }
+
+    pub fn settings(&self) -> &AuditSettings {
+        &self.settings
+    }
+
+    /// Queue an audit entry for insertion without blocking the caller
+    /// I'm spawning the insert so a slow database never holds up the response
+    pub fn record(&self, entry: AuditLog) {
+        let pool = self.db_pool.clone();
+        correlation::spawn(async move {
+            if let Err(e) = insert_audit_log(&pool, &entry).await {
+                warn!("Failed to persist audit log for {:?} {:?}: {}", entry.method, entry.path, e);
+            }
+        });
+    }
+
+    /// Persist an administrative event, always and before returning
+    /// I'm bypassing the request sampling and enabled switch since these rows are the trail of who changed what, and a failed write is logged rather than undoing an action that already happened
+    pub async fn record_event(&self, entry: AuditLog) {
+        if let Err(e) = insert_audit_log(&self.db_pool, &entry).await {
+            warn!(
+                "Failed to persist audit event {} {:?} by {:?}: {}",
+                entry.entity_type, entry.entity_id, entry.user_id, e
+            );
+        }
+    }
+
+    /// List audit entries newest first with optional filters
+    /// I'm returning the total alongside the page so callers can build pagination metadata
+    pub async fn list(&self, query: &AuditLogQuery, scope: AuditScope) -> Result<(Vec<AuditLog>, i64)> {
+        let total: i64 = bind_filters(sqlx::query(&format!("SELECT COUNT(*) AS total FROM audit_logs {}", AUDIT_FILTERS)), query, scope)
+            .fetch_one(&self.db_pool)
+            .await?
+            .try_get("total")?;
+
+        let select = format!(
+            "SELECT id, entity_type, entity_id, action, user_id, ip_address, user_agent,
+                    method, path, status_code, latency_ms, timestamp, changes, metadata
+             FROM audit_logs {}
+             ORDER BY timestamp DESC
+             LIMIT $12 OFFSET $13",
+            AUDIT_FILTERS
+        );
+        let rows = bind_filters(sqlx::query(&select), query, scope)
+        .bind(query.per_page() as i64)
+        .bind(query.offset() as i64)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let entries = rows
+            .iter()
+            .map(audit_log_from_row)
+            .collect::<Result<Vec<_>>>()?;
+
+        debug!("Loaded {} of {} audit log entries", entries.len(), total);
+        Ok((entries, total))
+    }
+}
+
+fn bind_filters<'q>(
+    statement: sqlx::query::Query<'q, Postgres, PgArguments>,
+    query: &'q AuditLogQuery,
+    scope: AuditScope,
+) -> sqlx::query::Query<'q, Postgres, PgArguments> {
+    statement
+        .bind(&query.user_id)
+        .bind(&query.path)
+        .bind(query.method.as_ref().map(|m| m.to_uppercase()))
+        .bind(query.status_code)
+        .bind(query.since)
+        .bind(query.until)
+        .bind(&query.entity_type)
+        .bind(&query.entity_id)
+        .bind(query.action.as_ref().map(|a| a.to_lowercase()))
+        .bind(scope == AuditScope::AdminEvents)
+        .bind(HTTP_REQUEST_AUDIT_ENTITY)
+}
+
+async fn insert_audit_log(pool: &DatabasePool, entry: &AuditLog) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO audit_logs (
+            id, entity_type, entity_id, action, user_id, ip_address, user_agent,
+            method, path, status_code, latency_ms, timestamp, changes, metadata
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+        "#
+    )
+    .bind(entry.id)
+    .bind(&entry.entity_type)
+    .bind(&entry.entity_id)
+    .bind(entry.action.as_str())
+    .bind(&entry.user_id)
+    .bind(&entry.ip_address)
+    .bind(&entry.user_agent)
+    .bind(&entry.method)
+    .bind(&entry.path)
+    .bind(entry.status_code)
+    .bind(entry.latency_ms)
+    .bind(entry.timestamp)
+    .bind(&entry.changes)
+    .bind(&entry.metadata)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+fn audit_log_from_row(row: &sqlx::postgres::PgRow) -> Result<AuditLog> {
+    let action: String = row.try_get("action")?;
+
+    Ok(AuditLog {
+        id: row.try_get("id")?,
+        entity_type: row.try_get("entity_type")?,
+        entity_id: row.try_get("entity_id")?,
+        action: action.parse().map_err(AppError::DatabaseError)?,
+        user_id: row.try_get("user_id")?,
+        ip_address: row.try_get("ip_address")?,
+        user_agent: row.try_get("user_agent")?,
+        method: row.try_get("method")?,
+        path: row.try_get("path")?,
+        status_code: row.try_get("status_code")?,
+        latency_ms: row.try_get("latency_ms")?,
+        timestamp: row.try_get("timestamp")?,
+        changes: row.try_get("changes")?,
+        metadata: row.try_get("metadata")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_settings_record_nothing() {
+        let settings = AuditSettings { enabled: false, sample_rate: 1.0, redact_pii: false };
+        assert!(!settings.should_record(200));
+        assert!(!settings.should_record(500));
+    }
+
+    #[test]
+    fn test_errors_bypass_sampling() {
+        let settings = AuditSettings { enabled: true, sample_rate: 0.0, redact_pii: false };
+        assert!(!settings.should_record(200));
+        assert!(settings.should_record(404));
+        assert!(settings.should_record(503));
+    }
+}
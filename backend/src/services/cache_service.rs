@@ -2,20 +2,29 @@
 
 use redis::{Client, AsyncCommands}; // Removed `Connection` as it wasn't directly used in the struct
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::future::Future;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use tracing::{info, warn, error, debug};
+use tracing::{info, warn, error, debug, instrument};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tokio::sync::RwLock;
 
-use crate::utils::error::{AppError, Result};
+use crate::utils::{
+    circuit_breaker::CircuitBreaker,
+    error::{AppError, Result},
+};
+
+/// Namespaces under the cache prefix that hold other services' state rather than cached responses
+const NON_CACHE_NAMESPACES: [&str; 5] = ["queue:", "leader:", "usage:", "fractal_jobs:", "ratelimit:"];
 
 
 #[derive(Clone)]
 pub struct CacheService {
     client: Client,
     key_prefix: String,
-    default_ttl: u64,
+    default_ttl: Arc<AtomicU64>,
     connection_pool: Arc<RwLock<Option<redis::aio::ConnectionManager>>>,
+    circuit_breaker: Option<CircuitBreaker>,
 }
 
 // Manually implement Debug for CacheService
@@ -24,7 +33,7 @@ impl std::fmt::Debug for CacheService {
         f.debug_struct("CacheService")
             .field("client", &"<RedisClient>") // Placeholder for client as it might not be Debug or simple to Debug
             .field("key_prefix", &self.key_prefix)
-            .field("default_ttl", &self.default_ttl)
+            .field("default_ttl", &self.default_ttl())
             .field("connection_pool", &"<ConnectionPool>") // Placeholder for connection_pool
             .finish()
         // Or, if you want to indicate that some fields are not shown:
@@ -76,8 +85,9 @@ impl CacheService {
         Self {
             client: redis_client,
             key_prefix: "perf_showcase:".to_string(),
-            default_ttl: 3600, // 1 hour default TTL
+            default_ttl: Arc::new(AtomicU64::new(3600)), // 1 hour default TTL
             connection_pool: Arc::new(RwLock::new(None)),
+            circuit_breaker: None,
         }
     }
 
@@ -87,14 +97,48 @@ impl CacheService {
         Self {
             client: redis_client,
             key_prefix,
-            default_ttl,
+            default_ttl: Arc::new(AtomicU64::new(default_ttl)),
             connection_pool: Arc::new(RwLock::new(None)),
+            circuit_breaker: None,
+        }
+    }
+
+    /// Guard cache calls with this breaker so a Redis outage fails them fast instead of on every command timeout
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(breaker);
+        self
+    }
+
+    /// A view of this cache whose keys all live under `namespace`, sharing the connection, breaker, and default TTL
+    /// I'm nesting the namespace inside the prefix so a global flush still reaches it and a scoped flush reaches nothing else
+    pub fn scoped(&self, namespace: &str) -> Self {
+        Self {
+            key_prefix: format!("{}{}:", self.key_prefix, namespace),
+            ..self.clone()
+        }
+    }
+
+    /// TTL applied when a caller doesn't pass one
+    pub fn default_ttl(&self) -> u64 {
+        self.default_ttl.load(Ordering::Relaxed)
+    }
+
+    /// Change the default TTL for every clone of this service; entries already stored keep their expiry
+    pub fn set_default_ttl(&self, seconds: u64) {
+        self.default_ttl.store(seconds, Ordering::Relaxed);
+    }
+
+    /// Run a Redis operation through the circuit breaker when one is attached, so failed commands count as well as failed connects
+    async fn guarded<T>(&self, operation: impl Future<Output = Result<T>>) -> Result<T> {
+        match &self.circuit_breaker {
+            Some(breaker) => breaker.call(|| operation).await,
+            None => operation.await,
         }
     }
 
     /// Get a connection with automatic pool management
     /// I'm implementing intelligent connection pooling with automatic recovery
-    async fn get_connection(&self) -> Result<redis::aio::ConnectionManager> {
+    async fn connect(&self) -> Result<redis::aio::ConnectionManager> {
         let mut pool_guard = self.connection_pool.write().await;
 
         if let Some(conn_manager) = pool_guard.as_ref() {
@@ -146,101 +190,110 @@ impl CacheService {
 
     /// Get a value from cache with automatic deserialization
     /// I'm implementing intelligent cache retrieval with metadata tracking
+    #[instrument(name = "cache.get", level = "debug", skip(self), err(Display, level = "debug"))]
     pub async fn get<T>(&self, key: &str) -> Result<Option<T>>
     where
     T: DeserializeOwned + Send + Sync + Serialize,
     {
-        let full_key = self.build_key(key);
-        let mut conn = self.get_connection().await?;
-
-        debug!("Cache GET: {}", full_key);
-
-        match conn.get::<_, Option<String>>(&full_key).await {
-            Ok(Some(cached_data)) => {
-                match serde_json::from_str::<CacheEntry<T>>(&cached_data) {
-                    Ok(mut entry) => {
-                        let now = self.current_timestamp();
-
-                        // Check if entry has expired
-                        if now > entry.expires_at {
-                            debug!("Cache entry expired: {}", full_key);
-                            // Asynchronously delete expired entry
-                            let _ = self.delete(key).await; // Use existing delete method
-                            return Ok(None);
-                        }
+        self.guarded(async {
+            let full_key = self.build_key(key);
+            let mut conn = self.connect().await?;
 
-                        // Update access metadata
-                        entry.access_count += 1;
-                        entry.last_accessed = now;
-
-                        // Update entry in cache (fire and forget, but handle potential errors)
-                        let updated_data_res = serde_json::to_string(&entry);
-                        if let Ok(updated_data) = updated_data_res {
-                           let set_result = conn.set::<_, _, ()>(&full_key, updated_data).await;
-                           if let Err(e) = set_result {
-                               warn!("Failed to update access metadata for cache key {}: {}", full_key, e);
-                           }
-                        } else if let Err(e) = updated_data_res {
-                             warn!("Failed to serialize updated metadata for cache key {}: {}", full_key, e);
-                        }
+            debug!("Cache GET: {}", full_key);
 
+            match conn.get::<_, Option<String>>(&full_key).await {
+                Ok(Some(cached_data)) => {
+                    match serde_json::from_str::<CacheEntry<T>>(&cached_data) {
+                        Ok(mut entry) => {
+                            let now = self.current_timestamp();
 
-                        debug!("Cache HIT: {}", full_key);
-                        Ok(Some(entry.data))
-                    }
-                    Err(e) => {
-                        warn!("Failed to deserialize cache entry {}: {}", full_key, e);
-                        // Delete corrupted entry
-                        let _ = self.delete(key).await;
-                        Ok(None)
+                            // Check if entry has expired
+                            if now > entry.expires_at {
+                                debug!("Cache entry expired: {}", full_key);
+                                // Asynchronously delete expired entry
+                                let _ = self.delete(key).await; // Use existing delete method
+                                return Ok(None);
+                            }
+
+                            // Update access metadata
+                            entry.access_count += 1;
+                            entry.last_accessed = now;
+
+                            // Update entry in cache (fire and forget, but handle potential errors)
+                            let updated_data_res = serde_json::to_string(&entry);
+                            if let Ok(updated_data) = updated_data_res {
+                               let set_result = conn.set::<_, _, ()>(&full_key, updated_data).await;
+                               if let Err(e) = set_result {
+                                   warn!("Failed to update access metadata for cache key {}: {}", full_key, e);
+                               }
+                            } else if let Err(e) = updated_data_res {
+                                 warn!("Failed to serialize updated metadata for cache key {}: {}", full_key, e);
+                            }
+
+
+                            debug!("Cache HIT: {}", full_key);
+                            Ok(Some(entry.data))
+                        }
+                        Err(e) => {
+                            warn!("Failed to deserialize cache entry {}: {}", full_key, e);
+                            // Delete corrupted entry
+                            let _ = self.delete(key).await;
+                            Ok(None)
+                        }
                     }
                 }
+                Ok(None) => {
+                    debug!("Cache MISS: {}", full_key);
+                    Ok(None)
+                }
+                Err(e) => {
+                    error!("Cache GET error for {}: {}", full_key, e);
+                    Err(AppError::CacheError(format!("Failed to get cache entry: {}", e)))
+                }
             }
-            Ok(None) => {
-                debug!("Cache MISS: {}", full_key);
-                Ok(None)
-            }
-            Err(e) => {
-                error!("Cache GET error for {}: {}", full_key, e);
-                Err(AppError::CacheError(format!("Failed to get cache entry: {}", e)))
-            }
-        }
+        })
+        .await
     }
 
     /// Set a value in cache with optional TTL
     /// I'm implementing intelligent cache storage with metadata and expiration management
+    #[instrument(name = "cache.set", level = "debug", skip(self, value), err(Display, level = "debug"))]
     pub async fn set<T>(&self, key: &str, value: &T, ttl_seconds: Option<u64>) -> Result<()>
     where
     T: Serialize + Send + Sync,
     {
-        let full_key = self.build_key(key);
-        let ttl = ttl_seconds.unwrap_or(self.default_ttl);
-        let now = self.current_timestamp();
-
-        let entry = CacheEntry {
-            data: value,
-            created_at: now,
-            expires_at: now + ttl,
-            access_count: 0,
-            last_accessed: now,
-            version: 1,
-        };
+        self.guarded(async {
+            let full_key = self.build_key(key);
+            let ttl = ttl_seconds.unwrap_or_else(|| self.default_ttl());
+            let now = self.current_timestamp();
+
+            let entry = CacheEntry {
+                data: value,
+                created_at: now,
+                expires_at: now + ttl,
+                access_count: 0,
+                last_accessed: now,
+                version: 1,
+            };
 
-        let serialized = serde_json::to_string(&entry)
-        .map_err(|e| AppError::SerializationError(format!("Failed to serialize cache entry: {}", e)))?;
+            let serialized = serde_json::to_string(&entry)
+            .map_err(|e| AppError::SerializationError(format!("Failed to serialize cache entry: {}", e)))?;
 
-        let mut conn = self.get_connection().await?;
+            let mut conn = self.connect().await?;
 
-        debug!("Cache SET: {} (TTL: {}s)", full_key, ttl);
+            debug!("Cache SET: {} (TTL: {}s)", full_key, ttl);
 
-        conn.set_ex::<_, _, ()>(&full_key, serialized, ttl).await // Using set_ex for value and TTL together
-        .map_err(|e| AppError::CacheError(format!("Failed to set cache entry: {}", e)))?;
+            conn.set_ex::<_, _, ()>(&full_key, serialized, ttl).await // Using set_ex for value and TTL together
+            .map_err(|e| AppError::CacheError(format!("Failed to set cache entry: {}", e)))?;
 
-        Ok(())
+            Ok(())
+        })
+        .await
     }
 
     /// Set a value in cache with default TTL
     /// I'm providing a convenient method for standard cache operations
+    #[instrument(name = "cache.set_default", level = "debug", skip(self, value), err(Display, level = "debug"))]
     pub async fn set_default<T>(&self, key: &str, value: &T) -> Result<()>
     where
     T: Serialize + Send + Sync,
@@ -250,234 +303,297 @@ impl CacheService {
 
     /// Delete a value from cache
     /// I'm implementing safe cache invalidation with error handling
+    #[instrument(name = "cache.delete", level = "debug", skip(self), ret, err(Display, level = "debug"))]
     pub async fn delete(&self, key: &str) -> Result<bool> {
-        let full_key = self.build_key(key);
-        let mut conn = self.get_connection().await?;
+        self.guarded(async {
+            let full_key = self.build_key(key);
+            let mut conn = self.connect().await?;
 
-        debug!("Cache DELETE: {}", full_key);
+            debug!("Cache DELETE: {}", full_key);
 
-        let deleted: i32 = conn.del(&full_key).await
-        .map_err(|e| AppError::CacheError(format!("Failed to delete cache entry: {}", e)))?;
+            let deleted: i32 = conn.del(&full_key).await
+            .map_err(|e| AppError::CacheError(format!("Failed to delete cache entry: {}", e)))?;
 
-        Ok(deleted > 0)
+            Ok(deleted > 0)
+        })
+        .await
     }
 
     /// Check if a key exists in cache
     /// I'm providing cache presence verification
+    #[instrument(name = "cache.exists", level = "debug", skip(self), ret, err(Display, level = "debug"))]
     pub async fn exists(&self, key: &str) -> Result<bool> {
-        let full_key = self.build_key(key);
-        let mut conn = self.get_connection().await?;
+        self.guarded(async {
+            let full_key = self.build_key(key);
+            let mut conn = self.connect().await?;
 
-        let exists: bool = conn.exists(&full_key).await
-        .map_err(|e| AppError::CacheError(format!("Failed to check cache existence: {}", e)))?;
+            let exists: bool = conn.exists(&full_key).await
+            .map_err(|e| AppError::CacheError(format!("Failed to check cache existence: {}", e)))?;
 
-        Ok(exists)
+            Ok(exists)
+        })
+        .await
     }
 
     /// Set expiration time for an existing key
     /// I'm providing TTL management for existing cache entries
+    #[instrument(name = "cache.expire", level = "debug", skip(self), ret, err(Display, level = "debug"))]
     pub async fn expire(&self, key: &str, ttl_seconds: u64) -> Result<bool> {
-        let full_key = self.build_key(key);
-        let mut conn = self.get_connection().await?;
+        self.guarded(async {
+            let full_key = self.build_key(key);
+            let mut conn = self.connect().await?;
 
-        debug!("Cache EXPIRE: {} (TTL: {}s)", full_key, ttl_seconds);
+            debug!("Cache EXPIRE: {} (TTL: {}s)", full_key, ttl_seconds);
 
-        let expired: bool = conn.expire::<_, _>(&full_key, ttl_seconds as i64).await
-        .map_err(|e| AppError::CacheError(format!("Failed to set cache expiration: {}", e)))?;
+            let expired: bool = conn.expire::<_, _>(&full_key, ttl_seconds as i64).await
+            .map_err(|e| AppError::CacheError(format!("Failed to set cache expiration: {}", e)))?;
 
-        Ok(expired)
+            Ok(expired)
+        })
+        .await
     }
 
     /// Get remaining TTL for a key
     /// I'm providing TTL inspection for cache management
+    #[instrument(name = "cache.ttl", level = "debug", skip(self), ret, err(Display, level = "debug"))]
     pub async fn ttl(&self, key: &str) -> Result<i64> {
-        let full_key = self.build_key(key);
-        let mut conn = self.get_connection().await?;
+        self.guarded(async {
+            let full_key = self.build_key(key);
+            let mut conn = self.connect().await?;
 
-        let ttl_val: Option<i64> = conn.ttl(&full_key).await // Changed to Option<i64> as per redis crate docs for non-existent keys or no expiry
-        .map_err(|e| AppError::CacheError(format!("Failed to get cache TTL: {}", e)))?;
+            let ttl_val: Option<i64> = conn.ttl(&full_key).await // Changed to Option<i64> as per redis crate docs for non-existent keys or no expiry
+            .map_err(|e| AppError::CacheError(format!("Failed to get cache TTL: {}", e)))?;
 
-        Ok(ttl_val.unwrap_or(-2)) // Return -2 if key does not exist, -1 if no expiry, consistent with Redis TTL command
+            Ok(ttl_val.unwrap_or(-2)) // Return -2 if key does not exist, -1 if no expiry, consistent with Redis TTL command
+        })
+        .await
     }
 
     /// Flush all cache entries with the current prefix
     /// I'm implementing safe cache clearing that respects key namespacing
+    #[instrument(name = "cache.flush_prefix", level = "debug", skip(self), ret, err(Display, level = "debug"))]
     pub async fn flush_prefix(&self) -> Result<u64> {
-        let pattern = format!("{}*", self.key_prefix);
-        let mut conn = self.get_connection().await?;
+        self.guarded(async {
+            let pattern = format!("{}*", self.key_prefix);
+            let mut conn = self.connect().await?;
 
-        info!("Flushing cache entries with pattern: {}", pattern);
+            info!("Flushing cache entries with pattern: {}", pattern);
 
-        // Get all keys matching the pattern
-        let keys: Vec<String> = conn.keys(&pattern).await
-        .map_err(|e| AppError::CacheError(format!("Failed to get cache keys: {}", e)))?;
+            // Get all keys matching the pattern
+            let keys: Vec<String> = conn.keys(&pattern).await
+            .map_err(|e| AppError::CacheError(format!("Failed to get cache keys: {}", e)))?;
 
-        if keys.is_empty() {
-            return Ok(0);
-        }
+            if keys.is_empty() {
+                return Ok(0);
+            }
 
-        // Delete all matching keys
-        let deleted: i32 = conn.del(&keys).await
-        .map_err(|e| AppError::CacheError(format!("Failed to delete cache keys: {}", e)))?;
+            // Delete all matching keys
+            let deleted: i32 = conn.del(&keys).await
+            .map_err(|e| AppError::CacheError(format!("Failed to delete cache keys: {}", e)))?;
 
-        info!("Flushed {} cache entries", deleted);
-        Ok(deleted as u64)
+            info!("Flushed {} cache entries", deleted);
+            Ok(deleted as u64)
+        })
+        .await
+    }
+
+    /// Flush cache entries whose key, after the prefix, matches a Redis glob `pattern`
+    /// I'm refusing patterns aimed inside the task queue, leader lease, or usage counters sharing the prefix, and skipping their keys when a broader pattern matches them
+    #[instrument(name = "cache.flush_pattern", level = "debug", skip(self), ret, err(Display, level = "debug"))]
+    pub async fn flush_pattern(&self, pattern: &str) -> Result<u64> {
+        self.guarded(async {
+            if let Some(namespace) = non_cache_namespace_targeted(pattern) {
+                return Err(AppError::ValidationError(format!(
+                    "Pattern '{}' matches {}* keys, which are not cache entries", pattern, namespace
+                )));
+            }
+
+            let full_pattern = self.build_key(pattern);
+            let mut conn = self.connect().await?;
+            let keys: Vec<String> = conn.keys(&full_pattern).await
+                .map_err(|e| AppError::CacheError(format!("Failed to get cache keys: {}", e)))?;
+            let keys: Vec<String> = keys
+                .into_iter()
+                .filter(|key| key.strip_prefix(self.key_prefix.as_str()).is_some_and(|key| non_cache_namespace_targeted(key).is_none()))
+                .collect();
+            if keys.is_empty() {
+                return Ok(0);
+            }
+
+            let deleted: i32 = conn.del(&keys).await
+                .map_err(|e| AppError::CacheError(format!("Failed to delete cache keys: {}", e)))?;
+            info!("Flushed {} cache entries matching {}", deleted, full_pattern);
+            Ok(deleted as u64)
+        })
+        .await
     }
 
     /// Get comprehensive cache statistics
     /// I'm providing detailed cache analytics for performance monitoring
+    #[instrument(name = "cache.get_stats", level = "debug", skip(self), err(Display, level = "debug"))]
     pub async fn get_stats(&self) -> Result<CacheStats> {
-        let mut conn = self.get_connection().await?;
-
-        // Get Redis info
-        let info_str: String = redis::cmd("INFO").query_async(&mut conn).await
-            .map_err(|e| AppError::CacheError(format!("Failed to get Redis info: {}", e)))?;
-
-        // Parse INFO string manually or use a helper if available (redis::InfoDict is not directly async)
-        let mut info_map = std::collections::HashMap::new();
-        for line in info_str.lines() {
-            if line.starts_with('#') || line.is_empty() {
-                continue;
-            }
-            let parts: Vec<&str> = line.split(':').collect();
-            if parts.len() == 2 {
-                info_map.insert(parts[0].to_string(), parts[1].trim().to_string());
+        self.guarded(async {
+            let mut conn = self.connect().await?;
+
+            // Get Redis info
+            let info_str: String = redis::cmd("INFO").query_async(&mut conn).await
+                .map_err(|e| AppError::CacheError(format!("Failed to get Redis info: {}", e)))?;
+
+            // Parse INFO string manually or use a helper if available (redis::InfoDict is not directly async)
+            let mut info_map = std::collections::HashMap::new();
+            for line in info_str.lines() {
+                if line.starts_with('#') || line.is_empty() {
+                    continue;
+                }
+                let parts: Vec<&str> = line.split(':').collect();
+                if parts.len() == 2 {
+                    info_map.insert(parts[0].to_string(), parts[1].trim().to_string());
+                }
             }
-        }
 
-        // Get keys with our prefix
-        let pattern = format!("{}*", self.key_prefix);
-        let keys: Vec<String> = conn.keys(&pattern).await
-        .map_err(|e| AppError::CacheError(format!("Failed to get cache keys: {}", e)))?;
+            // Get keys with our prefix
+            let pattern = format!("{}*", self.key_prefix);
+            let keys: Vec<String> = conn.keys(&pattern).await
+            .map_err(|e| AppError::CacheError(format!("Failed to get cache keys: {}", e)))?;
 
-        let total_keys = keys.len() as u64;
-        let memory_usage_bytes = info_map.get("used_memory").and_then(|s| s.parse().ok()).unwrap_or(0u64);
+            let total_keys = keys.len() as u64;
+            let memory_usage_bytes = info_map.get("used_memory").and_then(|s| s.parse().ok()).unwrap_or(0u64);
 
-        let keyspace_hits: u64 = info_map.get("keyspace_hits").and_then(|s| s.parse().ok()).unwrap_or(0);
-        let keyspace_misses: u64 = info_map.get("keyspace_misses").and_then(|s| s.parse().ok()).unwrap_or(0);
-        let total_requests = keyspace_hits + keyspace_misses;
+            let keyspace_hits: u64 = info_map.get("keyspace_hits").and_then(|s| s.parse().ok()).unwrap_or(0);
+            let keyspace_misses: u64 = info_map.get("keyspace_misses").and_then(|s| s.parse().ok()).unwrap_or(0);
+            let total_requests = keyspace_hits + keyspace_misses;
 
-        let hit_rate = if total_requests > 0 {
-            keyspace_hits as f64 / total_requests as f64
-        } else {
-            0.0
-        };
-        let miss_rate = 1.0 - hit_rate;
-
-        let most_accessed_keys = keys.into_iter().take(10).collect();
-
-        Ok(CacheStats {
-            total_keys,
-            hit_rate,
-            miss_rate,
-            memory_usage_bytes,
-            expired_keys: info_map.get("expired_keys").and_then(|s| s.parse().ok()).unwrap_or(0),
-            evicted_keys: info_map.get("evicted_keys").and_then(|s| s.parse().ok()).unwrap_or(0),
-            average_ttl_seconds: self.default_ttl as f64, // Simplified
-            most_accessed_keys,
+            let hit_rate = if total_requests > 0 {
+                keyspace_hits as f64 / total_requests as f64
+            } else {
+                0.0
+            };
+            let miss_rate = 1.0 - hit_rate;
+
+            let most_accessed_keys = keys.into_iter().take(10).collect();
+
+            Ok(CacheStats {
+                total_keys,
+                hit_rate,
+                miss_rate,
+                memory_usage_bytes,
+                expired_keys: info_map.get("expired_keys").and_then(|s| s.parse().ok()).unwrap_or(0),
+                evicted_keys: info_map.get("evicted_keys").and_then(|s| s.parse().ok()).unwrap_or(0),
+                average_ttl_seconds: self.default_ttl() as f64, // Simplified
+                most_accessed_keys,
+            })
         })
+        .await
     }
 
     /// Batch get operation for multiple keys
     /// I'm providing efficient bulk cache operations
+    #[instrument(name = "cache.mget", level = "debug", skip_all, fields(keys = keys.len()), err(Display, level = "debug"))]
     pub async fn mget<T>(&self, keys: &[&str]) -> Result<Vec<Option<T>>>
     where
     T: DeserializeOwned + Send + Sync,
     {
-        if keys.is_empty() {
-            return Ok(vec![]);
-        }
-
-        let full_keys: Vec<String> = keys.iter().map(|k| self.build_key(k)).collect();
-        let mut conn = self.get_connection().await?;
-
-        debug!("Cache MGET: {} keys", keys.len());
-
-        let results: Vec<Option<String>> = conn.mget(&full_keys).await
-        .map_err(|e| AppError::CacheError(format!("Failed to get multiple cache entries: {}", e)))?;
-
-        let mut output = Vec::with_capacity(results.len());
-        let now = self.current_timestamp();
+        self.guarded(async {
+            if keys.is_empty() {
+                return Ok(vec![]);
+            }
 
-        for (i, result) in results.into_iter().enumerate() {
-            match result {
-                Some(cached_data) => {
-                    match serde_json::from_str::<CacheEntry<T>>(&cached_data) {
-                        Ok(entry) => {
-                            if now <= entry.expires_at {
-                                output.push(Some(entry.data));
-                            } else {
-                                // Entry expired
+            let full_keys: Vec<String> = keys.iter().map(|k| self.build_key(k)).collect();
+            let mut conn = self.connect().await?;
+
+            debug!("Cache MGET: {} keys", keys.len());
+
+            let results: Vec<Option<String>> = conn.mget(&full_keys).await
+            .map_err(|e| AppError::CacheError(format!("Failed to get multiple cache entries: {}", e)))?;
+
+            let mut output = Vec::with_capacity(results.len());
+            let now = self.current_timestamp();
+
+            for (i, result) in results.into_iter().enumerate() {
+                match result {
+                    Some(cached_data) => {
+                        match serde_json::from_str::<CacheEntry<T>>(&cached_data) {
+                            Ok(entry) => {
+                                if now <= entry.expires_at {
+                                    output.push(Some(entry.data));
+                                } else {
+                                    // Entry expired
+                                    output.push(None);
+                                    // Asynchronously delete expired entry
+                                    let _ = self.delete(keys[i]).await;
+                                }
+                            }
+                            Err(_) => {
                                 output.push(None);
-                                // Asynchronously delete expired entry
+                                // Delete corrupted entry
                                 let _ = self.delete(keys[i]).await;
                             }
                         }
-                        Err(_) => {
-                            output.push(None);
-                            // Delete corrupted entry
-                            let _ = self.delete(keys[i]).await;
-                        }
                     }
+                    None => output.push(None),
                 }
-                None => output.push(None),
             }
-        }
 
-        Ok(output)
+            Ok(output)
+        })
+        .await
     }
 
     /// Batch set operation for multiple key-value pairs
     /// I'm providing efficient bulk cache storage
+    #[instrument(name = "cache.mset", level = "debug", skip_all, fields(entries = entries.len(), ttl_seconds = ?ttl_seconds), err(Display, level = "debug"))]
     pub async fn mset<T>(&self, entries: &[(&str, &T)], ttl_seconds: Option<u64>) -> Result<()>
     where
     T: Serialize + Send + Sync,
     {
-        if entries.is_empty() {
-            return Ok(());
-        }
+        self.guarded(async {
+            if entries.is_empty() {
+                return Ok(());
+            }
 
-        let ttl = ttl_seconds.unwrap_or(self.default_ttl);
-        let now = self.current_timestamp();
-        let mut conn = self.get_connection().await?;
+            let ttl = ttl_seconds.unwrap_or_else(|| self.default_ttl());
+            let now = self.current_timestamp();
+            let mut conn = self.connect().await?;
 
-        debug!("Cache MSET: {} entries (TTL: {}s)", entries.len(), ttl);
+            debug!("Cache MSET: {} entries (TTL: {}s)", entries.len(), ttl);
 
-        // Prepare entries as (key, value) tuples for mset_multiple
-        let mut kv_pairs_for_redis: Vec<(String, String)> = Vec::with_capacity(entries.len());
+            // Prepare entries as (key, value) tuples for mset_multiple
+            let mut kv_pairs_for_redis: Vec<(String, String)> = Vec::with_capacity(entries.len());
 
-        for (key, value) in entries {
-            let full_key = self.build_key(key);
-            let entry = CacheEntry {
-                data: value,
-                created_at: now,
-                expires_at: now + ttl,
-                access_count: 0,
-                last_accessed: now,
-                version: 1,
-            };
+            for (key, value) in entries {
+                let full_key = self.build_key(key);
+                let entry = CacheEntry {
+                    data: value,
+                    created_at: now,
+                    expires_at: now + ttl,
+                    access_count: 0,
+                    last_accessed: now,
+                    version: 1,
+                };
 
-            let serialized = serde_json::to_string(&entry)
-            .map_err(|e| AppError::SerializationError(format!("Failed to serialize cache entry: {}", e)))?;
+                let serialized = serde_json::to_string(&entry)
+                .map_err(|e| AppError::SerializationError(format!("Failed to serialize cache entry: {}", e)))?;
 
-            kv_pairs_for_redis.push((full_key, serialized));
-        }
+                kv_pairs_for_redis.push((full_key, serialized));
+            }
 
-        // Set all entries
-        conn.mset::<_, _, ()>(&kv_pairs_for_redis).await
-        .map_err(|e| AppError::CacheError(format!("Failed to set multiple cache entries: {}", e)))?;
+            // Set all entries
+            conn.mset::<_, _, ()>(&kv_pairs_for_redis).await
+            .map_err(|e| AppError::CacheError(format!("Failed to set multiple cache entries: {}", e)))?;
 
-        // Set expiration for all keys in a pipeline for efficiency
-        let mut pipe = redis::pipe();
-        for (key, _) in entries { // Iterate original keys to avoid issues with kv_pairs_for_redis potentially being moved
-            let full_key_for_expire = self.build_key(key);
-            pipe.expire(full_key_for_expire, ttl as i64);
-        }
-        pipe.query_async::<_, ()>(&mut conn).await
-            .map_err(|e| AppError::CacheError(format!("Failed to set expiration for multiple keys: {}", e)))?;
+            // Set expiration for all keys in a pipeline for efficiency
+            let mut pipe = redis::pipe();
+            for (key, _) in entries { // Iterate original keys to avoid issues with kv_pairs_for_redis potentially being moved
+                let full_key_for_expire = self.build_key(key);
+                pipe.expire(full_key_for_expire, ttl as i64);
+            }
+            pipe.query_async::<_, ()>(&mut conn).await
+                .map_err(|e| AppError::CacheError(format!("Failed to set expiration for multiple keys: {}", e)))?;
 
 
-        Ok(())
+            Ok(())
+        })
+        .await
     }
 
     /// Build full cache key with prefix
@@ -497,53 +613,77 @@ impl CacheService {
 
     /// Health check for cache service
     /// I'm implementing comprehensive cache health verification
+    #[instrument(name = "cache.health_check", level = "debug", skip(self), err(Display, level = "debug"))]
     pub async fn health_check(&self) -> Result<serde_json::Value> {
-        let start = std::time::Instant::now();
-        let mut conn = self.get_connection().await?;
+        self.guarded(async {
+            let start = std::time::Instant::now();
+            let mut conn = self.connect().await?;
 
-        // Test basic connectivity with ping
-        let ping_response: String = redis::cmd("PING").query_async(&mut conn).await
-        .map_err(|e| AppError::CacheError(format!("Cache ping failed: {}", e)))?;
+            // Test basic connectivity with ping
+            let ping_response: String = redis::cmd("PING").query_async(&mut conn).await
+            .map_err(|e| AppError::CacheError(format!("Cache ping failed: {}", e)))?;
 
-        if ping_response != "PONG" {
-            return Err(AppError::CacheError("Cache ping returned unexpected response".to_string()));
-        }
+            if ping_response != "PONG" {
+                return Err(AppError::CacheError("Cache ping returned unexpected response".to_string()));
+            }
 
-        // Test set/get operations
-        let test_key = "health_check_test";
-        let test_value = "test_data";
+            // Test set/get operations
+            let test_key = "health_check_test";
+            let test_value = "test_data";
 
-        conn.set_ex::<_, _, ()>(self.build_key(test_key), test_value, 10).await // Use set_ex
-        .map_err(|e| AppError::CacheError(format!("Cache set test failed: {}", e)))?;
+            conn.set_ex::<_, _, ()>(self.build_key(test_key), test_value, 10).await // Use set_ex
+            .map_err(|e| AppError::CacheError(format!("Cache set test failed: {}", e)))?;
 
-        let retrieved: String = conn.get(self.build_key(test_key)).await
-        .map_err(|e| AppError::CacheError(format!("Cache get test failed: {}", e)))?;
+            let retrieved: String = conn.get(self.build_key(test_key)).await
+            .map_err(|e| AppError::CacheError(format!("Cache get test failed: {}", e)))?;
 
-        if retrieved != test_value {
-            return Err(AppError::CacheError("Cache data integrity test failed".to_string()));
-        }
+            if retrieved != test_value {
+                return Err(AppError::CacheError("Cache data integrity test failed".to_string()));
+            }
 
-        // Clean up test key
-        let _: Option<i32> = conn.del(self.build_key(test_key)).await.map_err(|e| AppError::CacheError(format!("Cache del test failed: {}", e)))?;
+            // Clean up test key
+            let _: Option<i32> = conn.del(self.build_key(test_key)).await.map_err(|e| AppError::CacheError(format!("Cache del test failed: {}", e)))?;
 
 
-        let response_time = start.elapsed().as_millis();
+            let response_time = start.elapsed().as_millis();
 
-        Ok(serde_json::json!({
-            "status": "healthy",
-            "response_time_ms": response_time,
-            "ping_response": ping_response,
-            "connectivity": "ok",
-            "data_integrity": "ok"
-        }))
+            Ok(serde_json::json!({
+                "status": "healthy",
+                "response_time_ms": response_time,
+                "ping_response": ping_response,
+                "connectivity": "ok",
+                "data_integrity": "ok"
+            }))
+        })
+        .await
     }
 }
 
+/// The non-cache namespace a glob or key lies inside, judged by the literal text before its first wildcard
+fn non_cache_namespace_targeted(pattern: &str) -> Option<&'static str> {
+    let literal = pattern.split(['*', '?', '[', '\\']).next().unwrap_or_default();
+    NON_CACHE_NAMESPACES.into_iter().find(|namespace| literal.starts_with(namespace))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde::{Deserialize, Serialize};
 
+    #[test]
+    fn test_flush_patterns_stay_out_of_other_namespaces() {
+        assert_eq!(non_cache_namespace_targeted("github:*"), None);
+        assert_eq!(non_cache_namespace_targeted("github:repo:octocat:*"), None);
+        // Broad globs are allowed, and the keys they reach in other namespaces are skipped rather than deleted
+        assert_eq!(non_cache_namespace_targeted("*"), None);
+        assert_eq!(non_cache_namespace_targeted("q*"), None);
+        assert_eq!(non_cache_namespace_targeted("queue:tasks"), Some("queue:"));
+        assert_eq!(non_cache_namespace_targeted("leader:scheduler"), Some("leader:"));
+        assert_eq!(non_cache_namespace_targeted("usage:key:*"), Some("usage:"));
+        assert_eq!(non_cache_namespace_targeted("fractal_jobs:*"), Some("fractal_jobs:"));
+        assert_eq!(non_cache_namespace_targeted("ratelimit:key:*"), Some("ratelimit:"));
+    }
+
     #[derive(Debug, Serialize, Deserialize, PartialEq)]
     struct TestData {
         id: u32,
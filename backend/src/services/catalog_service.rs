@@ -0,0 +1,133 @@
+/*
+ * Preset catalog storage behind /api/fractals/presets, seeded with curated targets by its migration.
+ * I'm listing Julia constants before deep zooms and curated entries before added ones, so the frontend can show the catalog in the order it comes back.
+ */
+
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    database::{timing, DatabasePool},
+    models::catalog::{CatalogPreset, CatalogPresetInput, CatalogQuery},
+    utils::error::{AppError, Result},
+};
+
+const CATALOG_COLUMNS: &str = "id, name, kind, fractal_type, c_real, c_imag, power, center_x, center_y, zoom, max_iterations, \
+     description, tags, curated, created_at, updated_at";
+
+#[derive(Debug, Clone)]
+pub struct CatalogService {
+    db_pool: DatabasePool,
+}
+
+impl CatalogService {
+    pub fn new(db_pool: DatabasePool) -> Self {
+        Self { db_pool }
+    }
+
+    pub async fn list(&self, query: &CatalogQuery) -> Result<Vec<CatalogPreset>> {
+        let sql = format!(
+            "SELECT {} FROM catalog_presets
+             WHERE ($1::TEXT IS NULL OR kind = $1)
+               AND ($2::TEXT IS NULL OR fractal_type = $2)
+               AND ($3::TEXT IS NULL OR $3 = ANY(tags))
+             ORDER BY kind DESC, curated DESC, name",
+            CATALOG_COLUMNS
+        );
+        let presets = sqlx::query_as::<_, CatalogPreset>(&sql)
+            .bind(query.kind.map(|kind| kind.as_str()))
+            .bind(query.fractal_type.as_deref())
+            .bind(query.tag.as_deref())
+            .fetch_all(&self.db_pool);
+        Ok(timing::timed("catalog_presets_list", presets).await?)
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<CatalogPreset> {
+        sqlx::query_as::<_, CatalogPreset>(&format!("SELECT {} FROM catalog_presets WHERE id = $1", CATALOG_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(|| AppError::not_found(format!("catalog preset {}", id)))
+    }
+
+    pub async fn create(&self, input: &CatalogPresetInput) -> Result<CatalogPreset> {
+        let sql = format!(
+            "INSERT INTO catalog_presets
+                (name, kind, fractal_type, c_real, c_imag, power, center_x, center_y, zoom, max_iterations, description, tags)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+             RETURNING {}",
+            CATALOG_COLUMNS
+        );
+        let preset = bind_input(sqlx::query_as::<_, CatalogPreset>(&sql), input)
+            .fetch_one(&self.db_pool)
+            .await
+            .map_err(|e| duplicate_name(e, &input.name))?;
+
+        info!("Added catalog preset {} '{}'", preset.id, preset.name);
+        Ok(preset)
+    }
+
+    /// Replace an entry; a curated entry stays marked curated
+    pub async fn update(&self, id: Uuid, input: &CatalogPresetInput) -> Result<CatalogPreset> {
+        let sql = format!(
+            "UPDATE catalog_presets SET
+                name = $1, kind = $2, fractal_type = $3, c_real = $4, c_imag = $5, power = $6, center_x = $7, center_y = $8,
+                zoom = $9, max_iterations = $10, description = $11, tags = $12, updated_at = NOW()
+             WHERE id = $13
+             RETURNING {}",
+            CATALOG_COLUMNS
+        );
+        let preset = bind_input(sqlx::query_as::<_, CatalogPreset>(&sql), input)
+            .bind(id)
+            .fetch_optional(&self.db_pool)
+            .await
+            .map_err(|e| duplicate_name(e, &input.name))?
+            .ok_or_else(|| AppError::not_found(format!("catalog preset {}", id)))?;
+
+        info!("Updated catalog preset {} '{}'", preset.id, preset.name);
+        Ok(preset)
+    }
+
+    pub async fn delete(&self, id: Uuid) -> Result<()> {
+        let deleted = sqlx::query("DELETE FROM catalog_presets WHERE id = $1")
+            .bind(id)
+            .execute(&self.db_pool)
+            .await?
+            .rows_affected();
+        if deleted == 0 {
+            return Err(AppError::not_found(format!("catalog preset {}", id)));
+        }
+
+        info!("Deleted catalog preset {}", id);
+        Ok(())
+    }
+}
+
+type CatalogQueryAs<'q> = sqlx::query::QueryAs<'q, sqlx::Postgres, CatalogPreset, sqlx::postgres::PgArguments>;
+
+/// Bind an input's columns as $1 to $12, in the order the INSERT and UPDATE both list them
+fn bind_input<'q>(query: CatalogQueryAs<'q>, input: &'q CatalogPresetInput) -> CatalogQueryAs<'q> {
+    let (c_real, c_imag) = input.fractal_type.julia_constant().unzip();
+    query
+        .bind(&input.name)
+        .bind(input.kind.as_str())
+        .bind(input.fractal_type.name())
+        .bind(c_real)
+        .bind(c_imag)
+        .bind(input.fractal_type.multibrot_power())
+        .bind(input.center_x)
+        .bind(input.center_y)
+        .bind(input.zoom)
+        .bind(input.max_iterations as i32)
+        .bind(&input.description)
+        .bind(&input.tags)
+}
+
+fn duplicate_name(error: sqlx::Error, name: &str) -> AppError {
+    match error {
+        sqlx::Error::Database(e) if e.is_unique_violation() => {
+            AppError::ValidationError(format!("A catalog preset named {} already exists", name))
+        }
+        other => other.into(),
+    }
+}
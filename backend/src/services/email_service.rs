@@ -0,0 +1,257 @@
+/*
+ * Outbound email over SMTP for alerts and account notices, delivered through the task queue.
+ * I'm rendering each message when it is queued so a retry resends exactly what the event described, and counting every delivery outcome as a metric.
+ */
+
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::{
+    jobs::TaskQueue,
+    models::{jobs::QueuedTask, notification_templates::RenderedAlert},
+    utils::{
+        config::{Config, SmtpTls},
+        correlation,
+        error::{AppError, Result},
+        metrics::MetricsCollector,
+    },
+};
+
+/// Task queue that delivery attempts run on
+pub const EMAIL_QUEUE: &str = "email";
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// SMTP settings snapshotted from Config; email is off unless a host is set
+#[derive(Debug, Clone)]
+pub struct EmailSettings {
+    pub host: Option<String>,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub tls: SmtpTls,
+    pub from: String,
+    pub alert_recipients: Vec<String>,
+}
+
+impl EmailSettings {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            host: config.smtp_host.clone(),
+            port: config.smtp_port,
+            username: config.smtp_username.clone(),
+            password: config.smtp_password.clone(),
+            tls: config.smtp_tls,
+            from: config.smtp_from.clone(),
+            alert_recipients: config.alert_email_recipients.clone(),
+        }
+    }
+}
+
+/// Account notices this service knows how to write; alerts arrive already rendered from their notification templates
+#[derive(Debug, Clone)]
+pub enum EmailTemplate {
+    ApiKeyCreated {
+        username: String,
+        key_name: String,
+        prefix: String,
+        rotated: bool,
+    },
+}
+
+impl EmailTemplate {
+    pub fn subject(&self) -> String {
+        match self {
+            Self::ApiKeyCreated { rotated: true, key_name, .. } => format!("Your API key \"{}\" was rotated", key_name),
+            Self::ApiKeyCreated { key_name, .. } => format!("New API key \"{}\" created", key_name),
+        }
+    }
+
+    pub fn body(&self) -> String {
+        match self {
+            Self::ApiKeyCreated { username, key_name, prefix, rotated } => {
+                let action = if *rotated { "rotated" } else { "created" };
+                format!(
+                    "Hi {},\n\n\
+                    The API key \"{}\" ({}...) was {} on your account.\n\n\
+                    If this wasn't you, revoke the key and any others you don't recognise from your account.\n",
+                    username, key_name, prefix, action
+                )
+            }
+        }
+    }
+}
+
+/// A rendered message waiting on the queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct QueuedEmail {
+    to: Vec<String>,
+    subject: String,
+    body: String,
+}
+
+#[derive(Clone)]
+pub struct EmailService {
+    transport: Option<AsyncSmtpTransport<Tokio1Executor>>,
+    from: Mailbox,
+    alert_recipients: Vec<Mailbox>,
+    queue: TaskQueue,
+    metrics: MetricsCollector,
+}
+
+impl EmailService {
+    pub fn new(settings: EmailSettings, queue: TaskQueue, metrics: MetricsCollector) -> Result<Self> {
+        let from = parse_mailbox(&settings.from)?;
+        let alert_recipients = settings
+            .alert_recipients
+            .iter()
+            .map(|address| parse_mailbox(address))
+            .collect::<Result<Vec<_>>>()?;
+        let transport = settings.host.as_deref().map(|host| build_transport(host, &settings)).transpose()?;
+
+        Ok(Self {
+            transport,
+            from,
+            alert_recipients,
+            queue,
+            metrics,
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.transport.is_some()
+    }
+
+    /// Queue a message to one address without blocking the caller
+    pub fn send(&self, to: &str, template: EmailTemplate) {
+        match parse_mailbox(to) {
+            Ok(mailbox) => self.enqueue(vec![mailbox], template.subject(), template.body()),
+            Err(e) => warn!("Not sending \"{}\": {}", template.subject(), e),
+        }
+    }
+
+    /// Queue a rendered alert to ALERT_EMAIL_RECIPIENTS
+    pub fn send_alert(&self, alert: &RenderedAlert) {
+        self.enqueue(self.alert_recipients.clone(), alert.subject.clone(), alert.body.clone());
+    }
+
+    fn enqueue(&self, to: Vec<Mailbox>, subject: String, body: String) {
+        if !self.is_enabled() || to.is_empty() {
+            return;
+        }
+
+        let email = QueuedEmail {
+            to: to.iter().map(ToString::to_string).collect(),
+            subject,
+            body,
+        };
+        let service = self.clone();
+        correlation::spawn(async move {
+            if let Err(e) = service.queue.enqueue(EMAIL_QUEUE, "email.send", &email, None).await {
+                warn!("Failed to queue email \"{}\": {}", email.subject, e);
+                let _ = service.metrics.increment_counter("email_failed_total").await;
+            }
+        });
+    }
+
+    /// Make one delivery attempt for a queued task; errors hand the retry back to the queue
+    pub async fn process_delivery(&self, task: QueuedTask) -> Result<()> {
+        let Some(transport) = &self.transport else {
+            debug!("Dropping queued email {}: SMTP is not configured", task.id);
+            return Ok(());
+        };
+
+        let email: QueuedEmail = serde_json::from_value(task.payload.clone())?;
+        let message = self.build_message(&email)?;
+
+        match transport.send(message).await {
+            Ok(_) => {
+                info!("Sent email \"{}\" to {} recipient(s)", email.subject, email.to.len());
+                let _ = self.metrics.increment_counter("email_sent_total").await;
+                Ok(())
+            }
+            Err(e) => {
+                let _ = self.metrics.increment_counter("email_failed_total").await;
+                if task.is_last_attempt() {
+                    warn!("Email \"{}\" failed permanently: {}", email.subject, e);
+                }
+                Err(AppError::ExternalApiError(format!("SMTP delivery failed: {}", e)))
+            }
+        }
+    }
+
+    fn build_message(&self, email: &QueuedEmail) -> Result<Message> {
+        let mut builder = Message::builder()
+            .from(self.from.clone())
+            .subject(email.subject.as_str())
+            .header(ContentType::TEXT_PLAIN);
+        for address in &email.to {
+            builder = builder.to(parse_mailbox(address)?);
+        }
+
+        builder
+            .body(email.body.clone())
+            .map_err(|e| AppError::InternalServerError(format!("Failed to build email: {}", e)))
+    }
+}
+
+fn parse_mailbox(address: &str) -> Result<Mailbox> {
+    address
+        .trim()
+        .parse()
+        .map_err(|e| AppError::ValidationError(format!("Invalid email address '{}': {}", address, e)))
+}
+
+fn build_transport(host: &str, settings: &EmailSettings) -> Result<AsyncSmtpTransport<Tokio1Executor>> {
+    let builder = match settings.tls {
+        SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host),
+        SmtpTls::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host),
+        SmtpTls::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)),
+    }
+    .map_err(|e| AppError::ConfigurationError(format!("Invalid SMTP settings: {}", e)))?;
+
+    let mut builder = builder.port(settings.port).timeout(Some(SMTP_TIMEOUT));
+    if let (Some(username), Some(password)) = (&settings.username, &settings.password) {
+        builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+    }
+    Ok(builder.build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_account_notices_render_their_variables() {
+        let created = EmailTemplate::ApiKeyCreated {
+            username: "octocat".to_string(),
+            key_name: "ci".to_string(),
+            prefix: "dpk_ab12".to_string(),
+            rotated: false,
+        };
+        assert_eq!(created.subject(), "New API key \"ci\" created");
+        assert!(created.body().contains("\"ci\" (dpk_ab12...) was created"));
+
+        let rotated = EmailTemplate::ApiKeyCreated {
+            username: "octocat".to_string(),
+            key_name: "ci".to_string(),
+            prefix: "dpk_ab12".to_string(),
+            rotated: true,
+        };
+        assert_eq!(rotated.subject(), "Your API key \"ci\" was rotated");
+        assert!(rotated.body().contains("was rotated on your account"));
+    }
+
+    #[test]
+    fn test_messages_parse_their_addresses() {
+        assert!(parse_mailbox("Alerts <alerts@example.com>").is_ok());
+        assert!(parse_mailbox(" ops@example.com ").is_ok());
+        assert!(matches!(parse_mailbox("not an address"), Err(AppError::ValidationError(_))));
+    }
+}
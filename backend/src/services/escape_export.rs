@@ -0,0 +1,234 @@
+/*
+ * Escape-count export: a view's raw iteration matrix written as .npy, CSV, little-endian binary, or zstd-compressed runs for analysis outside the showcase.
+ * I'm encoding a band of rows per chunk, so even a CSV of the largest view streams out without a second full-size copy in memory.
+ * Runs suit escape counts well, since the interior and the far exterior are wide areas of a single value.
+ */
+
+use axum::body::Bytes;
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use std::fmt::Write as _;
+use std::io::Write as _;
+
+use crate::{
+    models::fractals::EscapeExport,
+    services::fractal_service::EscapeBuffer,
+    utils::error::{AppError, Result},
+};
+
+/// Rows encoded into each streamed chunk
+const ROWS_PER_CHUNK: usize = 64;
+
+/// How to read an rle export back, sent alongside it so a client needs nothing else to decode one
+pub const RLE_DECODING: &str = "zstd-decompress, then read runs to the end: a little-endian uint32 run length followed by one value of x-export-dtype; repeat each value run-length times and reshape row-major to x-export-shape";
+
+/// NumPy's name for how the buffer's values are stored, little-endian
+pub fn dtype(buffer: &EscapeBuffer) -> &'static str {
+    match buffer {
+        EscapeBuffer::Counts(_) => "<u4",
+        EscapeBuffer::Smooth(_) => "<f8",
+    }
+}
+
+/// The buffer's element type in the plain words the binary export's headers use
+pub fn dtype_name(buffer: &EscapeBuffer) -> &'static str {
+    match buffer {
+        EscapeBuffer::Counts(_) => "uint32",
+        EscapeBuffer::Smooth(_) => "float64",
+    }
+}
+
+/// Version 1.0 .npy header for a C-ordered height×width array, padded so the data starts on a 64-byte boundary
+pub fn npy_header(dtype: &str, width: u32, height: u32) -> Vec<u8> {
+    let mut dict = format!("{{'descr': '{}', 'fortran_order': False, 'shape': ({}, {}), }}", dtype, height, width);
+    // Magic, version, and the header length take 10 bytes; the dict ends in a newline
+    let padding = (64 - (10 + dict.len() + 1) % 64) % 64;
+    dict.push_str(&" ".repeat(padding));
+    dict.push('\n');
+
+    let mut header = b"\x93NUMPY\x01\x00".to_vec();
+    header.extend_from_slice(&(dict.len() as u16).to_le_bytes());
+    header.extend_from_slice(dict.as_bytes());
+    header
+}
+
+/// The export as a body stream: the .npy header when there is one, then bands of encoded rows
+pub fn export_stream(buffer: EscapeBuffer, width: u32, format: EscapeExport) -> BoxStream<'static, Result<Bytes>> {
+    let band = ROWS_PER_CHUNK * width.max(1) as usize;
+    if format == EscapeExport::Rle {
+        return compressed_runs(buffer, band);
+    }
+
+    let header = (format == EscapeExport::Npy)
+        .then(|| npy_header(dtype(&buffer), width, (buffer.len() / width.max(1) as usize) as u32));
+    let bands = buffer.len().div_ceil(band);
+
+    let head = stream::iter(header.map(|header| Ok(Bytes::from(header))));
+    let body = stream::iter((0..bands).map(move |index| {
+        let range = index * band..((index + 1) * band).min(buffer.len());
+        Ok(Bytes::from(encode_rows(&buffer, range, width as usize, format)))
+    }));
+    head.chain(body).boxed()
+}
+
+/// Whether an Accept-Encoding value lets zstd through, so an rle export can go out as Content-Encoding and arrive decompressed
+pub fn accepts_zstd(accept_encoding: &str) -> bool {
+    accept_encoding.split(',').any(|entry| {
+        let mut parts = entry.split(';').map(str::trim);
+        let coding = parts.next().unwrap_or_default();
+        let refused = parts.any(|param| matches!(param.strip_prefix("q="), Some(q) if q.parse::<f32>().is_ok_and(|q| q == 0.0)));
+        (coding.eq_ignore_ascii_case("zstd") || coding == "*") && !refused
+    })
+}
+
+/// The buffer's runs through a single zstd frame, a band at a time; a run crossing a band boundary is just written as two
+fn compressed_runs(buffer: EscapeBuffer, band: usize) -> BoxStream<'static, Result<Bytes>> {
+    let compression_failed = |e: std::io::Error| AppError::internal(format!("Export compression failed: {}", e));
+    let encoder = match zstd::stream::write::Encoder::new(Vec::new(), zstd::DEFAULT_COMPRESSION_LEVEL) {
+        Ok(encoder) => encoder,
+        Err(e) => return stream::iter([Err(compression_failed(e))]).boxed(),
+    };
+    let bands = buffer.len().div_ceil(band);
+
+    stream::try_unfold((buffer, 0, Some(encoder)), move |(buffer, index, encoder)| async move {
+        let Some(mut encoder) = encoder else {
+            return Ok(None);
+        };
+        if index == bands {
+            let tail = encoder.finish().map_err(compression_failed)?;
+            return Ok(Some((Bytes::from(tail), (buffer, index, None))));
+        }
+
+        let range = index * band..((index + 1) * band).min(buffer.len());
+        encoder.write_all(&run_lengths(&buffer, range)).map_err(compression_failed)?;
+        let compressed = std::mem::take(encoder.get_mut());
+        Ok(Some((Bytes::from(compressed), (buffer, index + 1, Some(encoder)))))
+    })
+    .try_filter(|chunk| futures::future::ready(!chunk.is_empty()))
+    .boxed()
+}
+
+fn run_lengths(buffer: &EscapeBuffer, range: std::ops::Range<usize>) -> Vec<u8> {
+    match buffer {
+        EscapeBuffer::Counts(counts) => runs(&counts[range], |count| count.to_le_bytes()),
+        // Bit-for-bit equality, so NaN runs collapse like any other value
+        EscapeBuffer::Smooth(values) => runs(&values[range], |value| value.to_bits().to_le_bytes()),
+    }
+}
+
+fn runs<T: Copy, const N: usize>(values: &[T], bytes: impl Fn(T) -> [u8; N]) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    let mut rest = values;
+    while let Some(&first) = rest.first() {
+        let key = bytes(first);
+        let length = rest.iter().take(u32::MAX as usize).take_while(|&&value| bytes(value) == key).count();
+        encoded.extend_from_slice(&(length as u32).to_le_bytes());
+        encoded.extend_from_slice(&key);
+        rest = &rest[length..];
+    }
+    encoded
+}
+
+fn encode_rows(buffer: &EscapeBuffer, range: std::ops::Range<usize>, width: usize, format: EscapeExport) -> Vec<u8> {
+    match (format, buffer) {
+        (EscapeExport::Csv, EscapeBuffer::Counts(counts)) => csv_rows(&counts[range], width),
+        (EscapeExport::Csv, EscapeBuffer::Smooth(values)) => csv_rows(&values[range], width),
+        (_, EscapeBuffer::Counts(counts)) => counts[range].iter().flat_map(|count| count.to_le_bytes()).collect(),
+        (_, EscapeBuffer::Smooth(values)) => values[range].iter().flat_map(|value| value.to_le_bytes()).collect(),
+    }
+}
+
+fn csv_rows<T: std::fmt::Display>(values: &[T], width: usize) -> Vec<u8> {
+    let mut text = String::with_capacity(values.len() * 4);
+    for row in values.chunks(width.max(1)) {
+        for (column, value) in row.iter().enumerate() {
+            if column > 0 {
+                text.push(',');
+            }
+            let _ = write!(text, "{}", value);
+        }
+        text.push('\n');
+    }
+    text.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn collect(buffer: EscapeBuffer, width: u32, format: EscapeExport) -> Vec<u8> {
+        export_stream(buffer, width, format)
+            .map(|chunk| chunk.unwrap().to_vec())
+            .collect::<Vec<_>>()
+            .await
+            .concat()
+    }
+
+    #[tokio::test]
+    async fn test_export_formats() {
+        let counts = EscapeBuffer::Counts(vec![1, 2, 3, 500, 5, 6]);
+
+        let npy = collect(counts.clone(), 3, EscapeExport::Npy).await;
+        let header_len = u16::from_le_bytes([npy[8], npy[9]]) as usize;
+        assert_eq!(&npy[..8], b"\x93NUMPY\x01\x00");
+        assert_eq!((10 + header_len) % 64, 0);
+        let dict = std::str::from_utf8(&npy[10..10 + header_len]).unwrap();
+        assert!(dict.starts_with("{'descr': '<u4', 'fortran_order': False, 'shape': (2, 3), }") && dict.ends_with('\n'));
+        assert_eq!(&npy[10 + header_len..10 + header_len + 8], [1, 0, 0, 0, 2, 0, 0, 0]);
+        assert_eq!(npy.len(), 10 + header_len + 6 * 4);
+
+        assert_eq!(collect(counts.clone(), 3, EscapeExport::Csv).await, b"1,2,3\n500,5,6\n");
+        assert_eq!(collect(counts, 3, EscapeExport::Bin).await.len(), 6 * 4);
+
+        let smooth = EscapeBuffer::Smooth(vec![1.5, 80.0]);
+        assert_eq!(collect(smooth.clone(), 2, EscapeExport::Csv).await, b"1.5,80\n");
+        assert_eq!(collect(smooth.clone(), 2, EscapeExport::Bin).await, [1.5f64.to_le_bytes(), 80f64.to_le_bytes()].concat());
+        assert_eq!((dtype(&smooth), dtype_name(&smooth)), ("<f8", "float64"));
+    }
+
+    #[tokio::test]
+    async fn test_export_streams_in_bands_of_rows() {
+        let counts = EscapeBuffer::Counts(vec![7; 10 * (ROWS_PER_CHUNK * 2 + 1)]);
+        let chunks: Vec<_> = export_stream(counts, 10, EscapeExport::Csv).collect().await;
+        assert_eq!(chunks.len(), 3);
+        let last = chunks.last().unwrap().as_ref().unwrap();
+        assert_eq!(&last[..], b"7,7,7,7,7,7,7,7,7,7\n");
+    }
+
+    #[tokio::test]
+    async fn test_rle_export_decodes_back_to_the_buffer() {
+        let mut counts = vec![100; 3 * ROWS_PER_CHUNK * 4];
+        counts[5] = 7;
+        counts[6] = 7;
+        let compressed = collect(EscapeBuffer::Counts(counts.clone()), 4, EscapeExport::Rle).await;
+        assert!(compressed.len() < counts.len());
+
+        let runs = zstd::stream::decode_all(&compressed[..]).unwrap();
+        let mut decoded = Vec::new();
+        for run in runs.chunks(8) {
+            let length = u32::from_le_bytes(run[..4].try_into().unwrap());
+            let value = u32::from_le_bytes(run[4..].try_into().unwrap());
+            decoded.extend(std::iter::repeat(value).take(length as usize));
+        }
+        assert_eq!(decoded, counts);
+        // Runs restart at each band of rows
+        assert_eq!(runs.len() / 8, 3 + 2);
+
+        let smooth = EscapeBuffer::Smooth(vec![2.5, 2.5, f64::NAN, f64::NAN]);
+        let runs = zstd::stream::decode_all(&collect(smooth, 2, EscapeExport::Rle).await[..]).unwrap();
+        assert_eq!(runs.len(), 2 * 12);
+        assert_eq!(f64::from_le_bytes(runs[4..12].try_into().unwrap()), 2.5);
+
+        let empty: Vec<_> = export_stream(EscapeBuffer::Counts(Vec::new()), 4, EscapeExport::Rle).try_collect().await.unwrap();
+        assert!(zstd::stream::decode_all(&empty.concat()[..]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_zstd_is_negotiated_from_accept_encoding() {
+        assert!(accepts_zstd("gzip, zstd"));
+        assert!(accepts_zstd("br;q=0.9, ZSTD;q=0.5"));
+        assert!(accepts_zstd("*"));
+        assert!(!accepts_zstd("gzip, br"));
+        assert!(!accepts_zstd("zstd;q=0"));
+        assert!(!accepts_zstd(""));
+    }
+}
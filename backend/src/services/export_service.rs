@@ -0,0 +1,296 @@
+/*
+ * Export service streaming stored analytics as JSON Lines, either straight into a response or into a file from a queued job.
+ * I'm sharing one row stream between both paths so a job's file holds exactly what the streaming endpoint would have sent.
+ */
+
+use axum::body::Bytes;
+use chrono::{DateTime, Utc};
+use futures::stream::{BoxStream, StreamExt};
+use serde::Serialize;
+use sqlx::FromRow;
+use std::path::PathBuf;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    database::DatabasePool,
+    jobs::TaskQueue,
+    models::{
+        exports::{CreateExportRequest, ExportDataset, ExportFilters, ExportJob},
+        jobs::QueuedTask,
+    },
+    utils::{
+        error::{AppError, Result},
+        jsonl::encode_line,
+    },
+};
+
+/// Task queue that export jobs run on
+pub const EXPORT_QUEUE: &str = "exports";
+
+const DOWNLOAD_CHUNK_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct FractalComputationExport {
+    pub id: Uuid,
+    pub fractal_type: String,
+    pub width: i32,
+    pub height: i32,
+    pub center_x: f64,
+    pub center_y: f64,
+    pub zoom_level: f64,
+    pub max_iterations: i32,
+    pub computation_time_ms: i32,
+    pub cpu_usage_percent: Option<f64>,
+    pub memory_usage_mb: Option<f64>,
+    pub pixels_per_ms: Option<f64>,
+    pub parameters: Option<serde_json::Value>,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct PerformanceMetricExport {
+    pub id: Uuid,
+    pub metric_type: String,
+    pub metric_name: String,
+    pub metric_value: f64,
+    pub metric_unit: String,
+    pub tags: Option<serde_json::Value>,
+    pub endpoint: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// One encoded JSON Lines record per row of `dataset`, pulled from a database cursor
+/// I'm leaving the request context columns out of fractal computations since those carry client addresses
+pub fn record_lines(pool: DatabasePool, dataset: ExportDataset, filters: ExportFilters) -> BoxStream<'static, Result<Bytes>> {
+    match dataset {
+        ExportDataset::FractalComputations => Box::pin(async_stream::stream! {
+            let mut rows = sqlx::query_as::<_, FractalComputationExport>(
+                r#"
+                SELECT id, fractal_type, width, height, center_x, center_y, zoom_level, max_iterations,
+                       computation_time_ms, cpu_usage_percent, memory_usage_mb, pixels_per_ms,
+                       parameters, timestamp
+                FROM fractal_computations
+                WHERE ($1::timestamptz IS NULL OR timestamp >= $1)
+                  AND ($2::timestamptz IS NULL OR timestamp < $2)
+                ORDER BY timestamp
+                LIMIT $3
+                "#
+            )
+            .bind(filters.since)
+            .bind(filters.until)
+            .bind(filters.limit)
+            .fetch(&pool);
+
+            while let Some(row) = rows.next().await {
+                match row {
+                    Ok(record) => yield encode_line(&record),
+                    Err(e) => {
+                        yield Err(e.into());
+                        break;
+                    }
+                }
+            }
+        }),
+        ExportDataset::PerformanceMetrics => Box::pin(async_stream::stream! {
+            let mut rows = sqlx::query_as::<_, PerformanceMetricExport>(
+                r#"
+                SELECT id, metric_type, metric_name, metric_value, metric_unit, tags, endpoint, timestamp
+                FROM performance_metrics
+                WHERE ($1::timestamptz IS NULL OR timestamp >= $1)
+                  AND ($2::timestamptz IS NULL OR timestamp < $2)
+                  AND ($3::text IS NULL OR metric_type = $3)
+                ORDER BY timestamp
+                LIMIT $4
+                "#
+            )
+            .bind(filters.since)
+            .bind(filters.until)
+            .bind(filters.metric_type)
+            .bind(filters.limit)
+            .fetch(&pool);
+
+            while let Some(row) = rows.next().await {
+                match row {
+                    Ok(record) => yield encode_line(&record),
+                    Err(e) => {
+                        yield Err(e.into());
+                        break;
+                    }
+                }
+            }
+        }),
+    }
+}
+
+/// Export jobs that write a dataset to a file in the background for later download
+#[derive(Debug, Clone)]
+pub struct ExportService {
+    db_pool: DatabasePool,
+    queue: TaskQueue,
+    storage_dir: PathBuf,
+}
+
+impl ExportService {
+    pub fn new(db_pool: DatabasePool, queue: TaskQueue, storage_dir: impl Into<PathBuf>) -> Self {
+        Self { db_pool, queue, storage_dir: storage_dir.into() }
+    }
+
+    /// Record an export job and queue it
+    pub async fn submit(&self, request: CreateExportRequest, requested_by: &str) -> Result<ExportJob> {
+        let id = Uuid::new_v4();
+        let job = sqlx::query_as::<_, ExportJob>(
+            r#"
+            INSERT INTO export_jobs (id, dataset, filters, status, requested_by)
+            VALUES ($1, $2, $3, 'queued', $4)
+            RETURNING id, dataset, filters, status, row_count, error, requested_by, created_at, completed_at
+            "#
+        )
+        .bind(id)
+        .bind(request.dataset.as_str())
+        .bind(serde_json::to_value(&request.filters)?)
+        .bind(requested_by)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        if let Err(e) = self.queue.enqueue(EXPORT_QUEUE, "export.run", &serde_json::json!({ "export_id": id }), None).await {
+            self.set_status(id, "failed", None, Some(format!("Failed to queue export: {}", e))).await?;
+            return Err(e);
+        }
+
+        info!("Queued {} export {} for {}", request.dataset, id, requested_by);
+        Ok(job)
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<ExportJob> {
+        sqlx::query_as::<_, ExportJob>(
+            "SELECT id, dataset, filters, status, row_count, error, requested_by, created_at, completed_at
+             FROM export_jobs WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::not_found(format!("export {}", id)))
+    }
+
+    /// Recent export jobs, newest first
+    pub async fn list(&self, limit: i64) -> Result<Vec<ExportJob>> {
+        Ok(sqlx::query_as::<_, ExportJob>(
+            "SELECT id, dataset, filters, status, row_count, error, requested_by, created_at, completed_at
+             FROM export_jobs ORDER BY created_at DESC LIMIT $1"
+        )
+        .bind(limit)
+        .fetch_all(&self.db_pool)
+        .await?)
+    }
+
+    /// Write one queued export to disk; errors hand the retry back to the queue
+    pub async fn run(&self, task: QueuedTask) -> Result<()> {
+        let id: Uuid = serde_json::from_value(task.payload["export_id"].clone())?;
+        let job = self.get(id).await?;
+        // A lease that expired after the file was written hands the task out again
+        if job.status == "succeeded" {
+            return Ok(());
+        }
+
+        self.set_status(id, "running", None, None).await?;
+        match self.write_file(&job).await {
+            Ok(rows) => {
+                info!("Export {} finished with {} rows", id, rows);
+                self.set_status(id, "succeeded", Some(rows), None).await
+            }
+            Err(e) => {
+                let status = if task.is_last_attempt() { "failed" } else { "queued" };
+                if let Err(update) = self.set_status(id, status, None, Some(e.to_string())).await {
+                    warn!("Failed to record failure of export {}: {}", id, update);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Stream a finished export's file
+    pub async fn download(&self, id: Uuid) -> Result<BoxStream<'static, Result<Bytes>>> {
+        let job = self.get(id).await?;
+        if job.status != "succeeded" {
+            return Err(AppError::BadRequestError(format!("Export {} is {}, not ready for download", id, job.status)));
+        }
+
+        let mut file = tokio::fs::File::open(self.file_path(id))
+            .await
+            .map_err(|e| AppError::NotFoundError(format!("Export file for {} is missing: {}", id, e)))?;
+
+        Ok(Box::pin(async_stream::stream! {
+            let mut buffer = vec![0u8; DOWNLOAD_CHUNK_BYTES];
+            loop {
+                match file.read(&mut buffer).await {
+                    Ok(0) => break,
+                    Ok(read) => yield Ok(Bytes::copy_from_slice(&buffer[..read])),
+                    Err(e) => {
+                        yield Err(AppError::InternalServerError(format!("Failed to read export file: {}", e)));
+                        break;
+                    }
+                }
+            }
+        }))
+    }
+
+    /// Write the export next to its final path and rename it into place, returning the row count
+    async fn write_file(&self, job: &ExportJob) -> Result<i64> {
+        let dataset: ExportDataset = serde_json::from_value(serde_json::Value::String(job.dataset.clone()))?;
+        let filters: ExportFilters = serde_json::from_value(job.filters.clone())?;
+
+        tokio::fs::create_dir_all(&self.storage_dir)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Failed to create export directory: {}", e)))?;
+        let path = self.file_path(job.id);
+        let tmp_path = path.with_extension("jsonl.part");
+        let file = tokio::fs::File::create(&tmp_path)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Failed to create export file: {}", e)))?;
+        let mut writer = tokio::io::BufWriter::new(file);
+
+        let mut rows = 0;
+        let mut lines = record_lines(self.db_pool.clone(), dataset, filters);
+        while let Some(line) = lines.next().await {
+            writer
+                .write_all(&line?)
+                .await
+                .map_err(|e| AppError::InternalServerError(format!("Failed to write export file: {}", e)))?;
+            rows += 1;
+        }
+        writer
+            .flush()
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Failed to write export file: {}", e)))?;
+
+        tokio::fs::rename(&tmp_path, &path)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Failed to finalise export file: {}", e)))?;
+        Ok(rows)
+    }
+
+    async fn set_status(&self, id: Uuid, status: &str, row_count: Option<i64>, error: Option<String>) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE export_jobs
+            SET status = $2, row_count = COALESCE($3, row_count),
+                error = CASE WHEN $2 = 'succeeded' THEN NULL ELSE COALESCE($4, error) END,
+                completed_at = CASE WHEN $2 IN ('succeeded', 'failed') THEN NOW() ELSE NULL END
+            WHERE id = $1
+            "#
+        )
+        .bind(id)
+        .bind(status)
+        .bind(row_count)
+        .bind(error)
+        .execute(&self.db_pool)
+        .await?;
+        Ok(())
+    }
+
+    fn file_path(&self, id: Uuid) -> PathBuf {
+        self.storage_dir.join(format!("{}.jsonl", id))
+    }
+}
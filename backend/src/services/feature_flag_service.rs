@@ -0,0 +1,165 @@
+/*
+ * Feature flag storage and evaluation, serving every check from an in-memory snapshot of the feature_flags table.
+ * I'm refreshing the snapshot on startup, after each edit, and whenever another instance announces a change, so request paths never touch the database.
+ */
+
+use arc_swap::ArcSwap;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::info;
+
+use crate::{
+    database::{timing, DatabasePool},
+    models::feature_flags::{is_valid_flag_name, FeatureFlag, FeatureFlagUpdate},
+    services::settings_service::SettingsService,
+    utils::error::{AppError, Result},
+};
+
+/// Prefix for flag edits in the shared settings change history
+pub const FLAG_HISTORY_PREFIX: &str = "feature_flag:";
+
+#[derive(Debug, Clone)]
+pub struct FeatureFlagService {
+    db_pool: DatabasePool,
+    flags: Arc<ArcSwap<HashMap<String, FeatureFlag>>>,
+}
+
+impl FeatureFlagService {
+    pub fn new(db_pool: DatabasePool) -> Self {
+        Self {
+            db_pool,
+            flags: Arc::new(ArcSwap::from_pointee(HashMap::new())),
+        }
+    }
+
+    /// Whether `principal` sees the flag; unknown flags are off
+    pub fn is_enabled(&self, name: &str, principal: Option<&str>) -> bool {
+        self.flags.load().get(name).is_some_and(|flag| flag.evaluate(principal))
+    }
+
+    /// Every flag evaluated for one caller
+    pub fn evaluate_all(&self, principal: Option<&str>) -> HashMap<String, bool> {
+        self.flags
+            .load()
+            .iter()
+            .map(|(name, flag)| (name.clone(), flag.evaluate(principal)))
+            .collect()
+    }
+
+    pub fn list(&self) -> Vec<FeatureFlag> {
+        let mut flags: Vec<FeatureFlag> = self.flags.load().values().cloned().collect();
+        flags.sort_by(|a, b| a.name.cmp(&b.name));
+        flags
+    }
+
+    /// Reload the snapshot from the database
+    pub async fn refresh(&self) -> Result<usize> {
+        let query = sqlx::query_as::<_, FeatureFlag>(
+            r#"
+            SELECT name, description, enabled, rollout_percentage, allow_principals, deny_principals, updated_by, updated_at
+            FROM feature_flags
+            "#
+        );
+        let flags = timing::timed("feature_flags_load", query.fetch_all(&self.db_pool))
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to load feature flags: {}", e)))?;
+
+        let count = flags.len();
+        self.flags.store(Arc::new(flags.into_iter().map(|flag| (flag.name.clone(), flag)).collect()));
+        Ok(count)
+    }
+
+    /// Create or replace a flag and record the change
+    pub async fn upsert(&self, name: &str, update: &FeatureFlagUpdate, changed_by: &str) -> Result<FeatureFlag> {
+        if !is_valid_flag_name(name) {
+            return Err(AppError::ValidationError(
+                "Flag names are 1-48 lowercase letters, digits, '_' or '-'".to_string(),
+            ));
+        }
+        if !(0..=100).contains(&update.rollout_percentage) {
+            return Err(AppError::ValidationError("rollout_percentage must be between 0 and 100".to_string()));
+        }
+
+        let mut tx = self.db_pool.begin().await?;
+        let old_value = Self::lock_current(&mut tx, name).await?;
+
+        let flag = sqlx::query_as::<_, FeatureFlag>(
+            r#"
+            INSERT INTO feature_flags (name, description, enabled, rollout_percentage, allow_principals, deny_principals, updated_by, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
+            ON CONFLICT (name) DO UPDATE SET
+                description = EXCLUDED.description,
+                enabled = EXCLUDED.enabled,
+                rollout_percentage = EXCLUDED.rollout_percentage,
+                allow_principals = EXCLUDED.allow_principals,
+                deny_principals = EXCLUDED.deny_principals,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = NOW()
+            RETURNING name, description, enabled, rollout_percentage, allow_principals, deny_principals, updated_by, updated_at
+            "#
+        )
+        .bind(name)
+        .bind(&update.description)
+        .bind(update.enabled)
+        .bind(update.rollout_percentage)
+        .bind(&update.allow_principals)
+        .bind(&update.deny_principals)
+        .bind(changed_by)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let history_key = format!("{}{}", FLAG_HISTORY_PREFIX, name);
+        let new_value = Some(flag_state(&flag));
+        SettingsService::record_change(&mut tx, &history_key, old_value, new_value, changed_by, update.reason.as_deref()).await?;
+        tx.commit().await?;
+
+        info!("Feature flag {} updated by {} (enabled: {}, rollout: {}%)", name, changed_by, flag.enabled, flag.rollout_percentage);
+        self.refresh().await?;
+        Ok(flag)
+    }
+
+    /// Delete a flag, which turns it off for everyone
+    pub async fn delete(&self, name: &str, changed_by: &str, reason: Option<&str>) -> Result<()> {
+        let mut tx = self.db_pool.begin().await?;
+        let Some(old_value) = Self::lock_current(&mut tx, name).await? else {
+            return Err(AppError::NotFoundError(format!("Feature flag {} not found", name)));
+        };
+
+        sqlx::query("DELETE FROM feature_flags WHERE name = $1")
+            .bind(name)
+            .execute(&mut *tx)
+            .await?;
+
+        let history_key = format!("{}{}", FLAG_HISTORY_PREFIX, name);
+        SettingsService::record_change(&mut tx, &history_key, Some(old_value), None, changed_by, reason).await?;
+        tx.commit().await?;
+
+        info!("Feature flag {} deleted by {}", name, changed_by);
+        self.refresh().await?;
+        Ok(())
+    }
+
+    async fn lock_current(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, name: &str) -> Result<Option<serde_json::Value>> {
+        let flag = sqlx::query_as::<_, FeatureFlag>(
+            r#"
+            SELECT name, description, enabled, rollout_percentage, allow_principals, deny_principals, updated_by, updated_at
+            FROM feature_flags WHERE name = $1 FOR UPDATE
+            "#
+        )
+        .bind(name)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        Ok(flag.as_ref().map(flag_state))
+    }
+}
+
+/// The parts of a flag worth diffing in the change history
+fn flag_state(flag: &FeatureFlag) -> serde_json::Value {
+    serde_json::json!({
+        "enabled": flag.enabled,
+        "rollout_percentage": flag.rollout_percentage,
+        "allow_principals": flag.allow_principals,
+        "deny_principals": flag.deny_principals,
+    })
+}
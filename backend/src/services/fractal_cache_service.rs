@@ -0,0 +1,141 @@
+/*
+ * Fractal render cache serving identical requests from Redis instead of drawing them again.
+ * I'm keying entries on the request's digest from the engine and keeping the cache out here, so FractalService stays free of Redis.
+ */
+
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use tracing::{debug, warn};
+
+use crate::{
+    models::fractals::OutputFormat,
+    services::{
+        cache_service::CacheService,
+        fractal_service::{FractalRequest, FractalResponse, FractalService, InteriorStats, Precision},
+        renderers::RendererKind,
+    },
+    utils::live_config::LiveConfig,
+};
+
+/// A render as stored; the renderer goes by name and is matched back to a registered one when read
+#[derive(Serialize, Deserialize)]
+struct CachedRender<'a> {
+    data: Cow<'a, [u8]>,
+    width: u32,
+    height: u32,
+    computation_time_ms: u128,
+    zoom_level: f64,
+    renderer: Cow<'a, str>,
+    compute_backend: RendererKind,
+    precision: Precision,
+}
+
+/// Finished renders kept for FRACTAL_CACHE_TTL, read per call so the TTL can be tuned at runtime
+#[derive(Clone)]
+pub struct FractalCacheService {
+    cache: CacheService,
+    fractal_service: FractalService,
+    live_config: LiveConfig,
+}
+
+impl FractalCacheService {
+    pub fn new(cache_service: &CacheService, fractal_service: FractalService, live_config: LiveConfig) -> Self {
+        Self {
+            cache: cache_service.scoped("fractals"),
+            fractal_service,
+            live_config,
+        }
+    }
+
+    /// None while CACHE_ENABLED is off or FRACTAL_CACHE_TTL is zero
+    fn ttl(&self) -> Option<u64> {
+        let config = self.live_config.load();
+        (config.cache_enabled && config.fractal_cache_ttl > 0).then_some(config.fractal_cache_ttl)
+    }
+
+    /// None while CACHE_ENABLED is off or FRACTAL_THUMBNAIL_CACHE_TTL is zero
+    pub fn thumbnail_ttl(&self) -> Option<u64> {
+        let config = self.live_config.load();
+        (config.cache_enabled && config.fractal_thumbnail_cache_ttl > 0).then_some(config.fractal_thumbnail_cache_ttl)
+    }
+
+    /// The same view encoded another way is a different thumbnail
+    fn thumbnail_key(request: &FractalRequest, format: OutputFormat) -> String {
+        format!("thumbnail:{}:{}", format.content_type(), request.cache_key())
+    }
+
+    /// An earlier thumbnail of the same request, already encoded
+    pub async fn get_thumbnail(&self, request: &FractalRequest, format: OutputFormat) -> Option<Vec<u8>> {
+        self.thumbnail_ttl()?;
+        match self.cache.get::<Vec<u8>>(&Self::thumbnail_key(request, format)).await {
+            Ok(encoded) => encoded,
+            Err(e) => {
+                debug!("Thumbnail cache unavailable, rendering instead: {}", e);
+                None
+            }
+        }
+    }
+
+    pub async fn put_thumbnail(&self, request: &FractalRequest, format: OutputFormat, encoded: &[u8]) {
+        let Some(ttl) = self.thumbnail_ttl() else {
+            return;
+        };
+        if let Err(e) = self.cache.set(&Self::thumbnail_key(request, format), &encoded, Some(ttl)).await {
+            warn!("Failed to cache {} thumbnail: {}", request.fractal_type.name(), e);
+        }
+    }
+
+    /// An earlier render of the same request, marked as a cache hit
+    pub async fn get(&self, request: &FractalRequest) -> Option<FractalResponse> {
+        self.ttl()?;
+        let cached = match self.cache.get::<CachedRender<'static>>(&request.cache_key()).await {
+            Ok(cached) => cached?,
+            Err(e) => {
+                debug!("Fractal cache unavailable, rendering instead: {}", e);
+                return None;
+            }
+        };
+
+        // An entry from a renderer this instance doesn't have counts as a miss
+        let renderer = self
+            .fractal_service
+            .renderers()
+            .list()
+            .into_iter()
+            .find(|info| info.name == cached.renderer)?
+            .name;
+        Some(FractalResponse {
+            data: cached.data.into_owned(),
+            width: cached.width,
+            height: cached.height,
+            computation_time_ms: cached.computation_time_ms,
+            zoom_level: cached.zoom_level,
+            renderer,
+            compute_backend: cached.compute_backend,
+            cache_hit: true,
+            precision: cached.precision,
+            threads: 0,
+            interior: InteriorStats::default(),
+        })
+    }
+
+    /// Keep a fresh render for identical requests; a failed write only costs the next request a render
+    pub async fn put(&self, request: &FractalRequest, response: &FractalResponse) {
+        let Some(ttl) = self.ttl() else {
+            return;
+        };
+        let cached = CachedRender {
+            data: Cow::Borrowed(&response.data),
+            width: response.width,
+            height: response.height,
+            computation_time_ms: response.computation_time_ms,
+            zoom_level: response.zoom_level,
+            renderer: Cow::Borrowed(response.renderer),
+            compute_backend: response.compute_backend,
+            precision: response.precision,
+        };
+        if let Err(e) = self.cache.set(&request.cache_key(), &cached, Some(ttl)).await {
+            warn!("Failed to cache {} render: {}", request.fractal_type.name(), e);
+        }
+    }
+}
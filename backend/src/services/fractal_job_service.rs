@@ -0,0 +1,286 @@
+/*
+ * Fractal job service running large renders off the request path, with their status and pixels kept in Redis for a while afterwards.
+ * I'm rendering a job region by region, the way the explorer does, so every finished region is progress a polling client can see.
+ * Cancelling signals the render on this instance straight away, and workers elsewhere notice the status in Redis after their current region.
+ */
+
+use chrono::Utc;
+use redis::AsyncCommands;
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::Semaphore;
+use tracing::{info, warn, Span};
+use uuid::Uuid;
+
+use crate::{
+    jobs::TaskQueue,
+    models::{
+        fractal_jobs::{FractalJob, FractalJobStatus},
+        jobs::QueuedTask,
+    },
+    services::{
+        cancel::CancelToken,
+        fractal_service::{FractalRequest, FractalService},
+    },
+    utils::{error::{AppError, Result}, redis_connection::SharedRedisConnection},
+};
+
+/// Task queue that fractal jobs run on
+pub const FRACTAL_JOB_QUEUE: &str = "fractal_jobs";
+
+const KEY_PREFIX: &str = "perf_showcase:fractal_jobs:";
+
+/// Regions a side a job's view is split into, matching the explorer's grid
+const REGION_GRID: u32 = 4;
+
+/// A finished job's pixels
+#[derive(Debug, Clone, Serialize)]
+pub struct FractalJobResult {
+    pub data: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub computation_time_ms: u128,
+}
+
+/// Queued fractal renders, limited to FRACTAL_JOB_CONCURRENCY at once on this instance
+#[derive(Clone)]
+pub struct FractalJobService {
+    redis: SharedRedisConnection,
+    queue: TaskQueue,
+    fractal_service: FractalService,
+    slots: Arc<Semaphore>,
+    ttl: Duration,
+    /// Cancel tokens of the jobs rendering on this instance
+    running: Arc<Mutex<HashMap<Uuid, CancelToken>>>,
+}
+
+impl FractalJobService {
+    pub fn new(
+        redis: SharedRedisConnection,
+        queue: TaskQueue,
+        fractal_service: FractalService,
+        concurrency: u32,
+        ttl: Duration,
+    ) -> Self {
+        Self {
+            redis,
+            queue,
+            fractal_service,
+            slots: Arc::new(Semaphore::new(concurrency.max(1) as usize)),
+            ttl,
+            running: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Record a job for `request` and queue it
+    pub async fn submit(&self, request: FractalRequest) -> Result<FractalJob> {
+        let mut job = FractalJob::new(Uuid::new_v4(), request.fractal_type.name(), request.width, request.height);
+        self.save(&job).await?;
+
+        let payload = serde_json::json!({ "job_id": job.id, "request": request });
+        if let Err(e) = self.queue.enqueue(FRACTAL_JOB_QUEUE, "fractal.render", &payload, None).await {
+            job.status = FractalJobStatus::Failed;
+            job.error = Some(format!("Failed to queue fractal job: {}", e));
+            self.save(&job).await?;
+            return Err(e);
+        }
+
+        info!("Queued {}x{} {} job {}", job.width, job.height, job.fractal_type, job.id);
+        Ok(job)
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<FractalJob> {
+        let mut conn = self.redis.get().await?;
+        let raw: Option<String> = conn.get(job_key(id)).await?;
+        match raw {
+            Some(raw) => Ok(serde_json::from_str(&raw)?),
+            None => Err(AppError::not_found(format!("fractal job {}", id))),
+        }
+    }
+
+    /// The pixels of a finished job
+    pub async fn result(&self, id: Uuid) -> Result<FractalJobResult> {
+        let job = self.get(id).await?;
+        if job.status != FractalJobStatus::Succeeded {
+            return Err(AppError::BadRequestError(format!("Fractal job {} is {}, not finished", id, job.status.as_str())));
+        }
+
+        let mut conn = self.redis.get().await?;
+        let data: Option<Vec<u8>> = conn.get(pixels_key(id)).await?;
+        let data = data.ok_or_else(|| AppError::NotFoundError(format!("Pixels for fractal job {} have expired", id)))?;
+        Ok(FractalJobResult {
+            data,
+            width: job.width,
+            height: job.height,
+            computation_time_ms: job.computation_time_ms.unwrap_or_default(),
+        })
+    }
+
+    /// Stop a queued or running job; cancelling one that is already cancelled changes nothing
+    pub async fn cancel(&self, id: Uuid) -> Result<FractalJob> {
+        let mut job = self.get(id).await?;
+        match job.status {
+            FractalJobStatus::Cancelled => return Ok(job),
+            status if status.is_finished() => {
+                return Err(AppError::BadRequestError(format!("Fractal job {} has already {}", id, status.as_str())));
+            }
+            _ => {}
+        }
+
+        job.status = FractalJobStatus::Cancelled;
+        job.completed_at = Some(Utc::now());
+        self.save(&job).await?;
+        if let Some(cancel) = self.running.lock().unwrap_or_else(|e| e.into_inner()).get(&id) {
+            cancel.cancel();
+        }
+
+        info!("Cancelled fractal job {} after {} of {} regions", id, job.regions_done, job.regions_total);
+        Ok(job)
+    }
+
+    /// Render one queued job once a slot is free; errors hand the retry back to the queue
+    pub async fn run(&self, task: QueuedTask) -> Result<()> {
+        let id: Uuid = serde_json::from_value(task.payload["job_id"].clone())?;
+        let request: FractalRequest = serde_json::from_value(task.payload["request"].clone())?;
+
+        let _slot = self.slots.acquire().await
+            .map_err(|_| AppError::internal("Fractal job slots closed"))?;
+
+        // Read once a slot is free, so a job cancelled while it waited never starts.
+        // A lease that expired after the pixels were stored hands the task out again, too
+        let mut job = self.get(id).await?;
+        if job.status.is_finished() {
+            return Ok(());
+        }
+
+        job.status = FractalJobStatus::Running;
+        job.started_at = Some(Utc::now());
+        job.regions_done = 0;
+        self.save(&job).await?;
+
+        let cancel = CancelToken::new();
+        self.running.lock().unwrap_or_else(|e| e.into_inner()).insert(id, cancel.clone());
+        let rendered = self.render(&mut job, request, &cancel).await;
+        self.running.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+
+        match rendered {
+            Ok(None) => {
+                info!("Fractal job {} stopped after {} of {} regions", id, job.regions_done, job.regions_total);
+                Ok(())
+            }
+            Ok(Some(pixels)) => {
+                self.store_pixels(id, pixels).await?;
+                job.status = FractalJobStatus::Succeeded;
+                job.error = None;
+                job.completed_at = Some(Utc::now());
+                info!("Fractal job {} finished in {}ms", id, job.computation_time_ms.unwrap_or_default());
+                self.save(&job).await
+            }
+            Err(e) => {
+                job.status = if task.is_last_attempt() { FractalJobStatus::Failed } else { FractalJobStatus::Queued };
+                job.error = Some(e.to_string());
+                job.completed_at = (job.status == FractalJobStatus::Failed).then(Utc::now);
+                if let Err(update) = self.save(&job).await {
+                    warn!("Failed to record failure of fractal job {}: {}", id, update);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Draw the view a region at a time, saving progress after each, and stitch the regions into one buffer.
+    /// None means the job was cancelled part way
+    async fn render(&self, job: &mut FractalJob, request: FractalRequest, cancel: &CancelToken) -> Result<Option<Vec<u8>>> {
+        // A worker dropped mid-job, at shutdown say, stops the region it left rendering
+        let _cancel_on_drop = cancel.cancel_on_drop();
+        let regions = request.regions(REGION_GRID);
+        job.regions_total = regions.len();
+        let mut pixels = vec![0u8; request.width as usize * request.height as usize * 4];
+        let mut computation_time_ms = 0;
+
+        for region in regions {
+            let fractal_service = self.fractal_service.clone();
+            let region_cancel = cancel.clone();
+            let span = Span::current();
+            let rendered = tokio::task::spawn_blocking(move || {
+                span.in_scope(|| fractal_service.render_cancellable(region.request, &region_cancel))
+            })
+            .await
+            .map_err(|e| AppError::FractalComputationError(format!("Render task failed: {}", e)))?;
+            // Being cancelled is the only way a cancellable render fails
+            let Ok(response) = rendered else {
+                return Ok(None);
+            };
+
+            // A job cancelled through another instance only shows it in Redis
+            if self.get(job.id).await?.status == FractalJobStatus::Cancelled {
+                return Ok(None);
+            }
+
+            paste_region(&mut pixels, request.width, region.x, region.y, response.width, &response.data);
+
+            computation_time_ms += response.computation_time_ms;
+            if !job.renderers.iter().any(|name| name == response.renderer) {
+                job.renderers.push(response.renderer.to_string());
+            }
+            job.regions_done += 1;
+            job.computation_time_ms = Some(computation_time_ms);
+            self.save(job).await?;
+        }
+
+        Ok(Some(pixels))
+    }
+
+    async fn save(&self, job: &FractalJob) -> Result<()> {
+        let mut conn = self.redis.get().await?;
+        conn.set_ex::<_, _, ()>(job_key(job.id), serde_json::to_string(job)?, self.ttl.as_secs()).await?;
+        Ok(())
+    }
+
+    async fn store_pixels(&self, id: Uuid, pixels: Vec<u8>) -> Result<()> {
+        let mut conn = self.redis.get().await?;
+        conn.set_ex::<_, _, ()>(pixels_key(id), pixels, self.ttl.as_secs()).await?;
+        Ok(())
+    }
+}
+
+/// Copy a region's RGBA rows into the full view at its top-left pixel
+fn paste_region(pixels: &mut [u8], view_width: u32, x: u32, y: u32, region_width: u32, region: &[u8]) {
+    let row_bytes = view_width as usize * 4;
+    let region_row_bytes = region_width as usize * 4;
+    for (row, line) in region.chunks_exact(region_row_bytes).enumerate() {
+        let start = (y as usize + row) * row_bytes + x as usize * 4;
+        pixels[start..start + region_row_bytes].copy_from_slice(line);
+    }
+}
+
+fn job_key(id: Uuid) -> String {
+    format!("{}{}", KEY_PREFIX, id)
+}
+
+fn pixels_key(id: Uuid) -> String {
+    format!("{}{}:pixels", KEY_PREFIX, id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regions_land_at_their_offsets() {
+        // A 4x2 view filled from two 2x2 regions, the right one first
+        let mut pixels = vec![0u8; 4 * 2 * 4];
+        paste_region(&mut pixels, 4, 2, 0, 2, &[2; 16]);
+        paste_region(&mut pixels, 4, 0, 0, 2, &[1; 16]);
+
+        let rows: Vec<&[u8]> = pixels.chunks_exact(16).collect();
+        for row in rows {
+            assert_eq!(&row[..8], &[1; 8]);
+            assert_eq!(&row[8..], &[2; 8]);
+        }
+    }
+}
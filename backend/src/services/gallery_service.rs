@@ -0,0 +1,144 @@
+/*
+ * Fractal gallery storage: saved views under short share ids, listed by recency or by how often they're opened.
+ * I'm counting a view each time a save is opened by id, so "popular" reflects the links people actually follow.
+ */
+
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    database::{timing, DatabasePool},
+    models::gallery::{new_saved_fractal_id, GalleryQuery, GallerySort, SavedFractal, MAX_GALLERY_PAGE},
+    services::fractal_service::FractalRequest,
+    utils::error::{AppError, Result},
+};
+
+const SAVED_FRACTAL_COLUMNS: &str = "id, title, fractal_type, width, height, request, image_id, user_id, view_count, created_at, last_viewed_at";
+
+/// Fresh ids tried before a save gives up; a collision at this id length means something else is wrong
+const ID_ATTEMPTS: usize = 3;
+
+const DEFAULT_GALLERY_PAGE: u32 = 24;
+
+#[derive(Debug, Clone)]
+pub struct GalleryService {
+    db_pool: DatabasePool,
+}
+
+impl GalleryService {
+    pub fn new(db_pool: DatabasePool) -> Self {
+        Self { db_pool }
+    }
+
+    /// Save `request` under a new share id in the tenant's gallery
+    pub async fn save(
+        &self,
+        tenant_id: &str,
+        title: Option<&str>,
+        request: &FractalRequest,
+        user_id: Option<Uuid>,
+    ) -> Result<SavedFractal> {
+        let sql = format!(
+            "INSERT INTO saved_fractals (id, title, fractal_type, width, height, request, user_id, tenant_id)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+             RETURNING {}",
+            SAVED_FRACTAL_COLUMNS
+        );
+        let request_json = serde_json::to_value(request)?;
+
+        for _ in 0..ID_ATTEMPTS {
+            let saved = sqlx::query_as::<_, SavedFractal>(&sql)
+                .bind(new_saved_fractal_id())
+                .bind(title)
+                .bind(request.fractal_type.name())
+                .bind(request.width as i32)
+                .bind(request.height as i32)
+                .bind(&request_json)
+                .bind(user_id)
+                .bind(tenant_id)
+                .fetch_one(&self.db_pool)
+                .await;
+            match saved {
+                Ok(saved) => {
+                    info!("Saved {} view as {}", saved.fractal_type, saved.id);
+                    return Ok(saved);
+                }
+                Err(sqlx::Error::Database(e)) if e.is_unique_violation() => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Err(AppError::internal("Could not find an unused id for the saved fractal"))
+    }
+
+    pub async fn list(&self, tenant_id: &str, query: &GalleryQuery) -> Result<Vec<SavedFractal>> {
+        let order = match query.sort {
+            GallerySort::Recent => "created_at DESC",
+            GallerySort::Popular => "view_count DESC, created_at DESC",
+        };
+        let sql = format!(
+            "SELECT {} FROM saved_fractals
+             WHERE tenant_id = $4 AND ($1::TEXT IS NULL OR fractal_type = $1)
+             ORDER BY {}
+             LIMIT $2 OFFSET $3",
+            SAVED_FRACTAL_COLUMNS, order
+        );
+        let saved = sqlx::query_as::<_, SavedFractal>(&sql)
+            .bind(query.fractal_type.as_deref())
+            .bind(i64::from(query.limit.unwrap_or(DEFAULT_GALLERY_PAGE).clamp(1, MAX_GALLERY_PAGE)))
+            .bind(i64::from(query.offset.unwrap_or(0)))
+            .bind(tenant_id)
+            .fetch_all(&self.db_pool);
+        Ok(timing::timed("saved_fractals_list", saved).await?)
+    }
+
+    pub async fn get(&self, tenant_id: &str, id: &str) -> Result<SavedFractal> {
+        sqlx::query_as::<_, SavedFractal>(&format!(
+            "SELECT {} FROM saved_fractals WHERE id = $1 AND tenant_id = $2",
+            SAVED_FRACTAL_COLUMNS
+        ))
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::not_found(format!("saved fractal {}", id)))
+    }
+
+    /// The save, counting this as one more view of it
+    pub async fn open(&self, tenant_id: &str, id: &str) -> Result<SavedFractal> {
+        sqlx::query_as::<_, SavedFractal>(&format!(
+            "UPDATE saved_fractals SET view_count = view_count + 1, last_viewed_at = NOW()
+             WHERE id = $1 AND tenant_id = $2 RETURNING {}",
+            SAVED_FRACTAL_COLUMNS
+        ))
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::not_found(format!("saved fractal {}", id)))
+    }
+
+    /// Remember where the save's render was stored, so later requests for the image skip the render
+    pub async fn set_image(&self, tenant_id: &str, id: &str, image_id: &str) -> Result<()> {
+        sqlx::query("UPDATE saved_fractals SET image_id = $2 WHERE id = $1 AND tenant_id = $3")
+            .bind(id)
+            .bind(image_id)
+            .bind(tenant_id)
+            .execute(&self.db_pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Whether any save, in any tenant, has its render stored under `image_id`
+    pub async fn holds_image(&self, image_id: &str) -> Result<bool> {
+        let held = sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM saved_fractals WHERE image_id = $1)")
+            .bind(image_id)
+            .fetch_one(&self.db_pool);
+        Ok(timing::timed("saved_fractals_holds_image", held).await?)
+    }
+}
+
+/// The engine request a save was made from
+pub fn saved_request(saved: &SavedFractal) -> Result<FractalRequest> {
+    serde_json::from_value(saved.request.clone())
+        .map_err(|e| AppError::internal(format!("Saved fractal {} has an unreadable request: {}", saved.id, e)))
+}
@@ -3,28 +3,63 @@
  * I'm implementing comprehensive GitHub API communication with automatic retry logic, performance optimization, and database caching.
  */
 
+use arc_swap::ArcSwap;
 use reqwest::{Client, header::{HeaderMap, HeaderValue, USER_AGENT, AUTHORIZATION}};
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::time::sleep;
-use tracing::{info, warn, error, debug};
-use uuid::Uuid;
+use tracing::{info, warn, debug, instrument};
 
 use crate::{
     models::github::{Repository, RepositoryStats, GitHubUser, RepositoryDetailed},
     services::cache_service::CacheService,
-    utils::error::{AppError, Result},
-    database::DatabasePool,
+    utils::{
+        circuit_breaker::CircuitBreaker,
+        correlation::WithCorrelationId,
+        error::{AppError, Result},
+        rate_limit::SharedRateLimiter,
+        retry::{retry_with_backoff, RetryConfig},
+    },
+    database::{with_retrying_transaction, DatabasePool},
 };
 
+/// Repositories upserted per transaction during a sync
+const REPOSITORY_SYNC_BATCH_SIZE: usize = 50;
+
+/// Outbound calls are paced per minute so an hourly budget can't be burned in one burst
+const PACING_WINDOW: Duration = Duration::from_secs(60);
+const PACING_KEY: &str = "github:outbound";
+/// Longest a call waits for the next pacing window before failing instead
+const MAX_PACING_WAIT: Duration = Duration::from_secs(30);
+
+/// Transient GitHub failures are retried briefly; longer outages are the circuit breaker's job
+const GITHUB_RETRY: RetryConfig = RetryConfig {
+    max_attempts: 3,
+    initial_delay: Duration::from_millis(250),
+    max_delay: Duration::from_secs(2),
+    multiplier: 2.0,
+    max_elapsed: Some(Duration::from_secs(10)),
+    jitter: true,
+};
+
+fn repositories_cache_key(username: &str) -> String {
+    format!("github:repos:{}", username)
+}
+
 #[derive(Debug, Clone)]
 pub struct GitHubService {
-    client: Client,
-    token: String,
+    client: std::sync::Arc<ArcSwap<Client>>,
     cache_service: CacheService,
     base_url: String,
     rate_limit_remaining: std::sync::Arc<std::sync::Mutex<u32>>,
     rate_limit_reset: std::sync::Arc<std::sync::Mutex<u64>>,
+    /// Seconds a synced repository row stays fresh before the next read triggers a resync
+    sync_interval: std::sync::Arc<AtomicU64>,
+    /// Shared limiter pacing calls across instances; None leaves pacing to GitHub's own headers
+    pacer: Option<SharedRateLimiter>,
+    requests_per_hour: std::sync::Arc<AtomicU32>,
+    circuit_breaker: Option<CircuitBreaker>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -89,48 +124,128 @@ pub struct RateLimitInfo {
     pub percentage_used: f64,
 }
 
-impl GitHubService {
-    pub fn new(token: String, cache_service: CacheService) -> Self {
-        // I'm setting up the HTTP client with optimal configuration for GitHub API
-        let mut headers = HeaderMap::new();
-        headers.insert(USER_AGENT, HeaderValue::from_static("dark-performance-showcase/0.1.0"));
-        headers.insert(
-            AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {}", token))
-            .expect("Invalid GitHub token format")
-        );
-        headers.insert("Accept", HeaderValue::from_static("application/vnd.github+json"));
-        headers.insert("X-GitHub-Api-Version", HeaderValue::from_static("2022-11-28"));
-
-        let client = Client::builder()
+/// HTTP client carrying the token and GitHub API headers on every request
+/// I'm setting up the client with optimal configuration for the GitHub API
+fn build_client(token: &str) -> Result<Client> {
+    let mut headers = HeaderMap::new();
+    headers.insert(USER_AGENT, HeaderValue::from_static("dark-performance-showcase/0.1.0"));
+    headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {}", token))
+            .map_err(|_| AppError::ConfigurationError("Invalid GitHub token format".to_string()))?,
+    );
+    headers.insert("Accept", HeaderValue::from_static("application/vnd.github+json"));
+    headers.insert("X-GitHub-Api-Version", HeaderValue::from_static("2022-11-28"));
+
+    Client::builder()
         .default_headers(headers)
         .timeout(Duration::from_secs(30))
         .pool_idle_timeout(Duration::from_secs(90))
         .pool_max_idle_per_host(10)
         .build()
-        .expect("Failed to create HTTP client");
+        .map_err(|e| AppError::InternalServerError(format!("Failed to create HTTP client: {}", e)))
+}
 
+impl GitHubService {
+    pub fn new(token: String, cache_service: CacheService) -> Self {
         Self {
-            client,
-            token,
+            client: std::sync::Arc::new(ArcSwap::from_pointee(build_client(&token).expect("Invalid GitHub token format"))),
             cache_service,
             base_url: "https://api.github.com".to_string(),
             rate_limit_remaining: std::sync::Arc::new(std::sync::Mutex::new(5000)),
             rate_limit_reset: std::sync::Arc::new(std::sync::Mutex::new(0)),
+            sync_interval: std::sync::Arc::new(AtomicU64::new(3600)),
+            pacer: None,
+            requests_per_hour: std::sync::Arc::new(AtomicU32::new(5000)),
+            circuit_breaker: None,
         }
     }
 
+    /// Send every GitHub call through this breaker so an outage fails fast instead of waiting out timeouts
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(breaker);
+        self
+    }
+
+    /// The same client, pacing, and breaker, caching into another cache view such as a tenant's namespace
+    pub fn with_cache(&self, cache_service: CacheService) -> Self {
+        Self { cache_service, ..self.clone() }
+    }
+
+    /// Pace outbound calls through a shared limiter so every instance together stays within the hourly budget
+    pub fn with_request_pacing(mut self, limiter: SharedRateLimiter, requests_per_hour: u32) -> Self {
+        self.pacer = Some(limiter);
+        self.set_request_budget(requests_per_hour);
+        self
+    }
+
+    /// Change the hourly budget outbound calls are paced to
+    pub fn set_request_budget(&self, requests_per_hour: u32) {
+        self.requests_per_hour.store(requests_per_hour, Ordering::Relaxed);
+    }
+
+    /// Change how long synced repositories count as fresh; rows already stored keep their expiry
+    pub fn set_sync_interval(&self, seconds: u64) {
+        self.sync_interval.store(seconds, Ordering::Relaxed);
+    }
+
+    /// Swap in a rotated token; requests already in flight finish with the old one
+    pub fn set_token(&self, token: &str) -> Result<()> {
+        self.client.store(std::sync::Arc::new(build_client(token)?));
+        Ok(())
+    }
+
+    /// The GitHub account an OAuth access token belongs to
+    /// I'm building a one-off client so the visitor's token never touches the shared client's credentials
+    #[instrument(name = "github.authenticated_user", level = "debug", skip_all, err(Display, level = "debug"))]
+    pub async fn authenticated_user(&self, access_token: &str) -> Result<GitHubUser> {
+        let request = build_client(access_token)
+        .map_err(|_| AppError::AuthenticationError("Malformed GitHub access token".to_string()))?
+        .get(format!("{}/user", self.base_url));
+        let response = self.send(request, "GitHub user lookup failed").await?;
+
+        match response.status() {
+            status if status.is_success() => {}
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+                return Err(AppError::AuthenticationError("GitHub rejected the access token".to_string()));
+            }
+            status => {
+                return Err(AppError::ExternalApiError(format!("GitHub user lookup failed: HTTP {}", status)));
+            }
+        }
+
+        response
+        .json()
+        .await
+        .map_err(|e| AppError::SerializationError(format!("Failed to parse GitHub user: {}", e)))
+    }
+
     /// Fetch all repositories for the authenticated user with intelligent caching
-    /// I'm implementing pagination handling and comprehensive error recovery
+    /// I'm serving the Redis copy while it lasts; refreshes that must see GitHub's current state go through SyncService
+    #[instrument(name = "github.get_user_repositories", level = "debug", skip(self), err(Display, level = "debug"))]
     pub async fn get_user_repositories(&self, username: &str) -> Result<Vec<Repository>> {
-        let cache_key = format!("github:repos:{}", username);
-
         // Check cache first - I'm implementing intelligent cache with TTL
-        if let Ok(Some(cached_repos)) = self.cache_service.get::<Vec<Repository>>(&cache_key).await {
+        if let Ok(Some(cached_repos)) = self.cache_service.get::<Vec<Repository>>(&repositories_cache_key(username)).await {
             debug!("Returning cached repositories for user: {}", username);
             return Ok(cached_repos);
         }
 
+        let all_repos = self.fetch_user_repositories(username).await?;
+        self.cache_user_repositories(username, &all_repos).await;
+        Ok(all_repos)
+    }
+
+    /// Replace the cached repository list for a user
+    pub async fn cache_user_repositories(&self, username: &str, repositories: &[Repository]) {
+        if let Err(e) = self.cache_service.set(&repositories_cache_key(username), &repositories, Some(3600)).await {
+            warn!("Failed to cache repository data: {}", e);
+        }
+    }
+
+    /// Page through every repository for a user straight from the GitHub API, bypassing the cache
+    /// I'm implementing pagination handling and comprehensive error recovery
+    #[instrument(name = "github.fetch_user_repositories", level = "debug", skip(self), err(Display, level = "debug"))]
+    pub async fn fetch_user_repositories(&self, username: &str) -> Result<Vec<Repository>> {
         info!("Fetching fresh repository data for user: {}", username);
 
         let mut all_repos = Vec::new();
@@ -148,11 +263,7 @@ impl GitHubService {
 
             debug!("Fetching repositories page {} for user: {}", page, username);
 
-            let response = self.client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| AppError::ExternalApiError(format!("GitHub API request failed: {}", e)))?;
+            let response = self.send(self.client.load().get(&url), "GitHub API request failed").await?;
 
             // Update rate limit information from headers
             self.update_rate_limit_from_headers(&response).await;
@@ -190,17 +301,12 @@ impl GitHubService {
         }
 
         info!("Fetched {} repositories for user: {}", all_repos.len(), username);
-
-        // Cache the results with 1-hour TTL
-          if let Err(e) = self.cache_service.set(&cache_key, &all_repos, Some(3600)).await {
-            warn!("Failed to cache repository data: {}", e);
-        }
-
         Ok(all_repos)
     }
 
     /// Get detailed information for a specific repository including README and stats
     /// I'm providing comprehensive repository analysis with performance metrics
+    #[instrument(name = "github.get_repository_details", level = "debug", skip(self), err(Display, level = "debug"))]
     pub async fn get_repository_details(&self, owner: &str, name: &str) -> Result<RepositoryDetailed> {
         let cache_key = format!("github:repo:{}:{}", owner, name);
 
@@ -215,11 +321,7 @@ impl GitHubService {
 
         let url = format!("{}/repos/{}/{}", self.base_url, owner, name);
 
-        let response = self.client
-        .get(&url)
-        .send()
-        .await
-        .map_err(|e| AppError::ExternalApiError(format!("GitHub API request failed: {}", e)))?;
+        let response = self.send(self.client.load().get(&url), "GitHub API request failed").await?;
 
         self.update_rate_limit_from_headers(&response).await;
 
@@ -260,6 +362,7 @@ impl GitHubService {
 
 
 // In src/services/github_service.rs
+#[instrument(name = "github.get_repository_readme", level = "debug", skip(self), err(Display, level = "debug"))]
 async fn get_repository_readme(&self, owner: &str, name: &str) -> Result<String> {
     let readme_variants = vec!["README.md", "readme.md", "README", "readme", "README.txt"];
 
@@ -270,7 +373,7 @@ async fn get_repository_readme(&self, owner: &str, name: &str) -> Result<String>
             "{}/repos/{}/{}/contents/{}",
             self.base_url, owner, name, readme_file
         );
-        let response_result = self.client.get(&url).send().await;
+        let response_result = self.send(self.client.load().get(&url), "README request failed").await;
 
         match response_result {
             Ok(mut resp) => {
@@ -322,14 +425,11 @@ async fn get_repository_readme(&self, owner: &str, name: &str) -> Result<String>
 
     /// Get current rate limit status
     /// I'm providing real-time rate limit monitoring for optimal API usage
+    #[instrument(name = "github.get_rate_limit_status", level = "debug", skip(self), err(Display, level = "debug"))]
     pub async fn get_rate_limit_status(&self) -> Result<GitHubRateLimit> {
         let url = format!("{}/rate_limit", self.base_url);
 
-        let response = self.client
-        .get(&url)
-        .send()
-        .await
-        .map_err(|e| AppError::ExternalApiError(format!("Rate limit check failed: {}", e)))?;
+        let response = self.send(self.client.load().get(&url), "Rate limit check failed").await?;
 
         if !response.status().is_success() {
             return Err(AppError::ExternalApiError(
@@ -358,6 +458,8 @@ async fn get_repository_readme(&self, owner: &str, name: &str) -> Result<String>
     /// Check rate limit and wait if necessary
     /// I'm implementing intelligent rate limit handling with automatic backoff
     async fn check_rate_limit(&self) -> Result<()> {
+        self.pace().await?;
+
         let remaining = {
             let remaining = self.rate_limit_remaining.lock().unwrap();
             *remaining
@@ -384,6 +486,53 @@ async fn get_repository_readme(&self, owner: &str, name: &str) -> Result<String>
         Ok(())
     }
 
+    /// Send a request with retries, through the circuit breaker when one is attached
+    /// I'm counting transport errors and 5xx answers against GitHub; every other status is left to the caller
+    async fn send(&self, request: reqwest::RequestBuilder, failure: &str) -> Result<reqwest::Response> {
+        // The breaker sees one outcome per call, and an open circuit skips the retries entirely
+        let send = || retry_with_backoff(|| async {
+            let response = request
+            .try_clone()
+            .ok_or_else(|| AppError::InternalServerError("GitHub request cannot be replayed".to_string()))?
+            .with_correlation_id()
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalApiError(format!("{}: {}", failure, e)))?;
+
+            if response.status().is_server_error() {
+                return Err(AppError::ExternalApiError(format!("{}: HTTP {}", failure, response.status())));
+            }
+            Ok(response)
+        }, &GITHUB_RETRY);
+
+        match &self.circuit_breaker {
+            Some(breaker) => breaker.call(send).await,
+            None => send().await,
+        }
+    }
+
+    /// Wait for a slot in the shared per-minute budget, failing when the next one is too far off
+    async fn pace(&self) -> Result<()> {
+        let Some(pacer) = &self.pacer else { return Ok(()) };
+        let per_minute = self.requests_per_hour.load(Ordering::Relaxed).div_ceil(60).max(1);
+
+        loop {
+            let decision = pacer.acquire(PACING_KEY, per_minute, PACING_WINDOW).await;
+            if decision.allowed {
+                return Ok(());
+            }
+            if decision.reset_after > MAX_PACING_WAIT {
+                return Err(AppError::RateLimitError(format!(
+                    "GitHub request budget of {}/min is spent; next slot in {}s",
+                    per_minute,
+                    decision.reset_after.as_secs()
+                )));
+            }
+            debug!("Pacing GitHub call for {}ms", decision.reset_after.as_millis());
+            sleep(decision.reset_after).await;
+        }
+    }
+
     /// Update rate limit information from response headers
     /// I'm tracking rate limits in real-time to prevent API exhaustion
     async fn update_rate_limit_from_headers(&self, response: &reqwest::Response) {
@@ -441,26 +590,41 @@ async fn get_repository_readme(&self, owner: &str, name: &str) -> Result<String>
                 license_name: api_repo.license.map(|l| l.name),
                 readme_content: None,
                 cache_updated_at: chrono::Utc::now(),
-                cache_expires_at: chrono::Utc::now() + chrono::Duration::hours(1),
+                cache_expires_at: chrono::Utc::now()
+                    + chrono::Duration::seconds(self.sync_interval.load(Ordering::Relaxed) as i64),
             }
         }
 
-    /// Store repositories in database cache for performance optimization
-    /// I'm implementing intelligent database caching with automatic cleanup
+    /// Drop the cached detail view for one repository so the next read refetches it
+    #[instrument(name = "github.invalidate_repository_cache", level = "debug", skip(self), ret, err(Display, level = "debug"))]
+    pub async fn invalidate_repository_cache(&self, owner: &str, name: &str) -> Result<bool> {
+        self.cache_service.delete(&format!("github:repo:{}:{}", owner, name)).await
+    }
+
+    /// Store repositories in database cache for performance optimization, returning how many rows were written
+    /// I'm upserting in batches, one retrying transaction each, so a conflicting sync only replays its own batch
+    #[instrument(name = "github.store_repositories_in_db", level = "debug", skip_all, fields(repositories = repositories.len()), err(Display, level = "debug"))]
     pub async fn store_repositories_in_db(
         &self,
         db_pool: &DatabasePool,
         repositories: &[Repository],
-    ) -> Result<()> {
-        for repo in repositories {
-            let result = sqlx::query(
-                r#"
+    ) -> Result<usize> {
+        let mut stored = 0;
+
+        for chunk in repositories.chunks(REPOSITORY_SYNC_BATCH_SIZE) {
+            let batch: std::sync::Arc<[Repository]> = chunk.into();
+            let result = with_retrying_transaction(db_pool, "repository_sync_batch", |tx| {
+                let batch = batch.clone();
+                Box::pin(async move {
+                    for repo in batch.iter() {
+                        let upsert = sqlx::query!(
+                            r#"
                 INSERT INTO repositories (
                     github_id, owner_login, name, full_name, description, html_url, clone_url, ssh_url,
                     language, size_kb, stargazers_count, watchers_count, forks_count, open_issues_count,
                     created_at, updated_at, pushed_at, is_private, is_fork, is_archived, topics,
-                    license_name, cache_updated_at, cache_expires_at
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24)
+                    license_name, cache_updated_at, cache_expires_at, readme_content
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25)
             ON CONFLICT (github_id) DO UPDATE SET
             description = EXCLUDED.description,
             html_url = EXCLUDED.html_url,
@@ -476,43 +640,56 @@ async fn get_repository_readme(&self, owner: &str, name: &str) -> Result<String>
                 topics = EXCLUDED.topics,
                 license_name = EXCLUDED.license_name,
                 cache_updated_at = EXCLUDED.cache_updated_at,
-                cache_expires_at = EXCLUDED.cache_expires_at
-                "#
-            )
-            .bind(repo.github_id)
-            .bind(&repo.owner_login)
-            .bind(&repo.name)
-            .bind(&repo.full_name)
-            .bind(&repo.description)
-            .bind(&repo.html_url)
-            .bind(&repo.clone_url)
-            .bind(&repo.ssh_url)
-            .bind(&repo.language)
-            .bind(repo.size_kb)
-            .bind(repo.stargazers_count)
-            .bind(repo.watchers_count)
-            .bind(repo.forks_count)
-            .bind(repo.open_issues_count)
-            .bind(repo.created_at)
-            .bind(repo.updated_at)
-            .bind(repo.pushed_at)
-            .bind(repo.is_private)
-            .bind(repo.is_fork)
-            .bind(repo.is_archived)
-            .bind(&repo.topics)
-            .bind(&repo.license_name)
-            .bind(repo.cache_updated_at)
-            .bind(repo.cache_expires_at)
-            .execute(db_pool)
+                cache_expires_at = EXCLUDED.cache_expires_at,
+                readme_content = COALESCE(EXCLUDED.readme_content, repositories.readme_content)
+                "#,
+                            repo.github_id,
+                            &repo.owner_login,
+                            &repo.name,
+                            &repo.full_name,
+                            repo.description.as_deref(),
+                            &repo.html_url,
+                            &repo.clone_url,
+                            &repo.ssh_url,
+                            repo.language.as_deref(),
+                            repo.size_kb,
+                            repo.stargazers_count,
+                            repo.watchers_count,
+                            repo.forks_count,
+                            repo.open_issues_count,
+                            repo.created_at,
+                            repo.updated_at,
+                            repo.pushed_at,
+                            repo.is_private,
+                            repo.is_fork,
+                            repo.is_archived,
+                            repo.topics.as_deref(),
+                            repo.license_name.as_deref(),
+                            repo.cache_updated_at,
+                            repo.cache_expires_at,
+                            repo.readme_content.as_deref(),
+                        );
+                        crate::database::timing::timed("repositories_upsert", upsert.execute(&mut **tx)).await?;
+                    }
+                    Ok(())
+                })
+            })
             .await;
 
-            if let Err(e) = result {
-                warn!("Failed to store repository {}/{} in database: {}", repo.owner_login, repo.name, e);
+            match result {
+                Ok(()) => stored += chunk.len(),
+                Err(e) => warn!("Failed to store a batch of {} repositories in database: {}", chunk.len(), e),
             }
         }
 
-        info!("Stored {} repositories in database cache", repositories.len());
-        Ok(())
+        info!("Stored {} of {} repositories in database cache", stored, repositories.len());
+
+        if stored > 0 {
+            if let Err(e) = crate::database::repositories::refresh_collection_stats(db_pool).await {
+                warn!("Failed to refresh collection stats after sync: {}", e);
+            }
+        }
+        Ok(stored)
     }
 }
 
@@ -0,0 +1,332 @@
+/*
+ * Rendered image persistence service encoding fractal pixel buffers once and serving the stored bytes afterwards.
+ * I'm addressing images by content hash so every URL is immutable and identical renders collapse into one file.
+ */
+
+use async_trait::async_trait;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    database::DatabasePool,
+    models::fractals::OutputFormat,
+    utils::error::{AppError, Result},
+};
+
+pub const PNG_CONTENT_TYPE: &str = "image/png";
+
+/// Quality of JPEG render responses; high enough that smooth palette gradients don't band
+const JPEG_QUALITY: u8 = 90;
+
+/// Backend that stores encoded image bytes under an opaque key
+/// I'm keeping the trait minimal so an object storage backend can slot in next to the disk one
+#[async_trait]
+pub trait ImageStore: Send + Sync + std::fmt::Debug {
+    async fn put(&self, key: &str, bytes: &[u8]) -> Result<()>;
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+    async fn exists(&self, key: &str) -> Result<bool>;
+    /// Remove the bytes under a key; a key that's already gone is not an error
+    async fn delete(&self, key: &str) -> Result<()>;
+}
+
+/// Filesystem image store sharding files into two-character subdirectories
+#[derive(Debug, Clone)]
+pub struct DiskImageStore {
+    root: PathBuf,
+}
+
+impl DiskImageStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path_for(&self, key: &str) -> PathBuf {
+        let shard = &key[..key.len().min(2)];
+        self.root.join(shard).join(key)
+    }
+}
+
+#[async_trait]
+impl ImageStore for DiskImageStore {
+    async fn put(&self, key: &str, bytes: &[u8]) -> Result<()> {
+        let path = self.path_for(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await
+                .map_err(|e| AppError::InternalServerError(format!("Failed to create image directory: {}", e)))?;
+        }
+
+        // I'm writing to a temp file of this write's own and renaming so readers never observe a partial image,
+        // and two concurrent identical renders don't interleave their bytes in one temp file
+        let tmp_path = path.with_file_name(format!("{}.{}.tmp", key, Uuid::new_v4().simple()));
+        tokio::fs::write(&tmp_path, bytes).await
+            .map_err(|e| AppError::InternalServerError(format!("Failed to write image: {}", e)))?;
+        if let Err(e) = tokio::fs::rename(&tmp_path, &path).await {
+            let _ = tokio::fs::remove_file(&tmp_path).await;
+            return Err(AppError::InternalServerError(format!("Failed to finalise image: {}", e)));
+        }
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.path_for(key)).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(AppError::InternalServerError(format!("Failed to read image: {}", e))),
+        }
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        Ok(tokio::fs::try_exists(self.path_for(key)).await.unwrap_or(false))
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.path_for(key)).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(AppError::InternalServerError(format!("Failed to delete image: {}", e))),
+        }
+    }
+}
+
+/// Reference to a persisted image returned alongside render responses
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredImage {
+    pub id: String,
+    pub url: String,
+    pub content_type: String,
+    pub byte_size: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImageService {
+    store: Arc<dyn ImageStore>,
+    db_pool: DatabasePool,
+    enabled: bool,
+}
+
+impl ImageService {
+    pub fn new(store: Arc<dyn ImageStore>, db_pool: DatabasePool, enabled: bool) -> Self {
+        Self { store, db_pool, enabled }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Encode an RGBA buffer as PNG and persist it, reusing an existing copy when present
+    /// I'm running the encoder on the blocking pool since large renders take real CPU time
+    pub async fn persist_rgba(
+        &self,
+        width: u32,
+        height: u32,
+        rgba: Vec<u8>,
+        parameters: serde_json::Value,
+    ) -> Result<StoredImage> {
+        let encoded = tokio::task::spawn_blocking(move || encode_png(width, height, &rgba))
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Image encoding task failed: {}", e)))??;
+
+        let id = content_id(&encoded);
+        let key = format!("{}.png", id);
+
+        if !self.store.exists(&key).await? {
+            self.store.put(&key, &encoded).await?;
+            info!("Persisted rendered image {} ({} bytes)", id, encoded.len());
+        } else {
+            debug!("Rendered image {} already stored", id);
+        }
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO rendered_images (id, content_type, byte_size, width, height, storage_key, parameters)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (id) DO UPDATE SET last_served_at = NOW()
+            "#
+        )
+        .bind(&id)
+        .bind(PNG_CONTENT_TYPE)
+        .bind(encoded.len() as i64)
+        .bind(width as i32)
+        .bind(height as i32)
+        .bind(&key)
+        .bind(parameters)
+        .execute(&self.db_pool)
+        .await;
+
+        if let Err(e) = result {
+            warn!("Failed to record metadata for image {}: {}", id, e);
+        }
+
+        Ok(StoredImage {
+            url: format!("/images/{}", id),
+            id,
+            content_type: PNG_CONTENT_TYPE.to_string(),
+            byte_size: encoded.len(),
+        })
+    }
+
+    /// Load stored image bytes by id
+    pub async fn load(&self, id: &str) -> Result<Option<Vec<u8>>> {
+        if !is_valid_image_id(id) {
+            return Err(AppError::bad_request("Invalid image id"));
+        }
+
+        let bytes = self.store.get(&format!("{}.png", id)).await?;
+        if bytes.is_some() {
+            // I'm only logging failures; a missed touch just makes the image a little likelier to be pruned
+            let touched = sqlx::query("UPDATE rendered_images SET last_served_at = NOW() WHERE id = $1")
+                .bind(id)
+                .execute(&self.db_pool)
+                .await;
+            if let Err(e) = touched {
+                warn!("Failed to mark image {} as served: {}", id, e);
+            }
+        }
+
+        Ok(bytes)
+    }
+
+    /// Delete stored renders neither served nor re-rendered in `days`, keeping any a saved fractal points at
+    /// I'm deleting the rows first so a concurrent load sees a clean miss rather than a row whose file is gone
+    pub async fn prune(&self, days: u32) -> Result<u64> {
+        let keys: Vec<String> = sqlx::query_scalar(
+            r#"
+            DELETE FROM rendered_images
+            WHERE COALESCE(last_served_at, created_at) < NOW() - make_interval(days => $1)
+              AND NOT EXISTS (SELECT 1 FROM saved_fractals WHERE saved_fractals.image_id = rendered_images.id)
+            RETURNING storage_key
+            "#
+        )
+        .bind(days as i32)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        for key in &keys {
+            if let Err(e) = self.store.delete(key).await {
+                warn!("Failed to delete pruned image {}: {}", key, e);
+            }
+        }
+
+        Ok(keys.len() as u64)
+    }
+}
+
+/// Encode an RGBA8 buffer as PNG
+pub fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Result<Vec<u8>> {
+    if rgba.len() != (width as usize) * (height as usize) * 4 {
+        return Err(AppError::InternalServerError(format!(
+            "Pixel buffer of {} bytes does not match {}x{} RGBA", rgba.len(), width, height
+        )));
+    }
+
+    let mut encoded = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut encoded, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()
+            .map_err(|e| AppError::InternalServerError(format!("PNG header error: {}", e)))?;
+        writer.write_image_data(rgba)
+            .map_err(|e| AppError::InternalServerError(format!("PNG encoding error: {}", e)))?;
+    }
+
+    Ok(encoded)
+}
+
+/// Encode an RGBA8 buffer in a response format; JPEG drops the alpha channel, which renders leave opaque anyway
+pub fn encode_image(format: OutputFormat, width: u32, height: u32, rgba: &[u8]) -> Result<Vec<u8>> {
+    use image::{ExtendedColorType, ImageEncoder};
+
+    if rgba.len() != (width as usize) * (height as usize) * 4 {
+        return Err(AppError::InternalServerError(format!(
+            "Pixel buffer of {} bytes does not match {}x{} RGBA", rgba.len(), width, height
+        )));
+    }
+
+    let mut encoded = Vec::new();
+    match format {
+        OutputFormat::Raw => return Err(AppError::internal("Raw output has no image encoding")),
+        OutputFormat::Png => return encode_png(width, height, rgba),
+        OutputFormat::Jpeg => {
+            let rgb: Vec<u8> = rgba.chunks_exact(4).flat_map(|pixel| [pixel[0], pixel[1], pixel[2]]).collect();
+            image::codecs::jpeg::JpegEncoder::new_with_quality(&mut encoded, JPEG_QUALITY)
+                .write_image(&rgb, width, height, ExtendedColorType::Rgb8)
+                .map_err(|e| AppError::InternalServerError(format!("JPEG encoding error: {}", e)))?;
+        }
+        OutputFormat::Webp => {
+            image::codecs::webp::WebPEncoder::new_lossless(&mut encoded)
+                .write_image(rgba, width, height, ExtendedColorType::Rgba8)
+                .map_err(|e| AppError::InternalServerError(format!("WebP encoding error: {}", e)))?;
+        }
+    }
+
+    Ok(encoded)
+}
+
+fn content_id(bytes: &[u8]) -> String {
+    let digest = Sha256::digest(bytes);
+    format!("{:x}", digest)[..32].to_string()
+}
+
+/// Image ids are lowercase hex digests; anything else is rejected before touching storage
+pub fn is_valid_image_id(id: &str) -> bool {
+    id.len() == 32 && id.chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_png_signature() {
+        let rgba = vec![255u8; 4 * 4 * 4];
+        let png = encode_png(4, 4, &rgba).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert!(encode_png(5, 4, &rgba).is_err());
+    }
+
+    #[test]
+    fn test_encode_image_formats() {
+        let rgba: Vec<u8> = (0..16 * 16).flat_map(|i| [i as u8, 40, 200, 255]).collect();
+        assert_eq!(&encode_image(OutputFormat::Png, 16, 16, &rgba).unwrap()[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&encode_image(OutputFormat::Jpeg, 16, 16, &rgba).unwrap()[..3], &[0xff, 0xd8, 0xff]);
+        let webp = encode_image(OutputFormat::Webp, 16, 16, &rgba).unwrap();
+        assert_eq!((&webp[..4], &webp[8..12]), (&b"RIFF"[..], &b"WEBP"[..]));
+
+        let decoded = image::load_from_memory(&webp).unwrap().to_rgba8();
+        assert_eq!(decoded.into_raw(), rgba);
+        assert!(encode_image(OutputFormat::Raw, 16, 16, &rgba).is_err());
+        assert!(encode_image(OutputFormat::Jpeg, 15, 16, &rgba).is_err());
+    }
+
+    #[test]
+    fn test_image_id_validation() {
+        let id = content_id(b"fractal");
+        assert!(is_valid_image_id(&id));
+        assert!(!is_valid_image_id("../../etc/passwd"));
+        assert!(!is_valid_image_id(&id.to_uppercase()));
+    }
+
+    #[tokio::test]
+    async fn test_disk_store_roundtrip() {
+        let root = std::env::temp_dir().join(format!("image-store-{}", Uuid::new_v4()));
+        let store = DiskImageStore::new(&root);
+
+        assert!(!store.exists("abcd.png").await.unwrap());
+        store.put("abcd.png", b"bytes").await.unwrap();
+        assert_eq!(store.get("abcd.png").await.unwrap().as_deref(), Some(&b"bytes"[..]));
+        let shard: Vec<_> = std::fs::read_dir(root.join("ab")).unwrap().collect();
+        assert_eq!(shard.len(), 1, "no temp file is left behind");
+
+        store.delete("abcd.png").await.unwrap();
+        assert!(!store.exists("abcd.png").await.unwrap());
+        store.delete("abcd.png").await.unwrap();
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
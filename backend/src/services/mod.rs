@@ -3,22 +3,78 @@
  * I'm organizing GitHub API integration, fractal computation, performance monitoring, and caching into a cohesive service layer that maintains clean separation of concerns.
  */
 
-pub mod fractal_service;
 pub mod github_service;
 pub mod performance_service;
 pub mod cache_service;
+pub mod audit_service;
+pub mod palette_service;
+pub mod image_service;
+pub mod escape_export;
+pub mod webhook_service;
+pub mod email_service;
+pub mod notification_template_service;
+pub mod recording_service;
+pub mod usage_service;
+pub mod settings_service;
+pub mod feature_flag_service;
+pub mod slo_service;
+pub mod export_service;
+pub mod animation_service;
+pub mod fractal_job_service;
+pub mod fractal_cache_service;
+pub mod catalog_service;
+pub mod gallery_service;
+pub mod sync_service;
+pub mod session_service;
+pub mod tenant_service;
+pub mod user_service;
+
+// The fractal engine lives in dark-performance-core, its kernels in fractal-engine; these keep the old module paths working
+pub use dark_performance_core::fractal as fractal_service;
+pub use dark_performance_core::renderers;
+pub use dark_performance_core::cancel;
+pub use fractal_engine::kernels;
+pub use dark_performance_core::mandelbulb;
+pub use dark_performance_core::cost;
+pub use dark_performance_core::strategies;
 
 // Re-export all services for convenient access throughout the application
 pub use fractal_service::FractalService;
+pub use renderers::{FractalRenderer, RendererRegistry};
 pub use github_service::GitHubService;
 pub use performance_service::PerformanceService;
 pub use cache_service::CacheService;
+pub use audit_service::{AuditService, AuditSettings};
+pub use palette_service::PaletteService;
+pub use image_service::{DiskImageStore, ImageService, ImageStore};
+pub use webhook_service::WebhookService;
+pub use email_service::{EmailService, EmailSettings, EmailTemplate};
+pub use notification_template_service::NotificationTemplateService;
+pub use recording_service::{RecordingService, RecordingSettings};
+pub use usage_service::UsageService;
+pub use settings_service::SettingsService;
+pub use feature_flag_service::FeatureFlagService;
+pub use slo_service::{SloService, SloSettings};
+pub use export_service::ExportService;
+pub use animation_service::AnimationService;
+pub use fractal_job_service::FractalJobService;
+pub use fractal_cache_service::FractalCacheService;
+pub use catalog_service::CatalogService;
+pub use gallery_service::GalleryService;
+pub use sync_service::{SyncService, SyncTrigger};
+pub use session_service::SessionService;
+pub use tenant_service::{TenantContext, TenantService};
+pub use user_service::UserService;
 
 use crate::{
     database::DatabasePool,
     utils::error::{AppError, Result},
+    AppState,
 };
-use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 use tokio::sync::RwLock;
 
 /// Service registry for centralized service management and dependency injection
@@ -28,6 +84,8 @@ pub struct ServiceRegistry {
     pub github_service: Arc<GitHubService>,
     pub performance_service: Arc<PerformanceService>,
     pub cache_service: Arc<CacheService>,
+    /// Present when built from a running app; warm-up then refreshes stale repositories through the sync pipeline
+    pub sync_service: Option<Arc<SyncService>>,
 }
 
 impl ServiceRegistry {
@@ -66,9 +124,21 @@ impl ServiceRegistry {
             github_service,
             performance_service,
             cache_service,
+            sync_service: None,
         })
     }
 
+    /// Registry over the running application's services, so warm-up fills the caches requests will actually read
+    pub fn from_app_state(app_state: &AppState) -> Self {
+        Self {
+            fractal_service: Arc::new(app_state.fractal_service.clone()),
+            github_service: Arc::new(app_state.github_service.clone()),
+            performance_service: Arc::new(app_state.performance_service.clone()),
+            cache_service: Arc::new(app_state.cache_service.clone()),
+            sync_service: Some(Arc::new(app_state.sync_service.clone())),
+        }
+    }
+
     /// Perform health checks on all services
     /// I'm implementing comprehensive service health verification
     pub async fn health_check(&self) -> Result<serde_json::Value> {
@@ -111,13 +181,11 @@ impl ServiceRegistry {
                 use crate::services::fractal_service::{FractalRequest, FractalType};
 
                 let test_request = FractalRequest {
-                    width: 32,
-                    height: 32,
                     center_x: -0.5,
                     center_y: 0.0,
                     zoom: 1.0,
                     max_iterations: 50,
-                    fractal_type: FractalType::Mandelbrot,
+                    ..FractalRequest::new(FractalType::Mandelbrot, 32, 32)
                 };
 
                 fractal_service.generate_mandelbrot(test_request)
@@ -211,7 +279,11 @@ impl ServiceRegistry {
         tracing::info!("Warming up services");
 
         // Warm up GitHub service by fetching initial repository data
-        if let Err(e) = self.github_service.get_user_repositories(github_username).await {
+        let github_warm_up = match &self.sync_service {
+            Some(sync_service) => sync_service.sync_if_stale(github_username, SyncTrigger::WarmUp).await.map(|_| ()),
+            None => self.github_service.get_user_repositories(github_username).await.map(|_| ()),
+        };
+        if let Err(e) = github_warm_up {
             tracing::warn!("Failed to warm up GitHub service: {}", e);
         }
 
@@ -222,13 +294,11 @@ impl ServiceRegistry {
                 use crate::services::fractal_service::{FractalRequest, FractalType};
 
                 let warm_up_request = FractalRequest {
-                    width: 128,
-                    height: 128,
                     center_x: -0.5,
                     center_y: 0.0,
                     zoom: 1.0,
                     max_iterations: 100,
-                    fractal_type: FractalType::Mandelbrot,
+                    ..FractalRequest::new(FractalType::Mandelbrot, 128, 128)
                 };
 
                 fractal_service.generate_mandelbrot(warm_up_request)
@@ -267,6 +337,40 @@ impl ServiceRegistry {
     }
 }
 
+/// How startup warm-up went, reported by the readiness probe
+#[derive(Debug, Clone, Serialize)]
+pub struct WarmUpStatus {
+    pub completed_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    /// Readiness was granted because warm-up ran past WARM_UP_TIMEOUT_SECONDS
+    pub timed_out: bool,
+}
+
+/// Holds readiness back until ServiceRegistry::warm_up has run once
+#[derive(Debug, Clone, Default)]
+pub struct WarmUpGate {
+    status: Arc<OnceLock<WarmUpStatus>>,
+}
+
+impl WarmUpGate {
+    /// Open the gate; only the first call is recorded
+    pub fn complete(&self, duration: Duration, timed_out: bool) {
+        let _ = self.status.set(WarmUpStatus {
+            completed_at: Utc::now(),
+            duration_ms: duration.as_millis() as u64,
+            timed_out,
+        });
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.status.get().is_some()
+    }
+
+    pub fn status(&self) -> Option<WarmUpStatus> {
+        self.status.get().cloned()
+    }
+}
+
 /// Service factory for creating individual services with proper configuration
 /// I'm providing factory methods for flexible service instantiation
 pub struct ServiceFactory;
@@ -322,94 +426,30 @@ pub trait Cacheable {
     fn cache_ttl(&self) -> u64;
 }
 
-/// Middleware for service request/response processing
-/// I'm implementing service middleware for cross-cutting concerns
-pub struct ServiceMiddleware;
-
-impl ServiceMiddleware {
-    /// Log service method calls for debugging and monitoring
-    /// I'm implementing service call logging for observability
-    pub async fn log_service_call<F, T>(
-        service_name: &str,
-        method_name: &str,
-        future: F,
-    ) -> Result<T>
-    where
-        F: std::future::Future<Output = Result<T>>,
-    {
-        let start_time = std::time::Instant::now();
-
-        tracing::debug!("Calling {}.{}", service_name, method_name);
-
-        match future.await {
-            Ok(result) => {
-                let duration = start_time.elapsed();
-                tracing::debug!(
-                    "{}.{} completed successfully in {:?}",
-                    service_name,
-                    method_name,
-                    duration
-                );
-                Ok(result)
-            }
-            Err(error) => {
-                let duration = start_time.elapsed();
-                tracing::error!(
-                    "{}.{} failed after {:?}: {}",
-                    service_name,
-                    method_name,
-                    duration,
-                    error
-                );
-                Err(error)
-            }
-        }
-    }
-
-    /// Add timing metrics to service calls
-    /// I'm implementing automatic performance tracking for service calls
-    pub async fn with_timing<F, T>(
-        metric_name: &str,
-        future: F,
-    ) -> Result<T>
-    where
-        F: std::future::Future<Output = Result<T>>,
-    {
-        let start_time = std::time::Instant::now();
-
-        let result = future.await;
-
-        let duration = start_time.elapsed();
-
-        // Here we would record the timing metric
-        tracing::debug!("Service call {} took {:?}", metric_name, duration);
-
-        result
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_warm_up_gate_opens_once() {
+        let gate = WarmUpGate::default();
+        assert!(!gate.is_complete());
+        assert!(gate.status().is_none());
+
+        let shared = gate.clone();
+        shared.complete(Duration::from_millis(1500), false);
+        gate.complete(Duration::from_secs(60), true);
+
+        let status = gate.status().unwrap();
+        assert!(gate.is_complete());
+        assert_eq!(status.duration_ms, 1500);
+        assert!(!status.timed_out);
+    }
+
     #[test]
     fn test_service_factory() {
         let fractal_service = ServiceFactory::create_fractal_service();
         // Service should be created successfully
         assert!(true);
     }
-
-    #[tokio::test]
-    async fn test_service_middleware_logging() {
-        let future = async { Ok::<i32, AppError>(42) };
-
-        let result = ServiceMiddleware::log_service_call(
-            "test_service",
-            "test_method",
-            future,
-        ).await;
-
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), 42);
-    }
 }
@@ -0,0 +1,284 @@
+/*
+ * Alert notification templates, rendering each alert's email and webhook text with tera from stored overrides or the built-in wording.
+ * I'm compiling every template into one snapshot on refresh, so firing an alert never touches the database or parses a template.
+ */
+
+use arc_swap::ArcSwap;
+use std::sync::Arc;
+use tera::{Context, Tera};
+use tracing::{info, warn};
+
+use crate::{
+    database::{timing, DatabasePool},
+    models::notification_templates::{
+        is_valid_rule_name, AlertContext, NotificationTemplate, RenderedAlert, TemplateChannel,
+        MAX_RULE_NAME_LEN, MAX_TEMPLATE_LEN, WILDCARD_RULE,
+    },
+    utils::error::{AppError, Result},
+};
+
+const TEMPLATE_COLUMNS: &str = "rule, channel, template, updated_by, updated_at";
+
+#[derive(Debug, Default)]
+struct TemplateSet {
+    tera: Tera,
+    stored: Vec<NotificationTemplate>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NotificationTemplateService {
+    db_pool: DatabasePool,
+    dashboard_url: String,
+    templates: Arc<ArcSwap<TemplateSet>>,
+}
+
+impl NotificationTemplateService {
+    pub fn new(db_pool: DatabasePool, dashboard_url: &str) -> Self {
+        let builtin = compile(Vec::new()).expect("built-in notification templates compile");
+        Self {
+            db_pool,
+            dashboard_url: dashboard_url.to_string(),
+            templates: Arc::new(ArcSwap::from_pointee(builtin)),
+        }
+    }
+
+    /// Start an alert's variables, linking the deployment's dashboard
+    pub fn alert(&self, rule: &str) -> AlertContext {
+        AlertContext::new(rule, &self.dashboard_url)
+    }
+
+    /// Render an alert for every channel, using the rule's templates, then the wildcard's, then the built-in wording
+    /// I'm falling back to the built-in wording when an override fails at render time so an alert is never lost to a template typo
+    pub fn render(&self, alert: &AlertContext) -> RenderedAlert {
+        let templates = self.templates.load();
+        let context = alert_context(alert);
+        let render = |channel: TemplateChannel| {
+            let name = [alert.rule.as_str(), WILDCARD_RULE]
+                .into_iter()
+                .map(|rule| template_name(rule, channel))
+                .find(|name| templates.tera.get_template(name).is_ok())
+                .unwrap_or_else(|| builtin_name(channel));
+
+            templates.tera.render(&name, &context).unwrap_or_else(|e| {
+                warn!("Notification template {} failed, using the built-in wording: {}", name, describe(&e));
+                templates.tera.render(&builtin_name(channel), &context).unwrap_or_default()
+            })
+        };
+
+        RenderedAlert {
+            subject: render(TemplateChannel::EmailSubject).trim().to_string(),
+            body: render(TemplateChannel::EmailBody),
+            message: render(TemplateChannel::Webhook).trim().to_string(),
+        }
+    }
+
+    /// Render a draft template, against the sample alert unless a context is given
+    pub fn preview(&self, template: &str, context: Option<AlertContext>) -> Result<String> {
+        let alert = context.unwrap_or_else(|| AlertContext::sample(&self.dashboard_url));
+        render_draft(template, &alert)
+    }
+
+    pub fn list(&self) -> Vec<NotificationTemplate> {
+        self.templates.load().stored.clone()
+    }
+
+    /// Reload the snapshot from the database
+    pub async fn refresh(&self) -> Result<usize> {
+        let sql = format!("SELECT {} FROM notification_templates ORDER BY rule, channel", TEMPLATE_COLUMNS);
+        let query = sqlx::query_as::<_, NotificationTemplate>(&sql);
+        let stored = timing::timed("notification_templates_load", query.fetch_all(&self.db_pool))
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to load notification templates: {}", e)))?;
+
+        let count = stored.len();
+        self.templates.store(Arc::new(compile(stored)?));
+        Ok(count)
+    }
+
+    /// Create or replace one rule's template for a channel, returning its previous and new state
+    pub async fn upsert(
+        &self,
+        rule: &str,
+        channel: TemplateChannel,
+        template: &str,
+        changed_by: &str,
+    ) -> Result<(Option<NotificationTemplate>, NotificationTemplate)> {
+        if !is_valid_rule_name(rule) {
+            return Err(AppError::ValidationError(format!(
+                "Rule names are '*' or 1-{} lowercase letters, digits, or '_'", MAX_RULE_NAME_LEN
+            )));
+        }
+        if template.trim().is_empty() || template.len() > MAX_TEMPLATE_LEN {
+            return Err(AppError::ValidationError(format!(
+                "Templates must be between 1 and {} bytes", MAX_TEMPLATE_LEN
+            )));
+        }
+        self.preview(template, None)?;
+
+        let mut tx = self.db_pool.begin().await?;
+        let sql = format!("SELECT {} FROM notification_templates WHERE rule = $1 AND channel = $2 FOR UPDATE", TEMPLATE_COLUMNS);
+        let before = sqlx::query_as::<_, NotificationTemplate>(&sql)
+            .bind(rule)
+            .bind(channel.as_str())
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        let sql = format!(
+            r#"
+            INSERT INTO notification_templates (rule, channel, template, updated_by, updated_at)
+            VALUES ($1, $2, $3, $4, NOW())
+            ON CONFLICT (rule, channel) DO UPDATE SET
+                template = EXCLUDED.template,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = NOW()
+            RETURNING {}
+            "#,
+            TEMPLATE_COLUMNS
+        );
+        let stored = sqlx::query_as::<_, NotificationTemplate>(&sql)
+            .bind(rule)
+            .bind(channel.as_str())
+            .bind(template)
+            .bind(changed_by)
+            .fetch_one(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        info!("Notification template {} updated by {}", template_name(rule, channel), changed_by);
+        self.refresh().await?;
+        Ok((before, stored))
+    }
+
+    /// Delete a template, returning the rule to the wildcard or built-in wording
+    pub async fn delete(&self, rule: &str, channel: TemplateChannel) -> Result<NotificationTemplate> {
+        let sql = format!("DELETE FROM notification_templates WHERE rule = $1 AND channel = $2 RETURNING {}", TEMPLATE_COLUMNS);
+        let deleted = sqlx::query_as::<_, NotificationTemplate>(&sql)
+            .bind(rule)
+            .bind(channel.as_str())
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(|| AppError::NotFoundError(format!("Notification template {} not found", template_name(rule, channel))))?;
+
+        info!("Notification template {} deleted", template_name(rule, channel));
+        self.refresh().await?;
+        Ok(deleted)
+    }
+}
+
+fn template_name(rule: &str, channel: TemplateChannel) -> String {
+    format!("{}.{}", rule, channel)
+}
+
+/// Built-in names can't collide with a rule, since rule names never contain ':'
+fn builtin_name(channel: TemplateChannel) -> String {
+    format!("builtin:{}", channel)
+}
+
+fn new_tera() -> Tera {
+    let mut tera = Tera::default();
+    // Nothing here is HTML, so values are inserted exactly as given
+    tera.autoescape_on(Vec::new());
+    tera
+}
+
+/// Compile the built-in templates and every stored one; a stored template that no longer compiles is skipped
+fn compile(stored: Vec<NotificationTemplate>) -> Result<TemplateSet> {
+    let mut tera = new_tera();
+    for channel in TemplateChannel::ALL {
+        tera.add_raw_template(&builtin_name(channel), channel.builtin_template())
+            .map_err(|e| AppError::InternalServerError(format!("Built-in {} template is invalid: {}", channel, describe(&e))))?;
+    }
+
+    for template in &stored {
+        let name = format!("{}.{}", template.rule, template.channel);
+        if let Err(e) = tera.add_raw_template(&name, &template.template) {
+            warn!("Skipping notification template {}: {}", name, describe(&e));
+        }
+    }
+    Ok(TemplateSet { tera, stored })
+}
+
+fn render_draft(template: &str, alert: &AlertContext) -> Result<String> {
+    let mut tera = new_tera();
+    tera.add_raw_template("draft", template)
+        .and_then(|_| tera.render("draft", &alert_context(alert)))
+        .map_err(|e| AppError::ValidationError(format!("Invalid template: {}", describe(&e))))
+}
+
+fn alert_context(alert: &AlertContext) -> Context {
+    Context::from_serialize(alert).unwrap_or_default()
+}
+
+/// Tera keeps the useful part of an error, like the unknown variable's name, in its source chain
+fn describe(error: &tera::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn stored(rule: &str, channel: TemplateChannel, template: &str) -> NotificationTemplate {
+        NotificationTemplate {
+            rule: rule.to_string(),
+            channel: channel.as_str().to_string(),
+            template: template.to_string(),
+            updated_by: "admin".to_string(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn service_with(templates: Vec<NotificationTemplate>) -> NotificationTemplateService {
+        let pool = sqlx::postgres::PgPoolOptions::new().connect_lazy("postgresql://localhost/test").unwrap();
+        let service = NotificationTemplateService::new(pool, "https://dash.example.com");
+        service.templates.store(Arc::new(compile(templates).unwrap()));
+        service
+    }
+
+    #[tokio::test]
+    async fn test_builtin_wording_uses_every_variable() {
+        let service = service_with(Vec::new());
+        let rendered = service.render(&AlertContext::sample("https://dash.example.com"));
+
+        assert_eq!(rendered.subject, "[alert] cpu_usage_high: cpu_usage_percent is 93.5");
+        assert!(rendered.body.contains("cpu_usage_percent is 93.5 (threshold 90)"));
+        assert!(rendered.body.contains("- system: CPU usage 93.5%\n"));
+        assert!(rendered.body.ends_with("Dashboard: https://dash.example.com\n"));
+        assert_eq!(rendered.message, "cpu_usage_high: cpu_usage_percent is 93.5 (threshold 90) (system: CPU usage 93.5%)");
+    }
+
+    #[tokio::test]
+    async fn test_rule_templates_beat_the_wildcard() {
+        let service = service_with(vec![
+            stored(WILDCARD_RULE, TemplateChannel::Webhook, ":rotating_light: {{ rule }} <{{ dashboard_url }}>"),
+            stored("cpu_usage_high", TemplateChannel::Webhook, "CPU at {{ value }}% (limit {{ threshold }}%)"),
+            stored("broken", TemplateChannel::Webhook, "{{ no_such_variable }}"),
+        ]);
+
+        let cpu = service.render(&service.alert("cpu_usage_high").with_metric("cpu", 95, Some(90)));
+        assert_eq!(cpu.message, "CPU at 95% (limit 90%)");
+
+        let other = service.render(&service.alert("service_unhealthy"));
+        assert_eq!(other.message, ":rotating_light: service_unhealthy <https://dash.example.com>");
+        assert_eq!(other.subject, "[alert] service_unhealthy");
+
+        // A template that fails at render time falls back to the built-in wording
+        assert_eq!(service.render(&service.alert("broken")).message, "broken");
+    }
+
+    #[tokio::test]
+    async fn test_drafts_are_checked_against_the_sample() {
+        let service = service_with(Vec::new());
+        assert_eq!(service.preview("{{ metric }}={{ value }}", None).unwrap(), "cpu_usage_percent=93.5");
+        assert!(matches!(service.preview("{{ metrc }}", None), Err(AppError::ValidationError(_))));
+        assert!(matches!(service.preview("{% if %}", None), Err(AppError::ValidationError(_))));
+    }
+}
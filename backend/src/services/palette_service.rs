@@ -0,0 +1,232 @@
+/*
+ * Palette and preset persistence service backing the upload endpoints and fractal colouring.
+ * I'm caching decoded palettes in memory since they are immutable once stored and read on every render that uses them.
+ */
+
+use dashmap::DashMap;
+use sqlx::Row;
+use std::sync::Arc;
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::{
+    database::DatabasePool,
+    models::palettes::{ColorStop, FractalPreset, Palette, PaletteFormat, PresetBundle, PresetParameters},
+    utils::error::{AppError, Result},
+};
+
+#[derive(Debug, Clone)]
+pub struct PaletteService {
+    db_pool: DatabasePool,
+    /// Keyed by owning tenant as well as id, so a cached palette never leaks across tenants
+    palette_cache: Arc<DashMap<(String, Uuid), Palette>>,
+}
+
+impl PaletteService {
+    pub fn new(db_pool: DatabasePool) -> Self {
+        Self {
+            db_pool,
+            palette_cache: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Persist a validated palette for `tenant_id`
+    pub async fn create_palette(&self, tenant_id: &str, palette: Palette) -> Result<Palette> {
+        sqlx::query(
+            r#"
+            INSERT INTO palettes (id, name, source_format, stops, stop_count, created_at, tenant_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#
+        )
+        .bind(palette.id)
+        .bind(&palette.name)
+        .bind(palette.source_format.as_str())
+        .bind(serde_json::to_value(&palette.stops)?)
+        .bind(palette.stops.len() as i32)
+        .bind(palette.created_at)
+        .bind(tenant_id)
+        .execute(&self.db_pool)
+        .await?;
+
+        info!("Stored palette {} ({} stops) for tenant {}", palette.id, palette.stops.len(), tenant_id);
+        self.palette_cache.insert((tenant_id.to_string(), palette.id), palette.clone());
+        Ok(palette)
+    }
+
+    /// Load one of the tenant's palettes by id, serving built-ins and repeated lookups from memory
+    pub async fn get_palette(&self, tenant_id: &str, id: Uuid) -> Result<Palette> {
+        if let Some(palette) = Palette::builtin_by_id(id) {
+            return Ok(palette);
+        }
+        let cache_key = (tenant_id.to_string(), id);
+        if let Some(palette) = self.palette_cache.get(&cache_key) {
+            debug!("Palette {} served from memory", id);
+            return Ok(palette.clone());
+        }
+
+        let row = sqlx::query(
+            "SELECT id, name, source_format, stops, created_at FROM palettes WHERE id = $1 AND tenant_id = $2"
+        )
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::not_found(format!("palette {}", id)))?;
+
+        let palette = palette_from_row(&row)?;
+        self.palette_cache.insert(cache_key, palette.clone());
+        Ok(palette)
+    }
+
+    /// The palette a render asked for by stored or built-in id, built-in name, or inline gradient stops
+    /// I'm refusing more than one at a time rather than picking a winner the caller can't see
+    pub async fn resolve_palette(
+        &self,
+        tenant_id: &str,
+        id: Option<Uuid>,
+        name: Option<&str>,
+        gradient: Option<&str>,
+    ) -> Result<Option<Palette>> {
+        match (id, name, gradient) {
+            (None, None, None) => Ok(None),
+            (Some(id), None, None) => Ok(Some(self.get_palette(tenant_id, id).await?)),
+            (None, Some(name), None) => Palette::builtin(name)
+                .map(Some)
+                .ok_or_else(|| AppError::ValidationError(format!("Unknown built-in palette: {}", name))),
+            (None, None, Some(gradient)) => Ok(Some(Palette::from_gradient(gradient)?)),
+            _ => Err(AppError::ValidationError(
+                "Choose one of `palette_id`, `palette`, or `gradient`".to_string()
+            )),
+        }
+    }
+
+    /// List the tenant's palettes newest first
+    pub async fn list_palettes(&self, tenant_id: &str, limit: i64, offset: i64) -> Result<Vec<Palette>> {
+        let rows = sqlx::query(
+            "SELECT id, name, source_format, stops, created_at FROM palettes
+             WHERE tenant_id = $3
+             ORDER BY created_at DESC LIMIT $1 OFFSET $2"
+        )
+        .bind(limit)
+        .bind(offset)
+        .bind(tenant_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        rows.iter().map(palette_from_row).collect()
+    }
+
+    /// Persist a preset bundle, storing an embedded palette first when present
+    /// I'm wrapping both inserts in one transaction so a bad preset never leaves an orphan palette
+    pub async fn create_preset(&self, tenant_id: &str, bundle: PresetBundle) -> Result<FractalPreset> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let palette_id = match (bundle.palette_id, bundle.palette) {
+            (Some(id), _) => {
+                let exists: bool =
+                    sqlx::query("SELECT EXISTS (SELECT 1 FROM palettes WHERE id = $1 AND tenant_id = $2) AS exists")
+                        .bind(id)
+                        .bind(tenant_id)
+                        .fetch_one(&mut *tx)
+                        .await?
+                        .try_get("exists")?;
+                if !exists {
+                    return Err(AppError::ValidationError(format!("Palette {} does not exist", id)));
+                }
+                Some(id)
+            }
+            (None, Some(embedded)) => {
+                let palette = Palette::parse(PaletteFormat::Json, embedded.to_string().as_bytes(), None)?;
+                sqlx::query(
+                    r#"
+                    INSERT INTO palettes (id, name, source_format, stops, stop_count, created_at, tenant_id)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7)
+                    "#
+                )
+                .bind(palette.id)
+                .bind(&palette.name)
+                .bind(palette.source_format.as_str())
+                .bind(serde_json::to_value(&palette.stops)?)
+                .bind(palette.stops.len() as i32)
+                .bind(palette.created_at)
+                .bind(tenant_id)
+                .execute(&mut *tx)
+                .await?;
+                Some(palette.id)
+            }
+            (None, None) => None,
+        };
+
+        let preset = FractalPreset {
+            id: Uuid::new_v4(),
+            name: bundle.name.trim().to_string(),
+            description: bundle.description,
+            parameters: bundle.parameters,
+            palette_id,
+            created_at: chrono::Utc::now(),
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO fractal_presets (id, name, description, parameters, palette_id, created_at, tenant_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#
+        )
+        .bind(preset.id)
+        .bind(&preset.name)
+        .bind(&preset.description)
+        .bind(serde_json::to_value(&preset.parameters)?)
+        .bind(preset.palette_id)
+        .bind(preset.created_at)
+        .bind(tenant_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        info!("Stored fractal preset {} '{}'", preset.id, preset.name);
+        Ok(preset)
+    }
+
+    /// Load one of the tenant's presets by id
+    pub async fn get_preset(&self, tenant_id: &str, id: Uuid) -> Result<FractalPreset> {
+        let row = sqlx::query(
+            "SELECT id, name, description, parameters, palette_id, created_at
+             FROM fractal_presets WHERE id = $1 AND tenant_id = $2"
+        )
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::not_found(format!("preset {}", id)))?;
+
+        let parameters: serde_json::Value = row.try_get("parameters")?;
+        let parameters: PresetParameters = serde_json::from_value(parameters)?;
+
+        Ok(FractalPreset {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            description: row.try_get("description")?,
+            parameters,
+            palette_id: row.try_get("palette_id")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+fn palette_from_row(row: &sqlx::postgres::PgRow) -> Result<Palette> {
+    let format: String = row.try_get("source_format")?;
+    let source_format = match format.as_str() {
+        "ggr" => PaletteFormat::Ggr,
+        _ => PaletteFormat::Json,
+    };
+    let stops: serde_json::Value = row.try_get("stops")?;
+    let stops: Vec<ColorStop> = serde_json::from_value(stops)?;
+
+    Ok(Palette::from_stops(
+        row.try_get("id")?,
+        row.try_get("name")?,
+        source_format,
+        stops,
+        row.try_get("created_at")?,
+    ))
+}
@@ -4,16 +4,15 @@
  */
 
 use serde::{Deserialize, Serialize};
-use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant};
 use sysinfo::{System, SystemExt, CpuExt, DiskExt, NetworkExt, NetworksExt, ComponentExt};
 use tokio::sync::RwLock;
-use tracing::{info, warn, debug};
+use tracing::{info, warn, instrument};
 use std::sync::Arc;
 use std::collections::VecDeque;
-use uuid::Uuid;
 
 use crate::{
-    utils::error::{AppError, Result},
+    utils::error::Result,
     database::DatabasePool,
 };
 
@@ -67,6 +66,7 @@ impl PerformanceService {
 
     /// Get current system metrics with comprehensive data collection
     /// I'm implementing real-time system monitoring with detailed analysis
+    #[instrument(name = "performance.get_system_metrics", level = "debug", skip(self), err(Display, level = "debug"))]
     pub async fn get_system_metrics(&self) -> Result<SystemMetrics> {
         let mut system = self.system.write().await;
         system.refresh_all();
@@ -152,6 +152,7 @@ impl PerformanceService {
 
     /// Get simplified system information for general use
     /// I'm providing basic system info without full metrics collection
+    #[instrument(name = "performance.get_system_info", level = "debug", skip(self), err(Display, level = "debug"))]
     pub async fn get_system_info(&self) -> Result<serde_json::Value> {
         let mut system = self.system.write().await;
         system.refresh_all();
@@ -183,30 +184,13 @@ impl PerformanceService {
 
     /// Run a basic performance benchmark
     /// I'm implementing a simple benchmark for demonstration purposes
+    #[instrument(name = "performance.run_benchmark", level = "debug", skip(self), err(Display, level = "debug"))]
     pub async fn run_benchmark(&self) -> Result<serde_json::Value> {
         info!("Starting performance benchmark");
         let start_time = Instant::now();
 
-        // Simple CPU benchmark: calculate prime numbers
-        let cpu_benchmark = tokio::task::spawn_blocking(|| {
-            let start = Instant::now();
-            let mut count = 0u32;
-            for i in 2..50000 {
-                if is_prime(i) {
-                    count += 1;
-                }
-            }
-            (count, start.elapsed())
-        }).await.unwrap();
-
-        // Simple memory benchmark
-        let memory_benchmark = tokio::task::spawn_blocking(|| {
-            let start = Instant::now();
-            let data_size: u64 = 10_000_000;
-            let data: Vec<u64> = (0..data_size).collect();
-            let sum: u64 = data.iter().sum();
-            (sum, start.elapsed())
-        }).await.unwrap();
+        let cpu_benchmark = tokio::task::spawn_blocking(cpu_benchmark).await.unwrap();
+        let memory_benchmark = tokio::task::spawn_blocking(memory_benchmark).await.unwrap();
 
         let total_time = start_time.elapsed();
 
@@ -222,7 +206,7 @@ impl PerformanceService {
             "memory_benchmark": {
                 "data_processed": memory_benchmark.0,
                 "duration_ms": memory_benchmark.1.as_millis(),
-                "mb_per_second": (10_000_000 * 8) as f64 / (1024.0 * 1024.0) / memory_benchmark.1.as_secs_f64()
+                "mb_per_second": memory_benchmark_megabytes() / memory_benchmark.1.as_secs_f64()
             },
             "system_info": self.get_system_info().await?
         });
@@ -233,6 +217,7 @@ impl PerformanceService {
 
     /// Get metrics history for analysis
     /// I'm providing historical data for trend analysis
+    #[instrument(name = "performance.get_metrics_history", level = "debug", skip(self), err(Display, level = "debug"))]
     pub async fn get_metrics_history(&self, limit: Option<usize>) -> Result<Vec<SystemMetrics>> {
         let history = self.metrics_history.read().await;
         let limit = limit.unwrap_or(100).min(history.len());
@@ -294,6 +279,29 @@ impl PerformanceService {
     }
 }
 
+const CPU_BENCHMARK_LIMIT: u32 = 50_000;
+const MEMORY_BENCHMARK_ELEMENTS: u64 = 10_000_000;
+
+/// Count the primes below 50,000 by trial division; returns the count and how long it took
+pub fn cpu_benchmark() -> (u32, Duration) {
+    let start = Instant::now();
+    let count = (2..CPU_BENCHMARK_LIMIT).filter(|&i| is_prime(i)).count() as u32;
+    (count, start.elapsed())
+}
+
+/// Allocate and sum ten million u64s; returns the sum and how long it took
+pub fn memory_benchmark() -> (u64, Duration) {
+    let start = Instant::now();
+    let data: Vec<u64> = (0..MEMORY_BENCHMARK_ELEMENTS).collect();
+    let sum: u64 = data.iter().sum();
+    (sum, start.elapsed())
+}
+
+/// Megabytes the memory benchmark touches
+pub fn memory_benchmark_megabytes() -> f64 {
+    (MEMORY_BENCHMARK_ELEMENTS * 8) as f64 / (1024.0 * 1024.0)
+}
+
 // Helper function for CPU benchmark
 fn is_prime(n: u32) -> bool {
     if n < 2 {
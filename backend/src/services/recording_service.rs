@@ -0,0 +1,318 @@
+/*
+ * Request recording store, keeping sanitized exchanges in Postgres or as one JSON file each on disk.
+ * I'm writing recordings off the request path and trimming the oldest past the configured count, so recording never slows or fills a dev box.
+ */
+
+use std::path::PathBuf;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::{
+    database::{timing, DatabasePool},
+    models::recordings::{RecordingQuery, RecordingSummary, RequestRecording},
+    utils::{
+        config::{Config, RecordingStore},
+        correlation,
+        error::{AppError, Result},
+    },
+};
+
+const RECORDING_COLUMNS: &str = "id, method, path, query, tenant, request_headers, request_body, request_body_bytes, \
+    status, response_headers, response_body, response_body_bytes, duration_ms, recorded_at";
+
+/// Recording settings snapshotted from Config
+#[derive(Debug, Clone)]
+pub struct RecordingSettings {
+    pub enabled: bool,
+    pub store: RecordingStore,
+    pub directory: PathBuf,
+    pub paths: Vec<String>,
+    pub max_body_bytes: usize,
+    pub max_entries: usize,
+}
+
+impl RecordingSettings {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            enabled: config.request_recording_enabled,
+            store: config.request_recording_store,
+            directory: PathBuf::from(&config.request_recording_path),
+            paths: config.request_recording_paths.clone(),
+            max_body_bytes: usize::try_from(config.request_recording_max_body_bytes).unwrap_or(usize::MAX),
+            max_entries: config.request_recording_max_entries as usize,
+        }
+    }
+
+    /// Whether requests to a path are recorded; admin routes never are, and versioned paths match their /api form
+    pub fn records(&self, path: &str) -> bool {
+        let path = path.strip_prefix("/v1").unwrap_or(path);
+        self.enabled
+            && !path.starts_with("/api/admin")
+            && self.paths.iter().any(|prefix| path.starts_with(prefix.as_str()))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordingService {
+    db_pool: DatabasePool,
+    settings: RecordingSettings,
+}
+
+impl RecordingService {
+    pub fn new(db_pool: DatabasePool, settings: RecordingSettings) -> Self {
+        Self { db_pool, settings }
+    }
+
+    pub fn settings(&self) -> &RecordingSettings {
+        &self.settings
+    }
+
+    /// Store a recording in the background, dropping the oldest past the limit
+    pub fn record(&self, recording: RequestRecording) {
+        let service = self.clone();
+        correlation::spawn(async move {
+            let id = recording.id;
+            let result = match service.settings.store {
+                RecordingStore::Database => service.insert_row(&recording).await,
+                RecordingStore::Disk => service.write_file(&recording).await,
+            };
+            match result {
+                Ok(()) => debug!("Recorded {} {} as {}", recording.method, recording.path, id),
+                Err(e) => warn!("Failed to store recording of {} {}: {}", recording.method, recording.path, e),
+            }
+        });
+    }
+
+    /// Newest recordings first
+    pub async fn list(&self, query: &RecordingQuery) -> Result<Vec<RecordingSummary>> {
+        match self.settings.store {
+            RecordingStore::Database => {
+                let sql = "SELECT id, method, path, query, tenant, status, duration_ms, recorded_at FROM request_recordings \
+                    WHERE ($1::TEXT IS NULL OR starts_with(path, $1)) ORDER BY recorded_at DESC LIMIT $2";
+                let rows = sqlx::query_as::<_, RecordingSummary>(sql)
+                    .bind(query.path.as_deref())
+                    .bind(query.limit() as i64)
+                    .fetch_all(&self.db_pool);
+                Ok(timing::timed("request_recordings_list", rows).await?)
+            }
+            RecordingStore::Disk => {
+                let mut summaries = Vec::new();
+                for path in self.recording_files().await?.into_iter().rev() {
+                    if summaries.len() == query.limit() {
+                        break;
+                    }
+                    let recording = read_file(&path).await?;
+                    if query.path.as_deref().map_or(true, |prefix| recording.path.starts_with(prefix)) {
+                        summaries.push(recording.summary());
+                    }
+                }
+                Ok(summaries)
+            }
+        }
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<RequestRecording> {
+        let recording = match self.settings.store {
+            RecordingStore::Database => {
+                let sql = format!("SELECT {} FROM request_recordings WHERE id = $1", RECORDING_COLUMNS);
+                sqlx::query_as::<_, RequestRecording>(&sql)
+                    .bind(id)
+                    .fetch_optional(&self.db_pool)
+                    .await?
+            }
+            RecordingStore::Disk => {
+                let suffix = file_suffix(id);
+                match self.recording_files().await?.into_iter().find(|path| path.to_string_lossy().ends_with(&suffix)) {
+                    Some(path) => Some(read_file(&path).await?),
+                    None => None,
+                }
+            }
+        };
+        recording.ok_or_else(|| AppError::NotFoundError(format!("Recording {} not found", id)))
+    }
+
+    /// Delete every recording, returning how many there were
+    pub async fn clear(&self) -> Result<u64> {
+        match self.settings.store {
+            RecordingStore::Database => Ok(sqlx::query("DELETE FROM request_recordings")
+                .execute(&self.db_pool)
+                .await?
+                .rows_affected()),
+            RecordingStore::Disk => {
+                let files = self.recording_files().await?;
+                for path in &files {
+                    remove_file(path).await?;
+                }
+                Ok(files.len() as u64)
+            }
+        }
+    }
+
+    async fn insert_row(&self, recording: &RequestRecording) -> Result<()> {
+        let sql = format!(
+            "INSERT INTO request_recordings ({}) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)",
+            RECORDING_COLUMNS
+        );
+        sqlx::query(&sql)
+            .bind(recording.id)
+            .bind(&recording.method)
+            .bind(&recording.path)
+            .bind(&recording.query)
+            .bind(&recording.tenant)
+            .bind(&recording.request_headers)
+            .bind(&recording.request_body)
+            .bind(recording.request_body_bytes)
+            .bind(recording.status)
+            .bind(&recording.response_headers)
+            .bind(&recording.response_body)
+            .bind(recording.response_body_bytes)
+            .bind(recording.duration_ms)
+            .bind(recording.recorded_at)
+            .execute(&self.db_pool)
+            .await?;
+
+        sqlx::query(
+            "DELETE FROM request_recordings WHERE id IN \
+            (SELECT id FROM request_recordings ORDER BY recorded_at DESC OFFSET $1)"
+        )
+        .bind(self.settings.max_entries as i64)
+        .execute(&self.db_pool)
+        .await?;
+        Ok(())
+    }
+
+    /// File names start with the recording time, so sorting them sorts the recordings
+    async fn write_file(&self, recording: &RequestRecording) -> Result<()> {
+        tokio::fs::create_dir_all(&self.settings.directory)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Failed to create recording directory: {}", e)))?;
+
+        let name = format!("{}{}", recording.recorded_at.format("%Y%m%dT%H%M%S%.6fZ"), file_suffix(recording.id));
+        tokio::fs::write(self.settings.directory.join(name), serde_json::to_vec_pretty(recording)?)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Failed to write recording: {}", e)))?;
+
+        let files = self.recording_files().await?;
+        for path in files.iter().take(files.len().saturating_sub(self.settings.max_entries)) {
+            remove_file(path).await?;
+        }
+        Ok(())
+    }
+
+    /// Recording files, oldest first
+    async fn recording_files(&self) -> Result<Vec<PathBuf>> {
+        let mut entries = match tokio::fs::read_dir(&self.settings.directory).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(AppError::InternalServerError(format!("Failed to read recording directory: {}", e))),
+        };
+
+        let mut files = Vec::new();
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Failed to read recording directory: {}", e)))?
+        {
+            let path = entry.path();
+            if path.extension().is_some_and(|extension| extension == "json") {
+                files.push(path);
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+}
+
+fn file_suffix(id: Uuid) -> String {
+    format!("-{}.json", id)
+}
+
+async fn read_file(path: &PathBuf) -> Result<RequestRecording> {
+    let bytes = tokio::fs::read(path)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Failed to read recording {}: {}", path.display(), e)))?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+async fn remove_file(path: &PathBuf) -> Result<()> {
+    match tokio::fs::remove_file(path).await {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(AppError::InternalServerError(format!("Failed to delete recording {}: {}", path.display(), e))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::config::ConfigBuilder;
+    use chrono::Utc;
+
+    fn recording(path: &str) -> RequestRecording {
+        RequestRecording {
+            id: Uuid::new_v4(),
+            method: "GET".to_string(),
+            path: path.to_string(),
+            query: None,
+            tenant: "default".to_string(),
+            request_headers: serde_json::json!({}),
+            request_body: Some(String::new()),
+            request_body_bytes: Some(0),
+            status: 200,
+            response_headers: serde_json::json!({ "content-type": "application/json" }),
+            response_body: Some("{}".to_string()),
+            response_body_bytes: Some(2),
+            duration_ms: 3,
+            recorded_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_recorded_paths() {
+        let config = ConfigBuilder::new().database_url("postgresql://localhost/test").github_token("token").build().unwrap();
+        let settings = RecordingSettings { enabled: true, ..RecordingSettings::from_config(&config) };
+
+        assert!(settings.records("/api/fractals/mandelbrot"));
+        assert!(settings.records("/v1/api/github/repos"));
+        assert!(!settings.records("/api/performance/metrics"));
+        assert!(!settings.records("/health"));
+
+        let everything = RecordingSettings { paths: vec!["/".to_string()], ..settings.clone() };
+        assert!(!everything.records("/api/admin/recordings"));
+        assert!(!RecordingSettings { enabled: false, ..settings }.records("/api/fractals/mandelbrot"));
+    }
+
+    #[tokio::test]
+    async fn test_disk_store_keeps_the_newest() {
+        let directory = std::env::temp_dir().join(format!("recordings-{}", Uuid::new_v4()));
+        let pool = sqlx::postgres::PgPoolOptions::new().connect_lazy("postgresql://localhost/test").unwrap();
+        let service = RecordingService::new(pool, RecordingSettings {
+            enabled: true,
+            store: RecordingStore::Disk,
+            directory: directory.clone(),
+            paths: vec!["/api".to_string()],
+            max_body_bytes: 1024,
+            max_entries: 2,
+        });
+
+        let recordings: Vec<_> = ["/api/fractals/mandelbrot", "/api/fractals/julia", "/api/github/repos"]
+            .into_iter()
+            .enumerate()
+            .map(|(i, path)| RequestRecording { recorded_at: Utc::now() + chrono::Duration::seconds(i as i64), ..recording(path) })
+            .collect();
+        for recording in &recordings {
+            service.write_file(recording).await.unwrap();
+        }
+
+        let listed = service.list(&RecordingQuery::default()).await.unwrap();
+        assert_eq!(listed.iter().map(|r| r.path.as_str()).collect::<Vec<_>>(), ["/api/github/repos", "/api/fractals/julia"]);
+        assert_eq!(service.get(recordings[1].id).await.unwrap().response_body.as_deref(), Some("{}"));
+        assert!(matches!(service.get(recordings[0].id).await, Err(AppError::NotFoundError(_))));
+
+        let filtered = service.list(&RecordingQuery { limit: None, path: Some("/api/fractals".to_string()) }).await.unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(service.clear().await.unwrap(), 2);
+
+        let _ = std::fs::remove_dir_all(directory);
+    }
+}
@@ -0,0 +1,185 @@
+/*
+ * Per-session render history: what each anonymous visitor rendered, so a returning visitor can pick up where they left off.
+ * I'm trimming each session to its newest entries on write, so a busy visitor's history stays bounded between retention sweeps.
+ */
+
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::{
+    database::DatabasePool,
+    models::sessions::SessionHistoryEntry,
+    services::{
+        fractal_service::{FractalRequest, FractalResponse},
+        image_service::StoredImage,
+    },
+    utils::error::Result,
+};
+
+#[derive(Debug, Clone)]
+pub struct SessionService {
+    db_pool: DatabasePool,
+    history_limit: u32,
+}
+
+impl SessionService {
+    pub fn new(db_pool: DatabasePool, history_limit: u32) -> Self {
+        Self { db_pool, history_limit }
+    }
+
+    /// Most entries kept per session
+    pub fn history_limit(&self) -> u32 {
+        self.history_limit
+    }
+
+    /// Record a render, creating the session under `tenant_id` on its first one
+    /// I'm skipping the entry when the id already belongs to another tenant, so a session can't write across tenants
+    pub async fn record(
+        &self,
+        tenant_id: &str,
+        session_id: Uuid,
+        request: &FractalRequest,
+        response: &FractalResponse,
+        parameters: &serde_json::Value,
+        image: Option<&StoredImage>,
+    ) -> Result<()> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let owned: Option<Uuid> = sqlx::query_scalar(
+            "INSERT INTO sessions (id, tenant_id) VALUES ($1, $2)
+             ON CONFLICT (id) DO UPDATE SET last_seen_at = NOW() WHERE sessions.tenant_id = EXCLUDED.tenant_id
+             RETURNING id"
+        )
+        .bind(session_id)
+        .bind(tenant_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if owned.is_none() {
+            warn!("Session {} belongs to another tenant than {}; not recording", session_id, tenant_id);
+            return Ok(());
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO session_history (
+                id, session_id, fractal_type, parameters, width, height, zoom_level,
+                computation_time_ms, renderer, image_id, image_url)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(session_id)
+        .bind(request.fractal_type.name())
+        .bind(replay_parameters(request, parameters))
+        .bind(response.width as i32)
+        .bind(response.height as i32)
+        .bind(response.zoom_level)
+        .bind(response.computation_time_ms as i64)
+        .bind(response.renderer)
+        .bind(image.map(|i| i.id.as_str()))
+        .bind(image.map(|i| i.url.as_str()))
+        .execute(&mut *tx)
+        .await?;
+
+        let trimmed = sqlx::query(
+            "DELETE FROM session_history WHERE session_id = $1 AND id NOT IN (
+                SELECT id FROM session_history WHERE session_id = $1 ORDER BY created_at DESC LIMIT $2)"
+        )
+        .bind(session_id)
+        .bind(self.history_limit as i64)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        tx.commit().await?;
+        if trimmed > 0 {
+            debug!("Trimmed {} old history entries from session {}", trimmed, session_id);
+        }
+        Ok(())
+    }
+
+    /// A session's renders under `tenant_id`, newest first
+    pub async fn history(
+        &self,
+        tenant_id: &str,
+        session_id: Uuid,
+        limit: i64,
+        fractal_type: Option<&str>,
+    ) -> Result<Vec<SessionHistoryEntry>> {
+        let entries = sqlx::query_as::<_, SessionHistoryEntry>(
+            "SELECT h.id, h.fractal_type, h.parameters, h.width, h.height, h.zoom_level, h.computation_time_ms,
+                    h.renderer, h.image_id, h.image_url, h.created_at
+             FROM session_history h
+             JOIN sessions s ON s.id = h.session_id AND s.tenant_id = $4
+             WHERE h.session_id = $1 AND ($2::TEXT IS NULL OR h.fractal_type = $2)
+             ORDER BY h.created_at DESC
+             LIMIT $3"
+        )
+        .bind(session_id)
+        .bind(fractal_type)
+        .bind(limit)
+        .bind(tenant_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(entries)
+    }
+
+    /// Forget a session and everything it rendered; returns how many entries were removed
+    pub async fn clear(&self, tenant_id: &str, session_id: Uuid) -> Result<u64> {
+        let mut tx = self.db_pool.begin().await?;
+        let deleted = sqlx::query(
+            "DELETE FROM session_history
+             WHERE session_id = $1 AND EXISTS (SELECT 1 FROM sessions WHERE id = $1 AND tenant_id = $2)"
+        )
+        .bind(session_id)
+        .bind(tenant_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        sqlx::query("DELETE FROM sessions WHERE id = $1 AND tenant_id = $2")
+            .bind(session_id)
+            .bind(tenant_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(deleted)
+    }
+}
+
+/// The endpoint's echoed parameters with size and zoom folded in, so an entry alone can request the same render
+pub fn replay_parameters(request: &FractalRequest, parameters: &serde_json::Value) -> serde_json::Value {
+    let mut replay = match parameters {
+        serde_json::Value::Object(map) => map.clone(),
+        _ => serde_json::Map::new(),
+    };
+    replay.insert("width".to_string(), request.width.into());
+    replay.insert("height".to_string(), request.height.into());
+    replay.insert("zoom".to_string(), request.zoom.into());
+    serde_json::Value::Object(replay)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::fractal_service::FractalType;
+
+    #[test]
+    fn test_replay_parameters_include_size_and_zoom() {
+        let request = FractalRequest {
+            center_x: 0.0,
+            center_y: 0.0,
+            zoom: 8.0,
+            max_iterations: 200,
+            ..FractalRequest::new(FractalType::Julia { c_real: -0.7, c_imag: 0.27015 }, 640, 480)
+        };
+        let echoed = serde_json::json!({ "fractal_type": "julia", "c_real": -0.7, "max_iterations": 200 });
+
+        let replay = replay_parameters(&request, &echoed);
+        assert_eq!(replay["width"], 640);
+        assert_eq!(replay["height"], 480);
+        assert_eq!(replay["zoom"], 8.0);
+        assert_eq!(replay["c_real"], -0.7);
+        assert_eq!(replay["max_iterations"], 200);
+    }
+}
@@ -0,0 +1,212 @@
+/*
+ * Runtime settings persisted in Postgres and layered over environment values through LiveConfig.
+ * I'm treating the database as the source of truth: every instance rebuilds its overrides from the table instead of trusting notification payloads.
+ */
+
+use sqlx::Row;
+use std::collections::BTreeMap;
+use tracing::{info, warn};
+
+use crate::{
+    database::{timing, DatabasePool},
+    models::settings::{RuntimeSetting, SettingChange, SettingHistoryQuery},
+    utils::{
+        error::{AppError, Result},
+        live_config::{tunable_value, LiveConfig, TUNABLE_SETTINGS},
+    },
+};
+
+#[derive(Debug, Clone)]
+pub struct SettingsService {
+    db_pool: DatabasePool,
+    live_config: LiveConfig,
+}
+
+struct StoredSetting {
+    value: serde_json::Value,
+    updated_by: String,
+    updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl SettingsService {
+    pub fn new(db_pool: DatabasePool, live_config: LiveConfig) -> Self {
+        Self { db_pool, live_config }
+    }
+
+    /// Reload every override from the database and publish the result
+    /// I'm skipping rows that no longer name a tunable so a setting removed in a newer release can't block startup
+    pub async fn refresh(&self) -> Result<Vec<&'static str>> {
+        let overrides = self
+            .stored()
+            .await?
+            .into_iter()
+            .filter(|(key, stored)| match self.live_config.check_override(key, &stored.value) {
+                Ok(()) => true,
+                Err(e) => {
+                    warn!("Ignoring stored runtime setting {}: {}", key, e);
+                    false
+                }
+            })
+            .map(|(key, stored)| (key, stored.value))
+            .collect();
+
+        self.live_config.set_overrides(overrides)
+    }
+
+    /// Every tunable with its effective value and where it came from
+    pub async fn list(&self) -> Result<Vec<RuntimeSetting>> {
+        let stored = self.stored().await?;
+        let current = self.live_config.load();
+        let base = self.live_config.base();
+
+        Ok(TUNABLE_SETTINGS
+            .iter()
+            .map(|key| {
+                let row = stored.get(*key);
+                RuntimeSetting {
+                    key: key.to_string(),
+                    value: tunable_value(&current, key).unwrap_or_default(),
+                    default_value: tunable_value(&base, key).unwrap_or_default(),
+                    overridden: row.is_some(),
+                    updated_by: row.map(|r| r.updated_by.clone()),
+                    updated_at: row.map(|r| r.updated_at),
+                }
+            })
+            .collect())
+    }
+
+    pub async fn get(&self, key: &str) -> Result<RuntimeSetting> {
+        self.list()
+            .await?
+            .into_iter()
+            .find(|setting| setting.key == key)
+            .ok_or_else(|| AppError::NotFoundError(format!("Unknown runtime setting {}", key)))
+    }
+
+    /// Override a setting and record the change
+    pub async fn set(&self, key: &str, value: serde_json::Value, changed_by: &str, reason: Option<&str>) -> Result<RuntimeSetting> {
+        self.ensure_tunable(key)?;
+        self.live_config.check_override(key, &value)?;
+
+        let mut tx = self.db_pool.begin().await?;
+        let old_value = Self::lock_current(&mut tx, key).await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO runtime_settings (key, value, updated_by, updated_at)
+            VALUES ($1, $2, $3, NOW())
+            ON CONFLICT (key) DO UPDATE SET value = EXCLUDED.value, updated_by = EXCLUDED.updated_by, updated_at = NOW()
+            "#
+        )
+        .bind(key)
+        .bind(&value)
+        .bind(changed_by)
+        .execute(&mut *tx)
+        .await?;
+
+        Self::record_change(&mut tx, key, old_value, Some(value), changed_by, reason).await?;
+        tx.commit().await?;
+
+        info!("Runtime setting {} overridden by {}", key, changed_by);
+        self.refresh().await?;
+        self.get(key).await
+    }
+
+    /// Drop an override so the environment value applies again
+    pub async fn reset(&self, key: &str, changed_by: &str, reason: Option<&str>) -> Result<RuntimeSetting> {
+        self.ensure_tunable(key)?;
+
+        let mut tx = self.db_pool.begin().await?;
+        let old_value = Self::lock_current(&mut tx, key).await?;
+
+        if old_value.is_some() {
+            sqlx::query("DELETE FROM runtime_settings WHERE key = $1")
+                .bind(key)
+                .execute(&mut *tx)
+                .await?;
+            Self::record_change(&mut tx, key, old_value, None, changed_by, reason).await?;
+            info!("Runtime setting {} reset by {}", key, changed_by);
+        }
+        tx.commit().await?;
+
+        self.refresh().await?;
+        self.get(key).await
+    }
+
+    /// Recorded changes, newest first
+    pub async fn history(&self, query: &SettingHistoryQuery) -> Result<Vec<SettingChange>> {
+        let sql = sqlx::query_as::<_, SettingChange>(
+            r#"
+            SELECT id, key, old_value, new_value, changed_by, reason, changed_at
+            FROM runtime_setting_changes
+            WHERE $1::TEXT IS NULL OR key = $1
+            ORDER BY changed_at DESC, id DESC
+            LIMIT $2
+            "#
+        )
+        .bind(query.key.as_deref())
+        .bind(query.limit());
+
+        timing::timed("runtime_setting_history", sql.fetch_all(&self.db_pool))
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to load setting history: {}", e)))
+    }
+
+    fn ensure_tunable(&self, key: &str) -> Result<()> {
+        if TUNABLE_SETTINGS.contains(&key) {
+            Ok(())
+        } else {
+            Err(AppError::NotFoundError(format!("Unknown runtime setting {}", key)))
+        }
+    }
+
+    async fn stored(&self) -> Result<BTreeMap<String, StoredSetting>> {
+        let query = sqlx::query("SELECT key, value, updated_by, updated_at FROM runtime_settings");
+        let rows = timing::timed("runtime_settings_load", query.fetch_all(&self.db_pool))
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to load runtime settings: {}", e)))?;
+
+        rows.iter()
+            .map(|row| {
+                Ok((
+                    row.try_get("key")?,
+                    StoredSetting {
+                        value: row.try_get("value")?,
+                        updated_by: row.try_get("updated_by")?,
+                        updated_at: row.try_get("updated_at")?,
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    /// Current override for `key`, locked so concurrent edits record an accurate old value
+    async fn lock_current(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, key: &str) -> Result<Option<serde_json::Value>> {
+        Ok(sqlx::query_scalar("SELECT value FROM runtime_settings WHERE key = $1 FOR UPDATE")
+            .bind(key)
+            .fetch_optional(&mut **tx)
+            .await?)
+    }
+
+    /// Append to the change history, which feature flags share under their own key prefix
+    pub(crate) async fn record_change(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        key: &str,
+        old_value: Option<serde_json::Value>,
+        new_value: Option<serde_json::Value>,
+        changed_by: &str,
+        reason: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO runtime_setting_changes (key, old_value, new_value, changed_by, reason) VALUES ($1, $2, $3, $4, $5)"
+        )
+        .bind(key)
+        .bind(old_value)
+        .bind(new_value)
+        .bind(changed_by)
+        .bind(reason)
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,266 @@
+/*
+ * Service level objective tracking computed from the audit log, reporting compliance, burn rates, and remaining error budget.
+ * I'm weighting sampled successes back up by the audit sample rate, since failures are always recorded and would otherwise look far more common than they are.
+ */
+
+use serde::Serialize;
+use sqlx::Row;
+use std::collections::BTreeMap;
+
+use crate::{
+    database::{timing, DatabasePool},
+    utils::{
+        config::Config,
+        error::{AppError, Result},
+    },
+};
+
+/// Short windows the burn rate is reported over, alongside the full SLO window
+const BURN_RATE_WINDOWS: [(&str, i64); 3] = [("1h", 3600), ("6h", 6 * 3600), ("24h", 24 * 3600)];
+
+/// Burn rates that spend 2% of a 30-day budget in an hour or 5% in six hours, the usual fast-burn paging thresholds
+const FAST_BURN_1H: f64 = 14.4;
+const FAST_BURN_6H: f64 = 6.0;
+
+/// Objectives and audit sampling, snapshotted from Config
+#[derive(Debug, Clone)]
+pub struct SloSettings {
+    pub availability_target: f64,
+    pub latency_target: f64,
+    pub latency_threshold_ms: u64,
+    pub window_days: u32,
+    pub audit_enabled: bool,
+    pub audit_sample_rate: f64,
+}
+
+impl SloSettings {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            availability_target: config.slo_availability_target,
+            latency_target: config.slo_latency_target,
+            latency_threshold_ms: config.slo_latency_threshold_ms,
+            window_days: config.slo_window_days,
+            audit_enabled: config.audit_log_enabled,
+            audit_sample_rate: config.audit_log_sample_rate,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SloHealth {
+    Healthy,
+    /// Burning fast enough to exhaust the budget well before the window ends
+    AtRisk,
+    Exhausted,
+}
+
+/// One objective over the full window
+#[derive(Debug, Clone, Serialize)]
+pub struct SloStatus {
+    pub name: String,
+    pub description: String,
+    pub target: f64,
+    /// Estimated event counts, scaled up for audit sampling
+    pub total_events: f64,
+    pub bad_events: f64,
+    pub compliance: f64,
+    /// Share of the window's error budget left; negative once overspent
+    pub error_budget_remaining: f64,
+    /// Observed error rate divided by the allowed one; 1.0 spends the budget exactly over the window
+    pub burn_rates: BTreeMap<String, f64>,
+    pub health: SloHealth,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SloReport {
+    pub window_days: u32,
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+    pub audit_sample_rate: f64,
+    pub objectives: Vec<SloStatus>,
+}
+
+/// Event counts for one time window
+#[derive(Debug, Clone, Copy, Default)]
+struct WindowCounts {
+    requests: f64,
+    failed: f64,
+    gets: f64,
+    slow_gets: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SloService {
+    db_pool: DatabasePool,
+    settings: SloSettings,
+}
+
+impl SloService {
+    pub fn new(db_pool: DatabasePool, settings: SloSettings) -> Self {
+        Self { db_pool, settings }
+    }
+
+    /// Current standing against every objective
+    pub async fn report(&self) -> Result<SloReport> {
+        if !self.settings.audit_enabled || self.settings.audit_sample_rate <= 0.0 {
+            return Err(AppError::ServiceUnavailableError(
+                "SLO tracking reads the audit log; enable AUDIT_LOG_ENABLED with a non-zero AUDIT_LOG_SAMPLE_RATE".to_string(),
+            ));
+        }
+
+        let window_seconds = i64::from(self.settings.window_days) * 86_400;
+        let full = self.counts(window_seconds).await?;
+        let mut short = Vec::with_capacity(BURN_RATE_WINDOWS.len());
+        for (label, seconds) in BURN_RATE_WINDOWS {
+            short.push((label, self.counts(seconds).await?));
+        }
+
+        let availability = objective(
+            "availability",
+            "Requests that did not fail with a server error".to_string(),
+            self.settings.availability_target,
+            |c| (c.requests, c.failed),
+            full,
+            &short,
+        );
+        let latency = objective(
+            "latency",
+            format!("GET requests answered within {}ms", self.settings.latency_threshold_ms),
+            self.settings.latency_target,
+            |c| (c.gets, c.slow_gets),
+            full,
+            &short,
+        );
+
+        Ok(SloReport {
+            window_days: self.settings.window_days,
+            generated_at: chrono::Utc::now(),
+            audit_sample_rate: self.settings.audit_sample_rate,
+            objectives: vec![availability, latency],
+        })
+    }
+
+    /// Counts over the trailing `seconds`, with each sampled success standing in for 1 / sample_rate requests
+    async fn counts(&self, seconds: i64) -> Result<WindowCounts> {
+        let success_weight = 1.0 / self.settings.audit_sample_rate.min(1.0);
+        let query = sqlx::query(
+            r#"
+            SELECT
+                COALESCE(SUM(CASE WHEN status_code >= 400 THEN 1 ELSE $2 END), 0)::FLOAT8 AS requests,
+                COUNT(*) FILTER (WHERE status_code >= 500)::FLOAT8 AS failed,
+                COALESCE(SUM(CASE WHEN status_code >= 400 THEN 1 ELSE $2 END) FILTER (WHERE method = 'GET'), 0)::FLOAT8 AS gets,
+                COALESCE(SUM(CASE WHEN status_code >= 400 THEN 1 ELSE $2 END) FILTER (WHERE method = 'GET' AND latency_ms > $3), 0)::FLOAT8 AS slow_gets
+            FROM audit_logs
+            WHERE entity_type = 'http_request'
+              AND status_code IS NOT NULL
+              AND timestamp >= NOW() - make_interval(secs => $1)
+            "#,
+        )
+        .bind(seconds as f64)
+        .bind(success_weight)
+        .bind(self.settings.latency_threshold_ms as i64);
+
+        let row = timing::timed("slo_window_counts", query.fetch_one(&self.db_pool))
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to count requests for SLOs: {}", e)))?;
+
+        Ok(WindowCounts {
+            requests: row.try_get("requests")?,
+            failed: row.try_get("failed")?,
+            gets: row.try_get("gets")?,
+            slow_gets: row.try_get("slow_gets")?,
+        })
+    }
+}
+
+fn objective(
+    name: &str,
+    description: String,
+    target: f64,
+    select: impl Fn(&WindowCounts) -> (f64, f64),
+    full: WindowCounts,
+    short: &[(&str, WindowCounts)],
+) -> SloStatus {
+    let (total, bad) = select(&full);
+    let mut burn_rates: BTreeMap<String, f64> = short
+        .iter()
+        .map(|(label, counts)| {
+            let (total, bad) = select(counts);
+            (label.to_string(), burn_rate(total, bad, target))
+        })
+        .collect();
+    burn_rates.insert("window".to_string(), burn_rate(total, bad, target));
+
+    let error_budget_remaining = budget_remaining(total, bad, target);
+    let health = if error_budget_remaining <= 0.0 {
+        SloHealth::Exhausted
+    } else if burn_rates["1h"] >= FAST_BURN_1H || burn_rates["6h"] >= FAST_BURN_6H {
+        SloHealth::AtRisk
+    } else {
+        SloHealth::Healthy
+    };
+
+    SloStatus {
+        name: name.to_string(),
+        description,
+        target,
+        total_events: total,
+        bad_events: bad,
+        compliance: if total > 0.0 { 1.0 - bad / total } else { 1.0 },
+        error_budget_remaining,
+        burn_rates,
+        health,
+    }
+}
+
+/// How many times faster than allowed the budget is being spent
+fn burn_rate(total: f64, bad: f64, target: f64) -> f64 {
+    if total <= 0.0 {
+        return 0.0;
+    }
+    (bad / total) / (1.0 - target)
+}
+
+/// Share of the budget left, where the budget is the bad events the target allows over the window
+fn budget_remaining(total: f64, bad: f64, target: f64) -> f64 {
+    let allowed = total * (1.0 - target);
+    if allowed <= 0.0 {
+        return 1.0;
+    }
+    1.0 - bad / allowed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(requests: f64, failed: f64) -> WindowCounts {
+        WindowCounts { requests, failed, ..Default::default() }
+    }
+
+    #[test]
+    fn test_budget_and_burn_rate() {
+        // 99.9% over 100k requests allows 100 failures; 25 spent leaves three quarters
+        assert!((budget_remaining(100_000.0, 25.0, 0.999) - 0.75).abs() < 1e-9);
+        assert!((burn_rate(100_000.0, 100.0, 0.999) - 1.0).abs() < 1e-9);
+        assert_eq!(burn_rate(0.0, 0.0, 0.999), 0.0);
+        assert_eq!(budget_remaining(0.0, 0.0, 0.999), 1.0);
+    }
+
+    #[test]
+    fn test_health_reflects_fast_burn_and_exhaustion() {
+        let availability = |c: &WindowCounts| (c.requests, c.failed);
+        let quiet = [("1h", counts(1000.0, 0.0)), ("6h", counts(6000.0, 0.0)), ("24h", counts(24000.0, 0.0))];
+        let status = objective("availability", String::new(), 0.999, availability, counts(720_000.0, 10.0), &quiet);
+        assert_eq!(status.health, SloHealth::Healthy);
+
+        let burning = [("1h", counts(1000.0, 20.0)), ("6h", counts(6000.0, 20.0)), ("24h", counts(24000.0, 20.0))];
+        let status = objective("availability", String::new(), 0.999, availability, counts(720_000.0, 30.0), &burning);
+        assert_eq!(status.health, SloHealth::AtRisk);
+        assert!((status.burn_rates["1h"] - 20.0).abs() < 1e-9);
+
+        let status = objective("availability", String::new(), 0.999, availability, counts(720_000.0, 800.0), &burning);
+        assert_eq!(status.health, SloHealth::Exhausted);
+        assert!(status.error_budget_remaining < 0.0);
+    }
+}
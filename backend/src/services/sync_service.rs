@@ -0,0 +1,169 @@
+/*
+ * GitHub repository sync pipeline: fetch from the API, upsert into Postgres, invalidate the Redis copies, and emit sync.completed.
+ * I'm routing stale reads, the scheduled job, warm-up, and admin commands through this one path so repository data is refreshed the same way whoever asks.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::{
+    database::{repositories, DatabasePool},
+    models::{github::Repository, webhooks::WebhookEvent},
+    services::{github_service::GitHubService, webhook_service::WebhookService},
+    utils::error::Result,
+};
+
+/// What asked for a sync; carried into logs and the sync.completed payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncTrigger {
+    /// A request found the stored repositories expired
+    Read,
+    Scheduled,
+    WarmUp,
+    Admin,
+}
+
+impl SyncTrigger {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SyncTrigger::Read => "read",
+            SyncTrigger::Scheduled => "scheduled",
+            SyncTrigger::WarmUp => "warm_up",
+            SyncTrigger::Admin => "admin",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncReport {
+    pub username: String,
+    pub trigger: SyncTrigger,
+    /// Repositories returned by GitHub
+    pub fetched: usize,
+    /// Rows written; lower than `fetched` when a batch failed to upsert
+    pub stored: usize,
+    /// Cached repository detail views that were dropped
+    pub caches_invalidated: usize,
+    pub duration_ms: u64,
+    pub completed_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct SyncOutcome {
+    pub report: SyncReport,
+    /// The fetched repositories, for callers that must serve them when storing fell short
+    pub repositories: Vec<Repository>,
+}
+
+impl SyncOutcome {
+    pub fn fully_stored(&self) -> bool {
+        self.report.stored == self.report.fetched
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SyncService {
+    github_service: GitHubService,
+    webhook_service: WebhookService,
+    db_pool: DatabasePool,
+    /// Syncs run one at a time, so readers that all find the data stale share one GitHub fetch
+    running: Arc<Mutex<()>>,
+}
+
+impl SyncService {
+    pub fn new(github_service: GitHubService, webhook_service: WebhookService, db_pool: DatabasePool) -> Self {
+        Self {
+            github_service,
+            webhook_service,
+            db_pool,
+            running: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Refresh a user's repositories from GitHub regardless of how fresh the stored copy is
+    pub async fn sync(&self, username: &str, trigger: SyncTrigger) -> Result<SyncOutcome> {
+        let _running = self.running.lock().await;
+        self.run(username, trigger).await
+    }
+
+    /// Refresh only when no stored repository is still fresh; None when another sync already did the work
+    pub async fn sync_if_stale(&self, username: &str, trigger: SyncTrigger) -> Result<Option<SyncOutcome>> {
+        let _running = self.running.lock().await;
+        if repositories::count_fresh(&self.db_pool, username).await.unwrap_or(0) > 0 {
+            return Ok(None);
+        }
+        self.run(username, trigger).await.map(Some)
+    }
+
+    async fn run(&self, username: &str, trigger: SyncTrigger) -> Result<SyncOutcome> {
+        let start = Instant::now();
+        info!("Syncing repositories for {} ({})", username, trigger.as_str());
+
+        let repositories = self.github_service.fetch_user_repositories(username).await?;
+        let stored = self.github_service.store_repositories_in_db(&self.db_pool, &repositories).await?;
+
+        let mut caches_invalidated = 0;
+        for repo in &repositories {
+            match self.github_service.invalidate_repository_cache(&repo.owner_login, &repo.name).await {
+                Ok(true) => caches_invalidated += 1,
+                Ok(false) => {}
+                Err(e) => warn!("Failed to invalidate cache for {}: {}", repo.full_name, e),
+            }
+        }
+        self.github_service.cache_user_repositories(username, &repositories).await;
+
+        let report = SyncReport {
+            username: username.to_string(),
+            trigger,
+            fetched: repositories.len(),
+            stored,
+            caches_invalidated,
+            duration_ms: start.elapsed().as_millis() as u64,
+            completed_at: Utc::now(),
+        };
+
+        self.webhook_service.emit(WebhookEvent::SyncCompleted, serde_json::json!({
+            "username": report.username,
+            "repository_count": report.fetched,
+            "stored_count": report.stored,
+            "trigger": report.trigger,
+        }));
+        info!(
+            "Synced {} of {} repositories for {} in {}ms",
+            report.stored, report.fetched, username, report.duration_ms
+        );
+
+        Ok(SyncOutcome { report, repositories })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_serializes_trigger_as_its_name() {
+        let outcome = SyncOutcome {
+            report: SyncReport {
+                username: "octocat".to_string(),
+                trigger: SyncTrigger::WarmUp,
+                fetched: 3,
+                stored: 2,
+                caches_invalidated: 1,
+                duration_ms: 12,
+                completed_at: Utc::now(),
+            },
+            repositories: Vec::new(),
+        };
+        assert!(!outcome.fully_stored());
+
+        let json = serde_json::to_value(&outcome.report).unwrap();
+        assert_eq!(json["trigger"], SyncTrigger::WarmUp.as_str());
+        assert_eq!(json["stored"], 2);
+    }
+}
@@ -0,0 +1,328 @@
+/*
+ * Tenant storage and resolution, serving every lookup from an in-memory directory of the tenants table.
+ * I'm refreshing the directory on startup, after each edit, and whenever another instance announces a change, so resolving a request never touches the database.
+ */
+
+use arc_swap::ArcSwap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::{
+    database::{timing, DatabasePool},
+    models::tenants::{Tenant, TenantInfo, TenantUpdate, DEFAULT_TENANT_SLUG, TENANT_PATH_PREFIX},
+    services::cache_service::CacheService,
+    utils::{
+        config::Config,
+        error::{AppError, Result},
+        live_config::LiveConfig,
+    },
+};
+
+const TENANT_COLUMNS: &str = "slug, display_name, github_username, hostnames, settings, enabled, updated_by, created_at, updated_at";
+
+/// One tenant as request handling sees it
+#[derive(Debug, Clone)]
+pub struct TenantContext {
+    pub slug: String,
+    pub display_name: String,
+    pub github_username: String,
+    pub enabled: bool,
+    overrides: BTreeMap<String, serde_json::Value>,
+    /// Cache view under `tenant:<slug>:`; the default tenant keeps the unprefixed keys it always used
+    pub cache: CacheService,
+}
+
+impl TenantContext {
+    pub fn is_default(&self) -> bool {
+        self.slug == DEFAULT_TENANT_SLUG
+    }
+
+    /// The live configuration with this tenant's overrides applied
+    /// I'm falling back to the shared values if a later deployment change makes the stored overrides invalid
+    pub fn config(&self, live_config: &LiveConfig) -> Arc<Config> {
+        live_config.load_with(&self.overrides).unwrap_or_else(|e| {
+            warn!("Ignoring settings for tenant {}: {}", self.slug, e);
+            live_config.load()
+        })
+    }
+
+    /// Prefix that addresses this tenant whatever the host, for links handed back to clients; empty for the default tenant
+    pub fn path_prefix(&self) -> String {
+        if self.is_default() {
+            String::new()
+        } else {
+            format!("{}{}", TENANT_PATH_PREFIX, self.slug)
+        }
+    }
+
+    /// Namespace a shared key, such as a rate limit bucket, so tenants never count against each other
+    pub fn scope_key(&self, key: &str) -> String {
+        if self.is_default() {
+            key.to_string()
+        } else {
+            format!("tenant:{}:{}", self.slug, key)
+        }
+    }
+
+    /// Refuse repositories outside this tenant's showcase; the default tenant may read any owner, as before tenancy
+    pub fn ensure_owner(&self, owner: &str) -> Result<()> {
+        if self.is_default() || owner.eq_ignore_ascii_case(&self.github_username) {
+            Ok(())
+        } else {
+            Err(AppError::NotFoundError(format!("Repositories of {} not found", owner)))
+        }
+    }
+
+    pub fn info(&self) -> TenantInfo {
+        TenantInfo {
+            slug: self.slug.clone(),
+            display_name: self.display_name.clone(),
+            github_username: self.github_username.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct TenantDirectory {
+    tenants: Vec<Tenant>,
+    by_slug: HashMap<String, Arc<TenantContext>>,
+    by_host: HashMap<String, Arc<TenantContext>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TenantService {
+    db_pool: DatabasePool,
+    live_config: LiveConfig,
+    cache_service: CacheService,
+    default: Arc<TenantContext>,
+    directory: Arc<ArcSwap<TenantDirectory>>,
+}
+
+impl TenantService {
+    pub fn new(db_pool: DatabasePool, live_config: LiveConfig, cache_service: CacheService, default_username: &str) -> Self {
+        let default = Arc::new(TenantContext {
+            slug: DEFAULT_TENANT_SLUG.to_string(),
+            display_name: default_username.to_string(),
+            github_username: default_username.to_string(),
+            enabled: true,
+            overrides: BTreeMap::new(),
+            cache: cache_service.clone(),
+        });
+
+        Self {
+            db_pool,
+            live_config,
+            cache_service,
+            default,
+            directory: Arc::new(ArcSwap::from_pointee(TenantDirectory::default())),
+        }
+    }
+
+    /// The tenant built from GITHUB_USERNAME, serving every request no other tenant claims
+    pub fn default_tenant(&self) -> Arc<TenantContext> {
+        self.default.clone()
+    }
+
+    pub fn by_slug(&self, slug: &str) -> Option<Arc<TenantContext>> {
+        if slug == DEFAULT_TENANT_SLUG {
+            return Some(self.default.clone());
+        }
+        self.directory.load().by_slug.get(slug).cloned()
+    }
+
+    /// The tenant claiming an already-normalised hostname
+    pub fn by_host(&self, host: &str) -> Option<Arc<TenantContext>> {
+        self.directory.load().by_host.get(host).cloned()
+    }
+
+    pub fn list(&self) -> Vec<Tenant> {
+        self.directory.load().tenants.clone()
+    }
+
+    /// Every tenant that may have cached `owner`'s repositories: those showcasing it, plus the default, which reads any owner
+    pub fn for_owner(&self, owner: &str) -> Vec<Arc<TenantContext>> {
+        let directory = self.directory.load();
+        std::iter::once(self.default.clone())
+            .chain(directory.by_slug.values().filter(|tenant| tenant.github_username.eq_ignore_ascii_case(owner)).cloned())
+            .collect()
+    }
+
+    /// GitHub accounts to keep synced: the default one and every enabled tenant's, without repeats
+    pub fn github_owners(&self) -> Vec<String> {
+        let directory = self.directory.load();
+        let mut owners: Vec<String> = Vec::new();
+        for tenant in std::iter::once(&self.default).chain(directory.by_slug.values()) {
+            if tenant.enabled && !owners.iter().any(|owner| owner.eq_ignore_ascii_case(&tenant.github_username)) {
+                owners.push(tenant.github_username.clone());
+            }
+        }
+        owners
+    }
+
+    /// Reload the directory from the database
+    pub async fn refresh(&self) -> Result<usize> {
+        let sql = format!("SELECT {} FROM tenants ORDER BY slug", TENANT_COLUMNS);
+        let query = sqlx::query_as::<_, Tenant>(&sql);
+        let tenants = timing::timed("tenants_load", query.fetch_all(&self.db_pool))
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to load tenants: {}", e)))?;
+
+        let mut by_slug = HashMap::with_capacity(tenants.len());
+        let mut by_host = HashMap::new();
+        for tenant in &tenants {
+            let context = Arc::new(self.context(tenant));
+            for host in &tenant.hostnames {
+                by_host.insert(host.clone(), context.clone());
+            }
+            by_slug.insert(tenant.slug.clone(), context);
+        }
+
+        let count = tenants.len();
+        self.directory.store(Arc::new(TenantDirectory { tenants, by_slug, by_host }));
+        Ok(count)
+    }
+
+    /// Create or replace a tenant, returning its previous and new state
+    pub async fn upsert(&self, slug: &str, update: &TenantUpdate, changed_by: &str) -> Result<(Option<Tenant>, Tenant)> {
+        let hostnames = update.validate(slug)?;
+        self.live_config.load_with(&update.settings)?.validate()?;
+        let display_name = update.display_name.clone().unwrap_or_else(|| update.github_username.clone());
+        let settings = serde_json::to_value(&update.settings)?;
+
+        let mut tx = self.db_pool.begin().await?;
+        let before = Self::lock_current(&mut tx, slug).await?;
+
+        let claimed_by: Option<String> = sqlx::query_scalar(
+            "SELECT slug FROM tenants WHERE slug <> $1 AND hostnames && $2 LIMIT 1"
+        )
+        .bind(slug)
+        .bind(&hostnames)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some(other) = claimed_by {
+            return Err(AppError::ValidationError(format!("A hostname is already served by tenant {}", other)));
+        }
+
+        let sql = format!(
+            r#"
+            INSERT INTO tenants (slug, display_name, github_username, hostnames, settings, enabled, updated_by, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
+            ON CONFLICT (slug) DO UPDATE SET
+                display_name = EXCLUDED.display_name,
+                github_username = EXCLUDED.github_username,
+                hostnames = EXCLUDED.hostnames,
+                settings = EXCLUDED.settings,
+                enabled = EXCLUDED.enabled,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = NOW()
+            RETURNING {}
+            "#,
+            TENANT_COLUMNS
+        );
+        let tenant = sqlx::query_as::<_, Tenant>(&sql)
+            .bind(slug)
+            .bind(&display_name)
+            .bind(&update.github_username)
+            .bind(&hostnames)
+            .bind(&settings)
+            .bind(update.enabled)
+            .bind(changed_by)
+            .fetch_one(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        info!("Tenant {} ({}) updated by {}", slug, tenant.github_username, changed_by);
+        self.refresh().await?;
+        Ok((before, tenant))
+    }
+
+    /// Delete a tenant and drop everything it cached
+    /// I'm leaving its repository rows alone since another tenant or the default may showcase the same owner
+    pub async fn delete(&self, slug: &str) -> Result<Tenant> {
+        let sql = format!("DELETE FROM tenants WHERE slug = $1 RETURNING {}", TENANT_COLUMNS);
+        let tenant = sqlx::query_as::<_, Tenant>(&sql)
+            .bind(slug)
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(|| AppError::NotFoundError(format!("Tenant {} not found", slug)))?;
+
+        if let Err(e) = self.tenant_cache(slug).flush_prefix().await {
+            warn!("Failed to flush cache for deleted tenant {}: {}", slug, e);
+        }
+
+        info!("Tenant {} deleted", slug);
+        self.refresh().await?;
+        Ok(tenant)
+    }
+
+    fn tenant_cache(&self, slug: &str) -> CacheService {
+        self.cache_service.scoped(&format!("tenant:{}", slug))
+    }
+
+    fn context(&self, tenant: &Tenant) -> TenantContext {
+        TenantContext {
+            slug: tenant.slug.clone(),
+            display_name: tenant.display_name.clone(),
+            github_username: tenant.github_username.clone(),
+            enabled: tenant.enabled,
+            overrides: tenant.setting_overrides(),
+            cache: self.tenant_cache(&tenant.slug),
+        }
+    }
+
+    async fn lock_current(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, slug: &str) -> Result<Option<Tenant>> {
+        let sql = format!("SELECT {} FROM tenants WHERE slug = $1 FOR UPDATE", TENANT_COLUMNS);
+        Ok(sqlx::query_as::<_, Tenant>(&sql)
+            .bind(slug)
+            .fetch_optional(&mut **tx)
+            .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::config::ConfigBuilder;
+
+    fn tenant(slug: &str, overrides: BTreeMap<String, serde_json::Value>) -> TenantContext {
+        let cache = CacheService::new(redis::Client::open("redis://127.0.0.1:6379").unwrap());
+        TenantContext {
+            slug: slug.to_string(),
+            display_name: "Octo".to_string(),
+            github_username: "octocat".to_string(),
+            enabled: true,
+            overrides,
+            cache: cache.scoped(&format!("tenant:{}", slug)),
+        }
+    }
+
+    #[test]
+    fn test_tenants_are_isolated_from_each_other() {
+        let octo = tenant("octo", BTreeMap::new());
+        assert_eq!(octo.scope_key("api:ip:10.0.0.1"), "tenant:octo:api:ip:10.0.0.1");
+        assert_eq!(octo.path_prefix(), "/t/octo");
+        assert!(octo.ensure_owner("OctoCat").is_ok());
+        assert!(matches!(octo.ensure_owner("someone-else"), Err(AppError::NotFoundError(_))));
+
+        let default = tenant(DEFAULT_TENANT_SLUG, BTreeMap::new());
+        assert_eq!(default.scope_key("api:ip:10.0.0.1"), "api:ip:10.0.0.1");
+        assert_eq!(default.path_prefix(), "");
+        assert!(default.ensure_owner("someone-else").is_ok());
+    }
+
+    #[test]
+    fn test_overrides_apply_only_to_their_tenant() {
+        let live = LiveConfig::new(
+            ConfigBuilder::new()
+                .database_url("postgresql://localhost/test")
+                .github_token("token")
+                .build()
+                .unwrap(),
+        );
+        let strict = tenant("octo", BTreeMap::from([("rate_limit_requests_per_minute".to_string(), serde_json::json!(5))]));
+
+        assert_eq!(strict.config(&live).rate_limit_requests_per_minute, 5);
+        assert_eq!(tenant("other", BTreeMap::new()).config(&live).rate_limit_requests_per_minute, 100);
+    }
+}
@@ -0,0 +1,229 @@
+/*
+ * Per-API-key usage analytics tracking request volume, error rates, and latency in hourly Redis buckets.
+ * I'm keeping the counters in Redis with a bounded retention so the hot path is a single pipelined round trip.
+ */
+
+use redis::AsyncCommands;
+use serde::Serialize;
+use std::collections::HashMap;
+use tracing::warn;
+
+use crate::utils::correlation;
+use crate::utils::error::Result;
+use crate::utils::redis_connection::SharedRedisConnection;
+
+const KEY_PREFIX: &str = "perf_showcase:usage:";
+const BUCKET_SECONDS: i64 = 3600;
+
+/// How long hourly buckets are kept before Redis expires them
+pub const USAGE_RETENTION_HOURS: i64 = 24 * 7;
+
+/// Usage for one principal within a single hour
+#[derive(Debug, Clone, Serialize)]
+pub struct HourlyUsage {
+    pub hour: chrono::DateTime<chrono::Utc>,
+    pub requests: u64,
+    pub errors: u64,
+    pub avg_latency_ms: f64,
+}
+
+/// Usage for one principal across a window of hours
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageSummary {
+    pub key_id: String,
+    pub window_hours: i64,
+    pub requests: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    pub avg_latency_ms: f64,
+    pub hourly: Vec<HourlyUsage>,
+}
+
+#[derive(Clone)]
+pub struct UsageService {
+    redis: SharedRedisConnection,
+    enabled: bool,
+}
+
+impl std::fmt::Debug for UsageService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UsageService")
+            .field("enabled", &self.enabled)
+            .finish()
+    }
+}
+
+impl UsageService {
+    pub fn new(redis: SharedRedisConnection, enabled: bool) -> Self {
+        Self {
+            redis,
+            enabled,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Count a finished request against its principal without blocking the caller
+    pub fn record(&self, key_id: String, status_code: u16, latency_ms: u64) {
+        if !self.enabled {
+            return;
+        }
+
+        let service = self.clone();
+        correlation::spawn(async move {
+            if let Err(e) = service.increment(&key_id, status_code, latency_ms).await {
+                warn!("Failed to record usage for {}: {}", key_id, e);
+            }
+        });
+    }
+
+    async fn increment(&self, key_id: &str, status_code: u16, latency_ms: u64) -> Result<()> {
+        let bucket = current_bucket();
+        let usage_key = usage_key(key_id, bucket);
+        let index_key = index_key(bucket);
+        let ttl = USAGE_RETENTION_HOURS * BUCKET_SECONDS;
+
+        let mut conn = self.redis.get().await?;
+        redis::pipe()
+            .hincr(&usage_key, "requests", 1).ignore()
+            .hincr(&usage_key, "errors", i64::from(status_code >= 400)).ignore()
+            .hincr(&usage_key, "latency_ms", latency_ms).ignore()
+            .expire(&usage_key, ttl).ignore()
+            .zincr(&index_key, key_id, 1).ignore()
+            .expire(&index_key, ttl).ignore()
+            .query_async::<_, ()>(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Requests counted against a principal in the current hour, and the seconds until that count starts over
+    pub async fn current_hour(&self, key_id: &str) -> Result<(u64, u64)> {
+        let now = chrono::Utc::now().timestamp();
+        let mut conn = self.redis.get().await?;
+        let requests: Option<u64> = conn.hget(usage_key(key_id, now / BUCKET_SECONDS), "requests").await?;
+        Ok((requests.unwrap_or(0), seconds_until_next_bucket(now)))
+    }
+
+    /// Summarise one principal's usage over the trailing window
+    pub async fn usage_for_key(&self, key_id: &str, hours: i64) -> Result<UsageSummary> {
+        let hours = hours.clamp(1, USAGE_RETENTION_HOURS);
+        let newest = current_bucket();
+        let mut conn = self.redis.get().await?;
+
+        let mut hourly = Vec::with_capacity(hours as usize);
+        for bucket in (newest - hours + 1)..=newest {
+            let fields: HashMap<String, u64> = conn.hgetall(usage_key(key_id, bucket)).await?;
+            if fields.is_empty() {
+                continue;
+            }
+
+            let requests = fields.get("requests").copied().unwrap_or(0);
+            hourly.push(HourlyUsage {
+                hour: bucket_start(bucket),
+                requests,
+                errors: fields.get("errors").copied().unwrap_or(0),
+                avg_latency_ms: average(fields.get("latency_ms").copied().unwrap_or(0), requests),
+            });
+        }
+
+        Ok(summarise(key_id, hours, hourly))
+    }
+
+    /// The heaviest principals over the trailing window, busiest first
+    /// I'm summing the per-hour sorted sets client-side since the window is at most a week of buckets
+    pub async fn top_keys(&self, hours: i64, limit: usize) -> Result<Vec<UsageSummary>> {
+        let hours = hours.clamp(1, USAGE_RETENTION_HOURS);
+        let newest = current_bucket();
+        let mut conn = self.redis.get().await?;
+
+        let mut totals: HashMap<String, f64> = HashMap::new();
+        for bucket in (newest - hours + 1)..=newest {
+            let members: Vec<(String, f64)> = conn.zrange_withscores(index_key(bucket), 0, -1).await?;
+            for (key_id, count) in members {
+                *totals.entry(key_id).or_default() += count;
+            }
+        }
+
+        let mut ranked: Vec<(String, f64)> = totals.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranked.truncate(limit);
+
+        let mut summaries = Vec::with_capacity(ranked.len());
+        for (key_id, _) in ranked {
+            summaries.push(self.usage_for_key(&key_id, hours).await?);
+        }
+
+        Ok(summaries)
+    }
+}
+
+fn current_bucket() -> i64 {
+    chrono::Utc::now().timestamp() / BUCKET_SECONDS
+}
+
+fn seconds_until_next_bucket(now: i64) -> u64 {
+    (BUCKET_SECONDS - now.rem_euclid(BUCKET_SECONDS)) as u64
+}
+
+fn bucket_start(bucket: i64) -> chrono::DateTime<chrono::Utc> {
+    chrono::DateTime::from_timestamp(bucket * BUCKET_SECONDS, 0).unwrap_or_default()
+}
+
+fn usage_key(key_id: &str, bucket: i64) -> String {
+    format!("{}{}:{}", KEY_PREFIX, key_id, bucket)
+}
+
+fn index_key(bucket: i64) -> String {
+    format!("{}keys:{}", KEY_PREFIX, bucket)
+}
+
+fn average(total: u64, count: u64) -> f64 {
+    if count == 0 { 0.0 } else { total as f64 / count as f64 }
+}
+
+fn summarise(key_id: &str, window_hours: i64, hourly: Vec<HourlyUsage>) -> UsageSummary {
+    let requests: u64 = hourly.iter().map(|h| h.requests).sum();
+    let errors: u64 = hourly.iter().map(|h| h.errors).sum();
+    let latency_total: f64 = hourly.iter().map(|h| h.avg_latency_ms * h.requests as f64).sum();
+
+    UsageSummary {
+        key_id: key_id.to_string(),
+        window_hours,
+        requests,
+        errors,
+        error_rate: if requests == 0 { 0.0 } else { errors as f64 / requests as f64 },
+        avg_latency_ms: if requests == 0 { 0.0 } else { latency_total / requests as f64 },
+        hourly,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_weights_latency_by_volume() {
+        let hourly = vec![
+            HourlyUsage { hour: bucket_start(1), requests: 3, errors: 1, avg_latency_ms: 10.0 },
+            HourlyUsage { hour: bucket_start(2), requests: 1, errors: 0, avg_latency_ms: 50.0 },
+        ];
+
+        let summary = summarise("key:abc", 24, hourly);
+        assert_eq!(summary.requests, 4);
+        assert_eq!(summary.errors, 1);
+        assert!((summary.error_rate - 0.25).abs() < f64::EPSILON);
+        assert!((summary.avg_latency_ms - 20.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_bucket_keys() {
+        assert_eq!(usage_key("key:abc", 42), "perf_showcase:usage:key:abc:42");
+        assert_eq!(index_key(42), "perf_showcase:usage:keys:42");
+        assert_eq!(bucket_start(1).timestamp(), 3600);
+        assert_eq!(seconds_until_next_bucket(7200), 3600);
+        assert_eq!(seconds_until_next_bucket(7199), 1);
+    }
+}
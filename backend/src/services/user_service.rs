@@ -0,0 +1,337 @@
+/*
+ * User accounts and personal API keys, plus the per-user daily counter that meters expensive fractal renders.
+ * I'm charging a render before it runs with one conditional upsert, so concurrent requests can never push a user past their quota.
+ */
+
+use chrono::{Duration, NaiveDate, Utc};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    database::DatabasePool,
+    models::{
+        github::GitHubUser,
+        users::{
+            api_key_prefix, generate_api_key, validate_key_name, validate_username, ApiKey, IssuedApiKey,
+            RegisteredUser, RenderQuota, User, MAX_ACTIVE_KEYS_PER_USER,
+        },
+    },
+    utils::{
+        error::{AppError, Result},
+        Utils,
+    },
+};
+
+const USER_COLUMNS: &str = "id, username, email, github_login, render_quota_daily, created_at, updated_at";
+const KEY_COLUMNS: &str =
+    "id, name, prefix, 'key:' || substr(key_hash, 1, 16) AS principal, created_at, last_used_at, revoked_at";
+const DEFAULT_KEY_NAME: &str = "default";
+const GITHUB_KEY_NAME: &str = "github";
+
+#[derive(Debug, Clone)]
+pub struct UserService {
+    db_pool: DatabasePool,
+    default_render_quota: u32,
+    expensive_render_pixels: u64,
+}
+
+impl UserService {
+    pub fn new(db_pool: DatabasePool, default_render_quota: u32, expensive_render_pixels: u64) -> Self {
+        Self {
+            db_pool,
+            default_render_quota,
+            expensive_render_pixels,
+        }
+    }
+
+    /// Whether a render of this size counts against the caller's quota
+    pub fn is_expensive(&self, pixels: u64) -> bool {
+        pixels >= self.expensive_render_pixels
+    }
+
+    /// Create an account under `tenant_id` and its first API key
+    pub async fn register(&self, tenant_id: &str, username: &str, email: Option<&str>) -> Result<RegisteredUser> {
+        validate_username(username)?;
+
+        let mut tx = self.db_pool.begin().await?;
+        let user = sqlx::query_as::<_, User>(&format!(
+            "INSERT INTO users (id, username, email, render_quota_daily, tenant_id)
+             VALUES ($1, $2, $3, $4, $5) RETURNING {}",
+            USER_COLUMNS
+        ))
+        .bind(Uuid::new_v4())
+        .bind(username)
+        .bind(email)
+        .bind(self.default_render_quota as i32)
+        .bind(tenant_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| unique_violation(e, format!("Username {} is already taken", username)))?;
+
+        let api_key = insert_key(&mut tx, user.id, DEFAULT_KEY_NAME).await?;
+        tx.commit().await?;
+
+        info!("Registered user {}", user.username);
+        Ok(RegisteredUser { user, api_key })
+    }
+
+    /// Sign in with a GitHub identity, creating the tenant's linked account the first time; each sign-in issues a new key
+    pub async fn sign_in_with_github(&self, tenant_id: &str, github_user: &GitHubUser) -> Result<RegisteredUser> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let existing = sqlx::query_as::<_, User>(&format!(
+            "UPDATE users SET github_login = $2, updated_at = NOW()
+             WHERE github_id = $1 AND tenant_id = $3 RETURNING {}",
+            USER_COLUMNS
+        ))
+        .bind(github_user.id)
+        .bind(&github_user.login)
+        .bind(tenant_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let user = match existing {
+            Some(user) => user,
+            None => sqlx::query_as::<_, User>(&format!(
+                "INSERT INTO users (id, username, email, github_id, github_login, render_quota_daily, tenant_id)
+                 VALUES ($1, $2, $3, $4, $2, $5, $6) RETURNING {}",
+                USER_COLUMNS
+            ))
+            .bind(Uuid::new_v4())
+            .bind(&github_user.login)
+            .bind(github_user.email.as_deref())
+            .bind(github_user.id)
+            .bind(self.default_render_quota as i32)
+            .bind(tenant_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| {
+                unique_violation(e, format!("Username {} is already registered without GitHub", github_user.login))
+            })?,
+        };
+
+        let api_key = insert_key(&mut tx, user.id, GITHUB_KEY_NAME).await?;
+        tx.commit().await?;
+
+        info!("GitHub sign-in for {} issued key {}", user.username, api_key.api_key.principal);
+        Ok(RegisteredUser { user, api_key })
+    }
+
+    /// The tenant's account behind an active key, stamping the key as used; None for unknown, revoked, or other tenants' keys
+    pub async fn authenticate(&self, tenant_id: &str, key: &str) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(&format!(
+            "WITH used AS (
+                UPDATE api_keys SET last_used_at = NOW()
+                WHERE key_hash = $1 AND revoked_at IS NULL
+                  AND user_id IN (SELECT id FROM users WHERE tenant_id = $2)
+                RETURNING user_id)
+             SELECT {} FROM users WHERE id = (SELECT user_id FROM used)",
+            USER_COLUMNS
+        ))
+        .bind(Utils::hash_string(key))
+        .bind(tenant_id)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    pub async fn list_keys(&self, user_id: Uuid) -> Result<Vec<ApiKey>> {
+        let keys = sqlx::query_as::<_, ApiKey>(&format!(
+            "SELECT {} FROM api_keys WHERE user_id = $1 ORDER BY created_at DESC",
+            KEY_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(keys)
+    }
+
+    pub async fn create_key(&self, user_id: Uuid, name: Option<&str>) -> Result<IssuedApiKey> {
+        let name = name.unwrap_or(DEFAULT_KEY_NAME);
+        validate_key_name(name)?;
+
+        let mut tx = self.db_pool.begin().await?;
+        let active: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM api_keys WHERE user_id = $1 AND revoked_at IS NULL")
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        if active >= MAX_ACTIVE_KEYS_PER_USER {
+            return Err(AppError::ValidationError(format!(
+                "At most {} active keys are allowed; revoke one first",
+                MAX_ACTIVE_KEYS_PER_USER
+            )));
+        }
+
+        let key = insert_key(&mut tx, user_id, name).await?;
+        tx.commit().await?;
+        Ok(key)
+    }
+
+    /// Revoke a key and issue its replacement under the same name
+    pub async fn rotate_key(&self, user_id: Uuid, key_id: Uuid) -> Result<IssuedApiKey> {
+        let mut tx = self.db_pool.begin().await?;
+        let name: String = sqlx::query_scalar(
+            "UPDATE api_keys SET revoked_at = NOW()
+             WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+             RETURNING name"
+        )
+        .bind(key_id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFoundError(format!("Active API key {} not found", key_id)))?;
+
+        let key = insert_key(&mut tx, user_id, &name).await?;
+        tx.commit().await?;
+
+        info!("Rotated API key {} into {}", key_id, key.api_key.id);
+        Ok(key)
+    }
+
+    pub async fn revoke_key(&self, user_id: Uuid, key_id: Uuid) -> Result<ApiKey> {
+        sqlx::query_as::<_, ApiKey>(&format!(
+            "UPDATE api_keys SET revoked_at = NOW()
+             WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+             RETURNING {}",
+            KEY_COLUMNS
+        ))
+        .bind(key_id)
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFoundError(format!("Active API key {} not found", key_id)))
+    }
+
+    /// Where a user stands against today's quota
+    pub async fn quota(&self, user: &User) -> Result<RenderQuota> {
+        let used: Option<i32> = sqlx::query_scalar("SELECT renders FROM render_usage WHERE user_id = $1 AND day = $2")
+            .bind(user.id)
+            .bind(today())
+            .fetch_optional(&self.db_pool)
+            .await?;
+
+        Ok(quota_status(user.render_quota_daily, used.unwrap_or(0), self.expensive_render_pixels))
+    }
+
+    /// Count an expensive render against the user's quota; cheap renders are always free
+    /// I'm charging before the render runs, so a render that later fails still uses up its slot
+    pub async fn charge_render(&self, user: &User, pixels: u64) -> Result<()> {
+        if !self.is_expensive(pixels) {
+            return Ok(());
+        }
+
+        let charged: Option<i32> = if user.render_quota_daily > 0 {
+            sqlx::query_scalar(
+                "INSERT INTO render_usage (user_id, day, renders) VALUES ($1, $2, 1)
+                 ON CONFLICT (user_id, day) DO UPDATE SET renders = render_usage.renders + 1
+                 WHERE render_usage.renders < $3
+                 RETURNING renders"
+            )
+            .bind(user.id)
+            .bind(today())
+            .bind(user.render_quota_daily)
+            .fetch_optional(&self.db_pool)
+            .await?
+        } else {
+            None
+        };
+
+        match charged {
+            Some(_) => Ok(()),
+            None => Err(AppError::RateLimitError(format!(
+                "Daily quota of {} renders at {} pixels or more is used up; it resets at {}",
+                user.render_quota_daily,
+                self.expensive_render_pixels,
+                next_reset().to_rfc3339()
+            ))),
+        }
+    }
+
+    /// Change the daily quota of one of the tenant's users, returning the previous quota alongside the updated user
+    pub async fn set_quota(&self, tenant_id: &str, user_id: Uuid, render_quota_daily: i32) -> Result<(i32, User)> {
+        if render_quota_daily < 0 {
+            return Err(AppError::ValidationError("Render quota cannot be negative".to_string()));
+        }
+
+        let mut tx = self.db_pool.begin().await?;
+        let previous: i32 =
+            sqlx::query_scalar("SELECT render_quota_daily FROM users WHERE id = $1 AND tenant_id = $2 FOR UPDATE")
+                .bind(user_id)
+                .bind(tenant_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .ok_or_else(|| AppError::NotFoundError(format!("User {} not found", user_id)))?;
+
+        let user = sqlx::query_as::<_, User>(&format!(
+            "UPDATE users SET render_quota_daily = $2, updated_at = NOW() WHERE id = $1 RETURNING {}",
+            USER_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(render_quota_daily)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok((previous, user))
+    }
+}
+
+fn quota_status(limit: i32, used: i32, expensive_render_pixels: u64) -> RenderQuota {
+    RenderQuota {
+        limit,
+        used,
+        remaining: (limit - used).max(0),
+        expensive_render_pixels,
+        resets_at: next_reset(),
+    }
+}
+
+async fn insert_key(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, user_id: Uuid, name: &str) -> Result<IssuedApiKey> {
+    let key = generate_api_key();
+    let api_key = sqlx::query_as::<_, ApiKey>(&format!(
+        "INSERT INTO api_keys (id, user_id, name, key_hash, prefix) VALUES ($1, $2, $3, $4, $5) RETURNING {}",
+        KEY_COLUMNS
+    ))
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(name)
+    .bind(Utils::hash_string(&key))
+    .bind(api_key_prefix(&key))
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(IssuedApiKey { api_key, key })
+}
+
+/// Quotas are counted per UTC day
+fn today() -> NaiveDate {
+    Utc::now().date_naive()
+}
+
+fn next_reset() -> chrono::DateTime<Utc> {
+    (today() + Duration::days(1)).and_hms_opt(0, 0, 0).expect("midnight exists").and_utc()
+}
+
+fn unique_violation(error: sqlx::Error, message: String) -> AppError {
+    match error {
+        sqlx::Error::Database(e) if e.is_unique_violation() => AppError::ValidationError(message),
+        other => other.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quota_never_reports_negative_remaining() {
+        let quota = quota_status(5, 7, 1_048_576);
+        assert_eq!(quota.remaining, 0);
+        assert_eq!(quota_status(5, 2, 1_048_576).remaining, 3);
+
+        let until_reset = quota.resets_at - Utc::now();
+        assert!(until_reset > Duration::zero() && until_reset <= Duration::days(1));
+    }
+}
@@ -0,0 +1,465 @@
+/*
+ * Outbound webhook service managing subscriptions and delivering signed event payloads through the task queue.
+ * I'm recording every delivery before queueing it so a crash mid-retry still leaves an auditable trail.
+ */
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use sqlx::Row;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    database::DatabasePool,
+    jobs::TaskQueue,
+    models::jobs::QueuedTask,
+    models::webhooks::{
+        validate_event_types, validate_webhook_url, CreateWebhookRequest, CreatedWebhook,
+        DeliveryStatus, UpdateWebhookRequest, WebhookDelivery, WebhookEvent, WebhookSubscription,
+    },
+    utils::{
+        correlation::{self, WithCorrelationId},
+        error::{AppError, Result},
+    },
+};
+
+pub const SIGNATURE_HEADER: &str = "x-webhook-signature";
+pub const EVENT_HEADER: &str = "x-webhook-event";
+pub const DELIVERY_HEADER: &str = "x-webhook-delivery";
+
+/// Task queue that delivery attempts run on
+pub const DELIVERY_QUEUE: &str = "webhooks";
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, Clone)]
+pub struct WebhookService {
+    db_pool: DatabasePool,
+    http_client: reqwest::Client,
+    queue: TaskQueue,
+    max_attempts: u32,
+    allow_http: bool,
+    enabled: bool,
+}
+
+impl WebhookService {
+    pub fn new(db_pool: DatabasePool, queue: TaskQueue, enabled: bool, max_attempts: u32, allow_http: bool) -> Self {
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .user_agent(concat!("dark-performance-webhooks/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            db_pool,
+            http_client,
+            queue,
+            max_attempts: max_attempts.max(1),
+            allow_http,
+            enabled,
+        }
+    }
+
+    /// Register a new subscription, generating a signing secret when none is supplied
+    pub async fn create_subscription(&self, request: CreateWebhookRequest) -> Result<CreatedWebhook> {
+        validate_webhook_url(&request.url, self.allow_http)?;
+        validate_event_types(&request.event_types)?;
+
+        let secret = match request.secret.filter(|s| !s.trim().is_empty()) {
+            Some(secret) if secret.len() < 16 => {
+                return Err(AppError::ValidationError("Webhook secrets must be at least 16 characters".to_string()));
+            }
+            Some(secret) => secret,
+            None => generate_secret(),
+        };
+
+        let now = chrono::Utc::now();
+        let subscription = WebhookSubscription {
+            id: Uuid::new_v4(),
+            url: request.url,
+            event_types: request.event_types,
+            secret: secret.clone(),
+            active: true,
+            description: request.description,
+            created_at: now,
+            updated_at: now,
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO webhook_subscriptions (id, url, event_types, secret, active, description, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#
+        )
+        .bind(subscription.id)
+        .bind(&subscription.url)
+        .bind(event_names(&subscription.event_types))
+        .bind(&subscription.secret)
+        .bind(subscription.active)
+        .bind(&subscription.description)
+        .bind(subscription.created_at)
+        .bind(subscription.updated_at)
+        .execute(&self.db_pool)
+        .await?;
+
+        info!("Created webhook subscription {} for {:?}", subscription.id, event_names(&subscription.event_types));
+        Ok(CreatedWebhook { subscription, secret })
+    }
+
+    pub async fn list_subscriptions(&self) -> Result<Vec<WebhookSubscription>> {
+        let rows = sqlx::query(
+            "SELECT id, url, event_types, secret, active, description, created_at, updated_at
+             FROM webhook_subscriptions ORDER BY created_at DESC"
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        rows.iter().map(subscription_from_row).collect()
+    }
+
+    pub async fn get_subscription(&self, id: Uuid) -> Result<WebhookSubscription> {
+        let row = sqlx::query(
+            "SELECT id, url, event_types, secret, active, description, created_at, updated_at
+             FROM webhook_subscriptions WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::not_found(format!("webhook {}", id)))?;
+
+        subscription_from_row(&row)
+    }
+
+    pub async fn update_subscription(&self, id: Uuid, update: UpdateWebhookRequest) -> Result<WebhookSubscription> {
+        let mut subscription = self.get_subscription(id).await?;
+
+        if let Some(url) = update.url {
+            validate_webhook_url(&url, self.allow_http)?;
+            subscription.url = url;
+        }
+        if let Some(event_types) = update.event_types {
+            validate_event_types(&event_types)?;
+            subscription.event_types = event_types;
+        }
+        if let Some(active) = update.active {
+            subscription.active = active;
+        }
+        if update.description.is_some() {
+            subscription.description = update.description;
+        }
+        subscription.updated_at = chrono::Utc::now();
+
+        sqlx::query(
+            r#"
+            UPDATE webhook_subscriptions
+            SET url = $2, event_types = $3, active = $4, description = $5, updated_at = $6
+            WHERE id = $1
+            "#
+        )
+        .bind(id)
+        .bind(&subscription.url)
+        .bind(event_names(&subscription.event_types))
+        .bind(subscription.active)
+        .bind(&subscription.description)
+        .bind(subscription.updated_at)
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(subscription)
+    }
+
+    pub async fn delete_subscription(&self, id: Uuid) -> Result<()> {
+        let result = sqlx::query("DELETE FROM webhook_subscriptions WHERE id = $1")
+            .bind(id)
+            .execute(&self.db_pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found(format!("webhook {}", id)));
+        }
+
+        info!("Deleted webhook subscription {}", id);
+        Ok(())
+    }
+
+    /// Recent deliveries for a subscription, newest first
+    pub async fn list_deliveries(&self, subscription_id: Uuid, limit: i64) -> Result<Vec<WebhookDelivery>> {
+        let rows = sqlx::query(
+            "SELECT id, subscription_id, event_type, payload, status, attempts, response_status,
+                    error, created_at, delivered_at
+             FROM webhook_deliveries WHERE subscription_id = $1
+             ORDER BY created_at DESC LIMIT $2"
+        )
+        .bind(subscription_id)
+        .bind(limit)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        rows.iter().map(delivery_from_row).collect()
+    }
+
+    /// Fan an event out to every active subscriber without blocking the caller
+    /// I'm spawning the whole fan-out so event sources never wait on subscriber lookups or slow receivers
+    pub fn emit(&self, event: WebhookEvent, data: serde_json::Value) {
+        if !self.enabled {
+            return;
+        }
+
+        let service = self.clone();
+        correlation::spawn(async move {
+            if let Err(e) = service.dispatch(event, data).await {
+                warn!("Failed to dispatch {} webhooks: {}", event.as_str(), e);
+            }
+        });
+    }
+
+    async fn dispatch(&self, event: WebhookEvent, data: serde_json::Value) -> Result<()> {
+        let subscribers: Vec<WebhookSubscription> = self
+            .list_subscriptions()
+            .await?
+            .into_iter()
+            .filter(|s| s.subscribes_to(event))
+            .collect();
+
+        debug!("Dispatching {} to {} subscribers", event.as_str(), subscribers.len());
+
+        for subscription in subscribers {
+            let delivery_id = Uuid::new_v4();
+            let payload = serde_json::json!({
+                "id": delivery_id,
+                "event": event.as_str(),
+                "created_at": chrono::Utc::now(),
+                "data": data,
+            });
+
+            sqlx::query(
+                r#"
+                INSERT INTO webhook_deliveries (id, subscription_id, event_type, payload, status)
+                VALUES ($1, $2, $3, $4, $5)
+                "#
+            )
+            .bind(delivery_id)
+            .bind(subscription.id)
+            .bind(event.as_str())
+            .bind(&payload)
+            .bind(DeliveryStatus::Pending.as_str())
+            .execute(&self.db_pool)
+            .await?;
+
+            let queued = self
+                .queue
+                .enqueue(DELIVERY_QUEUE, "webhook.deliver", &serde_json::json!({ "delivery_id": delivery_id }), Some(self.max_attempts))
+                .await;
+            if let Err(e) = queued {
+                warn!("Failed to queue webhook delivery {}: {}", delivery_id, e);
+                self.record_attempt(delivery_id, DeliveryStatus::Failed, 0, None, Some(format!("Failed to queue delivery: {}", e)))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Make one delivery attempt for a queued task; errors hand the retry back to the queue
+    pub async fn process_delivery(&self, task: QueuedTask) -> Result<()> {
+        let delivery_id: Uuid = serde_json::from_value(task.payload["delivery_id"].clone())?;
+        let row = sqlx::query(
+            r#"
+            SELECT d.event_type, d.payload, s.url, s.secret, s.active
+            FROM webhook_deliveries d
+            JOIN webhook_subscriptions s ON s.id = d.subscription_id
+            WHERE d.id = $1
+            "#
+        )
+        .bind(delivery_id)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        // Deleting a subscription cascades to its deliveries, so there is nothing left to send
+        let Some(row) = row else {
+            debug!("Dropping webhook delivery {}: subscription no longer exists", delivery_id);
+            return Ok(());
+        };
+        let attempts = task.attempts as i32;
+        if !row.try_get::<bool, _>("active")? {
+            self.record_attempt(delivery_id, DeliveryStatus::Failed, attempts, None, Some("Subscription is inactive".to_string()))
+                .await;
+            return Ok(());
+        }
+
+        let event: WebhookEvent = row.try_get::<String, _>("event_type")?.parse().map_err(AppError::DatabaseError)?;
+        let url: String = row.try_get("url")?;
+        let secret: String = row.try_get("secret")?;
+        let body = row.try_get::<serde_json::Value, _>("payload")?.to_string();
+
+        let (response_status, result) = self.send(&url, &secret, event, delivery_id, body).await;
+        match result {
+            Ok(()) => {
+                info!("Delivered webhook {} ({}) to {}", delivery_id, event.as_str(), url);
+                self.record_attempt(delivery_id, DeliveryStatus::Delivered, attempts, response_status, None).await;
+                Ok(())
+            }
+            Err(e) => {
+                // Deliveries stay pending between attempts so the log shows which ones are still being retried
+                let status = if task.is_last_attempt() {
+                    warn!("Webhook {} to {} failed permanently: {}", delivery_id, url, e);
+                    DeliveryStatus::Failed
+                } else {
+                    DeliveryStatus::Pending
+                };
+                self.record_attempt(delivery_id, status, attempts, response_status, Some(e.clone())).await;
+                Err(AppError::ExternalApiError(format!("Webhook delivery {} failed: {}", delivery_id, e)))
+            }
+        }
+    }
+
+    async fn send(
+        &self,
+        url: &str,
+        secret: &str,
+        event: WebhookEvent,
+        delivery_id: Uuid,
+        body: String,
+    ) -> (Option<i32>, std::result::Result<(), String>) {
+        let timestamp = chrono::Utc::now().timestamp();
+        let response = self
+            .http_client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, sign_payload(secret, timestamp, &body))
+            .header(EVENT_HEADER, event.as_str())
+            .header(DELIVERY_HEADER, delivery_id.to_string())
+            .with_correlation_id()
+            .body(body)
+            .send()
+            .await;
+
+        match response {
+            Ok(response) if response.status().is_success() => (Some(response.status().as_u16() as i32), Ok(())),
+            Ok(response) => (
+                Some(response.status().as_u16() as i32),
+                Err(format!("Receiver responded with {}", response.status())),
+            ),
+            Err(e) => (None, Err(e.to_string())),
+        }
+    }
+
+    async fn record_attempt(
+        &self,
+        delivery_id: Uuid,
+        status: DeliveryStatus,
+        attempts: i32,
+        response_status: Option<i32>,
+        error: Option<String>,
+    ) {
+        let update = sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = $2, attempts = $3, response_status = $4, error = $5,
+                delivered_at = CASE WHEN $2 = 'delivered' THEN NOW() ELSE NULL END
+            WHERE id = $1
+            "#
+        )
+        .bind(delivery_id)
+        .bind(status.as_str())
+        .bind(attempts)
+        .bind(response_status)
+        .bind(error)
+        .execute(&self.db_pool)
+        .await;
+
+        if let Err(e) = update {
+            warn!("Failed to record outcome of webhook delivery {}: {}", delivery_id, e);
+        }
+    }
+}
+
+/// Compute the signature header value for a payload
+/// I'm signing `timestamp.body` so receivers can reject replays of old deliveries
+pub fn sign_payload(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+
+    let signature: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+
+    format!("t={},v1={}", timestamp, signature)
+}
+
+fn generate_secret() -> String {
+    use rand::Rng;
+    let bytes: [u8; 32] = rand::thread_rng().gen();
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn event_names(events: &[WebhookEvent]) -> Vec<String> {
+    events.iter().map(|e| e.as_str().to_string()).collect()
+}
+
+fn subscription_from_row(row: &sqlx::postgres::PgRow) -> Result<WebhookSubscription> {
+    let event_types: Vec<String> = row.try_get("event_types")?;
+    let event_types = event_types
+        .iter()
+        .map(|e| e.parse())
+        .collect::<std::result::Result<Vec<WebhookEvent>, _>>()
+        .map_err(AppError::DatabaseError)?;
+
+    Ok(WebhookSubscription {
+        id: row.try_get("id")?,
+        url: row.try_get("url")?,
+        event_types,
+        secret: row.try_get("secret")?,
+        active: row.try_get("active")?,
+        description: row.try_get("description")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+fn delivery_from_row(row: &sqlx::postgres::PgRow) -> Result<WebhookDelivery> {
+    let event_type: String = row.try_get("event_type")?;
+    let status: String = row.try_get("status")?;
+
+    Ok(WebhookDelivery {
+        id: row.try_get("id")?,
+        subscription_id: row.try_get("subscription_id")?,
+        event_type: event_type.parse().map_err(AppError::DatabaseError)?,
+        payload: row.try_get("payload")?,
+        status: status.parse().map_err(AppError::DatabaseError)?,
+        attempts: row.try_get("attempts")?,
+        response_status: row.try_get("response_status")?,
+        error: row.try_get("error")?,
+        created_at: row.try_get("created_at")?,
+        delivered_at: row.try_get("delivered_at")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_is_stable_and_keyed() {
+        let signature = sign_payload("topsecret-topsecret", 1700000000, r#"{"event":"job.finished"}"#);
+        assert!(signature.starts_with("t=1700000000,v1="));
+        assert_eq!(signature.len(), "t=1700000000,v1=".len() + 64);
+        assert_eq!(signature, sign_payload("topsecret-topsecret", 1700000000, r#"{"event":"job.finished"}"#));
+        assert_ne!(signature, sign_payload("another-secret-value", 1700000000, r#"{"event":"job.finished"}"#));
+    }
+
+    #[test]
+    fn test_generated_secrets_are_unique_hex() {
+        let a = generate_secret();
+        assert_eq!(a.len(), 64);
+        assert!(a.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(a, generate_secret());
+    }
+}
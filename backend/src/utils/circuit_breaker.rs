@@ -0,0 +1,475 @@
+/*
+ * Async circuit breakers guarding the service's outbound dependencies: GitHub, Redis, and Postgres.
+ * I'm keeping each breaker's state behind one short-lived lock that is never held across an await, and broadcasting every transition so gauges and logs follow it.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+use crate::utils::{
+    config::Config,
+    error::{AppError, Result},
+    metrics::MetricsCollector,
+};
+
+const EVENT_CAPACITY: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+impl CircuitState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half_open",
+        }
+    }
+
+    /// Value published on the state gauge: 0 closed, 1 open, 2 half-open
+    pub fn gauge_value(self) -> f64 {
+        match self {
+            CircuitState::Closed => 0.0,
+            CircuitState::Open => 1.0,
+            CircuitState::HalfOpen => 2.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitSettings {
+    /// Consecutive failures that open a closed circuit
+    pub failure_threshold: u32,
+    /// How long an open circuit rejects calls before letting probes through
+    pub open_timeout: Duration,
+    /// Calls allowed through at once while half-open
+    pub half_open_probes: u32,
+}
+
+impl CircuitSettings {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            failure_threshold: config.circuit_breaker_failure_threshold,
+            open_timeout: Duration::from_secs(config.circuit_breaker_open_seconds),
+            half_open_probes: config.circuit_breaker_half_open_probes,
+        }
+    }
+}
+
+/// One state transition of one breaker
+#[derive(Debug, Clone, Serialize)]
+pub struct CircuitEvent {
+    pub dependency: &'static str,
+    pub from: CircuitState,
+    pub to: CircuitState,
+    pub at: DateTime<Utc>,
+}
+
+/// A breaker's current state as shown to operators
+#[derive(Debug, Clone, Serialize)]
+pub struct CircuitSnapshot {
+    pub dependency: &'static str,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub failure_threshold: u32,
+    pub opened_at: Option<DateTime<Utc>>,
+    /// Seconds until an open circuit starts probing again
+    pub retry_after_seconds: Option<u64>,
+}
+
+#[derive(Debug)]
+struct BreakerState {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened: Option<(Instant, DateTime<Utc>)>,
+    probes_in_flight: u32,
+}
+
+/// Whether a failed call says anything about the dependency's health
+/// I'm leaving caller mistakes and our own rate limits out, since the dependency answered those fine
+fn trips_breaker(error: &AppError) -> bool {
+    error.is_retryable() && !matches!(error.root_cause(), AppError::RateLimitError(_))
+}
+
+/// A breaker for one dependency; clones share state
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    dependency: &'static str,
+    settings: CircuitSettings,
+    state: Arc<Mutex<BreakerState>>,
+    events: broadcast::Sender<CircuitEvent>,
+}
+
+impl CircuitBreaker {
+    pub fn new(dependency: &'static str, settings: CircuitSettings) -> Self {
+        Self::with_events(dependency, settings, broadcast::channel(EVENT_CAPACITY).0)
+    }
+
+    fn with_events(dependency: &'static str, settings: CircuitSettings, events: broadcast::Sender<CircuitEvent>) -> Self {
+        Self {
+            dependency,
+            settings,
+            state: Arc::new(Mutex::new(BreakerState {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                opened: None,
+                probes_in_flight: 0,
+            })),
+            events,
+        }
+    }
+
+    pub fn dependency(&self) -> &'static str {
+        self.dependency
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.state.lock().unwrap().state
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<CircuitEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn snapshot(&self) -> CircuitSnapshot {
+        let state = self.state.lock().unwrap();
+        CircuitSnapshot {
+            dependency: self.dependency,
+            state: state.state,
+            consecutive_failures: state.consecutive_failures,
+            failure_threshold: self.settings.failure_threshold,
+            opened_at: state.opened.map(|(_, at)| at),
+            retry_after_seconds: match (state.state, state.opened) {
+                (CircuitState::Open, Some((since, _))) => {
+                    Some(self.settings.open_timeout.saturating_sub(since.elapsed()).as_secs())
+                }
+                _ => None,
+            },
+        }
+    }
+
+    /// Run the operation unless the circuit is open, counting its outcome towards the breaker's state
+    /// I'm answering ServiceUnavailableError without calling the operation at all while the circuit is open
+    pub async fn call<F, Fut, T>(&self, operation: F) -> Result<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        self.call_with(operation, trips_breaker, AppError::ServiceUnavailableError).await
+    }
+
+    /// `call` for operations failing with another error type: `trips` picks the failures that count and `rejected` builds the open-circuit error
+    pub async fn call_with<F, Fut, T, E>(
+        &self,
+        operation: F,
+        trips: impl FnOnce(&E) -> bool,
+        rejected: impl FnOnce(String) -> E,
+    ) -> std::result::Result<T, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = std::result::Result<T, E>>,
+    {
+        let probe = match self.admit(Instant::now()) {
+            Ok(probe) => probe,
+            Err(retry_after) => {
+                return Err(rejected(format!(
+                    "{} is unavailable (circuit open); retry in {}s",
+                    self.dependency,
+                    retry_after.as_secs().max(1)
+                )))
+            }
+        };
+
+        // Hands the probe slot back if the caller drops this future mid-call
+        let mut guard = ProbeGuard { breaker: self, armed: probe };
+        let result = operation().await;
+        guard.armed = false;
+
+        match &result {
+            Err(e) if trips(e) => self.record_failure(probe, Instant::now()),
+            _ => self.record_success(probe),
+        }
+        result
+    }
+
+    /// Ok(true) admits a half-open probe, Ok(false) a normal call; Err carries how long the circuit stays open
+    fn admit(&self, now: Instant) -> std::result::Result<bool, Duration> {
+        let mut state = self.state.lock().unwrap();
+        match state.state {
+            CircuitState::Closed => Ok(false),
+            CircuitState::Open => {
+                let since = state.opened.map(|(since, _)| since).unwrap_or(now);
+                let reopens_at = since + self.settings.open_timeout;
+                if now < reopens_at {
+                    return Err(reopens_at - now);
+                }
+                state.probes_in_flight = 1;
+                self.transition(&mut state, CircuitState::HalfOpen);
+                Ok(true)
+            }
+            CircuitState::HalfOpen => {
+                if state.probes_in_flight >= self.settings.half_open_probes {
+                    return Err(Duration::ZERO);
+                }
+                state.probes_in_flight += 1;
+                Ok(true)
+            }
+        }
+    }
+
+    fn record_success(&self, probe: bool) {
+        let mut state = self.state.lock().unwrap();
+        if probe {
+            state.probes_in_flight = state.probes_in_flight.saturating_sub(1);
+        }
+        match state.state {
+            CircuitState::Closed => state.consecutive_failures = 0,
+            CircuitState::HalfOpen => {
+                state.consecutive_failures = 0;
+                state.opened = None;
+                state.probes_in_flight = 0;
+                self.transition(&mut state, CircuitState::Closed);
+            }
+            // A call admitted before the circuit opened doesn't prove the dependency is back
+            CircuitState::Open => {}
+        }
+    }
+
+    fn record_failure(&self, probe: bool, now: Instant) {
+        let mut state = self.state.lock().unwrap();
+        if probe {
+            state.probes_in_flight = state.probes_in_flight.saturating_sub(1);
+        }
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+
+        let opens = match state.state {
+            CircuitState::Closed => state.consecutive_failures >= self.settings.failure_threshold,
+            CircuitState::HalfOpen => true,
+            CircuitState::Open => false,
+        };
+        if opens {
+            state.opened = Some((now, Utc::now()));
+            self.transition(&mut state, CircuitState::Open);
+        }
+    }
+
+    fn release_probe(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.probes_in_flight = state.probes_in_flight.saturating_sub(1);
+    }
+
+    fn transition(&self, state: &mut BreakerState, to: CircuitState) {
+        let from = std::mem::replace(&mut state.state, to);
+        match to {
+            CircuitState::Open => warn!(
+                "Circuit for {} opened after {} consecutive failures; rejecting calls for {}s",
+                self.dependency,
+                state.consecutive_failures,
+                self.settings.open_timeout.as_secs()
+            ),
+            CircuitState::HalfOpen => info!("Circuit for {} is half-open; probing", self.dependency),
+            CircuitState::Closed => info!("Circuit for {} closed; {} recovered", self.dependency, self.dependency),
+        }
+
+        // No subscribers just means nothing is reporting yet
+        let _ = self.events.send(CircuitEvent {
+            dependency: self.dependency,
+            from,
+            to,
+            at: Utc::now(),
+        });
+    }
+}
+
+struct ProbeGuard<'a> {
+    breaker: &'a CircuitBreaker,
+    armed: bool,
+}
+
+impl Drop for ProbeGuard<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            self.breaker.release_probe();
+        }
+    }
+}
+
+/// One breaker per outbound dependency, sharing a single event stream
+#[derive(Debug, Clone)]
+pub struct CircuitBreakers {
+    pub github: CircuitBreaker,
+    pub redis: CircuitBreaker,
+    pub postgres: CircuitBreaker,
+    events: broadcast::Sender<CircuitEvent>,
+}
+
+impl CircuitBreakers {
+    pub fn new(settings: CircuitSettings) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+        Self {
+            github: CircuitBreaker::with_events("github", settings, events.clone()),
+            redis: CircuitBreaker::with_events("redis", settings, events.clone()),
+            postgres: CircuitBreaker::with_events("postgres", settings, events.clone()),
+            events,
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(CircuitSettings::from_config(config))
+    }
+
+    pub fn all(&self) -> [&CircuitBreaker; 3] {
+        [&self.github, &self.redis, &self.postgres]
+    }
+
+    pub fn snapshots(&self) -> Vec<CircuitSnapshot> {
+        self.all().iter().map(|breaker| breaker.snapshot()).collect()
+    }
+
+    /// Transitions of every breaker in this set
+    pub fn subscribe(&self) -> broadcast::Receiver<CircuitEvent> {
+        self.events.subscribe()
+    }
+
+    /// Keep the state gauges and open counters in step with transitions; updates are pushed on change rather than polled
+    pub fn spawn_reporter(&self, metrics: MetricsCollector) -> tokio::task::JoinHandle<()> {
+        let breakers = self.clone();
+        let mut events = self.subscribe();
+        tokio::spawn(async move {
+            for breaker in breakers.all() {
+                let _ = metrics.set_gauge(&state_gauge(breaker.dependency()), breaker.state().gauge_value()).await;
+            }
+
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        let _ = metrics.set_gauge(&state_gauge(event.dependency), event.to.gauge_value()).await;
+                        if event.to == CircuitState::Open {
+                            let _ = metrics.increment_counter(&format!("circuit_breaker_{}_opened_total", event.dependency)).await;
+                        }
+                    }
+                    // Missed transitions only matter for the gauge, so I'm resyncing it from the live state
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        for breaker in breakers.all() {
+                            let _ = metrics.set_gauge(&state_gauge(breaker.dependency()), breaker.state().gauge_value()).await;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+}
+
+fn state_gauge(dependency: &str) -> String {
+    format!("circuit_breaker_{}_state", dependency)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new("test", CircuitSettings {
+            failure_threshold: 2,
+            open_timeout: Duration::from_secs(30),
+            half_open_probes: 1,
+        })
+    }
+
+    #[test]
+    fn test_opens_after_threshold_and_probes_after_timeout() {
+        let breaker = breaker();
+        let mut events = breaker.subscribe();
+        let start = Instant::now();
+
+        breaker.record_failure(false, start);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        breaker.record_failure(false, start);
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert_eq!(events.try_recv().unwrap().to, CircuitState::Open);
+
+        assert_eq!(breaker.admit(start + Duration::from_secs(10)), Err(Duration::from_secs(20)));
+
+        assert_eq!(breaker.admit(start + Duration::from_secs(30)), Ok(true));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.admit(start + Duration::from_secs(30)).is_err(), "only one probe at a time");
+
+        breaker.record_success(true);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(breaker.admit(start + Duration::from_secs(31)), Ok(false));
+        assert_eq!(events.try_recv().unwrap().to, CircuitState::HalfOpen);
+        assert_eq!(events.try_recv().unwrap().to, CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_failed_probe_reopens() {
+        let breaker = breaker();
+        let start = Instant::now();
+        breaker.record_failure(false, start);
+        breaker.record_failure(false, start);
+
+        let retry = start + Duration::from_secs(30);
+        assert_eq!(breaker.admit(retry), Ok(true));
+        breaker.record_failure(true, retry);
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(breaker.admit(retry + Duration::from_secs(1)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_only_dependency_failures_count() {
+        let breaker = breaker();
+        for _ in 0..3 {
+            let result: Result<()> = breaker.call(|| async { Err(AppError::NotFoundError("repo".to_string())) }).await;
+            assert!(matches!(result, Err(AppError::NotFoundError(_))));
+        }
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        for _ in 0..2 {
+            let _: Result<()> = breaker.call(|| async { Err(AppError::DatabaseError("down".to_string())) }).await;
+        }
+        let rejected: Result<()> = breaker.call(|| async { panic!("an open circuit must not call through") }).await;
+        assert!(matches!(rejected, Err(AppError::ServiceUnavailableError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_call_with_counts_the_failures_it_is_told_to() {
+        let breaker = breaker();
+        for error in ["syntax", "syntax", "refused", "refused"] {
+            let _: std::result::Result<(), String> = breaker
+                .call_with(|| async { Err(error.to_string()) }, |e| e == "refused", |message| message)
+                .await;
+        }
+
+        let rejected: std::result::Result<(), String> = breaker
+            .call_with(|| async { panic!("an open circuit must not call through") }, |_| true, |message| message)
+            .await;
+        assert!(rejected.unwrap_err().starts_with("test is unavailable (circuit open)"));
+    }
+
+    #[tokio::test]
+    async fn test_dropped_probe_frees_its_slot() {
+        let breaker = breaker();
+        let start = Instant::now() - Duration::from_secs(60);
+        breaker.record_failure(false, start);
+        breaker.record_failure(false, start);
+
+        let pending = breaker.call(|| std::future::pending::<Result<()>>());
+        assert!(tokio::time::timeout(Duration::from_millis(10), pending).await.is_err());
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        let probe: Result<u32> = breaker.call(|| async { Ok(7) }).await;
+        assert_eq!(probe.unwrap(), 7);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+}
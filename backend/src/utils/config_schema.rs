@@ -0,0 +1,404 @@
+/*
+ * JSON Schema for the configuration, naming each setting's environment variable, type, default, and profile defaults.
+ * I'm computing defaults by running Config::from_source over a hermetic source per environment, so the schema can't drift from the parser.
+ */
+
+use serde_json::{json, Map, Value};
+
+use crate::utils::{
+    config::Config,
+    config_source::ConfigSource,
+    error::{AppError, Result},
+    live_config::TUNABLE_SETTINGS,
+};
+
+const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Environments whose profile defaults are reported, development first as the baseline
+const PROFILES: [&str; 3] = ["development", "staging", "production"];
+
+#[derive(Debug, Clone, Copy)]
+enum Type {
+    String,
+    OptionalString,
+    Integer,
+    Number,
+    Boolean,
+    StringList,
+    /// Object mapping strings to numbers
+    NumberMap,
+    /// Object mapping strings to strings
+    StringMap,
+}
+
+/// Extra parsing rules worth telling tooling about
+#[derive(Debug, Clone, Copy)]
+enum Format {
+    Plain,
+    /// Accepts unit suffixes; bare numbers are in this unit
+    Duration(&'static str),
+    /// Accepts KB/MB/GB suffixes; bare numbers are bytes
+    Size,
+    /// Serialized variant names
+    Enum(&'static [&'static str]),
+    /// Credential, masked or omitted when the configuration is printed
+    Secret,
+}
+
+struct Setting {
+    field: &'static str,
+    env: &'static str,
+    ty: Type,
+    format: Format,
+    description: &'static str,
+}
+
+const fn setting(field: &'static str, env: &'static str, ty: Type, format: Format, description: &'static str) -> Setting {
+    Setting { field, env, ty, format, description }
+}
+
+use Format::{Duration, Enum, Plain, Secret, Size};
+use Type::{Boolean, Integer, Number, NumberMap, OptionalString, StringList, StringMap};
+
+/// Settings with no default; the schema fills them with placeholders only to compute everyone else's defaults
+const REQUIRED: [(&str, &str); 4] = [
+    ("DATABASE_URL", "postgresql://localhost/schema"),
+    ("REDIS_URL", "redis://localhost"),
+    ("GITHUB_TOKEN", "schema-placeholder"),
+    ("GITHUB_USERNAME", "schema-placeholder"),
+];
+
+const SETTINGS: &[Setting] = &[
+    setting("host", "HOST", Type::String, Plain, "Address the HTTP server binds to"),
+    setting("port", "PORT", Integer, Plain, "Port the HTTP server listens on"),
+    setting("environment", "ENVIRONMENT", Type::String, Enum(&["Development", "Staging", "Production"]),
+        "Deployment environment; also selects the configuration profile"),
+    setting("database_url", "DATABASE_URL", Type::String, Secret,
+        "Postgres connection string, or a vault:// or aws-sm:// reference"),
+    setting("database_max_connections", "DATABASE_MAX_CONNECTIONS", Integer, Plain, "Upper bound on pooled Postgres connections"),
+    setting("database_min_connections", "DATABASE_MIN_CONNECTIONS", Integer, Plain, "Postgres connections kept open while idle"),
+    setting("database_connection_timeout", "DATABASE_CONNECTION_TIMEOUT", Integer, Duration("seconds"),
+        "How long to wait for a Postgres connection"),
+    setting("database_pgbouncer_mode", "DATABASE_PGBOUNCER_MODE", Boolean, Plain,
+        "Disable statement caching and LISTEN/NOTIFY for pgBouncer transaction pooling"),
+    setting("redis_url", "REDIS_URL", Type::String, Secret, "Redis connection string, or a vault:// or aws-sm:// reference"),
+    setting("redis_max_connections", "REDIS_MAX_CONNECTIONS", Integer, Plain, "Upper bound on Redis connections"),
+    setting("redis_connection_timeout", "REDIS_CONNECTION_TIMEOUT", Integer, Duration("seconds"), "How long to wait for a Redis connection"),
+    setting("github_token", "GITHUB_TOKEN", Type::String, Secret, "GitHub API token, or a vault:// or aws-sm:// reference"),
+    setting("github_username", "GITHUB_USERNAME", Type::String, Plain, "GitHub account whose repositories are showcased"),
+    setting("github_api_base_url", "GITHUB_API_BASE_URL", Type::String, Plain, "GitHub API root, for GitHub Enterprise or test servers"),
+    setting("github_rate_limit_requests", "GITHUB_RATE_LIMIT_REQUESTS", Integer, Plain, "GitHub API requests allowed per hour"),
+    setting("github_cache_ttl", "GITHUB_CACHE_TTL", Integer, Duration("seconds"), "How long GitHub responses stay cached"),
+    setting("frontend_url", "FRONTEND_URL", Type::String, Plain, "Public URL of the frontend"),
+    setting("cors_allowed_origins", "CORS_ALLOWED_ORIGINS", StringList, Plain, "Origins allowed to call the API, comma-separated"),
+    setting("metrics_enabled", "METRICS_ENABLED", Boolean, Plain, "Collect and expose Prometheus metrics"),
+    setting("prometheus_port", "PROMETHEUS_PORT", Integer, Plain, "Port for the Prometheus exporter"),
+    setting("system_metrics_interval", "SYSTEM_METRICS_INTERVAL", Integer, Duration("seconds"), "How often system metrics are sampled"),
+    setting("fractal_max_width", "MAX_FRACTAL_WIDTH", Integer, Plain, "Largest fractal width in pixels"),
+    setting("fractal_max_height", "MAX_FRACTAL_HEIGHT", Integer, Plain, "Largest fractal height in pixels"),
+    setting("fractal_max_iterations", "MAX_FRACTAL_ITERATIONS", Integer, Plain, "Highest iteration count per pixel"),
+    setting("fractal_max_zoom", "MAX_FRACTAL_ZOOM", Number, Plain, "Deepest zoom factor"),
+    setting("fractal_auto_iterations_base", "FRACTAL_AUTO_ITERATIONS_BASE", Integer, Plain,
+        "Iterations an auto_iterations render gets at zoom 1 and below"),
+    setting("fractal_auto_iterations_per_decade", "FRACTAL_AUTO_ITERATIONS_PER_DECADE", Number, Plain,
+        "Scale of the iterations auto_iterations adds per tenfold of zoom"),
+    setting("fractal_auto_iterations_exponent", "FRACTAL_AUTO_ITERATIONS_EXPONENT", Number, Plain,
+        "Power the decades of zoom are raised to; auto_iterations is base + per_decade x log10(zoom)^exponent"),
+    setting("fractal_min_multibrot_power", "MIN_MULTIBROT_POWER", Number, Plain, "Smallest exponent a multibrot render may use"),
+    setting("fractal_max_multibrot_power", "MAX_MULTIBROT_POWER", Number, Plain, "Largest exponent a multibrot render may use"),
+    setting("fractal_computation_timeout", "FRACTAL_COMPUTATION_TIMEOUT", Integer, Duration("seconds"),
+        "How long one fractal render may run"),
+    setting("fractal_cache_ttl", "FRACTAL_CACHE_TTL", Integer, Duration("seconds"),
+        "How long a rendered fractal is served from Redis to identical requests; 0 turns the cache off"),
+    setting("fractal_thumbnail_cache_ttl", "FRACTAL_THUMBNAIL_CACHE_TTL", Integer, Duration("seconds"),
+        "How long an encoded thumbnail is served from Redis to identical requests; 0 turns the thumbnail cache off"),
+    setting("fractal_threads", "FRACTAL_THREADS", Integer, Plain,
+        "Threads in the dedicated render pool, which also caps a request's threads hint; 0 uses one per core"),
+    setting("fractal_buffer_pool", "FRACTAL_BUFFER_POOL", Integer, Plain,
+        "Free render buffers kept per size class for later renders to reuse; 0 allocates every buffer fresh"),
+    setting("fractal_max_request_cost", "FRACTAL_MAX_REQUEST_COST", Integer, Plain,
+        "Most a synchronous render may be estimated to cost, in millions of iterations; bigger ones must be submitted as jobs. 0 lifts the cap"),
+    setting("fractal_client_cost_per_minute", "FRACTAL_CLIENT_COST_PER_MINUTE", Integer, Plain,
+        "Estimated render cost, in millions of iterations, each client may spend per minute; 0 lifts the budget"),
+    setting("log_level", "RUST_LOG", Type::String, Plain, "Tracing filter directive, such as info or dark_performance_backend=debug"),
+    setting("log_format", "LOG_FORMAT", Type::String, Enum(&["Plain", "Json"]), "Log output format"),
+    setting("log_sample_paths", "LOG_SAMPLE_PATHS", NumberMap, Plain,
+        "Share of request completion lines kept per path prefix, as path=rate pairs, comma-separated"),
+    setting("rate_limit_enabled", "RATE_LIMIT_ENABLED", Boolean, Plain, "Enforce per-client request limits"),
+    setting("rate_limit_requests_per_minute", "RATE_LIMIT_REQUESTS_PER_MINUTE", Integer, Plain, "General API requests per client per minute"),
+    setting("fractal_rate_limit_per_minute", "FRACTAL_RATE_LIMIT_PER_MINUTE", Integer, Plain, "Fractal renders per client per minute"),
+    setting("rate_limit_backend", "RATE_LIMIT_BACKEND", Type::String, Enum(&["Memory", "Redis"]),
+        "Count rate limit windows in Redis, shared by every instance, or per instance in memory"),
+    setting("cache_enabled", "CACHE_ENABLED", Boolean, Plain, "Cache responses in Redis"),
+    setting("cache_default_ttl", "CACHE_DEFAULT_TTL", Integer, Duration("seconds"), "Lifetime of cache entries without their own TTL"),
+    setting("github_cache_enabled", "GITHUB_CACHE_ENABLED", Boolean, Plain, "Cache GitHub API responses"),
+    setting("audit_log_enabled", "AUDIT_LOG_ENABLED", Boolean, Plain, "Persist an audit trail of API requests"),
+    setting("audit_log_sample_rate", "AUDIT_LOG_SAMPLE_RATE", Number, Plain, "Share of requests audited, from 0.0 to 1.0"),
+    setting("audit_log_redact_pii", "AUDIT_LOG_REDACT_PII", Boolean, Plain, "Mask client addresses and user agents in the audit trail"),
+    setting("admin_api_token", "ADMIN_API_TOKEN", OptionalString, Secret, "Bearer token for /api/admin; admin endpoints are off without it"),
+    setting("image_storage_enabled", "IMAGE_STORAGE_ENABLED", Boolean, Plain, "Keep rendered images on disk"),
+    setting("image_storage_path", "IMAGE_STORAGE_PATH", Type::String, Plain, "Directory for rendered images"),
+    setting("image_retention_days", "IMAGE_RETENTION_DAYS", Integer, Duration("days"),
+        "Idle days before a stored render no saved fractal uses is deleted"),
+    setting("image_signing_key", "IMAGE_SIGNING_KEY", OptionalString, Secret,
+        "HMAC key, at least 32 characters, for signed share links to saved fractal images; no signed links without it"),
+    setting("image_share_url_ttl", "IMAGE_SHARE_URL_TTL", Integer, Duration("seconds"), "How long a signed share link stays valid"),
+    setting("tls_cert_path", "TLS_CERT_PATH", OptionalString, Plain, "PEM certificate chain for native TLS; requires TLS_KEY_PATH"),
+    setting("tls_key_path", "TLS_KEY_PATH", OptionalString, Plain, "PEM private key for native TLS; requires TLS_CERT_PATH"),
+    setting("trusted_proxies", "TRUSTED_PROXIES", StringList, Plain, "CIDR ranges whose forwarding headers are trusted, comma-separated"),
+    setting("shutdown_grace_period_seconds", "SHUTDOWN_GRACE_PERIOD_SECONDS", Integer, Duration("seconds"),
+        "How long in-flight requests and jobs get to finish after SIGTERM before the rest are checkpointed"),
+    setting("webhooks_enabled", "WEBHOOKS_ENABLED", Boolean, Plain, "Deliver outbound webhooks"),
+    setting("webhook_max_attempts", "WEBHOOK_MAX_ATTEMPTS", Integer, Plain, "Delivery attempts before a webhook is given up on"),
+    setting("webhook_allow_http", "WEBHOOK_ALLOW_HTTP", Boolean, Plain, "Allow webhook URLs without TLS"),
+    setting("usage_tracking_enabled", "USAGE_TRACKING_ENABLED", Boolean, Plain, "Record per-API-key usage"),
+    setting("usage_hourly_quota", "USAGE_HOURLY_QUOTA", Integer, Plain,
+        "Requests each API key may make per hour before getting 429s; 0 lifts the quota. Requires USAGE_TRACKING_ENABLED"),
+    setting("maintenance_mode", "MAINTENANCE_MODE", Boolean, Plain, "Start in maintenance mode"),
+    setting("maintenance_message", "MAINTENANCE_MESSAGE", OptionalString, Plain, "Message shown while in maintenance mode"),
+    setting("maintenance_retry_after", "MAINTENANCE_RETRY_AFTER", Integer, Duration("seconds"), "Retry-After sent while in maintenance mode"),
+    setting("health_check_interval_seconds", "HEALTH_CHECK_INTERVAL_SECONDS", Integer, Duration("seconds"),
+        "How often dependencies are probed in the background"),
+    setting("health_stale_after_seconds", "HEALTH_STALE_AFTER_SECONDS", Integer, Duration("seconds"),
+        "Age after which a background health result is re-checked inline"),
+    setting("health_cpu_degraded_percent", "HEALTH_CPU_DEGRADED_PERCENT", Number, Plain, "CPU usage above which the system check is degraded"),
+    setting("health_cpu_unhealthy_percent", "HEALTH_CPU_UNHEALTHY_PERCENT", Number, Plain, "CPU usage above which the system check fails"),
+    setting("health_memory_degraded_percent", "HEALTH_MEMORY_DEGRADED_PERCENT", Number, Plain, "Memory usage above which the system check is degraded"),
+    setting("health_memory_unhealthy_percent", "HEALTH_MEMORY_UNHEALTHY_PERCENT", Number, Plain, "Memory usage above which the system check fails"),
+    setting("health_disk_degraded_percent", "HEALTH_DISK_DEGRADED_PERCENT", Number, Plain, "Disk usage above which the system check is degraded"),
+    setting("health_disk_unhealthy_percent", "HEALTH_DISK_UNHEALTHY_PERCENT", Number, Plain, "Disk usage above which the system check fails"),
+    setting("health_github_min_remaining_percent", "HEALTH_GITHUB_MIN_REMAINING_PERCENT", Number, Plain,
+        "Share of the GitHub rate limit below which the GitHub check is degraded"),
+    setting("health_check_weights", "HEALTH_CHECK_WEIGHTS", NumberMap, Plain,
+        "check=weight pairs; failing checks whose weights reach 1.0 make the service unhealthy, and a weight of 0 leaves a check out"),
+    setting("warm_up_timeout_seconds", "WARM_UP_TIMEOUT_SECONDS", Integer, Duration("seconds"),
+        "Longest /health/ready waits for startup warm-up before reporting ready anyway"),
+    setting("retention_cleanup_enabled", "RETENTION_CLEANUP_ENABLED", Boolean, Plain, "Delete rows older than their retention period"),
+    setting("retention_cleanup_interval_seconds", "RETENTION_CLEANUP_INTERVAL_SECONDS", Integer, Duration("seconds"),
+        "How often retention cleanup runs"),
+    setting("performance_metrics_retention_days", "PERFORMANCE_METRICS_RETENTION_DAYS", Integer, Duration("days"),
+        "How long performance metrics are kept"),
+    setting("fractal_computations_retention_days", "FRACTAL_COMPUTATIONS_RETENTION_DAYS", Integer, Duration("days"),
+        "How long fractal computation records are kept"),
+    setting("audit_log_retention_days", "AUDIT_LOG_RETENTION_DAYS", Integer, Duration("days"), "How long audit log entries are kept"),
+    setting("webhook_delivery_retention_days", "WEBHOOK_DELIVERY_RETENTION_DAYS", Integer, Duration("days"),
+        "How long webhook delivery records are kept"),
+    setting("job_run_retention_days", "JOB_RUN_RETENTION_DAYS", Integer, Duration("days"), "How long scheduled job run history is kept"),
+    setting("session_ttl_days", "SESSION_TTL_DAYS", Integer, Duration("days"),
+        "Idle days before a visitor session and its history are deleted; also the session cookie lifetime"),
+    setting("session_history_limit", "SESSION_HISTORY_LIMIT", Integer, Plain, "Renders remembered per visitor session, from 1 to 1000"),
+    setting("user_daily_render_quota", "USER_DAILY_RENDER_QUOTA", Integer, Plain,
+        "Expensive renders a new account may run per UTC day; admins can change it per user"),
+    setting("expensive_render_pixels", "EXPENSIVE_RENDER_PIXELS", Integer, Plain,
+        "Renders of at least this many pixels count against the caller's daily quota"),
+    setting("circuit_breaker_failure_threshold", "CIRCUIT_BREAKER_FAILURE_THRESHOLD", Integer, Plain,
+        "Consecutive GitHub, Redis, or Postgres failures that open that dependency's circuit"),
+    setting("circuit_breaker_open_seconds", "CIRCUIT_BREAKER_OPEN_SECONDS", Integer, Duration("seconds"),
+        "How long an open circuit rejects calls before probing the dependency again"),
+    setting("circuit_breaker_half_open_probes", "CIRCUIT_BREAKER_HALF_OPEN_PROBES", Integer, Plain,
+        "Probe calls let through at once while a circuit is half-open"),
+    setting("retry_budget_ratio", "RETRY_BUDGET_RATIO", Number, Plain,
+        "Retries earned per first attempt in the process-wide retry budget, from 0.0 to 1.0"),
+    setting("retry_budget_min_per_second", "RETRY_BUDGET_MIN_PER_SECOND", Integer, Plain,
+        "Retries per second the budget allows regardless of traffic"),
+    setting("db_notifications_enabled", "DB_NOTIFICATIONS_ENABLED", Boolean, Plain, "Signal other instances through Postgres LISTEN/NOTIFY"),
+    setting("slow_query_threshold_ms", "SLOW_QUERY_THRESHOLD_MS", Integer, Duration("milliseconds"),
+        "Queries slower than this are logged; 0 disables"),
+    setting("db_pool_metrics_interval_seconds", "DB_POOL_METRICS_INTERVAL_SECONDS", Integer, Duration("seconds"),
+        "How often pool gauges are published; 0 disables"),
+    setting("config_reload_interval_seconds", "CONFIG_RELOAD_INTERVAL_SECONDS", Integer, Duration("seconds"),
+        "How often config files are checked for edits; 0 leaves SIGHUP as the only trigger"),
+    setting("github_token_rotation_seconds", "GITHUB_TOKEN_ROTATION_SECONDS", Integer, Duration("seconds"),
+        "How often a referenced GITHUB_TOKEN is re-read; 0 disables"),
+    setting("backup_max_archive_bytes", "BACKUP_MAX_ARCHIVE_SIZE", Integer, Size, "Largest backup archive accepted for import, after decompression"),
+    setting("sentry_dsn", "SENTRY_DSN", OptionalString, Secret, "Sentry DSN; High and Critical errors and panics are reported when set"),
+    setting("sentry_sample_rate", "SENTRY_SAMPLE_RATE", Number, Plain, "Share of error events sent to Sentry, from 0.0 to 1.0"),
+    setting("slo_availability_target", "SLO_AVAILABILITY_TARGET", Number, Plain, "Share of requests that must not fail with a 5xx, such as 0.999"),
+    setting("slo_latency_target", "SLO_LATENCY_TARGET", Number, Plain, "Share of GET requests that must finish under the latency threshold"),
+    setting("slo_latency_threshold_ms", "SLO_LATENCY_THRESHOLD_MS", Integer, Duration("milliseconds"), "Latency a GET must beat to count as good"),
+    setting("slo_window_days", "SLO_WINDOW_DAYS", Integer, Duration("days"), "Rolling window the error budget is measured over"),
+    setting("scheduler_enabled", "SCHEDULER_ENABLED", Boolean, Plain, "Run background jobs on their schedules; manual triggers work either way"),
+    setting("job_schedules", "JOB_SCHEDULES", StringMap, Plain,
+        "Per-job schedule overrides as job=schedule pairs, semicolon-separated; cron expressions or intervals like 15m"),
+    setting("leader_election_enabled", "LEADER_ELECTION_ENABLED", Boolean, Plain,
+        "Elect one instance through Redis to run scheduled jobs; when off every instance runs them"),
+    setting("leader_lease_seconds", "LEADER_LEASE_SECONDS", Integer, Duration("seconds"),
+        "How long a leader's lease lasts without renewal; it is renewed every third of this"),
+    setting("task_queue_workers", "TASK_QUEUE_WORKERS", Integer, Plain, "Concurrent workers per task queue on each instance"),
+    setting("task_queue_max_attempts", "TASK_QUEUE_MAX_ATTEMPTS", Integer, Plain, "Attempts before a queued task is dead-lettered, unless the producer sets its own"),
+    setting("task_queue_visibility_timeout_seconds", "TASK_QUEUE_VISIBILITY_TIMEOUT_SECONDS", Integer, Duration("seconds"),
+        "How long a claimed task stays hidden without a heartbeat before another worker may take it"),
+    setting("task_queue_poll_interval_ms", "TASK_QUEUE_POLL_INTERVAL_MS", Integer, Duration("milliseconds"), "How often idle workers check for new tasks"),
+    setting("export_storage_path", "EXPORT_STORAGE_PATH", Type::String, Plain, "Directory for finished export files"),
+    setting("animation_storage_path", "ANIMATION_STORAGE_PATH", Type::String, Plain, "Directory for finished zoom animations"),
+    setting("animation_max_frames", "ANIMATION_MAX_FRAMES", Integer, Plain, "Most frames one zoom animation may have"),
+    setting("animation_inline_max_pixels", "ANIMATION_INLINE_MAX_PIXELS", Integer, Plain,
+        "Largest width × height × frames rendered inside the request; bigger animations run as jobs"),
+    setting("fractal_job_concurrency", "FRACTAL_JOB_CONCURRENCY", Integer, Plain, "Fractal jobs rendering at once on each instance"),
+    setting("fractal_job_ttl_seconds", "FRACTAL_JOB_TTL_SECONDS", Integer, Duration("seconds"),
+        "How long a fractal job's status and pixels stay in Redis"),
+    setting("smtp_host", "SMTP_HOST", OptionalString, Plain, "SMTP server for alert and account emails; email is off without it"),
+    setting("smtp_port", "SMTP_PORT", Integer, Plain, "SMTP server port, usually 587 for STARTTLS or 465 for TLS"),
+    setting("smtp_username", "SMTP_USERNAME", OptionalString, Plain, "SMTP login; set together with SMTP_PASSWORD"),
+    setting("smtp_password", "SMTP_PASSWORD", OptionalString, Secret, "SMTP password; may be a vault:// or aws-sm:// reference"),
+    setting("smtp_tls", "SMTP_TLS", Type::String, Enum(&["None", "Starttls", "Tls"]), "How the SMTP connection is secured"),
+    setting("smtp_from", "SMTP_FROM", Type::String, Plain, "Sender address, optionally with a display name like Alerts <alerts@example.com>"),
+    setting("alert_email_recipients", "ALERT_EMAIL_RECIPIENTS", StringList, Plain, "Addresses that receive alert emails, comma-separated"),
+    setting("compression_enabled", "COMPRESSION_ENABLED", Boolean, Plain, "Compress responses for clients that accept it"),
+    setting("compression_encodings", "COMPRESSION_ENCODINGS", StringList, Plain,
+        "Encodings offered, comma-separated from zstd, br, gzip, and deflate; on equal q-values zstd wins, then br, gzip, deflate"),
+    setting("compression_level", "COMPRESSION_LEVEL", Type::String, Plain,
+        "fastest, default, best, or a number passed to each encoder as its quality level"),
+    setting("compression_min_size_bytes", "COMPRESSION_MIN_SIZE", Integer, Size,
+        "Responses smaller than this are sent uncompressed, up to 64KB; streamed responses of unknown size are always compressed"),
+    setting("request_recording_enabled", "REQUEST_RECORDING_ENABLED", Boolean, Plain,
+        "Record sanitized requests and responses for replay from the admin API; refused in production"),
+    setting("request_recording_store", "REQUEST_RECORDING_STORE", Type::String, Enum(&["Database", "Disk"]), "Where recordings are kept"),
+    setting("request_recording_path", "REQUEST_RECORDING_PATH", Type::String, Plain, "Directory for recordings when the store is disk"),
+    setting("request_recording_paths", "REQUEST_RECORDING_PATHS", StringList, Plain, "Path prefixes whose requests are recorded, comma-separated"),
+    setting("request_recording_max_body_bytes", "REQUEST_RECORDING_MAX_BODY_SIZE", Integer, Size, "Larger bodies, and binary ones, are recorded by size only"),
+    setting("request_recording_max_entries", "REQUEST_RECORDING_MAX_ENTRIES", Integer, Plain, "Recordings kept; the oldest are dropped past this"),
+];
+
+/// Build the schema document
+pub fn config_schema() -> Result<Value> {
+    let defaults = PROFILES
+        .iter()
+        .map(|profile| profile_defaults(profile).map(|values| (*profile, values)))
+        .collect::<Result<Vec<_>>>()?;
+    let (_, baseline) = &defaults[0];
+
+    let mut properties = Map::new();
+    for setting in SETTINGS {
+        let mut property = Map::new();
+        property.insert("description".to_string(), json!(setting.description));
+        property.insert("x-env-var".to_string(), json!(setting.env));
+
+        let ty = match setting.ty {
+            Type::String => json!("string"),
+            OptionalString => json!(["string", "null"]),
+            Integer => json!("integer"),
+            Number => json!("number"),
+            Boolean => json!("boolean"),
+            StringList => json!("array"),
+            NumberMap | StringMap => json!("object"),
+        };
+        property.insert("type".to_string(), ty);
+        if matches!(setting.ty, StringList) {
+            property.insert("items".to_string(), json!({ "type": "string" }));
+        }
+        if matches!(setting.ty, NumberMap) {
+            property.insert("additionalProperties".to_string(), json!({ "type": "number" }));
+        }
+        if matches!(setting.ty, StringMap) {
+            property.insert("additionalProperties".to_string(), json!({ "type": "string" }));
+        }
+
+        match setting.format {
+            Plain => {}
+            Duration(unit) => {
+                property.insert("x-unit".to_string(), json!(unit));
+                property.insert("x-env-format".to_string(), json!("duration"));
+            }
+            Size => {
+                property.insert("x-unit".to_string(), json!("bytes"));
+                property.insert("x-env-format".to_string(), json!("size"));
+            }
+            Enum(variants) => {
+                property.insert("enum".to_string(), json!(variants));
+            }
+            Secret => {
+                property.insert("writeOnly".to_string(), json!(true));
+            }
+        }
+
+        let required = REQUIRED.iter().any(|(env, _)| *env == setting.env);
+        if !required {
+            let default = baseline.get(setting.field).cloned().unwrap_or(Value::Null);
+            let overrides: Map<String, Value> = defaults[1..]
+                .iter()
+                .filter_map(|(profile, values)| {
+                    let value = values.get(setting.field).cloned().unwrap_or(Value::Null);
+                    (value != default && setting.field != "environment").then(|| (profile.to_string(), value))
+                })
+                .collect();
+
+            property.insert("default".to_string(), default);
+            if !overrides.is_empty() {
+                property.insert("x-profile-defaults".to_string(), Value::Object(overrides));
+            }
+        }
+        if TUNABLE_SETTINGS.contains(&setting.field) {
+            property.insert("x-runtime-tunable".to_string(), json!(true));
+        }
+
+        properties.insert(setting.field.to_string(), Value::Object(property));
+    }
+
+    let required: Vec<&str> = SETTINGS
+        .iter()
+        .filter(|s| REQUIRED.iter().any(|(env, _)| *env == s.env))
+        .map(|s| s.field)
+        .collect();
+
+    Ok(json!({
+        "$schema": SCHEMA_DIALECT,
+        "title": "Dark Performance backend configuration",
+        "description": "Effective configuration as printed by `config print`. Each property is set through its x-env-var \
+            environment variable or a CONFIG_FILE; x-profile-defaults lists defaults that differ by ENVIRONMENT.",
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    }))
+}
+
+/// Every setting's default under one profile, serialized as it appears in the config JSON
+fn profile_defaults(profile: &str) -> Result<Map<String, Value>> {
+    let mut tree: Map<String, Value> = REQUIRED
+        .iter()
+        .map(|(env, placeholder)| (env.to_ascii_lowercase(), json!(placeholder)))
+        .collect();
+    tree.insert("environment".to_string(), json!(profile));
+
+    let source = ConfigSource::from_tree(Value::Object(tree), Vec::new(), Some(profile))?;
+    match serde_json::to_value(Config::from_source(&source)?)? {
+        Value::Object(values) => Ok(values),
+        _ => Err(AppError::SerializationError("Config did not serialize to an object".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_covers_every_config_field() {
+        let serialized = profile_defaults("development").unwrap();
+        for field in serialized.keys() {
+            assert!(SETTINGS.iter().any(|s| s.field == field), "{} is missing from the config schema", field);
+        }
+        for setting in SETTINGS {
+            assert!(
+                serialized.contains_key(setting.field) || matches!(setting.format, Secret),
+                "{} in the config schema is not a Config field",
+                setting.field
+            );
+        }
+    }
+
+    #[test]
+    fn test_schema_reports_defaults_and_profiles() {
+        let schema = config_schema().unwrap();
+        let properties = &schema["properties"];
+
+        assert_eq!(properties["port"]["default"], 3001);
+        assert_eq!(properties["port"]["x-env-var"], "PORT");
+        assert_eq!(properties["fractal_max_width"]["x-env-var"], "MAX_FRACTAL_WIDTH");
+        assert_eq!(properties["database_max_connections"]["x-profile-defaults"]["production"], 100);
+        assert_eq!(properties["cache_default_ttl"]["x-unit"], "seconds");
+        assert_eq!(properties["cache_default_ttl"]["x-runtime-tunable"], true);
+        assert!(properties["github_token"].get("default").is_none());
+        assert!(schema["required"].as_array().unwrap().contains(&json!("database_url")));
+    }
+}
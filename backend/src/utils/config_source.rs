@@ -0,0 +1,378 @@
+/*
+ * Layered configuration source merging optional config files and environment profiles underneath environment variables.
+ * I'm flattening file settings onto the same variable names Config::from_env reads, so every value is parsed and validated by one code path.
+ */
+
+use figment::{
+    providers::{Format, Json, Toml, Yaml},
+    Figment,
+};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+
+use crate::utils::error::{AppError, Result};
+
+/// Comma-separated config files, merged in order with later files winning
+pub const CONFIG_FILE_VAR: &str = "CONFIG_FILE";
+
+/// Top-level table holding per-environment sections
+const PROFILES_KEY: &str = "profiles";
+
+/// Environment-specific defaults compiled into the binary
+static BUILTIN_PROFILES: Lazy<HashMap<&'static str, HashMap<String, String>>> = Lazy::new(|| {
+    let tree: serde_json::Value = Figment::from(Toml::string(include_str!("config_profiles.toml")))
+        .extract()
+        .expect("built-in config profiles are valid TOML");
+    let profiles = tree[PROFILES_KEY].as_object().cloned().unwrap_or_default();
+
+    ENVIRONMENTS
+        .iter()
+        .map(|name| {
+            let mut values = HashMap::new();
+            if let Some(section) = profiles.get(*name) {
+                flatten(section, None, &mut values).expect("built-in config profiles flatten");
+            }
+            (*name, values)
+        })
+        .collect()
+});
+
+/// Profile names, matching the ENVIRONMENT values Config accepts
+const ENVIRONMENTS: [&str; 3] = ["development", "staging", "production"];
+
+/// Flattened file keys whose environment variable is named differently
+const KEY_ALIASES: &[(&str, &str)] = &[
+    ("LOG_LEVEL", "RUST_LOG"),
+    ("FRACTAL_MAX_WIDTH", "MAX_FRACTAL_WIDTH"),
+    ("FRACTAL_MAX_HEIGHT", "MAX_FRACTAL_HEIGHT"),
+    ("FRACTAL_MAX_ITERATIONS", "MAX_FRACTAL_ITERATIONS"),
+    ("FRACTAL_MAX_ZOOM", "MAX_FRACTAL_ZOOM"),
+];
+
+/// Map settings a file may write as a table, with the separator their environment form puts between pairs
+const MAP_SETTINGS: &[(&str, &str)] = &[
+    ("LOG_SAMPLE_PATHS", ","),
+    ("HEALTH_CHECK_WEIGHTS", ","),
+    ("JOB_SCHEDULES", ";"),
+];
+
+/// Where a configuration lookup is answered from
+/// I'm checking the process environment first so deployments can always override a checked-in file,
+/// then the file's section for the active environment, the file's top level, and finally the built-in profile
+#[derive(Debug, Clone)]
+pub struct ConfigSource {
+    file_values: HashMap<String, String>,
+    profile_values: HashMap<String, String>,
+    builtin_values: HashMap<String, String>,
+    profile: Option<&'static str>,
+    files: Vec<PathBuf>,
+    read_env: bool,
+}
+
+impl ConfigSource {
+    /// Environment variables over the built-in profile for ENVIRONMENT
+    pub fn env_only() -> Self {
+        Self::from_tree(serde_json::Value::Null, Vec::new(), None)
+            .expect("an empty config tree always flattens")
+    }
+
+    /// Environment variables layered over the files named in CONFIG_FILE, if any
+    pub fn layered() -> Result<Self> {
+        match env::var(CONFIG_FILE_VAR) {
+            Ok(paths) if !paths.trim().is_empty() => {
+                let files: Vec<PathBuf> = paths
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|p| !p.is_empty())
+                    .map(PathBuf::from)
+                    .collect();
+                Self::from_files(&files)
+            }
+            _ => Ok(Self::env_only()),
+        }
+    }
+
+    /// Merge TOML, YAML, or JSON files (picked by extension) in order
+    pub fn from_files(files: &[PathBuf]) -> Result<Self> {
+        let mut figment = Figment::new();
+
+        for path in files {
+            if !path.is_file() {
+                return Err(AppError::ConfigurationError(format!(
+                    "Config file {} does not exist", path.display()
+                )));
+            }
+            figment = match file_format(path)? {
+                FileFormat::Toml => figment.merge(Toml::file(path)),
+                FileFormat::Yaml => figment.merge(Yaml::file(path)),
+                FileFormat::Json => figment.merge(Json::file(path)),
+            };
+        }
+
+        let tree: serde_json::Value = figment
+            .extract()
+            .map_err(|e| AppError::ConfigurationError(format!("Invalid config file: {}", e)))?;
+
+        Self::from_tree(tree, files.to_vec(), None)
+    }
+
+    /// Split a parsed config tree into its top-level values and the profile for the active environment
+    /// I'm ignoring process variables entirely when the environment is pinned, so tests and schema defaults are hermetic
+    pub(crate) fn from_tree(mut tree: serde_json::Value, files: Vec<PathBuf>, environment: Option<&str>) -> Result<Self> {
+        let profiles = tree
+            .as_object_mut()
+            .and_then(|table| table.remove(PROFILES_KEY))
+            .unwrap_or(serde_json::Value::Null);
+
+        let mut file_values = HashMap::new();
+        flatten(&tree, None, &mut file_values)?;
+
+        let sections = match profiles {
+            serde_json::Value::Object(sections) => sections,
+            serde_json::Value::Null => Default::default(),
+            _ => return Err(AppError::ConfigurationError("[profiles] must be a table of environment sections".to_string())),
+        };
+        if let Some(unknown) = sections.keys().find(|name| !ENVIRONMENTS.contains(&name.as_str())) {
+            return Err(AppError::ConfigurationError(format!(
+                "Unknown profile [profiles.{}]; expected one of {}", unknown, ENVIRONMENTS.join(", ")
+            )));
+        }
+
+        let requested = match environment {
+            Some(name) => Some(name.to_string()),
+            None => env::var("ENVIRONMENT")
+                .or_else(|_| env::var("ENV"))
+                .ok()
+                .or_else(|| file_values.get("ENVIRONMENT").or_else(|| file_values.get("ENV")).cloned()),
+        };
+        // An unrecognised name selects no profile; Config::from_source rejects it with a clearer message
+        let profile = canonical_environment(requested.as_deref().unwrap_or("development"));
+
+        let mut profile_values = HashMap::new();
+        if let Some(section) = profile.and_then(|name| sections.get(name)) {
+            flatten(section, None, &mut profile_values)?;
+        }
+        let builtin_values = profile
+            .and_then(|name| BUILTIN_PROFILES.get(name).cloned())
+            .unwrap_or_default();
+
+        Ok(Self { file_values, profile_values, builtin_values, profile, files, read_env: environment.is_none() })
+    }
+
+    /// Look a variable up: environment, file profile section, file top level, then built-in profile
+    pub fn var(&self, key: &str) -> Option<String> {
+        self.read_env
+            .then(|| env::var(key).ok())
+            .flatten()
+            .or_else(|| self.profile_values.get(key).cloned())
+            .or_else(|| self.file_values.get(key).cloned())
+            .or_else(|| self.builtin_values.get(key).cloned())
+    }
+
+    /// Profile selected by ENVIRONMENT, if it named a known one
+    pub fn profile(&self) -> Option<&'static str> {
+        self.profile
+    }
+
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Variable names the files set, for logging which settings came from where
+    pub fn file_keys(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = self.file_values.keys().chain(self.profile_values.keys()).map(String::as_str).collect();
+        keys.sort_unstable();
+        keys.dedup();
+        keys
+    }
+}
+
+/// Map ENVIRONMENT spellings onto profile names
+pub fn canonical_environment(name: &str) -> Option<&'static str> {
+    match name.to_lowercase().as_str() {
+        "development" | "dev" => Some("development"),
+        "staging" | "stage" => Some("staging"),
+        "production" | "prod" => Some("production"),
+        _ => None,
+    }
+}
+
+enum FileFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+fn file_format(path: &Path) -> Result<FileFormat> {
+    match path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase).as_deref() {
+        Some("toml") => Ok(FileFormat::Toml),
+        Some("yaml") | Some("yml") => Ok(FileFormat::Yaml),
+        Some("json") => Ok(FileFormat::Json),
+        _ => Err(AppError::ConfigurationError(format!(
+            "Config file {} must end in .toml, .yaml, .yml, or .json", path.display()
+        ))),
+    }
+}
+
+/// Turn nested tables into UPPER_SNAKE variable names: `[database] max_connections` becomes DATABASE_MAX_CONNECTIONS
+/// I'm joining arrays with commas since that's the list syntax the environment parsers already accept
+/// Every setting lands in one environment variable, so beyond plain values and lists of them only the map
+/// settings take a table (`[health_check_weights] github = 0.5`); arrays of tables are rejected by name
+fn flatten(value: &serde_json::Value, prefix: Option<&str>, out: &mut HashMap<String, String>) -> Result<()> {
+    use serde_json::Value;
+
+    match value {
+        Value::Object(table) if prefix.is_some_and(|key| MAP_SETTINGS.iter().any(|(name, _)| *name == key)) => {
+            let key = prefix.unwrap_or_default();
+            let separator = MAP_SETTINGS.iter().find(|(name, _)| *name == key).map_or(",", |(_, sep)| *sep);
+            let pairs = table
+                .iter()
+                .map(|(entry, child)| match child {
+                    Value::Array(_) | Value::Object(_) | Value::Null => Err(AppError::ConfigurationError(format!(
+                        "{}.{} must be a plain value", key, entry
+                    ))),
+                    other => Ok(format!("{}={}", entry, scalar_to_string(key, other)?)),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            out.insert(key.to_string(), pairs.join(separator));
+        }
+        Value::Object(table) => {
+            for (key, child) in table {
+                let name = key.to_ascii_uppercase().replace(['-', '.'], "_");
+                let name = match prefix {
+                    Some(prefix) => format!("{}_{}", prefix, name),
+                    None => name,
+                };
+                flatten(child, Some(&name), out)?;
+            }
+        }
+        Value::Null => {}
+        leaf => {
+            let Some(key) = prefix else {
+                return Err(AppError::ConfigurationError("Config file must contain a table of settings".to_string()));
+            };
+            let key = KEY_ALIASES
+                .iter()
+                .find(|(alias, _)| *alias == key)
+                .map_or(key, |(_, var)| *var);
+            out.insert(key.to_string(), scalar_to_string(key, leaf)?);
+        }
+    }
+    Ok(())
+}
+
+fn scalar_to_string(key: &str, value: &serde_json::Value) -> Result<String> {
+    use serde_json::Value;
+
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Bool(b) => Ok(b.to_string()),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                Value::Array(_) | Value::Object(_) => Err(AppError::ConfigurationError(format!(
+                    "{} must be a list of plain values; arrays of tables and nested lists have no environment form",
+                    key
+                ))),
+                other => scalar_to_string(key, other),
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(|items| items.join(",")),
+        Value::Null | Value::Object(_) => Ok(String::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_tables_flatten_to_env_names() {
+        let tree = serde_json::json!({
+            "port": 8080,
+            "database": { "max_connections": 50, "pgbouncer_mode": true },
+            "cors_allowed_origins": ["https://a.example", "https://b.example"],
+            "fractal": { "max_width": 2048 },
+            "log_level": "info"
+        });
+
+        let mut values = HashMap::new();
+        flatten(&tree, None, &mut values).unwrap();
+
+        assert_eq!(values["PORT"], "8080");
+        assert_eq!(values["DATABASE_MAX_CONNECTIONS"], "50");
+        assert_eq!(values["DATABASE_PGBOUNCER_MODE"], "true");
+        assert_eq!(values["CORS_ALLOWED_ORIGINS"], "https://a.example,https://b.example");
+        assert_eq!(values["MAX_FRACTAL_WIDTH"], "2048");
+        assert_eq!(values["RUST_LOG"], "info");
+    }
+
+    #[test]
+    fn test_map_settings_accept_tables() {
+        let tree = serde_json::json!({
+            "health_check_weights": { "github": 0.5, "redis": 1.0 },
+            "job_schedules": { "cleanup": "0 0,30 * * * *" }
+        });
+
+        let mut values = HashMap::new();
+        flatten(&tree, None, &mut values).unwrap();
+
+        assert_eq!(values["HEALTH_CHECK_WEIGHTS"], "github=0.5,redis=1.0");
+        assert_eq!(values["JOB_SCHEDULES"], "cleanup=0 0,30 * * * *");
+
+        let array_of_tables = serde_json::json!({ "cors_allowed_origins": [{ "url": "https://a.example" }] });
+        let err = flatten(&array_of_tables, None, &mut HashMap::new()).unwrap_err();
+        assert!(err.to_string().contains("CORS_ALLOWED_ORIGINS"));
+
+        let nested_map = serde_json::json!({ "job_schedules": { "cleanup": { "every": "15m" } } });
+        assert!(flatten(&nested_map, None, &mut HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_files_merge_in_order() {
+        let dir = std::env::temp_dir().join(format!("config-source-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let base = dir.join("base.toml");
+        let overlay = dir.join("overlay.yaml");
+        std::fs::write(&base, "port = 3001\n[github]\nusername = \"base\"\ncache_ttl = 60\n").unwrap();
+        std::fs::write(&overlay, "github:\n  username: overlay\n").unwrap();
+
+        let source = ConfigSource::from_files(&[base, overlay]).unwrap();
+        assert_eq!(source.var("GITHUB_USERNAME").as_deref(), Some("overlay"));
+        assert_eq!(source.var("GITHUB_CACHE_TTL").as_deref(), Some("60"));
+
+        assert!(ConfigSource::from_files(&[dir.join("missing.toml")]).is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_profile_sections_layer_by_environment() {
+        let tree = serde_json::json!({
+            "database": { "min_connections": 3 },
+            "github": { "cache_ttl": 60 },
+            "profiles": {
+                "production": { "github": { "cache_ttl": 900 }, "log_level": "error" },
+                "staging": { "github": { "cache_ttl": 300 } }
+            }
+        });
+
+        let production = ConfigSource::from_tree(tree.clone(), Vec::new(), Some("prod")).unwrap();
+        assert_eq!(production.profile(), Some("production"));
+        assert_eq!(production.var("GITHUB_CACHE_TTL").as_deref(), Some("900"));
+        assert_eq!(production.var("DATABASE_MIN_CONNECTIONS").as_deref(), Some("3"));
+        // The file's profile beats the built-in one, which still fills in keys the file leaves alone
+        assert_eq!(production.var("RUST_LOG").as_deref(), Some("error"));
+        assert_eq!(production.var("RATE_LIMIT_REQUESTS_PER_MINUTE").as_deref(), Some("60"));
+
+        let development = ConfigSource::from_tree(tree.clone(), Vec::new(), Some("development")).unwrap();
+        assert_eq!(development.var("GITHUB_CACHE_TTL").as_deref(), Some("60"));
+        assert_eq!(development.var("WEBHOOK_ALLOW_HTTP").as_deref(), Some("true"));
+        assert_eq!(development.var("RATE_LIMIT_REQUESTS_PER_MINUTE"), None);
+
+        let typo = serde_json::json!({ "profiles": { "prodution": {} } });
+        assert!(ConfigSource::from_tree(typo, Vec::new(), Some("production")).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+/*
+ * Request correlation ids, carried in a task-local for the life of a request and sent on every outbound call it makes.
+ * I'm trusting a caller's X-Request-Id when it looks sane so a proxy or frontend id ties straight through to our logs.
+ */
+
+use axum::http::HeaderMap;
+use std::future::Future;
+use tokio::task::JoinHandle;
+use tracing::Instrument;
+
+use crate::utils::Utils;
+
+/// Header the id is read from, echoed on responses, and sent on outbound requests
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Headers a caller or proxy may have put a request id in
+pub const REQUEST_ID_HEADERS: [&str; 2] = [REQUEST_ID_HEADER, "x-correlation-id"];
+
+const MAX_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static CORRELATION_ID: String;
+}
+
+/// The correlation id of the request being served, if any
+pub fn current() -> Option<String> {
+    CORRELATION_ID.try_with(Clone::clone).ok()
+}
+
+/// Run `future` with `id` as its correlation id
+pub async fn scope<F: Future>(id: String, future: F) -> F::Output {
+    CORRELATION_ID.scope(id, future).await
+}
+
+/// Spawn background work that keeps the caller's correlation id and tracing span
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let future = future.instrument(tracing::Span::current());
+    match current() {
+        Some(id) => tokio::spawn(CORRELATION_ID.scope(id, future)),
+        None => tokio::spawn(future),
+    }
+}
+
+/// The caller's id when it's usable, otherwise a fresh one
+pub fn from_headers(headers: &HeaderMap) -> String {
+    REQUEST_ID_HEADERS
+        .iter()
+        .find_map(|name| headers.get(*name))
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|id| is_valid(id))
+        .map_or_else(Utils::generate_correlation_id, str::to_string)
+}
+
+/// Ids end up in log lines and response headers, so only short printable ASCII is accepted
+fn is_valid(id: &str) -> bool {
+    !id.is_empty() && id.len() <= MAX_ID_LEN && id.bytes().all(|b| b.is_ascii_graphic())
+}
+
+/// Adds the current correlation id to outbound HTTP requests
+pub trait WithCorrelationId {
+    fn with_correlation_id(self) -> Self;
+}
+
+impl WithCorrelationId for reqwest::RequestBuilder {
+    fn with_correlation_id(self) -> Self {
+        match current() {
+            Some(id) => self.header(REQUEST_ID_HEADER, id),
+            None => self,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_id_follows_request_into_spawned_work() {
+        assert_eq!(current(), None);
+
+        let spawned = scope("req-42".to_string(), async { spawn(async { current() }) }).await;
+        assert_eq!(spawned.await.unwrap().as_deref(), Some("req-42"));
+        assert_eq!(current(), None);
+    }
+
+    #[test]
+    fn test_caller_ids_are_kept_only_when_sane() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-correlation-id", "abc-123".parse().unwrap());
+        assert_eq!(from_headers(&headers), "abc-123");
+
+        headers.insert(REQUEST_ID_HEADER, "has space".parse().unwrap());
+        assert_ne!(from_headers(&headers), "has space");
+
+        let generated = from_headers(&HeaderMap::new());
+        assert_eq!(generated.len(), 36);
+    }
+}
@@ -10,8 +10,19 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tracing::{error, warn};
 
+use crate::utils::correlation;
+
+/// Whether error responses include the context chain; only development turns this on
+static EXPOSE_CONTEXT: AtomicBool = AtomicBool::new(false);
+
+/// Include context chains in error responses, which leaks internal detail and is meant for development
+pub fn expose_error_context(enabled: bool) {
+    EXPOSE_CONTEXT.store(enabled, Ordering::Relaxed);
+}
+
 /// Custom Result type for consistent error handling throughout the application
 /// I'm providing a convenient alias that reduces boilerplate and ensures consistency
 pub type Result<T> = std::result::Result<T, AppError>;
@@ -70,6 +81,23 @@ pub enum AppError {
 
     #[error("Performance monitoring error: {0}")]
     PerformanceError(String),
+
+    /// A render estimated to cost more than a budget allows; the estimate always goes out in the response so clients can scale down
+    #[error("Render budget exceeded: {message}")]
+    RenderBudgetError {
+        message: String,
+        estimate: serde_json::Value,
+        /// Seconds until a spent budget refills; None when the render is too big for any budget
+        retry_after: Option<u64>,
+    },
+
+    /// Another error annotated with the operation that failed; classification always follows the wrapped error
+    #[error("{operation}: {source}")]
+    WithContext {
+        operation: String,
+        metadata: serde_json::Map<String, serde_json::Value>,
+        source: Box<AppError>,
+    },
 }
 
 /// Structured error response for API endpoints
@@ -108,7 +136,7 @@ pub enum ErrorCategory {
     Service,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub enum ErrorSeverity {
     Low,      // Non-critical, user can continue
     Medium,   // Some functionality affected
@@ -116,6 +144,16 @@ pub enum ErrorSeverity {
     Critical, // Service is down or severely compromised
 }
 
+/// What an error response leaves in its extensions for middleware that reports failures
+#[derive(Debug, Clone)]
+pub struct ReportedError {
+    pub code: String,
+    /// Full internal message, not the user-facing one
+    pub message: String,
+    pub severity: ErrorSeverity,
+    pub context: Option<serde_json::Value>,
+}
+
 impl AppError {
     /// Create a new database error with context
     /// I'm providing convenient constructors for common error scenarios
@@ -143,10 +181,39 @@ impl AppError {
         Self::InternalServerError(message.into())
     }
 
+    /// Wrap this error with the operation that failed and optional metadata
+    pub fn context<T: Into<String>>(self, operation: T, metadata: serde_json::Map<String, serde_json::Value>) -> Self {
+        Self::WithContext { operation: operation.into(), metadata, source: Box::new(self) }
+    }
+
+    /// The innermost error beneath any context layers
+    pub fn root_cause(&self) -> &AppError {
+        match self {
+            AppError::WithContext { source, .. } => source.root_cause(),
+            other => other,
+        }
+    }
+
+    /// Context layers, outermost first, with the root cause, or None for an unwrapped error
+    pub fn context_json(&self) -> Option<serde_json::Value> {
+        let mut layers = Vec::new();
+        let mut current = self;
+        while let AppError::WithContext { operation, metadata, source } = current {
+            layers.push(serde_json::json!({ "operation": operation, "metadata": metadata }));
+            current = source;
+        }
+
+        if layers.is_empty() {
+            return None;
+        }
+        Some(serde_json::json!({ "chain": layers, "cause": current.to_string() }))
+    }
+
     /// Get the appropriate HTTP status code for this error
     /// I'm mapping application errors to appropriate HTTP status codes
     pub fn status_code(&self) -> StatusCode {
         match self {
+            AppError::WithContext { source, .. } => source.status_code(),
             AppError::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             AppError::ExternalApiError(_) => StatusCode::BAD_GATEWAY,
             AppError::SerializationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
@@ -164,6 +231,8 @@ impl AppError {
             AppError::FractalComputationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
             AppError::GitHubApiError(_) => StatusCode::BAD_GATEWAY,
             AppError::PerformanceError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::RenderBudgetError { retry_after: Some(_), .. } => StatusCode::TOO_MANY_REQUESTS,
+            AppError::RenderBudgetError { retry_after: None, .. } => StatusCode::UNPROCESSABLE_ENTITY,
         }
     }
 
@@ -171,6 +240,7 @@ impl AppError {
     /// I'm categorizing errors for better monitoring and alerting
     pub fn category(&self) -> ErrorCategory {
         match self {
+            AppError::WithContext { source, .. } => source.category(),
             AppError::DatabaseError(_) | AppError::CacheError(_) => ErrorCategory::Database,
             AppError::ExternalApiError(_) | AppError::GitHubApiError(_) => ErrorCategory::ExternalApi,
             AppError::SerializationError(_) => ErrorCategory::Validation,
@@ -178,7 +248,7 @@ impl AppError {
             AppError::ValidationError(_) | AppError::BadRequestError(_) => ErrorCategory::UserInput,
             AppError::AuthenticationError(_) => ErrorCategory::Authentication,
             AppError::AuthorizationError(_) => ErrorCategory::Authorization,
-            AppError::RateLimitError(_) => ErrorCategory::RateLimit,
+            AppError::RateLimitError(_) | AppError::RenderBudgetError { .. } => ErrorCategory::RateLimit,
             AppError::NotFoundError(_) => ErrorCategory::NotFound,
             AppError::TimeoutError(_) => ErrorCategory::Timeout,
             AppError::ServiceUnavailableError(_) => ErrorCategory::Service,
@@ -192,9 +262,11 @@ impl AppError {
     /// I'm assessing error impact for appropriate alerting and response
     pub fn severity(&self) -> ErrorSeverity {
         match self {
+            AppError::WithContext { source, .. } => source.severity(),
             AppError::ValidationError(_)
             | AppError::BadRequestError(_)
-            | AppError::NotFoundError(_) => ErrorSeverity::Low,
+            | AppError::NotFoundError(_)
+            | AppError::RenderBudgetError { .. } => ErrorSeverity::Low,
 
             AppError::AuthenticationError(_)
             | AppError::AuthorizationError(_)
@@ -220,6 +292,7 @@ impl AppError {
     /// I'm identifying which errors might succeed on retry
     pub fn is_retryable(&self) -> bool {
         match self {
+            AppError::WithContext { source, .. } => source.is_retryable(),
             AppError::ExternalApiError(_)
             | AppError::GitHubApiError(_)
             | AppError::TimeoutError(_)
@@ -236,6 +309,7 @@ impl AppError {
             | AppError::ConfigurationError(_) => false,
 
             AppError::RateLimitError(_) => true, // Can retry after delay
+            AppError::RenderBudgetError { retry_after, .. } => retry_after.is_some(),
 
             _ => false,
         }
@@ -245,6 +319,7 @@ impl AppError {
     /// I'm providing clean, understandable messages for end users
     pub fn user_message(&self) -> String {
         match self {
+            AppError::WithContext { source, .. } => source.user_message(),
             AppError::DatabaseError(_) => "We're experiencing technical difficulties. Please try again later.".to_string(),
             AppError::ExternalApiError(_) => "External service is temporarily unavailable. Please try again.".to_string(),
             AppError::ValidationError(msg) => format!("Invalid input: {}", msg),
@@ -257,6 +332,7 @@ impl AppError {
             AppError::ServiceUnavailableError(_) => "Service is temporarily unavailable. Please try again later.".to_string(),
             AppError::FractalComputationError(msg) => format!("Fractal computation failed: {}", msg),
             AppError::GitHubApiError(_) => "GitHub service is temporarily unavailable.".to_string(),
+            AppError::RenderBudgetError { message, .. } => message.clone(),
             _ => "An unexpected error occurred. Please try again.".to_string(),
         }
     }
@@ -265,6 +341,7 @@ impl AppError {
     /// I'm providing unique error codes for easier support and debugging
     pub fn error_code(&self) -> String {
         match self {
+            AppError::WithContext { source, .. } => source.error_code(),
             AppError::DatabaseError(_) => "DB_ERROR".to_string(),
             AppError::ExternalApiError(_) => "EXT_API_ERROR".to_string(),
             AppError::SerializationError(_) => "SERIAL_ERROR".to_string(),
@@ -282,13 +359,17 @@ impl AppError {
             AppError::FractalComputationError(_) => "FRACTAL_ERROR".to_string(),
             AppError::GitHubApiError(_) => "GITHUB_API_ERROR".to_string(),
             AppError::PerformanceError(_) => "PERF_ERROR".to_string(),
+            AppError::RenderBudgetError { .. } => "RENDER_BUDGET_ERROR".to_string(),
         }
     }
 
     /// Log error with appropriate level and context
     /// I'm implementing intelligent error logging based on severity
     pub fn log_error(&self, context: Option<&str>) {
-        let context_info = context.map(|c| format!(" [{}]", c)).unwrap_or_default();
+        let mut context_info = context.map(|c| format!(" [{}]", c)).unwrap_or_default();
+        if let Some(chain) = self.context_json() {
+            context_info.push_str(&format!(" context={}", chain["chain"]));
+        }
 
         match self.severity() {
             ErrorSeverity::Critical => {
@@ -325,17 +406,30 @@ impl IntoResponse for AppError {
                 category: self.category(),
                 severity: self.severity(),
                 retryable: self.is_retryable(),
-                context: None, // Could be populated with additional context in the future
+                context: match self.root_cause() {
+                    AppError::RenderBudgetError { estimate, .. } => Some(estimate.clone()),
+                    _ => EXPOSE_CONTEXT.load(Ordering::Relaxed).then(|| self.context_json()).flatten(),
+                },
             },
             timestamp: chrono::Utc::now(),
-            request_id: None, // Could be populated from request middleware
+            request_id: correlation::current(),
             support_message: format!(
                 "If this problem persists, please contact support with error code: {}",
                 self.error_code()
             ),
         };
 
-        (status_code, Json(error_response)).into_response()
+        let mut response = (status_code, Json(error_response)).into_response();
+        if let AppError::RenderBudgetError { retry_after: Some(seconds), .. } = self.root_cause() {
+            response.headers_mut().insert(axum::http::header::RETRY_AFTER, axum::http::HeaderValue::from(*seconds));
+        }
+        response.extensions_mut().insert(ReportedError {
+            code: self.error_code(),
+            message: self.to_string(),
+            severity: self.severity(),
+            context: self.context_json(),
+        });
+        response
     }
 }
 
@@ -352,6 +446,9 @@ impl From<sqlx::Error> for AppError {
             }
             sqlx::Error::PoolTimedOut => AppError::TimeoutError("Database connection pool timeout".to_string()),
             sqlx::Error::PoolClosed => AppError::ServiceUnavailableError("Database pool is closed".to_string()),
+            sqlx::Error::Io(e) if e.kind() == std::io::ErrorKind::ConnectionRefused => {
+                AppError::ServiceUnavailableError(format!("Database unavailable: {}", e))
+            }
             _ => AppError::DatabaseError(format!("Database error: {}", err)),
         }
     }
@@ -398,6 +495,18 @@ impl From<redis::RedisError> for AppError {
     }
 }
 
+/// Conversion from the core engines' errors to AppError
+/// I'm mapping each core variant onto the HTTP category it already had before the split
+impl From<dark_performance_core::CoreError> for AppError {
+    fn from(err: dark_performance_core::CoreError) -> Self {
+        match err {
+            dark_performance_core::CoreError::ValidationError(message) => AppError::ValidationError(message),
+            dark_performance_core::CoreError::RenderError(message) => AppError::FractalComputationError(message),
+            dark_performance_core::CoreError::Cancelled => AppError::FractalComputationError("Render cancelled".to_string()),
+        }
+    }
+}
+
 /// Error context builder for adding additional information to errors
 /// I'm providing a way to enrich errors with context during error propagation
 pub struct ErrorContext {
@@ -422,13 +531,9 @@ impl ErrorContext {
         self
     }
 
+    /// Wrap `error` so the operation and metadata travel with it into logs and responses
     pub fn wrap_error(self, error: AppError) -> AppError {
-        // I'm preserving the original error type while adding context
-        // In a more sophisticated implementation, this could create a new error variant
-        // that contains the original error plus context
-        tracing::error!("Error in operation '{}': {} (metadata: {:?})",
-                        self.operation, error, self.metadata);
-        error
+        error.context(self.operation, self.metadata)
     }
 }
 
@@ -509,8 +614,49 @@ mod tests {
         .with_metadata("operation", "insert");
 
         let error = AppError::DatabaseError("Connection failed".to_string());
-        let _wrapped_error = context.wrap_error(error);
-        // The wrapped error should contain the original error
-        // In a real implementation, we might want to verify the context is preserved
+        let wrapped = context.wrap_error(error);
+
+        // Classification follows the original error
+        assert_eq!(wrapped.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(wrapped.error_code(), "DB_ERROR");
+        assert_eq!(wrapped.severity(), ErrorSeverity::High);
+        assert!(matches!(wrapped.root_cause(), AppError::DatabaseError(_)));
+        assert_eq!(wrapped.to_string(), "database_operation: Database error: Connection failed");
+
+        let context = wrapped.context_json().unwrap();
+        assert_eq!(context["chain"][0]["operation"], "database_operation");
+        assert_eq!(context["chain"][0]["metadata"]["table"], "users");
+        assert_eq!(context["cause"], "Database error: Connection failed");
+    }
+
+    #[test]
+    fn test_nested_context_keeps_source_chain() {
+        let result: Result<()> = Err(AppError::NotFoundError("palette 7".to_string()));
+        let error = result
+            .with_context(|| ErrorContext::new("load_palette").with_metadata("id", 7))
+            .with_context(|| ErrorContext::new("render_fractal"))
+            .unwrap_err();
+
+        assert_eq!(error.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(error.context_json().unwrap()["chain"][1]["operation"], "load_palette");
+        assert!(std::error::Error::source(&error).is_some());
+        assert!(AppError::NotFoundError("x".to_string()).context_json().is_none());
+    }
+
+    #[test]
+    fn test_render_budget_errors_always_carry_their_estimate() {
+        let spent = AppError::RenderBudgetError {
+            message: "over budget".to_string(),
+            estimate: serde_json::json!({ "estimate": { "cost": 900 } }),
+            retry_after: Some(12),
+        };
+        assert!(spent.is_retryable());
+        let response = spent.into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[axum::http::header::RETRY_AFTER], "12");
+
+        let too_big = AppError::RenderBudgetError { message: "too big".to_string(), estimate: serde_json::json!({}), retry_after: None };
+        assert!(!too_big.is_retryable());
+        assert_eq!(too_big.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
     }
 }
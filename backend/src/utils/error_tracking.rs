@@ -0,0 +1,121 @@
+/*
+ * Optional Sentry reporting for High and Critical severity errors and for panics, switched on by SENTRY_DSN.
+ * I'm reporting from a snapshot the error leaves on its response so the middleware can attach request id, route, and principal.
+ */
+
+use sentry::protocol::{Event, Exception, Level, User, Value};
+use sentry::{ClientInitGuard, Hub};
+use tracing::{info, warn};
+
+use crate::utils::config::Config;
+use crate::utils::error::{ErrorSeverity, ReportedError};
+
+/// Request details attached to a reported error
+#[derive(Debug, Clone, Default)]
+pub struct RequestContext {
+    pub request_id: Option<String>,
+    pub method: String,
+    pub route: String,
+    pub principal: Option<String>,
+}
+
+/// Start the Sentry client; keep the guard alive for the life of the process so queued events flush on exit
+pub fn init(config: &Config) -> Option<ClientInitGuard> {
+    let dsn = config.sentry_dsn.as_deref()?;
+    let dsn = match dsn.parse() {
+        Ok(dsn) => dsn,
+        Err(e) => {
+            warn!("Ignoring invalid SENTRY_DSN: {}", e);
+            return None;
+        }
+    };
+
+    let guard = sentry::init(sentry::ClientOptions {
+        dsn: Some(dsn),
+        release: sentry::release_name!(),
+        environment: Some(format!("{:?}", config.environment).to_lowercase().into()),
+        sample_rate: config.sentry_sample_rate,
+        attach_stacktrace: true,
+        ..Default::default()
+    });
+    info!("Error tracking enabled (sample rate {})", config.sentry_sample_rate);
+    Some(guard)
+}
+
+pub fn is_enabled() -> bool {
+    Hub::main().client().is_some_and(|client| client.is_enabled())
+}
+
+/// Whether an error is serious enough to page someone about
+pub fn should_report(severity: &ErrorSeverity) -> bool {
+    matches!(severity, ErrorSeverity::High | ErrorSeverity::Critical)
+}
+
+/// Send one failed request to Sentry
+pub fn capture(error: &ReportedError, request: &RequestContext) {
+    Hub::main().capture_event(build_event(error, request));
+}
+
+fn build_event(error: &ReportedError, request: &RequestContext) -> Event<'static> {
+    let mut event = Event {
+        level: match error.severity {
+            ErrorSeverity::Critical => Level::Fatal,
+            _ => Level::Error,
+        },
+        exception: vec![Exception {
+            ty: error.code.clone(),
+            value: Some(error.message.clone()),
+            ..Default::default()
+        }]
+        .into(),
+        transaction: Some(format!("{} {}", request.method, request.route)),
+        user: request.principal.as_ref().map(|principal| User {
+            id: Some(principal.clone()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    event.tags.insert("error_code".to_string(), error.code.clone());
+    event.tags.insert("route".to_string(), request.route.clone());
+    event.tags.insert("method".to_string(), request.method.clone());
+    if let Some(request_id) = &request.request_id {
+        event.tags.insert("request_id".to_string(), request_id.clone());
+    }
+    if let Some(context) = &error.context {
+        event.extra.insert("context".to_string(), Value::from(context.clone()));
+    }
+    event
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_carries_request_context() {
+        let error = ReportedError {
+            code: "DB_ERROR".to_string(),
+            message: "load_settings: Database error: connection reset".to_string(),
+            severity: ErrorSeverity::High,
+            context: Some(serde_json::json!({ "chain": [{ "operation": "load_settings" }] })),
+        };
+        let request = RequestContext {
+            request_id: Some("req-1".to_string()),
+            method: "GET".to_string(),
+            route: "/api/admin/settings".to_string(),
+            principal: Some("admin".to_string()),
+        };
+
+        let event = build_event(&error, &request);
+        assert_eq!(event.level, Level::Error);
+        assert_eq!(event.transaction.as_deref(), Some("GET /api/admin/settings"));
+        assert_eq!(event.tags["request_id"], "req-1");
+        assert_eq!(event.user.unwrap().id.as_deref(), Some("admin"));
+        assert_eq!(event.exception.values[0].ty, "DB_ERROR");
+        assert!(event.extra.contains_key("context"));
+
+        assert!(should_report(&ErrorSeverity::Critical));
+        assert!(!should_report(&ErrorSeverity::Medium));
+    }
+}
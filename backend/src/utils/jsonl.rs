@@ -0,0 +1,125 @@
+/*
+ * JSON Lines helpers for streaming request ingestion and chunked export responses.
+ * I'm splitting bodies incrementally so a multi-gigabyte upload never has to sit in memory at once.
+ */
+
+use axum::{
+    body::{Body, Bytes},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use futures::{Stream, StreamExt};
+use serde::Serialize;
+
+use crate::utils::error::{AppError, Result};
+
+pub const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// Upper bound on a single line so one malformed record can't exhaust memory
+pub const MAX_LINE_BYTES: usize = 1024 * 1024;
+
+/// Incremental newline splitter fed with body chunks as they arrive
+/// I'm only buffering the current partial line, which MAX_LINE_BYTES keeps bounded
+#[derive(Debug, Default)]
+pub struct JsonLinesDecoder {
+    buffer: Vec<u8>,
+    max_line_bytes: usize,
+}
+
+impl JsonLinesDecoder {
+    pub fn new() -> Self {
+        Self::with_max_line_bytes(MAX_LINE_BYTES)
+    }
+
+    pub fn with_max_line_bytes(max_line_bytes: usize) -> Self {
+        Self { buffer: Vec::new(), max_line_bytes }
+    }
+
+    /// Feed a chunk and return every complete, non-blank line it finished
+    pub fn push(&mut self, chunk: &[u8]) -> Result<Vec<Vec<u8>>> {
+        let mut lines = Vec::new();
+        let mut rest = chunk;
+
+        while let Some(pos) = rest.iter().position(|b| *b == b'\n') {
+            self.buffer.extend_from_slice(&rest[..pos]);
+            self.check_length()?;
+            let line = std::mem::take(&mut self.buffer);
+            if !is_blank(&line) {
+                lines.push(line);
+            }
+            rest = &rest[pos + 1..];
+        }
+
+        self.buffer.extend_from_slice(rest);
+        self.check_length()?;
+        Ok(lines)
+    }
+
+    /// Return the trailing line when the body doesn't end in a newline
+    pub fn finish(&mut self) -> Option<Vec<u8>> {
+        let line = std::mem::take(&mut self.buffer);
+        (!is_blank(&line)).then_some(line)
+    }
+
+    fn check_length(&self) -> Result<()> {
+        if self.buffer.len() > self.max_line_bytes {
+            return Err(AppError::ValidationError(format!(
+                "JSON Lines record exceeds {} bytes", self.max_line_bytes
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn is_blank(line: &[u8]) -> bool {
+    line.iter().all(u8::is_ascii_whitespace)
+}
+
+/// Serialize a value as one JSON Lines record including the trailing newline
+pub fn encode_line<T: Serialize>(value: &T) -> Result<Bytes> {
+    let mut line = serde_json::to_vec(value)?;
+    line.push(b'\n');
+    Ok(Bytes::from(line))
+}
+
+/// Wrap a stream of encoded records in a chunked `application/x-ndjson` response
+pub fn ndjson_response<S>(stream: S) -> Response
+where
+    S: Stream<Item = Result<Bytes>> + Send + 'static,
+{
+    let body = Body::from_stream(stream.map(|chunk| chunk.map_err(std::io::Error::other)));
+
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, HeaderValue::from_static(NDJSON_CONTENT_TYPE)),
+            (header::CACHE_CONTROL, HeaderValue::from_static("no-store")),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decoder_handles_split_chunks() {
+        let mut decoder = JsonLinesDecoder::new();
+        assert!(decoder.push(b"{\"a\":").unwrap().is_empty());
+
+        let lines = decoder.push(b"1}\n\n{\"b\":2}\n{\"c\"").unwrap();
+        assert_eq!(lines, vec![b"{\"a\":1}".to_vec(), b"{\"b\":2}".to_vec()]);
+
+        decoder.push(b":3}").unwrap();
+        assert_eq!(decoder.finish(), Some(b"{\"c\":3}".to_vec()));
+        assert_eq!(decoder.finish(), None);
+    }
+
+    #[test]
+    fn test_decoder_rejects_oversized_lines() {
+        let mut decoder = JsonLinesDecoder::with_max_line_bytes(8);
+        assert!(decoder.push(b"0123456789").is_err());
+    }
+}
@@ -0,0 +1,372 @@
+/*
+ * Runtime-reloadable configuration published through an ArcSwap so handlers always see the latest tunables.
+ * I'm only letting a reload or a database override touch settings that are safe to change under load; connection strings, ports, and pool sizes still need a restart.
+ */
+
+use arc_swap::ArcSwap;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use crate::utils::config::Config;
+use crate::utils::config_source::ConfigSource;
+use crate::utils::error::{AppError, Result};
+
+/// Shared handle to the current configuration
+/// I'm cloning cheaply across AppState copies; every clone sees the same swaps
+#[derive(Clone)]
+pub struct LiveConfig {
+    current: Arc<ArcSwap<Config>>,
+    layers: Arc<Mutex<Layers>>,
+    changes: watch::Sender<Arc<Config>>,
+}
+
+/// Environment and file values underneath the overrides stored in the database
+struct Layers {
+    base: Config,
+    overrides: BTreeMap<String, serde_json::Value>,
+}
+
+impl std::fmt::Debug for LiveConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LiveConfig")
+            .field("log_level", &self.current.load().log_level)
+            .finish_non_exhaustive()
+    }
+}
+
+impl LiveConfig {
+    pub fn new(config: Config) -> Self {
+        let current = Arc::new(config.clone());
+        let (changes, _) = watch::channel(current.clone());
+        Self {
+            current: Arc::new(ArcSwap::new(current)),
+            layers: Arc::new(Mutex::new(Layers { base: config, overrides: BTreeMap::new() })),
+            changes,
+        }
+    }
+
+    /// Snapshot of the configuration as of now; hold it for one request, not across reloads
+    pub fn load(&self) -> Arc<Config> {
+        self.current.load_full()
+    }
+
+    /// Receive every configuration published by a reload
+    pub fn subscribe(&self) -> watch::Receiver<Arc<Config>> {
+        self.changes.subscribe()
+    }
+
+    /// The environment and file values, before runtime overrides
+    pub fn base(&self) -> Config {
+        self.lock().base.clone()
+    }
+
+    /// Re-read environment and config files, then publish any tunable that changed
+    pub fn reload(&self) -> Result<Vec<&'static str>> {
+        self.apply(Config::load()?)
+    }
+
+    /// Copy the tunable settings from `candidate` onto the environment layer
+    /// I'm validating the merged result before swapping so a bad edit leaves the running values untouched
+    pub fn apply(&self, candidate: Config) -> Result<Vec<&'static str>> {
+        let mut layers = self.lock();
+        let mut base = layers.base.clone();
+        copy_tunables(&candidate, &mut base);
+
+        let changed = self.publish(overlay(&base, &layers.overrides)?)?;
+        layers.base = base;
+        Ok(changed)
+    }
+
+    /// Replace the runtime overrides layered on top of environment and file values
+    pub fn set_overrides(&self, overrides: BTreeMap<String, serde_json::Value>) -> Result<Vec<&'static str>> {
+        let mut layers = self.lock();
+        let changed = self.publish(overlay(&layers.base, &overrides)?)?;
+        layers.overrides = overrides;
+        Ok(changed)
+    }
+
+    /// Check that overriding `key` with `value` would produce a valid configuration, without applying it
+    pub fn check_override(&self, key: &str, value: &serde_json::Value) -> Result<()> {
+        let layers = self.lock();
+        let mut overrides = layers.overrides.clone();
+        overrides.insert(key.to_string(), value.clone());
+        overlay(&layers.base, &overrides)?.validate()
+    }
+
+    /// The current configuration with `overrides` on top, without publishing it; used for settings scoped to one tenant
+    pub fn load_with(&self, overrides: &BTreeMap<String, serde_json::Value>) -> Result<Arc<Config>> {
+        let current = self.load();
+        if overrides.is_empty() {
+            return Ok(current);
+        }
+        overlay(&current, overrides).map(Arc::new)
+    }
+
+    fn publish(&self, next: Config) -> Result<Vec<&'static str>> {
+        let changed = changed_tunables(&self.current.load(), &next);
+        if changed.is_empty() {
+            return Ok(changed);
+        }
+
+        next.validate()?;
+        let next = Arc::new(next);
+        self.current.store(next.clone());
+        self.changes.send_replace(next);
+
+        info!("Configuration reloaded, updated: {}", changed.join(", "));
+        Ok(changed)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Layers> {
+        self.layers.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Reload on SIGHUP and whenever a CONFIG_FILE changes on disk
+    /// I'm polling modification times rather than using inotify so the same code works on every platform and mount type
+    pub fn spawn_watcher(self, poll_interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let files = ConfigSource::layered().map(|s| s.files().to_vec()).unwrap_or_default();
+            let mut last_modified = modified_times(&files);
+            let mut poll = (!poll_interval.is_zero() && !files.is_empty()).then(|| tokio::time::interval(poll_interval));
+            let mut hangup = hangup_signal();
+
+            loop {
+                let trigger = tokio::select! {
+                    _ = tick(&mut poll) => {
+                        let modified = modified_times(&files);
+                        if modified == last_modified {
+                            continue;
+                        }
+                        last_modified = modified;
+                        "config file change"
+                    }
+                    _ = recv_hangup(&mut hangup) => "SIGHUP",
+                };
+
+                match self.reload() {
+                    Ok(changed) if changed.is_empty() => info!("Configuration reload ({}) found no tunable changes", trigger),
+                    Ok(_) => {}
+                    Err(e) => warn!("Configuration reload ({}) rejected, keeping current settings: {}", trigger, e),
+                }
+            }
+        })
+    }
+}
+
+/// Declares the settings a reload or runtime override may change without restarting the process
+/// I'm generating every accessor from one list so a new tunable can't be readable but not settable
+macro_rules! tunables {
+    ($($field:ident),* $(,)?) => {
+        pub const TUNABLE_SETTINGS: &[&str] = &[$(stringify!($field)),*];
+
+        fn copy_tunables(from: &Config, into: &mut Config) {
+            $( into.$field = from.$field.clone(); )*
+        }
+
+        fn changed_tunables(before: &Config, after: &Config) -> Vec<&'static str> {
+            let mut changed = Vec::new();
+            $(
+                if before.$field != after.$field {
+                    changed.push(stringify!($field));
+                }
+            )*
+            changed
+        }
+
+        /// Current value of a tunable as JSON, or None for names that aren't tunable
+        pub fn tunable_value(config: &Config, key: &str) -> Option<serde_json::Value> {
+            match key {
+                $( stringify!($field) => serde_json::to_value(&config.$field).ok(), )*
+                _ => None,
+            }
+        }
+
+        fn set_tunable(config: &mut Config, key: &str, value: &serde_json::Value) -> Result<()> {
+            match key {
+                $(
+                    stringify!($field) => {
+                        config.$field = serde_json::from_value(value.clone()).map_err(|e| {
+                            AppError::ValidationError(format!("Invalid value for {}: {}", key, e))
+                        })?;
+                    }
+                )*
+                _ => return Err(AppError::ValidationError(format!("{} is not a runtime-tunable setting", key))),
+            }
+            Ok(())
+        }
+    };
+}
+
+tunables!(
+    log_level,
+    log_sample_paths,
+    rate_limit_enabled,
+    rate_limit_requests_per_minute,
+    fractal_rate_limit_per_minute,
+    usage_hourly_quota,
+    github_rate_limit_requests,
+    fractal_max_width,
+    fractal_max_height,
+    fractal_max_iterations,
+    fractal_max_zoom,
+    fractal_auto_iterations_base,
+    fractal_auto_iterations_per_decade,
+    fractal_auto_iterations_exponent,
+    fractal_min_multibrot_power,
+    fractal_max_multibrot_power,
+    fractal_computation_timeout,
+    fractal_cache_ttl,
+    fractal_thumbnail_cache_ttl,
+    cache_default_ttl,
+    github_cache_ttl,
+    health_cpu_degraded_percent,
+    health_cpu_unhealthy_percent,
+    health_memory_degraded_percent,
+    health_memory_unhealthy_percent,
+    health_disk_degraded_percent,
+    health_disk_unhealthy_percent,
+    health_github_min_remaining_percent,
+    health_check_weights,
+);
+
+fn overlay(base: &Config, overrides: &BTreeMap<String, serde_json::Value>) -> Result<Config> {
+    let mut config = base.clone();
+    for (key, value) in overrides {
+        set_tunable(&mut config, key, value)?;
+    }
+    Ok(config)
+}
+
+fn modified_times(files: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    files
+        .iter()
+        .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+async fn tick(poll: &mut Option<tokio::time::Interval>) {
+    match poll {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(unix)]
+type HangupSignal = Option<tokio::signal::unix::Signal>;
+#[cfg(not(unix))]
+type HangupSignal = ();
+
+#[cfg(unix)]
+fn hangup_signal() -> HangupSignal {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    signal(SignalKind::hangup())
+        .map_err(|e| warn!("Failed to install SIGHUP handler, config reload on signal disabled: {}", e))
+        .ok()
+}
+
+#[cfg(not(unix))]
+fn hangup_signal() -> HangupSignal {}
+
+#[cfg(unix)]
+async fn recv_hangup(hangup: &mut HangupSignal) {
+    match hangup {
+        Some(signal) => {
+            if signal.recv().await.is_none() {
+                *hangup = None;
+            }
+        }
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(not(unix))]
+async fn recv_hangup(_hangup: &mut HangupSignal) {
+    std::future::pending().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::config::ConfigBuilder;
+
+    fn base_config() -> Config {
+        ConfigBuilder::new()
+            .database_url("postgresql://localhost/test")
+            .github_token("token")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_apply_swaps_only_tunables() {
+        let live = LiveConfig::new(base_config());
+        let mut receiver = live.subscribe();
+
+        let mut candidate = base_config();
+        candidate.fractal_max_width = 2048;
+        candidate.log_level = "debug".to_string();
+        candidate.port = 9999;
+
+        let changed = live.apply(candidate).unwrap();
+        assert_eq!(changed, vec!["log_level", "fractal_max_width"]);
+        assert_eq!(live.load().fractal_max_width, 2048);
+        assert_eq!(live.load().port, base_config().port);
+        assert!(receiver.has_changed().unwrap());
+        assert_eq!(receiver.borrow_and_update().log_level, "debug");
+
+        let mut same = base_config();
+        same.fractal_max_width = 2048;
+        same.log_level = "debug".to_string();
+        assert!(live.apply(same).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_reload_keeps_current_values() {
+        let live = LiveConfig::new(base_config());
+
+        let mut candidate = base_config();
+        candidate.fractal_max_width = 0;
+
+        assert!(live.apply(candidate).is_err());
+        assert_eq!(live.load().fractal_max_width, base_config().fractal_max_width);
+    }
+
+    #[test]
+    fn test_overrides_win_over_reloaded_values() {
+        let live = LiveConfig::new(base_config());
+
+        let overrides = BTreeMap::from([("fractal_max_iterations".to_string(), serde_json::json!(2000))]);
+        assert_eq!(live.set_overrides(overrides).unwrap(), vec!["fractal_max_iterations"]);
+
+        let mut candidate = base_config();
+        candidate.fractal_max_iterations = 5000;
+        candidate.cache_default_ttl = 60;
+        assert_eq!(live.apply(candidate).unwrap(), vec!["cache_default_ttl"]);
+        assert_eq!(live.load().fractal_max_iterations, 2000);
+
+        assert_eq!(live.set_overrides(BTreeMap::new()).unwrap(), vec!["fractal_max_iterations"]);
+        assert_eq!(live.load().fractal_max_iterations, 5000);
+
+        assert!(live.check_override("port", &serde_json::json!(1)).is_err());
+        assert!(live.check_override("fractal_max_width", &serde_json::json!("wide")).is_err());
+        assert!(live.check_override("fractal_max_width", &serde_json::json!(10)).is_err());
+        assert!(live.check_override("fractal_max_width", &serde_json::json!(1024)).is_ok());
+    }
+
+    #[test]
+    fn test_scoped_overrides_are_not_published() {
+        let live = LiveConfig::new(base_config());
+        let overrides = BTreeMap::from([("fractal_rate_limit_per_minute".to_string(), serde_json::json!(3))]);
+
+        assert_eq!(live.load_with(&overrides).unwrap().fractal_rate_limit_per_minute, 3);
+        assert_eq!(live.load().fractal_rate_limit_per_minute, base_config().fractal_rate_limit_per_minute);
+        assert!(live.load_with(&BTreeMap::from([("port".to_string(), serde_json::json!(1))])).is_err());
+    }
+}
@@ -0,0 +1,319 @@
+/*
+ * Tracing subscriber setup and per-request spans, in plain text for terminals or JSON lines for Loki and ELK.
+ * I'm putting request id, route, status, and latency on the request span so every line logged inside a handler carries them.
+ */
+
+use axum::extract::MatchedPath;
+use axum::http::{Request, Response};
+use chrono::Utc;
+use once_cell::sync::OnceCell;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tower_http::classify::{ServerErrorsAsFailures, SharedClassifier};
+use tower_http::trace::{DefaultOnRequest, MakeSpan, OnResponse, TraceLayer};
+use tracing::{field, level_filters::LevelFilter, Span};
+use tracing_subscriber::{layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Registry};
+
+use crate::models::logging::{LogFilterOverride, LogFilterStatus};
+use crate::utils::config::LogFormat;
+use crate::utils::correlation::REQUEST_ID_HEADERS;
+use crate::utils::error::{AppError, Result};
+
+/// Handle for swapping the log filter while the server runs
+pub type LogFilterHandle = reload::Handle<EnvFilter, Registry>;
+
+/// Install the global subscriber
+/// I'm keeping the filter reloadable so hot-reloaded log levels take effect without a restart
+pub fn init(format: LogFormat, directives: &str) -> LogFilterHandle {
+    let filter = EnvFilter::try_new(directives).unwrap_or_else(|_| EnvFilter::new("info"));
+    let (filter, handle) = reload::Layer::new(filter);
+
+    let (plain, json) = match format {
+        LogFormat::Plain => (Some(tracing_subscriber::fmt::layer()), None),
+        LogFormat::Json => (
+            None,
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .flatten_event(true)
+                    .with_current_span(true)
+                    // The span list keeps the request's fields on lines logged inside nested service spans
+                    .with_span_list(true),
+            ),
+        ),
+    };
+
+    tracing_subscriber::registry().with(filter).with(plain).with(json).init();
+    handle
+}
+
+/// HTTP trace layer that opens a `request` span per request and logs its outcome
+pub fn http_trace_layer() -> TraceLayer<SharedClassifier<ServerErrorsAsFailures>, RequestSpan, DefaultOnRequest, ResponseLog> {
+    TraceLayer::new_for_http().make_span_with(RequestSpan).on_response(ResponseLog)
+}
+
+/// Span carrying the request's identity, with status and latency filled in on completion
+#[derive(Debug, Clone, Copy)]
+pub struct RequestSpan;
+
+impl<B> MakeSpan<B> for RequestSpan {
+    fn make_span(&mut self, request: &Request<B>) -> Span {
+        let route = request
+            .extensions()
+            .get::<MatchedPath>()
+            .map_or_else(|| request.uri().path(), MatchedPath::as_str);
+        let request_id = REQUEST_ID_HEADERS
+            .iter()
+            .find_map(|name| request.headers().get(*name))
+            .and_then(|value| value.to_str().ok());
+
+        tracing::info_span!(
+            "request",
+            method = %request.method(),
+            route,
+            path = request.uri().path(),
+            request_id,
+            status = field::Empty,
+            latency_ms = field::Empty,
+        )
+    }
+}
+
+/// One line per finished request, at warn for server errors
+#[derive(Debug, Clone, Copy)]
+pub struct ResponseLog;
+
+/// Response marker for a request whose completion line was sampled out
+#[derive(Debug, Clone, Copy)]
+pub struct SkipRequestLog;
+
+impl<B> OnResponse<B> for ResponseLog {
+    fn on_response(self, response: &Response<B>, latency: Duration, span: &Span) {
+        let status = response.status().as_u16();
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+        span.record("status", status);
+        span.record("latency_ms", latency_ms);
+
+        if response.extensions().get::<SkipRequestLog>().is_some() {
+            return;
+        }
+        if response.status().is_server_error() {
+            tracing::warn!(status, latency_ms, "request failed");
+        } else {
+            tracing::info!(status, latency_ms, "request completed");
+        }
+    }
+}
+
+/// Share of completion lines kept for a path, from the longest matching LOG_SAMPLE_PATHS prefix
+pub fn sample_rate(rules: &BTreeMap<String, f64>, path: &str) -> Option<f64> {
+    rules
+        .iter()
+        .filter(|(prefix, _)| path.starts_with(prefix.as_str()))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, rate)| *rate)
+}
+
+/// Runtime control over the log filter: the configured log_level plus temporary per-target overrides
+/// I'm recomposing the whole filter on every change so an expiring override falls back to whatever the base is by then
+#[derive(Clone)]
+pub struct LogControl {
+    inner: Arc<LogControlInner>,
+}
+
+struct LogControlInner {
+    handle: OnceCell<LogFilterHandle>,
+    state: Mutex<FilterState>,
+}
+
+#[derive(Clone)]
+struct FilterState {
+    base: String,
+    overrides: BTreeMap<String, LogFilterOverride>,
+}
+
+impl LogControl {
+    pub fn new(base: &str) -> Self {
+        Self {
+            inner: Arc::new(LogControlInner {
+                handle: OnceCell::new(),
+                state: Mutex::new(FilterState { base: base.to_string(), overrides: BTreeMap::new() }),
+            }),
+        }
+    }
+
+    /// Connect to the installed subscriber; until then changes are tracked but not applied
+    pub fn attach(&self, handle: LogFilterHandle) -> Result<()> {
+        if self.inner.handle.set(handle).is_err() {
+            return Err(AppError::ConfigurationError("Log filter handle is already attached".to_string()));
+        }
+        let state = self.lock().clone();
+        self.apply(&state)
+    }
+
+    pub fn status(&self) -> LogFilterStatus {
+        let state = self.lock();
+        LogFilterStatus {
+            base: state.base.clone(),
+            overrides: state.overrides.values().cloned().collect(),
+            effective: compose(&state.base, &state.overrides),
+        }
+    }
+
+    /// Replace the configured directives, keeping any overrides in place
+    pub fn set_base(&self, directives: &str) -> Result<()> {
+        self.update(|state| {
+            state.base = directives.to_string();
+            Ok(())
+        })
+    }
+
+    /// Set one target's level, lapsing at `expires_at` if given
+    pub fn set_override(&self, filter: LogFilterOverride) -> Result<LogFilterStatus> {
+        validate_target(&filter.target)?;
+        filter.level.parse::<LevelFilter>().map_err(|_| {
+            AppError::ValidationError(format!(
+                "Invalid log level '{}', expected trace, debug, info, warn, error, or off", filter.level
+            ))
+        })?;
+        let expires_at = filter.expires_at;
+
+        self.update(|state| {
+            state.overrides.insert(filter.target.clone(), filter);
+            Ok(())
+        })?;
+
+        if let Some(expires_at) = expires_at {
+            let control = self.clone();
+            let delay = (expires_at - Utc::now()).to_std().unwrap_or_default();
+            tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                control.expire_overrides();
+            });
+        }
+        Ok(self.status())
+    }
+
+    /// Drop a target's override; removing one that isn't set is not an error
+    pub fn clear_override(&self, target: &str) -> Result<LogFilterStatus> {
+        self.update(|state| {
+            state.overrides.remove(target);
+            Ok(())
+        })?;
+        Ok(self.status())
+    }
+
+    fn expire_overrides(&self) {
+        let now = Utc::now();
+        let result = self.update(|state| {
+            state.overrides.retain(|target, filter| match filter.expires_at {
+                Some(expires_at) if expires_at <= now => {
+                    tracing::info!("Log level override for {} expired", target);
+                    false
+                }
+                _ => true,
+            });
+            Ok(())
+        });
+        if let Err(e) = result {
+            tracing::warn!("Failed to drop expired log level overrides: {}", e);
+        }
+    }
+
+    /// Apply a change to a copy of the state and keep it only if the resulting filter is valid
+    fn update(&self, change: impl FnOnce(&mut FilterState) -> Result<()>) -> Result<()> {
+        let mut state = self.lock();
+        let mut next = state.clone();
+        change(&mut next)?;
+        self.apply(&next)?;
+        *state = next;
+        Ok(())
+    }
+
+    fn apply(&self, state: &FilterState) -> Result<()> {
+        let directives = compose(&state.base, &state.overrides);
+        let filter = EnvFilter::try_new(&directives)
+            .map_err(|e| AppError::ValidationError(format!("Invalid log filter '{}': {}", directives, e)))?;
+
+        if let Some(handle) = self.inner.handle.get() {
+            handle
+                .reload(filter)
+                .map_err(|e| AppError::InternalServerError(format!("Failed to apply log filter: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, FilterState> {
+        self.inner.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Base directives with each overridden target's directive replaced
+fn compose(base: &str, overrides: &BTreeMap<String, LogFilterOverride>) -> String {
+    base.split(',')
+        .map(str::trim)
+        .filter(|directive| !directive.is_empty())
+        .filter(|directive| {
+            let target = directive.rsplit_once('=').map_or("", |(target, _)| target);
+            !overrides.contains_key(target)
+        })
+        .map(str::to_string)
+        .chain(overrides.values().map(|filter| format!("{}={}", filter.target, filter.level)))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn validate_target(target: &str) -> Result<()> {
+    let valid = !target.is_empty()
+        && target.split("::").all(|segment| {
+            !segment.is_empty() && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::ValidationError(format!(
+            "Invalid log target '{}', expected a module path such as dark_performance_backend::services",
+            target
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(target: &str, level: &str) -> LogFilterOverride {
+        LogFilterOverride {
+            target: target.to_string(),
+            level: level.to_string(),
+            expires_at: None,
+            set_by: "admin".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_overrides_replace_matching_base_directives() {
+        let control = LogControl::new("info,sqlx=warn");
+        let status = control.set_override(filter("sqlx", "debug")).unwrap();
+        assert_eq!(status.effective, "info,sqlx=debug");
+
+        control.set_override(filter("dark_performance_backend::services", "trace")).unwrap();
+        control.set_base("warn").unwrap();
+        assert_eq!(control.status().effective, "warn,dark_performance_backend::services=trace,sqlx=debug");
+
+        assert_eq!(control.clear_override("sqlx").unwrap().effective, "warn,dark_performance_backend::services=trace");
+        assert!(control.set_override(filter("sqlx", "loud")).is_err());
+        assert!(control.set_override(filter("bad target", "debug")).is_err());
+        assert!(control.set_base("info,sqlx=noisy").is_err());
+        assert_eq!(control.status().base, "warn");
+    }
+
+    #[test]
+    fn test_sample_rate_uses_longest_prefix() {
+        let rules = BTreeMap::from([("/api/health".to_string(), 0.1), ("/api/health/live".to_string(), 0.0)]);
+        assert_eq!(sample_rate(&rules, "/api/health/ready"), Some(0.1));
+        assert_eq!(sample_rate(&rules, "/api/health/live"), Some(0.0));
+        assert_eq!(sample_rate(&rules, "/api/fractals"), None);
+    }
+}
@@ -3,17 +3,34 @@
  */
 
 
+pub mod circuit_breaker;
 pub mod config;
+pub mod config_schema;
+pub mod config_source;
+pub mod correlation;
+pub mod encryption;
 pub mod error;
-pub mod metrics;
+pub mod error_tracking;
+pub mod jsonl;
+pub mod live_config;
+pub mod logging;
+pub mod network;
+pub mod rate_limit;
+pub mod redis_connection;
+pub mod retry;
+pub mod secrets;
+pub mod shutdown;
+pub mod uptime;
+
+pub use dark_performance_core::metrics;
 
 pub use config::Config;
 pub use error::{AppError, Result, ErrorContext, ResultExt};
 pub use metrics::{MetricsCollector, PerformanceTimer, TimingGuard};
+pub use retry::{retry_with_backoff, RetryConfig};
 
 use serde::{Deserialize, Serialize};
-use tracing::{info, warn};
-use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use chrono::{DateTime, Utc};
 
 pub struct Utils;
@@ -120,6 +137,45 @@ impl Utils {
         Ok((number * multiplier as f64) as u64)
     }
 
+    /// Parse durations like "500ms", "30s", "5m", "1h30m", or "7d"; a bare number means seconds
+    pub fn parse_duration(duration_str: &str) -> std::result::Result<Duration, AppError> {
+        let trimmed = duration_str.trim().to_lowercase();
+        if trimmed.is_empty() {
+            return Err(AppError::ConfigurationError("Empty duration string".to_string()));
+        }
+        if let Ok(seconds) = trimmed.parse::<u64>() {
+            return Ok(Duration::from_secs(seconds));
+        }
+
+        let mut total = Duration::ZERO;
+        let mut rest = trimmed.as_str();
+        while !rest.is_empty() {
+            let digits = rest.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(rest.len());
+            let units = rest[digits..].find(|c: char| c.is_ascii_digit()).map_or(rest.len(), |i| digits + i);
+            let (number_part, unit_part) = (&rest[..digits], rest[digits..units].trim());
+
+            let number: f64 = number_part.parse()
+                .map_err(|_| AppError::ConfigurationError(format!("Invalid duration: {}", duration_str)))?;
+            let unit_seconds = match unit_part {
+                "ms" => 0.001,
+                "s" | "sec" | "secs" => 1.0,
+                "m" | "min" | "mins" => 60.0,
+                "h" | "hr" | "hrs" => 3600.0,
+                "d" | "day" | "days" => 86400.0,
+                "" => return Err(AppError::ConfigurationError(format!("Missing unit in duration: {}", duration_str))),
+                _ => return Err(AppError::ConfigurationError(format!(
+                    "Unknown duration unit '{}' in {}; use ms, s, m, h, or d", unit_part, duration_str
+                ))),
+            };
+
+            total += Duration::try_from_secs_f64(number * unit_seconds)
+                .map_err(|_| AppError::ConfigurationError(format!("Duration out of range: {}", duration_str)))?;
+            rest = &rest[units..];
+        }
+
+        Ok(total)
+    }
+
     pub fn truncate_string(s: &str, max_len: usize) -> String {
         if s.len() <= max_len {
             s.to_string()
@@ -186,54 +242,6 @@ impl Utils {
     pub fn is_valid_url(url: &str) -> bool {
         url.starts_with("http://") || url.starts_with("https://")
     }
-
-    pub fn create_rate_limiter(max_requests: u32, window_seconds: u64) -> RateLimiter {
-        RateLimiter::new(max_requests, Duration::from_secs(window_seconds))
-    }
-}
-
-pub struct RateLimiter {
-    max_requests: u32,
-    window: Duration,
-    requests: std::sync::Mutex<Vec<Instant>>,
-}
-
-impl RateLimiter {
-    pub fn new(max_requests: u32, window: Duration) -> Self {
-        Self {
-            max_requests,
-            window,
-            requests: std::sync::Mutex::new(Vec::new()),
-        }
-    }
-
-    pub fn is_allowed(&self) -> bool {
-        let now = Instant::now();
-        let mut requests = self.requests.lock().unwrap();
-
-        requests.retain(|&request_time| now.duration_since(request_time) < self.window);
-
-        if requests.len() < self.max_requests as usize {
-            requests.push(now);
-            true
-        } else {
-            false
-        }
-    }
-
-    pub fn remaining_requests(&self) -> u32 {
-        let now = Instant::now();
-        let mut requests = self.requests.lock().unwrap();
-
-        requests.retain(|&request_time| now.duration_since(request_time) < self.window);
-
-        self.max_requests.saturating_sub(requests.len() as u32)
-    }
-
-    pub fn reset_time(&self) -> Option<Instant> {
-        let requests = self.requests.lock().unwrap();
-        requests.first().map(|&first_request| first_request + self.window)
-    }
 }
 
 pub struct Environment;
@@ -272,154 +280,6 @@ impl Environment {
     }
 }
 
-pub struct RetryConfig {
-    pub max_attempts: u32,
-    pub initial_delay: Duration,
-    pub max_delay: Duration,
-    pub multiplier: f64,
-}
-
-impl Default for RetryConfig {
-    fn default() -> Self {
-        Self {
-            max_attempts: 3,
-            initial_delay: Duration::from_millis(100),
-            max_delay: Duration::from_secs(30),
-            multiplier: 2.0,
-        }
-    }
-}
-
-pub async fn retry_with_backoff<F, T, E>(
-    operation: F,
-    config: RetryConfig,
-) -> std::result::Result<T, E>
-where
-    F: Fn() -> std::pin::Pin<Box<dyn std::future::Future<Output = std::result::Result<T, E>> + Send>>,
-    E: std::fmt::Debug,
-{
-    let mut current_delay = config.initial_delay;
-
-    for attempt in 1..=config.max_attempts {
-        match operation().await {
-            Ok(result) => return Ok(result),
-            Err(error) => {
-                if attempt == config.max_attempts {
-                    return Err(error);
-                }
-
-                tracing::warn!("Operation failed (attempt {}/{}): {:?}", attempt, config.max_attempts, error);
-
-                tokio::time::sleep(current_delay).await;
-
-                current_delay = Duration::from_millis(
-                    ((current_delay.as_millis() as f64) * config.multiplier) as u64
-                ).min(config.max_delay);
-            }
-        }
-    }
-
-    unreachable!()
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum CircuitState {
-    Closed,
-    Open,
-    HalfOpen,
-}
-
-pub struct CircuitBreaker {
-    state: std::sync::Mutex<CircuitState>,
-    failure_count: std::sync::Mutex<u32>,
-    last_failure_time: std::sync::Mutex<Option<Instant>>,
-    failure_threshold: u32,
-    timeout: Duration,
-}
-
-impl CircuitBreaker {
-    pub fn new(failure_threshold: u32, timeout: Duration) -> Self {
-        Self {
-            state: std::sync::Mutex::new(CircuitState::Closed),
-            failure_count: std::sync::Mutex::new(0),
-            last_failure_time: std::sync::Mutex::new(None),
-            failure_threshold,
-            timeout,
-        }
-    }
-
-    pub fn call<F, T, E>(&self, operation: F) -> std::result::Result<T, E>
-    where
-        F: FnOnce() -> std::result::Result<T, E>,
-        E: From<AppError>,
-    {
-        let can_proceed = {
-            let mut current_state_guard = self.state.lock().unwrap(); // Lock to read and potentially modify
-            match *current_state_guard {
-                CircuitState::Closed => true,
-                CircuitState::HalfOpen => true, // Allow one attempt in HalfOpen
-                CircuitState::Open => {
-                    let last_failure_time_guard = self.last_failure_time.lock().unwrap();
-                    if let Some(last_failure) = *last_failure_time_guard {
-                        if Instant::now().duration_since(last_failure) > self.timeout {
-                            info!("CircuitBreaker: Timeout elapsed, transitioning from Open to HalfOpen.");
-                            *current_state_guard = CircuitState::HalfOpen;
-                            true // Allow this call as the first attempt in HalfOpen
-                        } else {
-                            // Still in Open state, timeout not elapsed
-                            false
-                        }
-                    } else {
-                        // Should not happen if last_failure_time is always set on failure
-                        // but if it does, stay open.
-                        warn!("CircuitBreaker: In Open state but no last_failure_time recorded.");
-                        false
-                    }
-                }
-            }
-        };
-
-        if !can_proceed {
-            return Err(AppError::ServiceUnavailableError(
-                "Circuit breaker is OPEN".to_string(),
-            )
-            .into());
-        }
-
-        match operation() {
-            Ok(result) => {
-                let mut current_state_guard = self.state.lock().unwrap();
-                if *current_state_guard == CircuitState::HalfOpen {
-                    info!("CircuitBreaker: Successful call in HalfOpen state, transitioning to Closed.");
-                }
-                *current_state_guard = CircuitState::Closed;
-                *self.failure_count.lock().unwrap() = 0;
-                *self.last_failure_time.lock().unwrap() = None; // Clear last failure time
-                Ok(result)
-            }
-            Err(error) => {
-                let mut failure_count_guard = self.failure_count.lock().unwrap();
-                let mut current_state_guard = self.state.lock().unwrap();
-                let mut last_failure_time_guard = self.last_failure_time.lock().unwrap();
-
-                *failure_count_guard += 1;
-                *last_failure_time_guard = Some(Instant::now());
-
-                if *current_state_guard == CircuitState::HalfOpen {
-                    // Failure in HalfOpen state, trip back to Open
-                    info!("CircuitBreaker: Failure in HalfOpen state, transitioning back to Open.");
-                    *current_state_guard = CircuitState::Open;
-                } else if *failure_count_guard >= self.failure_threshold {
-                    // Failure threshold reached in Closed state, trip to Open
-                    info!("CircuitBreaker: Failure threshold reached, transitioning from Closed to Open.");
-                    *current_state_guard = CircuitState::Open;
-                }
-                Err(error)
-            }
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -440,6 +300,19 @@ mod tests {
         assert_eq!(Utils::parse_size("1GB").unwrap(), 1073741824);
     }
 
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(Utils::parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(Utils::parse_duration("30s").unwrap(), Duration::from_secs(30));
+        assert_eq!(Utils::parse_duration("5m").unwrap(), Duration::from_secs(300));
+        assert_eq!(Utils::parse_duration("1h30m").unwrap(), Duration::from_secs(5400));
+        assert_eq!(Utils::parse_duration("250ms").unwrap(), Duration::from_millis(250));
+        assert_eq!(Utils::parse_duration("1.5h").unwrap(), Duration::from_secs(5400));
+        assert!(Utils::parse_duration("5 fortnights").is_err());
+        assert!(Utils::parse_duration("1h30").is_err());
+        assert!(Utils::parse_duration("").is_err());
+    }
+
     #[test]
     fn test_truncate_string() {
         assert_eq!(Utils::truncate_string("hello", 10), "hello");
@@ -455,15 +328,6 @@ mod tests {
         assert_eq!(Utils::calculate_percentile(&values, 100.0), Some(5.0));
     }
 
-    #[test]
-    fn test_rate_limiter() {
-        let limiter = RateLimiter::new(2, Duration::from_secs(1));
-
-        assert!(limiter.is_allowed());
-        assert!(limiter.is_allowed());
-        assert!(!limiter.is_allowed());
-    }
-
     #[test]
     fn test_email_validation() {
         assert!(Utils::is_valid_email("test@example.com"));
@@ -0,0 +1,127 @@
+/*
+ * Network helpers for reasoning about client addresses and the proxies in front of the backend.
+ * I'm keeping CIDR handling dependency-free since all we need is prefix containment checks.
+ */
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// An IP network in CIDR notation such as `10.0.0.0/8` or `::1/128`
+/// I'm accepting bare addresses too and treating them as single-host networks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct IpCidr {
+    network: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpCidr {
+    pub fn new(network: IpAddr, prefix_len: u8) -> Result<Self, String> {
+        let max_len = match network {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+
+        if prefix_len > max_len {
+            return Err(format!("Prefix length /{} is too long for {}", prefix_len, network));
+        }
+
+        Ok(Self { network, prefix_len })
+    }
+
+    /// Check whether an address falls inside this network
+    /// I'm normalising IPv4-mapped IPv6 peers so dual-stack sockets still match IPv4 ranges
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let ip = match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+            other => other,
+        };
+
+        match (self.network, ip) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => {
+                prefix_matches(u32::from(net) as u128, u32::from(addr) as u128, self.prefix_len, 32)
+            }
+            (IpAddr::V6(net), IpAddr::V6(addr)) => {
+                prefix_matches(u128::from(net), u128::from(addr), self.prefix_len, 128)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn prefix_matches(network: u128, addr: u128, prefix_len: u8, bits: u8) -> bool {
+    if prefix_len == 0 {
+        return true;
+    }
+
+    let shift = bits - prefix_len;
+    (network >> shift) == (addr >> shift)
+}
+
+impl FromStr for IpCidr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+
+        let network: IpAddr = addr
+            .parse()
+            .map_err(|_| format!("Invalid IP address in CIDR: {}", s))?;
+
+        let prefix_len = match prefix {
+            Some(prefix) => prefix
+                .parse()
+                .map_err(|_| format!("Invalid prefix length in CIDR: {}", s))?,
+            None if network.is_ipv4() => 32,
+            None => 128,
+        };
+
+        Self::new(network, prefix_len)
+    }
+}
+
+impl TryFrom<String> for IpCidr {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<IpCidr> for String {
+    fn from(cidr: IpCidr) -> Self {
+        cidr.to_string()
+    }
+}
+
+impl fmt::Display for IpCidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix_len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cidr_parsing_and_containment() {
+        let private: IpCidr = "10.0.0.0/8".parse().unwrap();
+        assert!(private.contains("10.20.30.40".parse().unwrap()));
+        assert!(!private.contains("11.0.0.1".parse().unwrap()));
+        assert!(private.contains("::ffff:10.1.2.3".parse().unwrap()));
+
+        let host: IpCidr = "::1".parse().unwrap();
+        assert_eq!(host.to_string(), "::1/128");
+        assert!(host.contains("::1".parse().unwrap()));
+
+        assert!("10.0.0.0/33".parse::<IpCidr>().is_err());
+        assert!("not-an-ip/8".parse::<IpCidr>().is_err());
+    }
+}
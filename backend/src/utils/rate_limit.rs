@@ -0,0 +1,376 @@
+/*
+ * Fixed-window rate limiting behind one async trait, shared by the HTTP middleware and GitHub request pacing.
+ * I'm keeping counters in Redis so every instance draws from the same budget, and falling back to in-process windows while Redis is unreachable.
+ */
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+use tracing::{debug, info, warn};
+
+use crate::utils::{
+    config::{Config, RateLimitBackend},
+    error::{AppError, Result},
+};
+
+const KEY_PREFIX: &str = "perf_showcase:ratelimit:";
+
+/// Windows tracked in memory before expired ones are swept; new keys beyond this share an overflow window
+pub const MAX_TRACKED_WINDOWS: usize = 10_000;
+
+/// Prefix of the windows that keys arriving while the map is full share, one per limit and window length
+const OVERFLOW_KEY_PREFIX: &str = "\u{0}overflow:";
+
+/// How long to stay on in-process windows after Redis fails before trying it again
+const REDIS_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Counts the hit and reads back the window's remaining lifetime in one round trip
+const ACQUIRE_SCRIPT: &str = r"
+local count = redis.call('INCR', KEYS[1])
+if count == 1 then
+    redis.call('PEXPIRE', KEYS[1], ARGV[1])
+end
+return {count, redis.call('PTTL', KEYS[1])}
+";
+
+/// Adds the cost only when the window has room for all of it, then reads back whether it did, the window's total, and its remaining lifetime
+const CHARGE_SCRIPT: &str = r"
+local count = tonumber(redis.call('GET', KEYS[1]) or '0')
+local allowed = 0
+if count + tonumber(ARGV[2]) <= tonumber(ARGV[3]) then
+    allowed = 1
+    count = redis.call('INCRBY', KEYS[1], ARGV[2])
+    if redis.call('PTTL', KEYS[1]) < 0 then
+        redis.call('PEXPIRE', KEYS[1], ARGV[1])
+    end
+end
+return {allowed, count, redis.call('PTTL', KEYS[1])}
+";
+
+/// The outcome of counting one hit against a window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Time until the window closes and the budget refills
+    pub reset_after: Duration,
+}
+
+impl RateLimitDecision {
+    fn counted(count: u64, limit: u32, reset_after: Duration) -> Self {
+        Self {
+            allowed: count <= u64::from(limit),
+            limit,
+            remaining: u64::from(limit).saturating_sub(count) as u32,
+            reset_after,
+        }
+    }
+
+    /// Whether a charge went through, with `count` what the window holds afterwards
+    fn charged(allowed: bool, count: u64, limit: u32, reset_after: Duration) -> Self {
+        Self {
+            allowed,
+            limit,
+            remaining: u64::from(limit).saturating_sub(count) as u32,
+            reset_after,
+        }
+    }
+
+}
+
+/// A limiter allowing at most `limit` hits per key in each window
+/// I'm making acquire infallible so callers never have to decide what a broken limiter means; implementations degrade on their own
+#[async_trait]
+pub trait RateLimiter: Send + Sync + std::fmt::Debug {
+    async fn acquire(&self, key: &str, limit: u32, window: Duration) -> RateLimitDecision;
+
+    /// Spend `cost` of the window's `limit` when all of it fits; a refused charge spends nothing, so a client can retry smaller
+    async fn charge(&self, key: &str, cost: u32, limit: u32, window: Duration) -> RateLimitDecision;
+}
+
+pub type SharedRateLimiter = Arc<dyn RateLimiter>;
+
+/// Build the limiter RATE_LIMIT_BACKEND asks for
+pub fn from_config(config: &Config, redis_client: redis::Client) -> SharedRateLimiter {
+    match config.rate_limit_backend {
+        RateLimitBackend::Redis => Arc::new(RedisRateLimiter::new(redis_client)),
+        RateLimitBackend::Memory => Arc::new(InMemoryRateLimiter::new()),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Window {
+    started: Instant,
+    length: Duration,
+    count: u64,
+}
+
+impl Window {
+    fn closes_at(&self) -> Instant {
+        self.started + self.length
+    }
+}
+
+/// Per-process windows, bounded by MAX_TRACKED_WINDOWS
+#[derive(Debug, Default)]
+pub struct InMemoryRateLimiter {
+    windows: Mutex<HashMap<String, Window>>,
+}
+
+impl InMemoryRateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn acquire_at(&self, key: &str, limit: u32, window: Duration, now: Instant) -> RateLimitDecision {
+        self.with_window(key, limit, window, now, |tracked| {
+            tracked.count += 1;
+            RateLimitDecision::counted(tracked.count, limit, tracked.closes_at().saturating_duration_since(now))
+        })
+    }
+
+    fn charge_at(&self, key: &str, cost: u32, limit: u32, window: Duration, now: Instant) -> RateLimitDecision {
+        self.with_window(key, limit, window, now, |tracked| {
+            let allowed = tracked.count + u64::from(cost) <= u64::from(limit);
+            if allowed {
+                tracked.count += u64::from(cost);
+            }
+            RateLimitDecision::charged(allowed, tracked.count, limit, tracked.closes_at().saturating_duration_since(now))
+        })
+    }
+
+    /// Run `update` on the key's current window, opening a fresh one if it has closed
+    /// I'm sending new keys to a shared overflow window once the map is full of live windows, so a flood of fresh
+    /// addresses throttles itself together instead of every new key going unlimited
+    fn with_window(&self, key: &str, limit: u32, window: Duration, now: Instant, update: impl FnOnce(&mut Window) -> RateLimitDecision) -> RateLimitDecision {
+        let mut windows = self.windows.lock().unwrap();
+
+        let mut key = key.to_string();
+        if !windows.contains_key(&key) && windows.len() >= MAX_TRACKED_WINDOWS {
+            windows.retain(|_, tracked| tracked.closes_at() > now);
+            if windows.len() >= MAX_TRACKED_WINDOWS {
+                debug!("Rate limiter is tracking {} windows; counting {} against the overflow window", windows.len(), key);
+                key = format!("{}{}/{}ms", OVERFLOW_KEY_PREFIX, limit, window.as_millis());
+            }
+        }
+
+        let tracked = windows.entry(key).or_insert(Window {
+            started: now,
+            length: window,
+            count: 0,
+        });
+        if tracked.closes_at() <= now {
+            *tracked = Window { started: now, length: window, count: 0 };
+        }
+        update(tracked)
+    }
+}
+
+#[async_trait]
+impl RateLimiter for InMemoryRateLimiter {
+    async fn acquire(&self, key: &str, limit: u32, window: Duration) -> RateLimitDecision {
+        self.acquire_at(key, limit, window, Instant::now())
+    }
+
+    async fn charge(&self, key: &str, cost: u32, limit: u32, window: Duration) -> RateLimitDecision {
+        self.charge_at(key, cost, limit, window, Instant::now())
+    }
+}
+
+/// Windows shared by every instance through Redis
+#[derive(Clone)]
+pub struct RedisRateLimiter {
+    client: redis::Client,
+    connection: Arc<OnceCell<redis::aio::ConnectionManager>>,
+    script: Arc<redis::Script>,
+    charge_script: Arc<redis::Script>,
+    fallback: Arc<InMemoryRateLimiter>,
+    /// Set while Redis is failing; requests skip it until this passes so an outage doesn't add a connect timeout to each one
+    retry_redis_at: Arc<Mutex<Option<Instant>>>,
+}
+
+impl std::fmt::Debug for RedisRateLimiter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisRateLimiter")
+            .field("degraded", &self.retry_redis_at.lock().unwrap().is_some())
+            .finish()
+    }
+}
+
+impl RedisRateLimiter {
+    pub fn new(client: redis::Client) -> Self {
+        Self {
+            client,
+            connection: Arc::new(OnceCell::new()),
+            script: Arc::new(redis::Script::new(ACQUIRE_SCRIPT)),
+            charge_script: Arc::new(redis::Script::new(CHARGE_SCRIPT)),
+            fallback: Arc::new(InMemoryRateLimiter::new()),
+            retry_redis_at: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Unlike the cache's connection this retries once, since every limited request waits on it
+    async fn connection(&self) -> Result<redis::aio::ConnectionManager> {
+        self.connection
+            .get_or_try_init(|| async {
+                redis::aio::ConnectionManager::new_with_backoff(self.client.clone(), 2, 100, 1)
+                    .await
+                    .map_err(|e| AppError::CacheError(format!("Failed to create Redis connection manager: {}", e)))
+            })
+            .await
+            .cloned()
+    }
+
+    async fn acquire_shared(&self, key: &str, limit: u32, window: Duration) -> Result<RateLimitDecision> {
+        let mut connection = self.connection().await?;
+        let window_ms = window.as_millis().max(1) as u64;
+
+        let (count, ttl_ms): (u64, i64) = self
+            .script
+            .key(format!("{}{}", KEY_PREFIX, key))
+            .arg(window_ms)
+            .invoke_async(&mut connection)
+            .await
+            .map_err(|e| AppError::CacheError(format!("Rate limit script failed: {}", e)))?;
+
+        // PTTL is negative only if the key vanished between calls; treat that as a fresh window
+        let reset_after = if ttl_ms > 0 { Duration::from_millis(ttl_ms as u64) } else { window };
+        Ok(RateLimitDecision::counted(count, limit, reset_after))
+    }
+
+    async fn charge_shared(&self, key: &str, cost: u32, limit: u32, window: Duration) -> Result<RateLimitDecision> {
+        let mut connection = self.connection().await?;
+        let window_ms = window.as_millis().max(1) as u64;
+
+        let (allowed, count, ttl_ms): (u8, u64, i64) = self
+            .charge_script
+            .key(format!("{}{}", KEY_PREFIX, key))
+            .arg(window_ms)
+            .arg(cost)
+            .arg(limit)
+            .invoke_async(&mut connection)
+            .await
+            .map_err(|e| AppError::CacheError(format!("Rate limit charge script failed: {}", e)))?;
+
+        // A refused charge against a window that was never opened leaves no key, so no TTL
+        let reset_after = if ttl_ms > 0 { Duration::from_millis(ttl_ms as u64) } else { window };
+        Ok(RateLimitDecision::charged(allowed == 1, count, limit, reset_after))
+    }
+
+    /// The shared decision while Redis answers, and the in-process one while it is failing or backing off
+    async fn shared_or_fallback(
+        &self,
+        shared: impl std::future::Future<Output = Result<RateLimitDecision>> + Send,
+        fallback: impl FnOnce(&InMemoryRateLimiter) -> RateLimitDecision + Send,
+    ) -> RateLimitDecision {
+        let retry_at = *self.retry_redis_at.lock().unwrap();
+        if retry_at.is_some_and(|at| Instant::now() < at) {
+            return fallback(&self.fallback);
+        }
+
+        match shared.await {
+            Ok(decision) => {
+                if self.retry_redis_at.lock().unwrap().take().is_some() {
+                    info!("Redis rate limiting recovered; budgets are shared across instances again");
+                }
+                decision
+            }
+            Err(e) => {
+                let first_failure = self
+                    .retry_redis_at
+                    .lock()
+                    .unwrap()
+                    .replace(Instant::now() + REDIS_RETRY_INTERVAL)
+                    .is_none();
+                if first_failure {
+                    warn!("Rate limiting falls back to per-instance windows: {}", e);
+                }
+                fallback(&self.fallback)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl RateLimiter for RedisRateLimiter {
+    async fn acquire(&self, key: &str, limit: u32, window: Duration) -> RateLimitDecision {
+        self.shared_or_fallback(self.acquire_shared(key, limit, window), |fallback| {
+            fallback.acquire_at(key, limit, window, Instant::now())
+        })
+        .await
+    }
+
+    async fn charge(&self, key: &str, cost: u32, limit: u32, window: Duration) -> RateLimitDecision {
+        self.shared_or_fallback(self.charge_shared(key, cost, limit, window), |fallback| {
+            fallback.charge_at(key, cost, limit, window, Instant::now())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_allows_limit_then_refills() {
+        let limiter = InMemoryRateLimiter::new();
+        let window = Duration::from_secs(60);
+        let start = Instant::now();
+
+        assert!(limiter.acquire_at("client", 2, window, start).allowed);
+        let second = limiter.acquire_at("client", 2, window, start + Duration::from_secs(10));
+        assert!(second.allowed);
+        assert_eq!(second.remaining, 0);
+        assert_eq!(second.reset_after, Duration::from_secs(50));
+
+        assert!(!limiter.acquire_at("client", 2, window, start + Duration::from_secs(20)).allowed);
+        assert!(limiter.acquire_at("other", 2, window, start + Duration::from_secs(20)).allowed);
+
+        let refilled = limiter.acquire_at("client", 2, window, start + window);
+        assert!(refilled.allowed);
+        assert_eq!(refilled.remaining, 1);
+    }
+
+    #[test]
+    fn test_charges_only_spend_what_fits() {
+        let limiter = InMemoryRateLimiter::new();
+        let window = Duration::from_secs(60);
+        let start = Instant::now();
+
+        let first = limiter.charge_at("client", 60, 100, window, start);
+        assert!(first.allowed);
+        assert_eq!(first.remaining, 40);
+
+        // Too big for what's left, and refused without spending any of it
+        let refused = limiter.charge_at("client", 50, 100, window, start + Duration::from_secs(5));
+        assert!(!refused.allowed);
+        assert_eq!(refused.remaining, 40);
+        assert!(limiter.charge_at("client", 40, 100, window, start + Duration::from_secs(6)).allowed);
+        assert_eq!(limiter.charge_at("client", 100, 100, window, start + window).remaining, 0);
+    }
+
+    #[test]
+    fn test_tracked_windows_stay_bounded() {
+        let limiter = InMemoryRateLimiter::new();
+        let window = Duration::from_secs(1);
+        let start = Instant::now();
+
+        for i in 0..MAX_TRACKED_WINDOWS {
+            limiter.acquire_at(&i.to_string(), 1, window, start);
+        }
+        // Fresh keys past the cap share one window rather than going unlimited
+        assert!(limiter.acquire_at("fresh-1", 2, window, start).allowed);
+        assert!(limiter.acquire_at("fresh-2", 2, window, start).allowed);
+        assert!(!limiter.acquire_at("fresh-3", 2, window, start).allowed);
+        assert!(!limiter.charge_at("fresh-4", 5, 4, window, start).allowed);
+        assert!(limiter.acquire_at("0", 2, window, start).allowed, "tracked keys keep their own window");
+
+        let later = start + Duration::from_secs(2);
+        assert!(!limiter.acquire_at("fresh-1", 0, window, later).allowed);
+        assert_eq!(limiter.windows.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,38 @@
+/*
+ * One lazily opened Redis connection shared by the services that talk to Redis directly.
+ * I'm handing out clones of a single multiplexed ConnectionManager, so the task queue, leader election, usage counters, and fractal jobs share one socket instead of opening one each.
+ */
+
+use redis::aio::ConnectionManager;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+use crate::utils::error::{AppError, Result};
+
+/// Cheap to clone; every clone hands out the same connection manager
+#[derive(Clone)]
+pub struct SharedRedisConnection {
+    client: redis::Client,
+    connection: Arc<OnceCell<ConnectionManager>>,
+}
+
+impl SharedRedisConnection {
+    pub fn new(client: redis::Client) -> Self {
+        Self {
+            client,
+            connection: Arc::new(OnceCell::new()),
+        }
+    }
+
+    /// The shared connection manager, opened on first use and retried on the next call if that fails
+    pub async fn get(&self) -> Result<ConnectionManager> {
+        self.connection
+            .get_or_try_init(|| async {
+                ConnectionManager::new(self.client.clone())
+                    .await
+                    .map_err(|e| AppError::CacheError(format!("Failed to create Redis connection manager: {}", e)))
+            })
+            .await
+            .cloned()
+    }
+}
@@ -0,0 +1,283 @@
+/*
+ * The one retry helper for AppError-returning operations: exponential backoff with full jitter, an elapsed-time cap, and a process-wide retry budget.
+ * I'm drawing every retry from the shared budget so a struggling dependency sees a bounded trickle of extra calls instead of every caller's retries stacked on top.
+ */
+
+use std::future::Future;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+use crate::utils::error::{AppError, Result};
+
+static GLOBAL_BUDGET: OnceLock<RetryBudget> = OnceLock::new();
+
+/// Retries allowed per first attempt before the budget runs dry
+const DEFAULT_BUDGET_RATIO: f64 = 0.2;
+/// Retries per second the budget always allows, so a quiet service can still retry
+const DEFAULT_BUDGET_MIN_PER_SECOND: u32 = 10;
+
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    /// Attempts including the first one
+    pub max_attempts: u32,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub multiplier: f64,
+    /// Give up rather than start a wait that would end past this long after the first attempt
+    pub max_elapsed: Option<Duration>,
+    /// Sleep a random time up to the backoff instead of the backoff itself
+    pub jitter: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(30),
+            multiplier: 2.0,
+            max_elapsed: None,
+            jitter: true,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// The backoff ceiling before the given retry, counting retries from 1
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = self.multiplier.max(1.0).powi(retry.saturating_sub(1).min(i32::MAX as u32) as i32);
+        self.initial_delay.mul_f64(factor.min(u32::MAX as f64)).min(self.max_delay)
+    }
+
+    /// How long to wait before the given retry: the backoff itself, or a random time up to it with jitter on;
+    /// for retries that can't run inside retry_with_backoff, such as a task requeued for a later worker
+    pub fn delay(&self, retry: u32) -> Duration {
+        let backoff = self.backoff(retry);
+        if self.jitter {
+            backoff.mul_f64(rand::random::<f64>())
+        } else {
+            backoff
+        }
+    }
+}
+
+#[derive(Debug)]
+struct BudgetState {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// A shared allowance of retries that grows with first attempts and a small per-second floor
+/// I'm capping the balance at ten seconds of floor plus a hundred first attempts' deposits, so a quiet stretch can't bank a retry storm
+#[derive(Debug, Clone)]
+pub struct RetryBudget {
+    ratio: f64,
+    min_per_second: f64,
+    capacity: f64,
+    state: Arc<Mutex<BudgetState>>,
+}
+
+impl RetryBudget {
+    pub fn new(ratio: f64, min_per_second: u32) -> Self {
+        let min_per_second = f64::from(min_per_second);
+        let capacity = (min_per_second * 10.0).max(1.0) + ratio.max(0.0) * 100.0;
+        Self {
+            ratio: ratio.max(0.0),
+            min_per_second,
+            capacity,
+            state: Arc::new(Mutex::new(BudgetState {
+                tokens: capacity,
+                refilled_at: Instant::now(),
+            })),
+        }
+    }
+
+    /// The budget every retry_with_backoff call draws from; defaults apply until one is installed
+    pub fn global() -> &'static RetryBudget {
+        GLOBAL_BUDGET.get_or_init(|| RetryBudget::new(DEFAULT_BUDGET_RATIO, DEFAULT_BUDGET_MIN_PER_SECOND))
+    }
+
+    /// Credit one first attempt
+    pub fn deposit(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.tokens = (state.tokens + self.ratio).min(self.capacity);
+    }
+
+    /// Take one retry from the budget; false means the caller should give up instead
+    pub fn try_withdraw(&self) -> bool {
+        self.try_withdraw_at(Instant::now())
+    }
+
+    fn try_withdraw_at(&self, now: Instant) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let refill = now.saturating_duration_since(state.refilled_at).as_secs_f64() * self.min_per_second;
+        state.tokens = (state.tokens + refill).min(self.capacity);
+        state.refilled_at = now;
+
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Replace the default global budget; later calls are ignored
+pub fn install_budget(budget: RetryBudget) {
+    let _ = GLOBAL_BUDGET.set(budget);
+}
+
+/// Run `operation` until it succeeds, fails with a non-retryable error, or runs out of attempts, time, or budget
+/// I'm returning the last error unchanged so callers see what actually went wrong rather than a retry wrapper
+pub async fn retry_with_backoff<F, Fut, T>(operation: F, config: &RetryConfig) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    retry_within_budget(operation, config, RetryBudget::global()).await
+}
+
+async fn retry_within_budget<F, Fut, T>(mut operation: F, config: &RetryConfig, budget: &RetryBudget) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let started = Instant::now();
+    budget.deposit();
+
+    let mut attempt = 1;
+    loop {
+        let error = match operation().await {
+            Ok(result) => return Ok(result),
+            Err(error) => error,
+        };
+
+        if !should_retry(&error) || attempt >= config.max_attempts {
+            return Err(error);
+        }
+
+        let delay = config.delay(attempt);
+        if config.max_elapsed.is_some_and(|limit| started.elapsed() + delay > limit) {
+            debug!("Not retrying after attempt {}: retry time limit reached", attempt);
+            return Err(error);
+        }
+        if !budget.try_withdraw() {
+            warn!("Retry budget exhausted; giving up after attempt {}: {}", attempt, error);
+            return Err(error);
+        }
+
+        warn!(
+            "Operation failed (attempt {}/{}), retrying in {}ms: {}",
+            attempt, config.max_attempts, delay.as_millis(), error
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Rate limits won't lift within a backoff, so retrying them only burns budget
+fn should_retry(error: &AppError) -> bool {
+    error.is_retryable() && !matches!(error.root_cause(), AppError::RateLimitError(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn immediate(max_attempts: u32) -> RetryConfig {
+        RetryConfig {
+            max_attempts,
+            initial_delay: Duration::ZERO,
+            jitter: false,
+            ..RetryConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_backoff_grows_to_the_ceiling() {
+        let config = RetryConfig {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(350),
+            jitter: false,
+            ..RetryConfig::default()
+        };
+        assert_eq!(config.delay(1), Duration::from_millis(100));
+        assert_eq!(config.delay(2), Duration::from_millis(200));
+        assert_eq!(config.delay(3), Duration::from_millis(350));
+        assert_eq!(config.delay(60), Duration::from_millis(350));
+
+        let jittered = RetryConfig { jitter: true, ..config };
+        assert!((0..20).all(|_| jittered.delay(2) <= Duration::from_millis(200)));
+    }
+
+    #[tokio::test]
+    async fn test_retries_only_retryable_errors() {
+        let budget = RetryBudget::new(0.2, 10);
+        let calls = AtomicU32::new(0);
+        let result: Result<u32> = retry_within_budget(
+            || async {
+                match calls.fetch_add(1, Ordering::SeqCst) {
+                    0 | 1 => Err(AppError::ExternalApiError("flaky".to_string())),
+                    n => Ok(n),
+                }
+            },
+            &immediate(5),
+            &budget,
+        )
+        .await;
+        assert_eq!(result.unwrap(), 2);
+
+        calls.store(0, Ordering::SeqCst);
+        let result: Result<()> = retry_within_budget(
+            || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(AppError::ValidationError("bad input".to_string()))
+            },
+            &immediate(5),
+            &budget,
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::ValidationError(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_elapsed_cap_stops_retrying() {
+        let calls = AtomicU32::new(0);
+        let config = RetryConfig {
+            initial_delay: Duration::from_secs(60),
+            max_elapsed: Some(Duration::from_secs(1)),
+            jitter: false,
+            ..RetryConfig::default()
+        };
+        let result: Result<()> = retry_within_budget(
+            || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(AppError::TimeoutError("slow".to_string()))
+            },
+            &config,
+            &RetryBudget::new(0.2, 10),
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_budget_drains_and_refills() {
+        let budget = RetryBudget::new(0.0, 1);
+        let start = Instant::now();
+        let granted = (0..20).filter(|_| budget.try_withdraw_at(start)).count();
+        assert_eq!(granted, 10);
+        assert!(!budget.try_withdraw_at(start));
+        assert!(budget.try_withdraw_at(start + Duration::from_secs(1)));
+
+        for _ in 0..5 {
+            budget.deposit();
+        }
+        assert!(!budget.try_withdraw_at(start + Duration::from_secs(1)), "a zero ratio never earns retries");
+    }
+}
@@ -0,0 +1,171 @@
+/*
+ * Shutdown coordination: stop taking new work, give in-flight work a grace period to finish, then have what's left checkpoint itself.
+ * I'm tracking work with guards rather than join handles, so anything from a queue worker to a scheduled job can be counted without this module knowing about it.
+ */
+
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// How long work abandoned at the deadline gets to checkpoint before the process exits anyway
+const CHECKPOINT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Phase {
+    Running,
+    /// No new work is taken; in-flight work may finish
+    Draining,
+    /// The grace period is over; in-flight work should checkpoint and stop
+    Expired,
+}
+
+#[derive(Debug, Clone)]
+pub struct Shutdown {
+    inner: Arc<ShutdownInner>,
+}
+
+#[derive(Debug)]
+struct ShutdownInner {
+    grace_period: Duration,
+    phase: watch::Sender<Phase>,
+    in_flight: watch::Sender<usize>,
+}
+
+/// Counts one unit of in-flight work until dropped
+#[derive(Debug)]
+pub struct InFlight(Arc<ShutdownInner>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.in_flight.send_modify(|count| *count -= 1);
+    }
+}
+
+impl Shutdown {
+    pub fn new(grace_period: Duration) -> Self {
+        Self {
+            inner: Arc::new(ShutdownInner {
+                grace_period,
+                phase: watch::channel(Phase::Running).0,
+                in_flight: watch::channel(0).0,
+            }),
+        }
+    }
+
+    pub fn grace_period(&self) -> Duration {
+        self.inner.grace_period
+    }
+
+    /// Start draining; the grace period runs from the first call
+    pub fn begin(&self) {
+        let started = self.inner.phase.send_if_modified(|phase| {
+            let running = *phase == Phase::Running;
+            if running {
+                *phase = Phase::Draining;
+            }
+            running
+        });
+        if !started {
+            return;
+        }
+
+        info!("Draining {} in-flight jobs for up to {:?}", self.in_flight(), self.inner.grace_period);
+        let shutdown = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(shutdown.inner.grace_period).await;
+            shutdown.inner.phase.send_if_modified(|phase| {
+                let expiring = *phase != Phase::Expired;
+                *phase = Phase::Expired;
+                expiring
+            });
+        });
+    }
+
+    pub fn is_draining(&self) -> bool {
+        *self.inner.phase.borrow() != Phase::Running
+    }
+
+    /// Resolves once shutdown begins
+    pub async fn triggered(&self) {
+        self.reached(Phase::Draining).await
+    }
+
+    /// Resolves once the grace period is over
+    pub async fn expired(&self) {
+        self.reached(Phase::Expired).await
+    }
+
+    /// Count a unit of work that shutdown should wait for
+    pub fn track(&self) -> InFlight {
+        self.inner.in_flight.send_modify(|count| *count += 1);
+        InFlight(self.inner.clone())
+    }
+
+    pub fn in_flight(&self) -> usize {
+        *self.inner.in_flight.borrow()
+    }
+
+    /// Begin draining if nothing has yet and wait for tracked work to finish; false when the deadline cut it short
+    pub async fn drain(&self) -> bool {
+        self.begin();
+        tokio::select! {
+            _ = self.idle() => return true,
+            _ = self.expired() => {}
+        }
+
+        warn!("Shutdown grace period ended with {} jobs still in flight; checkpointing them", self.in_flight());
+        if tokio::time::timeout(CHECKPOINT_TIMEOUT, self.idle()).await.is_err() {
+            warn!("{} jobs did not checkpoint in time and will be retried once their leases expire", self.in_flight());
+        }
+        false
+    }
+
+    async fn idle(&self) {
+        let mut in_flight = self.inner.in_flight.subscribe();
+        let _ = in_flight.wait_for(|count| *count == 0).await;
+    }
+
+    async fn reached(&self, target: Phase) {
+        let mut phase = self.inner.phase.subscribe();
+        let _ = phase.wait_for(|phase| *phase >= target).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_drain_waits_for_tracked_work() {
+        let shutdown = Shutdown::new(Duration::from_secs(5));
+        let guard = shutdown.track();
+        assert_eq!(shutdown.in_flight(), 1);
+        assert!(!shutdown.is_draining());
+
+        let release = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            drop(guard);
+        });
+        assert!(shutdown.drain().await);
+        assert!(shutdown.is_draining());
+        assert_eq!(shutdown.in_flight(), 0);
+        release.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_deadline_expires_stuck_work() {
+        let shutdown = Shutdown::new(Duration::from_millis(20));
+        let guard = shutdown.track();
+
+        let worker = shutdown.clone();
+        let checkpoint = tokio::spawn(async move {
+            worker.expired().await;
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            drop(guard);
+        });
+        assert!(!shutdown.drain().await);
+        assert_eq!(shutdown.in_flight(), 0);
+        checkpoint.await.unwrap();
+    }
+}
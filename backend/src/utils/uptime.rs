@@ -0,0 +1,39 @@
+/*
+ * Process start time, recorded once at boot and shared through AppState.
+ * I'm keeping the wall-clock time for display next to a monotonic instant for the arithmetic, so clock adjustments can't make uptime jump.
+ */
+
+use chrono::{DateTime, Utc};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessStart {
+    instant: Instant,
+    at: DateTime<Utc>,
+}
+
+impl ProcessStart {
+    /// Call as early in main as possible; everything reporting uptime measures from here
+    pub fn now() -> Self {
+        Self {
+            instant: Instant::now(),
+            at: Utc::now(),
+        }
+    }
+
+    pub fn instant(&self) -> Instant {
+        self.instant
+    }
+
+    pub fn started_at(&self) -> DateTime<Utc> {
+        self.at
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.instant.elapsed()
+    }
+
+    pub fn uptime_seconds(&self) -> u64 {
+        self.uptime().as_secs()
+    }
+}